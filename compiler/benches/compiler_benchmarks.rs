@@ -0,0 +1,31 @@
+//! Benchmarks of each stage of the compiler on an example program, up to the optimized IR
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use kodeon_compiler::ir::IRGenerator;
+use kodeon_compiler::lexer::Lexer;
+use kodeon_compiler::optimizer::Optimizer;
+use kodeon_compiler::parser::Parser;
+use kodeon_compiler::semantic_analyzer::SemanticAnalyzer;
+
+const SOURCE: &str = include_str!("../../examples/comprehensive.kodeon");
+
+fn stages(c: &mut Criterion) {
+    c.bench_function("lex", |b| b.iter(|| Lexer::new(black_box(SOURCE)).tokenize().unwrap()));
+    c.bench_function("parse", |b| b.iter(|| Parser::new(black_box(SOURCE)).parse_program().into_result().unwrap()));
+
+    // Each later stage starts from the output of the one before it
+    let ast = Parser::new(SOURCE).parse_program().into_result().unwrap();
+    c.bench_function("analyze", |b| b.iter(|| SemanticAnalyzer::new().analyze(black_box(&ast)).unwrap()));
+    let program = SemanticAnalyzer::new().analyze(&ast).unwrap();
+    c.bench_function("generate_ir", |b| b.iter(|| IRGenerator::new().generate_ir(black_box(&program)).unwrap()));
+    c.bench_function("optimize", |b| {
+        b.iter_batched(
+            || IRGenerator::new().generate_ir(&program).unwrap(),
+            |mut module| Optimizer::new().optimize(&mut module).unwrap(),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+criterion_group!(benches, stages);
+criterion_main!(benches);
@@ -0,0 +1,244 @@
+//! Errors thrown by KODEON programs, unwound by the system unwinder (`_Unwind_*`, the Itanium ABI one
+//! libgcc and libunwind both provide)
+//!
+//! `lempar` calls `kodeon_throw`, which raises a record holding the thrown value and the virtual table
+//! of its class. `kodeon_personality` is the personality routine of every compiled function with a
+//! landing pad: it stops the unwinding at the landing pad of the call that threw. There the clauses of
+//! the `coba` statement test the class with `kodeon_exception_vtable`, and either take the value with
+//! `kodeon_catch` or hand the record back to the unwinder with `kodeon_resume`.
+
+use crate::lsda;
+use std::cell::Cell;
+use std::os::raw::c_int;
+use std::{process, ptr};
+
+/// Exception class of KODEON errors: vendor "KODEON", then a language of zeros
+pub const KODEON_EXCEPTION_CLASS: u64 = u64::from_be_bytes(*b"KODEON\0\0");
+
+// Reason codes of the unwinder (_URC_*)
+const URC_FATAL_PHASE2_ERROR: c_int = 2;
+const URC_FATAL_PHASE1_ERROR: c_int = 3;
+const URC_END_OF_STACK: c_int = 5;
+const URC_HANDLER_FOUND: c_int = 6;
+const URC_INSTALL_CONTEXT: c_int = 7;
+const URC_CONTINUE_UNWIND: c_int = 8;
+
+// Phase the personality routine is called in (_UA_*)
+const UA_SEARCH_PHASE: c_int = 1;
+
+// Registers a landing pad receives the record and the selector in
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+const DATA_REGISTERS: (c_int, c_int) = (0, 1);
+#[cfg(target_arch = "x86")]
+const DATA_REGISTERS: (c_int, c_int) = (0, 2);
+#[cfg(target_arch = "riscv64")]
+const DATA_REGISTERS: (c_int, c_int) = (10, 11);
+
+/// Header the unwinder keeps its state in (`struct _Unwind_Exception`)
+#[repr(C, align(16))]
+pub struct UnwindException {
+    exception_class: u64,
+    exception_cleanup: Option<unsafe extern "C" fn(c_int, *mut UnwindException)>,
+    private: [usize; 2],
+}
+
+/// Frame being unwound (`struct _Unwind_Context`), only handled through the unwinder
+#[repr(C)]
+pub struct UnwindContext {
+    _private: [u8; 0],
+}
+
+extern "C" {
+    fn _Unwind_RaiseException(exception: *mut UnwindException) -> c_int;
+    fn _Unwind_Resume(exception: *mut UnwindException) -> !;
+    fn _Unwind_DeleteException(exception: *mut UnwindException);
+    fn _Unwind_GetLanguageSpecificData(context: *mut UnwindContext) -> *const u8;
+    fn _Unwind_GetRegionStart(context: *mut UnwindContext) -> usize;
+    fn _Unwind_GetIPInfo(context: *mut UnwindContext, ip_before_instruction: *mut c_int) -> usize;
+    fn _Unwind_SetGR(context: *mut UnwindContext, index: c_int, value: usize);
+    fn _Unwind_SetIP(context: *mut UnwindContext, value: usize);
+}
+
+/// Error being thrown: the unwinder's header first, so that a pointer to it is one to the record
+#[repr(C)]
+struct Exception {
+    header: UnwindException,
+    object: *mut u8,    // value thrown: an instance, or a text
+    vtable: *const u8,  // virtual table of the instance's class, null for a text
+}
+
+thread_local! {
+    /// Value and virtual table of the error caught last, so that throwing it again keeps its class
+    static CAUGHT: Cell<(*mut u8, *const u8)> = const { Cell::new((ptr::null_mut(), ptr::null())) };
+}
+
+/// Throw `object`, an instance of the class with virtual table `vtable`, or a text when it is null
+///
+/// Values of untyped `tangkap` clauses are thrown again without a virtual table; the one they were
+/// caught with is kept. Exits the program when no landing pad takes the error.
+///
+/// # Safety
+/// `vtable` must be null or the virtual table of `object`'s class.
+#[no_mangle]
+pub unsafe extern "C" fn kodeon_throw(object: *mut u8, vtable: *const u8) -> ! {
+    let vtable = match CAUGHT.with(Cell::get) {
+        (caught, caught_vtable) if vtable.is_null() && caught == object => caught_vtable,
+        _ => vtable,
+    };
+    let exception = Box::into_raw(Box::new(Exception {
+        header: UnwindException {
+            exception_class: KODEON_EXCEPTION_CLASS,
+            exception_cleanup: Some(delete_exception),
+            private: [0; 2],
+        },
+        object,
+        vtable,
+    }));
+
+    // The unwinder only returns when it cannot unwind to a landing pad
+    let reason = _Unwind_RaiseException(exception.cast());
+    delete_exception(reason, exception.cast());
+    if reason == URC_END_OF_STACK {
+        eprintln!("❌ Uncaught error: no 'coba' statement catches it");
+    } else {
+        eprintln!("❌ Failed to throw an error (unwinder reason {})", reason);
+    }
+    process::exit(1)
+}
+
+/// Free the record of an error, once caught or when the unwinder drops it
+unsafe extern "C" fn delete_exception(_reason: c_int, exception: *mut UnwindException) {
+    drop(Box::from_raw(exception.cast::<Exception>()));
+}
+
+/// Keep unwinding `exception`, which a landing pad took without catching it
+///
+/// # Safety
+/// `exception` must be the record a landing pad received.
+#[no_mangle]
+pub unsafe extern "C" fn kodeon_resume(exception: *mut UnwindException) -> ! {
+    _Unwind_Resume(exception)
+}
+
+/// Virtual table of the class of the value error `exception` threw; null for a text
+///
+/// # Safety
+/// `exception` must be the record a landing pad received.
+#[no_mangle]
+pub unsafe extern "C" fn kodeon_exception_vtable(exception: *mut UnwindException) -> *const u8 {
+    match record(exception) {
+        Some(record) => (*record).vtable,
+        None => ptr::null(),
+    }
+}
+
+/// Catch `exception`: end its unwinding, free its record and return the value it threw
+///
+/// # Safety
+/// `exception` must be the record a landing pad received, and is invalid afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn kodeon_catch(exception: *mut UnwindException) -> *mut u8 {
+    let Some(record) = record(exception) else {
+        return ptr::null_mut();
+    };
+    let (object, vtable) = ((*record).object, (*record).vtable);
+    CAUGHT.with(|caught| caught.set((object, vtable)));
+    _Unwind_DeleteException(exception);
+    object
+}
+
+/// Record of `exception`, if KODEON threw it
+unsafe fn record(exception: *mut UnwindException) -> Option<*mut Exception> {
+    if exception.is_null() || (*exception).exception_class != KODEON_EXCEPTION_CLASS {
+        return None;
+    }
+    Some(exception.cast())
+}
+
+/// Personality routine of compiled functions
+///
+/// Every landing pad the compiler emits catches all errors, so the search phase stops at the first
+/// frame with a landing pad for the call that threw, and the cleanup phase continues in it. Errors of
+/// other languages unwind through KODEON functions without stopping.
+///
+/// # Safety
+/// Only the unwinder calls it, with the frame it is unwinding.
+#[no_mangle]
+pub unsafe extern "C" fn kodeon_personality(
+    version: c_int,
+    actions: c_int,
+    exception_class: u64,
+    exception: *mut UnwindException,
+    context: *mut UnwindContext,
+) -> c_int {
+    let search_phase = actions & UA_SEARCH_PHASE != 0;
+    if version != 1 {
+        return URC_FATAL_PHASE1_ERROR;
+    }
+    if exception_class != KODEON_EXCEPTION_CLASS {
+        return URC_CONTINUE_UNWIND;
+    }
+    let lsda = _Unwind_GetLanguageSpecificData(context);
+    if lsda.is_null() {
+        return URC_CONTINUE_UNWIND;
+    }
+    let mut ip_before_instruction = 0;
+    let mut ip = _Unwind_GetIPInfo(context, &mut ip_before_instruction);
+    // The address is that of the instruction after the call, which may be the next call site
+    if ip_before_instruction == 0 {
+        ip -= 1;
+    }
+
+    match lsda::find_landing_pad(lsda, _Unwind_GetRegionStart(context), ip) {
+        Ok(Some(_)) if search_phase => URC_HANDLER_FOUND,
+        Ok(Some(landing_pad)) => {
+            _Unwind_SetGR(context, DATA_REGISTERS.0, exception as usize);
+            _Unwind_SetGR(context, DATA_REGISTERS.1, 0);
+            _Unwind_SetIP(context, landing_pad);
+            URC_INSTALL_CONTEXT
+        }
+        Ok(None) => URC_CONTINUE_UNWIND,
+        Err(_) if search_phase => URC_FATAL_PHASE1_ERROR,
+        Err(_) => URC_FATAL_PHASE2_ERROR,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raise(object: *mut u8, vtable: *const u8) -> *mut UnwindException {
+        Box::into_raw(Box::new(Exception {
+            header: UnwindException {
+                exception_class: KODEON_EXCEPTION_CLASS,
+                exception_cleanup: Some(delete_exception),
+                private: [0; 2],
+            },
+            object,
+            vtable,
+        }))
+        .cast()
+    }
+
+    #[test]
+    fn test_catching_takes_the_thrown_value() {
+        let mut instance = 7u8;
+        let vtable = [0u8; 8];
+        let exception = raise(&mut instance, vtable.as_ptr());
+        unsafe {
+            assert_eq!(kodeon_exception_vtable(exception), vtable.as_ptr());
+            assert_eq!(kodeon_catch(exception), &mut instance as *mut u8);
+        }
+        // The class the value was caught with is remembered for throwing it again
+        assert_eq!(CAUGHT.with(Cell::get), (&mut instance as *mut u8, vtable.as_ptr()));
+    }
+
+    #[test]
+    fn test_errors_of_other_languages_are_not_caught() {
+        let mut foreign = UnwindException { exception_class: u64::from_be_bytes(*b"GNUCC++\0"), exception_cleanup: None, private: [0; 2] };
+        unsafe {
+            assert!(kodeon_exception_vtable(&mut foreign).is_null());
+            assert!(kodeon_catch(&mut foreign).is_null());
+        }
+    }
+}
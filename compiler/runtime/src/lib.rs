@@ -0,0 +1,9 @@
+//! KODEON Runtime Library
+//! Support code the LLVM backend calls into, linked into every compiled program
+
+pub mod exception;
+pub mod list;
+mod lsda;
+
+pub use exception::*;
+pub use list::*;
@@ -0,0 +1,182 @@
+//! Lists of compiled programs
+//!
+//! A list holds elements of one size back to back, and only knows them as bytes. The backend creates
+//! one with `kodeon_list_new`, giving the size of its elements, copies elements in with
+//! `kodeon_list_push` and reads or writes them through the slot `kodeon_list_at` returns.
+
+use std::{process, ptr};
+
+/// Growable list of elements of `element_size` bytes
+pub struct List {
+    element_size: usize,
+    length: usize,
+    words: Vec<u64>, // elements back to back, in words so that slots are aligned for the values loaded from them
+}
+
+impl List {
+    fn new(element_size: usize) -> Self {
+        List { element_size, length: 0, words: Vec::new() }
+    }
+
+    /// Address of the slot of element `index`
+    fn slot(&mut self, index: usize) -> *mut u8 {
+        // The offset is inside the words, or one past them for the slot of an empty element
+        unsafe { self.words.as_mut_ptr().cast::<u8>().add(index * self.element_size) }
+    }
+
+    /// Make room for `length` elements
+    fn reserve(&mut self, length: usize) {
+        let words = (length * self.element_size).div_ceil(8);
+        if self.words.len() < words {
+            self.words.resize(words.max(self.words.len() * 2), 0);
+        }
+    }
+
+    /// Exit the program unless `index` is the index of an element
+    fn check(&self, index: i64) -> usize {
+        match usize::try_from(index) {
+            Ok(index) if index < self.length => index,
+            _ => {
+                eprintln!("❌ Index {} is out of range for a list of {} elements", index, self.length);
+                process::exit(1)
+            }
+        }
+    }
+}
+
+/// New empty list of elements of `element_size` bytes
+#[no_mangle]
+pub extern "C" fn kodeon_list_new(element_size: i64) -> *mut List {
+    Box::into_raw(Box::new(List::new(usize::try_from(element_size).unwrap_or(0))))
+}
+
+/// Number of elements of `list`
+///
+/// # Safety
+/// `list` must be a list `kodeon_list_new` or `kodeon_list_slice` returned.
+#[no_mangle]
+pub unsafe extern "C" fn kodeon_list_length(list: *const List) -> i64 {
+    (*list).length as i64
+}
+
+/// Slot of element `index` of `list`, valid until an element is added to the list; exits the program
+/// when there is no such element
+///
+/// # Safety
+/// `list` must be a list `kodeon_list_new` or `kodeon_list_slice` returned.
+#[no_mangle]
+pub unsafe extern "C" fn kodeon_list_at(list: *mut List, index: i64) -> *mut u8 {
+    let list = &mut *list;
+    let index = list.check(index);
+    list.slot(index)
+}
+
+/// Append a copy of the element at `element` to `list`
+///
+/// # Safety
+/// `list` must be a list `kodeon_list_new` or `kodeon_list_slice` returned, and `element` must point to
+/// as many bytes as its elements have.
+#[no_mangle]
+pub unsafe extern "C" fn kodeon_list_push(list: *mut List, element: *const u8) {
+    let list = &mut *list;
+    list.reserve(list.length + 1);
+    let slot = list.slot(list.length);
+    ptr::copy_nonoverlapping(element, slot, list.element_size);
+    list.length += 1;
+}
+
+/// New list with the elements of `list` from `start` up to, but not including, `end`; both are clamped
+/// to the length of the list, so a range outside it gives an empty list
+///
+/// # Safety
+/// `list` must be a list `kodeon_list_new` or `kodeon_list_slice` returned.
+#[no_mangle]
+pub unsafe extern "C" fn kodeon_list_slice(list: *mut List, start: i64, end: i64) -> *mut List {
+    let list = &mut *list;
+    let clamp = |bound: i64| bound.clamp(0, list.length as i64) as usize;
+    let (start, end) = (clamp(start), clamp(end));
+    let mut slice = List::new(list.element_size);
+    if start < end {
+        slice.reserve(end - start);
+        ptr::copy_nonoverlapping(list.slot(start), slice.slot(0), (end - start) * list.element_size);
+        slice.length = end - start;
+    }
+    Box::into_raw(Box::new(slice))
+}
+
+/// Exchange elements `first` and `second` of `list`; exits the program when either does not exist
+///
+/// # Safety
+/// `list` must be a list `kodeon_list_new` or `kodeon_list_slice` returned.
+#[no_mangle]
+pub unsafe extern "C" fn kodeon_list_swap(list: *mut List, first: i64, second: i64) {
+    let list = &mut *list;
+    let (first, second) = (list.check(first), list.check(second));
+    if first != second {
+        ptr::swap_nonoverlapping(list.slot(first), list.slot(second), list.element_size);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// List of the integers in `values`
+    fn integers(values: &[i64]) -> *mut List {
+        let list = kodeon_list_new(8);
+        for value in values {
+            unsafe { kodeon_list_push(list, (value as *const i64).cast()) };
+        }
+        list
+    }
+
+    fn elements(list: *mut List) -> Vec<i64> {
+        unsafe { (0..kodeon_list_length(list)).map(|index| *kodeon_list_at(list, index).cast::<i64>()).collect() }
+    }
+
+    #[test]
+    fn test_pushed_elements_are_read_back_in_order() {
+        let values: Vec<i64> = (0..100).map(|value| value * value).collect();
+        let list = integers(&values);
+        assert_eq!(elements(list), values);
+
+        // Slots are written in place
+        unsafe { *kodeon_list_at(list, 3).cast::<i64>() = -1 };
+        assert_eq!(elements(list)[..5], [0, 1, 4, -1, 16]);
+
+        // Elements of other sizes stay apart
+        let bytes = kodeon_list_new(3);
+        for element in [[1u8, 2, 3], [4, 5, 6]] {
+            unsafe { kodeon_list_push(bytes, element.as_ptr()) };
+        }
+        unsafe {
+            assert_eq!(kodeon_list_length(bytes), 2);
+            assert_eq!(*kodeon_list_at(bytes, 1).add(2), 6);
+        }
+    }
+
+    #[test]
+    fn test_slices_clamp_their_bounds() {
+        let list = integers(&[1, 2, 3, 4, 5]);
+        let slice = |start, end| elements(unsafe { kodeon_list_slice(list, start, end) });
+        assert_eq!(slice(1, 3), [2, 3]);
+        assert_eq!(slice(-2, 2), [1, 2]);
+        assert_eq!(slice(3, 10), [4, 5]);
+        assert!(slice(4, 2).is_empty());
+        assert!(slice(7, 9).is_empty());
+        // A slice is a copy
+        let copy = unsafe { kodeon_list_slice(list, 0, 5) };
+        unsafe { kodeon_list_push(copy, (&6i64 as *const i64).cast()) };
+        assert_eq!(elements(list).len(), 5);
+    }
+
+    #[test]
+    fn test_swap_exchanges_elements() {
+        let list = integers(&[1, 2, 3]);
+        unsafe {
+            kodeon_list_swap(list, 0, 2);
+            kodeon_list_swap(list, 1, 1);
+        }
+        assert_eq!(elements(list), [3, 2, 1]);
+    }
+}
@@ -0,0 +1,141 @@
+//! Reading the language-specific data area (LSDA) LLVM emits for each function with landing pads
+//!
+//! The area starts with a header, followed by the call-site table: each range of instructions that
+//! may throw, and the landing pad to continue in when it does.
+
+// Pointer encodings (DW_EH_PE_*); call sites are offsets, so only the formats matter
+const DW_EH_PE_OMIT: u8 = 0xff;
+const DW_EH_PE_ABSPTR: u8 = 0x00;
+const DW_EH_PE_ULEB128: u8 = 0x01;
+const DW_EH_PE_UDATA2: u8 = 0x02;
+const DW_EH_PE_UDATA4: u8 = 0x03;
+const DW_EH_PE_UDATA8: u8 = 0x04;
+
+/// An LSDA using a pointer encoding the runtime cannot read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnsupportedEncoding(pub u8);
+
+/// Landing pad of the call site `ip` is in, in the function starting at `function_start`
+///
+/// Returns `None` when the call site has no landing pad, or no call site covers `ip`.
+///
+/// # Safety
+/// `lsda` must point to a well-formed LSDA.
+pub unsafe fn find_landing_pad(lsda: *const u8, function_start: usize, ip: usize) -> Result<Option<usize>, UnsupportedEncoding> {
+    let mut reader = Reader { pointer: lsda };
+
+    let landing_pad_base = match reader.u8() {
+        DW_EH_PE_OMIT => function_start,
+        encoding => reader.encoded(encoding)? as usize,
+    };
+    // Type table of the catch clauses; every KODEON landing pad catches all errors, so it is not needed
+    if reader.u8() != DW_EH_PE_OMIT {
+        reader.uleb128();
+    }
+    let call_site_encoding = reader.u8();
+    let table_length = reader.uleb128() as usize;
+    let table_end = reader.pointer.add(table_length);
+
+    while reader.pointer < table_end {
+        let start = function_start + reader.encoded(call_site_encoding)? as usize;
+        let length = reader.encoded(call_site_encoding)? as usize;
+        let landing_pad = reader.encoded(call_site_encoding)? as usize;
+        reader.uleb128(); // action
+        // Call sites are sorted by address
+        if ip < start {
+            break;
+        }
+        if ip < start + length {
+            return Ok((landing_pad != 0).then_some(landing_pad_base + landing_pad));
+        }
+    }
+    Ok(None)
+}
+
+/// Cursor over an LSDA
+struct Reader {
+    pointer: *const u8,
+}
+
+impl Reader {
+    unsafe fn u8(&mut self) -> u8 {
+        let value = *self.pointer;
+        self.pointer = self.pointer.add(1);
+        value
+    }
+
+    unsafe fn uleb128(&mut self) -> u64 {
+        let mut value = 0;
+        let mut shift = 0;
+        loop {
+            let byte = self.u8();
+            value |= u64::from(byte & 0x7f) << shift;
+            shift += 7;
+            if byte & 0x80 == 0 {
+                return value;
+            }
+        }
+    }
+
+    unsafe fn fixed<T: Copy + Into<u64>>(&mut self) -> u64 {
+        let value = (self.pointer as *const T).read_unaligned();
+        self.pointer = self.pointer.add(std::mem::size_of::<T>());
+        value.into()
+    }
+
+    /// Value in pointer encoding `encoding`
+    unsafe fn encoded(&mut self, encoding: u8) -> Result<u64, UnsupportedEncoding> {
+        match encoding {
+            DW_EH_PE_ABSPTR if cfg!(target_pointer_width = "64") => Ok(self.fixed::<u64>()),
+            DW_EH_PE_ABSPTR => Ok(self.fixed::<u32>()),
+            DW_EH_PE_ULEB128 => Ok(self.uleb128()),
+            DW_EH_PE_UDATA2 => Ok(self.fixed::<u16>()),
+            DW_EH_PE_UDATA4 => Ok(self.fixed::<u32>()),
+            DW_EH_PE_UDATA8 => Ok(self.fixed::<u64>()),
+            _ => Err(UnsupportedEncoding(encoding)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FUNCTION: usize = 0x1000;
+
+    fn landing_pad(lsda: &[u8], ip: usize) -> Result<Option<usize>, UnsupportedEncoding> {
+        unsafe { find_landing_pad(lsda.as_ptr(), FUNCTION, ip) }
+    }
+
+    #[test]
+    fn test_call_sites_in_uleb128() {
+        let lsda = [
+            DW_EH_PE_OMIT, DW_EH_PE_OMIT, DW_EH_PE_ULEB128, 12,
+            0x00, 0x10, 0x40, 0x01, // a call with a landing pad
+            0x20, 0x08, 0x00, 0x00, // a call without one
+            0x30, 0x05, 0xac, 0x02, // a landing pad 300 bytes in
+        ];
+        assert_eq!(landing_pad(&lsda, FUNCTION + 0x04), Ok(Some(FUNCTION + 0x40)));
+        assert_eq!(landing_pad(&lsda, FUNCTION + 0x18), Ok(None));
+        assert_eq!(landing_pad(&lsda, FUNCTION + 0x24), Ok(None));
+        assert_eq!(landing_pad(&lsda, FUNCTION + 0x34), Ok(Some(FUNCTION + 300)));
+        assert_eq!(landing_pad(&lsda, FUNCTION + 0x100), Ok(None));
+    }
+
+    #[test]
+    fn test_call_sites_in_udata4_after_a_type_table() {
+        let mut lsda = vec![DW_EH_PE_OMIT, 0x9b, 0x20, DW_EH_PE_UDATA4, 13];
+        for value in [0x08u32, 0x10, 0x60] {
+            lsda.extend_from_slice(&value.to_le_bytes());
+        }
+        lsda.push(0x01);
+        assert_eq!(landing_pad(&lsda, FUNCTION + 0x0c), Ok(Some(FUNCTION + 0x60)));
+        assert_eq!(landing_pad(&lsda, FUNCTION + 0x04), Ok(None));
+    }
+
+    #[test]
+    fn test_unsupported_encodings_are_reported() {
+        let lsda = [DW_EH_PE_OMIT, DW_EH_PE_OMIT, 0x0b, 4, 0, 0, 0, 0];
+        assert_eq!(landing_pad(&lsda, FUNCTION), Err(UnsupportedEncoding(0x0b)));
+    }
+}
@@ -153,7 +153,7 @@ print(message)
     pub const LOOP_EXAMPLE: &str = r#"// Loop example
 counter = 0
 while counter < 5:
-    print("Counter: " + counter)
+    print("Counter: " + str(counter))
     counter = counter + 1
 "#;
 
@@ -167,17 +167,17 @@ else:
 
     pub const FOR_LOOP_EXAMPLE: &str = r#"// For loop example
 for i in range(5):
-    print("Number: " + i)
+    print("Number: " + str(i))
 "#;
 
     pub const STANDARD_LIBRARY_EXAMPLE: &str = r#"// Standard library example
 message = "Hello, KODEON!"
 length = len(message)
 print("Message: " + message)
-print("Length: " + length)
+print("Length: " + str(length))
 
 // Range example
 numbers = range(1, 6)
-print("Numbers: " + numbers)
+print("Numbers: " + str(numbers))
 "#;
 }
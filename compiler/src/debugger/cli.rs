@@ -9,6 +9,12 @@ pub struct DebuggerCLI {
     debugger: Debugger,
 }
 
+impl Default for DebuggerCLI {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl DebuggerCLI {
     /// Create a new debugger CLI
     pub fn new() -> Self {
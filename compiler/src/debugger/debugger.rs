@@ -1,6 +1,6 @@
 //! Debugger implementation for the KODEON programming language
 
-use crate::ir::{IRModule, DebugInfo};
+use crate::ir::IRModule;
 use std::collections::HashMap;
 use std::io::{self, Write};
 
@@ -39,6 +39,12 @@ pub struct DebugValue {
     pub scope: String,
 }
 
+impl Default for Debugger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Debugger {
     /// Create a new debugger
     pub fn new() -> Self {
@@ -62,7 +68,7 @@ impl Debugger {
 
         self.breakpoints
             .entry(file_name.to_string())
-            .or_insert_with(Vec::new)
+            .or_default()
             .push(breakpoint);
 
         println!("Breakpoint added at {} line {}", file_name, line);
@@ -86,7 +92,7 @@ impl Debugger {
 
         self.breakpoints
             .entry(file_name.to_string())
-            .or_insert_with(Vec::new)
+            .or_default()
             .push(breakpoint);
 
         println!("Conditional breakpoint added at {} line {}", file_name, line);
@@ -168,6 +174,11 @@ impl Debugger {
         println!("Debugging function: {}", function.name);
         self.current_function = function.name.clone();
 
+        // Parameters are in scope for the whole function, with the types the analyzer inferred
+        for parameter in &function.parameters {
+            self.declare_variable(&parameter.name, &parameter.param_type, &parameter.debug_info);
+        }
+
         // Process basic blocks in the function
         for block in &function.blocks {
             self.debug_block(block)?;
@@ -187,8 +198,8 @@ impl Debugger {
             }
         }
 
-        // Process instructions in the block
-        for instruction in &block.instructions {
+        // Process instructions in the block, the one it invokes included
+        for instruction in block.instructions() {
             self.debug_instruction(instruction)?;
         }
 
@@ -206,6 +217,7 @@ impl Debugger {
             crate::ir::Instruction::Call { debug_info, .. } => debug_info,
             crate::ir::Instruction::Alloca { debug_info, .. } => debug_info,
             crate::ir::Instruction::Return { debug_info, .. } => debug_info,
+            crate::ir::Instruction::Throw { debug_info, .. } => debug_info,
             // Elegant instructions
             crate::ir::Instruction::Chain { debug_info, .. } => debug_info,
             crate::ir::Instruction::Pipeline { debug_info, .. } => debug_info,
@@ -216,6 +228,27 @@ impl Debugger {
             crate::ir::Instruction::Range { debug_info, .. } => debug_info,
             crate::ir::Instruction::ObjectLiteral { debug_info, .. } => debug_info,
             crate::ir::Instruction::MemberAccess { debug_info, .. } => debug_info,
+            // Instances of classes
+            crate::ir::Instruction::NewObject { debug_info, .. } => debug_info,
+            crate::ir::Instruction::GetField { debug_info, .. } => debug_info,
+            crate::ir::Instruction::SetField { debug_info, .. } => debug_info,
+            crate::ir::Instruction::VirtualCall { debug_info, .. } => debug_info,
+            crate::ir::Instruction::InterfaceCall { debug_info, .. } => debug_info,
+            // Values of enums, and tuples
+            crate::ir::Instruction::NewVariant { debug_info, .. } => debug_info,
+            crate::ir::Instruction::MakeTuple { debug_info, .. } => debug_info,
+            // Closures
+            crate::ir::Instruction::AllocaShared { debug_info, .. } => debug_info,
+            crate::ir::Instruction::MakeClosure { debug_info, .. } => debug_info,
+            crate::ir::Instruction::BindCapture { debug_info, .. } => debug_info,
+            crate::ir::Instruction::ClosureCall { debug_info, .. } => debug_info,
+            // Exceptions
+            crate::ir::Instruction::LandingPad { debug_info, .. } => debug_info,
+            crate::ir::Instruction::ExceptionIs { debug_info, .. } => debug_info,
+            crate::ir::Instruction::Catch { debug_info, .. } => debug_info,
+            // Optionals
+            crate::ir::Instruction::NullCheck { debug_info, .. } => debug_info,
+            crate::ir::Instruction::Unwrap { debug_info, .. } => debug_info,
             crate::ir::Instruction::ForEachLoop { debug_info, .. } => debug_info,
             crate::ir::Instruction::PatternMatch { debug_info, .. } => debug_info,
             crate::ir::Instruction::Await { debug_info, .. } => debug_info,
@@ -240,6 +273,10 @@ impl Debugger {
             crate::ir::Instruction::AtomicFetchSub { debug_info, .. } => debug_info,
         };
 
+        if let crate::ir::Instruction::Alloca { result, alloca_type, .. } = instruction {
+            self.declare_variable(result, alloca_type, debug_info);
+        }
+
         // Check for breakpoints at the instruction
         if let Some(ref debug_info) = debug_info {
             self.current_line = debug_info.line;
@@ -251,6 +288,16 @@ impl Debugger {
         Ok(())
     }
 
+    /// Add a variable the program declares, typed by its source type when the IR records one
+    fn declare_variable(&mut self, name: &str, ir_type: &crate::ir::Type, debug_info: &Option<crate::ir::DebugInfo>) {
+        let type_name = debug_info
+            .as_ref()
+            .and_then(|debug_info| debug_info.type_name.clone())
+            .unwrap_or_else(|| format!("{:?}", ir_type));
+        let scope = self.current_function.clone();
+        self.add_variable(name.to_string(), "<uninitialized>".to_string(), type_name, scope);
+    }
+
     /// Handle a breakpoint
     fn handle_breakpoint(&mut self, file_name: &str, line: usize) -> Result<(), String> {
         println!("Breakpoint hit at {} line {}", file_name, line);
@@ -321,7 +368,7 @@ impl Debugger {
     }
 
     /// Print all variables in the current scope
-    fn print_variables(&self) {
+    pub fn print_variables(&self) {
         if self.variables.is_empty() {
             println!("No variables in current scope");
         } else {
@@ -342,7 +389,7 @@ impl Debugger {
     }
 
     /// List source code around current line
-    fn list_source(&self) {
+    pub fn list_source(&self) {
         println!("Source code around line {} in function {}",
                  self.current_line, self.current_function);
         // In a real implementation, this would show actual source code
@@ -350,7 +397,7 @@ impl Debugger {
     }
 
     /// Show call stack/backtrace
-    fn show_backtrace(&self) {
+    pub fn show_backtrace(&self) {
         println!("Call stack:");
         println!("  #0  {} at line {}", self.current_function, self.current_line);
         // In a real implementation, this would show the full call stack
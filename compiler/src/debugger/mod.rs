@@ -1,6 +1,7 @@
 //! Debugger module for the KODEON programming language
 
 pub mod cli;
+#[allow(clippy::module_inception)]
 pub mod debugger;
 
 pub use debugger::Debugger;
@@ -0,0 +1,521 @@
+//! Compiler diagnostics with labeled source spans
+//!
+//! A `Diagnostic` describes one problem: its severity, an optional error code, the spans
+//! it is about and any notes or help. The `DiagnosticRenderer` prints it for a terminal,
+//! with the offending source lines underlined, or as one line of JSON for editors and CI.
+
+use crate::lexer::{FileId, Position, Span};
+use serde_json::json;
+
+/// How serious a diagnostic is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+    Help,
+}
+
+impl Severity {
+    /// Name used in rendered output
+    pub fn name(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Note => "note",
+            Severity::Help => "help",
+        }
+    }
+
+    /// ANSI color of the severity
+    fn color(&self) -> &'static str {
+        match self {
+            Severity::Error => "\x1b[1;31m",
+            Severity::Warning => "\x1b[1;33m",
+            Severity::Note => "\x1b[1;32m",
+            Severity::Help => "\x1b[1;36m",
+        }
+    }
+}
+
+/// Whether a label marks the cause of a diagnostic or related code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LabelStyle {
+    Primary,   // underlined with '^'
+    Secondary, // underlined with '-'
+}
+
+/// A span of source with a message
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    pub style: LabelStyle,
+    pub span: Span,
+    pub message: String,
+}
+
+/// A problem found in the source
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: Option<String>, // stable identifier such as `E0001`
+    pub message: String,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+    pub help: Option<String>,
+}
+
+impl Diagnostic {
+    /// Create a diagnostic without labels
+    pub fn new(severity: Severity, message: impl Into<String>) -> Self {
+        Diagnostic {
+            severity,
+            code: None,
+            message: message.into(),
+            labels: Vec::new(),
+            notes: Vec::new(),
+            help: None,
+        }
+    }
+
+    /// Create an error
+    pub fn error(message: impl Into<String>) -> Self {
+        Self::new(Severity::Error, message)
+    }
+
+    /// Create a warning
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self::new(Severity::Warning, message)
+    }
+
+    /// Set how serious the diagnostic is
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    /// Set the error code
+    pub fn with_code(mut self, code: impl Into<String>) -> Self {
+        self.code = Some(code.into());
+        self
+    }
+
+    /// Add a label at the cause of the problem
+    pub fn with_primary(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.labels.push(Label { style: LabelStyle::Primary, span, message: message.into() });
+        self
+    }
+
+    /// Add a label at related code
+    pub fn with_secondary(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.labels.push(Label { style: LabelStyle::Secondary, span, message: message.into() });
+        self
+    }
+
+    /// Add a note; empty notes are ignored
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        let note = note.into();
+        if !note.is_empty() {
+            self.notes.push(note);
+        }
+        self
+    }
+
+    /// Set the suggested fix; an empty suggestion is ignored
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        let help = help.into();
+        if !help.is_empty() {
+            self.help = Some(help);
+        }
+        self
+    }
+
+    /// The first primary label, where the diagnostic is reported
+    pub fn primary_label(&self) -> Option<&Label> {
+        self.labels.iter().find(|label| label.style == LabelStyle::Primary)
+    }
+}
+
+/// A source file that diagnostics point into
+#[derive(Debug, Clone)]
+pub struct SourceFile {
+    id: FileId,
+    name: String,
+    source: String,
+    line_starts: Vec<usize>, // byte offset of the start of each line
+}
+
+impl SourceFile {
+    fn new(id: FileId, name: String, source: String) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+        SourceFile { id, name, source, line_starts }
+    }
+
+    pub fn id(&self) -> FileId {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Zero-based index of the line containing a byte offset
+    fn line_index(&self, offset: usize) -> usize {
+        match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(next) => next - 1,
+        }
+    }
+
+    /// Text of a zero-based line, without its line break
+    fn line_text(&self, line: usize) -> &str {
+        let start = self.line_starts[line];
+        let end = self.line_starts.get(line + 1).copied().unwrap_or(self.source.len());
+        self.source[start..end].trim_end_matches(['\n', '\r'])
+    }
+
+    /// One-based line and column (in characters) of a byte offset
+    pub fn location(&self, offset: usize) -> (usize, usize) {
+        let offset = offset.min(self.source.len());
+        let line = self.line_index(offset);
+        let column = self.source[self.line_starts[line]..offset].chars().count() + 1;
+        (line + 1, column)
+    }
+
+    /// Byte offset of a one-based line and column
+    fn offset(&self, line: usize, column: usize) -> usize {
+        let Some(&start) = self.line_starts.get(line.saturating_sub(1)) else {
+            return self.source.len();
+        };
+        let text = self.line_text(line.saturating_sub(1));
+        let in_line = text.char_indices().nth(column.saturating_sub(1)).map_or(text.len(), |(index, _)| index);
+        start + in_line
+    }
+
+    /// Span of the word (or single character) at a position, for errors that only know where they start
+    pub fn span_at(&self, position: &Position) -> Span {
+        let start = self.offset(position.line, position.column);
+        let rest = &self.source[start..];
+        let word = rest.char_indices()
+            .find(|(_, ch)| !(ch.is_alphanumeric() || *ch == '_'))
+            .map_or(rest.len(), |(index, _)| index);
+        let length = match word {
+            0 => rest.chars().next().filter(|ch| *ch != '\n' && *ch != '\r').map_or(0, char::len_utf8),
+            word => word,
+        };
+        Span::new(self.id, start, start + length)
+    }
+}
+
+/// The source files of a compilation
+#[derive(Debug, Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        SourceMap { files: Vec::new() }
+    }
+
+    /// Add a file and return its id
+    pub fn add(&mut self, name: impl Into<String>, source: impl Into<String>) -> FileId {
+        let id = FileId(self.files.len() as u32);
+        self.files.push(SourceFile::new(id, name.into(), source.into()));
+        id
+    }
+
+    pub fn get(&self, file: FileId) -> Option<&SourceFile> {
+        self.files.get(file.0 as usize)
+    }
+}
+
+/// How diagnostics are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorFormat {
+    Human, // source snippets for a terminal
+    Json,  // one JSON object per line
+}
+
+impl ErrorFormat {
+    /// Parse the value of `--error-format`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "human" => Some(ErrorFormat::Human),
+            "json" => Some(ErrorFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+const BOLD: &str = "\x1b[1m";
+const GUTTER: &str = "\x1b[1;34m";
+const RESET: &str = "\x1b[0m";
+
+/// Writes diagnostics against a source map
+pub struct DiagnosticRenderer<'a> {
+    sources: &'a SourceMap,
+    color: bool,
+}
+
+impl<'a> DiagnosticRenderer<'a> {
+    pub fn new(sources: &'a SourceMap) -> Self {
+        DiagnosticRenderer { sources, color: false }
+    }
+
+    /// Color the output with ANSI escapes
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Render in the given format
+    pub fn render_as(&self, diagnostic: &Diagnostic, format: ErrorFormat) -> String {
+        match format {
+            ErrorFormat::Human => self.render(diagnostic),
+            ErrorFormat::Json => self.render_json(diagnostic),
+        }
+    }
+
+    fn paint(&self, style: &str, text: &str) -> String {
+        if self.color {
+            format!("{}{}{}", style, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// Render for a terminal:
+    ///
+    /// ```text
+    /// error[E0001]: Variable 'jumlah' is not declared
+    ///  --> main.kodeon:3:17
+    ///   |
+    /// 3 |         hasil = jumlah + 5
+    ///   |                 ^^^^^^ not declared in this scope
+    ///   |
+    ///   = help: Declare it before this line
+    /// ```
+    pub fn render(&self, diagnostic: &Diagnostic) -> String {
+        let severity = diagnostic.severity;
+        let mut out = String::new();
+
+        let header = match &diagnostic.code {
+            Some(code) => format!("{}[{}]", severity.name(), code),
+            None => severity.name().to_string(),
+        };
+        out.push_str(&self.paint(severity.color(), &header));
+        out.push_str(&self.paint(BOLD, &format!(": {}", diagnostic.message)));
+        out.push('\n');
+
+        // Labels grouped by file, in source order, the file of the primary label first
+        let mut labels: Vec<&Label> = diagnostic.labels.iter()
+            .filter(|label| self.sources.get(label.span.file).is_some())
+            .collect();
+        let main_file = diagnostic.primary_label().map(|label| label.span.file);
+        labels.sort_by_key(|label| (Some(label.span.file) != main_file, label.span.file.0, label.span.start));
+
+        let width = labels.iter()
+            .map(|label| {
+                let file = self.sources.get(label.span.file).unwrap();
+                file.location(label.span.start).0.to_string().len()
+            })
+            .max()
+            .unwrap_or(0);
+        let blank = " ".repeat(width);
+        let bar = self.paint(GUTTER, "|");
+
+        let mut index = 0;
+        while index < labels.len() {
+            let file = self.sources.get(labels[index].span.file).unwrap();
+            let reported = diagnostic.primary_label()
+                .filter(|label| label.span.file == file.id())
+                .unwrap_or(labels[index]);
+            let (line, column) = file.location(reported.span.start);
+            out.push_str(&format!("{}{} {}:{}:{}\n", blank, self.paint(GUTTER, "-->"), file.name(), line, column));
+            out.push_str(&format!("{} {}\n", blank, bar));
+
+            // Every label of this file, one source line at a time
+            let mut previous_line: Option<usize> = None;
+            while index < labels.len() && labels[index].span.file == file.id() {
+                let line = file.line_index(labels[index].span.start);
+                let on_line: Vec<&Label> = labels[index..].iter()
+                    .take_while(|label| label.span.file == file.id() && file.line_index(label.span.start) == line)
+                    .copied()
+                    .collect();
+                index += on_line.len();
+
+                if previous_line.is_some_and(|previous| line > previous + 1) {
+                    out.push_str(&format!("{}\n", self.paint(GUTTER, "...")));
+                }
+                previous_line = Some(line);
+
+                let text = file.line_text(line);
+                let number = format!("{:>width$}", line + 1, width = width);
+                out.push_str(&format!("{} {} {}\n", self.paint(GUTTER, &number), bar, expand_tabs(text)));
+
+                for label in on_line {
+                    let line_start = file.line_starts[line];
+                    let start = label.span.start - line_start;
+                    // Spans that run past this line are underlined to its end
+                    let end = (label.span.end.max(label.span.start) - line_start).min(text.len()).max(start);
+                    let padding = display_width(&text[..start.min(text.len())]);
+                    let length = display_width(&text[start.min(text.len())..end]).max(1);
+                    let (marker, style) = match label.style {
+                        LabelStyle::Primary => ('^', severity.color()),
+                        LabelStyle::Secondary => ('-', GUTTER),
+                    };
+                    let mut underline = marker.to_string().repeat(length);
+                    if !label.message.is_empty() {
+                        underline.push(' ');
+                        underline.push_str(&label.message);
+                    }
+                    out.push_str(&format!("{} {} {}{}\n", blank, bar, " ".repeat(padding), self.paint(style, &underline)));
+                }
+            }
+        }
+
+        if !labels.is_empty() && (!diagnostic.notes.is_empty() || diagnostic.help.is_some()) {
+            out.push_str(&format!("{} {}\n", blank, bar));
+        }
+        let extra = diagnostic.notes.iter()
+            .map(|note| ("note", note))
+            .chain(diagnostic.help.iter().map(|help| ("help", help)));
+        for (kind, text) in extra {
+            let mut lines = text.lines();
+            let first = lines.next().unwrap_or("");
+            out.push_str(&format!("{} {} {}: {}\n", blank, self.paint(GUTTER, "="), self.paint(BOLD, kind), first));
+            let indent = " ".repeat(width + kind.len() + 5);
+            for line in lines {
+                out.push_str(&format!("{}{}\n", indent, line));
+            }
+        }
+
+        out
+    }
+
+    /// Render as a single line of JSON
+    pub fn render_json(&self, diagnostic: &Diagnostic) -> String {
+        let labels: Vec<_> = diagnostic.labels.iter()
+            .map(|label| {
+                let file = self.sources.get(label.span.file);
+                let start = file.map(|file| file.location(label.span.start));
+                let end = file.map(|file| file.location(label.span.end));
+                json!({
+                    "style": match label.style {
+                        LabelStyle::Primary => "primary",
+                        LabelStyle::Secondary => "secondary",
+                    },
+                    "file": file.map(|file| file.name()),
+                    "byte_start": label.span.start,
+                    "byte_end": label.span.end,
+                    "line_start": start.map(|(line, _)| line),
+                    "column_start": start.map(|(_, column)| column),
+                    "line_end": end.map(|(line, _)| line),
+                    "column_end": end.map(|(_, column)| column),
+                    "message": label.message,
+                })
+            })
+            .collect();
+
+        json!({
+            "severity": diagnostic.severity.name(),
+            "code": diagnostic.code,
+            "message": diagnostic.message,
+            "labels": labels,
+            "notes": diagnostic.notes,
+            "help": diagnostic.help,
+            "rendered": DiagnosticRenderer::new(self.sources).render(diagnostic),
+        })
+        .to_string()
+    }
+}
+
+/// Replace tabs so that underlines line up with the printed source
+fn expand_tabs(text: &str) -> String {
+    text.replace('\t', "    ")
+}
+
+/// Width of text once printed, counting a tab as four columns
+fn display_width(text: &str) -> usize {
+    text.chars().map(|ch| if ch == '\t' { 4 } else { 1 }).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sources() -> (SourceMap, FileId) {
+        let mut sources = SourceMap::new();
+        let file = sources.add("main.kodeon", "buat π = 1\n\tbuat x = π + y\nbuat x = 2\n");
+        (sources, file)
+    }
+
+    #[test]
+    fn test_render_snippet() {
+        let (sources, file) = sources();
+        let diagnostic = Diagnostic::error("Variable 'y' is not declared")
+            .with_code("E0001")
+            .with_primary(Span::new(file, 27, 28), "not declared in this scope")
+            .with_secondary(Span::new(file, 5, 7), "declared here")
+            .with_note("Names must be declared before they are used")
+            .with_help("Declare it first:\nbuat y = 0");
+
+        let expected = concat!(
+            "error[E0001]: Variable 'y' is not declared\n",
+            " --> main.kodeon:2:15\n",
+            "  |\n",
+            "1 | buat π = 1\n",
+            "  |      - declared here\n",
+            "2 |     buat x = π + y\n",
+            "  |                  ^ not declared in this scope\n",
+            "  |\n",
+            "  = note: Names must be declared before they are used\n",
+            "  = help: Declare it first:\n",
+            "          buat y = 0\n",
+        );
+        assert_eq!(DiagnosticRenderer::new(&sources).render(&diagnostic), expected);
+    }
+
+    #[test]
+    fn test_render_color() {
+        let (sources, file) = sources();
+        let diagnostic = Diagnostic::warning("Unused variable").with_primary(Span::new(file, 0, 4), "");
+        let rendered = DiagnosticRenderer::new(&sources).with_color(true).render(&diagnostic);
+
+        assert!(rendered.starts_with("\x1b[1;33mwarning\x1b[0m"));
+        assert!(rendered.contains("\x1b[1;33m^^^^\x1b[0m"));
+    }
+
+    #[test]
+    fn test_render_json() {
+        let (sources, file) = sources();
+        let diagnostic = Diagnostic::error("Duplicate declaration of 'x'")
+            .with_primary(Span::new(file, 34, 35), "redeclared here");
+        let value: serde_json::Value = serde_json::from_str(&DiagnosticRenderer::new(&sources).render_json(&diagnostic)).unwrap();
+
+        assert_eq!(value["severity"], "error");
+        assert_eq!(value["code"], serde_json::Value::Null);
+        let label = &value["labels"][0];
+        assert_eq!(label["file"], "main.kodeon");
+        assert_eq!((label["line_start"].as_u64(), label["column_start"].as_u64()), (Some(3), Some(6)));
+        assert!(value["rendered"].as_str().unwrap().contains("^ redeclared here"));
+    }
+
+    #[test]
+    fn test_span_at_position() {
+        let (sources, file) = sources();
+        let source = sources.get(file).unwrap();
+
+        let span = source.span_at(&Position { line: 2, column: 11, offset: 0 });
+        assert_eq!(span.text(source.source()), "π");
+        let span = source.span_at(&Position { line: 2, column: 2, offset: 0 });
+        assert_eq!(span.text(source.source()), "buat");
+        assert_eq!(source.location(span.start), (2, 2));
+    }
+}
@@ -0,0 +1,638 @@
+//! API documentation generator for KODEON packages
+//!
+//! Every `.kodeon` file of a package is a module. The generator collects the definitions of
+//! each module with their `///` doc comments and source signatures, registers them in a
+//! symbol table for cross-references, and renders one Markdown or HTML page per module plus
+//! an index page.
+
+use crate::lexer::{Lexer, LosslessToken, Position, Token, TriviaKind};
+use crate::parser::{ASTNode, ParseError, Parser, Statement};
+use crate::semantic_analyzer::{
+    ClassInfo, FunctionSignature, Parameter, Symbol, SymbolTable, SymbolType, VariableInfo,
+};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Output format of the generated documentation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DocFormat {
+    Markdown,
+    Html,
+}
+
+impl DocFormat {
+    /// Format named on the command line
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "markdown" | "md" => Some(DocFormat::Markdown),
+            "html" => Some(DocFormat::Html),
+            _ => None,
+        }
+    }
+
+    /// File extension of the generated pages
+    pub fn extension(&self) -> &'static str {
+        match self {
+            DocFormat::Markdown => "md",
+            DocFormat::Html => "html",
+        }
+    }
+}
+
+/// Kind of a documented definition
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DocItemKind {
+    Class,
+    Interface,
+    Function,
+    Constant,
+    Variable,
+}
+
+/// Documented definition
+#[derive(Debug, Clone)]
+pub struct DocItem {
+    pub kind: DocItemKind,
+    pub name: String,
+    pub anchor: String,         // Anchor of the item on its module page, e.g. `Matematika.sinus`
+    pub signature: String,      // Definition header as written in the source
+    pub parameters: Vec<String>, // Parameter names of functions
+    pub doc: Option<String>,    // Text of the `///` doc comment
+    pub parent_class: Option<String>,
+    pub members: Vec<DocItem>,  // Fields and methods of a class, methods of an interface
+    pub position: Position,
+}
+
+/// Documentation of one module
+#[derive(Debug, Clone)]
+pub struct ModuleDoc {
+    pub name: String, // Module path with dots, e.g. `core.math`
+    pub items: Vec<DocItem>,
+}
+
+/// Error while generating documentation
+#[derive(Debug)]
+pub enum DocError {
+    Io(PathBuf, std::io::Error),
+    Parse(PathBuf, String, Box<ParseError>), // file, its source and the error
+}
+
+impl std::fmt::Display for DocError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DocError::Io(path, error) => write!(f, "Cannot access {}: {}", path.display(), error),
+            DocError::Parse(path, _, error) => write!(f, "Cannot parse {}:\n{}", path.display(), error),
+        }
+    }
+}
+
+impl std::error::Error for DocError {}
+
+/// Documentation generator
+pub struct DocGenerator {
+    format: DocFormat,
+    modules: Vec<ModuleDoc>,
+    symbols: SymbolTable,                  // One scope per module with its top-level definitions
+    module_scopes: HashMap<String, usize>, // Module name -> its scope in `symbols`
+}
+
+impl DocGenerator {
+    /// Create a generator for the given output format
+    pub fn new(format: DocFormat) -> Self {
+        DocGenerator {
+            format,
+            modules: Vec::new(),
+            symbols: SymbolTable::new(),
+            module_scopes: HashMap::new(),
+        }
+    }
+
+    /// Documented modules, in the order they were added
+    pub fn modules(&self) -> &[ModuleDoc] {
+        &self.modules
+    }
+
+    /// Add every `.kodeon` file under `root`; `core/math.kodeon` becomes module `core.math`
+    pub fn add_package(&mut self, root: &Path) -> Result<(), DocError> {
+        let mut files: Vec<PathBuf> = WalkDir::new(root)
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.into_path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "kodeon"))
+            .collect();
+        files.sort();
+
+        for path in files {
+            let source = fs::read_to_string(&path).map_err(|error| DocError::Io(path.clone(), error))?;
+            let module = path
+                .strip_prefix(root)
+                .unwrap_or(&path)
+                .with_extension("")
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join(".");
+            if let Err(error) = self.add_source(&module, &source) {
+                return Err(DocError::Parse(path, source, Box::new(error)));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Parse `source` and add it as module `name`
+    pub fn add_source(&mut self, name: &str, source: &str) -> Result<(), ParseError> {
+        let statements = match Parser::new(source).parse_program().into_result()? {
+            ASTNode::Program(statements) => statements,
+            _ => Vec::new(),
+        };
+        let tokens = Lexer::new(source).tokenize_lossless()?;
+
+        let items = statements
+            .iter()
+            .filter_map(|statement| Self::document(statement, None, source, &tokens))
+            .collect::<Vec<_>>();
+
+        self.symbols.current_scope = 0;
+        self.symbols.enter_scope(Position::start());
+        for item in &items {
+            self.symbols.add_symbol(item.name.clone(), Self::symbol(item));
+        }
+        self.module_scopes.insert(name.to_string(), self.symbols.current_scope);
+
+        self.modules.push(ModuleDoc { name: name.to_string(), items });
+        Ok(())
+    }
+
+    /// Documentation of a definition, or None for other statements and private definitions
+    fn document(statement: &Statement, class: Option<&str>, source: &str, tokens: &[LosslessToken]) -> Option<DocItem> {
+        let mut parameters = Vec::new();
+        let (kind, name, doc, parent_class, body) = match &statement.node {
+            ASTNode::ClassDef { name, doc, parent_class, body, access_modifier, .. } => {
+                if access_modifier.as_deref() == Some("private") {
+                    return None;
+                }
+                (DocItemKind::Class, name, doc, parent_class.clone(), Some(body))
+            }
+            ASTNode::InterfaceDef { name, doc, methods, access_modifier } => {
+                if access_modifier.as_deref() == Some("private") {
+                    return None;
+                }
+                (DocItemKind::Interface, name, doc, None, Some(methods))
+            }
+            ASTNode::FunctionDef { name, doc, access_modifier, parameters: names, .. } => {
+                if access_modifier.as_deref() == Some("private") {
+                    return None;
+                }
+                parameters = names.iter().map(|parameter| parameter.name.clone()).collect();
+                (DocItemKind::Function, name, doc, None, None)
+            }
+            ASTNode::Declaration { identifier, doc, mutable, access_modifier, .. } => {
+                if access_modifier.as_deref() == Some("private") {
+                    return None;
+                }
+                let kind = if *mutable { DocItemKind::Variable } else { DocItemKind::Constant };
+                (kind, identifier, doc, None, None)
+            }
+            _ => return None,
+        };
+
+        let anchor = match class {
+            Some(class) => format!("{}.{}", class, name),
+            None => name.clone(),
+        };
+        let members = body
+            .map(|body| {
+                body.iter()
+                    .filter_map(|member| Self::document(member, Some(name), source, tokens))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Some(DocItem {
+            kind,
+            name: name.clone(),
+            anchor,
+            signature: Self::signature(&statement.position, source, tokens),
+            doc: doc.clone(),
+            parameters,
+            parent_class,
+            members,
+            position: statement.position.clone(),
+        })
+    }
+
+    /// Header of the definition starting at `position`, without its block and comments
+    fn signature(position: &Position, source: &str, tokens: &[LosslessToken]) -> String {
+        let start = match tokens.iter().position(|token| token.position.offset == position.offset) {
+            Some(start) => start,
+            None => return String::new(),
+        };
+
+        let mut signature = String::new();
+        for (index, token) in tokens.iter().enumerate().skip(start) {
+            let next = tokens.get(index + 1).map(|next| &next.token);
+            let ends_header = match token.token {
+                Token::Newline | Token::LeftBrace | Token::Eof => true,
+                Token::Colon => matches!(next, Some(Token::Newline | Token::Eof)),
+                _ => false,
+            };
+            if ends_header {
+                break;
+            }
+
+            signature.push_str(token.span.text(source));
+            for trivia in &token.trailing_trivia {
+                if trivia.kind == TriviaKind::Whitespace {
+                    signature.push_str(trivia.span.text(source));
+                }
+            }
+        }
+
+        signature.trim_end().to_string()
+    }
+
+    /// Symbol table entry for a documented definition
+    fn symbol(item: &DocItem) -> Symbol {
+        let signature = |item: &DocItem| FunctionSignature {
+            parameters: item
+                .parameters
+                .iter()
+                .map(|name| Parameter { name: name.clone(), param_type: None, position: item.position.clone() })
+                .collect(),
+            return_type: None,
+            inferred_type: None,
+            type_parameters: Vec::new(),
+            position: item.position.clone(),
+        };
+        let variable = |item: &DocItem| VariableInfo {
+            var_type: None,
+            inferred_type: None,
+            is_constant: item.kind == DocItemKind::Constant,
+        };
+
+        let symbol_type = match item.kind {
+            DocItemKind::Class | DocItemKind::Interface => SymbolType::Class(ClassInfo {
+                fields: item
+                    .members
+                    .iter()
+                    .filter(|member| member.kind != DocItemKind::Function)
+                    .map(|member| (member.name.clone(), variable(member)))
+                    .collect(),
+                methods: item
+                    .members
+                    .iter()
+                    .filter(|member| member.kind == DocItemKind::Function)
+                    .map(|member| (member.name.clone(), signature(member)))
+                    .collect(),
+                parent: item.parent_class.clone(),
+                interfaces: Vec::new(),
+                access: HashMap::new(), // private members are not documented
+                assigned_fields: HashSet::new(),
+                is_interface: item.kind == DocItemKind::Interface,
+                type_parameters: Vec::new(),
+                position: item.position.clone(),
+            }),
+            DocItemKind::Function => SymbolType::Function(signature(item)),
+            DocItemKind::Constant | DocItemKind::Variable => SymbolType::Variable(variable(item)),
+        };
+
+        Symbol {
+            name: item.name.clone(),
+            symbol_type,
+            is_initialized: true,
+            is_used: false,
+            position: item.position.clone(),
+            id: None,
+        }
+    }
+
+    /// Link target for a name used in `module`: its own definitions first, then other modules
+    ///
+    /// `Class.member` names link to the member when the class defines it.
+    fn resolve(&mut self, name: &str, module: &str) -> Option<String> {
+        let (head, member) = match name.split_once('.') {
+            Some((class, member)) => (class, Some(member)),
+            None => (name, None),
+        };
+        let defines = |symbol: &Symbol| match (member, &symbol.symbol_type) {
+            (None, _) => true,
+            (Some(member), SymbolType::Class(class)) => {
+                class.fields.contains_key(member) || class.methods.contains_key(member)
+            }
+            (Some(_), _) => false,
+        };
+
+        self.symbols.current_scope = self.module_scopes[module];
+        if self.symbols.lookup_symbol(head).is_some_and(defines) {
+            return Some(format!("#{}", name));
+        }
+
+        let mut modules: Vec<(&String, &usize)> = self.module_scopes.iter().collect();
+        modules.sort();
+        modules
+            .into_iter()
+            .find(|(_, scope)| self.symbols.scopes[**scope].symbols.get(head).is_some_and(defines))
+            .map(|(other, _)| format!("{}.{}#{}", other, self.format.extension(), name))
+    }
+
+    /// Write one page per module and an index page to `output`, returning the written files
+    pub fn write(&mut self, output: &Path) -> Result<Vec<PathBuf>, DocError> {
+        fs::create_dir_all(output).map_err(|error| DocError::Io(output.to_path_buf(), error))?;
+
+        let mut pages = Vec::new();
+        for module in self.modules.clone() {
+            pages.push((format!("{}.{}", module.name, self.format.extension()), self.render_module(&module)));
+        }
+        pages.push((format!("index.{}", self.format.extension()), self.render_index()));
+
+        let mut written = Vec::new();
+        for (file, contents) in pages {
+            let path = output.join(file);
+            fs::write(&path, contents).map_err(|error| DocError::Io(path.clone(), error))?;
+            written.push(path);
+        }
+        Ok(written)
+    }
+
+    /// Render the index page listing every module
+    pub fn render_index(&self) -> String {
+        let extension = self.format.extension();
+        match self.format {
+            DocFormat::Markdown => {
+                let mut page = String::from("# API Reference\n\n");
+                for module in &self.modules {
+                    page.push_str(&format!("- [`{}`]({}.{})\n", module.name, module.name, extension));
+                }
+                page
+            }
+            DocFormat::Html => {
+                let mut body = String::from("<h1>API Reference</h1>\n<ul>\n");
+                for module in &self.modules {
+                    body.push_str(&format!(
+                        "<li><a href=\"{}.{}\"><code>{}</code></a></li>\n",
+                        escape_html(&module.name),
+                        extension,
+                        escape_html(&module.name)
+                    ));
+                }
+                body.push_str("</ul>\n");
+                html_page("API Reference", &body)
+            }
+        }
+    }
+
+    /// Render the page of one module
+    pub fn render_module(&mut self, module: &ModuleDoc) -> String {
+        let mut page = match self.format {
+            DocFormat::Markdown => format!("# Module `{}`\n", module.name),
+            DocFormat::Html => format!("<h1>Module <code>{}</code></h1>\n", escape_html(&module.name)),
+        };
+
+        let sections = [
+            (DocItemKind::Class, "Classes"),
+            (DocItemKind::Interface, "Interfaces"),
+            (DocItemKind::Function, "Functions"),
+            (DocItemKind::Constant, "Constants"),
+            (DocItemKind::Variable, "Variables"),
+        ];
+        for (kind, title) in sections {
+            let items: Vec<&DocItem> = module.items.iter().filter(|item| item.kind == kind).collect();
+            if items.is_empty() {
+                continue;
+            }
+            page.push_str(&self.heading(2, title, None));
+            for item in items {
+                self.render_item(&mut page, item, &module.name, 3);
+            }
+        }
+
+        match self.format {
+            DocFormat::Markdown => page,
+            DocFormat::Html => html_page(&module.name, &page),
+        }
+    }
+
+    /// Render a definition and, for classes and interfaces, their members
+    fn render_item(&mut self, page: &mut String, item: &DocItem, module: &str, level: usize) {
+        page.push_str(&self.heading(level, &item.anchor, Some(&item.anchor)));
+
+        match self.format {
+            DocFormat::Markdown => page.push_str(&format!("```kodeon\n{}\n```\n", item.signature)),
+            DocFormat::Html => page.push_str(&format!("<pre><code>{}</code></pre>\n", escape_html(&item.signature))),
+        }
+
+        if let Some(parent) = &item.parent_class {
+            let link = self.resolve(parent, module);
+            page.push_str(&match (self.format, link) {
+                (DocFormat::Markdown, Some(link)) => format!("\nInherits from [`{}`]({}).\n", parent, link),
+                (DocFormat::Markdown, None) => format!("\nInherits from `{}`.\n", parent),
+                (DocFormat::Html, Some(link)) => format!(
+                    "<p>Inherits from <a href=\"{}\"><code>{}</code></a>.</p>\n",
+                    escape_html(&link),
+                    escape_html(parent)
+                ),
+                (DocFormat::Html, None) => format!("<p>Inherits from <code>{}</code>.</p>\n", escape_html(parent)),
+            });
+        }
+
+        if let Some(doc) = &item.doc {
+            let text = self.render_doc(doc, module);
+            page.push_str(&text);
+        }
+
+        let sections = [("Fields", false), ("Methods", true)];
+        for (title, methods) in sections {
+            let members: Vec<&DocItem> = item
+                .members
+                .iter()
+                .filter(|member| (member.kind == DocItemKind::Function) == methods)
+                .collect();
+            if members.is_empty() {
+                continue;
+            }
+            page.push_str(&self.heading(level + 1, title, None));
+            for member in members {
+                self.render_item(page, member, module, level + 2);
+            }
+        }
+    }
+
+    /// Render a heading, with an anchor for definitions
+    fn heading(&self, level: usize, text: &str, anchor: Option<&str>) -> String {
+        let level = level.min(6);
+        match (self.format, anchor) {
+            (DocFormat::Markdown, Some(anchor)) => {
+                format!("\n<a id=\"{}\"></a>\n\n{} `{}`\n\n", anchor, "#".repeat(level), text)
+            }
+            (DocFormat::Markdown, None) => format!("\n{} {}\n", "#".repeat(level), text),
+            (DocFormat::Html, Some(anchor)) => format!(
+                "<h{} id=\"{}\"><code>{}</code></h{}>\n",
+                level,
+                escape_html(anchor),
+                escape_html(text),
+                level
+            ),
+            (DocFormat::Html, None) => format!("<h{}>{}</h{}>\n", level, escape_html(text), level),
+        }
+    }
+
+    /// Render doc comment text, linking `names` in backticks that resolve to a definition
+    fn render_doc(&mut self, doc: &str, module: &str) -> String {
+        let mut parts: Vec<(bool, String)> = Vec::new(); // (is code, text)
+        for (index, part) in doc.split('`').enumerate() {
+            parts.push((index % 2 == 1, part.to_string()));
+        }
+        // An unmatched backtick leaves the rest of the text as written
+        if parts.last().is_some_and(|(is_code, _)| *is_code) {
+            let (_, last) = parts.pop().unwrap();
+            let (_, previous) = parts.pop().unwrap();
+            parts.push((false, format!("{}`{}", previous, last)));
+        }
+
+        let mut text = String::new();
+        for (is_code, part) in parts {
+            let link = if is_code { self.resolve(&part, module) } else { None };
+            text.push_str(&match (self.format, is_code, link) {
+                (DocFormat::Markdown, true, Some(link)) => format!("[`{}`]({})", part, link),
+                (DocFormat::Markdown, true, None) => format!("`{}`", part),
+                (DocFormat::Markdown, false, _) => part,
+                (DocFormat::Html, true, Some(link)) => {
+                    format!("<a href=\"{}\"><code>{}</code></a>", escape_html(&link), escape_html(&part))
+                }
+                (DocFormat::Html, true, None) => format!("<code>{}</code>", escape_html(&part)),
+                (DocFormat::Html, false, _) => escape_html(&part),
+            });
+        }
+
+        match self.format {
+            DocFormat::Markdown => format!("\n{}\n", text),
+            DocFormat::Html => text
+                .split("\n\n")
+                .map(|paragraph| format!("<p>{}</p>\n", paragraph.trim()))
+                .collect(),
+        }
+    }
+}
+
+/// Escape text for HTML
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Complete HTML document around `body`
+fn html_page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape_html(title),
+        body
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = concat!(
+        "/// Bentuk dua dimensi\n",
+        "kelas Bentuk:\n",
+        "    /// Luas bentuk, lihat `Lingkaran`\n",
+        "    fungsi luas():  // selalu nol\n",
+        "        kembalikan 0\n",
+        "\n",
+        "/// Lingkaran dengan `jari` tertentu\n",
+        "kelas Lingkaran(Bentuk):\n",
+        "    buat jari = 1\n",
+        "    pribadi fungsi rahasia():\n",
+        "        kembalikan 1\n",
+        "\n",
+        "/// Jumlah <a> & <b>, lebih mudah dari `Bentuk.luas` atau `Bentuk.keliling`\n",
+        "fungsi tambah(a, b: int) {\n",
+        "    kembalikan a + b\n",
+        "}\n",
+        "\n",
+        "konstan PI = 3.14\n",
+    );
+
+    fn generator(format: DocFormat) -> DocGenerator {
+        let mut generator = DocGenerator::new(format);
+        generator.add_source("geometri", SOURCE).unwrap();
+        generator
+    }
+
+    #[test]
+    fn test_collects_documented_items() {
+        let generator = generator(DocFormat::Markdown);
+        let items = &generator.modules()[0].items;
+
+        assert_eq!(items.len(), 4);
+        assert_eq!(items[0].doc.as_deref(), Some("Bentuk dua dimensi"));
+        assert_eq!(items[0].members[0].anchor, "Bentuk.luas");
+        assert_eq!(items[0].members[0].signature, "fungsi luas()");
+        assert_eq!(items[1].members.len(), 1); // the private method is not documented
+        assert_eq!(items[2].signature, "fungsi tambah(a, b: int)");
+        assert_eq!(items[3].kind, DocItemKind::Constant);
+        assert_eq!(items[2].parameters, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_render_markdown() {
+        let mut generator = generator(DocFormat::Markdown);
+        let module = generator.modules()[0].clone();
+        let page = generator.render_module(&module);
+
+        assert!(page.starts_with("# Module `geometri`"));
+        assert!(page.contains("## Classes"));
+        assert!(page.contains("```kodeon\nkelas Lingkaran(Bentuk)\n```"));
+        assert!(page.contains("Inherits from [`Bentuk`](#Bentuk)."));
+        assert!(page.contains("Luas bentuk, lihat [`Lingkaran`](#Lingkaran)"));
+        assert!(page.contains("[`Bentuk.luas`](#Bentuk.luas)"));
+        assert!(page.contains("`Bentuk.keliling`"));
+        assert!(page.contains("Lingkaran dengan `jari` tertentu"));
+        assert!(!page.contains("rahasia"));
+    }
+
+    #[test]
+    fn test_render_html() {
+        let mut generator = generator(DocFormat::Html);
+        let module = generator.modules()[0].clone();
+        let page = generator.render_module(&module);
+
+        assert!(page.contains("<h3 id=\"tambah\"><code>tambah</code></h3>"));
+        assert!(page.contains("<p>Jumlah &lt;a&gt; &amp; &lt;b&gt;, lebih mudah dari <a href=\"#Bentuk.luas\"><code>Bentuk.luas</code></a>"));
+        assert!(page.contains("<a href=\"#Bentuk\"><code>Bentuk</code></a>"));
+    }
+
+    #[test]
+    fn test_documents_interfaces() {
+        let mut generator = DocGenerator::new(DocFormat::Markdown);
+        generator
+            .add_source("gambar", "/// Sesuatu yang bisa digambar\nantarmuka Gambar:\n    /// Gambar di `kanvas`\n    fungsi gambar(kanvas): bool\n")
+            .unwrap();
+        let module = generator.modules()[0].clone();
+        assert_eq!(module.items[0].kind, DocItemKind::Interface);
+        assert_eq!(module.items[0].members[0].signature, "fungsi gambar(kanvas): bool");
+
+        let page = generator.render_module(&module);
+        assert!(page.contains("## Interfaces"));
+        assert!(page.contains("<a id=\"Gambar.gambar\"></a>"));
+    }
+
+    #[test]
+    fn test_links_across_modules() {
+        let mut generator = generator(DocFormat::Markdown);
+        generator
+            .add_source("bidang", "/// Bidang yang berisi `Bentuk`\nkelas Bidang(Bentuk):\n    buat isi = []\n")
+            .unwrap();
+        let module = generator.modules()[1].clone();
+        let page = generator.render_module(&module);
+
+        assert!(page.contains("[`Bentuk`](geometri.md#Bentuk)"));
+        assert!(generator.render_index().contains("- [`bidang`](bidang.md)"));
+    }
+}
@@ -1,100 +1,244 @@
 //! Enhanced error messages for the KODEON programming language
+//!
+//! Every error the compiler reports has a stable code such as `K0201`. The text for each
+//! code lives in a catalog per language, `messages/<locale>.msg`, so that translations are
+//! data rather than code.
 
-use crate::lexer::Position;
+use crate::diagnostics::Diagnostic;
+use crate::lexer::{Position, Span};
+use std::collections::HashMap;
 
-/// Error message templates with multi-language support
+/// Language of diagnostics
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Language {
+    English,
+    Indonesian,
+}
+
+impl Language {
+    /// Language for a tag such as `id`, `en` or a locale such as `id_ID.UTF-8`
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let primary = tag.split(['_', '-', '.', '@']).next().unwrap_or("").to_ascii_lowercase();
+        match primary.as_str() {
+            "en" | "english" => Some(Language::English),
+            // `in` is the former code of Indonesian, still used by some systems
+            "id" | "in" | "indonesian" => Some(Language::Indonesian),
+            _ => None,
+        }
+    }
+
+    /// Language of the user's locale (`LC_ALL`, `LC_MESSAGES`, then `LANG`), English if unknown
+    pub fn from_env() -> Self {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Self::from_tag(&value))
+            .unwrap_or(Language::English)
+    }
+
+    /// Tag of the language, as accepted by `--lang`
+    pub fn tag(&self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::Indonesian => "id",
+        }
+    }
+}
+
+/// Message catalogs shipped with the compiler, by language
+const CATALOGS: &[(Language, &str)] = &[
+    (Language::English, include_str!("messages/en.msg")),
+    (Language::Indonesian, include_str!("messages/id.msg")),
+];
+
+/// Catalog entry: the text of one error code in one language
+#[derive(Debug, Clone, Default)]
 pub struct ErrorMessage {
-    pub id: &'static str,
-    pub english: &'static str,
-    pub indonesian: &'static str,
-    pub context: &'static str,
-    pub suggestion: &'static str,
-    pub example: &'static str,
+    pub code: String,       // stable code, e.g. K0201
+    pub name: String,       // identifier in the compiler source, e.g. undeclared_variable
+    pub title: String,      // short summary
+    pub message: String,    // headline, with `{argument}` placeholders
+    pub label: String,      // text under the offending source, with placeholders
+    pub secondary: String,  // text under related source, such as an earlier declaration
+    pub context: String,    // why the error happens
+    pub suggestion: String, // how to fix it
+    pub example: String,    // correct code
 }
 
-/// Collection of error messages
-pub struct ErrorMessages;
+impl ErrorMessage {
+    /// Headline with its placeholders filled in
+    pub fn format_message(&self, arguments: &[(&str, String)]) -> String {
+        fill(&self.message, arguments)
+    }
 
-impl ErrorMessages {
-    /// Get error message by ID and language
-    pub fn get_message(id: &str, language: &str) -> Option<&'static ErrorMessage> {
-        match id {
-            "unexpected_token" => Some(&UNEXPECTED_TOKEN),
-            "unexpected_eof" => Some(&UNEXPECTED_EOF),
-            "undeclared_variable" => Some(&UNDECLARED_VARIABLE),
-            "duplicate_declaration" => Some(&DUPLICATE_DECLARATION),
-            "type_mismatch" => Some(&TYPE_MISMATCH),
-            "invalid_operation" => Some(&INVALID_OPERATION),
-            "uninitialized_variable" => Some(&UNINITIALIZED_VARIABLE),
-            _ => None,
+    /// Label with its placeholders filled in
+    pub fn format_label(&self, arguments: &[(&str, String)]) -> String {
+        fill(&self.label, arguments)
+    }
+}
+
+/// Replace each `{name}` in `template` by its argument
+fn fill(template: &str, arguments: &[(&str, String)]) -> String {
+    arguments.iter().fold(template.to_string(), |text, (name, value)| {
+        text.replace(&format!("{{{}}}", name), value)
+    })
+}
+
+/// Join a suggested fix and an example of correct code into one help text
+fn help_with_example(suggestion: &str, example: &str, language: Language) -> String {
+    let lead = match language {
+        Language::English => "for example",
+        Language::Indonesian => "contoh",
+    };
+    match (suggestion.is_empty(), example.is_empty()) {
+        (_, true) => suggestion.to_string(),
+        (true, false) => format!("{}:\n{}", lead, example),
+        (false, false) => format!("{}\n{}:\n{}", suggestion, lead, example),
+    }
+}
+
+/// Parse a catalog; panics on malformed input, since catalogs are compiled in
+fn parse_catalog(text: &str) -> Vec<ErrorMessage> {
+    let mut entries: Vec<ErrorMessage> = Vec::new();
+    let mut field: Option<&str> = None;
+
+    for (index, line) in text.lines().enumerate() {
+        let line_number = index + 1;
+        let trimmed = line.trim();
+        if trimmed.starts_with('#') || (trimmed.is_empty() && !line.starts_with('|')) {
+            continue;
+        }
+
+        if let Some(code) = trimmed.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+            entries.push(ErrorMessage { code: code.to_string(), ..Default::default() });
+            field = None;
+            continue;
         }
+
+        let entry = entries.last_mut()
+            .unwrap_or_else(|| panic!("catalog line {} is outside of an entry", line_number));
+
+        let (name, value, continued) = match line.strip_prefix('|') {
+            Some(rest) => {
+                let name = field.unwrap_or_else(|| panic!("catalog line {} continues no field", line_number));
+                (name, rest.strip_prefix(' ').unwrap_or(rest), true)
+            }
+            None => {
+                let (name, value) = line.split_once('=')
+                    .unwrap_or_else(|| panic!("catalog line {} is not of the form 'field = text'", line_number));
+                (name.trim(), value.trim(), false)
+            }
+        };
+
+        let target = match name {
+            "name" => &mut entry.name,
+            "title" => &mut entry.title,
+            "message" => &mut entry.message,
+            "label" => &mut entry.label,
+            "secondary" => &mut entry.secondary,
+            "context" => &mut entry.context,
+            "suggestion" => &mut entry.suggestion,
+            "example" => &mut entry.example,
+            other => panic!("unknown field '{}' at catalog line {}", other, line_number),
+        };
+        if continued && !target.is_empty() {
+            target.push('\n');
+        }
+        target.push_str(value);
+        field = Some(name);
     }
+
+    entries
 }
 
-// Error message definitions
 lazy_static::lazy_static! {
-    pub static ref UNEXPECTED_TOKEN: ErrorMessage = ErrorMessage {
-        id: "unexpected_token",
-        english: "Unexpected token",
-        indonesian: "Token tidak terduga",
-        context: "The parser encountered a token it wasn't expecting at this position.",
-        suggestion: "Check if you've forgotten a semicolon, brace, or other punctuation mark.",
-        example: "   // Correct syntax:\n   x = 5;\n   y = 10;"
-    };
+    static ref MESSAGES: HashMap<Language, Vec<ErrorMessage>> = CATALOGS
+        .iter()
+        .map(|(language, text)| (*language, parse_catalog(text)))
+        .collect();
+}
 
-    pub static ref UNEXPECTED_EOF: ErrorMessage = ErrorMessage {
-        id: "unexpected_eof",
-        english: "Unexpected end of file",
-        indonesian: "Akhir file tidak terduga",
-        context: "The parser reached the end of the file while expecting more tokens.",
-        suggestion: "Check if you've closed all braces, parentheses, or other delimiters.",
-        example: "   // Correct syntax:\n   function example() {\n       // code here\n   }"
-    };
+/// Collection of error messages
+pub struct ErrorMessages;
 
-    pub static ref UNDECLARED_VARIABLE: ErrorMessage = ErrorMessage {
-        id: "undeclared_variable",
-        english: "Undeclared variable",
-        indonesian: "Variabel tidak dideklarasikan",
-        context: "You're trying to use a variable that hasn't been declared.",
-        suggestion: "Declare the variable before using it, or check if you've spelled the name correctly.",
-        example: "   // Correct syntax:\n   var jumlah = 0;\n   hasil = jumlah + 5;"
-    };
+impl ErrorMessages {
+    /// Get error message by code (`K0201`) or name (`undeclared_variable`) and language
+    pub fn get_message(id: &str, language: Language) -> Option<&'static ErrorMessage> {
+        MESSAGES[&language]
+            .iter()
+            .find(|message| message.code.eq_ignore_ascii_case(id) || message.name == id)
+    }
 
-    pub static ref DUPLICATE_DECLARATION: ErrorMessage = ErrorMessage {
-        id: "duplicate_declaration",
-        english: "Duplicate declaration",
-        indonesian: "Deklarasi duplikat",
-        context: "You're trying to declare a variable or function that already exists in this scope.",
-        suggestion: "Use a different name, or remove the duplicate declaration.",
-        example: "   // Correct syntax:\n   var x = 5;\n   var y = 10;  // Different name"
-    };
+    /// Every error code, in order
+    pub fn codes() -> impl Iterator<Item = &'static str> {
+        MESSAGES[&Language::English].iter().map(|message| message.code.as_str())
+    }
 
-    pub static ref TYPE_MISMATCH: ErrorMessage = ErrorMessage {
-        id: "type_mismatch",
-        english: "Type mismatch",
-        indonesian: "Ketidakcocokan tipe",
-        context: "You're trying to perform an operation on incompatible types.",
-        suggestion: "Convert one of the values to match the other type, or use an appropriate operation.",
-        example: "   // Correct syntax:\n   hasil = string(5) + \"hello\";  // Convert int to string\n   // Or:\n   hasil = 5 + int(\"10\");  // Convert string to int"
-    };
+    /// Long-form explanation of an error code, as printed by `kodeon explain`
+    pub fn explain(code: &str, language: Language) -> Option<String> {
+        let message = Self::get_message(code, language)?;
+        let (why, fix, example) = match language {
+            Language::English => ("Why", "How to fix", "Example"),
+            Language::Indonesian => ("Penyebab", "Cara memperbaiki", "Contoh"),
+        };
 
-    pub static ref INVALID_OPERATION: ErrorMessage = ErrorMessage {
-        id: "invalid_operation",
-        english: "Invalid operation",
-        indonesian: "Operasi tidak valid",
-        context: "The operation you're trying to perform is not supported on this type.",
-        suggestion: "Check if you're using the correct operator for this type, or convert to a compatible type.",
-        example: "   // Correct syntax:\n   var x = 5;\n   var y = x + 10;  // Addition is supported for integers"
-    };
+        let mut text = format!("{}: {}\n\n{}:\n    {}\n\n{}:\n    {}\n", message.code, message.title, why, message.context, fix, message.suggestion);
+        if !message.example.is_empty() {
+            text.push_str(&format!("\n{}:\n", example));
+            for line in message.example.lines() {
+                text.push_str(&format!("    {}\n", line));
+            }
+        }
+        Some(text)
+    }
 
-    pub static ref UNINITIALIZED_VARIABLE: ErrorMessage = ErrorMessage {
-        id: "uninitialized_variable",
-        english: "Uninitialized variable",
-        indonesian: "Variabel tidak diinisialisasi",
-        context: "You're trying to use a variable before giving it a value.",
-        suggestion: "Assign a value to the variable before using it.",
-        example: "   // Correct syntax:\n   var x;  // Declaration\n   x = 5;  // Initialization\n   print(x);  // Usage"
-    };
+    /// Build an error diagnostic from the catalog
+    ///
+    /// `details` are the context, suggestion and example written where the error was found.
+    /// They are more specific than the catalog, but English only, so they are used for
+    /// English output and the catalog text otherwise.
+    pub fn diagnostic(
+        code: &str,
+        arguments: &[(&str, String)],
+        span: Span,
+        details: (&str, &str, &str),
+        language: Language,
+    ) -> Diagnostic {
+        let message = Self::lookup(code, language);
+        let (context, suggestion, example) = match (language, details) {
+            (Language::English, (context, suggestion, example)) if !context.is_empty() || !suggestion.is_empty() => {
+                (context, suggestion, example)
+            }
+            _ => (message.context.as_str(), message.suggestion.as_str(), message.example.as_str()),
+        };
+
+        Diagnostic::error(message.format_message(arguments))
+            .with_code(message.code.as_str())
+            .with_primary(span, message.format_label(arguments))
+            .with_note(context)
+            .with_help(help_with_example(suggestion, example, language))
+    }
+
+    /// Headline of `code` with its placeholders filled in, for text printed outside a diagnostic
+    pub fn message(code: &str, arguments: &[(&str, String)], language: Language) -> String {
+        Self::lookup(code, language).format_message(arguments)
+    }
+
+    /// Build an error diagnostic without a source location, such as a backend failure
+    pub fn unlocated(code: &str, arguments: &[(&str, String)], language: Language) -> Diagnostic {
+        let message = Self::lookup(code, language);
+        Diagnostic::error(message.format_message(arguments))
+            .with_code(message.code.as_str())
+            .with_note(message.context.as_str())
+            .with_help(help_with_example(&message.suggestion, &message.example, language))
+    }
+
+    /// Catalog entry of a code the compiler itself reports, which every catalog must have
+    fn lookup(code: &str, language: Language) -> &'static ErrorMessage {
+        Self::get_message(code, language)
+            .unwrap_or_else(|| panic!("error code {} is missing from the {} catalog", code, language.tag()))
+    }
 }
 
 /// Create an enhanced parse error with detailed information
@@ -103,42 +247,26 @@ pub fn create_enhanced_parse_error(
     expected: String,
     found: String,
     position: Position,
-    language: &str,
+    language: Language,
 ) -> crate::parser::ParseError {
-    if let Some(msg) = ErrorMessages::get_message(error_type, language) {
-        match error_type {
-            "unexpected_token" => crate::parser::ParseError::UnexpectedToken {
-                expected,
-                found,
-                position,
-                context: msg.context.to_string(),
-                suggestion: if language == "indonesian" { msg.suggestion } else { msg.english }.to_string(),
-                example: msg.example.to_string(),
-            },
-            "unexpected_eof" => crate::parser::ParseError::UnexpectedEOF {
-                expected,
-                position,
-                context: msg.context.to_string(),
-                suggestion: if language == "indonesian" { msg.suggestion } else { msg.english }.to_string(),
-                example: msg.example.to_string(),
-            },
-            _ => crate::parser::ParseError::InvalidSyntax {
-                message: if language == "indonesian" { msg.indonesian } else { msg.english }.to_string(),
-                position,
-                context: msg.context.to_string(),
-                suggestion: if language == "indonesian" { msg.suggestion } else { msg.english }.to_string(),
-                example: msg.example.to_string(),
-            },
-        }
-    } else {
+    let Some(msg) = ErrorMessages::get_message(error_type, language) else {
         // Fallback to basic error
-        crate::parser::ParseError::InvalidSyntax {
+        return crate::parser::ParseError::InvalidSyntax {
             message: format!("Unknown error: {}", error_type),
             position,
             context: "".to_string(),
             suggestion: "".to_string(),
             example: "".to_string(),
-        }
+        };
+    };
+
+    let context = msg.context.clone();
+    let suggestion = msg.suggestion.clone();
+    let example = msg.example.clone();
+    match msg.name.as_str() {
+        "unexpected_token" => crate::parser::ParseError::UnexpectedToken { expected, found, position, context, suggestion, example },
+        "unexpected_eof" => crate::parser::ParseError::UnexpectedEOF { expected, position, context, suggestion, example },
+        _ => crate::parser::ParseError::InvalidSyntax { message: msg.title.clone(), position, context, suggestion, example },
     }
 }
 
@@ -150,63 +278,96 @@ pub fn create_enhanced_semantic_error(
     found: String,
     position: Position,
     first_position: Option<Position>,
-    language: &str,
+    language: Language,
 ) -> crate::semantic_analyzer::SemanticError {
-    if let Some(msg) = ErrorMessages::get_message(error_type, language) {
-        match error_type {
-            "undeclared_variable" => crate::semantic_analyzer::SemanticError::UndeclaredVariable {
-                name,
-                position,
-                context: msg.context.to_string(),
-                suggestion: if language == "indonesian" { msg.suggestion } else { msg.english }.to_string(),
-                example: msg.example.to_string(),
-            },
-            "duplicate_declaration" => crate::semantic_analyzer::SemanticError::DuplicateDeclaration {
-                name,
-                first_position: first_position.unwrap_or(position.clone()),
-                duplicate_position: position,
-                context: msg.context.to_string(),
-                suggestion: if language == "indonesian" { msg.suggestion } else { msg.english }.to_string(),
-                example: msg.example.to_string(),
-            },
-            "type_mismatch" => crate::semantic_analyzer::SemanticError::TypeMismatch {
-                expected,
-                found,
-                position,
-                context: msg.context.to_string(),
-                suggestion: if language == "indonesian" { msg.suggestion } else { msg.english }.to_string(),
-                example: msg.example.to_string(),
-            },
-            "invalid_operation" => crate::semantic_analyzer::SemanticError::InvalidOperation {
-                message: if language == "indonesian" { msg.indonesian } else { msg.english }.to_string(),
-                position,
-                context: msg.context.to_string(),
-                suggestion: if language == "indonesian" { msg.suggestion } else { msg.english }.to_string(),
-                example: msg.example.to_string(),
-            },
-            "uninitialized_variable" => crate::semantic_analyzer::SemanticError::UninitializedVariable {
-                name,
-                position,
-                context: msg.context.to_string(),
-                suggestion: if language == "indonesian" { msg.suggestion } else { msg.english }.to_string(),
-                example: msg.example.to_string(),
-            },
-            _ => crate::semantic_analyzer::SemanticError::InvalidOperation {
-                message: format!("Unknown error: {}", error_type),
-                position,
-                context: "".to_string(),
-                suggestion: "".to_string(),
-                example: "".to_string(),
-            },
-        }
-    } else {
+    use crate::semantic_analyzer::SemanticError;
+
+    let Some(msg) = ErrorMessages::get_message(error_type, language) else {
         // Fallback to basic error
-        crate::semantic_analyzer::SemanticError::InvalidOperation {
+        return SemanticError::InvalidOperation {
             message: format!("Unknown error: {}", error_type),
             position,
             context: "".to_string(),
             suggestion: "".to_string(),
             example: "".to_string(),
+        };
+    };
+
+    let context = msg.context.clone();
+    let suggestion = msg.suggestion.clone();
+    let example = msg.example.clone();
+    match msg.name.as_str() {
+        "undeclared_variable" => SemanticError::UndeclaredVariable { name, position, context, suggestion, example },
+        "duplicate_declaration" => SemanticError::DuplicateDeclaration {
+            name,
+            first_position: first_position.unwrap_or(position.clone()),
+            duplicate_position: position,
+            context,
+            suggestion,
+            example,
+        },
+        "type_mismatch" => SemanticError::TypeMismatch { expected, found, position, context, suggestion, example },
+        "uninitialized_variable" => SemanticError::UninitializedVariable { name, position, context, suggestion, example },
+        _ => SemanticError::InvalidOperation { message: msg.title.clone(), position, context, suggestion, example },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_catalogs_are_complete() {
+        let english: Vec<&ErrorMessage> = MESSAGES[&Language::English].iter().collect();
+        for (language, _) in CATALOGS {
+            let catalog = &MESSAGES[language];
+            let codes: HashSet<&str> = catalog.iter().map(|message| message.code.as_str()).collect();
+            assert_eq!(codes.len(), catalog.len(), "duplicate code in the {} catalog", language.tag());
+
+            for reference in &english {
+                let message = ErrorMessages::get_message(&reference.code, *language)
+                    .unwrap_or_else(|| panic!("{} is missing from the {} catalog", reference.code, language.tag()));
+                assert_eq!(message.name, reference.name);
+                assert!(!message.title.is_empty() && !message.message.is_empty() && !message.context.is_empty());
+            }
+            assert_eq!(catalog.len(), english.len());
         }
     }
+
+    #[test]
+    fn test_lookup_and_format() {
+        let message = ErrorMessages::get_message("undeclared_variable", Language::Indonesian).unwrap();
+        assert_eq!(message.code, "K0201");
+        assert_eq!(message.format_message(&[("name", "jumlah".to_string())]), "Variabel 'jumlah' belum dideklarasikan");
+
+        let message = ErrorMessages::get_message("k0002", Language::English).unwrap();
+        assert_eq!(message.example, "tampilkan \"Halo, Dunia!\"\nbuat teks = \"\"\"Baris 1\nBaris 2\"\"\"");
+        assert!(ErrorMessages::get_message("K9999", Language::English).is_none());
+    }
+
+    #[test]
+    fn test_language_from_locale() {
+        assert_eq!(Language::from_tag("id_ID.UTF-8"), Some(Language::Indonesian));
+        assert_eq!(Language::from_tag("en-US"), Some(Language::English));
+        assert_eq!(Language::from_tag("in"), Some(Language::Indonesian));
+        assert_eq!(Language::from_tag("C"), None);
+    }
+
+    #[test]
+    fn test_explain() {
+        let text = ErrorMessages::explain("K0202", Language::Indonesian).unwrap();
+        assert!(text.starts_with("K0202: Deklarasi ganda\n"));
+        assert!(text.contains("Contoh:\n    buat x = 5\n    x = 10\n"));
+        assert!(ErrorMessages::explain("K0304", Language::English).unwrap().ends_with("Report the program together with the note.\n"));
+    }
+
+    #[test]
+    fn test_unlocated_diagnostic() {
+        let diagnostic = ErrorMessages::unlocated("K0302", &[], Language::Indonesian).with_note("unknown type");
+        assert_eq!(diagnostic.code.as_deref(), Some("K0302"));
+        assert_eq!(diagnostic.message, "Backend LLVM tidak dapat mengompilasi program ini");
+        assert!(diagnostic.labels.is_empty());
+        assert_eq!(diagnostic.notes.last().map(String::as_str), Some("unknown type"));
+    }
 }
@@ -0,0 +1,825 @@
+//! Control-flow analysis of the HIR
+//!
+//! The top level of the program, each function, each lambda and each class body is lowered to a
+//! control-flow graph whose blocks record, in order, where variables are declared, assigned and read.
+//! A forward pass over each graph finds reads of variables that may not have a value yet, and second
+//! assignments to a `biarkan` declared without one. Statements no path reaches are reported as
+//! unreachable, and variables and parameters no expression reads as unused.
+
+use crate::hir::{Callee, Capture, Constant, Expression, ExpressionKind, Pattern, PatternKind, Program, StageKind, Statement, StatementKind, SymbolId, SymbolKind};
+use crate::lexer::{Position, Span};
+use crate::types::Type;
+use crate::semantic_analyzer::{SemanticError, SemanticWarning};
+use std::collections::HashSet;
+
+/// Example shown for reads of variables that may not have a value
+const UNINITIALIZED_EXAMPLE: &str = "   buat pesan\n   jika berhasil:\n       pesan = \"selesai\"\n   sebaliknya:\n       pesan = \"gagal\"\n   tampilkan pesan";
+
+/// Errors and warnings found in a program
+#[derive(Debug, Default)]
+pub struct Report {
+    pub errors: Vec<SemanticError>, // in source order
+    pub warnings: Vec<SemanticWarning>, // in source order
+}
+
+/// Find the problems in the flow of `program`
+pub fn analyze(program: &Program) -> Report {
+    let mut report = Report::default();
+    let mut read = HashSet::new();
+    let mut candidates = Vec::new();
+    let mut bodies = vec![Body { statements: &program.body, function: None }];
+    while let Some(body) = bodies.pop() {
+        let mut graph = Graph::new(program);
+        graph.statements(body.statements);
+        graph.check(&mut report);
+        if let Some(function) = body.function {
+            graph.check_return(&function, &mut report);
+        }
+        read.extend(graph.blocks.iter().flat_map(|block| &block.events).filter_map(|event| match event {
+            Event::Read(symbol, _) => Some(*symbol),
+            _ => None,
+        }));
+        bodies.append(&mut graph.nested);
+        candidates.append(&mut graph.candidates);
+    }
+
+    // Top-level names the module exports are read by the modules importing it
+    let exported: HashSet<&str> = program.body.iter()
+        .filter_map(|statement| match &statement.kind {
+            StatementKind::Export(names) => Some(names.iter().map(String::as_str)),
+            _ => None,
+        })
+        .flatten()
+        .collect();
+    for candidate in candidates {
+        let name = &program.symbol(candidate.symbol).name;
+        if read.contains(&candidate.symbol) || name.starts_with('_') || exported.contains(name.as_str()) {
+            continue;
+        }
+        report.warnings.push(candidate.warning(name));
+    }
+
+    report.errors.sort_by_key(|error| (error.position().line, error.position().column));
+    report.warnings.sort_by_key(|warning| (warning.position().line, warning.position().column));
+    report
+}
+
+/// Whether a loop condition is `benar` when compiling, as in `selama benar`
+fn always(condition: &Expression) -> bool {
+    matches!(condition.evaluate(), Ok(Constant::Boolean(true)))
+}
+
+/// What a block does with a variable
+#[derive(Debug, Clone)]
+enum Event {
+    Declare(SymbolId), // `buat x` without a value: `x` holds nothing yet
+    Assign(SymbolId, Position),
+    Read(SymbolId, Position),
+}
+
+/// Straight-line code and the blocks that may run after it
+#[derive(Debug, Default)]
+struct Block {
+    events: Vec<Event>,
+    successors: Vec<usize>,
+}
+
+/// Statements analyzed in a graph of their own
+struct Body<'a> {
+    statements: &'a [Statement],
+    function: Option<Returning<'a>>, // for the body of a function or lambda
+}
+
+/// Function or lambda whose body is analyzed
+struct Returning<'a> {
+    name: String, // e.g. `'tanda'` or `a lambda`
+    ty: &'a Type, // type of the function
+    position: &'a Position,
+}
+
+/// Loop whose body is being lowered
+struct Loop {
+    next: usize, // block `lanjut` goes to
+    exit: usize, // block `pecah` goes to
+}
+
+/// Variable or parameter that is reported unless an expression reads it
+struct Candidate {
+    symbol: SymbolId,
+    position: Position,
+    span: Option<Span>,
+    function: Option<String>, // for a parameter, the function declaring it, e.g. `'sapa'` or `a lambda`
+}
+
+impl Candidate {
+    fn warning(self, name: &str) -> SemanticWarning {
+        match self.function {
+            Some(function) => SemanticWarning::UnusedParameter {
+                name: name.to_string(),
+                position: self.position,
+                span: self.span,
+                context: format!("The body of {} never reads the parameter '{}'", function, name),
+                suggestion: format!("Remove the parameter, or name it '_{}' if callers must still pass it", name),
+                example: "   fungsi sapa(nama, _waktu):\n       tampilkan \"Halo, \" + nama".to_string(),
+            },
+            None => SemanticWarning::UnusedVariable {
+                name: name.to_string(),
+                position: self.position,
+                span: self.span,
+                context: format!("'{}' is given a value that no expression reads", name),
+                suggestion: format!("Remove the variable, or name it '_{}' if it is kept on purpose", name),
+                example: "   buat total = hitung()\n   tampilkan total".to_string(),
+            },
+        }
+    }
+}
+
+/// Variables declared without a value that have one where the analysis is
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Assigned {
+    always: HashSet<SymbolId>, // on every path here
+    maybe: HashSet<SymbolId>, // on some path here
+}
+
+impl Assigned {
+    /// What is known where the paths of `self` and `other` join
+    fn join(&self, other: &Assigned) -> Assigned {
+        Assigned {
+            always: &self.always & &other.always,
+            maybe: &self.maybe | &other.maybe,
+        }
+    }
+
+    fn apply(&mut self, event: &Event) {
+        match event {
+            Event::Declare(symbol) => {
+                self.always.remove(symbol);
+                self.maybe.remove(symbol);
+            }
+            Event::Assign(symbol, _) => {
+                self.always.insert(*symbol);
+                self.maybe.insert(*symbol);
+            }
+            Event::Read(..) => {}
+        }
+    }
+}
+
+/// Control-flow graph of one body; block 0 is its entry
+struct Graph<'a> {
+    program: &'a Program,
+    blocks: Vec<Block>,
+    current: usize, // block the next statement is lowered into
+    loops: Vec<Loop>, // loops around the statement being lowered, innermost last
+    sequences: Vec<Vec<(usize, &'a Statement)>>, // statement lists, with the block each statement starts in
+    recording: bool, // off while `akhirnya` is lowered a second time, so its statements are reported once
+    nested: Vec<Body<'a>>, // bodies of the functions, lambdas and classes defined in this one
+    candidates: Vec<Candidate>,
+}
+
+impl<'a> Graph<'a> {
+    fn new(program: &'a Program) -> Self {
+        Graph {
+            program,
+            blocks: vec![Block::default()],
+            current: 0,
+            loops: Vec::new(),
+            sequences: Vec::new(),
+            recording: true,
+            nested: Vec::new(),
+            candidates: Vec::new(),
+        }
+    }
+
+    /// Add an empty block
+    fn block(&mut self) -> usize {
+        self.blocks.push(Block::default());
+        self.blocks.len() - 1
+    }
+
+    fn edge(&mut self, from: usize, to: usize) {
+        self.blocks[from].successors.push(to);
+    }
+
+    /// Continue the current block with `to`
+    fn jump(&mut self, to: usize) {
+        self.edge(self.current, to);
+    }
+
+    /// End the current block without a successor; what follows starts a block no path reaches
+    fn leave(&mut self) {
+        self.current = self.block();
+    }
+
+    fn record(&mut self, event: Event) {
+        self.blocks[self.current].events.push(event);
+    }
+
+    /// Report `symbol` if no expression reads it
+    fn candidate(&mut self, symbol: SymbolId, position: &Position, span: Option<Span>, function: Option<String>) {
+        if self.recording {
+            self.candidates.push(Candidate { symbol, position: position.clone(), span, function });
+        }
+    }
+
+    /// Analyze `statements` in a graph of their own
+    fn nest(&mut self, statements: &'a [Statement], function: Option<Returning<'a>>) {
+        if self.recording {
+            self.nested.push(Body { statements, function });
+        }
+    }
+
+    fn statements(&mut self, statements: &'a [Statement]) {
+        let mut sequence = Vec::new();
+        for statement in statements {
+            sequence.push((self.current, statement));
+            self.statement(statement);
+        }
+        if self.recording {
+            self.sequences.push(sequence);
+        }
+    }
+
+    fn statement(&mut self, statement: &'a Statement) {
+        let position = &statement.position;
+        match &statement.kind {
+            StatementKind::Let { symbol, value } => {
+                match value {
+                    Some(value) => {
+                        self.expression(value);
+                        self.record(Event::Assign(*symbol, position.clone()));
+                    }
+                    None => self.record(Event::Declare(*symbol)),
+                }
+                if matches!(self.program.symbol(*symbol).kind, SymbolKind::Variable { .. }) {
+                    self.candidate(*symbol, position, Some(statement.span), None);
+                }
+            }
+            StatementKind::Assign { symbol, value } => {
+                self.expression(value);
+                self.record(Event::Assign(*symbol, position.clone()));
+            }
+            StatementKind::Expression(expression)
+            | StatementKind::MutexLock(expression)
+            | StatementKind::MutexUnlock(expression)
+            | StatementKind::SignalCondition(expression)
+            | StatementKind::BroadcastCondition(expression) => self.expression(expression),
+            StatementKind::Return(value) => {
+                if let Some(value) = value {
+                    self.expression(value);
+                }
+                self.leave();
+            }
+            StatementKind::Throw(value) => {
+                self.expression(value);
+                self.leave();
+            }
+            StatementKind::If { condition, then_block, else_block } => {
+                self.expression(condition);
+                let branch = self.current;
+                let join = self.block();
+                for block in std::iter::once(then_block).chain(else_block) {
+                    self.current = self.block();
+                    self.edge(branch, self.current);
+                    self.statements(block);
+                    self.jump(join);
+                }
+                if else_block.is_none() {
+                    self.edge(branch, join);
+                }
+                self.current = join;
+            }
+            StatementKind::While { condition, body } => {
+                let header = self.block();
+                self.jump(header);
+                self.current = header;
+                self.expression(condition);
+                self.loop_body(None, !always(condition), body, statement);
+            }
+            StatementKind::DoWhile { body, condition } => {
+                let start = self.block();
+                let test = self.block();
+                let exit = self.block();
+                self.jump(start);
+                self.current = start;
+                self.loops.push(Loop { next: test, exit });
+                self.statements(body);
+                self.loops.pop();
+                self.jump(test);
+                self.current = test;
+                self.expression(condition);
+                self.jump(start);
+                if !always(condition) {
+                    self.jump(exit);
+                }
+                self.current = exit;
+            }
+            StatementKind::For { variable, start, end, body, .. } => {
+                self.expression(start);
+                self.expression(end);
+                self.enter_loop(*variable, body, statement);
+            }
+            StatementKind::ForEach { variable, iterable, body } => {
+                self.expression(iterable);
+                self.enter_loop(*variable, body, statement);
+            }
+            StatementKind::Function(function) => {
+                // Methods keep the parameters of the methods they override, and a function without a body is a stub
+                let info = self.program.symbol(function.symbol);
+                let returning = Returning { name: format!("'{}'", info.name), ty: &info.ty, position: &info.position };
+                self.nest(&function.body, (!function.body.is_empty()).then_some(returning));
+                if info.kind == SymbolKind::Function && !function.body.is_empty() {
+                    for &parameter in &function.parameters {
+                        let parameter_position = &self.program.symbol(parameter).position;
+                        self.candidate(parameter, parameter_position, None, Some(format!("'{}'", info.name)));
+                    }
+                }
+            }
+            StatementKind::Class(class) => self.nest(&class.body, None),
+            StatementKind::TryCatch { try_block, catches, finally_block } => {
+                // An error may be thrown before any statement of the block ran, so each clause starts from
+                // what was known before the block
+                let entry = self.block();
+                self.jump(entry);
+                let join = self.block();
+                self.current = self.block();
+                self.edge(entry, self.current);
+                self.statements(try_block);
+                let mut ends = vec![self.current];
+                for catch in catches {
+                    self.current = self.block();
+                    self.edge(entry, self.current);
+                    if let Some(variable) = catch.variable {
+                        self.record(Event::Assign(variable, catch.position.clone()));
+                    }
+                    self.statements(&catch.body);
+                    ends.push(self.current);
+                }
+                match finally_block {
+                    Some(finally_block) => {
+                        // `akhirnya` runs once after an error, `kembalikan`, `pecah` or `lanjut`, which leave
+                        // the statement, and once more on the way to the statement after it
+                        self.current = self.block();
+                        self.edge(entry, self.current);
+                        self.statements(finally_block);
+                        self.current = self.block();
+                        for end in ends {
+                            self.edge(end, self.current);
+                        }
+                        let recording = std::mem::replace(&mut self.recording, false);
+                        self.statements(finally_block);
+                        self.recording = recording;
+                        self.jump(join);
+                    }
+                    None => {
+                        for end in ends {
+                            self.edge(end, join);
+                        }
+                    }
+                }
+                self.current = join;
+            }
+            StatementKind::Break | StatementKind::Continue => {
+                if let Some(target) = self.loops.last() {
+                    let target = if matches!(statement.kind, StatementKind::Break) { target.exit } else { target.next };
+                    self.jump(target);
+                }
+                self.leave();
+            }
+            StatementKind::When { value, cases, else_case } => {
+                self.expression(value);
+                let dispatch = self.current;
+                let join = self.block();
+                for case in cases {
+                    self.current = self.block();
+                    self.edge(dispatch, self.current);
+                    self.pattern(&case.pattern);
+                    if let Some(guard) = &case.guard {
+                        self.expression(guard);
+                    }
+                    self.statements(&case.body);
+                    self.jump(join);
+                }
+                if let Some(else_case) = else_case {
+                    self.current = self.block();
+                    self.edge(dispatch, self.current);
+                    self.statements(else_case);
+                    self.jump(join);
+                }
+                if cases.is_empty() && else_case.is_none() {
+                    self.edge(dispatch, join);
+                }
+                self.current = join;
+            }
+            // The body of a goroutine or of a quantum or neural network construct may not run
+            StatementKind::Go(body) => self.maybe(body),
+            StatementKind::Unsupported { symbol, operands, body, .. } => {
+                for operand in operands {
+                    self.expression(operand);
+                }
+                if let Some(symbol) = symbol {
+                    self.record(Event::Assign(*symbol, position.clone()));
+                }
+                self.maybe(body);
+            }
+            StatementKind::ChannelReceive { channel, variable } => {
+                self.expression(channel);
+                self.record(Event::Assign(*variable, position.clone()));
+            }
+            StatementKind::MemberAssign { object: first, value: second, .. }
+            | StatementKind::ChannelSend { channel: first, value: second }
+            | StatementKind::WaitCondition { condition: first, mutex: second }
+            | StatementKind::AtomicStore { address: first, value: second } => {
+                self.expression(first);
+                self.expression(second);
+            }
+            StatementKind::IndexAssign { object, index, value } => {
+                self.expression(object);
+                self.expression(index);
+                self.expression(value);
+            }
+            StatementKind::Const { .. }
+            | StatementKind::Interface(_)
+            | StatementKind::Enum(_)
+            | StatementKind::Import { .. }
+            | StatementKind::Export(_) => {}
+        }
+    }
+
+    /// Lower a counting or `untuk ... dalam` loop, whose body starts by giving `variable` its next value
+    fn enter_loop(&mut self, variable: SymbolId, body: &'a [Statement], statement: &'a Statement) {
+        let header = self.block();
+        self.jump(header);
+        self.current = header;
+        self.loop_body(Some(variable), true, body, statement);
+    }
+
+    /// Lower the body of a loop whose test is the current block, and continue after the loop; unless
+    /// `ends`, the test always runs the body again and only `pecah` leaves
+    fn loop_body(&mut self, variable: Option<SymbolId>, ends: bool, body: &'a [Statement], statement: &'a Statement) {
+        let header = self.current;
+        let exit = self.block();
+        if ends {
+            self.jump(exit);
+        }
+        self.current = self.block();
+        self.edge(header, self.current);
+        if let Some(variable) = variable {
+            self.record(Event::Assign(variable, statement.position.clone()));
+            self.candidate(variable, &statement.position, None, None);
+        }
+        self.loops.push(Loop { next: header, exit });
+        self.statements(body);
+        self.loops.pop();
+        self.jump(header);
+        self.current = exit;
+    }
+
+    /// Lower statements that may be skipped
+    fn maybe(&mut self, body: &'a [Statement]) {
+        let before = self.current;
+        let join = self.block();
+        self.edge(before, join);
+        self.current = self.block();
+        self.edge(before, self.current);
+        self.statements(body);
+        self.jump(join);
+        self.current = join;
+    }
+
+    fn pattern(&mut self, pattern: &Pattern) {
+        match &pattern.kind {
+            PatternKind::Binding(symbol) => self.record(Event::Assign(*symbol, pattern.position.clone())),
+            PatternKind::Tuple(patterns) | PatternKind::Variant { fields: patterns, .. } => {
+                for pattern in patterns {
+                    self.pattern(pattern);
+                }
+            }
+            PatternKind::Wildcard | PatternKind::Literal(_) | PatternKind::Range { .. } => {}
+        }
+    }
+
+    fn expressions(&mut self, expressions: &'a [Expression]) {
+        for expression in expressions {
+            self.expression(expression);
+        }
+    }
+
+    /// Record the reads of an expression, in the order they are evaluated
+    fn expression(&mut self, expression: &'a Expression) {
+        match &expression.kind {
+            ExpressionKind::Variable(symbol) => self.record(Event::Read(*symbol, expression.position.clone())),
+            ExpressionKind::Interpolation(expressions)
+            | ExpressionKind::List(expressions)
+            | ExpressionKind::Tuple(expressions)
+            | ExpressionKind::Variant { arguments: expressions, .. }
+            | ExpressionKind::Construct { arguments: expressions, .. }
+            | ExpressionKind::SuperCall { arguments: expressions, .. }
+            | ExpressionKind::Unsupported { operands: expressions, .. } => self.expressions(expressions),
+            ExpressionKind::Call { callee, arguments, .. } => {
+                // Calling a variable reads the function it holds
+                if let Callee::Symbol(symbol) = callee {
+                    self.record(Event::Read(*symbol, expression.position.clone()));
+                }
+                self.expressions(arguments);
+            }
+            ExpressionKind::Map(fields) => {
+                for (_, value) in fields {
+                    self.expression(value);
+                }
+            }
+            ExpressionKind::MethodCall { object, arguments, .. } => {
+                self.expression(object);
+                self.expressions(arguments);
+            }
+            ExpressionKind::Binary { left: first, right: second, .. }
+            | ExpressionKind::Index { object: first, index: second }
+            | ExpressionKind::Elvis { value: first, default: second }
+            | ExpressionKind::Range { start: first, end: second, .. } => {
+                self.expression(first);
+                self.expression(second);
+            }
+            ExpressionKind::OptionalChain { object, binding, access } => {
+                self.expression(object);
+                self.record(Event::Assign(*binding, expression.position.clone()));
+                self.expression(access);
+            }
+            ExpressionKind::Propagate { value, error, early } => {
+                self.expression(value);
+                if let Some(error) = error {
+                    self.record(Event::Assign(*error, expression.position.clone()));
+                }
+                self.expression(early);
+            }
+            ExpressionKind::Unary { operand: inner, .. }
+            | ExpressionKind::Member { object: inner, .. }
+            | ExpressionKind::Spread(inner)
+            | ExpressionKind::Optional(inner)
+            | ExpressionKind::Unwrap(inner)
+            | ExpressionKind::Await(inner)
+            | ExpressionKind::Yield(inner)
+            | ExpressionKind::ChannelReceive(inner) => self.expression(inner),
+            ExpressionKind::Comprehension { element, variable, iterable, condition } => {
+                self.expression(iterable);
+                self.record(Event::Assign(*variable, expression.position.clone()));
+                if let Some(condition) = condition {
+                    self.expression(condition);
+                }
+                self.expression(element);
+            }
+            ExpressionKind::Lambda(lambda) => {
+                // A closure copies the variables it captures by value when it is created
+                for &capture in &lambda.captures {
+                    if self.program.symbol(capture).captured == Some(Capture::ByValue) {
+                        self.record(Event::Read(capture, expression.position.clone()));
+                    }
+                }
+                let returning = Returning { name: "a lambda".to_string(), ty: &expression.ty, position: &expression.position };
+                self.nest(&lambda.body, Some(returning));
+                for &parameter in &lambda.parameters {
+                    let parameter_position = &self.program.symbol(parameter).position;
+                    self.candidate(parameter, parameter_position, None, Some("a lambda".to_string()));
+                }
+            }
+            ExpressionKind::Pipeline { source, stages } => {
+                self.expression(source);
+                for stage in stages {
+                    match &stage.kind {
+                        StageKind::Filter(argument) | StageKind::Map(argument) | StageKind::Take(argument) | StageKind::Skip(argument) => {
+                            self.expression(argument);
+                        }
+                        StageKind::Sort(comparator) => {
+                            if let Some(comparator) = comparator {
+                                self.expression(comparator);
+                            }
+                        }
+                        StageKind::Reduce { function, initial } => {
+                            self.expression(function);
+                            if let Some(initial) = initial {
+                                self.expression(initial);
+                            }
+                        }
+                    }
+                }
+            }
+            ExpressionKind::Integer(_)
+            | ExpressionKind::Float(_)
+            | ExpressionKind::String(_)
+            | ExpressionKind::Boolean(_)
+            | ExpressionKind::Null
+            | ExpressionKind::SelfRef
+            | ExpressionKind::External(_)
+            | ExpressionKind::MakeChannel => {}
+        }
+    }
+
+    /// Blocks a path from the entry reaches
+    fn reachable(&self) -> Vec<bool> {
+        let mut reachable = vec![false; self.blocks.len()];
+        let mut work = vec![0];
+        while let Some(block) = work.pop() {
+            if !std::mem::replace(&mut reachable[block], true) {
+                work.extend(&self.blocks[block].successors);
+            }
+        }
+        reachable
+    }
+
+    /// Report what is wrong with the flow of this body
+    fn check(&self, report: &mut Report) {
+        let reachable = self.reachable();
+        self.check_assignments(report);
+        // The first statement of a list that runs after a statement leaving the list; definitions do not run
+        for sequence in &self.sequences {
+            let Some(&(first, _)) = sequence.first() else {
+                continue;
+            };
+            if !reachable[first] {
+                continue;
+            }
+            let unreachable = sequence.iter().position(|&(block, statement)| !reachable[block] && !matches!(
+                statement.kind,
+                StatementKind::Function(_) | StatementKind::Class(_) | StatementKind::Interface(_) | StatementKind::Enum(_)
+                    | StatementKind::Const { .. } | StatementKind::Import { .. } | StatementKind::Export(_)
+            ));
+            if let Some(index) = unreachable {
+                let statement = sequence[index].1;
+                let last = sequence[sequence.len() - 1].1;
+                report.warnings.push(SemanticWarning::UnreachableCode {
+                    position: statement.position.clone(),
+                    span: Some(statement.span.to(last.span)),
+                    context: "The statements before it always leave this block with 'kembalikan', 'lempar', 'pecah' or 'lanjut'".to_string(),
+                    suggestion: "Remove the code, or move it before the statement that leaves the block".to_string(),
+                    example: "   fungsi tanda(x):\n       jika x < 0:\n           kembalikan -1\n       kembalikan 1".to_string(),
+                });
+            }
+        }
+    }
+
+    /// Report the end of the body of a function that returns a value, if a path reaches it
+    fn check_return(&self, function: &Returning, report: &mut Report) {
+        let Type::Function { return_type, .. } = function.ty else {
+            return;
+        };
+        if **return_type == Type::Void || !self.reachable()[self.current] {
+            return;
+        }
+        report.errors.push(SemanticError::MissingReturn {
+            function: function.name.clone(),
+            returns: return_type.to_string(),
+            position: function.position.clone(),
+            context: format!("{} returns '{}', but a path through its body reaches the end without 'kembalikan' or 'lempar'", function.name, return_type),
+            suggestion: "Return a value after the last statement, or at the end of every branch that ends the body".to_string(),
+            example: "   fungsi tanda(x: int): int:\n       jika x < 0:\n           kembalikan -1\n       kembalikan 1".to_string(),
+        });
+    }
+
+    /// Report reads of variables declared without a value where some path has not assigned them, and
+    /// assignments to a `biarkan` some path has already assigned
+    fn check_assignments(&self, report: &mut Report) {
+        let declared: HashSet<SymbolId> = self.blocks.iter()
+            .flat_map(|block| &block.events)
+            .filter_map(|event| match event {
+                Event::Declare(symbol) => Some(*symbol),
+                _ => None,
+            })
+            .collect();
+        if declared.is_empty() {
+            return;
+        }
+
+        // What is known at the start of each block a path reaches, until nothing changes
+        let mut states: Vec<Option<Assigned>> = vec![None; self.blocks.len()];
+        states[0] = Some(Assigned::default());
+        let mut work = vec![0];
+        while let Some(block) = work.pop() {
+            let mut state = states[block].clone().expect("blocks are visited once a path reaches them");
+            for event in &self.blocks[block].events {
+                state.apply(event);
+            }
+            for &successor in &self.blocks[block].successors {
+                let joined = match &states[successor] {
+                    Some(known) => known.join(&state),
+                    None => state.clone(),
+                };
+                if states[successor].as_ref() != Some(&joined) {
+                    states[successor] = Some(joined);
+                    work.push(successor);
+                }
+            }
+        }
+
+        let mut reported = HashSet::new();
+        for (block, state) in self.blocks.iter().zip(states) {
+            let Some(mut state) = state else {
+                continue;
+            };
+            for event in &block.events {
+                match event {
+                    Event::Read(symbol, position)
+                        if declared.contains(symbol) && !state.always.contains(symbol) && reported.insert((*symbol, "read")) =>
+                    {
+                        let name = &self.program.symbol(*symbol).name;
+                        report.errors.push(SemanticError::UninitializedVariable {
+                            name: name.clone(),
+                            position: position.clone(),
+                            context: format!("'{}' is declared without a value, and is not assigned on every path to this read", name),
+                            suggestion: format!("Give '{}' a value where it is declared, or assign it on every path before it is read", name),
+                            example: UNINITIALIZED_EXAMPLE.to_string(),
+                        });
+                    }
+                    Event::Assign(symbol, position) if declared.contains(symbol) && state.maybe.contains(symbol) => {
+                        let info = self.program.symbol(*symbol);
+                        if info.kind == (SymbolKind::Variable { mutable: false }) && reported.insert((*symbol, "assignment")) {
+                            report.errors.push(SemanticError::ImmutableAssignment {
+                                name: info.name.clone(),
+                                declared_at: info.position.clone(),
+                                position: position.clone(),
+                                context: format!("'{}' is declared with 'biarkan' without a value, so it may be assigned once, and a path to here already assigned it", info.name),
+                                suggestion: format!("Declare it with 'mut': 'biarkan mut {}'", info.name),
+                                example: "   biarkan mut jumlah = 0\n   jumlah = jumlah + 1".to_string(),
+                            });
+                        }
+                    }
+                    _ => {}
+                }
+                state.apply(event);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+    use crate::semantic_analyzer::SemanticAnalyzer;
+
+    fn warnings(source: &str) -> Vec<SemanticWarning> {
+        let ast = Parser::new(source).parse_program().into_result().unwrap();
+        let mut analyzer = SemanticAnalyzer::new();
+        analyzer.analyze(&ast).unwrap();
+        analyzer.warnings().to_vec()
+    }
+
+    fn rejection(source: &str) -> SemanticError {
+        let ast = Parser::new(source).parse_program().into_result().unwrap();
+        SemanticAnalyzer::new().analyze(&ast).unwrap_err()
+    }
+
+    #[test]
+    fn test_variables_are_assigned_on_every_path_before_reads() {
+        assert!(warnings("buat x\njika benar:\n    x = 1\nsebaliknya:\n    x = 2\ntampilkan x\n").is_empty());
+        let error = rejection("buat x\njika benar:\n    x = 1\ntampilkan x\n");
+        assert!(matches!(error, SemanticError::UninitializedVariable { ref name, ref position, .. } if name == "x" && position.line == 4), "{}", error);
+
+        // A loop may run no times, and a clause of `coba` may run before any statement of the block
+        assert_eq!(rejection("buat x\nselama salah:\n    x = 1\ntampilkan x\n").code(), "K0205");
+        assert_eq!(rejection("buat x\ncoba:\n    x = 1\ntangkap e:\n    tampilkan x\n").code(), "K0205");
+        assert!(warnings("buat x\ncoba:\n    x = 1\ntangkap e:\n    x = 0\nakhirnya:\n    tampilkan \"-\"\ntampilkan x\n").is_empty());
+        // A branch that leaves does not reach the read
+        assert!(warnings("fungsi f(a):\n    buat x\n    jika a:\n        kembalikan 0\n    sebaliknya:\n        x = 1\n    kembalikan x\n").is_empty());
+        // `pecah` leaves the loop before the variable is assigned
+        assert_eq!(rejection("buat x\nselama benar:\n    jika salah:\n        pecah\n    x = 1\n    pecah\ntampilkan x\n").code(), "K0205");
+        // `selama benar` and `ulangi ... selama benar` are only left by `pecah`
+        assert!(warnings("fungsi f(): int:\n    buat y\n    selama benar:\n        y = 1\n        pecah\n    kembalikan y\ntampilkan f()\n").is_empty());
+        assert!(warnings("buat y\nulangi:\n    y = 2\n    pecah\nselama benar\ntampilkan y\n").is_empty());
+    }
+
+    #[test]
+    fn test_biarkan_without_value_is_assigned_once() {
+        assert!(warnings("biarkan x\njika benar:\n    x = 1\nsebaliknya:\n    x = 2\ntampilkan x\n").is_empty());
+        let error = rejection("biarkan x\nx = 1\nx = 2\ntampilkan x\n");
+        assert!(matches!(error, SemanticError::ImmutableAssignment { ref position, .. } if position.line == 3), "{}", error);
+        assert_eq!(rejection("biarkan x\nselama benar:\n    x = 1\ntampilkan x\n").code(), "K0212");
+    }
+
+    #[test]
+    fn test_unused_variables_and_parameters_are_reported() {
+        let found = warnings("buat a = 1\nbuat _b = 2\nbuat c = 3\ntampilkan c\nfungsi f(x, y):\n    kembalikan x\nf(1, 2)\n");
+        let names: Vec<(&str, &str)> = found.iter()
+            .map(|warning| match warning {
+                SemanticWarning::UnusedVariable { name, .. } => ("variable", name.as_str()),
+                SemanticWarning::UnusedParameter { name, .. } => ("parameter", name.as_str()),
+                SemanticWarning::UnreachableCode { .. } => ("unreachable", ""),
+            })
+            .collect();
+        assert_eq!(names, [("variable", "a"), ("parameter", "y")]);
+        // Reads in closures, lambdas and methods count
+        assert!(warnings("buat n = 2\nbuat kali = (x: int) => x * n\ntampilkan kali(3)\n").is_empty());
+    }
+
+    #[test]
+    fn test_code_after_leaving_a_block_is_unreachable() {
+        let found = warnings("fungsi f(x):\n    kembalikan x\n    tampilkan x\n    tampilkan 2\ntampilkan f(1)\n");
+        let [SemanticWarning::UnreachableCode { position, span: Some(span), .. }] = found.as_slice() else {
+            panic!("Expected one unreachable code warning, got {:?}", found);
+        };
+        assert_eq!(position.line, 3);
+        assert!(span.len() > "tampilkan x".len());
+
+        assert!(warnings("buat i = 0\nselama i < 3:\n    i = i + 1\n    jika i == 2:\n        lanjut\n    tampilkan i\n").is_empty());
+        let found = warnings("untuk i dari 0 sampai 3:\n    pecah\n    tampilkan i\n");
+        assert!(matches!(found.as_slice(), [SemanticWarning::UnreachableCode { .. }]), "{:?}", found);
+    }
+}
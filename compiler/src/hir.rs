@@ -0,0 +1,776 @@
+//! Typed high-level IR of a KODEON program
+//!
+//! The semantic analyzer lowers the parser's AST into this form: every name is bound to a
+//! symbol and every expression carries its inferred type, so later stages never look names
+//! up or guess types again.
+
+use crate::lexer::{Position, Span};
+use crate::parser::{BinaryOperator, LiteralPattern, UnaryOperator};
+use crate::types::Type;
+
+/// Index of a symbol in `Program::symbols`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SymbolId(pub usize);
+
+/// What a symbol names
+#[derive(Debug, Clone, PartialEq)]
+pub enum SymbolKind {
+    Variable { mutable: bool },
+    Constant, // `konstan`; its reads are replaced by its value
+    Parameter,
+    Function,
+    Class,
+    Interface,
+    Enum,
+    Variant { enumeration: SymbolId, index: usize }, // builds a value of the enum; its type is the function taking its fields
+    Field { class: SymbolId },
+    Method { class: SymbolId, is_static: bool },
+    Builtin, // provided by the runtime
+    Import,  // module brought in with `impor`
+}
+
+/// How lambdas capture a variable of an enclosing function
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Capture {
+    ByValue,     // each closure copies the value the variable has when the closure is created
+    ByReference, // the variable is assigned after its declaration, so its closures share it with its function
+}
+
+/// Declaration a symbol id refers to
+#[derive(Debug, Clone)]
+pub struct SymbolInfo {
+    pub name: String,
+    pub kind: SymbolKind,
+    pub ty: Type, // type of the value; a class has the type of its instances
+    pub position: Position, // Position where the symbol was declared
+    pub captured: Option<Capture>, // how lambdas capture the variable, if any does
+}
+
+/// Analyzed program
+#[derive(Debug, Clone)]
+pub struct Program {
+    pub body: Vec<Statement>,
+    pub symbols: Vec<SymbolInfo>, // indexed by `SymbolId`
+}
+
+/// Statement with the position of its source
+#[derive(Debug, Clone)]
+pub struct Statement {
+    pub kind: StatementKind,
+    pub position: Position,
+    pub span: Span,
+}
+
+/// Function definition; its type is the type of its symbol
+#[derive(Debug, Clone)]
+pub struct Function {
+    pub symbol: SymbolId,
+    pub type_parameters: Vec<String>, // names of the types a generic function is specialized for
+    pub parameters: Vec<SymbolId>,
+    pub body: Vec<Statement>,
+    pub is_async: bool,
+    pub is_static: bool,
+    pub variadic: bool, // last parameter collects the remaining arguments
+}
+
+/// Lambda: a function without a name, and the variables of enclosing functions its body uses
+#[derive(Debug, Clone)]
+pub struct Lambda {
+    pub parameters: Vec<SymbolId>,
+    pub body: Vec<Statement>,
+    pub captures: Vec<SymbolId>, // in order of first use; `SymbolInfo::captured` says how
+    pub captures_self: bool, // uses `ini` of the enclosing method
+}
+
+/// Stage of a collection pipeline, with the type of the value it produces
+#[derive(Debug, Clone)]
+pub struct Stage {
+    pub kind: StageKind,
+    pub ty: Type, // a list, except for a reduction
+}
+
+/// What a pipeline stage does with the list it receives
+#[derive(Debug, Clone)]
+pub enum StageKind {
+    Filter(Expression), // predicate
+    Map(Expression),
+    Take(Expression), // number of elements
+    Skip(Expression),
+    Sort(Option<Expression>), // comparator, which returns a negative number when its first argument comes first
+    Reduce { // `kurangi(f, awal)`, or `lipat(awal, f)`; without `awal`, the first element starts the reduction
+        function: Expression,
+        initial: Option<Expression>,
+    },
+}
+
+impl StageKind {
+    /// Arguments of the stage
+    pub fn arguments_mut(&mut self) -> Vec<&mut Expression> {
+        match self {
+            StageKind::Filter(argument) | StageKind::Map(argument) | StageKind::Take(argument) | StageKind::Skip(argument) => vec![argument],
+            StageKind::Sort(comparator) => comparator.iter_mut().collect(),
+            StageKind::Reduce { function, initial } => std::iter::once(function).chain(initial.as_mut()).collect(),
+        }
+    }
+}
+
+/// Class definition; fields are the `Let` statements of its body and methods its functions
+#[derive(Debug, Clone)]
+pub struct Class {
+    pub symbol: SymbolId,
+    pub parent: Option<SymbolId>,
+    pub interfaces: Vec<SymbolId>, // interfaces the class implements
+    pub type_parameters: Vec<String>, // names of the types a generic class is specialized for
+    pub body: Vec<Statement>,
+}
+
+/// Interface definition: the methods a class implementing it provides, in declaration order
+#[derive(Debug, Clone)]
+pub struct Interface {
+    pub symbol: SymbolId,
+    pub methods: Vec<(String, Type)>, // name and function type of each method, without the instance
+}
+
+/// Enum definition: the variants its values take, in declaration order
+#[derive(Debug, Clone)]
+pub struct Enum {
+    pub symbol: SymbolId,
+    pub type_parameters: Vec<String>, // names of the types a generic enum is specialized for
+    pub variants: Vec<Variant>,
+}
+
+/// Variant of an enum
+#[derive(Debug, Clone)]
+pub struct Variant {
+    pub symbol: SymbolId,
+    pub fields: Vec<Type>, // in terms of the type parameters of the enum
+}
+
+/// Clause of a `coba` statement, catching the errors that are instances of `class`, or every error
+#[derive(Debug, Clone)]
+pub struct Catch {
+    pub variable: Option<SymbolId>,
+    pub class: Option<String>,
+    pub body: Vec<Statement>,
+    pub position: Position,
+}
+
+/// Case of a `ketika` statement
+#[derive(Debug, Clone)]
+pub struct MatchCase {
+    pub pattern: Pattern,
+    pub guard: Option<Expression>, // `jika` condition, checked once the pattern matched
+    pub body: Vec<Statement>,
+    pub position: Position,
+}
+
+/// Pattern with the type of the values it matches
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    pub kind: PatternKind,
+    pub ty: Type,
+    pub position: Position,
+}
+
+/// Kinds of patterns
+#[derive(Debug, Clone)]
+pub enum PatternKind {
+    Wildcard,
+    Binding(SymbolId), // variable the matched value is bound to
+    Literal(LiteralPattern),
+    Range {
+        start: i64,
+        end: i64, // included
+    },
+    Tuple(Vec<Pattern>),
+    Variant {
+        enumeration: SymbolId,
+        index: usize, // position of the variant in its enum
+        fields: Vec<Pattern>,
+    },
+}
+
+/// Kinds of statements
+#[derive(Debug, Clone)]
+pub enum StatementKind {
+    Let {
+        symbol: SymbolId,
+        value: Option<Expression>, // None for `buat x` without initializer
+    },
+    Const {
+        symbol: SymbolId,
+        value: Constant, // computed when compiling
+    },
+    Assign {
+        symbol: SymbolId,
+        value: Expression,
+    },
+    Expression(Expression),
+    Return(Option<Expression>),
+    Throw(Expression),
+    If {
+        condition: Expression,
+        then_block: Vec<Statement>,
+        else_block: Option<Vec<Statement>>,
+    },
+    While {
+        condition: Expression,
+        body: Vec<Statement>,
+    },
+    DoWhile {
+        body: Vec<Statement>,
+        condition: Expression,
+    },
+    For {
+        variable: SymbolId,
+        start: Expression,
+        end: Expression,
+        descending: bool,
+        body: Vec<Statement>,
+    },
+    ForEach {
+        variable: SymbolId,
+        iterable: Expression,
+        body: Vec<Statement>,
+    },
+    Function(Function),
+    Class(Class),
+    Interface(Interface),
+    Enum(Enum),
+    TryCatch {
+        try_block: Vec<Statement>,
+        catches: Vec<Catch>,
+        finally_block: Option<Vec<Statement>>, // runs however the statement is left
+    },
+    Break,
+    Continue,
+    Import {
+        module: String,
+        symbol: SymbolId,
+    },
+    Export(Vec<String>),
+    MemberAssign {
+        object: Expression,
+        field: String,
+        value: Expression,
+    },
+    IndexAssign {
+        object: Expression,
+        index: Expression,
+        value: Expression,
+    },
+    When {
+        value: Expression,
+        cases: Vec<MatchCase>,
+        else_case: Option<Vec<Statement>>, // runs when no case matches
+    },
+    Go(Vec<Statement>),
+    ChannelSend {
+        channel: Expression,
+        value: Expression,
+    },
+    ChannelReceive {
+        channel: Expression,
+        variable: SymbolId,
+    },
+    MutexLock(Expression),
+    MutexUnlock(Expression),
+    WaitCondition {
+        condition: Expression,
+        mutex: Expression,
+    },
+    SignalCondition(Expression),
+    BroadcastCondition(Expression),
+    AtomicStore {
+        address: Expression,
+        value: Expression,
+    },
+    // Quantum and neural network statements, analyzed but not compiled yet
+    Unsupported {
+        construct: &'static str,
+        symbol: Option<SymbolId>, // name the statement declares, if any
+        operands: Vec<Expression>,
+        body: Vec<Statement>,
+    },
+}
+
+/// Expression with its type and the position of its source
+#[derive(Debug, Clone)]
+pub struct Expression {
+    pub kind: ExpressionKind,
+    pub ty: Type,
+    pub position: Position,
+    pub span: Span,
+}
+
+/// What a call calls
+#[derive(Debug, Clone, PartialEq)]
+pub enum Callee {
+    Symbol(SymbolId),
+    External(String), // resolved against the runtime library or an open import when linking
+}
+
+/// Kinds of expressions
+#[derive(Debug, Clone)]
+pub enum ExpressionKind {
+    Integer(i64),
+    Float(f64),
+    String(String),
+    Boolean(bool),
+    Null,
+    Interpolation(Vec<Expression>), // "Halo {nama}", as the text pieces and values to join
+    SelfRef,
+    Variable(SymbolId),
+    External(String), // name from an `impor` without alias
+    Binary {
+        left: Box<Expression>,
+        operator: BinaryOperator,
+        right: Box<Expression>,
+    },
+    Unary {
+        operator: UnaryOperator,
+        operand: Box<Expression>,
+    },
+    Call {
+        callee: Callee,
+        arguments: Vec<Expression>,
+        type_arguments: Vec<Type>, // types inferred for the type parameters of a generic function, in order
+    },
+    Construct { // `Kelas(...)` or `Kelas.baru(...)`
+        class: SymbolId,
+        arguments: Vec<Expression>,
+    },
+    MethodCall {
+        object: Box<Expression>,
+        method: String,
+        arguments: Vec<Expression>,
+    },
+    SuperCall { // `super.metode(...)`: calls the method `class` defines on `ini`, without dispatch
+        class: SymbolId,
+        method: String,
+        arguments: Vec<Expression>,
+    },
+    Member {
+        object: Box<Expression>,
+        field: String,
+    },
+    Index {
+        object: Box<Expression>,
+        index: Box<Expression>,
+    },
+    Spread(Box<Expression>),
+    List(Vec<Expression>),
+    Tuple(Vec<Expression>),
+    Variant { // value of variant `index` of an enum, built from its fields
+        enumeration: SymbolId,
+        index: usize,
+        arguments: Vec<Expression>,
+    },
+    Map(Vec<(String, Expression)>),
+    Comprehension {
+        element: Box<Expression>,
+        variable: SymbolId,
+        iterable: Box<Expression>,
+        condition: Option<Box<Expression>>,
+    },
+    Range {
+        start: Box<Expression>,
+        end: Box<Expression>,
+        inclusive: bool,
+    },
+    Optional(Box<Expression>),
+    Unwrap(Box<Expression>), // value of an optional variable where a check proved it is not `nol`
+    Elvis { // `nama ?: "anonim"`
+        value: Box<Expression>,
+        default: Box<Expression>, // only evaluated when the value is `nol`
+    },
+    OptionalChain { // `pengguna?.nama`: `access` reads `binding`, which holds the object when it is not `nol`
+        object: Box<Expression>,
+        binding: SymbolId,
+        access: Box<Expression>,
+    },
+    Propagate { // `baca(berkas)?`: the value of an optional or a `Sukses`, or else returns `early`
+        value: Box<Expression>,
+        error: Option<SymbolId>, // variable the error of a `Gagal` is bound to
+        early: Box<Expression>, // `nol`, or `Gagal(error)`, of the function's return type
+    },
+    Await(Box<Expression>),
+    Yield(Box<Expression>),
+    ChannelReceive(Box<Expression>),
+    MakeChannel,
+    Lambda(Lambda), // its type is the function type of the lambda
+    Pipeline { // `angka |> saring(...) |> petakan(...)`
+        source: Box<Expression>, // a list
+        stages: Vec<Stage>,
+    },
+    // Pointers, atomics and quantum and neural network expressions, analyzed but not compiled yet
+    Unsupported {
+        construct: &'static str,
+        operands: Vec<Expression>,
+    },
+}
+
+impl Program {
+    /// Declaration of a symbol
+    pub fn symbol(&self, id: SymbolId) -> &SymbolInfo {
+        &self.symbols[id.0]
+    }
+
+    /// Apply `visit` to the type of every symbol and expression
+    pub fn visit_types_mut(&mut self, visit: &mut dyn FnMut(&mut Type)) {
+        for symbol in &mut self.symbols {
+            visit(&mut symbol.ty);
+        }
+        visit_statements(&mut self.body, visit);
+    }
+}
+
+fn visit_statements(statements: &mut [Statement], visit: &mut dyn FnMut(&mut Type)) {
+    for statement in statements {
+        statement.visit_types_mut(visit);
+    }
+}
+
+impl Statement {
+    /// Apply `visit` to the type of every expression in the statement
+    pub fn visit_types_mut(&mut self, visit: &mut dyn FnMut(&mut Type)) {
+        match &mut self.kind {
+            StatementKind::Let { value, .. } | StatementKind::Return(value) => {
+                if let Some(value) = value {
+                    value.visit_types_mut(visit);
+                }
+            }
+            StatementKind::Assign { value: expression, .. }
+            | StatementKind::Expression(expression)
+            | StatementKind::Throw(expression)
+            | StatementKind::ChannelReceive { channel: expression, .. }
+            | StatementKind::MutexLock(expression)
+            | StatementKind::MutexUnlock(expression)
+            | StatementKind::SignalCondition(expression)
+            | StatementKind::BroadcastCondition(expression) => expression.visit_types_mut(visit),
+            StatementKind::If { condition, then_block, else_block } => {
+                condition.visit_types_mut(visit);
+                visit_statements(then_block, visit);
+                if let Some(else_block) = else_block {
+                    visit_statements(else_block, visit);
+                }
+            }
+            StatementKind::While { condition, body } | StatementKind::DoWhile { body, condition } => {
+                condition.visit_types_mut(visit);
+                visit_statements(body, visit);
+            }
+            StatementKind::For { start, end, body, .. } => {
+                start.visit_types_mut(visit);
+                end.visit_types_mut(visit);
+                visit_statements(body, visit);
+            }
+            StatementKind::ForEach { iterable, body, .. } => {
+                iterable.visit_types_mut(visit);
+                visit_statements(body, visit);
+            }
+            StatementKind::Function(Function { body, .. })
+            | StatementKind::Class(Class { body, .. })
+            | StatementKind::Go(body) => visit_statements(body, visit),
+            StatementKind::TryCatch { try_block, catches, finally_block } => {
+                visit_statements(try_block, visit);
+                for catch in catches {
+                    visit_statements(&mut catch.body, visit);
+                }
+                if let Some(finally_block) = finally_block {
+                    visit_statements(finally_block, visit);
+                }
+            }
+            StatementKind::MemberAssign { object, value, .. } => {
+                object.visit_types_mut(visit);
+                value.visit_types_mut(visit);
+            }
+            StatementKind::IndexAssign { object, index, value } => {
+                object.visit_types_mut(visit);
+                index.visit_types_mut(visit);
+                value.visit_types_mut(visit);
+            }
+            StatementKind::When { value, cases, else_case } => {
+                value.visit_types_mut(visit);
+                for case in cases {
+                    case.pattern.visit_types_mut(visit);
+                    if let Some(guard) = &mut case.guard {
+                        guard.visit_types_mut(visit);
+                    }
+                    visit_statements(&mut case.body, visit);
+                }
+                if let Some(else_case) = else_case {
+                    visit_statements(else_case, visit);
+                }
+            }
+            StatementKind::ChannelSend { channel: first, value: second }
+            | StatementKind::WaitCondition { condition: first, mutex: second }
+            | StatementKind::AtomicStore { address: first, value: second } => {
+                first.visit_types_mut(visit);
+                second.visit_types_mut(visit);
+            }
+            StatementKind::Unsupported { operands, body, .. } => {
+                for operand in operands {
+                    operand.visit_types_mut(visit);
+                }
+                visit_statements(body, visit);
+            }
+            StatementKind::Interface(Interface { methods, .. }) => {
+                for (_, ty) in methods {
+                    visit(ty);
+                }
+            }
+            StatementKind::Enum(Enum { variants, .. }) => {
+                for ty in variants.iter_mut().flat_map(|variant| &mut variant.fields) {
+                    visit(ty);
+                }
+            }
+            StatementKind::Const { .. }
+            | StatementKind::Break
+            | StatementKind::Continue
+            | StatementKind::Import { .. }
+            | StatementKind::Export(_) => {}
+        }
+    }
+}
+
+impl Pattern {
+    /// Apply `visit` to the type of the pattern and of every pattern inside it
+    pub fn visit_types_mut(&mut self, visit: &mut dyn FnMut(&mut Type)) {
+        visit(&mut self.ty);
+        if let PatternKind::Tuple(patterns) | PatternKind::Variant { fields: patterns, .. } = &mut self.kind {
+            for pattern in patterns {
+                pattern.visit_types_mut(visit);
+            }
+        }
+    }
+}
+
+impl Expression {
+    /// Apply `visit` to the type of the expression and of every expression inside it
+    pub fn visit_types_mut(&mut self, visit: &mut dyn FnMut(&mut Type)) {
+        visit(&mut self.ty);
+        match &mut self.kind {
+            ExpressionKind::Interpolation(expressions)
+            | ExpressionKind::List(expressions)
+            | ExpressionKind::Tuple(expressions)
+            | ExpressionKind::Variant { arguments: expressions, .. }
+            | ExpressionKind::Construct { arguments: expressions, .. }
+            | ExpressionKind::SuperCall { arguments: expressions, .. }
+            | ExpressionKind::Unsupported { operands: expressions, .. } => {
+                for expression in expressions {
+                    expression.visit_types_mut(visit);
+                }
+            }
+            ExpressionKind::Call { arguments, type_arguments, .. } => {
+                for argument in arguments {
+                    argument.visit_types_mut(visit);
+                }
+                for ty in type_arguments {
+                    visit(ty);
+                }
+            }
+            ExpressionKind::Map(fields) => {
+                for (_, value) in fields {
+                    value.visit_types_mut(visit);
+                }
+            }
+            ExpressionKind::MethodCall { object, arguments, .. } => {
+                object.visit_types_mut(visit);
+                for argument in arguments {
+                    argument.visit_types_mut(visit);
+                }
+            }
+            ExpressionKind::Binary { left: first, right: second, .. }
+            | ExpressionKind::Index { object: first, index: second }
+            | ExpressionKind::Elvis { value: first, default: second }
+            | ExpressionKind::OptionalChain { object: first, access: second, .. }
+            | ExpressionKind::Propagate { value: first, early: second, .. }
+            | ExpressionKind::Range { start: first, end: second, .. } => {
+                first.visit_types_mut(visit);
+                second.visit_types_mut(visit);
+            }
+            ExpressionKind::Unary { operand: inner, .. }
+            | ExpressionKind::Member { object: inner, .. }
+            | ExpressionKind::Spread(inner)
+            | ExpressionKind::Optional(inner)
+            | ExpressionKind::Unwrap(inner)
+            | ExpressionKind::Await(inner)
+            | ExpressionKind::Yield(inner)
+            | ExpressionKind::ChannelReceive(inner) => inner.visit_types_mut(visit),
+            ExpressionKind::Comprehension { element, iterable, condition, .. } => {
+                element.visit_types_mut(visit);
+                iterable.visit_types_mut(visit);
+                if let Some(condition) = condition {
+                    condition.visit_types_mut(visit);
+                }
+            }
+            ExpressionKind::Lambda(Lambda { body, .. }) => visit_statements(body, visit),
+            ExpressionKind::Pipeline { source, stages } => {
+                source.visit_types_mut(visit);
+                for stage in stages {
+                    visit(&mut stage.ty);
+                    for argument in stage.kind.arguments_mut() {
+                        argument.visit_types_mut(visit);
+                    }
+                }
+            }
+            ExpressionKind::Integer(_)
+            | ExpressionKind::Float(_)
+            | ExpressionKind::String(_)
+            | ExpressionKind::Boolean(_)
+            | ExpressionKind::Null
+            | ExpressionKind::SelfRef
+            | ExpressionKind::Variable(_)
+            | ExpressionKind::External(_)
+            | ExpressionKind::MakeChannel => {}
+        }
+    }
+}
+
+/// Value of a `konstan`, computed when compiling
+#[derive(Debug, Clone, PartialEq)]
+pub enum Constant {
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    String(String),
+}
+
+impl Constant {
+    /// Expression of the value
+    pub fn kind(&self) -> ExpressionKind {
+        match self {
+            Constant::Integer(value) => ExpressionKind::Integer(*value),
+            Constant::Float(value) => ExpressionKind::Float(*value),
+            Constant::Boolean(value) => ExpressionKind::Boolean(*value),
+            Constant::String(value) => ExpressionKind::String(value.clone()),
+        }
+    }
+
+    fn as_float(&self) -> Option<f64> {
+        match self {
+            Constant::Integer(value) => Some(*value as f64),
+            Constant::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+/// Part of an expression that has no value when compiling, and why
+#[derive(Debug, Clone, PartialEq)]
+pub struct NotConstant {
+    pub position: Position,
+    pub reason: String,
+}
+
+impl Expression {
+    /// Compute the value of the expression when compiling; reads of other constants were already replaced
+    /// by their values
+    pub fn evaluate(&self) -> Result<Constant, NotConstant> {
+        let fail = |reason: &str| NotConstant { position: self.position.clone(), reason: reason.to_string() };
+        match &self.kind {
+            ExpressionKind::Integer(value) => Ok(Constant::Integer(*value)),
+            ExpressionKind::Float(value) => Ok(Constant::Float(*value)),
+            ExpressionKind::Boolean(value) => Ok(Constant::Boolean(*value)),
+            ExpressionKind::String(value) => Ok(Constant::String(value.clone())),
+            ExpressionKind::Unary { operator, operand } => match (operator, operand.evaluate()?) {
+                (UnaryOperator::Negate | UnaryOperator::Balik, Constant::Integer(value)) => {
+                    value.checked_neg().map(Constant::Integer).ok_or_else(|| fail("the result overflows 'int'"))
+                }
+                (UnaryOperator::Negate | UnaryOperator::Balik, Constant::Float(value)) => Ok(Constant::Float(-value)),
+                (UnaryOperator::Not | UnaryOperator::Tidak, Constant::Boolean(value)) => Ok(Constant::Boolean(!value)),
+                (UnaryOperator::BitNot, Constant::Integer(value)) => Ok(Constant::Integer(!value)),
+                _ => Err(fail("the operator is not computed when compiling")),
+            },
+            ExpressionKind::Binary { left, operator, right } => {
+                let (left, right) = (left.evaluate()?, right.evaluate()?);
+                evaluate_binary(operator, &left, &right).map_err(|reason| fail(&reason))
+            }
+            ExpressionKind::Variable(_) => Err(fail("a variable only has a value when the program runs")),
+            ExpressionKind::Call { .. } | ExpressionKind::MethodCall { .. } => {
+                Err(fail("calls only run when the program runs"))
+            }
+            _ => Err(fail("only literals, other constants and operators on them are computed when compiling")),
+        }
+    }
+}
+
+/// Apply a binary operator to constants
+fn evaluate_binary(operator: &BinaryOperator, left: &Constant, right: &Constant) -> Result<Constant, String> {
+    use Constant::*;
+    let overflow = || "the result overflows 'int'".to_string();
+    let integers = |operation: fn(i64, i64) -> Option<i64>| match (left, right) {
+        (Integer(left), Integer(right)) => Some(operation(*left, *right).map(Integer).ok_or_else(overflow)),
+        _ => None,
+    };
+    let floats = |operation: fn(f64, f64) -> f64| Some(Float(operation(left.as_float()?, right.as_float()?)));
+    let unsupported = || format!("'{:?}' does not apply to {:?} and {:?}", operator, left, right);
+
+    match operator {
+        BinaryOperator::Add | BinaryOperator::Tambah => match (left, right) {
+            (String(left), String(right)) => Ok(String(format!("{}{}", left, right))),
+            _ => integers(i64::checked_add).unwrap_or_else(|| floats(|a, b| a + b).ok_or_else(unsupported)),
+        },
+        BinaryOperator::Subtract | BinaryOperator::Kurang => {
+            integers(i64::checked_sub).unwrap_or_else(|| floats(|a, b| a - b).ok_or_else(unsupported))
+        }
+        BinaryOperator::Multiply | BinaryOperator::Kali => match (left, right) {
+            (String(text), Integer(count)) | (Integer(count), String(text)) => {
+                Ok(String(text.repeat(usize::try_from(*count).unwrap_or(0))))
+            }
+            _ => integers(i64::checked_mul).unwrap_or_else(|| floats(|a, b| a * b).ok_or_else(unsupported)),
+        },
+        BinaryOperator::Divide | BinaryOperator::Bagi | BinaryOperator::Modulo => {
+            if matches!(right, Integer(0)) {
+                return Err("it divides by zero".to_string());
+            }
+            let divide = matches!(operator, BinaryOperator::Divide | BinaryOperator::Bagi);
+            let integer = integers(if divide { i64::checked_div } else { i64::checked_rem });
+            integer.unwrap_or_else(|| floats(if divide { |a, b| a / b } else { |a, b| a % b }).ok_or_else(unsupported))
+        }
+        BinaryOperator::Power => match (left, right) {
+            (Integer(base), Integer(exponent)) => u32::try_from(*exponent).ok()
+                .and_then(|exponent| base.checked_pow(exponent))
+                .map(Integer)
+                .ok_or_else(overflow),
+            _ => floats(f64::powf).ok_or_else(unsupported),
+        },
+        BinaryOperator::Equal | BinaryOperator::SamaDengan | BinaryOperator::NotEqual => {
+            let equal = match (left.as_float(), right.as_float()) {
+                (Some(left), Some(right)) => left == right,
+                _ => left == right,
+            };
+            Ok(Boolean(equal == (*operator != BinaryOperator::NotEqual)))
+        }
+        BinaryOperator::Less | BinaryOperator::Greater | BinaryOperator::LessEqual | BinaryOperator::GreaterEqual
+        | BinaryOperator::KurangDari | BinaryOperator::LebihDari => {
+            let order = match (left, right) {
+                (String(left), String(right)) => left.partial_cmp(right),
+                _ => left.as_float().zip(right.as_float()).and_then(|(left, right)| left.partial_cmp(&right)),
+            };
+            let order = order.ok_or_else(unsupported)?;
+            Ok(Boolean(match operator {
+                BinaryOperator::Less | BinaryOperator::KurangDari => order.is_lt(),
+                BinaryOperator::Greater | BinaryOperator::LebihDari => order.is_gt(),
+                BinaryOperator::LessEqual => order.is_le(),
+                _ => order.is_ge(),
+            }))
+        }
+        BinaryOperator::And | BinaryOperator::Or => match (left, right) {
+            (Boolean(left), Boolean(right)) => {
+                Ok(Boolean(if *operator == BinaryOperator::And { *left && *right } else { *left || *right }))
+            }
+            _ => Err(unsupported()),
+        },
+        BinaryOperator::BitAnd => integers(|a, b| Some(a & b)).unwrap_or_else(|| Err(unsupported())),
+        BinaryOperator::BitOr => integers(|a, b| Some(a | b)).unwrap_or_else(|| Err(unsupported())),
+        BinaryOperator::BitXor => integers(|a, b| Some(a ^ b)).unwrap_or_else(|| Err(unsupported())),
+        BinaryOperator::LeftShift => integers(|a, b| u32::try_from(b).ok().and_then(|b| a.checked_shl(b)))
+            .unwrap_or_else(|| Err(unsupported())),
+        BinaryOperator::RightShift => integers(|a, b| u32::try_from(b).ok().and_then(|b| a.checked_shr(b)))
+            .unwrap_or_else(|| Err(unsupported())),
+        BinaryOperator::In | BinaryOperator::Is | BinaryOperator::Range | BinaryOperator::Assign => {
+            Err("the operator is not computed when compiling".to_string())
+        }
+    }
+}
@@ -1,7 +1,11 @@
 //! Intermediate Representation (IR) for the KODEON programming language
 
-use std::collections::HashMap;
-use crate::parser::{ASTNode, Statement, BinaryOperator, UnaryOperator, ParseError};
+use std::collections::{HashMap, HashSet};
+use crate::diagnostics::{Diagnostic, SourceFile};
+use crate::error_messages::{ErrorMessages, Language};
+use crate::hir::{self, Capture, SymbolId, SymbolKind};
+use crate::lexer::Position;
+use crate::parser::{BinaryOperator, LiteralPattern, UnaryOperator};
 use crate::module_resolver::ModuleResolver;
 
 /// Enhanced debug information for source code locations
@@ -13,6 +17,7 @@ pub struct DebugInfo {
     pub scope: Option<String>,        // Variable scope information
     pub function: Option<String>,     // Function name
     pub module: Option<String>,       // Module name
+    pub type_name: Option<String>,    // Source type of the value the item defines, e.g. `list<int>`
     pub timestamp: std::time::SystemTime, // When this debug info was created
 }
 
@@ -26,6 +31,7 @@ impl DebugInfo {
             scope: None,
             function: None,
             module: None,
+            type_name: None,
             timestamp: std::time::SystemTime::now(),
         }
     }
@@ -39,6 +45,7 @@ impl DebugInfo {
             scope: Some(scope),
             function: None,
             module: None,
+            type_name: None,
             timestamp: std::time::SystemTime::now(),
         }
     }
@@ -54,6 +61,12 @@ impl DebugInfo {
         self.module = Some(module);
         self
     }
+
+    /// Set the source type of the value the item defines
+    pub fn with_type_name(mut self, type_name: String) -> Self {
+        self.type_name = Some(type_name);
+        self
+    }
 }
 
 /// IR module containing functions and global variables
@@ -61,6 +74,10 @@ impl DebugInfo {
 pub struct IRModule {
     pub functions: Vec<Function>,
     pub global_vars: Vec<GlobalVariable>,
+    pub classes: Vec<ClassLayout>, // parents before the classes extending them
+    pub interfaces: Vec<InterfaceLayout>,
+    pub enums: Vec<EnumLayout>,
+    pub closures: Vec<ClosureLayout>,
     pub debug_info: Option<DebugInfo>, // Module-level debug info
     pub module_name: String,           // Name of the module
 }
@@ -71,6 +88,10 @@ impl IRModule {
         IRModule {
             functions: Vec::new(),
             global_vars: Vec::new(),
+            classes: Vec::new(),
+            interfaces: Vec::new(),
+            enums: Vec::new(),
+            closures: Vec::new(),
             debug_info: None,
             module_name,
         }
@@ -81,6 +102,80 @@ impl IRModule {
         self.debug_info = Some(debug_info);
         self
     }
+
+    /// Check the blocks of every function, as `Function::verify` does
+    pub fn verify(&self) -> Result<(), String> {
+        self.functions.iter().try_for_each(Function::verify)
+    }
+}
+
+/// Fields and method table of the instances of a class
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassLayout {
+    pub name: String,
+    pub parent: Option<String>,
+    pub fields: Vec<(String, Type)>, // inherited fields first, so an instance can stand for one of its parent
+    pub methods: Vec<(String, String)>, // virtual table: each method and the function run for it, inherited slots first
+    pub interfaces: Vec<(String, Vec<String>)>, // interface tables: each interface implemented, inherited ones included, and the functions run for its methods
+}
+
+impl ClassLayout {
+    /// Index of field `name` in an instance
+    pub fn field_index(&self, name: &str) -> Option<usize> {
+        self.fields.iter().position(|(field, _)| field == name)
+    }
+
+    /// Slot of method `name` in the virtual table
+    pub fn method_slot(&self, name: &str) -> Option<usize> {
+        self.methods.iter().position(|(method, _)| method == name)
+    }
+
+    /// Functions run for the methods of interface `name`, in the order of its slots
+    pub fn interface_table(&self, name: &str) -> Option<&[String]> {
+        self.interfaces.iter().find(|(interface, _)| interface == name).map(|(_, functions)| functions.as_slice())
+    }
+}
+
+/// Methods of an interface; values of the interface are instances of the classes implementing it
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterfaceLayout {
+    pub name: String,
+    pub methods: Vec<(String, Type)>, // each method and its function type without the instance, in slot order
+}
+
+impl InterfaceLayout {
+    /// Slot of method `name` in the interface tables
+    pub fn method_slot(&self, name: &str) -> Option<usize> {
+        self.methods.iter().position(|(method, _)| method == name)
+    }
+}
+
+/// Variants of an enum; a value holds the index of its variant and the fields of that variant
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumLayout {
+    pub name: String,
+    pub variants: Vec<(String, Vec<Type>)>, // each variant and the types of its fields, in declaration order
+}
+
+impl EnumLayout {
+    /// Index of variant `name`
+    pub fn variant_index(&self, name: &str) -> Option<usize> {
+        self.variants.iter().position(|(variant, _)| variant == name)
+    }
+}
+
+/// Environment of a closure: the variables its function captured from the functions enclosing it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClosureLayout {
+    pub function: String, // function of the closure, which receives the environment as its first parameter
+    pub captures: Vec<(String, Type, Capture)>, // each variable captured, its type and whether it is copied or shared
+}
+
+impl ClosureLayout {
+    /// Index of captured variable `name` in the environment
+    pub fn capture_index(&self, name: &str) -> Option<usize> {
+        self.captures.iter().position(|(capture, _, _)| capture == name)
+    }
 }
 
 /// Function in IR
@@ -127,6 +222,72 @@ impl Function {
     pub fn add_scope_variable(&mut self, var_name: String) {
         self.scope_variables.push(var_name);
     }
+
+    /// Remove the blocks no path from the entry block reaches, such as those holding the statements
+    /// after a return, so that none of them ends with a return the function cannot make
+    pub fn remove_unreachable_blocks(&mut self) {
+        let indices: HashMap<&str, usize> = self.blocks.iter().enumerate()
+            .map(|(index, block)| (block.name.as_str(), index))
+            .collect();
+        let mut reached = vec![false; self.blocks.len()];
+        let mut pending = vec![0];
+        while let Some(index) = pending.pop() {
+            if index >= reached.len() || reached[index] {
+                continue;
+            }
+            reached[index] = true;
+            pending.extend(self.blocks[index].terminator.successors().into_iter().filter_map(|target| indices.get(target).copied()));
+        }
+        let mut reached = reached.into_iter();
+        self.blocks.retain(|_| reached.next().unwrap_or(false));
+    }
+
+    /// Check that each block has one name and ends once, at its terminator: none of its own instructions
+    /// returns or throws, the terminator only continues in blocks of the function, and returns give a
+    /// value exactly when the function has a return type
+    pub fn verify(&self) -> Result<(), String> {
+        let mut names = HashSet::new();
+        for block in &self.blocks {
+            if !names.insert(block.name.as_str()) {
+                return Err(format!("Function {} has more than one block named {}", self.name, block.name));
+            }
+        }
+        for block in &self.blocks {
+            if block.instructions.iter().any(|instruction| matches!(instruction, Instruction::Return { .. } | Instruction::Throw { .. })) {
+                return Err(format!("Block {} of function {} ends before its terminator", block.name, self.name));
+            }
+            if let Some(target) = block.terminator.successors().into_iter().find(|target| !names.contains(target)) {
+                return Err(format!("Block {} of function {} continues in block {}, which does not exist", block.name, self.name, target));
+            }
+            if let Terminator::Return { value } = &block.terminator {
+                if !self.returns(value.as_ref()) {
+                    return Err(format!("Block {} of function {} returns {:?}, which does not have type {:?}", block.name, self.name, value, self.return_type));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a return of `value` fits the return type; the types of constants are known, and those of
+    /// other values were checked when analyzing the program
+    fn returns(&self, value: Option<&Value>) -> bool {
+        let return_type = match &self.return_type {
+            Type::Async { inner_type } => inner_type.as_ref(),
+            return_type => return_type,
+        };
+        match (return_type, value) {
+            (Type::Void, None) => true,
+            (Type::Void, Some(_)) | (_, None) => false,
+            (_, Some(Value::Constant(constant))) => match constant {
+                Constant::Int(_) => matches!(return_type, Type::Int | Type::Float),
+                Constant::Float(_) => *return_type == Type::Float,
+                Constant::Bool(_) => *return_type == Type::Bool,
+                Constant::String(_) => *return_type == Type::String,
+                _ => true,
+            },
+            (_, Some(_)) => true,
+        }
+    }
 }
 
 /// Basic block in IR
@@ -171,6 +332,24 @@ impl BasicBlock {
     pub fn add_block_variable(&mut self, var_name: String) {
         self.block_variables.push(var_name);
     }
+
+    /// Instructions of the block, and the call of the invoke ending it
+    pub fn instructions(&self) -> impl Iterator<Item = &Instruction> {
+        let invoked = match &self.terminator {
+            Terminator::Invoke { call, .. } => Some(call.as_ref()),
+            _ => None,
+        };
+        self.instructions.iter().chain(invoked)
+    }
+
+    /// Instructions of the block, and the call of the invoke ending it
+    pub fn instructions_mut(&mut self) -> impl Iterator<Item = &mut Instruction> {
+        let invoked = match &mut self.terminator {
+            Terminator::Invoke { call, .. } => Some(call.as_mut()),
+            _ => None,
+        };
+        self.instructions.iter_mut().chain(invoked)
+    }
 }
 
 /// Parameter for a function
@@ -204,6 +383,7 @@ pub struct GlobalVariable {
     pub name: String,
     pub var_type: Type,
     pub initializer: Option<Value>,
+    pub constant: bool, // `konstan`: the value never changes
     pub debug_info: Option<DebugInfo>, // Variable-level debug info
 }
 
@@ -214,6 +394,7 @@ impl GlobalVariable {
             name,
             var_type,
             initializer: None,
+            constant: false,
             debug_info: None,
         }
     }
@@ -224,6 +405,12 @@ impl GlobalVariable {
         self
     }
 
+    /// Mark the global as a constant
+    pub fn as_constant(mut self) -> Self {
+        self.constant = true;
+        self
+    }
+
     /// Set variable-level debug information
     pub fn with_debug_info(mut self, debug_info: DebugInfo) -> Self {
         self.debug_info = Some(debug_info);
@@ -239,7 +426,8 @@ pub enum Type {
     Bool,
     String,
     Array { element_type: Box<Type> },
-    Object { name: String },
+    Tuple { element_types: Vec<Type> },
+    Object { name: String }, // instance of a class, or value of an enum
     Function { param_types: Vec<Type>, return_type: Box<Type> },
     Void,
     // New types for enhanced features
@@ -265,6 +453,36 @@ pub enum Type {
     Condition,                            // Condition variable
 }
 
+impl From<&crate::types::Type> for Type {
+    /// IR type of a value the semantic analyzer inferred to have type `ty`
+    fn from(ty: &crate::types::Type) -> Self {
+        use crate::types::Type as Source;
+
+        match ty {
+            Source::Int => Type::Int,
+            Source::Float => Type::Float,
+            Source::Bool => Type::Bool,
+            Source::String => Type::String,
+            Source::Void => Type::Void,
+            Source::List(element) => Type::Array { element_type: Box::new(Type::from(&**element)) },
+            Source::Tuple(elements) => Type::Tuple { element_types: elements.iter().map(Type::from).collect() },
+            Source::Optional(inner) => Type::Optional { inner_type: Box::new(Type::from(&**inner)) },
+            Source::Function { parameters, return_type, .. } => Type::Function {
+                param_types: parameters.iter().map(Type::from).collect(),
+                return_type: Box::new(Type::from(&**return_type)),
+            },
+            Source::Named { name, arguments } if arguments.len() == 1
+                && matches!(name.to_lowercase().as_str(), "channel" | "saluran") => {
+                Type::Channel { element_type: Box::new(Type::from(&arguments[0])) }
+            }
+            // An instance of a generic class or enum is one of its specialization, e.g. `Kotak<int>`
+            Source::Named { .. } => Type::Object { name: ty.to_string() },
+            // Maps and values only known at run time are objects of the runtime library
+            Source::Map(_, _) | Source::Variable(_) | Source::Parameter(_) | Source::Any => Type::Object { name: ty.to_string() },
+        }
+    }
+}
+
 /// Values in IR
 #[derive(Debug, Clone)]
 pub enum Value {
@@ -359,6 +577,11 @@ pub enum Instruction {
         value: Option<Value>,
         debug_info: Option<DebugInfo>, // Instruction-level debug info
     },
+    Throw {             // `Terminator::Unwind`, in the nested instructions of a case
+        exception: Value,
+        exception_type: Type,
+        debug_info: Option<DebugInfo>, // Instruction-level debug info
+    },
     // Elegant instructions for reduced boilerplate
     Chain {             // Method chaining instruction
         result: String,
@@ -366,10 +589,11 @@ pub enum Instruction {
         methods: Vec<(String, Vec<Value>)>, // method name and arguments
         debug_info: Option<DebugInfo>, // Instruction-level debug info
     },
-    Pipeline {          // Pipeline operator support
+    Pipeline {          // Run list `initial` through `stages`, each taking what the one before produces
         result: String,
         initial: Value,
-        operations: Vec<Value>, // sequence of operations
+        element_type: Type, // type of the elements of `initial`
+        stages: Vec<Stage>,
         debug_info: Option<DebugInfo>, // Instruction-level debug info
     },
     Destructure {       // Object/array destructuring
@@ -409,17 +633,118 @@ pub enum Instruction {
         property: String,
         debug_info: Option<DebugInfo>, // Instruction-level debug info
     },
+    // Instructions for instances of classes with a `ClassLayout`
+    NewObject {         // Allocate an instance with its fields zeroed and its virtual table set
+        result: String,
+        class: String,
+        debug_info: Option<DebugInfo>, // Instruction-level debug info
+    },
+    GetField {
+        result: String,
+        object: Value,
+        class: String,
+        index: usize,   // index in `ClassLayout::fields`
+        debug_info: Option<DebugInfo>, // Instruction-level debug info
+    },
+    SetField {
+        object: Value,
+        class: String,
+        index: usize,   // index in `ClassLayout::fields`
+        value: Value,
+        debug_info: Option<DebugInfo>, // Instruction-level debug info
+    },
+    VirtualCall {       // Call the function in slot `slot` of the object's virtual table, passing the object first
+        result: Option<String>,
+        object: Value,
+        class: String,
+        slot: usize,    // index in `ClassLayout::methods`
+        arguments: Vec<Value>,
+        debug_info: Option<DebugInfo>, // Instruction-level debug info
+    },
+    InterfaceCall {     // Call the function in slot `slot` of the table the object's class has for `interface`, passing the object first
+        result: Option<String>,
+        object: Value,
+        interface: String,
+        slot: usize,    // index in `InterfaceLayout::methods`
+        arguments: Vec<Value>,
+        debug_info: Option<DebugInfo>, // Instruction-level debug info
+    },
+    // Instructions for values of enums with an `EnumLayout`, and tuples
+    NewVariant {        // Build a value of variant `variant` from its fields
+        result: String,
+        enumeration: String,
+        variant: usize, // index in `EnumLayout::variants`
+        fields: Vec<Value>,
+        debug_info: Option<DebugInfo>, // Instruction-level debug info
+    },
+    MakeTuple {
+        result: String,
+        elements: Vec<Value>,
+        debug_info: Option<DebugInfo>, // Instruction-level debug info
+    },
+    // Instructions for closures with a `ClosureLayout`
+    AllocaShared {      // Allocate a variable closures capture by reference on the heap, so it outlives its function
+        result: String,
+        alloca_type: Type,
+        debug_info: Option<DebugInfo>, // Instruction-level debug info
+    },
+    MakeClosure {       // Pair function `function` with a new environment holding the variables it captures
+        result: String,
+        function: String,
+        captures: Vec<String>, // variables in `ClosureLayout::captures` order; shared ones are stored as their address
+        debug_info: Option<DebugInfo>, // Instruction-level debug info
+    },
+    BindCapture {       // Bind `variable` in the function of a closure to capture `index` of the environment it receives
+        variable: String,
+        function: String,
+        index: usize,   // index in `ClosureLayout::captures`
+        debug_info: Option<DebugInfo>, // Instruction-level debug info
+    },
+    ClosureCall {       // Call the function of a closure, passing its environment first
+        result: Option<String>,
+        closure: Value,
+        function_type: Type, // type of the closure, without the environment
+        arguments: Vec<Value>,
+        debug_info: Option<DebugInfo>, // Instruction-level debug info
+    },
+    // Exceptions
+    LandingPad {        // Bind the error being thrown; first instruction of each block an invoke unwinds to
+        result: String,
+        debug_info: Option<DebugInfo>, // Instruction-level debug info
+    },
+    ExceptionIs {       // Whether the thrown value of error `exception` is an instance of `class` or of a class extending it
+        result: String,
+        exception: Value,
+        class: String,
+        debug_info: Option<DebugInfo>, // Instruction-level debug info
+    },
+    Catch {             // Stop the unwinding of error `exception`, and take the value it threw
+        result: String,
+        exception: Value,
+        debug_info: Option<DebugInfo>, // Instruction-level debug info
+    },
+    // Optionals
+    NullCheck {         // Whether an optional value is `nol`
+        result: String,
+        value: Value,
+        debug_info: Option<DebugInfo>, // Instruction-level debug info
+    },
+    Unwrap {            // Value an optional holds; it must not be `nol`
+        result: String,
+        value: Value,
+        debug_info: Option<DebugInfo>, // Instruction-level debug info
+    },
     ForEachLoop {
         variable: String,
         iterable: Value,
         body: Vec<Instruction>,
         debug_info: Option<DebugInfo>, // Instruction-level debug info
     },
-    PatternMatch {
+    PatternMatch {      // Run the first case whose pattern matches, as `decision_tree` orders the tests
         result: String,
         expression: Value,
-        cases: Vec<(Value, Vec<Instruction>)>,
-        default: Option<Vec<Instruction>>,
+        cases: Vec<MatchCase>,
+        default: Option<Vec<Instruction>>, // runs when no case matches; without it, the cases match every value
         debug_info: Option<DebugInfo>, // Instruction-level debug info
     },
     Await {
@@ -523,6 +848,395 @@ pub enum Instruction {
     },
 }
 
+impl Instruction {
+    /// Whether the instruction runs code that may throw, so that in a `coba` statement it must be invoked
+    pub fn may_throw(&self) -> bool {
+        match self {
+            Instruction::Call { .. }
+            | Instruction::VirtualCall { .. }
+            | Instruction::InterfaceCall { .. }
+            | Instruction::ClosureCall { .. }
+            | Instruction::Pipeline { .. }
+            | Instruction::Throw { .. } => true,
+            Instruction::PatternMatch { cases, default, .. } => cases.iter()
+                .flat_map(|case| case.guard.iter().flat_map(|(guard, _)| guard).chain(&case.body))
+                .chain(default.iter().flatten())
+                .any(Instruction::may_throw),
+            _ => false,
+        }
+    }
+
+    /// Values the instruction reads, those of the instructions nested in it included, for passes that
+    /// replace them
+    pub fn operands_mut(&mut self) -> Vec<&mut Value> {
+        match self {
+            Instruction::BinaryOp { left, right, .. }
+            | Instruction::Range { start: left, end: right, .. }
+            | Instruction::SetField { object: left, value: right, .. }
+            | Instruction::ChannelSend { channel: left, value: right, .. }
+            | Instruction::ConditionWait { condition: left, mutex: right, .. }
+            | Instruction::AtomicStore { address: left, value: right, .. }
+            | Instruction::AtomicExchange { address: left, value: right, .. }
+            | Instruction::AtomicFetchAdd { address: left, value: right, .. }
+            | Instruction::AtomicFetchSub { address: left, value: right, .. } => vec![left, right],
+            Instruction::UnaryOp { operand: value, .. }
+            | Instruction::Store { value, .. }
+            | Instruction::Throw { exception: value, .. }
+            | Instruction::Destructure { value, .. }
+            | Instruction::MemberAccess { object: value, .. }
+            | Instruction::GetField { object: value, .. }
+            | Instruction::ExceptionIs { exception: value, .. }
+            | Instruction::Catch { exception: value, .. }
+            | Instruction::NullCheck { value, .. }
+            | Instruction::Unwrap { value, .. }
+            | Instruction::Await { value, .. }
+            | Instruction::Yield { value, .. }
+            | Instruction::ChannelReceive { channel: value, .. }
+            | Instruction::MakeGoroutine { function: value, .. }
+            | Instruction::MutexLock { mutex: value, .. }
+            | Instruction::MutexUnlock { mutex: value, .. }
+            | Instruction::ConditionSignal { condition: value, .. }
+            | Instruction::ConditionBroadcast { condition: value, .. }
+            | Instruction::AtomicLoad { address: value, .. } => vec![value],
+            Instruction::Return { value, .. } => value.iter_mut().collect(),
+            Instruction::Call { arguments, .. }
+            | Instruction::NewVariant { fields: arguments, .. }
+            | Instruction::MakeTuple { elements: arguments, .. } => arguments.iter_mut().collect(),
+            Instruction::VirtualCall { object, arguments, .. }
+            | Instruction::InterfaceCall { object, arguments, .. }
+            | Instruction::ClosureCall { closure: object, arguments, .. }
+            | Instruction::GoRoutine { function: object, arguments, .. } => std::iter::once(object).chain(arguments).collect(),
+            Instruction::Chain { object, methods, .. } => {
+                std::iter::once(object).chain(methods.iter_mut().flat_map(|(_, arguments)| arguments)).collect()
+            }
+            Instruction::Pipeline { initial, stages, .. } => {
+                let mut operands = vec![initial];
+                for stage in stages {
+                    match stage {
+                        Stage::Loop(steps) => operands.extend(steps.iter_mut().map(|step| match step {
+                            LoopStep::Filter(function) | LoopStep::Map { function, .. } => function,
+                        })),
+                        Stage::Take(value) | Stage::Skip(value) => operands.push(value),
+                        Stage::Sort(comparator) => operands.extend(comparator),
+                        Stage::Reduce { function, initial, .. } => operands.extend(std::iter::once(function).chain(initial)),
+                    }
+                }
+                operands
+            }
+            Instruction::ListComprehension { expression, iterable, condition, .. } => {
+                [expression, iterable].into_iter().chain(condition).collect()
+            }
+            Instruction::ObjectLiteral { properties, .. } => properties.values_mut().collect(),
+            Instruction::AtomicCompareExchange { address, expected, desired, .. } => vec![address, expected, desired],
+            Instruction::ForEachLoop { iterable, body, .. } => {
+                std::iter::once(iterable).chain(body.iter_mut().flat_map(Instruction::operands_mut)).collect()
+            }
+            Instruction::PatternMatch { expression, cases, default, .. } => {
+                let mut operands = vec![expression];
+                for case in cases {
+                    if let Some((instructions, condition)) = &mut case.guard {
+                        operands.extend(instructions.iter_mut().flat_map(Instruction::operands_mut));
+                        operands.push(condition);
+                    }
+                    operands.extend(case.body.iter_mut().flat_map(Instruction::operands_mut));
+                }
+                operands.extend(default.iter_mut().flatten().flat_map(Instruction::operands_mut));
+                operands
+            }
+            Instruction::Load { .. }
+            | Instruction::Alloca { .. }
+            | Instruction::Swap { .. }
+            | Instruction::NewObject { .. }
+            | Instruction::AllocaShared { .. }
+            | Instruction::MakeClosure { .. }
+            | Instruction::BindCapture { .. }
+            | Instruction::LandingPad { .. }
+            | Instruction::MakeChannel { .. } => Vec::new(),
+        }
+    }
+}
+
+/// Stage of a `Pipeline`
+#[derive(Debug, Clone)]
+pub enum Stage {
+    Loop(Vec<LoopStep>), // one pass over the list, running the steps on each element in order
+    Take(Value),
+    Skip(Value),
+    Sort(Option<Value>), // comparator closure; without one, elements are compared by value
+    Reduce {
+        function: Value, // closure combining the value so far with each element
+        initial: Option<Value>, // without one, the first element starts the reduction
+        result_type: Type,
+    },
+}
+
+/// Step a `Stage::Loop` runs on each element of the list
+#[derive(Debug, Clone)]
+pub enum LoopStep {
+    Filter(Value), // closure deciding whether the element is kept
+    Map {
+        function: Value, // closure whose result replaces the element
+        element_type: Type, // type of the results
+    },
+}
+
+/// Case of a `PatternMatch`
+#[derive(Debug)]
+pub struct MatchCase {
+    pub pattern: Pattern,
+    pub guard: Option<(Vec<Instruction>, Value)>, // instructions computing the `jika` condition, and the condition
+    pub body: Vec<Instruction>,
+}
+
+/// Case of a `Terminator::Match`, whose body runs in a block of its own
+#[derive(Debug)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub guard: Option<(Vec<Instruction>, Value)>, // instructions computing the `jika` condition, and the condition
+    pub target: String, // block running the body
+}
+
+/// Case a decision tree chooses between
+pub trait Case {
+    fn pattern(&self) -> &Pattern;
+    fn guard(&self) -> Option<&(Vec<Instruction>, Value)>;
+}
+
+impl Case for MatchCase {
+    fn pattern(&self) -> &Pattern {
+        &self.pattern
+    }
+
+    fn guard(&self) -> Option<&(Vec<Instruction>, Value)> {
+        self.guard.as_ref()
+    }
+}
+
+impl Case for MatchArm {
+    fn pattern(&self) -> &Pattern {
+        &self.pattern
+    }
+
+    fn guard(&self) -> Option<&(Vec<Instruction>, Value)> {
+        self.guard.as_ref()
+    }
+}
+
+/// Pattern of a case
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    Wildcard,
+    Binding(String), // variable the matched value is stored to
+    Constant(Constant), // equal to the constant
+    Range(i64, i64), // whole number between both, included
+    Tuple(Vec<Pattern>),
+    Variant {
+        enumeration: String,
+        index: usize, // index in `EnumLayout::variants`
+        fields: Vec<Pattern>,
+    },
+}
+
+/// Step from a value to one of its parts
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    Element(usize), // element of a tuple
+    Field { enumeration: String, variant: usize, index: usize }, // field of a value known to be of variant `variant`
+}
+
+/// Test on a part of the matched value that is not a variant
+#[derive(Debug, Clone)]
+pub enum Test {
+    Equal(Constant),
+    Between(i64, i64), // included
+}
+
+/// Decision tree a `PatternMatch` or `Terminator::Match` runs: each node tests one part of the matched value, reached by the steps
+/// of its `access` from the value
+#[derive(Debug, Clone)]
+pub enum Decision {
+    Fail, // no case matches: the default runs
+    Run {
+        case: usize,
+        bindings: Vec<(String, Vec<Step>)>, // variables to store the parts they bind to first
+        otherwise: Option<Box<Decision>>, // where to go when the guard of the case is false
+    },
+    Switch {
+        access: Vec<Step>,
+        enumeration: String,
+        branches: Vec<(usize, Decision)>, // by variant
+        default: Box<Decision>, // for the variants without a branch
+    },
+    Test {
+        access: Vec<Step>,
+        test: Test,
+        then: Box<Decision>,
+        otherwise: Box<Decision>,
+    },
+}
+
+/// Decision tree running the first of `cases` that matches, testing each part of the value at most once on
+/// each path
+pub fn decision_tree<C: Case>(cases: &[C]) -> Decision {
+    let rows = cases.iter().enumerate()
+        .map(|(case, match_case)| Row {
+            tests: vec![(Vec::new(), match_case.pattern())],
+            bindings: Vec::new(),
+            case,
+            guarded: match_case.guard().is_some(),
+        }.normalized())
+        .collect();
+    compile_rows(rows)
+}
+
+/// Case still in the running, with the tests its pattern has left
+#[derive(Clone)]
+struct Row<'a> {
+    tests: Vec<(Vec<Step>, &'a Pattern)>, // part of the value and the pattern it must match
+    bindings: Vec<(String, Vec<Step>)>,
+    case: usize,
+    guarded: bool,
+}
+
+impl<'a> Row<'a> {
+    /// The row with wildcards dropped, bindings recorded and tuples replaced by their elements, which
+    /// leaves only constants, ranges and variants to test
+    fn normalized(mut self) -> Self {
+        let mut tests = Vec::new();
+        let mut pending: Vec<(Vec<Step>, &'a Pattern)> = self.tests.drain(..).rev().collect();
+        while let Some((access, pattern)) = pending.pop() {
+            match pattern {
+                Pattern::Wildcard => {}
+                Pattern::Binding(variable) => self.bindings.push((variable.clone(), access)),
+                Pattern::Tuple(elements) => {
+                    for (index, element) in elements.iter().enumerate().rev() {
+                        pending.push((step(&access, Step::Element(index)), element));
+                    }
+                }
+                _ => tests.push((access, pattern)),
+            }
+        }
+        self.tests = tests;
+        self
+    }
+
+    /// Index of the test of the part at `access`, if the row tests it
+    fn test_at(&self, access: &[Step]) -> Option<usize> {
+        self.tests.iter().position(|(tested, _)| tested == access)
+    }
+
+    /// The row for values whose part at `access` is of variant `variant`; `None` if it needs another variant
+    fn specialize(&self, access: &[Step], variant: usize) -> Option<Self> {
+        let Some(index) = self.test_at(access) else {
+            return Some(self.clone());
+        };
+        let Pattern::Variant { enumeration, index: tested, fields } = self.tests[index].1 else {
+            return Some(self.clone());
+        };
+        if *tested != variant {
+            return None;
+        }
+        let mut row = self.clone();
+        let field_tests = fields.iter().enumerate().map(|(field, pattern)| {
+            let field = Step::Field { enumeration: enumeration.clone(), variant, index: field };
+            (step(access, field), pattern)
+        });
+        row.tests.splice(index..=index, field_tests);
+        Some(row.normalized())
+    }
+
+    /// The row without its test at `index`
+    fn without(&self, index: usize) -> Self {
+        let mut row = self.clone();
+        row.tests.remove(index);
+        row
+    }
+}
+
+/// `access` followed by `next`
+fn step(access: &[Step], next: Step) -> Vec<Step> {
+    let mut access = access.to_vec();
+    access.push(next);
+    access
+}
+
+/// Whether two patterns that are not variants test the same thing
+fn same_test(left: &Pattern, right: &Pattern) -> bool {
+    match (left, right) {
+        (Pattern::Range(low, high), Pattern::Range(other_low, other_high)) => low == other_low && high == other_high,
+        (Pattern::Constant(left), Pattern::Constant(right)) => same_constant(left, right) == Some(true),
+        _ => false,
+    }
+}
+
+/// Whether two constants a pattern may hold are equal; `None` if they cannot be compared
+fn same_constant(left: &Constant, right: &Constant) -> Option<bool> {
+    match (left, right) {
+        (Constant::Int(left), Constant::Int(right)) => Some(left == right),
+        (Constant::Float(left), Constant::Float(right)) => Some(left.to_bits() == right.to_bits()),
+        (Constant::Bool(left), Constant::Bool(right)) => Some(left == right),
+        (Constant::String(left), Constant::String(right)) => Some(left == right),
+        (Constant::Null, Constant::Null) => Some(true),
+        _ => None,
+    }
+}
+
+/// Decision tree for `rows`, tried in order
+fn compile_rows(rows: Vec<Row>) -> Decision {
+    let Some(first) = rows.first() else {
+        return Decision::Fail;
+    };
+    // A case whose pattern has nothing left to test runs, unless its guard is false
+    let Some((access, pattern)) = first.tests.first().cloned() else {
+        let otherwise = first.guarded.then(|| Box::new(compile_rows(rows[1..].to_vec())));
+        return Decision::Run { case: first.case, bindings: first.bindings.clone(), otherwise };
+    };
+    match pattern {
+        Pattern::Variant { enumeration, .. } => {
+            // One branch for each variant a case names at this part; the other variants share the default
+            let mut variants = Vec::new();
+            for row in &rows {
+                if let Some(index) = row.test_at(&access) {
+                    if let Pattern::Variant { index: variant, .. } = row.tests[index].1 {
+                        if !variants.contains(variant) {
+                            variants.push(*variant);
+                        }
+                    }
+                }
+            }
+            let branches = variants.into_iter()
+                .map(|variant| (variant, compile_rows(rows.iter().filter_map(|row| row.specialize(&access, variant)).collect())))
+                .collect();
+            let default = compile_rows(rows.iter().filter(|row| row.test_at(&access).is_none()).cloned().collect());
+            Decision::Switch { access, enumeration: enumeration.clone(), branches, default: Box::new(default) }
+        }
+        _ => {
+            let test = match pattern {
+                Pattern::Range(low, high) => Test::Between(*low, *high),
+                Pattern::Constant(constant) => Test::Equal(constant.clone()),
+                _ => unreachable!("normalized rows only test constants, ranges and variants"),
+            };
+            // When the test passes, rows testing the same drop it and rows needing another constant drop out;
+            // when it fails, rows testing the same drop out. Other rows keep their test for later.
+            let then = rows.iter()
+                .filter_map(|row| match row.test_at(&access) {
+                    Some(index) if same_test(row.tests[index].1, pattern) => Some(row.without(index)),
+                    Some(index) => match (row.tests[index].1, pattern) {
+                        (Pattern::Constant(left), Pattern::Constant(right)) if same_constant(left, right) == Some(false) => None,
+                        _ => Some(row.clone()),
+                    },
+                    None => Some(row.clone()),
+                })
+                .collect();
+            let otherwise = rows.iter()
+                .filter(|row| !matches!(row.test_at(&access), Some(index) if same_test(row.tests[index].1, pattern)))
+                .cloned()
+                .collect();
+            Decision::Test { access, test, then: Box::new(compile_rows(then)), otherwise: Box::new(compile_rows(otherwise)) }
+        }
+    }
+}
+
 /// Binary operations
 #[derive(Debug)]
 pub enum BinaryOp {
@@ -559,17 +1273,6 @@ pub enum UnaryOp {
     Dereference,  // *
 }
 
-/// Atomic ordering for atomic operations
-#[derive(Debug)]
-pub enum AtomicOrdering {
-    Relaxed,
-    Consume,
-    Acquire,
-    Release,
-    AcqRel,
-    SeqCst,
-}
-
 /// Terminators for basic blocks
 #[derive(Debug)]
 pub enum Terminator {
@@ -580,6 +1283,59 @@ pub enum Terminator {
         then_target: String,
         else_target: String,
     },
+    Invoke {            // Run `call`, then continue in `normal`, or in `unwind` if it throws
+        call: Box<Instruction>,
+        normal: String,
+        unwind: String,
+    },
+    Unwind {            // Throw `exception`, an instance of a class or a text
+        exception: Value,
+        exception_type: Type,
+        unwind: Option<String>, // landing pad of the `coba` statement around, if any
+    },
+    Resume {            // Keep unwinding error `exception`, which a landing pad took
+        exception: Value,
+        unwind: Option<String>, // landing pad of the `coba` statement around, if any
+    },
+    Match {             // Continue in the block of the first case whose pattern matches, as `decision_tree` orders the tests
+        expression: Value,
+        arms: Vec<MatchArm>,
+        default: Option<String>, // block run when no case matches; without it, the cases match every value
+    },
+}
+
+impl Terminator {
+    /// Values the terminator reads, those of the call of an invoke and of the guards of a match included
+    pub fn operands_mut(&mut self) -> Vec<&mut Value> {
+        match self {
+            Terminator::Return { value } => value.iter_mut().collect(),
+            Terminator::Branch { .. } => Vec::new(),
+            Terminator::ConditionalBranch { condition: value, .. }
+            | Terminator::Unwind { exception: value, .. }
+            | Terminator::Resume { exception: value, .. } => vec![value],
+            Terminator::Invoke { call, .. } => call.operands_mut(),
+            Terminator::Match { expression, arms, .. } => {
+                let mut operands = vec![expression];
+                for (instructions, condition) in arms.iter_mut().filter_map(|arm| arm.guard.as_mut()) {
+                    operands.extend(instructions.iter_mut().flat_map(Instruction::operands_mut));
+                    operands.push(condition);
+                }
+                operands
+            }
+        }
+    }
+
+    /// Blocks the terminator may continue in
+    pub fn successors(&self) -> Vec<&str> {
+        match self {
+            Terminator::Return { .. } => Vec::new(),
+            Terminator::Branch { target } => vec![target],
+            Terminator::ConditionalBranch { then_target, else_target, .. } => vec![then_target, else_target],
+            Terminator::Invoke { normal, unwind, .. } => vec![normal, unwind],
+            Terminator::Unwind { unwind, .. } | Terminator::Resume { unwind, .. } => unwind.iter().map(String::as_str).collect(),
+            Terminator::Match { arms, default, .. } => arms.iter().map(|arm| arm.target.as_str()).chain(default.as_deref()).collect(),
+        }
+    }
 }
 
 /// IR builder for constructing IR programmatically
@@ -588,20 +1344,26 @@ pub struct IRBuilder {
     current_function: Option<usize>,
     current_block: Option<usize>,
     next_value_id: usize,
+    unwind_target: Option<String>, // landing pad instructions that may throw unwind to, in a `coba` statement
+    terminated: HashSet<(usize, usize)>, // function and index of the blocks whose terminator was set
 }
 
-impl IRBuilder {
+impl Default for IRBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IRBuilder {
     /// Create a new IR builder
     pub fn new() -> Self {
         IRBuilder {
-            module: IRModule {
-                functions: Vec::new(),
-                global_vars: Vec::new(),
-                debug_info: None, // No module-level debug info initially
-            },
+            module: IRModule::new("main".to_string()),
             current_function: None,
             current_block: None,
             next_value_id: 0,
+            unwind_target: None,
+            terminated: HashSet::new(),
         }
     }
 
@@ -612,32 +1374,23 @@ impl IRBuilder {
         parameters: Vec<Parameter>,
         return_type: Type,
     ) -> usize {
-        let function = Function {
-            name,
-            parameters,
-            return_type,
-            blocks: Vec::new(),
-            debug_info: None, // No debug info initially
-        };
+        let mut function = Function::new(name, return_type);
+        function.parameters = parameters;
 
         self.module.functions.push(function);
         let function_index = self.module.functions.len() - 1;
         self.current_function = Some(function_index);
+        self.current_block = None;
         function_index
     }
 
     /// Create a new basic block
     pub fn create_block(&mut self, name: String) -> Result<usize, String> {
         if let Some(function_index) = self.current_function {
-            let block = BasicBlock {
-                name,
-                instructions: Vec::new(),
-                terminator: Terminator::Return { value: None }, // Default terminator
-                debug_info: None, // No debug info initially
-            };
-
-            self.module.functions[function_index].blocks.push(block);
+            self.module.functions[function_index].add_block(BasicBlock::new(name));
             let block_index = self.module.functions[function_index].blocks.len() - 1;
+            // The index may have belonged to a block that was taken out of the function
+            self.terminated.remove(&(function_index, block_index));
             self.current_block = Some(block_index);
             Ok(block_index)
         } else {
@@ -645,54 +1398,94 @@ impl IRBuilder {
         }
     }
 
-    /// Add an instruction to the current block
-    pub fn add_instruction(&mut self, instruction: Instruction) -> Result<String, String> {
+    /// Function and block instructions are currently added to
+    pub fn insertion_point(&self) -> (Option<usize>, Option<usize>) {
+        (self.current_function, self.current_block)
+    }
+
+    /// Continue adding instructions at a point returned by `insertion_point`
+    pub fn set_insertion_point(&mut self, (function, block): (Option<usize>, Option<usize>)) {
+        self.current_function = function;
+        self.current_block = block;
+    }
+
+    /// Name for the result of a new instruction
+    pub fn fresh_value(&mut self) -> String {
+        let name = self.next_value_id.to_string();
+        self.next_value_id += 1;
+        name
+    }
+
+    /// Current basic block
+    fn current_block_mut(&mut self) -> Result<&mut BasicBlock, String> {
         if let (Some(function_index), Some(block_index)) =
             (self.current_function, self.current_block)
         {
-            self.module.functions[function_index].blocks[block_index]
-                .instructions
-                .push(instruction);
-
-            // Generate a unique name for the result
-            let result_name = format!("%{}", self.next_value_id);
-            self.next_value_id += 1;
-            Ok(result_name)
+            Ok(&mut self.module.functions[function_index].blocks[block_index])
         } else {
             Err("No current block".to_string())
         }
     }
 
+    /// Add an instruction to the current block. With an unwind target, an instruction that may throw
+    /// is invoked instead, ending the block, and the next instructions go to the block it continues in
+    pub fn add_instruction(&mut self, instruction: Instruction) -> Result<(), String> {
+        match self.unwind_target.clone() {
+            Some(unwind) if instruction.may_throw() => {
+                let normal = format!("invoke{}", self.fresh_value());
+                self.set_terminator(Terminator::Invoke { call: Box::new(instruction), normal: normal.clone(), unwind })?;
+                self.create_block(normal)?;
+            }
+            _ => self.current_block_mut()?.add_instruction(instruction),
+        }
+        Ok(())
+    }
+
+    /// Landing pad instructions that may throw unwind to
+    pub fn unwind_target(&self) -> Option<String> {
+        self.unwind_target.clone()
+    }
+
+    /// Make instructions that may throw unwind to `target`
+    pub fn set_unwind_target(&mut self, target: Option<String>) {
+        self.unwind_target = target;
+    }
+
+    /// Set the terminator of the current block; a block has only one
+    pub fn set_terminator(&mut self, terminator: Terminator) -> Result<(), String> {
+        if let (Some(function), Some(block)) = (self.current_function, self.current_block) {
+            if !self.terminated.insert((function, block)) {
+                let name = &self.module.functions[function].blocks[block].name;
+                return Err(format!("Block {} already has a terminator", name));
+            }
+        }
+        self.current_block_mut()?.set_terminator(terminator);
+        Ok(())
+    }
+
+    /// Whether the terminator of the block at a point returned by `insertion_point` was set; until it is,
+    /// the block returns nothing
+    pub fn is_terminated(&self, point: (Option<usize>, Option<usize>)) -> bool {
+        matches!(point, (Some(function), Some(block)) if self.terminated.contains(&(function, block)))
+    }
+
     /// Set debug information for the current module
     pub fn set_module_debug_info(&mut self, file_name: String, line: usize, column: usize) {
-        self.module.debug_info = Some(DebugInfo {
-            file_name,
-            line,
-            column,
-        });
+        self.module.debug_info = Some(DebugInfo::new(file_name, line, column));
     }
 
     /// Set debug information for the current function
     pub fn set_function_debug_info(&mut self, file_name: String, line: usize, column: usize) {
         if let Some(function_index) = self.current_function {
-            self.module.functions[function_index].debug_info = Some(DebugInfo {
-                file_name,
-                line,
-                column,
-            });
+            let function = &mut self.module.functions[function_index];
+            function.debug_info = Some(DebugInfo::new(file_name, line, column).with_function(function.name.clone()));
         }
     }
 
     /// Set debug information for the current block
     pub fn set_block_debug_info(&mut self, file_name: String, line: usize, column: usize) {
-        if let (Some(function_index), Some(block_index)) =
-            (self.current_function, self.current_block)
-        {
-            self.module.functions[function_index].blocks[block_index].debug_info = Some(DebugInfo {
-                file_name,
-                line,
-                column,
-            });
+        if let Ok(block) = self.current_block_mut() {
+            block.debug_info = Some(DebugInfo::new(file_name, line, column));
         }
     }
 
@@ -706,6 +1499,15 @@ impl IRBuilder {
         &mut self.module
     }
 
+    /// Take the module built so far, leaving an empty one of the same name
+    pub fn take_module(&mut self) -> IRModule {
+        let module_name = self.module.module_name.clone();
+        self.current_function = None;
+        self.current_block = None;
+        self.terminated.clear();
+        std::mem::replace(&mut self.module, IRModule::new(module_name))
+    }
+
     /// Add a list comprehension instruction
     pub fn add_list_comprehension(
         &mut self,
@@ -714,14 +1516,16 @@ impl IRBuilder {
         iterable: Value,
         condition: Option<Value>,
     ) -> Result<String, String> {
-        let instruction = Instruction::ListComprehension {
-            result: "".to_string(),
+        let result = self.fresh_value();
+        self.add_instruction(Instruction::ListComprehension {
+            result: result.clone(),
             expression,
             variable,
             iterable,
             condition,
-        };
-        self.add_instruction(instruction)
+            debug_info: None,
+        })?;
+        Ok(result)
     }
 
     /// Add a range instruction
@@ -731,13 +1535,15 @@ impl IRBuilder {
         end: Value,
         inclusive: bool,
     ) -> Result<String, String> {
-        let instruction = Instruction::Range {
-            result: "".to_string(),
+        let result = self.fresh_value();
+        self.add_instruction(Instruction::Range {
+            result: result.clone(),
             start,
             end,
             inclusive,
-        };
-        self.add_instruction(instruction)
+            debug_info: None,
+        })?;
+        Ok(result)
     }
 
     /// Add an object literal instruction
@@ -745,11 +1551,13 @@ impl IRBuilder {
         &mut self,
         properties: HashMap<String, Value>,
     ) -> Result<String, String> {
-        let instruction = Instruction::ObjectLiteral {
-            result: "".to_string(),
+        let result = self.fresh_value();
+        self.add_instruction(Instruction::ObjectLiteral {
+            result: result.clone(),
             properties,
-        };
-        self.add_instruction(instruction)
+            debug_info: None,
+        })?;
+        Ok(result)
     }
 
     /// Add a member access instruction
@@ -758,12 +1566,14 @@ impl IRBuilder {
         object: Value,
         property: String,
     ) -> Result<String, String> {
-        let instruction = Instruction::MemberAccess {
-            result: "".to_string(),
+        let result = self.fresh_value();
+        self.add_instruction(Instruction::MemberAccess {
+            result: result.clone(),
             object,
             property,
-        };
-        self.add_instruction(instruction)
+            debug_info: None,
+        })?;
+        Ok(result)
     }
 
     /// Add an await instruction
@@ -771,11 +1581,13 @@ impl IRBuilder {
         &mut self,
         value: Value,
     ) -> Result<String, String> {
-        let instruction = Instruction::Await {
-            result: "".to_string(),
+        let result = self.fresh_value();
+        self.add_instruction(Instruction::Await {
+            result: result.clone(),
             value,
-        };
-        self.add_instruction(instruction)
+            debug_info: None,
+        })?;
+        Ok(result)
     }
 
     /// Add a yield instruction
@@ -783,11 +1595,13 @@ impl IRBuilder {
         &mut self,
         value: Value,
     ) -> Result<String, String> {
-        let instruction = Instruction::Yield {
-            result: "".to_string(),
+        let result = self.fresh_value();
+        self.add_instruction(Instruction::Yield {
+            result: result.clone(),
             value,
-        };
-        self.add_instruction(instruction)
+            debug_info: None,
+        })?;
+        Ok(result)
     }
 
     /// Add a make channel instruction (Go-style)
@@ -796,12 +1610,11 @@ impl IRBuilder {
         result: String,
         channel_type: Type,
     ) -> Result<(), String> {
-        let instruction = Instruction::MakeChannel {
+        self.add_instruction(Instruction::MakeChannel {
             result,
             channel_type,
-        };
-        self.current_block_mut()?.instructions.push(instruction);
-        Ok(())
+            debug_info: None,
+        })
     }
 
     /// Add a channel send instruction (Go-style)
@@ -810,12 +1623,11 @@ impl IRBuilder {
         channel: Value,
         value: Value,
     ) -> Result<(), String> {
-        let instruction = Instruction::ChannelSend {
+        self.add_instruction(Instruction::ChannelSend {
             channel,
             value,
-        };
-        self.current_block_mut()?.instructions.push(instruction);
-        Ok(())
+            debug_info: None,
+        })
     }
 
     /// Add a channel receive instruction (Go-style)
@@ -824,12 +1636,11 @@ impl IRBuilder {
         result: String,
         channel: Value,
     ) -> Result<(), String> {
-        let instruction = Instruction::ChannelReceive {
+        self.add_instruction(Instruction::ChannelReceive {
             result,
             channel,
-        };
-        self.current_block_mut()?.instructions.push(instruction);
-        Ok(())
+            debug_info: None,
+        })
     }
 
     /// Add a goroutine creation instruction (Go-style)
@@ -838,12 +1649,11 @@ impl IRBuilder {
         result: String,
         function: Value,
     ) -> Result<(), String> {
-        let instruction = Instruction::MakeGoroutine {
+        self.add_instruction(Instruction::MakeGoroutine {
             result,
             function,
-        };
-        self.current_block_mut()?.instructions.push(instruction);
-        Ok(())
+            debug_info: None,
+        })
     }
 
     /// Add a mutex lock instruction
@@ -851,11 +1661,10 @@ impl IRBuilder {
         &mut self,
         mutex: Value,
     ) -> Result<(), String> {
-        let instruction = Instruction::MutexLock {
+        self.add_instruction(Instruction::MutexLock {
             mutex,
-        };
-        self.current_block_mut()?.instructions.push(instruction);
-        Ok(())
+            debug_info: None,
+        })
     }
 
     /// Add a mutex unlock instruction
@@ -863,11 +1672,10 @@ impl IRBuilder {
         &mut self,
         mutex: Value,
     ) -> Result<(), String> {
-        let instruction = Instruction::MutexUnlock {
+        self.add_instruction(Instruction::MutexUnlock {
             mutex,
-        };
-        self.current_block_mut()?.instructions.push(instruction);
-        Ok(())
+            debug_info: None,
+        })
     }
 
     /// Add a condition wait instruction
@@ -876,12 +1684,11 @@ impl IRBuilder {
         condition: Value,
         mutex: Value,
     ) -> Result<(), String> {
-        let instruction = Instruction::ConditionWait {
+        self.add_instruction(Instruction::ConditionWait {
             condition,
             mutex,
-        };
-        self.current_block_mut()?.instructions.push(instruction);
-        Ok(())
+            debug_info: None,
+        })
     }
 
     /// Add a condition signal instruction
@@ -889,11 +1696,10 @@ impl IRBuilder {
         &mut self,
         condition: Value,
     ) -> Result<(), String> {
-        let instruction = Instruction::ConditionSignal {
+        self.add_instruction(Instruction::ConditionSignal {
             condition,
-        };
-        self.current_block_mut()?.instructions.push(instruction);
-        Ok(())
+            debug_info: None,
+        })
     }
 
     /// Add a condition broadcast instruction
@@ -901,11 +1707,10 @@ impl IRBuilder {
         &mut self,
         condition: Value,
     ) -> Result<(), String> {
-        let instruction = Instruction::ConditionBroadcast {
+        self.add_instruction(Instruction::ConditionBroadcast {
             condition,
-        };
-        self.current_block_mut()?.instructions.push(instruction);
-        Ok(())
+            debug_info: None,
+        })
     }
 
     /// Add an atomic load instruction
@@ -915,13 +1720,12 @@ impl IRBuilder {
         address: Value,
         ordering: AtomicOrdering,
     ) -> Result<(), String> {
-        let instruction = Instruction::AtomicLoad {
+        self.add_instruction(Instruction::AtomicLoad {
             result,
             address,
             ordering,
-        };
-        self.current_block_mut()?.instructions.push(instruction);
-        Ok(())
+            debug_info: None,
+        })
     }
 
     /// Add an atomic store instruction
@@ -931,13 +1735,12 @@ impl IRBuilder {
         value: Value,
         ordering: AtomicOrdering,
     ) -> Result<(), String> {
-        let instruction = Instruction::AtomicStore {
+        self.add_instruction(Instruction::AtomicStore {
             address,
             value,
             ordering,
-        };
-        self.current_block_mut()?.instructions.push(instruction);
-        Ok(())
+            debug_info: None,
+        })
     }
 
     /// Add an atomic exchange instruction
@@ -948,17 +1751,17 @@ impl IRBuilder {
         value: Value,
         ordering: AtomicOrdering,
     ) -> Result<(), String> {
-        let instruction = Instruction::AtomicExchange {
+        self.add_instruction(Instruction::AtomicExchange {
             result,
             address,
             value,
             ordering,
-        };
-        self.current_block_mut()?.instructions.push(instruction);
-        Ok(())
+            debug_info: None,
+        })
     }
 
     /// Add an atomic compare exchange instruction
+    #[allow(clippy::too_many_arguments)]
     pub fn add_atomic_compare_exchange(
         &mut self,
         result: String,
@@ -968,16 +1771,15 @@ impl IRBuilder {
         success_ordering: AtomicOrdering,
         failure_ordering: AtomicOrdering,
     ) -> Result<(), String> {
-        let instruction = Instruction::AtomicCompareExchange {
+        self.add_instruction(Instruction::AtomicCompareExchange {
             result,
             address,
             expected,
             desired,
             success_ordering,
             failure_ordering,
-        };
-        self.current_block_mut()?.instructions.push(instruction);
-        Ok(())
+            debug_info: None,
+        })
     }
 
     /// Add an atomic fetch add instruction
@@ -988,14 +1790,13 @@ impl IRBuilder {
         value: Value,
         ordering: AtomicOrdering,
     ) -> Result<(), String> {
-        let instruction = Instruction::AtomicFetchAdd {
+        self.add_instruction(Instruction::AtomicFetchAdd {
             result,
             address,
             value,
             ordering,
-        };
-        self.current_block_mut()?.instructions.push(instruction);
-        Ok(())
+            debug_info: None,
+        })
     }
 
     /// Add an atomic fetch sub instruction
@@ -1006,530 +1807,1926 @@ impl IRBuilder {
         value: Value,
         ordering: AtomicOrdering,
     ) -> Result<(), String> {
-        let instruction = Instruction::AtomicFetchSub {
+        self.add_instruction(Instruction::AtomicFetchSub {
             result,
             address,
             value,
             ordering,
-        };
-        self.current_block_mut()?.instructions.push(instruction);
-        Ok(())
+            debug_info: None,
+        })
+    }
+}
+
+/// Reason `IRGenerator::generate_ir` could not generate a program
+#[derive(Debug, Clone)]
+pub enum IRError {
+    // Expression the analyzer accepts but the generator cannot translate yet, such as a pointer
+    Unsupported {
+        construct: &'static str,
+        position: Position,
+    },
+    // Failure inside the generator or its builder, which is a bug in the compiler
+    Internal(String),
+}
+
+impl IRError {
+    /// Stable catalog code of the error
+    pub fn code(&self) -> &'static str {
+        match self {
+            IRError::Internal(_) => "K0301",
+            IRError::Unsupported { .. } => "K0303",
+        }
+    }
+
+    /// Details that fill the placeholders of the catalog message
+    pub fn arguments(&self) -> Vec<(&'static str, String)> {
+        match self {
+            IRError::Unsupported { construct, .. } => vec![("construct", construct.to_string())],
+            IRError::Internal(_) => Vec::new(),
+        }
+    }
+
+    /// Build the diagnostic reported for the error, in `language`
+    pub fn to_diagnostic(&self, file: &SourceFile, language: Language) -> Diagnostic {
+        match self {
+            IRError::Unsupported { position, .. } => {
+                ErrorMessages::diagnostic(self.code(), &self.arguments(), file.span_at(position), ("", "", ""), language)
+            }
+            // What went wrong is only known in English, and only helps when reporting the bug
+            IRError::Internal(message) => ErrorMessages::unlocated(self.code(), &[], language).with_note(message.as_str()),
+        }
+    }
+}
+
+impl std::fmt::Display for IRError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            IRError::Unsupported { construct, position } => {
+                write!(f, "Unsupported expression at line {}: {}", position.line, construct)
+            }
+            IRError::Internal(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<String> for IRError {
+    fn from(message: String) -> Self {
+        IRError::Internal(message)
     }
 }
 
-/// IR generator that translates AST to IR
+/// IR generator that lowers the typed HIR of an analyzed program to IR
+///
+/// IR types are converted from the types the semantic analyzer inferred, and each variable
+/// records its source type in its debug information, so the LLVM backend and the debugger
+/// see the same types as the analyzer.
 pub struct IRGenerator {
-    module: IRModule,
-    current_function: Option<String>,
-    current_block: Option<String>,
     builder: IRBuilder,
     // Module resolver for handling imports
     module_resolver: ModuleResolver,
+    file_name: String, // source file named in debug information
+    current_function: String, // function whose body is being generated
+    variables: HashMap<SymbolId, String>, // IR name of each variable met so far
+    variable_names: HashSet<String>, // IR names already given to variables
+    classes: HashMap<String, ClassLayout>, // layout of each class the program defines
+    class_methods: HashMap<String, Vec<String>>, // methods each class defines itself, static ones and constructors included
+    interfaces: HashMap<String, InterfaceLayout>, // methods of each interface the program defines
+    enums: HashMap<String, EnumLayout>, // variants of each enum the program defines, and of each specialization of a generic one
+    generic_functions: HashMap<SymbolId, hir::Function>, // generated once for the type arguments of each call
+    generic_classes: HashMap<String, hir::Class>, // laid out and generated once for the type arguments of each instance type
+    generic_enums: HashMap<String, hir::Enum>, // laid out once for the type arguments of each value type
+    specializations: HashSet<String>, // names of the specializations of generic functions generated so far
+    substitution: HashMap<String, crate::types::Type>, // type argument of each type parameter of the specialization being generated
+    lambdas: usize, // lambdas lifted to functions so far, numbering the next one
+    scopes: Vec<Scope>, // loops and `coba` statements around the statement being translated, innermost last
+    tries: usize, // `coba` statements translated so far, numbering the blocks of the next one
+}
+
+/// Statement around the one being translated that a `kembalikan`, `pecah` or `lanjut` may leave
+enum Scope {
+    Loop {
+        next: String, // block `lanjut` continues in
+        exit: String, // block `pecah` continues in
+    },
+    Try {
+        unwind: String, // landing pad of the body or `tangkap` clause being translated
+        finally: Option<Vec<hir::Statement>>, // `akhirnya` block, run on every way out
+    },
+}
+
+impl Default for IRGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl IRGenerator {
     /// Create a new IR generator
     pub fn new() -> Self {
-        IRGenerator {
-            module: IRModule {
-                functions: Vec::new(),
-                global_vars: Vec::new(),
-                debug_info: None, // No module-level debug info initially
-            },
-            current_function: None,
-            current_block: None,
-            builder: IRBuilder::new(),
-            module_resolver: ModuleResolver::new(),
-        }
+        Self::with_module_resolver(ModuleResolver::new())
     }
 
     /// Create a new IR generator with a custom module resolver
     pub fn with_module_resolver(module_resolver: ModuleResolver) -> Self {
         IRGenerator {
-            module: IRModule {
-                functions: Vec::new(),
-                global_vars: Vec::new(),
-                debug_info: None, // No module-level debug info initially
-            },
-            current_function: None,
-            current_block: None,
             builder: IRBuilder::new(),
             module_resolver,
+            file_name: "main.kodeon".to_string(),
+            current_function: String::new(),
+            variables: HashMap::new(),
+            variable_names: HashSet::new(),
+            classes: HashMap::new(),
+            class_methods: HashMap::new(),
+            interfaces: HashMap::new(),
+            enums: HashMap::new(),
+            generic_functions: HashMap::new(),
+            generic_classes: HashMap::new(),
+            generic_enums: HashMap::new(),
+            specializations: HashSet::new(),
+            substitution: HashMap::new(),
+            lambdas: 0,
+            scopes: Vec::new(),
+            tries: 0,
         }
     }
 
-    /// Generate IR from an AST
-    pub fn generate_ir(&mut self, ast: &crate::parser::ASTNode) -> Result<IRModule, String> {
-        self.translate_node(ast)?;
-        Ok(self.builder.get_module().clone())
-    }
-
-    /// Translate an AST node to IR
-    fn translate_node(&mut self, node: &crate::parser::ASTNode) -> Result<Option<Value>, String> {
-        match node {
-            crate::parser::ASTNode::Program(statements) => {
-                // Create main function
-                self.builder.create_function(
-                    "main".to_string(),
-                    vec![],
-                    Type::Int,
-                );
-
-                // Create entry block
-                self.builder.create_block("entry".to_string())?;
-
-                // Translate statements
-                for statement in statements {
-                    self.translate_statement(statement)?;
-                }
-
-                // Add return instruction
-                self.builder.set_terminator(Terminator::Return {
-                    value: Some(Value::Constant(Constant::Int(0))),
-                })?;
-
-                Ok(None)
-            }
-            crate::parser::ASTNode::Number(value) => {
-                Ok(Some(Value::Constant(Constant::Float(*value))))
-            }
-            crate::parser::ASTNode::String(value) => {
-                Ok(Some(Value::Constant(Constant::String(value.clone()))))
-            }
-            crate::parser::ASTNode::Boolean(value) => {
-                Ok(Some(Value::Constant(Constant::Bool(*value))))
+    /// Generate IR from the HIR returned by `SemanticAnalyzer::analyze`
+    pub fn generate_ir(&mut self, program: &hir::Program) -> Result<IRModule, IRError> {
+        // The statements of the program run in `main`; its functions become functions of their own
+        self.builder.create_function("main".to_string(), vec![], Type::Int);
+        self.builder.set_function_debug_info(self.file_name.clone(), 1, 1);
+        self.current_function = "main".to_string();
+        self.builder.create_block("entry".to_string())?;
+
+        // Classes are laid out first, since instances may be used before their class is defined,
+        // after the interfaces whose tables they fill; generic ones wait for their type arguments
+        let mut classes = Vec::new();
+        let mut interfaces = Vec::new();
+        let mut enums = Vec::new();
+        let mut functions = Vec::new();
+        Self::collect_classes(program, &program.body, &mut classes, &mut interfaces, &mut enums, &mut functions);
+        for function in functions {
+            self.generic_functions.insert(function.symbol, function.clone());
+        }
+        for (name, class) in &classes {
+            if !class.type_parameters.is_empty() {
+                self.generic_classes.insert(name.clone(), (*class).clone());
             }
-            crate::parser::ASTNode::Identifier(name, _) => {
-                Ok(Some(Value::Variable(name.clone())))
+        }
+        classes.retain(|(_, class)| class.type_parameters.is_empty());
+        for interface in interfaces {
+            let name = program.symbol(interface.symbol).name.clone();
+            let methods = interface.methods.iter().map(|(method, ty)| (method.clone(), Type::from(ty))).collect();
+            let layout = InterfaceLayout { name: name.clone(), methods };
+            self.interfaces.insert(name, layout.clone());
+            self.builder.get_module_mut().interfaces.push(layout);
+        }
+        for (name, _) in &classes {
+            self.lay_out_class(program, &classes, name);
+        }
+        for enumeration in enums {
+            let name = program.symbol(enumeration.symbol).name.clone();
+            if enumeration.type_parameters.is_empty() {
+                self.lay_out_enum(program, enumeration, name);
+            } else {
+                self.generic_enums.insert(name, enumeration.clone());
             }
-            crate::parser::ASTNode::BinaryOp { left, operator, right, .. } => {
-                let left_val = self.translate_node(left)?.unwrap();
-                let right_val = self.translate_node(right)?.unwrap();
+        }
 
-                let op = match operator {
-                    crate::parser::BinaryOperator::Add => BinaryOp::Add,
-                    crate::parser::BinaryOperator::Subtract => BinaryOp::Sub,
-                    crate::parser::BinaryOperator::Multiply => BinaryOp::Mul,
-                    crate::parser::BinaryOperator::Divide => BinaryOp::Div,
-                    crate::parser::BinaryOperator::Modulo => BinaryOp::Mod,
-                    crate::parser::BinaryOperator::Equal => BinaryOp::Eq,
-                    crate::parser::BinaryOperator::NotEqual => BinaryOp::Ne,
-                    crate::parser::BinaryOperator::Less => BinaryOp::Lt,
-                    crate::parser::BinaryOperator::Greater => BinaryOp::Gt,
-                    crate::parser::BinaryOperator::LessEqual => BinaryOp::Le,
-                    crate::parser::BinaryOperator::GreaterEqual => BinaryOp::Ge,
-                    crate::parser::BinaryOperator::And => BinaryOp::And,
-                    crate::parser::BinaryOperator::Or => BinaryOp::Or,
-                    crate::parser::BinaryOperator::BitAnd => BinaryOp::BitAnd,
-                    crate::parser::BinaryOperator::BitOr => BinaryOp::BitOr,
-                    crate::parser::BinaryOperator::BitXor => BinaryOp::BitXor,
-                    crate::parser::BinaryOperator::LeftShift => BinaryOp::LeftShift,
-                    crate::parser::BinaryOperator::RightShift => BinaryOp::RightShift,
-                    crate::parser::BinaryOperator::Range => BinaryOp::Add, // Simplified for now
-                    crate::parser::BinaryOperator::In => BinaryOp::In,
-                    crate::parser::BinaryOperator::Assign => return Err("Assignment should be handled as statement".to_string()),
-                };
+        self.translate_block(program, &program.body)?;
 
-                let result = self.builder.add_instruction(Instruction::BinaryOp {
-                    result: "".to_string(), // Will be filled by the builder
-                    op,
-                    left: left_val,
-                    right: right_val,
-                })?;
+        self.builder.set_terminator(Terminator::Return {
+            value: Some(Value::Constant(Constant::Int(0))),
+        })?;
+        let mut module = self.builder.take_module();
+        // Statements after a return or throw were given blocks of their own, which nothing branches to
+        module.functions.iter_mut().for_each(Function::remove_unreachable_blocks);
+        module.verify()?;
+        Ok(module)
+    }
 
-                Ok(Some(Value::Variable(result)))
-            }
-            crate::parser::ASTNode::UnaryOp { operator, operand, .. } => {
-                let operand_val = self.translate_node(operand)?.unwrap();
+    /// Debug information for an instruction generated from source at `position`
+    fn debug_info(&self, position: &Position) -> DebugInfo {
+        DebugInfo::new(self.file_name.clone(), position.line, position.column)
+            .with_function(self.current_function.clone())
+    }
 
-                let op = match operator {
-                    crate::parser::UnaryOperator::Negate => UnaryOp::Neg,
-                    crate::parser::UnaryOperator::Not => UnaryOp::Not,
-                    crate::parser::UnaryOperator::Increment => UnaryOp::Increment,
-                    crate::parser::UnaryOperator::Decrement => UnaryOp::Decrement,
-                    crate::parser::UnaryOperator::BitNot => UnaryOp::BitNot,
-                    crate::parser::UnaryOperator::AddressOf => UnaryOp::AddressOf,
-                    crate::parser::UnaryOperator::Dereference => UnaryOp::Dereference,
-                };
+    /// Name of the value a symbol refers to; variables get a name of their own in case they shadow another
+    fn value_name(&mut self, program: &hir::Program, symbol: SymbolId) -> String {
+        let info = program.symbol(symbol);
+        if let SymbolKind::Method { class, .. } = info.kind {
+            return format!("{}.{}", program.symbol(class).name, info.name);
+        }
+        if !matches!(info.kind, SymbolKind::Variable { .. } | SymbolKind::Parameter | SymbolKind::Constant | SymbolKind::Import) {
+            return info.name.clone();
+        }
+        if let Some(name) = self.variables.get(&symbol) {
+            return name.clone();
+        }
+        let name = if self.variable_names.contains(&info.name) {
+            format!("{}.{}", info.name, symbol.0)
+        } else {
+            info.name.clone()
+        };
+        self.variable_names.insert(name.clone());
+        self.variables.insert(symbol, name.clone());
+        name
+    }
 
-                let result = self.builder.add_instruction(Instruction::UnaryOp {
-                    result: "".to_string(), // Will be filled by the builder
-                    op,
-                    operand: operand_val,
-                })?;
+    /// `ty` with the type parameters of the specialization being generated replaced by their arguments
+    fn concrete(&self, ty: &crate::types::Type) -> crate::types::Type {
+        ty.substitute(&self.substitution)
+    }
 
-                Ok(Some(Value::Variable(result)))
-            }
-            crate::parser::ASTNode::FunctionCall { name, arguments, .. } => {
-                let arg_values: Vec<Value> = arguments
-                    .iter()
-                    .map(|arg| self.translate_node(arg))
-                    .collect::<Result<Vec<_>, _>>()?
-                    .into_iter()
-                    .flatten()
-                    .collect();
-
-                let result = self.builder.add_instruction(Instruction::Call {
-                    result: Some("".to_string()), // Will be filled by the builder
-                    function: name.clone(),
-                    arguments: arg_values,
-                })?;
+    /// IR type of a value of type `ty`; values of an interface are references to instances of any class implementing it
+    fn ir_type(&self, ty: &crate::types::Type) -> Type {
+        match Type::from(&self.concrete(ty)) {
+            Type::Object { name } if self.interfaces.contains_key(&name) => Type::Trait { name },
+            ir_type => ir_type,
+        }
+    }
 
-                Ok(Some(Value::Variable(result)))
-            }
-            crate::parser::ASTNode::ArrayLiteral(elements, _) => {
-                // For now, we'll create an array allocation and store each element
-                let element_values: Vec<Value> = elements
-                    .iter()
-                    .map(|elem| self.translate_node(elem))
-                    .collect::<Result<Vec<_>, _>>()?
-                    .into_iter()
-                    .flatten()
-                    .collect();
-
-                // Create an array type (simplified)
-                let array_type = Type::Array {
-                    element_type: Box::new(Type::Int), // Simplified - would determine actual type
-                };
+    /// IR type of the elements of a list of type `ty`
+    fn element_type(&self, ty: &crate::types::Type) -> Type {
+        match self.concrete(ty) {
+            crate::types::Type::List(element) => self.ir_type(&element),
+            _ => self.ir_type(&crate::types::Type::Any),
+        }
+    }
 
-                // Allocate the array
-                let array_var = self.builder.add_instruction(Instruction::Alloca {
-                    result: "".to_string(),
-                    alloca_type: array_type,
-                })?;
+    /// Allocate a variable of the type its symbol was inferred to have; one closures capture by reference
+    /// is shared with them
+    fn allocate(&mut self, program: &hir::Program, symbol: SymbolId, position: &Position) -> Result<String, IRError> {
+        let name = self.value_name(program, symbol);
+        let info = program.symbol(symbol);
+        let alloca_type = self.ir_type(&info.ty);
+        let debug_info = Some(self.debug_info(position).with_type_name(self.concrete(&info.ty).to_string()));
+        self.builder.add_instruction(if info.captured == Some(Capture::ByReference) {
+            Instruction::AllocaShared { result: name.clone(), alloca_type, debug_info }
+        } else {
+            Instruction::Alloca { result: name.clone(), alloca_type, debug_info }
+        })?;
+        Ok(name)
+    }
 
-                // Store each element (simplified)
-                for (i, element) in element_values.iter().enumerate() {
-                    // In a real implementation, we would generate code to store each element
-                    // at the appropriate index in the array
-                }
+    /// Parameters receiving the arguments of a function; one closures capture by reference arrives under
+    /// a name of its own, and `share_parameters` copies it to the variable they share
+    fn parameters(&mut self, program: &hir::Program, symbols: &[SymbolId]) -> Vec<Parameter> {
+        let mut parameters = Vec::new();
+        for &parameter in symbols {
+            let info = program.symbol(parameter);
+            let debug_info = self.debug_info(&info.position).with_type_name(self.concrete(&info.ty).to_string());
+            let mut name = self.value_name(program, parameter);
+            if info.captured == Some(Capture::ByReference) {
+                name = format!("{}.arg", name);
+            }
+            parameters.push(Parameter::new(name, self.ir_type(&info.ty)).with_debug_info(debug_info));
+        }
+        parameters
+    }
 
-                Ok(Some(Value::Variable(array_var)))
+    /// Copy the parameters closures capture by reference to the variables they share
+    fn share_parameters(&mut self, program: &hir::Program, symbols: &[SymbolId], position: &Position) -> Result<(), IRError> {
+        for &parameter in symbols {
+            if program.symbol(parameter).captured == Some(Capture::ByReference) {
+                let variable = self.allocate(program, parameter, position)?;
+                self.store(variable.clone(), Value::Variable(format!("{}.arg", variable)), position)?;
             }
-            crate::parser::ASTNode::ObjectLiteral(properties, _) => {
-                let mut ir_properties = HashMap::new();
+        }
+        Ok(())
+    }
 
-                for (key, value) in properties {
-                    if let Some(val) = self.translate_node(value)? {
-                        ir_properties.insert(key.clone(), val);
-                    }
-                }
+    /// Type of `ini` in the function being generated: its receiver, or the instance its closure captured
+    fn receiver_type(&self) -> Type {
+        let module = self.builder.get_module();
+        let receiver = module.functions.iter()
+            .find(|function| function.name == self.current_function)
+            .and_then(|function| function.parameters.iter().find(|parameter| parameter.name == "ini"))
+            .map(|parameter| parameter.param_type.clone());
+        let captured = module.closures.iter()
+            .find(|closure| closure.function == self.current_function)
+            .and_then(|closure| closure.captures.iter().find(|(name, _, _)| name == "ini"))
+            .map(|(_, ty, _)| ty.clone());
+        receiver.or(captured).unwrap_or_else(|| Type::from(&crate::types::Type::Any))
+    }
 
-                let result = self.builder.add_object_literal(ir_properties)?;
-                Ok(Some(Value::Variable(result)))
-            }
-            crate::parser::ASTNode::RangeExpr { start, end, inclusive, .. } => {
-                let start_val = self.translate_node(start)?.unwrap();
-                let end_val = self.translate_node(end)?.unwrap();
+    /// Lift a lambda to a function taking the environment of its captured variables first, and build the
+    /// closure pairing them
+    fn translate_lambda(&mut self, program: &hir::Program, lambda: &hir::Lambda, ty: &crate::types::Type, position: &Position) -> Result<Value, IRError> {
+        let function = format!("{}.lambda{}", self.current_function, self.lambdas);
+        self.lambdas += 1;
 
-                let result = self.builder.add_range(start_val, end_val, *inclusive)?;
-                Ok(Some(Value::Variable(result)))
-            }
-            crate::parser::ASTNode::ListComprehension { expression, variable, iterable, condition, .. } => {
-                let expr_val = self.translate_node(expression)?.unwrap();
-                let iter_val = self.translate_node(iterable)?.unwrap();
-                let cond_val = if let Some(cond) = condition {
-                    Some(self.translate_node(cond)?.unwrap())
-                } else {
-                    None
-                };
+        let mut captures = Vec::new();
+        if lambda.captures_self {
+            captures.push(("ini".to_string(), self.receiver_type(), Capture::ByValue));
+        }
+        for &symbol in &lambda.captures {
+            let info = program.symbol(symbol);
+            let name = self.value_name(program, symbol);
+            captures.push((name, self.ir_type(&info.ty), info.captured.unwrap_or(Capture::ByValue)));
+        }
+        let layout = ClosureLayout { function: function.clone(), captures };
+        // Laid out before its body, so lambdas nested in it find the instance it captured
+        self.builder.get_module_mut().closures.push(layout.clone());
 
-                let result = self.builder.add_list_comprehension(expr_val, variable.clone(), iter_val, cond_val)?;
-                Ok(Some(Value::Variable(result)))
+        let return_type = match self.ir_type(ty) {
+            Type::Function { return_type, .. } => *return_type,
+            _ => Type::from(&crate::types::Type::Any),
+        };
+        let environment = Type::Object { name: format!("{}.$env", function) };
+        let mut parameters = vec![Parameter::new("$env".to_string(), environment)];
+        parameters.extend(self.parameters(program, &lambda.parameters));
+        self.in_function(function.clone(), parameters, return_type, position, |generator| {
+            for (index, (variable, _, _)) in layout.captures.iter().enumerate() {
+                generator.builder.add_instruction(Instruction::BindCapture {
+                    variable: variable.clone(),
+                    function: function.clone(),
+                    index,
+                    debug_info: Some(generator.debug_info(position)),
+                })?;
             }
-            crate::parser::ASTNode::MemberAccess { object, property, .. } => {
-                let object_val = self.translate_node(object)?.unwrap();
+            generator.share_parameters(program, &lambda.parameters, position)?;
+            generator.translate_block(program, &lambda.body)
+        })?;
 
-                let result = self.builder.add_member_access(object_val, property.clone())?;
-                Ok(Some(Value::Variable(result)))
-            }
-            crate::parser::ASTNode::AwaitExpr(expr, _) => {
-                let expr_val = self.translate_node(expr)?.unwrap();
+        let result = self.builder.fresh_value();
+        self.builder.add_instruction(Instruction::MakeClosure {
+            result: result.clone(),
+            function,
+            captures: layout.captures.into_iter().map(|(name, _, _)| name).collect(),
+            debug_info: Some(self.debug_info(position).with_type_name(self.concrete(ty).to_string())),
+        })?;
+        Ok(Value::Variable(result))
+    }
 
-                let result = self.builder.add_await(expr_val)?;
-                Ok(Some(Value::Variable(result)))
+    /// Instructions `generate` adds, collected instead of added to the current block, and what it returns;
+    /// a return ends them with `Instruction::Return` and a throw with `Instruction::Throw`. They may not
+    /// branch, so only expressions and returns are captured
+    fn capture<R>(&mut self, generate: impl FnOnce(&mut Self) -> Result<R, IRError>) -> Result<(Vec<Instruction>, R), IRError> {
+        let outer_point = self.builder.insertion_point();
+        // Nested instructions are not invoked one by one: the instruction holding them is, when they may throw
+        let unwind_target = self.builder.unwind_target();
+        self.builder.set_unwind_target(None);
+        let block = self.builder.create_block("capture".to_string())?;
+        let result = generate(self);
+        let function = outer_point.0.ok_or("No current function".to_string())?;
+        // A block falls through unless a return or throw ends it
+        let terminated = self.builder.is_terminated((Some(function), Some(block)));
+        self.builder.set_insertion_point(outer_point);
+        self.builder.set_unwind_target(unwind_target);
+        let mut blocks = self.builder.get_module_mut().functions[function].blocks.split_off(block).into_iter();
+        let result = result?;
+
+        // The blocks after the first hold the statements after a return or throw, which never run
+        let captured = blocks.next().ok_or("No captured block".to_string())?;
+        let mut instructions = captured.instructions;
+        match captured.terminator {
+            _ if !terminated => {}
+            Terminator::Return { value } => {
+                instructions.push(Instruction::Return { value, debug_info: captured.debug_info });
             }
-            crate::parser::ASTNode::YieldExpr(expr, _) => {
-                let expr_val = self.translate_node(expr)?.unwrap();
-
-                let result = self.builder.add_yield(expr_val)?;
-                Ok(Some(Value::Variable(result)))
+            Terminator::Unwind { exception, exception_type, .. } => {
+                instructions.push(Instruction::Throw { exception, exception_type, debug_info: captured.debug_info });
             }
-            _ => Err(format!("Unsupported AST node: {:?}", node)),
+            _ => return Err(IRError::Internal("Captured instructions cannot continue in other blocks".to_string())),
         }
+        Ok((instructions, result))
     }
 
-    /// Translate a statement to IR
-    pub fn translate_statement(&mut self, statement: Statement) -> Result<(), ParseError> {
-        match statement.node {
-            crate::parser::Statement::Declaration { identifier, value, mutable, .. } => {
-                // Allocate space for the variable
-                let var_type = Type::Int; // Simplified - would determine actual type in real implementation
+    /// End the current block with `terminator`; the statements after it go to a new block nothing branches to
+    fn end_block(&mut self, terminator: Terminator) -> Result<(), IRError> {
+        self.builder.set_terminator(terminator)?;
+        let after = format!("after{}", self.builder.fresh_value());
+        self.builder.create_block(after)?;
+        Ok(())
+    }
 
-                self.builder.add_instruction(Instruction::Alloca {
-                    result: identifier.clone(),
-                    alloca_type: var_type,
-                })?;
+    /// Translate with `scope` entered
+    fn in_scope<R>(&mut self, scope: Scope, translate: impl FnOnce(&mut Self) -> Result<R, IRError>) -> Result<R, IRError> {
+        self.scopes.push(scope);
+        self.update_unwind_target();
+        let result = translate(self);
+        self.scopes.pop();
+        self.update_unwind_target();
+        result
+    }
+
+    /// Make instructions that may throw unwind to the landing pad of the innermost `coba` statement
+    fn update_unwind_target(&mut self) {
+        let target = self.scopes.iter().rev().find_map(|scope| match scope {
+            Scope::Try { unwind, .. } => Some(unwind.clone()),
+            Scope::Loop { .. } => None,
+        });
+        self.builder.set_unwind_target(target);
+    }
+
+    /// Run the `akhirnya` blocks of the `coba` statements an exit leaves, innermost first: those inside the
+    /// innermost loop for `pecah` and `lanjut`, and all of them for `kembalikan`
+    fn run_finally_blocks(&mut self, program: &hir::Program, leaves_function: bool) -> Result<(), IRError> {
+        for index in (0..self.scopes.len()).rev() {
+            let finally = match &self.scopes[index] {
+                Scope::Loop { .. } if !leaves_function => break,
+                Scope::Try { finally: Some(finally), .. } => finally.clone(),
+                _ => continue,
+            };
+            // A finally block runs outside the statement it belongs to
+            let inner = self.scopes.split_off(index);
+            self.update_unwind_target();
+            let result = self.translate_block(program, &finally);
+            self.scopes.extend(inner);
+            self.update_unwind_target();
+            result?;
+        }
+        Ok(())
+    }
 
-                // Evaluate the value
-                let value_ref = self.translate_node(value)?;
-                if let Some(val) = value_ref {
-                    // Store the value in the variable
-                    self.builder.add_instruction(Instruction::Store {
-                        variable: identifier.clone(),
-                        value: val,
+    /// Translate a `coba` statement. Its body runs with calls invoked, unwinding to a landing pad that tests the
+    /// class of the error against each `tangkap` clause in turn; its `akhirnya` block is copied to each way out:
+    /// the end of the body and of each clause, each exit (`Scope`), and the pads that keep unwinding
+    fn translate_try(
+        &mut self,
+        program: &hir::Program,
+        try_block: &[hir::Statement],
+        catches: &[hir::Catch],
+        finally_block: &Option<Vec<hir::Statement>>,
+        position: &Position,
+    ) -> Result<(), IRError> {
+        let id = self.tries;
+        self.tries += 1;
+        let label = |part: &str| format!("try{}.{}", id, part);
+        let outer_unwind = self.builder.unwind_target();
+
+        let scope = Scope::Try { unwind: label("landing"), finally: finally_block.clone() };
+        self.in_scope(scope, |generator| generator.translate_block(program, try_block))?;
+        self.translate_finally(program, finally_block)?;
+        self.builder.set_terminator(Terminator::Branch { target: label("end") })?;
+
+        self.builder.create_block(label("landing"))?;
+        let exception = self.landing_pad(position)?;
+        let mut unhandled = true;
+        for (index, catch) in catches.iter().enumerate() {
+            let body = label(&format!("catch{}", index));
+            let next = if index + 1 < catches.len() { label(&format!("test{}", index + 1)) } else { label("unhandled") };
+            match &catch.class {
+                Some(class) => {
+                    let matches = self.builder.fresh_value();
+                    self.builder.add_instruction(Instruction::ExceptionIs {
+                        result: matches.clone(),
+                        exception: exception.clone(),
+                        class: class.clone(),
+                        debug_info: Some(self.debug_info(&catch.position)),
                     })?;
-                }
-                Ok(())
-            }
-            crate::parser::Statement::Assignment { identifier, value, .. } => {
-                // Evaluate the value
-                let value_ref = self.translate_node(value)?;
-                if let Some(val) = value_ref {
-                    // Store the value in the variable
-                    self.builder.add_instruction(Instruction::Store {
-                        variable: identifier.clone(),
-                        value: val,
+                    self.builder.set_terminator(Terminator::ConditionalBranch {
+                        condition: Value::Variable(matches),
+                        then_target: body.clone(),
+                        else_target: next.clone(),
                     })?;
                 }
-                Ok(())
-            }
-            crate::parser::Statement::Expression(expr) => {
-                self.translate_node(expr)?;
-                Ok(())
-            }
-            crate::parser::Statement::FunctionDef { name, parameters, body, access_modifier, is_static, is_async, .. } => {
-                // Create function parameters
-                let params: Vec<Parameter> = parameters
-                    .iter()
-                    .map(|param| Parameter {
-                        name: param.clone(),
-                        param_type: Type::Int, // Simplified - would determine actual type in real implementation
-                    })
-                    .collect();
-
-                // Determine return type (simplified)
-                let return_type = if *is_async {
-                    Type::Async { inner_type: Box::new(Type::Int) }
-                } else {
-                    Type::Int
-                };
-
-                // Create function
-                self.builder.create_function(
-                    name.clone(),
-                    params,
-                    return_type,
-                );
-
-                // Create entry block
-                self.builder.create_block("entry".to_string())?;
-
-                // Translate function body
-                for stmt in body {
-                    self.translate_statement(stmt)?;
+                // A clause without a class catches every error, so none reaches the clauses after it
+                None => {
+                    self.builder.set_terminator(Terminator::Branch { target: body.clone() })?;
+                    unhandled = false;
                 }
+            }
 
-                // Add default return for now
-                self.builder.set_terminator(Terminator::Return {
-                    value: Some(Value::Constant(Constant::Int(0))),
-                })?;
-
-                Ok(())
+            self.builder.create_block(body)?;
+            let value = self.builder.fresh_value();
+            self.builder.add_instruction(Instruction::Catch {
+                result: value.clone(),
+                exception: exception.clone(),
+                debug_info: Some(self.debug_info(&catch.position)),
+            })?;
+            if let Some(variable) = catch.variable {
+                let variable = self.allocate(program, variable, &catch.position)?;
+                self.store(variable, Value::Variable(value), &catch.position)?;
             }
-            crate::parser::Statement::ClassDef { name, body, access_modifier, parent_class, .. } => {
-                // In IR, classes are represented as collections of functions and data
-                // For now, we'll just process the body statements
-                for stmt in body {
-                    self.translate_statement(stmt)?;
+            // Errors the clause throws still run the finally block, in a pad of their own
+            match finally_block {
+                Some(finally) => {
+                    let scope = Scope::Try { unwind: label("cleanup"), finally: Some(finally.clone()) };
+                    self.in_scope(scope, |generator| generator.translate_block(program, &catch.body))?;
                 }
-                Ok(())
+                None => self.translate_block(program, &catch.body)?,
             }
-            crate::parser::Statement::ReturnStmt(expr) => {
-                let value_ref = self.translate_node(expr)?;
-                self.builder.set_terminator(Terminator::Return {
-                    value: value_ref,
-                })?;
-                Ok(())
+            self.translate_finally(program, finally_block)?;
+            self.builder.set_terminator(Terminator::Branch { target: label("end") })?;
+            if !unhandled {
+                break;
             }
-            crate::parser::Statement::IfStatement { condition, then_block, else_block, .. } => {
-                let condition_val = self.translate_node(condition)?;
-
-                // For now, we'll just translate the then block
-                // A full implementation would need to generate proper control flow
-                for stmt in then_block {
-                    self.translate_statement(stmt)?;
-                }
+            self.builder.create_block(next)?;
+        }
 
-                if let Some(else_stmts) = else_block {
-                    for stmt in else_stmts {
-                        self.translate_statement(stmt)?;
-                    }
-                }
+        // An error no clause catches runs the finally block and keeps unwinding
+        if unhandled {
+            self.translate_finally(program, finally_block)?;
+            self.builder.set_terminator(Terminator::Resume { exception, unwind: outer_unwind.clone() })?;
+        }
+        if finally_block.is_some() && !catches.is_empty() {
+            self.builder.create_block(label("cleanup"))?;
+            let exception = self.landing_pad(position)?;
+            self.translate_finally(program, finally_block)?;
+            self.builder.set_terminator(Terminator::Resume { exception, unwind: outer_unwind })?;
+        }
+        self.builder.create_block(label("end"))?;
+        Ok(())
+    }
 
-                Ok(())
-            }
-            crate::parser::Statement::WhileLoop { condition, body, .. } => {
-                let condition_val = self.translate_node(condition)?;
+    /// Bind the error being thrown at the start of a block invokes unwind to
+    fn landing_pad(&mut self, position: &Position) -> Result<Value, IRError> {
+        let exception = self.builder.fresh_value();
+        self.builder.add_instruction(Instruction::LandingPad {
+            result: exception.clone(),
+            debug_info: Some(self.debug_info(position)),
+        })?;
+        Ok(Value::Variable(exception))
+    }
 
-                // For now, we'll just translate the body
-                // A full implementation would need to generate proper control flow
-                for stmt in body {
-                    self.translate_statement(stmt)?;
-                }
+    /// Translate the `akhirnya` block of a `coba` statement, if it has one
+    fn translate_finally(&mut self, program: &hir::Program, finally_block: &Option<Vec<hir::Statement>>) -> Result<(), IRError> {
+        match finally_block {
+            Some(finally) => self.translate_block(program, finally),
+            None => Ok(()),
+        }
+    }
 
-                Ok(())
-            }
-            crate::parser::Statement::ForLoop { variable, start, end, body, .. } => {
-                // Translate initialization
-                let start_val = self.translate_node(start)?;
-                if let Some(val) = start_val {
-                    self.builder.add_instruction(Instruction::Store {
-                        variable: variable.clone(),
-                        value: val,
-                    })?;
-                }
+    /// Translate a `jika` statement: the condition branches to the `then` block, or to the `else` block if
+    /// there is one, and both continue at the end
+    fn translate_if(
+        &mut self,
+        program: &hir::Program,
+        condition: &hir::Expression,
+        then_block: &[hir::Statement],
+        else_block: Option<&[hir::Statement]>,
+    ) -> Result<(), IRError> {
+        let id = self.builder.fresh_value();
+        let label = |part: &str| format!("if{}.{}", id, part);
+        let condition = self.translate_expression(program, condition)?;
+        let else_target = if else_block.is_some() { label("else") } else { label("end") };
+        self.builder.set_terminator(Terminator::ConditionalBranch { condition, then_target: label("then"), else_target })?;
+
+        self.builder.create_block(label("then"))?;
+        self.translate_block(program, then_block)?;
+        self.builder.set_terminator(Terminator::Branch { target: label("end") })?;
+        if let Some(else_block) = else_block {
+            self.builder.create_block(label("else"))?;
+            self.translate_block(program, else_block)?;
+            self.builder.set_terminator(Terminator::Branch { target: label("end") })?;
+        }
+        self.builder.create_block(label("end"))?;
+        Ok(())
+    }
 
-                // For now, we'll just translate the body
-                // A full implementation would need to generate proper control flow
-                for stmt in body {
-                    self.translate_statement(stmt)?;
-                }
+    /// Translate a `ketika` statement: the match continues in the block of the first case whose pattern
+    /// matches, or of the `sebaliknya` case, and each case continues at the end
+    fn translate_when(
+        &mut self,
+        program: &hir::Program,
+        value: &hir::Expression,
+        cases: &[hir::MatchCase],
+        else_case: Option<&[hir::Statement]>,
+    ) -> Result<(), IRError> {
+        let id = self.builder.fresh_value();
+        let label = |part: &str| format!("match{}.{}", id, part);
+        let expression = self.translate_expression(program, value)?;
+        let mut arms = Vec::new();
+        let mut bodies = Vec::new();
+        for (index, case) in cases.iter().enumerate() {
+            // The variables a pattern binds are allocated before the match, which stores to them
+            let pattern = self.translate_pattern(program, &case.pattern)?;
+            let guard = match &case.guard {
+                Some(guard) => Some(self.capture(|generator| generator.translate_expression(program, guard))?),
+                None => None,
+            };
+            let target = label(&format!("case{}", index));
+            bodies.push((target.clone(), case.body.as_slice()));
+            arms.push(MatchArm { pattern, guard, target });
+        }
+        let default = else_case.map(|else_case| {
+            bodies.push((label("else"), else_case));
+            label("else")
+        });
+        self.builder.set_terminator(Terminator::Match { expression, arms, default })?;
 
-                Ok(())
-            }
-            crate::parser::Statement::ForEachLoop { variable, iterable, body, .. } => {
-                let iterable_val = self.translate_node(iterable)?;
+        for (target, body) in bodies {
+            self.builder.create_block(target)?;
+            self.translate_block(program, body)?;
+            self.builder.set_terminator(Terminator::Branch { target: label("end") })?;
+        }
+        self.builder.create_block(label("end"))?;
+        Ok(())
+    }
 
-                if let Some(val) = iterable_val {
-                    let instruction = Instruction::ForEachLoop {
-                        variable: variable.clone(),
-                        iterable: val,
-                        body: vec![], // Simplified - would generate actual body instructions
-                    };
-                    self.builder.add_instruction(instruction)?;
-                }
+    /// Translate a `selama` loop, or with `test_first` false an `ulangi` loop, whose body runs once before the
+    /// condition is tested. `lanjut` continues at the condition
+    fn translate_while(&mut self, program: &hir::Program, condition: &hir::Expression, body: &[hir::Statement], test_first: bool) -> Result<(), IRError> {
+        let id = self.builder.fresh_value();
+        let label = |part: &str| format!("loop{}.{}", id, part);
+        let first = if test_first { label("condition") } else { label("body") };
+        self.builder.set_terminator(Terminator::Branch { target: first })?;
+
+        self.builder.create_block(label("body"))?;
+        self.loop_body(program, body, label("condition"), label("end"))?;
+        self.builder.create_block(label("condition"))?;
+        let condition = self.translate_expression(program, condition)?;
+        self.builder.set_terminator(Terminator::ConditionalBranch { condition, then_target: label("body"), else_target: label("end") })?;
+        self.builder.create_block(label("end"))?;
+        Ok(())
+    }
 
-                // For now, we'll just translate the body
-                for stmt in body {
-                    self.translate_statement(stmt)?;
-                }
+    /// Translate an `untuk` loop, which counts its variable from `start` to `end`, both included, down when
+    /// `descending`. The end is computed once, before the loop, and `lanjut` continues at the step
+    #[allow(clippy::too_many_arguments)]
+    fn translate_for(
+        &mut self,
+        program: &hir::Program,
+        variable: SymbolId,
+        start: &hir::Expression,
+        end: &hir::Expression,
+        descending: bool,
+        body: &[hir::Statement],
+        position: &Position,
+    ) -> Result<(), IRError> {
+        let id = self.builder.fresh_value();
+        let label = |part: &str| format!("loop{}.{}", id, part);
+        let variable = self.allocate(program, variable, position)?;
+        let start = self.translate_expression(program, start)?;
+        self.store(variable.clone(), start, position)?;
+        let limit = self.temporary(&end.ty, position)?;
+        let end = self.translate_expression(program, end)?;
+        self.store(limit.clone(), end, position)?;
+        self.builder.set_terminator(Terminator::Branch { target: label("condition") })?;
+
+        let (step, test) = if descending { (BinaryOp::Sub, BinaryOp::Ge) } else { (BinaryOp::Add, BinaryOp::Le) };
+        self.builder.create_block(label("body"))?;
+        self.loop_body(program, body, label("step"), label("end"))?;
+        self.builder.create_block(label("step"))?;
+        let next = self.binary(step, Value::Variable(variable.clone()), Value::Constant(Constant::Int(1)), position)?;
+        self.store(variable.clone(), next, position)?;
+        self.builder.set_terminator(Terminator::Branch { target: label("condition") })?;
+        self.builder.create_block(label("condition"))?;
+        let condition = self.binary(test, Value::Variable(variable), Value::Variable(limit), position)?;
+        self.builder.set_terminator(Terminator::ConditionalBranch { condition, then_target: label("body"), else_target: label("end") })?;
+        self.builder.create_block(label("end"))?;
+        Ok(())
+    }
 
-                Ok(())
+    /// Translate an `untuk setiap` loop, which gives its variable each element of the iterable in turn, by
+    /// index. The iterable and its length are computed once, before the loop, and `lanjut` continues at the step
+    fn translate_for_each(
+        &mut self,
+        program: &hir::Program,
+        variable: SymbolId,
+        iterable: &hir::Expression,
+        body: &[hir::Statement],
+        position: &Position,
+    ) -> Result<(), IRError> {
+        let id = self.builder.fresh_value();
+        let label = |part: &str| format!("loop{}.{}", id, part);
+        let element_type = program.symbol(variable).ty.clone();
+        let variable = self.allocate(program, variable, position)?;
+        let items = self.temporary(&iterable.ty, position)?;
+        let value = self.translate_expression(program, iterable)?;
+        self.store(items.clone(), value, position)?;
+        let index = self.temporary(&crate::types::Type::Int, position)?;
+        self.store(index.clone(), Value::Constant(Constant::Int(0)), position)?;
+        let length = self.call_value("length", vec![Value::Variable(items.clone())], &crate::types::Type::Int, position)?;
+        self.builder.set_terminator(Terminator::Branch { target: label("condition") })?;
+
+        self.builder.create_block(label("body"))?;
+        let arguments = vec![Value::Variable(items), Value::Variable(index.clone())];
+        let element = self.call_value("get_index", arguments, &element_type, position)?;
+        self.store(variable, element, position)?;
+        self.loop_body(program, body, label("step"), label("end"))?;
+        self.builder.create_block(label("step"))?;
+        let next = self.binary(BinaryOp::Add, Value::Variable(index.clone()), Value::Constant(Constant::Int(1)), position)?;
+        self.store(index.clone(), next, position)?;
+        self.builder.set_terminator(Terminator::Branch { target: label("condition") })?;
+        self.builder.create_block(label("condition"))?;
+        let condition = self.binary(BinaryOp::Lt, Value::Variable(index), length, position)?;
+        self.builder.set_terminator(Terminator::ConditionalBranch { condition, then_target: label("body"), else_target: label("end") })?;
+        self.builder.create_block(label("end"))?;
+        Ok(())
+    }
+
+    /// Translate the body of a loop from the current block, continuing in `next` at its end; `lanjut`
+    /// continues in `next` too, and `pecah` in `exit`
+    fn loop_body(&mut self, program: &hir::Program, body: &[hir::Statement], next: String, exit: String) -> Result<(), IRError> {
+        let scope = Scope::Loop { next: next.clone(), exit };
+        self.in_scope(scope, |generator| generator.translate_block(program, body))?;
+        self.builder.set_terminator(Terminator::Branch { target: next }).map_err(IRError::from)
+    }
+
+    /// Pattern of a case; the variables it binds are allocated where the match is
+    fn translate_pattern(&mut self, program: &hir::Program, pattern: &hir::Pattern) -> Result<Pattern, IRError> {
+        Ok(match &pattern.kind {
+            hir::PatternKind::Wildcard => Pattern::Wildcard,
+            hir::PatternKind::Binding(symbol) => Pattern::Binding(self.allocate(program, *symbol, &pattern.position)?),
+            hir::PatternKind::Literal(literal) => Pattern::Constant(match literal {
+                LiteralPattern::Integer(value) => Constant::Int(*value),
+                LiteralPattern::Float(value) => Constant::Float(*value),
+                LiteralPattern::String(value) => Constant::String(value.clone()),
+                LiteralPattern::Boolean(value) => Constant::Bool(*value),
+                LiteralPattern::Null => Constant::Null,
+            }),
+            hir::PatternKind::Range { start, end } => Pattern::Range(*start, *end),
+            hir::PatternKind::Tuple(elements) => {
+                let mut patterns = Vec::new();
+                for element in elements {
+                    patterns.push(self.translate_pattern(program, element)?);
+                }
+                Pattern::Tuple(patterns)
             }
-            crate::parser::Statement::TryCatch { try_block, catch_block, finally_block, .. } => {
-                // For now, we'll just translate the try block
-                for stmt in try_block {
-                    self.translate_statement(stmt)?;
+            hir::PatternKind::Variant { enumeration, index, fields } => {
+                let mut patterns = Vec::new();
+                for field in fields {
+                    patterns.push(self.translate_pattern(program, field)?);
                 }
+                let enumeration = self.enum_of(program, &pattern.ty)
+                    .unwrap_or_else(|| program.symbol(*enumeration).name.clone());
+                Pattern::Variant { enumeration, index: *index, fields: patterns }
+            }
+        })
+    }
+
+    /// Translate the statements of a block
+    fn translate_block(&mut self, program: &hir::Program, statements: &[hir::Statement]) -> Result<(), IRError> {
+        for statement in statements {
+            self.translate_statement(program, statement)?;
+        }
+        Ok(())
+    }
 
-                // Then the catch block
-                for stmt in catch_block {
-                    self.translate_statement(stmt)?;
+    /// Classes, interfaces, enums and generic functions defined in `statements` and in the bodies nested in
+    /// them, in source order
+    fn collect_classes<'a>(
+        program: &hir::Program,
+        statements: &'a [hir::Statement],
+        classes: &mut Vec<(String, &'a hir::Class)>,
+        interfaces: &mut Vec<&'a hir::Interface>,
+        enums: &mut Vec<&'a hir::Enum>,
+        generic_functions: &mut Vec<&'a hir::Function>,
+    ) {
+        for statement in statements {
+            match &statement.kind {
+                hir::StatementKind::Class(class) => {
+                    classes.push((program.symbol(class.symbol).name.clone(), class));
+                    Self::collect_classes(program, &class.body, classes, interfaces, enums, generic_functions);
                 }
+                hir::StatementKind::Interface(interface) => interfaces.push(interface),
+                hir::StatementKind::Enum(enumeration) => enums.push(enumeration),
+                hir::StatementKind::Function(function) => {
+                    // Methods are dispatched on their instance, so only functions are specialized
+                    if !function.type_parameters.is_empty() && program.symbol(function.symbol).kind == SymbolKind::Function {
+                        generic_functions.push(function);
+                    }
+                    Self::collect_classes(program, &function.body, classes, interfaces, enums, generic_functions);
+                }
+                _ => {}
+            }
+        }
+    }
 
-                // Then the finally block if it exists
-                if let Some(finally_stmts) = finally_block {
-                    for stmt in finally_stmts {
-                        self.translate_statement(stmt)?;
+    /// Lay out class `name`, after the classes it extends
+    fn lay_out_class(&mut self, program: &hir::Program, classes: &[(String, &hir::Class)], name: &str) {
+        let Some((_, class)) = classes.iter().find(|(class, _)| class == name) else {
+            return;
+        };
+        if self.classes.contains_key(name) {
+            return;
+        }
+        // A class extending itself, directly or not, sees its own layout empty instead of recursing
+        self.classes.insert(name.to_string(), ClassLayout {
+            name: name.to_string(),
+            parent: None,
+            fields: Vec::new(),
+            methods: Vec::new(),
+            interfaces: Vec::new(),
+        });
+        let parent = class.parent.map(|parent| program.symbol(parent).name.clone());
+        if let Some(parent) = &parent {
+            self.lay_out_class(program, classes, parent);
+        }
+        let mut layout = match parent.as_ref().and_then(|parent| self.classes.get(parent)) {
+            Some(parent_layout) => ClassLayout {
+                name: name.to_string(),
+                parent: parent.clone(),
+                fields: parent_layout.fields.clone(),
+                methods: parent_layout.methods.clone(),
+                interfaces: Vec::new(),
+            },
+            // Instances of a class extending one defined elsewhere only have the fields they declare
+            None => ClassLayout {
+                name: name.to_string(),
+                parent: None,
+                fields: Vec::new(),
+                methods: Vec::new(),
+                interfaces: Vec::new(),
+            },
+        };
+
+        let mut methods = Vec::new();
+        for member in &class.body {
+            match &member.kind {
+                hir::StatementKind::Let { symbol, .. } => {
+                    let field = program.symbol(*symbol);
+                    if matches!(field.kind, SymbolKind::Field { .. }) && layout.field_index(&field.name).is_none() {
+                        layout.fields.push((field.name.clone(), self.ir_type(&field.ty)));
+                    }
+                }
+                hir::StatementKind::Function(method) => {
+                    let method_name = program.symbol(method.symbol).name.clone();
+                    // Constructors and static methods are called directly, so they get no slot
+                    if !method.is_static && !Self::is_constructor(&method_name) {
+                        let function = format!("{}.{}", name, method_name);
+                        match layout.method_slot(&method_name) {
+                            Some(slot) => layout.methods[slot].1 = function,
+                            None => layout.methods.push((method_name.clone(), function)),
+                        }
                     }
+                    methods.push(method_name);
                 }
+                _ => {}
+            }
+        }
+
+        self.class_methods.insert(name.to_string(), methods);
+        self.classes.insert(name.to_string(), layout.clone()); // methods are looked up through the parent
+
+        // The tables of inherited interfaces are filled again, with the overrides of this class
+        let mut interfaces: Vec<String> = parent.as_ref()
+            .and_then(|parent| self.classes.get(parent))
+            .map(|parent_layout| parent_layout.interfaces.iter().map(|(interface, _)| interface.clone()).collect())
+            .unwrap_or_default();
+        for &interface in &class.interfaces {
+            let interface = &program.symbol(interface).name;
+            if !interfaces.contains(interface) {
+                interfaces.push(interface.clone());
+            }
+        }
+        for interface in interfaces {
+            let Some(interface_layout) = self.interfaces.get(&interface) else {
+                continue;
+            };
+            // The analyzer checked that the class has every method of the interface
+            let functions = interface_layout.methods.iter()
+                .filter_map(|(method, _)| self.implementation(name, method))
+                .collect();
+            layout.interfaces.push((interface, functions));
+        }
+
+        self.classes.insert(name.to_string(), layout.clone());
+        self.builder.get_module_mut().classes.push(layout);
+    }
+
+    /// Lay out enum `enumeration` as `name`, with its fields in terms of the type arguments being generated
+    fn lay_out_enum(&mut self, program: &hir::Program, enumeration: &hir::Enum, name: String) {
+        let variants = enumeration.variants.iter()
+            .map(|variant| (program.symbol(variant.symbol).name.clone(), variant.fields.iter().map(|field| self.ir_type(field)).collect()))
+            .collect();
+        let layout = EnumLayout { name: name.clone(), variants };
+        self.enums.insert(name, layout.clone());
+        self.builder.get_module_mut().enums.push(layout);
+    }
+
+    /// Name of the layout of the enum of values of type `ty`; a value of a generic enum is one of its
+    /// specialization, e.g. `Pilihan<int>`, laid out on first use
+    fn enum_of(&mut self, program: &hir::Program, ty: &crate::types::Type) -> Option<String> {
+        let concrete = self.concrete(ty);
+        let crate::types::Type::Named { name, arguments } = concrete.non_optional() else {
+            return None;
+        };
+        if self.enums.contains_key(name) {
+            return Some(name.clone());
+        }
+        let generic = self.generic_enums.get(name)?.clone();
+        let specialization = concrete.non_optional().to_string();
+        if !self.enums.contains_key(&specialization) {
+            let substitution = generic.type_parameters.iter().cloned().zip(arguments.iter().cloned()).collect();
+            let outer = std::mem::replace(&mut self.substitution, substitution);
+            self.lay_out_enum(program, &generic, specialization.clone());
+            self.substitution = outer;
+        }
+        Some(specialization)
+    }
 
+    /// Whether a method named `name` constructs the instances of its class
+    fn is_constructor(name: &str) -> bool {
+        name == "baru" || name == "new"
+    }
+
+    /// Function run for method `method` of class `class`: its own, or the one of its nearest ancestor
+    fn implementation(&self, class: &str, method: &str) -> Option<String> {
+        let mut visited = HashSet::new();
+        let mut current = class.to_string();
+        while visited.insert(current.clone()) {
+            if self.class_methods.get(&current)?.iter().any(|name| name == method) {
+                return Some(format!("{}.{}", current, method));
+            }
+            current = self.classes.get(&current)?.parent.clone()?;
+        }
+        None
+    }
+
+    /// Class and index of field `field` of a value of type `object`, if it is an instance of a laid out class
+    fn field_index(&mut self, program: &hir::Program, object: &crate::types::Type, field: &str) -> Result<Option<(String, usize)>, IRError> {
+        let Some(class) = self.class_of(program, object)? else {
+            return Ok(None);
+        };
+        let index = self.classes.get(&class).and_then(|layout| layout.field_index(field));
+        Ok(index.map(|index| (class, index)))
+    }
+
+    /// Name of the layout of the class or interface of values of type `ty`; an instance of a generic class
+    /// is one of its specialization, e.g. `Kotak<int>`, generated on first use
+    fn class_of(&mut self, program: &hir::Program, ty: &crate::types::Type) -> Result<Option<String>, IRError> {
+        match self.concrete(ty).non_optional() {
+            crate::types::Type::Named { name, arguments } if self.generic_classes.contains_key(name) => {
+                self.specialize_class(program, name, arguments).map(Some)
+            }
+            crate::types::Type::Named { name, .. } => Ok(Some(name.clone())),
+            _ => Ok(None),
+        }
+    }
+
+    /// Lay out and generate generic class `class` for the type arguments `arguments`, unless it was already,
+    /// and return the name of the specialization
+    fn specialize_class(&mut self, program: &hir::Program, class: &str, arguments: &[crate::types::Type]) -> Result<String, IRError> {
+        let name = crate::types::Type::Named { name: class.to_string(), arguments: arguments.to_vec() }.to_string();
+        let Some(generic) = self.generic_classes.get(class).cloned() else {
+            return Ok(name);
+        };
+        if self.classes.contains_key(&name) {
+            return Ok(name);
+        }
+        let substitution = generic.type_parameters.iter().cloned().zip(arguments.iter().cloned()).collect();
+        let outer = std::mem::replace(&mut self.substitution, substitution);
+        self.lay_out_class(program, &[(name.clone(), &generic)], &name);
+        let position = program.symbol(generic.symbol).position.clone();
+        let result = self.translate_class(program, &generic, &name, &position);
+        self.substitution = outer;
+        result.map(|_| name)
+    }
+
+    /// Generate generic function `symbol` for the type arguments `arguments`, unless it was already, and
+    /// return the name of the specialization, e.g. `maks<int>`
+    fn specialize_function(&mut self, program: &hir::Program, symbol: SymbolId, arguments: &[crate::types::Type]) -> Result<String, IRError> {
+        let Some(generic) = self.generic_functions.get(&symbol).cloned() else {
+            return Ok(self.value_name(program, symbol));
+        };
+        let arguments_text: Vec<String> = arguments.iter().map(|argument| argument.to_string()).collect();
+        let name = format!("{}<{}>", program.symbol(symbol).name, arguments_text.join(", "));
+        // Inserted before the body is generated, so a recursive call finds it
+        if !self.specializations.insert(name.clone()) {
+            return Ok(name);
+        }
+        let substitution = generic.type_parameters.iter().cloned().zip(arguments.iter().cloned()).collect();
+        let outer = std::mem::replace(&mut self.substitution, substitution);
+        let position = program.symbol(symbol).position.clone();
+        let result = self.translate_function(program, &generic, name.clone(), None, &position);
+        self.substitution = outer;
+        result.map(|_| name)
+    }
+
+    /// Generate a function named `name`, whose body `body` generates
+    fn in_function(
+        &mut self,
+        name: String,
+        parameters: Vec<Parameter>,
+        return_type: Type,
+        position: &Position,
+        body: impl FnOnce(&mut Self) -> Result<(), IRError>,
+    ) -> Result<(), IRError> {
+        let outer_point = self.builder.insertion_point();
+        let outer_function = std::mem::replace(&mut self.current_function, name.clone());
+        // Exits and errors do not leave a function through the statements around its definition
+        let outer_scopes = std::mem::take(&mut self.scopes);
+        let outer_unwind_target = self.builder.unwind_target();
+        self.builder.set_unwind_target(None);
+        self.builder.create_function(name, parameters, return_type);
+        self.builder.set_function_debug_info(self.file_name.clone(), position.line, position.column);
+
+        let result = self.builder.create_block("entry".to_string()).map_err(IRError::from).and_then(|_| body(self));
+
+        self.current_function = outer_function;
+        self.scopes = outer_scopes;
+        self.builder.set_unwind_target(outer_unwind_target);
+        self.builder.set_insertion_point(outer_point);
+        result
+    }
+
+    /// Parameter `ini` through which a method of class `class` receives its instance
+    fn receiver(&self, class: &str, position: &Position) -> Parameter {
+        let debug_info = self.debug_info(position).with_type_name(class.to_string());
+        Parameter::new("ini".to_string(), Type::Object { name: class.to_string() }).with_debug_info(debug_info)
+    }
+
+    /// Translate a function, or a method when `name` is qualified by its class; a method that is not
+    /// static receives its instance as `receiver`
+    fn translate_function(
+        &mut self,
+        program: &hir::Program,
+        function: &hir::Function,
+        name: String,
+        receiver: Option<&str>,
+        position: &Position,
+    ) -> Result<(), IRError> {
+        let return_type = match &program.symbol(function.symbol).ty {
+            crate::types::Type::Function { return_type, .. } => self.ir_type(return_type),
+            _ => Type::from(&crate::types::Type::Any),
+        };
+        let return_type = if function.is_async {
+            Type::Async { inner_type: Box::new(return_type) }
+        } else {
+            return_type
+        };
+
+        let mut parameters = Vec::new();
+        if let Some(class) = receiver {
+            parameters.push(self.receiver(class, position));
+        }
+        parameters.extend(self.parameters(program, &function.parameters));
+        self.in_function(name, parameters, return_type, position, |generator| {
+            generator.share_parameters(program, &function.parameters, position)?;
+            generator.translate_block(program, &function.body)
+        })
+    }
+
+    /// Generate `Kelas.$init`, which gives the fields of a new instance their initial values, starting
+    /// with the fields it inherits
+    fn translate_initializer(&mut self, program: &hir::Program, class: &hir::Class, class_name: &str, position: &Position) -> Result<(), IRError> {
+        let parameters = vec![self.receiver(class_name, position)];
+        let parent = self.classes.get(class_name).and_then(|layout| layout.parent.clone());
+        self.in_function(format!("{}.$init", class_name), parameters, Type::Void, position, |generator| {
+            if let Some(parent) = parent {
+                generator.call(None, &format!("{}.$init", parent), vec![Value::Variable("ini".to_string())], position)?;
+            }
+            for member in &class.body {
+                let hir::StatementKind::Let { symbol, value: Some(value) } = &member.kind else {
+                    continue;
+                };
+                let object_type = crate::types::Type::named(class_name);
+                if let Some((class, index)) = generator.field_index(program, &object_type, &program.symbol(*symbol).name)? {
+                    let value = generator.translate_expression(program, value)?;
+                    generator.builder.add_instruction(Instruction::SetField {
+                        object: Value::Variable("ini".to_string()),
+                        class,
+                        index,
+                        value,
+                        debug_info: Some(generator.debug_info(&member.position)),
+                    })?;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Translate a class: methods become functions named after it, and field initializers run in `Kelas.$init`
+    fn translate_class(&mut self, program: &hir::Program, class: &hir::Class, class_name: &str, position: &Position) -> Result<(), IRError> {
+        self.translate_initializer(program, class, class_name, position)?;
+        for member in &class.body {
+            if let hir::StatementKind::Function(method) = &member.kind {
+                let name = format!("{}.{}", class_name, program.symbol(method.symbol).name);
+                let receiver = (!method.is_static).then_some(class_name);
+                self.translate_function(program, method, name, receiver, &member.position)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Translate a statement to IR
+    fn translate_statement(&mut self, program: &hir::Program, statement: &hir::Statement) -> Result<(), IRError> {
+        let position = &statement.position;
+        match &statement.kind {
+            hir::StatementKind::Let { symbol, value } => {
+                let variable = self.allocate(program, *symbol, position)?;
+                if let Some(value) = value {
+                    let value = self.translate_expression(program, value)?;
+                    self.store(variable, value, position)?;
+                }
                 Ok(())
             }
-            crate::parser::Statement::BreakStmt => {
-                // Break statements would need proper control flow handling
+            // Reads of a constant were replaced by its value; the global keeps it for debuggers and other modules
+            hir::StatementKind::Const { symbol, value } => {
+                let name = self.value_name(program, *symbol);
+                let info = program.symbol(*symbol);
+                let debug_info = self.debug_info(position).with_type_name(self.concrete(&info.ty).to_string());
+                let initializer = match value {
+                    hir::Constant::Integer(value) => Constant::Int(*value),
+                    hir::Constant::Float(value) => Constant::Float(*value),
+                    hir::Constant::Boolean(value) => Constant::Bool(*value),
+                    hir::Constant::String(value) => Constant::String(value.clone()),
+                };
+                let global = GlobalVariable::new(name, self.ir_type(&info.ty))
+                    .with_initializer(Value::Constant(initializer))
+                    .as_constant()
+                    .with_debug_info(debug_info);
+                self.builder.get_module_mut().global_vars.push(global);
                 Ok(())
             }
-            crate::parser::Statement::ContinueStmt => {
-                // Continue statements would need proper control flow handling
+            hir::StatementKind::Assign { symbol, value } => {
+                let value = self.translate_expression(program, value)?;
+                let variable = self.value_name(program, *symbol);
+                self.store(variable, value, position)
+            }
+            hir::StatementKind::Expression(expression) => {
+                self.translate_expression(program, expression)?;
                 Ok(())
             }
-            crate::parser::Statement::ImportStmt { module, alias, .. } => {
-                // Handle import statements by resolving and processing the module
-                match self.module_resolver.resolve_module(&module) {
-                    Ok(module_content) => {
-                        // Parse the imported module content
-                        let mut import_parser = crate::parser::Parser::new(&module_content)?;
-                        let import_ast = import_parser.parse_program()?;
-
-                        // Process the imported module AST
-                        // For now, we'll just acknowledge the import and store the alias if provided
-                        let module_name = if let Some(alias_name) = alias {
-                            alias_name.clone()
-                        } else {
-                            module.clone()
-                        };
-
-                        println!("Imported module: {} as {}", module, module_name);
-
-                        // In a full implementation, we would integrate the imported module's
-                        // functions and variables into the current module's scope
-                        // For now, we'll just note the import
-                    }
-                    Err(e) => {
-                        eprintln!("Warning: Failed to resolve module '{}': {}", module, e);
+            hir::StatementKind::Return(value) => {
+                let finally = self.scopes.iter().any(|scope| matches!(scope, Scope::Try { finally: Some(_), .. }));
+                let value = match value {
+                    // The finally blocks it leaves run after the value is computed, and may change the variables
+                    // it was computed from, so it is returned from a copy
+                    Some(expression) if finally => {
+                        let value = self.translate_expression(program, expression)?;
+                        let copy = self.temporary(&expression.ty, position)?;
+                        self.store(copy.clone(), value, position)?;
+                        Some(Value::Variable(copy))
                     }
+                    Some(expression) => Some(self.translate_expression(program, expression)?),
+                    None => None,
+                };
+                self.run_finally_blocks(program, true)?;
+                self.end_block(Terminator::Return { value })
+            }
+            hir::StatementKind::Throw(value) => {
+                let exception_type = self.ir_type(&value.ty);
+                let exception = self.translate_expression(program, value)?;
+                let unwind = self.builder.unwind_target();
+                self.end_block(Terminator::Unwind { exception, exception_type, unwind })
+            }
+            hir::StatementKind::If { condition, then_block, else_block } => {
+                self.translate_if(program, condition, then_block, else_block.as_deref())
+            }
+            hir::StatementKind::While { condition, body } => self.translate_while(program, condition, body, true),
+            hir::StatementKind::DoWhile { body, condition } => self.translate_while(program, condition, body, false),
+            hir::StatementKind::For { variable, start, end, descending, body } => {
+                self.translate_for(program, *variable, start, end, *descending, body, position)
+            }
+            hir::StatementKind::ForEach { variable, iterable, body } => {
+                self.translate_for_each(program, *variable, iterable, body, position)
+            }
+            // Generic functions and classes are generated for the type arguments they are used with
+            hir::StatementKind::Function(function) if !function.type_parameters.is_empty() => Ok(()),
+            hir::StatementKind::Class(class) if !class.type_parameters.is_empty() => Ok(()),
+            hir::StatementKind::Function(function) => {
+                let name = program.symbol(function.symbol).name.clone();
+                self.translate_function(program, function, name, None, position)
+            }
+            hir::StatementKind::Class(class) => {
+                let class_name = program.symbol(class.symbol).name.clone();
+                self.translate_class(program, class, &class_name, position)
+            }
+            // Interfaces and enums were laid out before the program; they generate no code
+            hir::StatementKind::Interface(_) | hir::StatementKind::Enum(_) => Ok(()),
+            hir::StatementKind::TryCatch { try_block, catches, finally_block } => {
+                self.translate_try(program, try_block, catches, finally_block, position)
+            }
+            hir::StatementKind::Break | hir::StatementKind::Continue => {
+                let Some((next, exit)) = self.scopes.iter().rev().find_map(|scope| match scope {
+                    Scope::Loop { next, exit } => Some((next.clone(), exit.clone())),
+                    Scope::Try { .. } => None,
+                }) else {
+                    return Err(IRError::Internal(format!("'pecah' or 'lanjut' outside of a loop at line {}", position.line)));
+                };
+                // The finally blocks inside the loop run first
+                self.run_finally_blocks(program, false)?;
+                let target = if matches!(statement.kind, hir::StatementKind::Break) { exit } else { next };
+                self.end_block(Terminator::Branch { target })
+            }
+            hir::StatementKind::Import { module, .. } => {
+                // The names an import brings in are resolved when linking
+                if let Err(error) = self.module_resolver.resolve_module(module) {
+                    eprintln!("Warning: Failed to resolve module '{}': {}", module, error);
                 }
                 Ok(())
             }
-            crate::parser::Statement::WhenStmt { expression, cases, else_case, .. } => {
-                let expr_val = self.translate_node(expression)?;
+            hir::StatementKind::Export(_) => Ok(()),
+            hir::StatementKind::MemberAssign { object, field, value } => {
+                let field_index = self.field_index(program, &object.ty, field)?;
+                let object = self.translate_expression(program, object)?;
+                let value = self.translate_expression(program, value)?;
+                if let Some((class, index)) = field_index {
+                    return self.builder.add_instruction(Instruction::SetField {
+                        object,
+                        class,
+                        index,
+                        value,
+                        debug_info: Some(self.debug_info(position)),
+                    }).map_err(IRError::from);
+                }
+                // Fields of values only known at run time are set by the runtime library
+                let field = Value::Constant(Constant::String(field.clone()));
+                self.call(None, "set_member", vec![object, field, value], position)
+            }
+            hir::StatementKind::IndexAssign { object, index, value } => {
+                let object = self.translate_expression(program, object)?;
+                let index = self.translate_expression(program, index)?;
+                let value = self.translate_expression(program, value)?;
+                self.call(None, "set_index", vec![object, index, value], position)
+            }
+            hir::StatementKind::When { value, cases, else_case } => {
+                self.translate_when(program, value, cases, else_case.as_deref())
+            }
+            hir::StatementKind::Go(body) => {
+                // Goroutines are not generated yet, so the body runs in place
+                self.translate_block(program, body)
+            }
+            hir::StatementKind::ChannelSend { channel, value } => {
+                let channel = self.translate_expression(program, channel)?;
+                let value = self.translate_expression(program, value)?;
+                self.builder.add_instruction(Instruction::ChannelSend {
+                    channel,
+                    value,
+                    debug_info: Some(self.debug_info(position)),
+                }).map_err(IRError::from)
+            }
+            hir::StatementKind::ChannelReceive { channel, variable } => {
+                let channel = self.translate_expression(program, channel)?;
+                let received = self.builder.fresh_value();
+                self.builder.add_instruction(Instruction::ChannelReceive {
+                    result: received.clone(),
+                    channel,
+                    debug_info: Some(self.debug_info(position)),
+                })?;
+                // `terima x <- ch` declares `x` unless it already exists
+                let variable = match self.variables.get(variable) {
+                    Some(name) => name.clone(),
+                    None => self.allocate(program, *variable, position)?,
+                };
+                self.store(variable, Value::Variable(received), position)
+            }
+            hir::StatementKind::MutexLock(mutex) => {
+                let mutex = self.translate_expression(program, mutex)?;
+                self.builder.add_instruction(Instruction::MutexLock { mutex, debug_info: Some(self.debug_info(position)) }).map_err(IRError::from)
+            }
+            hir::StatementKind::MutexUnlock(mutex) => {
+                let mutex = self.translate_expression(program, mutex)?;
+                self.builder.add_instruction(Instruction::MutexUnlock { mutex, debug_info: Some(self.debug_info(position)) }).map_err(IRError::from)
+            }
+            hir::StatementKind::WaitCondition { condition, mutex } => {
+                let condition = self.translate_expression(program, condition)?;
+                let mutex = self.translate_expression(program, mutex)?;
+                self.builder.add_instruction(Instruction::ConditionWait {
+                    condition,
+                    mutex,
+                    debug_info: Some(self.debug_info(position)),
+                }).map_err(IRError::from)
+            }
+            hir::StatementKind::SignalCondition(condition) => {
+                let condition = self.translate_expression(program, condition)?;
+                self.builder.add_instruction(Instruction::ConditionSignal { condition, debug_info: Some(self.debug_info(position)) }).map_err(IRError::from)
+            }
+            hir::StatementKind::BroadcastCondition(condition) => {
+                let condition = self.translate_expression(program, condition)?;
+                self.builder.add_instruction(Instruction::ConditionBroadcast { condition, debug_info: Some(self.debug_info(position)) }).map_err(IRError::from)
+            }
+            hir::StatementKind::AtomicStore { address, value } => {
+                let address = self.translate_expression(program, address)?;
+                let value = self.translate_expression(program, value)?;
+                self.builder.add_instruction(Instruction::AtomicStore {
+                    address,
+                    value,
+                    ordering: AtomicOrdering::SeqCst,
+                    debug_info: Some(self.debug_info(position)),
+                }).map_err(IRError::from)
+            }
+            hir::StatementKind::Unsupported { .. } => Ok(()), // Skip unsupported statements for now
+        }
+    }
 
-                if let Some(val) = expr_val {
-                    let mut ir_cases = Vec::new();
+    /// The operand of a comparison of `left` and `right` that is an optional compared with `nol`, if one is
+    fn null_comparison<'a>(&self, left: &'a hir::Expression, right: &'a hir::Expression) -> Option<&'a hir::Expression> {
+        let optional = match (&left.kind, &right.kind) {
+            (hir::ExpressionKind::Null, _) => right,
+            (_, hir::ExpressionKind::Null) => left,
+            _ => return None,
+        };
+        matches!(self.concrete(&optional.ty), crate::types::Type::Optional(_)).then_some(optional)
+    }
 
-                    for (pattern, body_stmts) in cases {
-                        let pattern_val = self.translate_node(pattern)?;
-                        // Simplified - would generate actual body instructions
-                        if let Some(pat_val) = pattern_val {
-                            ir_cases.push((pat_val, vec![]));
-                        }
+    /// Value an optional holds
+    fn unwrap(&mut self, optional: Value, position: &Position) -> Result<Value, IRError> {
+        let result = self.builder.fresh_value();
+        self.builder.add_instruction(Instruction::Unwrap {
+            result: result.clone(),
+            value: optional,
+            debug_info: Some(self.debug_info(position)),
+        })?;
+        Ok(Value::Variable(result))
+    }
 
-                        // Translate body statements
-                        for stmt in body_stmts {
-                            self.translate_statement(stmt)?;
-                        }
-                    }
+    /// Variable holding a value of type `ty`, such as one computed in the cases of a match
+    fn temporary(&mut self, ty: &crate::types::Type, position: &Position) -> Result<String, IRError> {
+        let result = self.builder.fresh_value();
+        self.builder.add_instruction(Instruction::Alloca {
+            result: result.clone(),
+            alloca_type: self.ir_type(ty),
+            debug_info: Some(self.debug_info(position).with_type_name(self.concrete(ty).to_string())),
+        })?;
+        Ok(result)
+    }
 
-                    let default_body = if let Some(else_stmts) = else_case {
-                        // Translate else case statements
-                        for stmt in else_stmts {
-                            self.translate_statement(stmt)?;
-                        }
-                        Some(vec![]) // Simplified
-                    } else {
-                        None
-                    };
-
-                    let instruction = Instruction::PatternMatch {
-                        result: "".to_string(),
-                        expression: val,
-                        cases: ir_cases,
-                        default: default_body,
-                    };
-                    self.builder.add_instruction(instruction)?;
+    /// Run `when_null` if `optional` is `nol`, and `when_held` otherwise
+    fn match_optional(&mut self, optional: Value, when_null: Vec<Instruction>, when_held: Vec<Instruction>, position: &Position) -> Result<(), IRError> {
+        let result = self.builder.fresh_value();
+        self.builder.add_instruction(Instruction::PatternMatch {
+            result,
+            expression: optional,
+            cases: vec![
+                MatchCase { pattern: Pattern::Constant(Constant::Null), guard: None, body: when_null },
+                MatchCase { pattern: Pattern::Wildcard, guard: None, body: when_held },
+            ],
+            default: None,
+            debug_info: Some(self.debug_info(position)),
+        }).map_err(IRError::from)
+    }
+
+    /// Store a value in a variable
+    fn store(&mut self, variable: String, value: Value, position: &Position) -> Result<(), IRError> {
+        self.builder.add_instruction(Instruction::Store {
+            variable,
+            value,
+            debug_info: Some(self.debug_info(position)),
+        }).map_err(IRError::from)
+    }
+
+    /// Compute `left op right`
+    fn binary(&mut self, op: BinaryOp, left: Value, right: Value, position: &Position) -> Result<Value, IRError> {
+        let result = self.builder.fresh_value();
+        self.builder.add_instruction(Instruction::BinaryOp {
+            result: result.clone(),
+            op,
+            left,
+            right,
+            debug_info: Some(self.debug_info(position)),
+        })?;
+        Ok(Value::Variable(result))
+    }
+
+    /// Call a function, naming its result `result` if it returns a value
+    fn call(&mut self, result: Option<String>, function: &str, arguments: Vec<Value>, position: &Position) -> Result<(), IRError> {
+        self.builder.add_instruction(Instruction::Call {
+            result,
+            function: function.to_string(),
+            arguments,
+            debug_info: Some(self.debug_info(position)),
+        }).map_err(IRError::from)
+    }
+
+    /// Call a function that returns a value of type `ty`, and return that value
+    fn call_value(&mut self, function: &str, arguments: Vec<Value>, ty: &crate::types::Type, position: &Position) -> Result<Value, IRError> {
+        if *ty == crate::types::Type::Void {
+            self.call(None, function, arguments, position)?;
+            return Ok(Value::Constant(Constant::Empty));
+        }
+        let result = self.builder.fresh_value();
+        self.call(Some(result.clone()), function, arguments, position)?;
+        Ok(Value::Variable(result))
+    }
+
+    /// Translate a pipeline; each map and filter stage is a loop of its own, until the optimizer fuses
+    /// adjacent ones
+    fn translate_pipeline(
+        &mut self,
+        program: &hir::Program,
+        source: &hir::Expression,
+        stages: &[hir::Stage],
+        ty: &crate::types::Type,
+        position: &Position,
+    ) -> Result<Value, IRError> {
+        let initial = self.translate_expression(program, source)?;
+        let mut translated = Vec::new();
+        for stage in stages {
+            translated.push(match &stage.kind {
+                hir::StageKind::Filter(predicate) => {
+                    Stage::Loop(vec![LoopStep::Filter(self.translate_expression(program, predicate)?)])
                 }
+                hir::StageKind::Map(function) => Stage::Loop(vec![LoopStep::Map {
+                    function: self.translate_expression(program, function)?,
+                    element_type: self.element_type(&stage.ty),
+                }]),
+                hir::StageKind::Take(count) => Stage::Take(self.translate_expression(program, count)?),
+                hir::StageKind::Skip(count) => Stage::Skip(self.translate_expression(program, count)?),
+                hir::StageKind::Sort(comparator) => Stage::Sort(match comparator {
+                    Some(comparator) => Some(self.translate_expression(program, comparator)?),
+                    None => None,
+                }),
+                hir::StageKind::Reduce { function, initial } => Stage::Reduce {
+                    function: self.translate_expression(program, function)?,
+                    initial: match initial {
+                        Some(initial) => Some(self.translate_expression(program, initial)?),
+                        None => None,
+                    },
+                    result_type: self.ir_type(&stage.ty),
+                },
+            });
+        }
 
-                Ok(())
-            }
-            _ => Ok(()), // Skip unsupported statements for now
+        let result = self.builder.fresh_value();
+        self.builder.add_instruction(Instruction::Pipeline {
+            result: result.clone(),
+            initial,
+            element_type: self.element_type(&source.ty),
+            stages: translated,
+            debug_info: Some(self.debug_info(position).with_type_name(self.concrete(ty).to_string())),
+        })?;
+        Ok(Value::Variable(result))
+    }
+
+    /// Translate a list of expressions
+    fn translate_expressions(&mut self, program: &hir::Program, expressions: &[hir::Expression]) -> Result<Vec<Value>, IRError> {
+        let mut values = Vec::new();
+        for expression in expressions {
+            values.push(self.translate_expression(program, expression)?);
         }
+        Ok(values)
     }
 
-    /// Set debug information for the current module
-    pub fn set_module_debug_info(&mut self, file_name: String, line: usize, column: usize) {
-        self.module.debug_info = Some(DebugInfo {
-            file_name,
-            line,
-            column,
-        });
+    /// Translate an expression to IR and return its value
+    fn translate_expression(&mut self, program: &hir::Program, expression: &hir::Expression) -> Result<Value, IRError> {
+        let position = &expression.position;
+        match &expression.kind {
+            hir::ExpressionKind::Integer(value) => Ok(Value::Constant(Constant::Int(*value))),
+            hir::ExpressionKind::Float(value) => Ok(Value::Constant(Constant::Float(*value))),
+            hir::ExpressionKind::String(value) => Ok(Value::Constant(Constant::String(value.clone()))),
+            hir::ExpressionKind::Boolean(value) => Ok(Value::Constant(Constant::Bool(*value))),
+            hir::ExpressionKind::Null => Ok(Value::Constant(Constant::Null)),
+            hir::ExpressionKind::Interpolation(parts) => {
+                // Lower "a {x} b" to string_concat(string_concat("a ", to_string(x)), " b")
+                let mut result: Option<Value> = None;
+                for part in parts {
+                    let mut value = self.translate_expression(program, part)?;
+                    if part.ty != crate::types::Type::String {
+                        value = self.call_value("to_string", vec![value], &crate::types::Type::String, position)?;
+                    }
+                    result = Some(match result {
+                        None => value,
+                        Some(left) => self.call_value("string_concat", vec![left, value], &crate::types::Type::String, position)?,
+                    });
+                }
+                Ok(result.unwrap_or(Value::Constant(Constant::String(String::new()))))
+            }
+            hir::ExpressionKind::SelfRef => Ok(Value::Variable("ini".to_string())),
+            hir::ExpressionKind::Variable(symbol) => Ok(Value::Variable(self.value_name(program, *symbol))),
+            hir::ExpressionKind::External(name) => Ok(Value::Variable(name.clone())),
+            // `x == nol` tests the flag of an optional
+            hir::ExpressionKind::Binary { left, operator, right }
+                if matches!(operator, BinaryOperator::Equal | BinaryOperator::SamaDengan | BinaryOperator::NotEqual)
+                    && self.null_comparison(left, right).is_some() => {
+                let optional = self.null_comparison(left, right).unwrap_or(left);
+                let value = self.translate_expression(program, optional)?;
+                let is_null = self.builder.fresh_value();
+                self.builder.add_instruction(Instruction::NullCheck {
+                    result: is_null.clone(),
+                    value,
+                    debug_info: Some(self.debug_info(position)),
+                })?;
+                if *operator != BinaryOperator::NotEqual {
+                    return Ok(Value::Variable(is_null));
+                }
+                let result = self.builder.fresh_value();
+                self.builder.add_instruction(Instruction::UnaryOp {
+                    result: result.clone(),
+                    op: UnaryOp::Not,
+                    operand: Value::Variable(is_null),
+                    debug_info: Some(self.debug_info(position)),
+                })?;
+                Ok(Value::Variable(result))
+            }
+            // Strings are joined by the runtime, as in interpolation
+            hir::ExpressionKind::Binary { left, operator: BinaryOperator::Add | BinaryOperator::Tambah, right }
+                if self.concrete(&left.ty) == crate::types::Type::String && self.concrete(&right.ty) == crate::types::Type::String => {
+                let left = self.translate_expression(program, left)?;
+                let right = self.translate_expression(program, right)?;
+                self.call_value("string_concat", vec![left, right], &crate::types::Type::String, position)
+            }
+            hir::ExpressionKind::Binary { left, operator, right } => {
+                let left = self.translate_expression(program, left)?;
+                let right = self.translate_expression(program, right)?;
+                let op = match operator {
+                    BinaryOperator::Add | BinaryOperator::Tambah => BinaryOp::Add,
+                    BinaryOperator::Subtract | BinaryOperator::Kurang => BinaryOp::Sub,
+                    BinaryOperator::Multiply | BinaryOperator::Kali => BinaryOp::Mul,
+                    BinaryOperator::Divide | BinaryOperator::Bagi => BinaryOp::Div,
+                    BinaryOperator::Modulo => BinaryOp::Mod,
+                    BinaryOperator::Equal | BinaryOperator::SamaDengan => BinaryOp::Eq,
+                    BinaryOperator::NotEqual => BinaryOp::Ne,
+                    BinaryOperator::Less | BinaryOperator::KurangDari => BinaryOp::Lt,
+                    BinaryOperator::Greater | BinaryOperator::LebihDari => BinaryOp::Gt,
+                    BinaryOperator::LessEqual => BinaryOp::Le,
+                    BinaryOperator::GreaterEqual => BinaryOp::Ge,
+                    BinaryOperator::And => BinaryOp::And,
+                    BinaryOperator::Or => BinaryOp::Or,
+                    BinaryOperator::BitAnd => BinaryOp::BitAnd,
+                    BinaryOperator::BitOr => BinaryOp::BitOr,
+                    BinaryOperator::BitXor => BinaryOp::BitXor,
+                    BinaryOperator::LeftShift => BinaryOp::LeftShift,
+                    BinaryOperator::RightShift => BinaryOp::RightShift,
+                    BinaryOperator::In => BinaryOp::In,
+                    BinaryOperator::Power => return self.call_value("pow", vec![left, right], &expression.ty, position),
+                    BinaryOperator::Is => return self.call_value("is_instance", vec![left, right], &expression.ty, position),
+                    BinaryOperator::Range => {
+                        let result = self.builder.add_range(left, right, false)?;
+                        return Ok(Value::Variable(result));
+                    }
+                    BinaryOperator::Assign => return Err(IRError::Internal("Assignment should be handled as statement".to_string())),
+                };
+                self.binary(op, left, right, position)
+            }
+            hir::ExpressionKind::Unary { operator, operand } => {
+                let operand = self.translate_expression(program, operand)?;
+                let op = match operator {
+                    UnaryOperator::Negate | UnaryOperator::Balik => UnaryOp::Neg,
+                    UnaryOperator::Not | UnaryOperator::Tidak => UnaryOp::Not,
+                    UnaryOperator::Increment => UnaryOp::Increment,
+                    UnaryOperator::Decrement => UnaryOp::Decrement,
+                    UnaryOperator::BitNot => UnaryOp::BitNot,
+                    UnaryOperator::AddressOf => UnaryOp::AddressOf,
+                    UnaryOperator::Dereference => UnaryOp::Dereference,
+                };
+                let result = self.builder.fresh_value();
+                self.builder.add_instruction(Instruction::UnaryOp {
+                    result: result.clone(),
+                    op,
+                    operand,
+                    debug_info: Some(self.debug_info(position)),
+                })?;
+                Ok(Value::Variable(result))
+            }
+            hir::ExpressionKind::Call { callee, arguments, type_arguments } => {
+                let arguments = self.translate_expressions(program, arguments)?;
+                // A variable holds a closure, whose function is called with the environment it carries
+                if let hir::Callee::Symbol(symbol) = callee {
+                    let info = program.symbol(*symbol);
+                    if matches!(info.kind, SymbolKind::Variable { .. } | SymbolKind::Parameter) {
+                        let function_type = self.ir_type(&info.ty);
+                        let closure = Value::Variable(self.value_name(program, *symbol));
+                        let result = (self.concrete(&expression.ty) != crate::types::Type::Void).then(|| self.builder.fresh_value());
+                        self.builder.add_instruction(Instruction::ClosureCall {
+                            result: result.clone(),
+                            closure,
+                            function_type,
+                            arguments,
+                            debug_info: Some(self.debug_info(position)),
+                        })?;
+                        return Ok(result.map(Value::Variable).unwrap_or(Value::Constant(Constant::Empty)));
+                    }
+                }
+                let function = match callee {
+                    // A generic function runs its specialization for the types inferred at this call
+                    hir::Callee::Symbol(symbol) if !type_arguments.is_empty() => {
+                        let type_arguments: Vec<crate::types::Type> = type_arguments.iter().map(|ty| self.concrete(ty)).collect();
+                        self.specialize_function(program, *symbol, &type_arguments)?
+                    }
+                    hir::Callee::Symbol(symbol) => self.value_name(program, *symbol),
+                    hir::Callee::External(name) => name.clone(),
+                };
+                self.call_value(&function, arguments, &self.concrete(&expression.ty), position)
+            }
+            hir::ExpressionKind::Variant { enumeration, index, arguments } => {
+                let fields = self.translate_expressions(program, arguments)?;
+                let enumeration = self.enum_of(program, &expression.ty)
+                    .unwrap_or_else(|| program.symbol(*enumeration).name.clone());
+                let result = self.builder.fresh_value();
+                self.builder.add_instruction(Instruction::NewVariant {
+                    result: result.clone(),
+                    enumeration: enumeration.clone(),
+                    variant: *index,
+                    fields,
+                    debug_info: Some(self.debug_info(position).with_type_name(enumeration)),
+                })?;
+                Ok(Value::Variable(result))
+            }
+            hir::ExpressionKind::Tuple(elements) => {
+                let elements = self.translate_expressions(program, elements)?;
+                let result = self.builder.fresh_value();
+                self.builder.add_instruction(Instruction::MakeTuple {
+                    result: result.clone(),
+                    elements,
+                    debug_info: Some(self.debug_info(position).with_type_name(self.concrete(&expression.ty).to_string())),
+                })?;
+                Ok(Value::Variable(result))
+            }
+            hir::ExpressionKind::Construct { class, arguments } => {
+                let arguments = self.translate_expressions(program, arguments)?;
+                let mut class = program.symbol(*class).name.clone();
+                if self.generic_classes.contains_key(&class) {
+                    class = self.class_of(program, &expression.ty)?.unwrap_or(class);
+                }
+                if !self.classes.contains_key(&class) {
+                    // A class defined elsewhere is constructed by calling the function named after it
+                    return self.call_value(&class, arguments, &expression.ty, position);
+                }
+                // The instance gets its initial field values, then the constructor runs on it
+                let object = self.builder.fresh_value();
+                self.builder.add_instruction(Instruction::NewObject {
+                    result: object.clone(),
+                    class: class.clone(),
+                    debug_info: Some(self.debug_info(position).with_type_name(class.clone())),
+                })?;
+                self.call(None, &format!("{}.$init", class), vec![Value::Variable(object.clone())], position)?;
+                let constructor = ["baru", "new"].into_iter().find_map(|name| self.implementation(&class, name));
+                if let Some(constructor) = constructor {
+                    let mut values = vec![Value::Variable(object.clone())];
+                    values.extend(arguments);
+                    self.call(None, &constructor, values, position)?;
+                }
+                Ok(Value::Variable(object))
+            }
+            hir::ExpressionKind::MethodCall { object, method, arguments } => {
+                // `Kelas.metode(...)` calls a static method, which takes no instance
+                if let hir::ExpressionKind::Variable(symbol) = &object.kind {
+                    let class = program.symbol(*symbol);
+                    if class.kind == SymbolKind::Class {
+                        let function = self.implementation(&class.name, method)
+                            .unwrap_or_else(|| format!("{}.{}", class.name, method));
+                        let arguments = self.translate_expressions(program, arguments)?;
+                        return self.call_value(&function, arguments, &expression.ty, position);
+                    }
+                }
+                // Methods with a slot in the virtual table run the function of the instance's own class
+                let class = self.class_of(program, &object.ty)?;
+                let slot = class.as_ref()
+                    .and_then(|name| Some((name.clone(), self.classes.get(name)?.method_slot(method)?)));
+                // Methods of an interface run the function the table of the instance's class has for them
+                let interface_slot = class.as_ref()
+                    .and_then(|name| Some((name.clone(), self.interfaces.get(name)?.method_slot(method)?)));
+                let object = self.translate_expression(program, object)?;
+                let arguments = self.translate_expressions(program, arguments)?;
+                if let Some((interface, slot)) = interface_slot {
+                    let result = (expression.ty != crate::types::Type::Void).then(|| self.builder.fresh_value());
+                    self.builder.add_instruction(Instruction::InterfaceCall {
+                        result: result.clone(),
+                        object,
+                        interface,
+                        slot,
+                        arguments,
+                        debug_info: Some(self.debug_info(position)),
+                    })?;
+                    return Ok(result.map(Value::Variable).unwrap_or(Value::Constant(Constant::Empty)));
+                }
+                if let Some((class, slot)) = slot {
+                    let result = (expression.ty != crate::types::Type::Void).then(|| self.builder.fresh_value());
+                    self.builder.add_instruction(Instruction::VirtualCall {
+                        result: result.clone(),
+                        object,
+                        class,
+                        slot,
+                        arguments,
+                        debug_info: Some(self.debug_info(position)),
+                    })?;
+                    return Ok(result.map(Value::Variable).unwrap_or(Value::Constant(Constant::Empty)));
+                }
+                // Otherwise the receiver is passed first to a function named `Kelas.metode`
+                let function = match &class {
+                    Some(class) => format!("{}.{}", class, method),
+                    None => method.clone(),
+                };
+                let mut values = vec![object];
+                values.extend(arguments);
+                self.call_value(&function, values, &expression.ty, position)
+            }
+            hir::ExpressionKind::SuperCall { class, method, arguments } => {
+                // The parent's method runs on the same instance, without going through the virtual table
+                let function = format!("{}.{}", program.symbol(*class).name, method);
+                let mut values = vec![Value::Variable("ini".to_string())];
+                values.extend(self.translate_expressions(program, arguments)?);
+                self.call_value(&function, values, &expression.ty, position)
+            }
+            hir::ExpressionKind::Member { object, field } => {
+                let field_index = self.field_index(program, &object.ty, field)?;
+                let object = self.translate_expression(program, object)?;
+                let result = self.builder.fresh_value();
+                if let Some((class, index)) = field_index {
+                    self.builder.add_instruction(Instruction::GetField {
+                        result: result.clone(),
+                        object,
+                        class,
+                        index,
+                        debug_info: Some(self.debug_info(position)),
+                    })?;
+                    return Ok(Value::Variable(result));
+                }
+                self.builder.add_instruction(Instruction::MemberAccess {
+                    result: result.clone(),
+                    object,
+                    property: field.clone(),
+                    debug_info: Some(self.debug_info(position)),
+                })?;
+                Ok(Value::Variable(result))
+            }
+            hir::ExpressionKind::Index { object, index } => {
+                let object = self.translate_expression(program, object)?;
+                let index = self.translate_expression(program, index)?;
+                self.call_value("get_index", vec![object, index], &expression.ty, position)
+            }
+            hir::ExpressionKind::Spread(value) | hir::ExpressionKind::Optional(value) => {
+                self.translate_expression(program, value)
+            }
+            hir::ExpressionKind::Unwrap(value) => {
+                let value = self.translate_expression(program, value)?;
+                self.unwrap(value, position)
+            }
+            hir::ExpressionKind::Elvis { value, default } => {
+                if !matches!(self.concrete(&value.ty), crate::types::Type::Optional(_)) {
+                    return self.translate_expression(program, value);
+                }
+                let result = self.temporary(&expression.ty, position)?;
+                let optional = self.translate_expression(program, value)?;
+                // The default is only computed when the value is `nol`
+                let (when_null, _) = self.capture(|generator| {
+                    let default = generator.translate_expression(program, default)?;
+                    generator.store(result.clone(), default, position)
+                })?;
+                let (when_held, _) = self.capture(|generator| {
+                    let held = generator.unwrap(optional.clone(), position)?;
+                    generator.store(result.clone(), held, position)
+                })?;
+                self.match_optional(optional, when_null, when_held, position)?;
+                Ok(Value::Variable(result))
+            }
+            hir::ExpressionKind::OptionalChain { object, binding, access } => {
+                let optional = self.translate_expression(program, object)?;
+                let binding = self.allocate(program, *binding, position)?;
+                // An object that is never `nol` is accessed as with `.`
+                if !matches!(self.concrete(&object.ty), crate::types::Type::Optional(_)) {
+                    self.store(binding, optional, position)?;
+                    return self.translate_expression(program, access);
+                }
+                let returns_value = self.concrete(&expression.ty) != crate::types::Type::Void;
+                let result = self.temporary(&expression.ty, position)?;
+                let (when_null, _) = self.capture(|generator| {
+                    if returns_value {
+                        generator.store(result.clone(), Value::Constant(Constant::Null), position)?;
+                    }
+                    Ok(())
+                })?;
+                let (when_held, _) = self.capture(|generator| {
+                    let held = generator.unwrap(optional.clone(), position)?;
+                    generator.store(binding, held, position)?;
+                    let accessed = generator.translate_expression(program, access)?;
+                    if returns_value {
+                        generator.store(result.clone(), accessed, position)?;
+                    }
+                    Ok(())
+                })?;
+                self.match_optional(optional, when_null, when_held, position)?;
+                Ok(Value::Variable(result))
+            }
+            hir::ExpressionKind::Propagate { value, error, early } => {
+                let result = self.temporary(&expression.ty, position)?;
+                let propagated = self.translate_expression(program, value)?;
+                // Returning early runs the finally blocks the return leaves, as `kembalikan` does
+                let (returns, _) = self.capture(|generator| {
+                    let early = generator.translate_expression(program, early)?;
+                    generator.run_finally_blocks(program, true)?;
+                    generator.end_block(Terminator::Return { value: Some(early) })
+                })?;
+                let Some(error) = error else {
+                    let (when_held, _) = self.capture(|generator| {
+                        let held = generator.unwrap(propagated.clone(), position)?;
+                        generator.store(result.clone(), held, position)
+                    })?;
+                    self.match_optional(propagated, returns, when_held, position)?;
+                    return Ok(Value::Variable(result));
+                };
+                // A `Sukses` stores its value to the result, and a `Gagal` its error to the variable `early` reads
+                let enumeration = self.enum_of(program, &value.ty).ok_or("'?' on a value of no enum".to_string())?;
+                let error = self.allocate(program, *error, position)?;
+                let cases = vec![
+                    MatchCase {
+                        pattern: Pattern::Variant { enumeration: enumeration.clone(), index: 1, fields: vec![Pattern::Binding(error)] },
+                        guard: None,
+                        body: returns,
+                    },
+                    MatchCase {
+                        pattern: Pattern::Variant { enumeration, index: 0, fields: vec![Pattern::Binding(result.clone())] },
+                        guard: None,
+                        body: Vec::new(),
+                    },
+                ];
+                let matched = self.builder.fresh_value();
+                self.builder.add_instruction(Instruction::PatternMatch {
+                    result: matched,
+                    expression: propagated,
+                    cases,
+                    default: None,
+                    debug_info: Some(self.debug_info(position)),
+                })?;
+                Ok(Value::Variable(result))
+            }
+            hir::ExpressionKind::List(elements) => {
+                let elements = self.translate_expressions(program, elements)?;
+                let list = self.builder.fresh_value();
+                self.builder.add_instruction(Instruction::Alloca {
+                    result: list.clone(),
+                    alloca_type: self.ir_type(&expression.ty),
+                    debug_info: Some(self.debug_info(position).with_type_name(expression.ty.to_string())),
+                })?;
+                for element in elements {
+                    self.call(None, "list_push", vec![Value::Variable(list.clone()), element], position)?;
+                }
+                Ok(Value::Variable(list))
+            }
+            hir::ExpressionKind::Map(fields) => {
+                let mut properties = HashMap::new();
+                for (name, value) in fields {
+                    properties.insert(name.clone(), self.translate_expression(program, value)?);
+                }
+                let result = self.builder.fresh_value();
+                self.builder.add_instruction(Instruction::ObjectLiteral {
+                    result: result.clone(),
+                    properties,
+                    debug_info: Some(self.debug_info(position)),
+                })?;
+                Ok(Value::Variable(result))
+            }
+            hir::ExpressionKind::Comprehension { element, variable, iterable, condition } => {
+                let iterable = self.translate_expression(program, iterable)?;
+                let variable = self.allocate(program, *variable, position)?;
+                let element = self.translate_expression(program, element)?;
+                let condition = match condition {
+                    Some(condition) => Some(self.translate_expression(program, condition)?),
+                    None => None,
+                };
+                let result = self.builder.fresh_value();
+                self.builder.add_instruction(Instruction::ListComprehension {
+                    result: result.clone(),
+                    expression: element,
+                    variable,
+                    iterable,
+                    condition,
+                    debug_info: Some(self.debug_info(position)),
+                })?;
+                Ok(Value::Variable(result))
+            }
+            hir::ExpressionKind::Range { start, end, inclusive } => {
+                let start = self.translate_expression(program, start)?;
+                let end = self.translate_expression(program, end)?;
+                let result = self.builder.fresh_value();
+                self.builder.add_instruction(Instruction::Range {
+                    result: result.clone(),
+                    start,
+                    end,
+                    inclusive: *inclusive,
+                    debug_info: Some(self.debug_info(position)),
+                })?;
+                Ok(Value::Variable(result))
+            }
+            hir::ExpressionKind::Await(value) => {
+                let value = self.translate_expression(program, value)?;
+                let result = self.builder.fresh_value();
+                self.builder.add_instruction(Instruction::Await {
+                    result: result.clone(),
+                    value,
+                    debug_info: Some(self.debug_info(position)),
+                })?;
+                Ok(Value::Variable(result))
+            }
+            hir::ExpressionKind::Yield(value) => {
+                let value = self.translate_expression(program, value)?;
+                let result = self.builder.fresh_value();
+                self.builder.add_instruction(Instruction::Yield {
+                    result: result.clone(),
+                    value,
+                    debug_info: Some(self.debug_info(position)),
+                })?;
+                Ok(Value::Variable(result))
+            }
+            hir::ExpressionKind::ChannelReceive(channel) => {
+                let channel = self.translate_expression(program, channel)?;
+                let result = self.builder.fresh_value();
+                self.builder.add_instruction(Instruction::ChannelReceive {
+                    result: result.clone(),
+                    channel,
+                    debug_info: Some(self.debug_info(position)),
+                })?;
+                Ok(Value::Variable(result))
+            }
+            hir::ExpressionKind::MakeChannel => {
+                let result = self.builder.fresh_value();
+                self.builder.add_instruction(Instruction::MakeChannel {
+                    result: result.clone(),
+                    channel_type: Type::from(&expression.ty),
+                    debug_info: Some(self.debug_info(position)),
+                })?;
+                Ok(Value::Variable(result))
+            }
+            hir::ExpressionKind::Lambda(lambda) => self.translate_lambda(program, lambda, &expression.ty, position),
+            hir::ExpressionKind::Pipeline { source, stages } => {
+                self.translate_pipeline(program, source, stages, &expression.ty, position)
+            }
+            hir::ExpressionKind::Unsupported { construct, .. } => {
+                Err(IRError::Unsupported { construct, position: position.clone() })
+            }
+        }
     }
 
-    /// Set debug information for the current function
-    pub fn set_function_debug_info(&mut self, file_name: String, line: usize, column: usize) {
-        // This would set debug info for the current function
-        // Implementation would depend on how we track the current function
+    /// Set debug information for the module, naming the source file of its instructions
+    pub fn set_module_debug_info(&mut self, file_name: String, line: usize, column: usize) {
+        self.file_name = file_name.clone();
+        self.builder.set_module_debug_info(file_name, line, column);
     }
 }
 
@@ -1539,7 +3736,51 @@ pub fn print_ir(module: &IRModule) {
 
     // Print global variables
     for global in &module.global_vars {
-        println!("@{} = global {}", global.name, print_type(&global.var_type));
+        let kind = if global.constant { "constant" } else { "global" };
+        match &global.initializer {
+            Some(initializer) => println!("@{} = {} {} {}", global.name, kind, print_type(&global.var_type), print_value_str(initializer)),
+            None => println!("@{} = {} {}", global.name, kind, print_type(&global.var_type)),
+        }
+    }
+
+    // Print class layouts and their virtual tables
+    for class in &module.classes {
+        let fields: Vec<String> = class.fields.iter().map(|(_, ty)| print_type(ty)).collect();
+        println!("%{} = type {{ vtable, {} }}", class.name, fields.join(", "));
+        let methods: Vec<String> = class.methods.iter().map(|(_, function)| format!("@{}", function)).collect();
+        println!("@{}.vtable = [{}]", class.name, methods.join(", "));
+        for (interface, functions) in &class.interfaces {
+            let functions: Vec<String> = functions.iter().map(|function| format!("@{}", function)).collect();
+            println!("@{}.{}.itable = [{}]", class.name, interface, functions.join(", "));
+        }
+    }
+
+    // Print enums and the fields of their variants
+    for enumeration in &module.enums {
+        let variants: Vec<String> = enumeration.variants.iter()
+            .map(|(variant, fields)| {
+                let fields: Vec<String> = fields.iter().map(print_type).collect();
+                format!("{}({})", variant, fields.join(", "))
+            })
+            .collect();
+        println!("%{} = enum {{ {} }}", enumeration.name, variants.join(" | "));
+    }
+
+    // Print the environments of closures
+    for closure in &module.closures {
+        let captures: Vec<String> = closure.captures.iter()
+            .map(|(name, ty, capture)| match capture {
+                Capture::ByValue => format!("{} {}", print_type(ty), name),
+                Capture::ByReference => format!("ref<{}> {}", print_type(ty), name),
+            })
+            .collect();
+        println!("%{}.$env = type {{ {} }}", closure.function, captures.join(", "));
+    }
+
+    // Print interfaces and the methods of their tables
+    for interface in &module.interfaces {
+        let methods: Vec<String> = interface.methods.iter().map(|(method, _)| method.clone()).collect();
+        println!("%{} = interface {{ {} }}", interface.name, methods.join(", "));
     }
 
     // Print functions
@@ -1577,7 +3818,7 @@ pub fn print_ir(module: &IRModule) {
 /// Print an instruction
 fn print_instruction(instruction: &Instruction) {
     match instruction {
-        Instruction::BinaryOp { result, op, left, right } => {
+        Instruction::BinaryOp { result, op, left, right, .. } => {
             print!("{} = ", result);
             match op {
                 BinaryOp::Add => print!("add "),
@@ -1605,15 +3846,22 @@ fn print_instruction(instruction: &Instruction) {
             print_value(right);
             println!();
         }
-        Instruction::Store { variable, value } => {
+        Instruction::Store { variable, value, .. } => {
             print!("store {}, %{}", print_value_str(value), variable);
             println!();
         }
-        Instruction::Alloca { result, alloca_type } => {
+        Instruction::Alloca { result, alloca_type, .. } => {
             print!("{} = alloca {}", result, print_type(alloca_type));
             println!();
         }
-        Instruction::ListComprehension { result, expression, variable, iterable, condition } => {
+        Instruction::Call { result, function, arguments, .. } => {
+            if let Some(result) = result {
+                print!("%{} = ", result);
+            }
+            let arguments: Vec<String> = arguments.iter().map(print_value_str).collect();
+            println!("call @{}({})", function, arguments.join(", "));
+        }
+        Instruction::ListComprehension { result, expression, variable, iterable, condition, .. } => {
             print!("{} = listcomp ", result);
             print_value(expression);
             print!(" for {} in ", variable);
@@ -1624,7 +3872,7 @@ fn print_instruction(instruction: &Instruction) {
             }
             println!();
         }
-        Instruction::Range { result, start, end, inclusive } => {
+        Instruction::Range { result, start, end, inclusive, .. } => {
             print!("{} = range ", result);
             print_value(start);
             if *inclusive {
@@ -1635,7 +3883,7 @@ fn print_instruction(instruction: &Instruction) {
             print_value(end);
             println!();
         }
-        Instruction::ObjectLiteral { result, properties } => {
+        Instruction::ObjectLiteral { result, properties, .. } => {
             print!("{} = object {{", result);
             for (i, (key, value)) in properties.iter().enumerate() {
                 if i > 0 {
@@ -1647,56 +3895,144 @@ fn print_instruction(instruction: &Instruction) {
             print!("}}");
             println!();
         }
-        Instruction::MemberAccess { result, object, property } => {
+        Instruction::MemberAccess { result, object, property, .. } => {
             print!("{} = memberaccess ", result);
             print_value(object);
             print!(".{}", property);
             println!();
         }
-        Instruction::Await { result, value } => {
+        Instruction::NewObject { result, class, .. } => {
+            println!("%{} = new %{}", result, class);
+        }
+        Instruction::NewVariant { result, enumeration, variant, fields, .. } => {
+            let fields: Vec<String> = fields.iter().map(print_value_str).collect();
+            println!("%{} = variant %{} {}({})", result, enumeration, variant, fields.join(", "));
+        }
+        Instruction::MakeTuple { result, elements, .. } => {
+            let elements: Vec<String> = elements.iter().map(print_value_str).collect();
+            println!("%{} = tuple ({})", result, elements.join(", "));
+        }
+        Instruction::AllocaShared { result, alloca_type, .. } => {
+            println!("%{} = alloca shared {}", result, print_type(alloca_type));
+        }
+        Instruction::MakeClosure { result, function, captures, .. } => {
+            let captures: Vec<String> = captures.iter().map(|name| format!("%{}", name)).collect();
+            println!("%{} = closure @{} [{}]", result, function, captures.join(", "));
+        }
+        Instruction::BindCapture { variable, function, index, .. } => {
+            println!("%{} = capture %{}.$env, {}", variable, function, index);
+        }
+        Instruction::ClosureCall { result, closure, arguments, .. } => {
+            if let Some(result) = result {
+                print!("%{} = ", result);
+            }
+            let arguments: Vec<String> = arguments.iter().map(print_value_str).collect();
+            println!("ccall {}({})", print_value_str(closure), arguments.join(", "));
+        }
+        Instruction::Throw { exception, .. } => {
+            println!("throw {}", print_value_str(exception));
+        }
+        Instruction::LandingPad { result, .. } => {
+            println!("%{} = landingpad", result);
+        }
+        Instruction::ExceptionIs { result, exception, class, .. } => {
+            println!("%{} = exception.is {}, %{}", result, print_value_str(exception), class);
+        }
+        Instruction::Catch { result, exception, .. } => {
+            println!("%{} = catch {}", result, print_value_str(exception));
+        }
+        Instruction::NullCheck { result, value, .. } => {
+            println!("%{} = is_null {}", result, print_value_str(value));
+        }
+        Instruction::Unwrap { result, value, .. } => {
+            println!("%{} = unwrap {}", result, print_value_str(value));
+        }
+        Instruction::Pipeline { result, initial, stages, .. } => {
+            let stages: Vec<String> = stages.iter().map(print_stage).collect();
+            println!("%{} = pipeline {} |> {}", result, print_value_str(initial), stages.join(" |> "));
+        }
+        Instruction::PatternMatch { expression, cases, default, .. } => {
+            println!("match {} {{", print_value_str(expression));
+            for case in cases {
+                println!("  case {:?}{}:", case.pattern, if case.guard.is_some() { " if" } else { "" });
+                for instruction in &case.body {
+                    print!("    ");
+                    print_instruction(instruction);
+                }
+            }
+            if let Some(default) = default {
+                println!("  default:");
+                for instruction in default {
+                    print!("    ");
+                    print_instruction(instruction);
+                }
+            }
+            println!("  }}");
+        }
+        Instruction::GetField { result, object, class, index, .. } => {
+            println!("%{} = getfield %{} {}, {}", result, class, print_value_str(object), index);
+        }
+        Instruction::SetField { object, class, index, value, .. } => {
+            println!("setfield %{} {}, {}, {}", class, print_value_str(object), index, print_value_str(value));
+        }
+        Instruction::VirtualCall { result, object, class, slot, arguments, .. } => {
+            if let Some(result) = result {
+                print!("%{} = ", result);
+            }
+            let arguments: Vec<String> = arguments.iter().map(print_value_str).collect();
+            println!("vcall %{} {}[{}]({})", class, print_value_str(object), slot, arguments.join(", "));
+        }
+        Instruction::InterfaceCall { result, object, interface, slot, arguments, .. } => {
+            if let Some(result) = result {
+                print!("%{} = ", result);
+            }
+            let arguments: Vec<String> = arguments.iter().map(print_value_str).collect();
+            println!("icall %{} {}[{}]({})", interface, print_value_str(object), slot, arguments.join(", "));
+        }
+        Instruction::Await { result, value, .. } => {
             print!("{} = await ", result);
             print_value(value);
             println!();
         }
-        Instruction::Yield { result, value } => {
+        Instruction::Yield { result, value, .. } => {
             print!("{} = yield ", result);
             print_value(value);
             println!();
         }
-        Instruction::MutexLock { mutex } => {
+        Instruction::MutexLock { mutex, .. } => {
             print!("mutex.lock ");
             print_value(mutex);
             println!();
         }
-        Instruction::MutexUnlock { mutex } => {
+        Instruction::MutexUnlock { mutex, .. } => {
             print!("mutex.unlock ");
             print_value(mutex);
             println!();
         }
-        Instruction::ConditionWait { condition, mutex } => {
+        Instruction::ConditionWait { condition, mutex, .. } => {
             print!("condition.wait ");
             print_value(condition);
             print!(", ");
             print_value(mutex);
             println!();
         }
-        Instruction::ConditionSignal { condition } => {
+        Instruction::ConditionSignal { condition, .. } => {
             print!("condition.signal ");
             print_value(condition);
             println!();
         }
-        Instruction::ConditionBroadcast { condition } => {
+        Instruction::ConditionBroadcast { condition, .. } => {
             print!("condition.broadcast ");
             print_value(condition);
             println!();
         }
-        Instruction::AtomicLoad { result, address, ordering } => {
+        Instruction::AtomicLoad { result, address, ordering, .. } => {
             print!("{} = atomic.load ", result);
             print_value(address);
             print!(", ordering={:?}", ordering);
             println!();
         }
-        Instruction::AtomicStore { address, value, ordering } => {
+        Instruction::AtomicStore { address, value, ordering, .. } => {
             print!("atomic.store ");
             print_value(address);
             print!(", ");
@@ -1704,7 +4040,7 @@ fn print_instruction(instruction: &Instruction) {
             print!(", ordering={:?}", ordering);
             println!();
         }
-        Instruction::AtomicExchange { result, address, value, ordering } => {
+        Instruction::AtomicExchange { result, address, value, ordering, .. } => {
             print!("{} = atomic.exchange ", result);
             print_value(address);
             print!(", ");
@@ -1712,7 +4048,7 @@ fn print_instruction(instruction: &Instruction) {
             print!(", ordering={:?}", ordering);
             println!();
         }
-        Instruction::AtomicCompareExchange { result, address, expected, desired, success_ordering, failure_ordering } => {
+        Instruction::AtomicCompareExchange { result, address, expected, desired, success_ordering, failure_ordering, .. } => {
             print!("{} = atomic.cmpxchg ", result);
             print_value(address);
             print!(", ");
@@ -1722,7 +4058,7 @@ fn print_instruction(instruction: &Instruction) {
             print!(", success_ordering={:?}, failure_ordering={:?}", success_ordering, failure_ordering);
             println!();
         }
-        Instruction::AtomicFetchAdd { result, address, value, ordering } => {
+        Instruction::AtomicFetchAdd { result, address, value, ordering, .. } => {
             print!("{} = atomic.fetch_add ", result);
             print_value(address);
             print!(", ");
@@ -1730,7 +4066,7 @@ fn print_instruction(instruction: &Instruction) {
             print!(", ordering={:?}", ordering);
             println!();
         }
-        Instruction::AtomicFetchSub { result, address, value, ordering } => {
+        Instruction::AtomicFetchSub { result, address, value, ordering, .. } => {
             print!("{} = atomic.fetch_sub ", result);
             print_value(address);
             print!(", ");
@@ -1754,7 +4090,70 @@ fn print_terminator(terminator: &Terminator) {
             }
             println!();
         }
-        _ => println!("; Terminator not implemented"),
+        Terminator::Branch { target } => println!("br label %{}", target),
+        Terminator::ConditionalBranch { condition, then_target, else_target } => {
+            println!("br {}, label %{}, label %{}", print_value_str(condition), then_target, else_target);
+        }
+        Terminator::Invoke { call, normal, unwind } => {
+            print!("invoke ");
+            print_instruction(call);
+            println!("    to label %{} unwind label %{}", normal, unwind);
+        }
+        Terminator::Unwind { exception, unwind, .. } => {
+            println!("unwind {}{}", print_value_str(exception), print_unwind(unwind));
+        }
+        Terminator::Resume { exception, unwind } => {
+            println!("resume {}{}", print_value_str(exception), print_unwind(unwind));
+        }
+        Terminator::Match { expression, arms, default } => {
+            println!("match {} {{", print_value_str(expression));
+            for arm in arms {
+                if let Some((guard, _)) = &arm.guard {
+                    for instruction in guard {
+                        print!("    ");
+                        print_instruction(instruction);
+                    }
+                }
+                println!("  case {:?}{}: label %{}", arm.pattern, if arm.guard.is_some() { " if" } else { "" }, arm.target);
+            }
+            if let Some(default) = default {
+                println!("  default: label %{}", default);
+            }
+            println!("  }}");
+        }
+    }
+}
+
+/// Text of the landing pad a terminator unwinds to, if it has one
+fn print_unwind(unwind: &Option<String>) -> String {
+    match unwind {
+        Some(target) => format!(" unwind label %{}", target),
+        None => String::new(),
+    }
+}
+
+/// Text of a pipeline stage
+fn print_stage(stage: &Stage) -> String {
+    match stage {
+        Stage::Loop(steps) => {
+            let steps: Vec<String> = steps.iter()
+                .map(|step| match step {
+                    LoopStep::Filter(predicate) => format!("filter {}", print_value_str(predicate)),
+                    LoopStep::Map { function, element_type } => {
+                        format!("map {} -> {}", print_value_str(function), print_type(element_type))
+                    }
+                })
+                .collect();
+            format!("loop [{}]", steps.join(", "))
+        }
+        Stage::Take(count) => format!("take {}", print_value_str(count)),
+        Stage::Skip(count) => format!("skip {}", print_value_str(count)),
+        Stage::Sort(Some(comparator)) => format!("sort {}", print_value_str(comparator)),
+        Stage::Sort(None) => "sort".to_string(),
+        Stage::Reduce { function, initial, result_type } => {
+            let initial = initial.as_ref().map(|initial| format!(", {}", print_value_str(initial))).unwrap_or_default();
+            format!("reduce {}{} -> {}", print_value_str(function), initial, print_type(result_type))
+        }
     }
 }
 
@@ -1773,7 +4172,9 @@ fn print_value_str(value: &Value) -> String {
         Value::Variable(name) => format!("%{}", name),
         Value::InstructionRef(_) => "%<instruction>".to_string(),
         Value::MutexValue => "%mutex".to_string(),
+        Value::Constant(Constant::Null) => "null".to_string(),
         Value::ConditionValue => "%condition".to_string(),
+        _ => "%<value>".to_string(),
     }
 }
 
@@ -1792,6 +4193,15 @@ fn print_type(ty: &Type) -> String {
         Type::Async { inner_type } => format!("async<{}>", print_type(inner_type)),
         Type::Mutex => "mutex".to_string(),
         Type::Condition => "condition".to_string(),
+        Type::Object { name } | Type::Trait { name } => format!("%{}", name),
+        Type::Tuple { element_types } => {
+            let elements: Vec<String> = element_types.iter().map(print_type).collect();
+            format!("({})", elements.join(", "))
+        }
+        Type::Function { param_types, return_type } => {
+            let parameters: Vec<String> = param_types.iter().map(print_type).collect();
+            format!("fn({}) -> {}", parameters.join(", "), print_type(return_type))
+        }
         _ => "<type>".to_string(),
     }
 }
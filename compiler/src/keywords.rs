@@ -0,0 +1,404 @@
+//! Keyword tables for the natural languages KODEON can be written in
+//!
+//! Keywords are data rather than code: each language has a table in `keywords/<locale>.kw`
+//! that maps the words written in source to canonical tokens. Adding a language or a
+//! dialect means adding a table, not new token variants.
+
+use crate::lexer::{is_identifier_continue, is_identifier_start, Token};
+use std::collections::HashMap;
+use std::sync::Arc;
+use unicode_normalization::UnicodeNormalization;
+
+/// Keyword tables shipped with the compiler, by locale
+const BUILTIN_TABLES: &[(&str, &str)] = &[
+    ("id", include_str!("keywords/id.kw")),
+    ("en", include_str!("keywords/en.kw")),
+];
+
+lazy_static::lazy_static! {
+    static ref BUILTIN: Arc<KeywordRegistry> = Arc::new(
+        BUILTIN_TABLES
+            .iter()
+            .fold(KeywordRegistry::new(), |registry, (locale, _)| {
+                registry.with_set(KeywordSet::for_locale(locale).unwrap())
+            })
+    );
+}
+
+/// Error in a keyword table
+#[derive(Debug, PartialEq)]
+pub enum KeywordError {
+    MalformedLine(String, usize),  // line is not of the form `word = Keyword`
+    InvalidWord(String, usize),    // word is not a valid identifier
+    UnknownKeyword(String, usize), // no canonical keyword has this name
+    DuplicateWord(String, usize),  // word is mapped twice in the same table
+}
+
+impl std::fmt::Display for KeywordError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            KeywordError::MalformedLine(text, line) => {
+                write!(f, "Expected 'word = Keyword' at line {} of keyword table, found '{}'", line, text)
+            }
+            KeywordError::InvalidWord(word, line) => {
+                write!(f, "'{}' at line {} of keyword table is not a valid identifier", word, line)
+            }
+            KeywordError::UnknownKeyword(name, line) => {
+                write!(f, "Unknown keyword '{}' at line {} of keyword table", name, line)
+            }
+            KeywordError::DuplicateWord(word, line) => {
+                write!(f, "'{}' is mapped twice in keyword table, again at line {}", word, line)
+            }
+        }
+    }
+}
+
+impl std::error::Error for KeywordError {}
+
+/// Keywords of one natural language, keyed by the words written in source
+#[derive(Debug, Clone)]
+pub struct KeywordSet {
+    locale: String,                // Language tag, e.g. "id" or "en"
+    words: HashMap<String, Token>, // Word in NFC form -> canonical token
+}
+
+impl KeywordSet {
+    /// Create an empty keyword set
+    pub fn new(locale: &str) -> Self {
+        KeywordSet {
+            locale: locale.to_string(),
+            words: HashMap::new(),
+        }
+    }
+
+    /// Keyword set shipped with the compiler for `locale`
+    pub fn for_locale(locale: &str) -> Option<Self> {
+        BUILTIN_TABLES
+            .iter()
+            .find(|(name, _)| *name == locale)
+            .map(|(name, table)| Self::parse(name, table).expect("built-in keyword table is valid"))
+    }
+
+    /// Parse a keyword table with one `word = Keyword` mapping per line
+    ///
+    /// `Keyword` is the name of a canonical token (`Jika`, `Fungsi`, ...) or one of the
+    /// boolean literals `true` and `false`. Blank lines and lines starting with `#` are ignored.
+    pub fn parse(locale: &str, table: &str) -> Result<Self, KeywordError> {
+        let mut set = KeywordSet::new(locale);
+
+        for (index, line) in table.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (word, name) = line
+                .split_once('=')
+                .map(|(word, name)| (word.trim(), name.trim()))
+                .ok_or_else(|| KeywordError::MalformedLine(line.to_string(), line_number))?;
+            if word.is_empty() || name.is_empty() {
+                return Err(KeywordError::MalformedLine(line.to_string(), line_number));
+            }
+            if !is_identifier(word) {
+                return Err(KeywordError::InvalidWord(word.to_string(), line_number));
+            }
+            let token = canonical_token(name)
+                .ok_or_else(|| KeywordError::UnknownKeyword(name.to_string(), line_number))?;
+
+            let word: String = word.nfc().collect();
+            if set.words.contains_key(&word) {
+                return Err(KeywordError::DuplicateWord(word, line_number));
+            }
+            set.words.insert(word, token);
+        }
+
+        Ok(set)
+    }
+
+    /// Add a keyword to the set
+    pub fn with_keyword(mut self, word: &str, token: Token) -> Self {
+        self.words.insert(word.nfc().collect(), token);
+        self
+    }
+
+    /// Language tag of this set
+    pub fn locale(&self) -> &str {
+        &self.locale
+    }
+
+    /// Canonical token for `word`, which must already be in NFC form
+    pub fn get(&self, word: &str) -> Option<&Token> {
+        self.words.get(word)
+    }
+
+    /// Number of words in the set
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    /// Check whether the set has no words
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+}
+
+/// Keyword sets recognized by the lexer
+///
+/// Sets are searched in order, so when two languages use the same word for different
+/// keywords the set added first wins.
+#[derive(Debug, Clone, Default)]
+pub struct KeywordRegistry {
+    sets: Vec<KeywordSet>,
+}
+
+impl KeywordRegistry {
+    /// Create a registry without any keywords
+    pub fn new() -> Self {
+        KeywordRegistry { sets: Vec::new() }
+    }
+
+    /// Registry with every keyword set shipped with the compiler
+    pub fn builtin() -> Arc<KeywordRegistry> {
+        Arc::clone(&BUILTIN)
+    }
+
+    /// Add a keyword set
+    pub fn with_set(mut self, set: KeywordSet) -> Self {
+        self.sets.push(set);
+        self
+    }
+
+    /// Canonical token for `word`, which must already be in NFC form
+    pub fn lookup(&self, word: &str) -> Option<&Token> {
+        self.sets.iter().find_map(|set| set.get(word))
+    }
+
+    /// Locales of the registered sets, in lookup order
+    pub fn locales(&self) -> impl Iterator<Item = &str> {
+        self.sets.iter().map(|set| set.locale())
+    }
+}
+
+/// Check whether `word` is a single identifier
+fn is_identifier(word: &str) -> bool {
+    let mut chars = word.chars();
+    chars.next().is_some_and(is_identifier_start) && chars.all(is_identifier_continue)
+}
+
+/// Token for the canonical keyword `name`
+fn canonical_token(name: &str) -> Option<Token> {
+    let token = match name {
+        "Buat" => Token::Buat,
+        "Jika" => Token::Jika,
+        "Maka" => Token::Maka,
+        "Sebaliknya" => Token::Sebaliknya,
+        "Fungsi" => Token::Fungsi,
+        "Kelas" => Token::Kelas,
+        "Kembalikan" => Token::Kembalikan,
+        "Ulangi" => Token::Ulangi,
+        "Selama" => Token::Selama,
+        "Untuk" => Token::Untuk,
+        "Di" => Token::Di,
+        "Dari" => Token::Dari,
+        "Sampai" => Token::Sampai,
+        "Lakukan" => Token::Lakukan,
+        "Variabel" => Token::Variabel,
+        "Konstan" => Token::Konstan,
+        "Struktur" => Token::Struktur,
+        "Impor" => Token::Impor,
+        "DariModul" => Token::DariModul,
+        "Sebagai" => Token::Sebagai,
+        "Coba" => Token::Coba,
+        "Tangkap" => Token::Tangkap,
+        "Akhirnya" => Token::Akhirnya,
+        "Lempar" => Token::Lempar,
+        "Null" => Token::Null,
+        "Baru" => Token::Baru,
+        "Ini" => Token::Ini,
+        "Super" => Token::Super,
+        "Publik" => Token::Publik,
+        "Pribadi" => Token::Pribadi,
+        "Terlindungi" => Token::Terlindungi,
+        "Statis" => Token::Statis,
+        "Abstrak" => Token::Abstrak,
+        "Akhir" => Token::Akhir,
+        "Sinkron" => Token::Sinkron,
+        "Asal" => Token::Asal,
+        "Pecah" => Token::Pecah,
+        "Lanjut" => Token::Lanjut,
+        "LakukanSementara" => Token::LakukanSementara,
+        "UntukSetiap" => Token::UntukSetiap,
+        "PergiKe" => Token::PergiKe,
+        "Implementasi" => Token::Implementasi,
+        "Antarmuka" => Token::Antarmuka,
+        "Asli" => Token::Asli,
+        "Paket" => Token::Paket,
+        "Ketika" => Token::Ketika,
+        "Biarkan" => Token::Biarkan,
+        "Mut" => Token::Mut,
+        "Ref" => Token::Ref,
+        "Ptr" => Token::Ptr,
+        "UkuranDari" => Token::UkuranDari,
+        "Tipe" => Token::Tipe,
+        "Alias" => Token::Alias,
+        "Ekstensi" => Token::Ekstensi,
+        "Operator" => Token::Operator,
+        "Templat" => Token::Templat,
+        "Menggunakan" => Token::Menggunakan,
+        "Teman" => Token::Teman,
+        "Virtual" => Token::Virtual,
+        "Konst" => Token::Konst,
+        "Volatil" => Token::Volatil,
+        "Daftar" => Token::Daftar,
+        "Peta" => Token::Peta,
+        "Himpunan" => Token::Himpunan,
+        "Opsional" => Token::Opsional,
+        "Hasil" => Token::Hasil,
+        "Tunggu" => Token::Tunggu,
+        "Async" => Token::Async,
+        "Hasilkan" => Token::Hasilkan,
+        "Rentang" => Token::Rentang,
+        "Saring" => Token::Saring,
+        "Petakan" => Token::Petakan,
+        "Kurangi" => Token::Kurangi,
+        "Lipat" => Token::Lipat,
+        "Ambil" => Token::Ambil,
+        "Lompati" => Token::Lompati,
+        "Gabungkan" => Token::Gabungkan,
+        "Urutkan" => Token::Urutkan,
+        "Balik" => Token::Balik,
+        "Panjang" => Token::Panjang,
+        "Kasus" => Token::Kasus,
+        "Adalah" => Token::Adalah,
+        "Ekspor" => Token::Ekspor,
+        "Jalan" => Token::Jalan,
+        "BuatChannel" => Token::BuatChannel,
+        "Kirim" => Token::Kirim,
+        "Terima" => Token::Terima,
+        "KunciMutex" => Token::KunciMutex,
+        "BukaKunciMutex" => Token::BukaKunciMutex,
+        "BuatKondisi" => Token::BuatKondisi,
+        "TungguKondisi" => Token::TungguKondisi,
+        "SinyalKondisi" => Token::SinyalKondisi,
+        "SiarkanKondisi" => Token::SiarkanKondisi,
+        "MuatAtomik" => Token::MuatAtomik,
+        "SimpanAtomik" => Token::SimpanAtomik,
+        "BuatAlamat" => Token::BuatAlamat,
+        "Html" => Token::Html,
+        "Css" => Token::Css,
+        "Script" => Token::Script,
+        "Endpoint" => Token::Endpoint,
+        "Route" => Token::Route,
+        "Middleware" => Token::Middleware,
+        "Dataset" => Token::Dataset,
+        "Visualize" => Token::Visualize,
+        "Scene" => Token::Scene,
+        "Sprite" => Token::Sprite,
+        "Animate" => Token::Animate,
+        "Collide" => Token::Collide,
+        "Sensor" => Token::Sensor,
+        "Actuator" => Token::Actuator,
+        "Pin" => Token::Pin,
+        "Read" => Token::Read,
+        "Write" => Token::Write,
+        "Task" => Token::Task,
+        "Schedule" => Token::Schedule,
+        "Trigger" => Token::Trigger,
+        "Workflow" => Token::Workflow,
+        "Kanvas" => Token::Kanvas,
+        "Gambar" => Token::Gambar,
+        "Warna" => Token::Warna,
+        "Bentuk" => Token::Bentuk,
+        "Animasi" => Token::Animasi,
+        "Bingkai" => Token::Bingkai,
+        "Suara" => Token::Suara,
+        "Musik" => Token::Musik,
+        "Seni" => Token::Seni,
+        "Desain" => Token::Desain,
+        "Kreatif" => Token::Kreatif,
+        "Ekspresi" => Token::Ekspresi,
+        "Bayangkan" => Token::Bayangkan,
+        "Mimpi" => Token::Mimpi,
+        "Kubit" => Token::Kubit,
+        "Gerbang" => Token::Gerbang,
+        "Sirkuit" => Token::Sirkuit,
+        "Ukur" => Token::Ukur,
+        "Superposisi" => Token::Superposisi,
+        "Keterkaitan" => Token::Keterkaitan,
+        "Simulasi" => Token::Simulasi,
+        "Jaringan" => Token::Jaringan,
+        "Lapisan" => Token::Lapisan,
+        "Tensor" => Token::Tensor,
+        "Model" => Token::Model,
+        "Latih" => Token::Latih,
+        "Prediksi" => Token::Prediksi,
+        "Optimisasi" => Token::Optimisasi,
+        "FungsiHilang" => Token::FungsiHilang,
+        "Gradien" => Token::Gradien,
+        "And" => Token::And,
+        "Or" => Token::Or,
+        "Not" => Token::Not,
+        "true" => Token::Boolean(true),
+        "false" => Token::Boolean(false),
+        _ => return None,
+    };
+    Some(token)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_tables_parse() {
+        for (locale, table) in BUILTIN_TABLES {
+            let set = KeywordSet::parse(locale, table).unwrap();
+            assert!(!set.is_empty(), "Keyword table '{}' is empty", locale);
+        }
+    }
+
+    #[test]
+    fn test_languages_share_canonical_tokens() {
+        let indonesian = KeywordSet::for_locale("id").unwrap();
+        let english = KeywordSet::for_locale("en").unwrap();
+
+        assert_eq!(indonesian.get("jika"), Some(&Token::Jika));
+        assert_eq!(english.get("if"), Some(&Token::Jika));
+        assert_eq!(indonesian.get("model"), english.get("model"));
+        assert_eq!(english.get("train"), Some(&Token::Latih));
+        assert_eq!(english.get("true"), Some(&Token::Boolean(true)));
+        assert_eq!(english.get("jika"), None);
+    }
+
+    #[test]
+    fn test_registry_lookup_order() {
+        let first = KeywordSet::new("a").with_keyword("kata", Token::Jika);
+        let second = KeywordSet::new("b").with_keyword("kata", Token::Selama);
+        let registry = KeywordRegistry::new().with_set(first).with_set(second);
+
+        assert_eq!(registry.lookup("kata"), Some(&Token::Jika));
+        assert_eq!(registry.locales().collect::<Vec<_>>(), vec!["a", "b"]);
+        assert_eq!(KeywordRegistry::builtin().locales().collect::<Vec<_>>(), vec!["id", "en"]);
+    }
+
+    #[test]
+    fn test_table_errors() {
+        assert_eq!(
+            KeywordSet::parse("jv", "# Javanese\nyen Jika").unwrap_err(),
+            KeywordError::MalformedLine("yen Jika".to_string(), 2)
+        );
+        assert_eq!(
+            KeywordSet::parse("jv", "do-while = LakukanSementara").unwrap_err(),
+            KeywordError::InvalidWord("do-while".to_string(), 1)
+        );
+        assert_eq!(
+            KeywordSet::parse("jv", "yen = If").unwrap_err(),
+            KeywordError::UnknownKeyword("If".to_string(), 1)
+        );
+        assert_eq!(
+            KeywordSet::parse("jv", "yen = Jika\nyen = Selama").unwrap_err(),
+            KeywordError::DuplicateWord("yen".to_string(), 2)
+        );
+    }
+}
@@ -1,7 +1,11 @@
 //! Lexer for the KODEON programming language
 
+use crate::keywords::KeywordRegistry;
+use std::collections::VecDeque;
 use std::str::Chars;
 use std::iter::Peekable;
+use std::sync::Arc;
+use unicode_normalization::UnicodeNormalization;
 
 /// Position in the source code with enhanced error tracking
 #[derive(Debug, Clone, PartialEq)]
@@ -47,12 +51,101 @@ pub struct TokenWithPosition {
     pub token: Token,
     pub position: Position,
     pub length: usize, // Length of the token in characters
+    pub span: Span,    // Bytes of the token in its file; empty for Indent, Dedent and Eof
+}
+
+/// Identifier of a source file, assigned by the code that loads the files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct FileId(pub u32);
+
+/// Byte range in a source file, from `start` up to but not including `end`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub file: FileId,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    /// Create a new span
+    pub fn new(file: FileId, start: usize, end: usize) -> Self {
+        Span { file, start, end }
+    }
+
+    /// Span from the start of `self` to the end of `other`
+    pub fn to(&self, other: Span) -> Span {
+        Span::new(self.file, self.start, other.end.max(self.start))
+    }
+
+    /// Length of the span in bytes
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Check whether the span covers no text
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Text of the span in `source`
+    pub fn text<'s>(&self, source: &'s str) -> &'s str {
+        &source[self.start..self.end]
+    }
+}
+
+/// Kind of source text that does not affect the meaning of a program
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriviaKind {
+    Whitespace,   // spaces, tabs and carriage returns
+    Newline,      // a line break that is not a Newline token, e.g. inside parentheses
+    LineComment,  // `// ...`, without the line break
+    BlockComment, // `/* ... */`
+    DocComment,   // `/// ...` or `/** ... */`
+}
+
+/// Whitespace or a comment kept by the lossless lexer
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trivia {
+    pub kind: TriviaKind,
+    pub span: Span,
+}
+
+/// Token with its exact source text and the trivia around it
+///
+/// Concatenating the leading trivia, the token and the trailing trivia of every token
+/// reproduces the source exactly.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LosslessToken {
+    pub token: Token,
+    pub position: Position,
+    pub span: Span,                   // Text of the token; empty for Indent, Dedent and Eof
+    pub leading_trivia: Vec<Trivia>,  // Trivia before the token that is not trailing trivia of the previous one
+    pub trailing_trivia: Vec<Trivia>, // Trivia after the token up to the end of its line
+}
+
+impl LosslessToken {
+    /// Span of the token including its trivia
+    pub fn full_span(&self) -> Span {
+        let start = self.leading_trivia.first().map_or(self.span.start, |trivia| trivia.span.start);
+        let end = self.trailing_trivia.last().map_or(self.span.end, |trivia| trivia.span.end);
+        Span::new(self.span.file, start, end)
+    }
+}
+
+/// Piece of an interpolated string literal
+#[derive(Debug, PartialEq, Clone)]
+pub enum StringPart {
+    Text(String),
+    Expression(String, Position, usize), // Source of an `{expression}`, where it starts, and its byte offset
 }
 
 /// Token types for the KODEON language
 #[derive(Debug, PartialEq, Clone)]
 pub enum Token {
-    // Keywords - Indonesian
+    // Keywords
+    //
+    // Each concept has one canonical variant, named after its Indonesian word; the words
+    // of every language map to it through the keyword tables in `keywords`
     Buat,           // create
     Jika,           // if
     Maka,           // then
@@ -77,8 +170,6 @@ pub enum Token {
     Tangkap,        // catch
     Akhirnya,       // finally
     Lempar,         // throw
-    Benar,          // true
-    Salah,          // false
     Null,           // null
     Baru,           // new
     Ini,            // this
@@ -135,8 +226,11 @@ pub enum Token {
     Urutkan,        // sort
     Balik,          // reverse
     Panjang,        // length/len
+    Kasus,          // case (inside ketika)
+    Adalah,         // is (type test)
+    Ekspor,         // export
 
-    // Concurrency Keywords - Indonesian
+    // Concurrency Keywords
     Jalan,          // go (goroutine)
     BuatChannel,    // make_channel
     Kirim,          // send (channel send)
@@ -151,21 +245,6 @@ pub enum Token {
     SimpanAtomik,   // atomic_store
     BuatAlamat,     // create_address (atomic address)
 
-    // Concurrency Keywords - English
-    Go,             // go (goroutine)
-    MakeChannel,    // make_channel
-    Send,           // send (channel send)
-    Receive,        // receive (channel receive)
-    LockMutex,      // lock_mutex
-    UnlockMutex,    // unlock_mutex
-    CreateCondition,// create_condition
-    WaitCondition,  // wait_condition
-    SignalCondition,// signal_condition
-    BroadcastCondition, // broadcast_condition
-    AtomicLoad,     // atomic_load
-    AtomicStore,    // atomic_store
-    CreateAddress,  // create_address (atomic address)
-
     // Domain-Specific Keywords for Universal Use
     // Web Development
     Html,           // html
@@ -175,10 +254,7 @@ pub enum Token {
     Route,          // route
     Middleware,     // middleware
 
-    // Data Science & AI
-    Model,          // model
-    Train,          // train
-    Predict,        // predict
+    // Data Science
     Dataset,        // dataset
     Visualize,      // visualize
 
@@ -202,22 +278,6 @@ pub enum Token {
     Workflow,       // workflow
 
     // Creative Coding Keywords
-    Canvas,         // canvas
-    Draw,           // draw
-    Color,          // color
-    Shape,          // shape
-    Animation,      // animation
-    Frame,          // frame
-    Sound,          // sound
-    Music,          // music
-    Art,            // art
-    Design,         // design
-    Creative,       // creative
-    Express,        // express
-    Imagine,        // imagine
-    Dream,          // dream
-
-    // Creative Coding Keywords - Indonesian
     Kanvas,         // canvas
     Gambar,         // draw
     Warna,          // color
@@ -233,7 +293,7 @@ pub enum Token {
     Bayangkan,      // imagine
     Mimpi,          // dream
 
-    // Quantum Computing Keywords - Indonesian
+    // Quantum Computing Keywords
     Kubit,          // qubit
     Gerbang,        // gate
     Sirkuit,        // circuit
@@ -242,16 +302,7 @@ pub enum Token {
     Keterkaitan,    // entanglement
     Simulasi,       // simulate
 
-    // Quantum Computing Keywords - English
-    Qubit,          // qubit
-    Gate,           // gate
-    Circuit,        // circuit
-    Measure,        // measure
-    Superposition,  // superposition
-    Entanglement,   // entanglement
-    Simulate,       // simulate
-
-    // Neural Network Keywords - Indonesian
+    // Neural Network Keywords
     Jaringan,       // network
     Lapisan,        // layer
     Tensor,         // tensor
@@ -262,156 +313,12 @@ pub enum Token {
     FungsiHilang,   // loss_function
     Gradien,        // gradient
 
-    // Neural Network Keywords - English
-    Network,        // network
-    Layer,          // layer
-    TensorEng,      // tensor
-    ModelEng,       // model
-    Train,          // train
-    Predict,        // predict
-    Optimize,       // optimize
-    LossFunction,   // loss_function
-    Gradient,       // gradient
-
-    // Keywords - English
-    Create,         // create
-    If,             // if
-    Then,           // then
-    Otherwise,      // otherwise
-    Function,       // function
-    Class,          // class
-    Return,         // return
-    Repeat,         // repeat
-    While,          // while
-    For,            // for
-    In,             // in
-    From,           // from
-    To,             // to
-    Do,             // do
-    Variable,       // variable
-    Constant,       // constant
-    Struct,         // struct
-    Import,         // import
-    FromModule,     // from module
-    As,             // as
-    Try,            // try
-    Catch,          // catch
-    Finally,        // finally
-    Throw,          // throw
-    True,           // true
-    False,          // false
-    NullEng,        // null
-    New,            // new
-    This,           // this
-    SuperEng,       // super
-    Public,         // public
-    Private,        // private
-    Protected,      // protected
-    Static,         // static
-    Abstract,       // abstract
-    Final,          // final
-    Synchronized,   // synchronized
-    Assert,         // assert
-    Break,          // break
-    Continue,       // continue
-    DoWhile,        // do-while
-    ForEach,        // for-each
-    Goto,           // goto
-    Implements,     // implements
-    Interface,      // interface
-    Native,         // native
-    Package,        // package
-    When,           // when (pattern matching)
-    Let,            // let (immutable binding)
-    MutEng,         // mut (mutable binding)
-    RefEng,         // ref (reference)
-    PtrEng,         // ptr (pointer)
-    Sizeof,         // sizeof
-    TypeEng,        // type
-    AliasEng,       // alias
-    Extension,      // extension
-    OperatorEng,    // operator
-    Template,       // template
-    Using,          // using
-    Friend,         // friend
-    VirtualEng,     // virtual
-    Const,          // const
-    Volatile,       // volatile
-    List,           // list
-    Map,            // map
-    Set,            // set
-    Optional,       // optional
-    Result,         // result
-    Await,          // await
-    AsyncEng,       // async
-    Yield,          // yield
-    Range,          // range
-    Filter,         // filter
-    MapFunc,        // map (functional)
-    Reduce,         // reduce
-    Fold,           // fold
-    Take,           // take
-    Skip,           // skip
-    Join,           // join
-    Sort,           // sort
-    Reverse,        // reverse
-    Length,         // length/len
-
-    // Domain-Specific Keywords for Universal Use
-    // Web Development
-    HtmlEng,        // html
-    CssEng,         // css
-    ScriptEng,      // script
-    EndpointEng,    // endpoint
-    RouteEng,       // route
-    MiddlewareEng,  // middleware
-
-    // Data Science & AI
-    ModelEng,       // model
-    TrainEng,       // train
-    PredictEng,     // predict
-    DatasetEng,     // dataset
-    VisualizeEng,   // visualize
-
-    // Game Development
-    SceneEng,       // scene
-    SpriteEng,      // sprite
-    AnimateEng,     // animate
-    CollideEng,     // collide
-
-    // IoT & Embedded
-    SensorEng,      // sensor
-    ActuatorEng,    // actuator
-    PinEng,         // pin
-    ReadEng,        // read
-    WriteEng,       // write
-
-    // Automation & Scripting
-    TaskEng,        // task
-    ScheduleEng,    // schedule
-    TriggerEng,     // trigger
-    WorkflowEng,    // workflow
-
-    // Creative Coding Keywords
-    CanvasEng,      // canvas
-    DrawEng,        // draw
-    ColorEng,       // color
-    ShapeEng,       // shape
-    AnimationEng,   // animation
-    FrameEng,       // frame
-    SoundEng,       // sound
-    MusicEng,       // music
-    ArtEng,         // art
-    DesignEng,      // design
-    CreativeEng,    // creative
-    ExpressEng,     // express
-    ImagineEng,     // imagine
-    DreamEng,       // dream
-
     // Literals
     Identifier(String),
-    Number(f64),
+    Integer(i64),
+    Float(f64),
     String(String),
+    InterpolatedString(Vec<StringPart>), // "Halo {nama}"
     Boolean(bool),
 
     // Operators
@@ -440,13 +347,17 @@ pub enum Token {
     LeftShift,      // <<
     RightShift,     // >>
     Arrow,          // ->
+    LeftArrow,      // <- (channel send/receive)
     FatArrow,       // =>
+    PipeArrow,      // |>
     DotDot,         // ..
     DotDotDot,      // ...
     At,             // @
     Hash,           // #
     Dollar,         // $
     Question,       // ?
+    QuestionDot,    // ?. (safe navigation)
+    Elvis,          // ?: (default of an optional value)
     Bang,           // !
     Tilde,          // ~
     Backtick,       // `
@@ -469,20 +380,40 @@ pub enum Token {
     // Comments
     LineComment(String),
     BlockComment(String),
+    DocComment(String), // `/// ...` or `/** ... */`, without the comment markers
 
     // Special
     Newline,
     Indent,
     Dedent,
+    Invalid, // text the lexer reported an error for, up to the end of its line
     Eof,
 }
 
+/// Check whether `ch` can start an identifier (Unicode XID_Start or `_`)
+pub fn is_identifier_start(ch: char) -> bool {
+    ch == '_' || unicode_ident::is_xid_start(ch)
+}
+
+/// Check whether `ch` can continue an identifier (Unicode XID_Continue)
+pub fn is_identifier_continue(ch: char) -> bool {
+    unicode_ident::is_xid_continue(ch)
+}
+
 /// Lexer error with position information
 #[derive(Debug)]
 pub enum LexerError {
     InvalidCharacter(char, Position),
     UnterminatedString(Position),
     UnterminatedComment(Position),
+    InconsistentIndentation(Position), // tabs and spaces mixed in indentation
+    InvalidDedent(Position),           // dedent does not match any outer indentation level
+    InvalidNumber(String, &'static str, Position), // malformed numeric literal and the reason
+    IntegerOverflow(String, Position), // integer literal does not fit in 64 bits
+    FloatOverflow(String, Position),   // float literal is too large to represent
+    InvalidEscape(String, Position),   // unknown or malformed escape sequence in a string
+    UnterminatedInterpolation(Position), // `{` in a string without a matching `}`
+    EmptyInterpolation(Position),      // `{}` in a string
 }
 
 impl std::fmt::Display for LexerError {
@@ -497,12 +428,87 @@ impl std::fmt::Display for LexerError {
             LexerError::UnterminatedComment(pos) => {
                 write!(f, "Unterminated comment at line {}, column {}", pos.line, pos.column)
             }
+            LexerError::InconsistentIndentation(pos) => {
+                write!(f, "Inconsistent use of tabs and spaces in indentation at line {}, column {}", pos.line, pos.column)
+            }
+            LexerError::InvalidDedent(pos) => {
+                write!(f, "Unindent does not match any outer indentation level at line {}, column {}", pos.line, pos.column)
+            }
+            LexerError::InvalidNumber(literal, reason, pos) => {
+                write!(f, "Invalid number '{}' at line {}, column {}: {}", literal, pos.line, pos.column, reason)
+            }
+            LexerError::IntegerOverflow(literal, pos) => {
+                write!(f, "Integer '{}' does not fit in 64 bits at line {}, column {}", literal, pos.line, pos.column)
+            }
+            LexerError::FloatOverflow(literal, pos) => {
+                write!(f, "Float '{}' is too large at line {}, column {}", literal, pos.line, pos.column)
+            }
+            LexerError::InvalidEscape(escape, pos) => {
+                write!(f, "Invalid escape sequence '{}' at line {}, column {}", escape, pos.line, pos.column)
+            }
+            LexerError::UnterminatedInterpolation(pos) => {
+                write!(f, "Unterminated interpolation at line {}, column {}", pos.line, pos.column)
+            }
+            LexerError::EmptyInterpolation(pos) => {
+                write!(f, "Empty interpolation at line {}, column {}", pos.line, pos.column)
+            }
         }
     }
 }
 
 impl std::error::Error for LexerError {}
 
+impl LexerError {
+    /// Stable code of the error in the message catalog
+    pub fn code(&self) -> &'static str {
+        match self {
+            LexerError::InvalidCharacter(..) => "K0001",
+            LexerError::UnterminatedString(_) => "K0002",
+            LexerError::UnterminatedComment(_) => "K0003",
+            LexerError::InconsistentIndentation(_) => "K0004",
+            LexerError::InvalidDedent(_) => "K0005",
+            LexerError::InvalidNumber(..) => "K0006",
+            LexerError::IntegerOverflow(..) => "K0007",
+            LexerError::FloatOverflow(..) => "K0008",
+            LexerError::InvalidEscape(..) => "K0009",
+            LexerError::UnterminatedInterpolation(_) => "K0010",
+            LexerError::EmptyInterpolation(_) => "K0011",
+        }
+    }
+
+    /// Where the error was found
+    pub fn position(&self) -> &Position {
+        match self {
+            LexerError::InvalidCharacter(_, position)
+            | LexerError::UnterminatedString(position)
+            | LexerError::UnterminatedComment(position)
+            | LexerError::InconsistentIndentation(position)
+            | LexerError::InvalidDedent(position)
+            | LexerError::InvalidNumber(_, _, position)
+            | LexerError::IntegerOverflow(_, position)
+            | LexerError::FloatOverflow(_, position)
+            | LexerError::InvalidEscape(_, position)
+            | LexerError::UnterminatedInterpolation(position)
+            | LexerError::EmptyInterpolation(position) => position,
+        }
+    }
+
+    /// Details that fill the placeholders of the catalog message
+    pub fn arguments(&self) -> Vec<(&'static str, String)> {
+        match self {
+            LexerError::InvalidCharacter(ch, _) => vec![("character", ch.to_string())],
+            LexerError::InvalidNumber(literal, reason, _) => {
+                vec![("literal", literal.clone()), ("reason", reason.to_string())]
+            }
+            LexerError::IntegerOverflow(literal, _) | LexerError::FloatOverflow(literal, _) => {
+                vec![("literal", literal.clone())]
+            }
+            LexerError::InvalidEscape(escape, _) => vec![("escape", escape.clone())],
+            _ => vec![],
+        }
+    }
+}
+
 /// Lexer for KODEON source code
 pub struct Lexer<'a> {
     chars: Peekable<Chars<'a>>,
@@ -510,7 +516,18 @@ pub struct Lexer<'a> {
     indent_stack: Vec<usize>,
     line: usize,
     column: usize,
+    offset: usize,
+    byte_offset: usize,         // Byte offset of the next character in the file
     input: &'a str,
+    start_offset: usize,        // Character offset of the start of `input`
+    start_byte: usize,          // Byte offset of the start of `input`
+    file: FileId,               // File that spans refer to
+    at_line_start: bool,        // Next character begins a new logical line
+    brackets: Vec<char>,        // Open ( and [, and { inside them: 'b' for a block, '{' for a map literal
+    previous: Option<Token>,    // Last token read, other than comments
+    indent_char: Option<char>,  // Whitespace character used for indentation in this file
+    pending_tokens: VecDeque<TokenWithPosition>, // Indent/Dedent tokens waiting to be returned
+    keywords: Arc<KeywordRegistry>, // Words that lex as keywords instead of identifiers
 }
 
 impl<'a> Lexer<'a> {
@@ -522,20 +539,64 @@ impl<'a> Lexer<'a> {
             indent_stack: Vec::new(),
             line: 1,
             column: 1,
+            offset: 0,
+            byte_offset: 0,
             input,
+            start_offset: 0,
+            start_byte: 0,
+            file: FileId::default(),
+            at_line_start: true,
+            brackets: Vec::new(),
+            previous: None,
+            indent_char: None,
+            pending_tokens: VecDeque::new(),
+            keywords: KeywordRegistry::builtin(),
         }
     }
 
+    /// Recognize the keywords in `keywords` instead of the built-in Indonesian and English ones
+    pub fn with_keywords(mut self, keywords: Arc<KeywordRegistry>) -> Self {
+        self.keywords = keywords;
+        self
+    }
+
+    /// Start lexing at `position`, `byte_offset` bytes into the file, instead of the start
+    /// of a file, for source that is embedded in another file such as an interpolated expression
+    pub fn with_start(mut self, position: Position, byte_offset: usize) -> Self {
+        self.line = position.line;
+        self.column = position.column;
+        self.offset = position.offset;
+        self.start_offset = position.offset;
+        self.byte_offset = byte_offset;
+        self.start_byte = byte_offset;
+        self.at_line_start = false;
+        self
+    }
+
+    /// Report spans in `file`
+    pub fn with_file(mut self, file: FileId) -> Self {
+        self.file = file;
+        self
+    }
+
     /// Get the current position
     fn current_position(&self) -> Position {
         Position {
             line: self.line,
             column: self.column,
+            offset: self.offset,
         }
     }
 
+    /// Empty span at the current character
+    fn empty_span(&self) -> Span {
+        Span::new(self.file, self.byte_offset, self.byte_offset)
+    }
+
     /// Advance the position counter
     fn advance_position(&mut self, ch: char) {
+        self.offset += 1;
+        self.byte_offset += ch.len_utf8();
         if ch == '\n' {
             self.line += 1;
             self.column = 1;
@@ -544,15 +605,217 @@ impl<'a> Lexer<'a> {
         }
     }
 
+    /// Tokenize the entire input, including the final Eof token
+    pub fn tokenize(&mut self) -> Result<Vec<TokenWithPosition>, LexerError> {
+        let mut tokens = Vec::new();
+        loop {
+            let token = self.next_token_with_position()?;
+            let is_eof = token.token == Token::Eof;
+            tokens.push(token);
+            if is_eof {
+                break;
+            }
+        }
+        Ok(tokens)
+    }
+
+    /// Tokenize the entire input, recovering from errors
+    ///
+    /// After an error the rest of the line becomes one `Invalid` token, and lexing goes on
+    /// with the next line, so one run reports the errors of every line.
+    pub fn tokenize_recovering(&mut self) -> (Vec<TokenWithPosition>, Vec<LexerError>) {
+        let mut tokens = Vec::new();
+        let mut errors = Vec::new();
+        loop {
+            match self.next_token_with_position() {
+                Ok(token) => {
+                    let is_eof = token.token == Token::Eof;
+                    tokens.push(token);
+                    if is_eof {
+                        break;
+                    }
+                }
+                Err(error) => {
+                    // Indentation tokens queued before the error come first
+                    tokens.extend(self.pending_tokens.drain(..));
+                    let position = error.position().clone();
+                    let start = self.byte_at(&position);
+                    while let Some(&ch) = self.chars.peek() {
+                        if ch == '\n' {
+                            break;
+                        }
+                        self.chars.next();
+                        self.advance_position(ch);
+                    }
+                    tokens.push(TokenWithPosition {
+                        token: Token::Invalid,
+                        length: self.offset - position.offset,
+                        position,
+                        span: Span::new(self.file, start, self.byte_offset),
+                    });
+                    errors.push(error);
+                }
+            }
+        }
+        (tokens, errors)
+    }
+
+    /// Byte offset in the file of `position`, which the lexer has passed
+    fn byte_at(&self, position: &Position) -> usize {
+        let skipped = position.offset - self.start_offset;
+        self.start_byte + self.input.char_indices().nth(skipped).map_or(self.input.len(), |(index, _)| index)
+    }
+
+    /// Tokenize the entire input without losing any text
+    ///
+    /// Whitespace and comments become trivia attached to the neighbouring tokens, and every
+    /// token and trivia carries its byte span in the input, so tools such as formatters can
+    /// rebuild the source exactly. Indent, Dedent and Eof tokens have empty spans.
+    pub fn tokenize_lossless(&mut self) -> Result<Vec<LosslessToken>, LexerError> {
+        let tokens = self.tokenize()?;
+
+        let mut result: Vec<LosslessToken> = Vec::with_capacity(tokens.len());
+        let mut pending = Vec::new(); // trivia since the last token
+        let mut cursor = self.start_byte;
+        let mut previous_line_end = true; // the last real token ends a line (or there is none)
+
+        for token in tokens {
+            let kind = match token.token {
+                Token::LineComment(_) => Some(TriviaKind::LineComment),
+                Token::BlockComment(_) => Some(TriviaKind::BlockComment),
+                Token::DocComment(_) => Some(TriviaKind::DocComment),
+                _ => None,
+            };
+            let is_synthetic = matches!(token.token, Token::Indent | Token::Dedent);
+
+            let span = if is_synthetic {
+                Span::new(self.file, cursor, cursor)
+            } else {
+                token.span
+            };
+
+            if !is_synthetic {
+                self.push_gap_trivia(cursor, span.start, &mut pending);
+                cursor = span.end;
+            }
+
+            if let Some(kind) = kind {
+                pending.push(Trivia { kind, span });
+                continue;
+            }
+
+            // Trivia on the same line as the previous token trails it; the rest leads this one
+            let mut leading = std::mem::take(&mut pending);
+            if !previous_line_end && !is_synthetic {
+                let split = leading
+                    .iter()
+                    .position(|trivia| trivia.kind == TriviaKind::Newline)
+                    .unwrap_or(leading.len());
+                let trailing: Vec<Trivia> = leading.drain(..split).collect();
+                if let Some(previous) = result.iter_mut().rev().find(|previous| !previous.span.is_empty()) {
+                    previous.trailing_trivia = trailing;
+                }
+            }
+            if !is_synthetic {
+                previous_line_end = token.token == Token::Newline;
+            }
+
+            result.push(LosslessToken {
+                token: token.token,
+                position: token.position,
+                span,
+                leading_trivia: leading,
+                trailing_trivia: Vec::new(),
+            });
+        }
+
+        Ok(result)
+    }
+
+    /// Split the text between two tokens into whitespace and line break trivia
+    fn push_gap_trivia(&self, start: usize, end: usize, trivia: &mut Vec<Trivia>) {
+        let span = |start, end| Span::new(self.file, start, end);
+        let mut run_start = start;
+        for (index, ch) in self.input[start - self.start_byte..end - self.start_byte].char_indices() {
+            if ch == '\n' {
+                let offset = start + index;
+                if run_start < offset {
+                    trivia.push(Trivia { kind: TriviaKind::Whitespace, span: span(run_start, offset) });
+                }
+                trivia.push(Trivia { kind: TriviaKind::Newline, span: span(offset, offset + 1) });
+                run_start = offset + 1;
+            }
+        }
+        if run_start < end {
+            trivia.push(Trivia { kind: TriviaKind::Whitespace, span: span(run_start, end) });
+        }
+    }
+
     /// Get the next token from the input
     pub fn next_token(&mut self) -> Result<Token, LexerError> {
-        // Skip whitespace (but track indentation)
+        Ok(self.next_token_with_position()?.token)
+    }
+
+    /// Get the next token from the input together with its position
+    pub fn next_token_with_position(&mut self) -> Result<TokenWithPosition, LexerError> {
+        if let Some(token) = self.pending_tokens.pop_front() {
+            return Ok(token);
+        }
+
+        // Measure indentation at the start of each logical line
+        if self.at_line_start {
+            self.at_line_start = false;
+            self.read_indentation()?;
+            if let Some(token) = self.pending_tokens.pop_front() {
+                return Ok(token);
+            }
+        }
+
+        // Skip whitespace (indentation has already been tracked)
         self.skip_whitespace()?;
 
+        let position = self.current_position();
+        let start = self.byte_offset;
+
+        // Close any open indentation blocks before the end of the file
+        if self.chars.peek().is_none() {
+            while let Some(indent) = self.indent_stack.pop() {
+                self.current_indent = indent;
+                self.pending_tokens.push_back(TokenWithPosition {
+                    token: Token::Dedent,
+                    position: position.clone(),
+                    length: 0,
+                    span: self.empty_span(),
+                });
+            }
+            self.pending_tokens.push_back(TokenWithPosition {
+                token: Token::Eof,
+                position,
+                length: 0,
+                span: self.empty_span(),
+            });
+            return Ok(self.pending_tokens.pop_front().unwrap());
+        }
+
+        let token = self.read_token()?;
+        let length = self.offset - position.offset;
+        if !matches!(token, Token::LineComment(_) | Token::BlockComment(_) | Token::DocComment(_)) {
+            self.previous = Some(token.clone());
+        }
+
+        Ok(TokenWithPosition {
+            token,
+            position,
+            length,
+            span: Span::new(self.file, start, self.byte_offset),
+        })
+    }
+
+    /// Read a single token starting at the current character
+    fn read_token(&mut self) -> Result<Token, LexerError> {
         // Check for EOF
-        match self.chars.peek() {
-            None => return Ok(Token::Eof),
-            _ => {}
+        if self.chars.peek().is_none() {
+            return Ok(Token::Eof);
         }
 
         // Check for comments
@@ -566,7 +829,8 @@ impl<'a> Lexer<'a> {
             }
         }
 
-        let position = self.current_position();
+        // r"..." is a raw string, not an identifier
+        let raw_string = self.chars.peek() == Some(&'r') && matches!(self.peek_second(), Some('"' | '\''));
 
         // Check for single character tokens
         match self.chars.peek() {
@@ -578,7 +842,7 @@ impl<'a> Lexer<'a> {
                     self.advance_position('+');
                     return Ok(Token::Increment);
                 }
-                return Ok(Token::Plus);
+                Ok(Token::Plus)
             }
             Some(&'-') => {
                 self.chars.next();
@@ -588,7 +852,12 @@ impl<'a> Lexer<'a> {
                     self.advance_position('-');
                     return Ok(Token::Decrement);
                 }
-                return Ok(Token::Minus);
+                if let Some(&'>') = self.chars.peek() {
+                    self.chars.next();
+                    self.advance_position('>');
+                    return Ok(Token::Arrow);
+                }
+                Ok(Token::Minus)
             }
             Some(&'*') => {
                 self.chars.next();
@@ -598,17 +867,17 @@ impl<'a> Lexer<'a> {
                     self.advance_position('*');
                     return Ok(Token::Power);
                 }
-                return Ok(Token::Multiply);
+                Ok(Token::Multiply)
             }
             Some(&'/') => {
                 self.chars.next();
                 self.advance_position('/');
-                return Ok(Token::Divide);
+                Ok(Token::Divide)
             }
             Some(&'%') => {
                 self.chars.next();
                 self.advance_position('%');
-                return Ok(Token::Modulo);
+                Ok(Token::Modulo)
             }
             Some(&'=') => {
                 self.chars.next();
@@ -618,7 +887,12 @@ impl<'a> Lexer<'a> {
                     self.advance_position('=');
                     return Ok(Token::Equal);
                 }
-                return Ok(Token::Assign);
+                if let Some(&'>') = self.chars.peek() {
+                    self.chars.next();
+                    self.advance_position('>');
+                    return Ok(Token::FatArrow);
+                }
+                Ok(Token::Assign)
             }
             Some(&'!') => {
                 self.chars.next();
@@ -629,7 +903,7 @@ impl<'a> Lexer<'a> {
                     return Ok(Token::NotEqual);
                 }
                 // Standalone '!' for negation
-                return Ok(Token::Not);
+                Ok(Token::Not)
             }
             Some(&'<') => {
                 self.chars.next();
@@ -639,7 +913,12 @@ impl<'a> Lexer<'a> {
                     self.advance_position('=');
                     return Ok(Token::LessEqual);
                 }
-                return Ok(Token::Less);
+                if let Some(&'-') = self.chars.peek() {
+                    self.chars.next();
+                    self.advance_position('-');
+                    return Ok(Token::LeftArrow);
+                }
+                Ok(Token::Less)
             }
             Some(&'>') => {
                 self.chars.next();
@@ -649,155 +928,490 @@ impl<'a> Lexer<'a> {
                     self.advance_position('=');
                     return Ok(Token::GreaterEqual);
                 }
-                return Ok(Token::Greater);
+                Ok(Token::Greater)
+            }
+            Some(&'|') => {
+                let pos = self.current_position();
+                self.chars.next();
+                self.advance_position('|');
+                if let Some(&'>') = self.chars.peek() {
+                    self.chars.next();
+                    self.advance_position('>');
+                    return Ok(Token::PipeArrow);
+                }
+                Err(LexerError::InvalidCharacter('|', pos))
             }
             Some(&'(') => {
                 self.chars.next();
                 self.advance_position('(');
-                return Ok(Token::LeftParen);
+                self.brackets.push('(');
+                Ok(Token::LeftParen)
             }
             Some(&')') => {
                 self.chars.next();
                 self.advance_position(')');
-                return Ok(Token::RightParen);
+                self.brackets.pop();
+                Ok(Token::RightParen)
             }
             Some(&'{') => {
                 self.chars.next();
                 self.advance_position('{');
-                return Ok(Token::LeftBrace);
+                // After parameters, as in `fungsi(x) {`, or among statements, a '{' opens a block whose
+                // statements are separated by line breaks; otherwise it opens a map, as in `(x) => {`
+                if !self.brackets.is_empty() {
+                    let block = self.brackets.last() == Some(&'b') || self.previous == Some(Token::RightParen);
+                    self.brackets.push(if block { 'b' } else { '{' });
+                }
+                Ok(Token::LeftBrace)
             }
             Some(&'}') => {
                 self.chars.next();
                 self.advance_position('}');
-                return Ok(Token::RightBrace);
+                // A '}' that matches no '{' inside the brackets closes an enclosing block,
+                // so the brackets were left unclosed
+                match self.brackets.iter().rposition(|&bracket| matches!(bracket, '{' | 'b')) {
+                    Some(index) => self.brackets.truncate(index),
+                    None => self.brackets.clear(),
+                }
+                Ok(Token::RightBrace)
             }
             Some(&'[') => {
                 self.chars.next();
                 self.advance_position('[');
-                return Ok(Token::LeftBracket);
+                self.brackets.push('[');
+                Ok(Token::LeftBracket)
             }
             Some(&']') => {
                 self.chars.next();
                 self.advance_position(']');
-                return Ok(Token::RightBracket);
+                self.brackets.pop();
+                Ok(Token::RightBracket)
             }
             Some(&',') => {
                 self.chars.next();
                 self.advance_position(',');
-                return Ok(Token::Comma);
+                Ok(Token::Comma)
             }
             Some(&'.') => {
                 self.chars.next();
                 self.advance_position('.');
-                return Ok(Token::Dot);
+                if let Some(&'.') = self.chars.peek() {
+                    self.chars.next();
+                    self.advance_position('.');
+                    if let Some(&'.') = self.chars.peek() {
+                        self.chars.next();
+                        self.advance_position('.');
+                        return Ok(Token::DotDotDot);
+                    }
+                    return Ok(Token::DotDot);
+                }
+                Ok(Token::Dot)
             }
             Some(&':') => {
                 self.chars.next();
                 self.advance_position(':');
-                return Ok(Token::Colon);
+                Ok(Token::Colon)
             }
             Some(&';') => {
                 self.chars.next();
                 self.advance_position(';');
-                return Ok(Token::Semicolon);
+                Ok(Token::Semicolon)
+            }
+            Some(&'?') => {
+                self.chars.next();
+                self.advance_position('?');
+                match self.chars.peek().copied() {
+                    Some('.') => {
+                        self.chars.next();
+                        self.advance_position('.');
+                        Ok(Token::QuestionDot)
+                    }
+                    // `?:` ending a line is an optional type or `?` before a block, as in `fungsi cari(): int?:`
+                    Some(':') if !self.colon_ends_line() => {
+                        self.chars.next();
+                        self.advance_position(':');
+                        Ok(Token::Elvis)
+                    }
+                    _ => Ok(Token::Question),
+                }
             }
             Some(&'\\') => {
                 self.chars.next();
                 self.advance_position('\\');
-                return Ok(Token::Backslash);
+                Ok(Token::Backslash)
             }
-            Some(&'"') => {
-                return self.read_string();
+            Some(&('"' | '\'')) => {
+                self.read_string()
             }
-            Some(&'0'..=&'9') => {
-                return self.read_number();
+            Some(&'r') if raw_string => {
+                self.read_string()
             }
-            Some(&'a'..=&'z') | Some(&'A'..=&'Z') | Some(&'_') => {
-                return self.read_identifier();
+            Some(&('0'..='9')) => {
+                self.read_number()
+            }
+            Some(&c) if is_identifier_start(c) => {
+                self.read_identifier()
             }
             Some(&'\n') => {
                 self.chars.next();
                 self.advance_position('\n');
-                return Ok(Token::Newline);
+                self.at_line_start = true;
+                Ok(Token::Newline)
             }
-            Some(c) => {
+            Some(&c) => {
                 let pos = self.current_position();
                 self.chars.next();
-                self.advance_position(*c);
-                return Err(LexerError::InvalidCharacter(*c, pos));
+                self.advance_position(c);
+                Err(LexerError::InvalidCharacter(c, pos))
             }
             None => {
-                return Ok(Token::Eof);
+                Ok(Token::Eof)
             }
         }
-
-        Ok(Token::Eof)
     }
 
-    /// Skip whitespace characters, tracking indentation
+    /// Skip whitespace characters; newlines are skipped inside parentheses, brackets and maps, and
+    /// before a line that continues the current one
     fn skip_whitespace(&mut self) -> Result<(), LexerError> {
         while let Some(&ch) = self.chars.peek() {
             match ch {
-                ' ' | '\t' => {
+                ' ' | '\t' | '\r' => {
                     self.chars.next();
                     self.advance_position(ch);
                 }
+                // A statement cannot be inside brackets, so the brackets before it were left unclosed
+                '\n' if self.ignores_line_breaks() && self.next_line_starts_statement() => {
+                    let block = self.brackets.iter().rposition(|&bracket| bracket == 'b');
+                    self.brackets.truncate(block.map_or(0, |index| index + 1));
+                    break;
+                }
+                '\n' if self.ignores_line_breaks() || self.next_line_continues() => {
+                    self.chars.next();
+                    self.advance_position(ch);
+                }
+                _ => break,
+            }
+        }
+        Ok(())
+    }
+
+    /// Check whether line breaks are inside parentheses, brackets or a map, and not in a block inside them
+    fn ignores_line_breaks(&self) -> bool {
+        matches!(self.brackets.last(), Some('(' | '[' | '{'))
+    }
+
+    /// Check whether the line after the current newline continues the expression before it,
+    /// by starting with a member access or `|>`: `angka\n    .saring(...)`
+    fn next_line_continues(&self) -> bool {
+        let mut lookahead = self.chars.clone();
+        lookahead.next(); // the newline
+        while lookahead.next_if(|&ch| matches!(ch, ' ' | '\t' | '\r')).is_some() {}
+        match lookahead.next() {
+            Some('.') => lookahead.next().is_some_and(is_identifier_start),
+            Some('|') => lookahead.next() == Some('>'),
+            _ => false,
+        }
+    }
+
+    /// Check whether the line after the current newline starts with a keyword that only starts
+    /// statements, such as `buat` or `kembalikan`
+    fn next_line_starts_statement(&self) -> bool {
+        let mut lookahead = self.chars.clone();
+        lookahead.next(); // the newline
+        while lookahead.next_if(|&ch| matches!(ch, ' ' | '\t' | '\r')).is_some() {}
+        let word: String = std::iter::from_fn(|| lookahead.next_if(|&ch| is_identifier_continue(ch))).nfc().collect();
+        matches!(
+            self.keywords.lookup(&word),
+            Some(Token::Kelas | Token::Kembalikan | Token::Impor | Token::Ekspor | Token::Buat | Token::Variabel | Token::Konstan)
+        )
+    }
+
+    /// Read the indentation of a new line and queue Indent/Dedent tokens
+    fn read_indentation(&mut self) -> Result<(), LexerError> {
+        // Indentation is not significant inside parentheses and brackets
+        if self.ignores_line_breaks() {
+            return Ok(());
+        }
+
+        let start_pos = self.current_position();
+        let mut width = 0;
+        let mut has_spaces = false;
+        let mut has_tabs = false;
+
+        while let Some(&ch) = self.chars.peek() {
+            match ch {
+                ' ' => has_spaces = true,
+                '\t' => has_tabs = true,
                 _ => break,
             }
+            self.chars.next();
+            self.advance_position(ch);
+            width += 1;
+        }
+
+        // Blank lines and comment-only lines do not affect indentation
+        match self.chars.peek() {
+            None | Some(&'\n') | Some(&'\r') => return Ok(()),
+            Some(&'/') => {
+                let mut lookahead = self.chars.clone();
+                lookahead.next();
+                if let Some(&'/') | Some(&'*') = lookahead.peek() {
+                    return Ok(());
+                }
+            }
+            _ => {}
+        }
+
+        if has_spaces && has_tabs {
+            return Err(LexerError::InconsistentIndentation(start_pos));
+        }
+
+        if width > 0 {
+            let indent_char = if has_tabs { '\t' } else { ' ' };
+            match self.indent_char {
+                None => self.indent_char = Some(indent_char),
+                Some(existing) if existing != indent_char => {
+                    return Err(LexerError::InconsistentIndentation(start_pos));
+                }
+                _ => {}
+            }
+        }
+
+        let position = self.current_position();
+
+        if width > self.current_indent {
+            self.indent_stack.push(self.current_indent);
+            self.current_indent = width;
+            self.pending_tokens.push_back(TokenWithPosition {
+                token: Token::Indent,
+                position: start_pos,
+                length: width,
+                span: self.empty_span(),
+            });
+        } else {
+            while width < self.current_indent {
+                self.current_indent = self.indent_stack.pop().unwrap_or(0);
+                self.pending_tokens.push_back(TokenWithPosition {
+                    token: Token::Dedent,
+                    position: position.clone(),
+                    length: 0,
+                    span: self.empty_span(),
+                });
+            }
+
+            if width != self.current_indent {
+                return Err(LexerError::InvalidDedent(position));
+            }
         }
+
         Ok(())
     }
 
-    /// Read a string literal
+    /// Look at the character after the next one
+    fn peek_second(&self) -> Option<char> {
+        let mut lookahead = self.chars.clone();
+        lookahead.next();
+        lookahead.next()
+    }
+
+    /// Check whether the next character is a `:` with only blanks or a comment after it on its line
+    fn colon_ends_line(&self) -> bool {
+        let mut lookahead = self.chars.clone().skip(1).skip_while(|ch| *ch == ' ' || *ch == '\t');
+        match lookahead.next() {
+            None | Some('\n' | '\r') => true,
+            Some('/') => matches!(lookahead.next(), Some('/' | '*')),
+            _ => false,
+        }
+    }
+
+    /// Check whether the input continues with `text`
+    fn starts_with(&self, text: &str) -> bool {
+        self.chars.clone().take(text.chars().count()).eq(text.chars())
+    }
+
+    /// Read a string literal: `"..."` or `'...'`, `"""..."""` or `'''...'''` spanning lines,
+    /// and raw strings prefixed with `r`, which have no escapes or interpolation
     fn read_string(&mut self) -> Result<Token, LexerError> {
         let start_pos = self.current_position();
 
-        // Skip opening quote
-        self.chars.next();
-        self.advance_position('"');
+        let raw = self.chars.peek() == Some(&'r');
+        if raw {
+            self.chars.next();
+            self.advance_position('r');
+        }
 
-        let mut result = String::new();
+        let quote = self.chars.next().unwrap_or('"');
+        self.advance_position(quote);
+
+        // Triple quotes start a multi-line string; a newline right after them is not included
+        let triple = self.starts_with(&format!("{}{}", quote, quote));
+        if triple {
+            for _ in 0..2 {
+                self.chars.next();
+                self.advance_position(quote);
+            }
+            if self.chars.peek() == Some(&'\n') {
+                self.chars.next();
+                self.advance_position('\n');
+            }
+        }
+        let closing = if triple { quote.to_string().repeat(3) } else { quote.to_string() };
+
+        let mut parts = Vec::new();
+        let mut text = String::new();
+
+        loop {
+            if self.starts_with(&closing) {
+                for _ in 0..closing.len() {
+                    self.chars.next();
+                    self.advance_position(quote);
+                }
+                break;
+            }
+
+            let ch = match self.chars.peek() {
+                Some(&ch) => ch,
+                None => return Err(LexerError::UnterminatedString(start_pos)),
+            };
 
-        while let Some(&ch) = self.chars.peek() {
             match ch {
-                '"' => {
-                    // Skip closing quote
+                '\n' if !triple => return Err(LexerError::UnterminatedString(start_pos)),
+                '\\' if !raw => text.push(self.read_escape()?),
+                '{' if !raw => {
+                    if !text.is_empty() {
+                        parts.push(StringPart::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(self.read_interpolation(triple)?);
+                }
+                _ => {
                     self.chars.next();
-                    self.advance_position('"');
-                    return Ok(Token::String(result));
+                    self.advance_position(ch);
+                    text.push(ch);
                 }
-                '\n' => {
-                    return Err(LexerError::UnterminatedString(start_pos));
+            }
+        }
+
+        if parts.is_empty() {
+            return Ok(Token::String(text));
+        }
+        if !text.is_empty() {
+            parts.push(StringPart::Text(text));
+        }
+        Ok(Token::InterpolatedString(parts))
+    }
+
+    /// Read an escape sequence in a string: `\n`, `\t`, `\r`, `\0`, `\\`, `\"`, `\'`,
+    /// `\{`, `\}` or `\u{1F600}`
+    fn read_escape(&mut self) -> Result<char, LexerError> {
+        let position = self.current_position();
+        self.chars.next(); // consume backslash
+        self.advance_position('\\');
+
+        let escaped = match self.chars.peek() {
+            Some(&escaped) if escaped != '\n' => escaped,
+            _ => return Err(LexerError::InvalidEscape("\\".to_string(), position)),
+        };
+        self.chars.next();
+        self.advance_position(escaped);
+
+        let ch = match escaped {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            '0' => '\0',
+            '\\' | '"' | '\'' | '{' | '}' => escaped,
+            'u' => {
+                // \u{...} with 1 to 6 hex digits naming a Unicode scalar value
+                let mut escape = String::from("\\u");
+                if self.chars.peek() != Some(&'{') {
+                    return Err(LexerError::InvalidEscape(escape, position));
                 }
-                '\\' => {
-                    // Handle escape sequences
-                    self.chars.next(); // consume backslash
-                    self.advance_position('\\');
-                    if let Some(&escaped) = self.chars.peek() {
-                        self.chars.next(); // consume escaped character
-                        self.advance_position(escaped);
-                        match escaped {
-                            'n' => result.push('\n'),
-                            't' => result.push('\t'),
-                            'r' => result.push('\r'),
-                            '"' => result.push('"'),
-                            '\\' => result.push('\\'),
-                            _ => result.push(escaped),
-                        }
+                while let Some(&ch) = self.chars.peek() {
+                    if ch == '"' || ch == '\'' || ch == '\n' {
+                        break;
+                    }
+                    self.chars.next();
+                    self.advance_position(ch);
+                    escape.push(ch);
+                    if ch == '}' {
+                        break;
                     }
                 }
-                _ => {
+
+                let digits = escape
+                    .strip_prefix("\\u{")
+                    .and_then(|rest| rest.strip_suffix('}'))
+                    .filter(|digits| (1..=6).contains(&digits.len()));
+                return digits
+                    .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+                    .and_then(char::from_u32)
+                    .ok_or(LexerError::InvalidEscape(escape, position));
+            }
+            _ => return Err(LexerError::InvalidEscape(format!("\\{}", escaped), position)),
+        };
+
+        Ok(ch)
+    }
+
+    /// Read an interpolated expression `{...}` in a string
+    fn read_interpolation(&mut self, multiline: bool) -> Result<StringPart, LexerError> {
+        let open_position = self.current_position();
+        self.chars.next(); // consume {
+        self.advance_position('{');
+
+        let start = self.current_position();
+        let start_byte = self.byte_offset;
+        let mut source = String::new();
+        let mut depth = 0;
+        let mut quote = None; // Quote of a string literal inside the expression
+
+        loop {
+            let ch = match self.chars.peek() {
+                Some(&'\n') if !multiline => None,
+                Some(&ch) => Some(ch),
+                None => None,
+            };
+            let ch = ch.ok_or_else(|| LexerError::UnterminatedInterpolation(open_position.clone()))?;
+
+            match (quote, ch) {
+                (Some(q), _) if ch == q => quote = None,
+                (Some(_), '\\') => {
+                    // Keep escapes of nested strings intact, including escaped quotes
                     self.chars.next();
                     self.advance_position(ch);
-                    result.push(ch);
+                    source.push(ch);
+                    if let Some(&escaped) = self.chars.peek() {
+                        if escaped != '\n' {
+                            self.chars.next();
+                            self.advance_position(escaped);
+                            source.push(escaped);
+                        }
+                    }
+                    continue;
                 }
+                (Some(_), _) => {}
+                (None, '"' | '\'') => quote = Some(ch),
+                (None, '{') => depth += 1,
+                (None, '}') if depth == 0 => break,
+                (None, '}') => depth -= 1,
+                (None, _) => {}
             }
+
+            self.chars.next();
+            self.advance_position(ch);
+            source.push(ch);
         }
 
-        Err(LexerError::UnterminatedString(start_pos))
+        self.chars.next(); // consume }
+        self.advance_position('}');
+
+        if source.trim().is_empty() {
+            return Err(LexerError::EmptyInterpolation(open_position));
+        }
+        Ok(StringPart::Expression(source, start, start_byte))
     }
 
-    /// Read a line comment
+    /// Read a line comment or a `///` doc comment
     fn read_line_comment(&mut self) -> Result<Token, LexerError> {
         // Skip "//"
         self.chars.next();
@@ -816,10 +1430,16 @@ impl<'a> Lexer<'a> {
             result.push(ch);
         }
 
-        Ok(Token::LineComment(result))
+        // `///` starts a doc comment, but `////` is an ordinary comment
+        match result.strip_prefix('/') {
+            Some(doc) if !doc.starts_with('/') => {
+                Ok(Token::DocComment(doc.strip_prefix(' ').unwrap_or(doc).trim_end().to_string()))
+            }
+            _ => Ok(Token::LineComment(result)),
+        }
     }
 
-    /// Read a block comment
+    /// Read a block comment or a `/** */` doc comment
     fn read_block_comment(&mut self) -> Result<Token, LexerError> {
         let start_pos = self.current_position();
 
@@ -830,316 +1450,195 @@ impl<'a> Lexer<'a> {
         self.advance_position('*');
 
         let mut result = String::new();
-        let mut prev_char = '\0';
+        let mut terminated = false;
 
-        while let Some(&ch) = self.chars.peek() {
-            if prev_char == '*' && ch == '/' {
-                // Skip "*/"
-                self.chars.next();
-                self.advance_position('*');
+        while let Some(ch) = self.chars.next() {
+            self.advance_position(ch);
+            if ch == '*' && self.chars.peek() == Some(&'/') {
+                // Skip the closing "/"
                 self.chars.next();
                 self.advance_position('/');
-                // Remove the '*' from the result
-                if !result.is_empty() {
-                    result.pop();
-                }
+                terminated = true;
                 break;
             }
-            self.chars.next();
-            self.advance_position(ch);
             result.push(ch);
-            prev_char = ch;
         }
 
-        if !(prev_char == '*' && self.chars.peek() == Some(&'/')) && !result.is_empty() {
+        if !terminated {
             return Err(LexerError::UnterminatedComment(start_pos));
         }
 
-        Ok(Token::BlockComment(result))
+        // `/** ... */` is a doc comment, but `/**/` and `/*** ... */` are ordinary comments
+        match result.strip_prefix('*') {
+            Some(doc) if !doc.is_empty() && !doc.starts_with('*') => Ok(Token::DocComment(Self::block_doc_text(doc))),
+            _ => Ok(Token::BlockComment(result)),
+        }
     }
 
-    /// Read a number literal
+    /// Text of a `/** */` doc comment without the leading ` * ` of each line
+    fn block_doc_text(doc: &str) -> String {
+        let lines: Vec<&str> = doc
+            .lines()
+            .map(|line| {
+                let line = line.trim_start();
+                let line = line.strip_prefix('*').unwrap_or(line);
+                line.strip_prefix(' ').unwrap_or(line).trim_end()
+            })
+            .collect();
+
+        let start = lines.iter().position(|line| !line.is_empty()).unwrap_or(lines.len());
+        let end = lines.iter().rposition(|line| !line.is_empty()).map_or(start, |end| end + 1);
+        lines[start..end].join("\n")
+    }
+
+    /// Read a number: a decimal integer or float, or a `0x`, `0b` or `0o` integer.
+    /// Digits may be separated with `_`, and floats may have an exponent (`1e-9`).
     fn read_number(&mut self) -> Result<Token, LexerError> {
-        let mut result = String::new();
+        let start = self.current_position();
+        let mut literal = String::new(); // Source text, for error messages
+
+        // Prefixed integers
+        let mut lookahead = self.chars.clone();
+        lookahead.next();
+        let radix = match (self.chars.peek(), lookahead.peek()) {
+            (Some(&'0'), Some(&('x' | 'X'))) => Some(16),
+            (Some(&'0'), Some(&('b' | 'B'))) => Some(2),
+            (Some(&'0'), Some(&('o' | 'O'))) => Some(8),
+            _ => None,
+        };
+        if let Some(radix) = radix {
+            self.take_char(&mut literal); // 0
+            self.take_char(&mut literal); // x, b or o
+            let digits = self.read_digits(radix, &mut literal, &start)?;
+            self.check_number_end(&mut literal, &start)?;
+            return i64::from_str_radix(&digits, radix)
+                .map(Token::Integer)
+                .map_err(|_| LexerError::IntegerOverflow(literal, start));
+        }
+
+        let mut number = self.read_digits(10, &mut literal, &start)?;
+        let mut is_float = false;
+
+        // Only treat '.' as a decimal point when a digit follows, so that
+        // ranges like `1..5` and member access like `1.abs()` still lex
+        let mut lookahead = self.chars.clone();
+        lookahead.next();
+        if self.chars.peek() == Some(&'.') && matches!(lookahead.peek(), Some(&('0'..='9'))) {
+            self.take_char(&mut literal);
+            number.push('.');
+            number.push_str(&self.read_digits(10, &mut literal, &start)?);
+            is_float = true;
+        }
+
+        // Exponent: e10, E+3, e-9
+        if matches!(self.chars.peek(), Some(&('e' | 'E'))) {
+            let mut lookahead = self.chars.clone();
+            lookahead.next();
+            if matches!(lookahead.peek(), Some(&('+' | '-'))) {
+                lookahead.next();
+            }
+            if !matches!(lookahead.peek(), Some(&('0'..='9'))) {
+                self.take_char(&mut literal);
+                return Err(LexerError::InvalidNumber(literal, "the exponent has no digits", start));
+            }
+            self.take_char(&mut literal);
+            number.push('e');
+            if matches!(self.chars.peek(), Some(&('+' | '-'))) {
+                number.push(self.take_char(&mut literal));
+            }
+            number.push_str(&self.read_digits(10, &mut literal, &start)?);
+            is_float = true;
+        }
+
+        self.check_number_end(&mut literal, &start)?;
+
+        if is_float {
+            match number.parse::<f64>() {
+                Ok(value) if value.is_finite() => Ok(Token::Float(value)),
+                _ => Err(LexerError::FloatOverflow(literal, start)),
+            }
+        } else if number.parse::<u64>() == Ok(i64::MIN.unsigned_abs()) {
+            // Too large on its own, but `i64::MIN` after a minus sign; the parser checks that there is one
+            Ok(Token::Integer(i64::MIN))
+        } else {
+            number
+                .parse::<i64>()
+                .map(Token::Integer)
+                .map_err(|_| LexerError::IntegerOverflow(literal, start))
+        }
+    }
+
+    /// Read a group of digits in the given radix, dropping `_` separators
+    fn read_digits(&mut self, radix: u32, literal: &mut String, start: &Position) -> Result<String, LexerError> {
+        let mut digits = String::new();
+        let mut last = None;
 
         while let Some(&ch) = self.chars.peek() {
-            match ch {
-                '0'..='9' | '.' => {
-                    self.chars.next();
-                    self.advance_position(ch);
-                    result.push(ch);
+            if ch == '_' {
+                if last.is_none() || last == Some('_') {
+                    self.take_char(literal);
+                    return Err(LexerError::InvalidNumber(literal.clone(), "'_' must be placed between digits", start.clone()));
                 }
-                _ => break,
+            } else if ch.is_ascii_digit() || (radix == 16 && ch.is_ascii_hexdigit()) {
+                if !ch.is_digit(radix) {
+                    self.take_char(literal);
+                    let reason = if radix == 2 { "binary numbers only use 0 and 1" } else { "octal numbers only use digits 0 to 7" };
+                    return Err(LexerError::InvalidNumber(literal.clone(), reason, start.clone()));
+                }
+                digits.push(ch);
+            } else {
+                break;
             }
+            last = Some(ch);
+            self.take_char(literal);
         }
 
-        match result.parse::<f64>() {
-            Ok(num) => Ok(Token::Number(num)),
-            Err(_) => {
-                let pos = self.current_position();
-                Err(LexerError::InvalidCharacter(result.chars().next().unwrap_or('0'), pos))
+        if last == Some('_') {
+            return Err(LexerError::InvalidNumber(literal.clone(), "'_' must be placed between digits", start.clone()));
+        }
+        if digits.is_empty() {
+            return Err(LexerError::InvalidNumber(literal.clone(), "expected digits", start.clone()));
+        }
+        Ok(digits)
+    }
+
+    /// Reject letters directly after a number, such as `12abc` or `0xFG`
+    fn check_number_end(&mut self, literal: &mut String, start: &Position) -> Result<(), LexerError> {
+        match self.chars.peek() {
+            Some(&ch) if is_identifier_continue(ch) => {
+                self.take_char(literal);
+                Err(LexerError::InvalidNumber(literal.clone(), "numbers cannot be followed by letters", start.clone()))
             }
+            _ => Ok(()),
         }
     }
 
+    /// Consume the next character, appending it to `literal`
+    fn take_char(&mut self, literal: &mut String) -> char {
+        let ch = self.chars.next().unwrap_or_default();
+        self.advance_position(ch);
+        literal.push(ch);
+        ch
+    }
+
     /// Read an identifier or keyword
     fn read_identifier(&mut self) -> Result<Token, LexerError> {
         let mut result = String::new();
 
         while let Some(&ch) = self.chars.peek() {
-            match ch {
-                'a'..='z' | 'A'..='Z' | '0'..='9' | '_' => {
-                    self.chars.next();
-                    self.advance_position(ch);
-                    result.push(ch);
-                }
-                _ => break,
+            if !is_identifier_continue(ch) {
+                break;
             }
+            self.chars.next();
+            self.advance_position(ch);
+            result.push(ch);
         }
 
-        // Check if it's a keyword
-        match result.as_str() {
-            // Indonesian keywords
-            "buat" => Ok(Token::Buat),
-            "jika" => Ok(Token::Jika),
-            "maka" => Ok(Token::Maka),
-            "sebaliknya" => Ok(Token::Sebaliknya),
-            "fungsi" => Ok(Token::Fungsi),
-            "kelas" => Ok(Token::Kelas),
-            "kembalikan" => Ok(Token::Kembalikan),
-            "ulangi" => Ok(Token::Ulangi),
-            "selama" => Ok(Token::Selama),
-            "untuk" => Ok(Token::Untuk),
-            "di" => Ok(Token::Di),
-            "dari" => Ok(Token::Dari),
-            "sampai" => Ok(Token::Sampai),
-            "lakukan" => Ok(Token::Lakukan),
-            "variabel" => Ok(Token::Variabel),
-            "konstan" => Ok(Token::Konstan),
-            "struktur" => Ok(Token::Struktur),
-            "impor" => Ok(Token::Impor),
-            "sebagai" => Ok(Token::Sebagai),
-            "coba" => Ok(Token::Coba),
-            "tangkap" => Ok(Token::Tangkap),
-            "akhirnya" => Ok(Token::Akhirnya),
-            "lempar" => Ok(Token::Lempar),
-            "benar" => Ok(Token::Boolean(true)),
-            "salah" => Ok(Token::Boolean(false)),
-            "null" => Ok(Token::Null),
-            "baru" => Ok(Token::Baru),
-            "ini" => Ok(Token::Ini),
-            "super" => Ok(Token::Super),
-            "dan" => Ok(Token::And),
-            "atau" => Ok(Token::Or),
-            "tidak" => Ok(Token::Not),
-            "publik" => Ok(Token::Publik),
-            "pribadi" => Ok(Token::Pribadi),
-            "terlindungi" => Ok(Token::Terlindungi),
-            "statis" => Ok(Token::Statis),
-            "abstrak" => Ok(Token::Abstrak),
-            "akhir" => Ok(Token::Akhir),
-            "sinkron" => Ok(Token::Sinkron),
-            "asal" => Ok(Token::Asal),
-            "pecah" => Ok(Token::Pecah),
-            "lanjut" => Ok(Token::Lanjut),
-            "lakukans" => Ok(Token::LakukanSementara),
-            "untuksetiap" => Ok(Token::UntukSetiap),
-            "pergike" => Ok(Token::PergiKe),
-            "implementasi" => Ok(Token::Implementasi),
-            "antarmuka" => Ok(Token::Antarmuka),
-            "asli" => Ok(Token::Asli),
-            "paket" => Ok(Token::Paket),
-            "ketika" => Ok(Token::Ketika),
-            "biarkan" => Ok(Token::Biarkan),
-            "mut" => Ok(Token::Mut),
-            "ref" => Ok(Token::Ref),
-            "ptr" => Ok(Token::Ptr),
-            "ukurandari" => Ok(Token::UkuranDari),
-            "tipe" => Ok(Token::Tipe),
-            "alias" => Ok(Token::Alias),
-            "ekstensi" => Ok(Token::Ekstensi),
-            "operator" => Ok(Token::Operator),
-            "templat" => Ok(Token::Templat),
-            "menggunakan" => Ok(Token::Menggunakan),
-            "teman" => Ok(Token::Teman),
-            "virtual" => Ok(Token::Virtual),
-            "konst" => Ok(Token::Konst),
-            "volatil" => Ok(Token::Volatile),
-            "daftar" => Ok(Token::Daftar),
-            "peta" => Ok(Token::Peta),
-            "himpunan" => Ok(Token::Himpunan),
-            "opsional" => Ok(Token::Opsional),
-            "hasil" => Ok(Token::Hasil),
-            "tunggu" => Ok(Token::Tunggu),
-            "async" => Ok(Token::Async),
-            "hasilkan" => Ok(Token::Hasilkan),
-            "rentang" => Ok(Token::Rentang),
-            "saring" => Ok(Token::Saring),
-            "petakan" => Ok(Token::Petakan),
-            "kurangi" => Ok(Token::Kurangi),
-            "lipat" => Ok(Token::Lipat),
-            "ambil" => Ok(Token::Ambil),
-            "lompati" => Ok(Token::Lompati),
-            "gabungkan" => Ok(Token::Gabungkan),
-            "urutkan" => Ok(Token::Urutkan),
-            "balik" => Ok(Token::Balik),
-            "panjang" => Ok(Token::Panjang),
-
-            // Quantum Computing Keywords - Indonesian
-            "kubit" => Ok(Token::Kubit),
-            "gerbang" => Ok(Token::Gerbang),
-            "sirkuit" => Ok(Token::Sirkuit),
-            "ukur" => Ok(Token::Ukur),
-            "superposisi" => Ok(Token::Superposisi),
-            "keterkaitan" => Ok(Token::Keterkaitan),
-            "simulasi" => Ok(Token::Simulasi),
-
-            // Neural Network Keywords - Indonesian
-            "jaringan" => Ok(Token::Jaringan),
-            "lapisan" => Ok(Token::Lapisan),
-            "tensor" => Ok(Token::Tensor),
-            "model" => Ok(Token::Model),
-            "latih" => Ok(Token::Latih),
-            "prediksi" => Ok(Token::Prediksi),
-            "optimisasi" => Ok(Token::Optimisasi),
-            "fungsi_hilang" => Ok(Token::FungsiHilang),
-            "gradien" => Ok(Token::Gradien),
-
-            // Neural Network Keywords - English
-            "network" => Ok(Token::Network),
-            "layer" => Ok(Token::Layer),
-            "tensor" => Ok(Token::TensorEng),
-            "model" => Ok(Token::ModelEng),
-            "train" => Ok(Token::Train),
-            "predict" => Ok(Token::Predict),
-            "optimize" => Ok(Token::Optimize),
-            "loss_function" => Ok(Token::LossFunction),
-            "gradient" => Ok(Token::Gradient),
-
-            // Concurrency keywords - English
-            "go" => Ok(Token::Go),
-            "make_channel" => Ok(Token::MakeChannel),
-            "send" => Ok(Token::Send),
-            "receive" => Ok(Token::Receive),
-            "lock_mutex" => Ok(Token::LockMutex),
-            "unlock_mutex" => Ok(Token::UnlockMutex),
-            "create_condition" => Ok(Token::CreateCondition),
-            "wait_condition" => Ok(Token::WaitCondition),
-            "signal_condition" => Ok(Token::SignalCondition),
-            "broadcast_condition" => Ok(Token::BroadcastCondition),
-            "atomic_load" => Ok(Token::AtomicLoad),
-            "atomic_store" => Ok(Token::AtomicStore),
-            "create_address" => Ok(Token::CreateAddress),
-
-            // English keywords
-            "create" => Ok(Token::Create),
-            "if" => Ok(Token::If),
-            "then" => Ok(Token::Then),
-            "otherwise" => Ok(Token::Otherwise),
-            "function" => Ok(Token::Function),
-            "class" => Ok(Token::Class),
-            "return" => Ok(Token::Return),
-            "repeat" => Ok(Token::Repeat),
-            "while" => Ok(Token::While),
-            "for" => Ok(Token::For),
-            "in" => Ok(Token::In),
-            "from" => Ok(Token::From),
-            "to" => Ok(Token::To),
-            "do" => Ok(Token::Do),
-            "variable" => Ok(Token::Variable),
-            "constant" => Ok(Token::Constant),
-            "struct" => Ok(Token::Struct),
-            "import" => Ok(Token::Import),
-            "as" => Ok(Token::As),
-            "try" => Ok(Token::Try),
-            "catch" => Ok(Token::Catch),
-            "finally" => Ok(Token::Finally),
-            "throw" => Ok(Token::Throw),
-            "true" => Ok(Token::Boolean(true)),
-            "false" => Ok(Token::Boolean(false)),
-            "null" => Ok(Token::NullEng),
-            "new" => Ok(Token::New),
-            "this" => Ok(Token::This),
-            "super" => Ok(Token::SuperEng),
-            "public" => Ok(Token::Public),
-            "private" => Ok(Token::Private),
-            "protected" => Ok(Token::Protected),
-            "static" => Ok(Token::Static),
-            "abstract" => Ok(Token::Abstract),
-            "final" => Ok(Token::Final),
-            "synchronized" => Ok(Token::Synchronized),
-            "assert" => Ok(Token::Assert),
-            "break" => Ok(Token::Break),
-            "continue" => Ok(Token::Continue),
-            "do-while" => Ok(Token::DoWhile),
-            "for-each" => Ok(Token::ForEach),
-            "goto" => Ok(Token::Goto),
-            "implements" => Ok(Token::Implements),
-            "interface" => Ok(Token::Interface),
-            "native" => Ok(Token::Native),
-            "package" => Ok(Token::Package),
-            "when" => Ok(Token::When),
-            "let" => Ok(Token::Let),
-            "mut" => Ok(Token::MutEng),
-            "ref" => Ok(Token::RefEng),
-            "ptr" => Ok(Token::PtrEng),
-            "sizeof" => Ok(Token::Sizeof),
-            "type" => Ok(Token::TypeEng),
-            "alias" => Ok(Token::AliasEng),
-            "extension" => Ok(Token::Extension),
-            "operator" => Ok(Token::OperatorEng),
-            "template" => Ok(Token::Template),
-            "using" => Ok(Token::Using),
-            "friend" => Ok(Token::Friend),
-            "virtual" => Ok(Token::VirtualEng),
-            "const" => Ok(Token::Const),
-            "volatile" => Ok(Token::Volatile),
-            "list" => Ok(Token::List),
-            "map" => Ok(Token::Map),
-            "set" => Ok(Token::Set),
-            "optional" => Ok(Token::Optional),
-            "result" => Ok(Token::Result),
-            "await" => Ok(Token::Await),
-            "async" => Ok(Token::AsyncEng),
-            "yield" => Ok(Token::Yield),
-            "range" => Ok(Token::Range),
-            "filter" => Ok(Token::Filter),
-            "map" => Ok(Token::MapFunc),
-            "reduce" => Ok(Token::Reduce),
-            "fold" => Ok(Token::Fold),
-            "take" => Ok(Token::Take),
-            "skip" => Ok(Token::Skip),
-            "join" => Ok(Token::Join),
-            "sort" => Ok(Token::Sort),
-            "reverse" => Ok(Token::Reverse),
-            "length" => Ok(Token::Length),
-
-            // Quantum Computing Keywords - English
-            "qubit" => Ok(Token::Qubit),
-            "gate" => Ok(Token::Gate),
-            "circuit" => Ok(Token::Circuit),
-            "measure" => Ok(Token::Measure),
-            "superposition" => Ok(Token::Superposition),
-            "entanglement" => Ok(Token::Entanglement),
-            "simulate" => Ok(Token::Simulate),
-
-            // Concurrency keywords - English
-            "go" => Ok(Token::Go),
-            "make_channel" => Ok(Token::MakeChannel),
-            "send" => Ok(Token::Send),
-            "receive" => Ok(Token::Receive),
-            "lock_mutex" => Ok(Token::LockMutex),
-            "unlock_mutex" => Ok(Token::UnlockMutex),
-            "create_condition" => Ok(Token::CreateCondition),
-            "wait_condition" => Ok(Token::WaitCondition),
-            "signal_condition" => Ok(Token::SignalCondition),
-            "broadcast_condition" => Ok(Token::BroadcastCondition),
-            "atomic_load" => Ok(Token::AtomicLoad),
-            "atomic_store" => Ok(Token::AtomicStore),
-            "create_address" => Ok(Token::CreateAddress),
-
-            // Not a keyword, it's an identifier
-            _ => Ok(Token::Identifier(result)),
+        // Identifiers are compared in NFC form, so precomposed and decomposed spellings match
+        let result: String = result.nfc().collect();
+        match self.keywords.lookup(&result) {
+            Some(token) => Ok(token.clone()),
+            None => Ok(Token::Identifier(result)),
         }
     }
 }
@@ -1147,6 +1646,7 @@ impl<'a> Lexer<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::keywords::KeywordSet;
 
     #[test]
     fn test_simple_tokens() {
@@ -1231,8 +1731,426 @@ mod tests {
 
         // "5" at line 2, column 5
         match lexer.next_token().unwrap() {
-            Token::Number(value) => assert_eq!(value, 5.0),
-            _ => panic!("Expected number"),
+            Token::Integer(value) => assert_eq!(value, 5),
+            _ => panic!("Expected integer"),
+        }
+    }
+
+    #[test]
+    fn test_indent_dedent() {
+        let input = "jika x maka:\n    y = 1\n    jika y maka:\n        z = 2\nw = 3";
+        let mut lexer = Lexer::new(input);
+        let tokens: Vec<Token> = lexer.tokenize().unwrap().into_iter().map(|t| t.token).collect();
+
+        let indents = tokens.iter().filter(|t| **t == Token::Indent).count();
+        let dedents = tokens.iter().filter(|t| **t == Token::Dedent).count();
+        assert_eq!(indents, 2);
+        assert_eq!(dedents, 2);
+
+        // Both blocks close before "w" on the last line
+        let w_index = tokens.iter().position(|t| *t == Token::Identifier("w".to_string())).unwrap();
+        assert_eq!(tokens[w_index - 1], Token::Dedent);
+        assert_eq!(tokens[w_index - 2], Token::Dedent);
+    }
+
+    #[test]
+    fn test_indent_positions() {
+        let input = "fungsi f():\n    kembali 1\n";
+        let mut lexer = Lexer::new(input);
+        let tokens = lexer.tokenize().unwrap();
+
+        let indent = tokens.iter().find(|t| t.token == Token::Indent).unwrap();
+        assert_eq!(indent.position.line, 2);
+        assert_eq!(indent.position.column, 1);
+        assert_eq!(indent.length, 4);
+
+        // The open block is closed at the end of the file
+        let len = tokens.len();
+        assert_eq!(tokens[len - 2].token, Token::Dedent);
+        assert_eq!(tokens[len - 1].token, Token::Eof);
+    }
+
+    #[test]
+    fn test_blank_and_comment_lines_keep_indentation() {
+        let input = "jika x maka:\n    a = 1\n\n// catatan\n    b = 2\n";
+        let mut lexer = Lexer::new(input);
+        let tokens: Vec<Token> = lexer.tokenize().unwrap().into_iter().map(|t| t.token).collect();
+
+        assert_eq!(tokens.iter().filter(|t| **t == Token::Indent).count(), 1);
+        assert_eq!(tokens.iter().filter(|t| **t == Token::Dedent).count(), 1);
+    }
+
+    #[test]
+    fn test_indentation_ignored_inside_parentheses() {
+        let input = "tampilkan(a,\n        b,\n    c)\nd = [1,\n  2]\n";
+        let mut lexer = Lexer::new(input);
+        let tokens: Vec<Token> = lexer.tokenize().unwrap().into_iter().map(|t| t.token).collect();
+
+        assert!(!tokens.contains(&Token::Indent));
+        assert!(!tokens.contains(&Token::Dedent));
+        assert_eq!(tokens.iter().filter(|t| **t == Token::Newline).count(), 2);
+    }
+
+    #[test]
+    fn test_pipelines_and_continued_lines() {
+        let input = "hasil = angka\n    |> ambil(2)\nbuat b = angka\n    .saring(f)\n    .urutkan()\nc = 1\n";
+        let mut lexer = Lexer::new(input);
+        let tokens: Vec<Token> = lexer.tokenize().unwrap().into_iter().map(|t| t.token).collect();
+
+        assert!(tokens.contains(&Token::PipeArrow));
+        // A line starting with a member access or '|>' continues the one before it
+        assert!(!tokens.contains(&Token::Indent));
+        assert_eq!(tokens.iter().filter(|t| **t == Token::Newline).count(), 3);
+        assert!(matches!(Lexer::new("a | b").tokenize(), Err(LexerError::InvalidCharacter('|', _))));
+    }
+
+    #[test]
+    fn test_optional_operators() {
+        let tokens = |input: &str| -> Vec<Token> { Lexer::new(input).tokenize().unwrap().into_iter().map(|t| t.token).collect() };
+
+        assert_eq!(tokens("a?.b ?: c")[1..5], [Token::QuestionDot, Token::Identifier("b".to_string()), Token::Elvis, Token::Identifier("c".to_string())]);
+        // A `?:` at the end of a line is an optional type, or `?`, before a block
+        let signature = tokens("fungsi cari(): int?:  // teks\n");
+        assert!(signature.windows(2).any(|pair| pair == [Token::Question, Token::Colon]));
+        assert!(!signature.contains(&Token::Elvis));
+    }
+
+    #[test]
+    fn test_stray_brace_closes_unclosed_parentheses() {
+        let input = "f({\n  a: 1\n})\ng() {\n    h(\n}\nx = 1\n";
+        let mut lexer = Lexer::new(input);
+        let tokens: Vec<Token> = lexer.tokenize().unwrap().into_iter().map(|t| t.token).collect();
+
+        // Newlines after `f(...)`, `g() {`, the stray `}` and `x = 1`
+        assert_eq!(tokens.iter().filter(|t| **t == Token::Newline).count(), 4);
+    }
+
+    #[test]
+    fn test_block_braces_inside_parentheses_keep_line_breaks() {
+        let input = "g(1, fungsi() {\n    a\n    b\n}, {\n    c: 1\n})\n";
+        let mut lexer = Lexer::new(input);
+        let tokens: Vec<Token> = lexer.tokenize().unwrap().into_iter().map(|t| t.token).collect();
+
+        // Line breaks separate the statements of the block, but not the entries of the map
+        assert_eq!(tokens.iter().filter(|t| **t == Token::Newline).count(), 4);
+        assert_eq!(tokens.iter().filter(|t| **t == Token::Indent).count(), 1);
+        assert_eq!(tokens.iter().filter(|t| **t == Token::Dedent).count(), 1);
+    }
+
+    #[test]
+    fn test_statement_keywords_end_unclosed_brackets() {
+        let mut lexer = Lexer::new("f(1,\n  2\nbuat x = [\nkembalikan x\n");
+        let tokens: Vec<Token> = lexer.tokenize().unwrap().into_iter().map(|t| t.token).collect();
+
+        // The line break before each statement ends the line the brackets were left open on
+        assert_eq!(tokens.iter().filter(|t| **t == Token::Newline).count(), 3);
+        assert_eq!(tokens.iter().position(|t| *t == Token::Newline), tokens.iter().position(|t| *t == Token::Buat).map(|index| index - 1));
+    }
+
+    #[test]
+    fn test_tokenize_recovering_skips_the_rest_of_the_line() {
+        let mut lexer = Lexer::new("buat s = \"abc\nbuat n = 1 # 2\nbuat m = 3\n");
+        let (tokens, errors) = lexer.tokenize_recovering();
+
+        assert!(matches!(errors[..], [LexerError::UnterminatedString(_), LexerError::InvalidCharacter('#', _)]));
+        let invalid: Vec<&TokenWithPosition> = tokens.iter().filter(|t| t.token == Token::Invalid).collect();
+        assert_eq!(invalid.len(), 2);
+        assert_eq!(invalid[0].span.text(lexer.input), "\"abc");
+        assert_eq!(invalid[1].span.text(lexer.input), "# 2");
+        assert_eq!(tokens.iter().filter(|t| t.token == Token::Buat).count(), 3);
+    }
+
+    #[test]
+    fn test_mixed_tabs_and_spaces() {
+        let input = "jika x maka:\n \ty = 1\n";
+        let mut lexer = Lexer::new(input);
+
+        match lexer.tokenize() {
+            Err(LexerError::InconsistentIndentation(pos)) => assert_eq!(pos.line, 2),
+            other => panic!("Expected inconsistent indentation error, got {:?}", other),
+        }
+
+        let input = "jika x maka:\n    y = 1\njika y maka:\n\tz = 2\n";
+        let mut lexer = Lexer::new(input);
+
+        match lexer.tokenize() {
+            Err(LexerError::InconsistentIndentation(pos)) => assert_eq!(pos.line, 4),
+            other => panic!("Expected inconsistent indentation error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_invalid_dedent() {
+        let input = "jika x maka:\n    y = 1\n  z = 2\n";
+        let mut lexer = Lexer::new(input);
+
+        match lexer.tokenize() {
+            Err(LexerError::InvalidDedent(pos)) => {
+                assert_eq!(pos.line, 3);
+                assert_eq!(pos.column, 3);
+            }
+            other => panic!("Expected invalid dedent error, got {:?}", other),
+        }
+    }
+
+    fn lex_number(input: &str) -> Result<Token, LexerError> {
+        let mut lexer = Lexer::new(input);
+        let token = lexer.next_token()?;
+        assert_eq!(lexer.next_token()?, Token::Eof, "{} should be a single token", input);
+        Ok(token)
+    }
+
+    #[test]
+    fn test_integer_and_float_literals() {
+        assert_eq!(lex_number("42").unwrap(), Token::Integer(42));
+        assert_eq!(lex_number("2.75").unwrap(), Token::Float(2.75));
+        assert_eq!(lex_number("1_000_000").unwrap(), Token::Integer(1_000_000));
+        assert_eq!(lex_number("0xFF").unwrap(), Token::Integer(255));
+        assert_eq!(lex_number("0b1010_1010").unwrap(), Token::Integer(0b1010_1010));
+        assert_eq!(lex_number("0o755").unwrap(), Token::Integer(0o755));
+        assert_eq!(lex_number("1e-9").unwrap(), Token::Float(1e-9));
+        assert_eq!(lex_number("2.5E+3").unwrap(), Token::Float(2500.0));
+        assert_eq!(lex_number("9223372036854775807").unwrap(), Token::Integer(i64::MAX));
+    }
+
+    #[test]
+    fn test_number_before_range_and_member_access() {
+        let mut lexer = Lexer::new("1..5 2.abs");
+        let tokens: Vec<Token> = lexer.tokenize().unwrap().into_iter().map(|t| t.token).collect();
+
+        assert_eq!(tokens[0], Token::Integer(1));
+        assert_eq!(tokens[1], Token::DotDot);
+        assert_eq!(tokens[2], Token::Integer(5));
+        assert_eq!(tokens[3], Token::Integer(2));
+        assert_eq!(tokens[4], Token::Dot);
+    }
+
+    #[test]
+    fn test_integer_overflow() {
+        match lex_number("9223372036854775809") {
+            Err(LexerError::IntegerOverflow(literal, pos)) => {
+                assert_eq!(literal, "9223372036854775809");
+                assert_eq!(pos.column, 1);
+            }
+            other => panic!("Expected integer overflow, got {:?}", other),
+        }
+        // The digits of `-9223372036854775808` are left to the parser
+        assert_eq!(lex_number("9_223_372_036_854_775_808").unwrap(), Token::Integer(i64::MIN));
+        assert!(matches!(lex_number("0x1_0000_0000_0000_0000"), Err(LexerError::IntegerOverflow(..))));
+        assert!(matches!(lex_number("1e400"), Err(LexerError::FloatOverflow(..))));
+    }
+
+    #[test]
+    fn test_invalid_numbers() {
+        for input in ["0x", "0b102", "0o8", "1__000", "1_", "1e", "12abc", "0xFG"] {
+            assert!(
+                matches!(lex_number(input), Err(LexerError::InvalidNumber(..))),
+                "{} should be rejected",
+                input
+            );
+        }
+    }
+    fn lex_string(input: &str) -> Result<Token, LexerError> {
+        let mut lexer = Lexer::new(input);
+        let token = lexer.next_token()?;
+        assert_eq!(lexer.next_token()?, Token::Eof, "{} should be a single token", input);
+        Ok(token)
+    }
+
+    #[test]
+    fn test_string_escapes() {
+        assert_eq!(lex_string(r#""a\tb\nc\\d\"e\{f\}""#).unwrap(), Token::String("a\tb\nc\\d\"e{f}".to_string()));
+        assert_eq!(lex_string(r#""\u{48}\u{1F600}""#).unwrap(), Token::String("H\u{1F600}".to_string()));
+        assert_eq!(lex_string(r#"'single "quotes"'"#).unwrap(), Token::String("single \"quotes\"".to_string()));
+    }
+
+    #[test]
+    fn test_raw_and_multiline_strings() {
+        assert_eq!(lex_string(r#"r"C:\data\{x}""#).unwrap(), Token::String(r"C:\data\{x}".to_string()));
+        assert_eq!(
+            lex_string("\"\"\"\nBaris 1\n  Baris \"2\"\n\"\"\"").unwrap(),
+            Token::String("Baris 1\n  Baris \"2\"\n".to_string())
+        );
+        assert_eq!(lex_string("r'''a\\n\nb'''").unwrap(), Token::String("a\\n\nb".to_string()));
+    }
+
+    #[test]
+    fn test_string_interpolation() {
+        let token = lex_string(r#""Halo {nama}, {umur + 1} tahun""#).unwrap();
+
+        match token {
+            Token::InterpolatedString(parts) => {
+                assert_eq!(parts.len(), 5);
+                assert_eq!(parts[0], StringPart::Text("Halo ".to_string()));
+                match &parts[1] {
+                    StringPart::Expression(source, position, byte_offset) => {
+                        assert_eq!(source, "nama");
+                        assert_eq!(position.column, 8);
+                        assert_eq!(position.offset, 7);
+                        assert_eq!(*byte_offset, 7);
+                    }
+                    other => panic!("Expected expression, got {:?}", other),
+                }
+                assert!(matches!(&parts[4], StringPart::Text(text) if text == " tahun"));
+            }
+            other => panic!("Expected interpolated string, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_invalid_escape_position() {
+        match lex_string("\"abc\\qdef\"") {
+            Err(LexerError::InvalidEscape(escape, pos)) => {
+                assert_eq!(escape, "\\q");
+                assert_eq!(pos.column, 5);
+            }
+            other => panic!("Expected invalid escape, got {:?}", other),
+        }
+        assert!(matches!(lex_string(r#""\u{110000}""#), Err(LexerError::InvalidEscape(..))));
+        assert!(matches!(lex_string(r#""\u41""#), Err(LexerError::InvalidEscape(..))));
+    }
+
+    #[test]
+    fn test_invalid_interpolation() {
+        assert!(matches!(lex_string("\"Halo {nama\""), Err(LexerError::UnterminatedInterpolation(..))));
+        assert!(matches!(lex_string("\"Halo { }\""), Err(LexerError::EmptyInterpolation(..))));
+        assert!(matches!(lex_string("\"Halo\nDunia\""), Err(LexerError::UnterminatedString(..))));
+    }
+
+    #[test]
+    fn test_english_keywords_are_canonical() {
+        let mut lexer = Lexer::new("create if function class benar false");
+
+        assert_eq!(lexer.next_token().unwrap(), Token::Buat);
+        assert_eq!(lexer.next_token().unwrap(), Token::Jika);
+        assert_eq!(lexer.next_token().unwrap(), Token::Fungsi);
+        assert_eq!(lexer.next_token().unwrap(), Token::Kelas);
+        assert_eq!(lexer.next_token().unwrap(), Token::Boolean(true));
+        assert_eq!(lexer.next_token().unwrap(), Token::Boolean(false));
+    }
+
+    #[test]
+    fn test_unicode_identifiers() {
+        let tokens: Vec<Token> = Lexer::new("café = π * 名前_2")
+            .tokenize()
+            .unwrap()
+            .into_iter()
+            .map(|t| t.token)
+            .collect();
+
+        assert_eq!(tokens[0], Token::Identifier("café".to_string()));
+        assert_eq!(tokens[2], Token::Identifier("π".to_string()));
+        assert_eq!(tokens[4], Token::Identifier("名前_2".to_string()));
+    }
+
+    #[test]
+    fn test_identifiers_are_normalized() {
+        // "e" followed by a combining acute accent is the same name as "é"
+        let mut lexer = Lexer::new("cafe\u{301}");
+        let token = lexer.next_token_with_position().unwrap();
+
+        assert_eq!(token.token, Token::Identifier("café".to_string()));
+        assert_eq!(token.length, 5);
+
+        // A combining mark cannot start an identifier
+        assert!(matches!(Lexer::new("\u{301}a").next_token(), Err(LexerError::InvalidCharacter('\u{301}', _))));
+    }
+
+    #[test]
+    fn test_custom_keywords() {
+        let javanese = KeywordSet::new("jv")
+            .with_keyword("yen", Token::Jika)
+            .with_keyword("gawe", Token::Buat);
+        let keywords = Arc::new(KeywordRegistry::new().with_set(javanese));
+        let mut lexer = Lexer::new("yen gawe jika").with_keywords(keywords);
+
+        assert_eq!(lexer.next_token().unwrap(), Token::Jika);
+        assert_eq!(lexer.next_token().unwrap(), Token::Buat);
+        assert_eq!(lexer.next_token().unwrap(), Token::Identifier("jika".to_string()));
+    }
+
+    fn rebuild(source: &str, tokens: &[LosslessToken]) -> String {
+        let mut result = String::new();
+        for token in tokens {
+            for trivia in &token.leading_trivia {
+                result.push_str(trivia.span.text(source));
+            }
+            result.push_str(token.span.text(source));
+            for trivia in &token.trailing_trivia {
+                result.push_str(trivia.span.text(source));
+            }
         }
+        result
+    }
+
+    #[test]
+    fn test_lossless_round_trip() {
+        let source = concat!(
+            "// Salam\r\n",
+            "fungsi sapa(nama,   /* tamu */\n",
+            "         umur):\n",
+            "\n",
+            "    tampilkan \"Halo {nama}\"  // café\n",
+            "    x = [1,\n",
+            "  2]\n",
+            "\n",
+            "/* akhir */  ",
+        );
+        let tokens = Lexer::new(source).tokenize_lossless().unwrap();
+
+        assert_eq!(rebuild(source, &tokens), source);
+        assert_eq!(tokens.last().unwrap().token, Token::Eof);
+    }
+
+    #[test]
+    fn test_lossless_trivia_attachment() {
+        let source = "π = 1 // satu\n  /* dua */\ny";
+        let tokens = Lexer::new(source).tokenize_lossless().unwrap();
+
+        // Spans are in bytes: "π" is two bytes long
+        assert_eq!(tokens[0].span, Span::new(FileId::default(), 0, 2));
+        assert_eq!(tokens[1].span.text(source), "=");
+        assert_eq!(tokens[0].trailing_trivia.len(), 1);
+
+        let one = &tokens[2];
+        assert_eq!(one.token, Token::Integer(1));
+        let kinds: Vec<TriviaKind> = one.trailing_trivia.iter().map(|trivia| trivia.kind).collect();
+        assert_eq!(kinds, vec![TriviaKind::Whitespace, TriviaKind::LineComment]);
+        assert_eq!(one.trailing_trivia[1].span.text(source), "// satu");
+
+        // The comment on its own line leads the Newline token that ends it
+        let newline = &tokens[4];
+        assert_eq!(newline.token, Token::Newline);
+        assert_eq!(newline.leading_trivia.len(), 2);
+        assert_eq!(newline.leading_trivia[1].span.text(source), "/* dua */");
+        assert_eq!(newline.full_span().text(source), "  /* dua */\n");
+
+        assert_eq!(tokens[5].token, Token::Identifier("y".to_string()));
+        assert!(tokens[5].leading_trivia.is_empty());
+    }
+
+    #[test]
+    fn test_lossless_layout_tokens() {
+        let source = "jika x:\n    y\n";
+        let tokens = Lexer::new(source).tokenize_lossless().unwrap();
+
+        let indent = tokens.iter().position(|t| t.token == Token::Indent).unwrap();
+        assert!(tokens[indent].span.is_empty());
+        assert_eq!(tokens[indent + 1].leading_trivia[0].span.text(source), "    ");
+        assert!(tokens.iter().any(|t| t.token == Token::Dedent && t.span.is_empty()));
+        assert_eq!(rebuild(source, &tokens), source);
+    }
+
+    #[test]
+    fn test_doc_comments() {
+        let mut lexer = Lexer::new("/// Satu\n//// dua\n/** Tiga */ /**/ /*** empat */");
+
+        assert_eq!(lexer.next_token().unwrap(), Token::DocComment("Satu".to_string()));
+        assert_eq!(lexer.next_token().unwrap(), Token::Newline);
+        assert_eq!(lexer.next_token().unwrap(), Token::LineComment("// dua".to_string()));
+        assert_eq!(lexer.next_token().unwrap(), Token::Newline);
+        assert_eq!(lexer.next_token().unwrap(), Token::DocComment("Tiga".to_string()));
+        assert_eq!(lexer.next_token().unwrap(), Token::BlockComment("".to_string()));
+        assert_eq!(lexer.next_token().unwrap(), Token::BlockComment("** empat ".to_string()));
     }
 }
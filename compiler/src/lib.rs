@@ -1,22 +1,36 @@
 //! KODEON Programming Language Compiler Library
 
+// Errors carry their context, suggestion and example, so they are large on purpose
+#![allow(clippy::result_large_err)]
+
+pub mod keywords;
 pub mod lexer;
 pub mod parser;
 pub mod semantic_analyzer;
+pub mod flow;
+pub mod patterns;
+pub mod types;
+pub mod hir;
 pub mod ir;
 pub mod llvm_backend;
 pub mod module_resolver;
 pub mod optimizer;
 pub mod error_messages;
 pub mod debugger;
+pub mod doc_generator;
+pub mod diagnostics;
 
 // Re-export the main components for easier access
+pub use keywords::{KeywordRegistry, KeywordSet};
 pub use lexer::{Lexer, Token};
 pub use parser::{Parser, ASTNode};
-pub use semantic_analyzer::{SemanticAnalyzer, SymbolTable, Symbol};
+pub use semantic_analyzer::{SemanticAnalyzer, SemanticWarning, SymbolTable, Symbol};
+pub use types::Type;
 pub use ir::{IRModule, IRGenerator, print_ir};
 pub use llvm_backend::LLVMBackend;
 pub use module_resolver::ModuleResolver;
 pub use optimizer::Optimizer;
-pub use error_messages::{ErrorMessage, ErrorMessages};
+pub use error_messages::{ErrorMessage, ErrorMessages, Language};
 pub use debugger::{Debugger, create_debugger};
+pub use doc_generator::{DocFormat, DocGenerator};
+pub use diagnostics::{Diagnostic, DiagnosticRenderer, ErrorFormat, SourceMap};
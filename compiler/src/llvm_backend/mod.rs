@@ -1,12 +1,14 @@
 //! LLVM backend for the KODEON programming language
 
-use crate::ir::{IRModule, Type, DebugInfo};
+use crate::hir::Capture;
+use crate::ir::{Case, ClassLayout, ClosureLayout, Decision, EnumLayout, IRModule, InterfaceLayout, LoopStep, MatchCase, Stage, Step, Test, Type, DebugInfo};
+use inkwell::basic_block::BasicBlock;
 use inkwell::context::Context;
-use inkwell::module::Module;
+use inkwell::module::{Linkage, Module};
 use inkwell::targets::{InitializationConfig, Target};
-use inkwell::types::BasicTypeEnum;
-use inkwell::values::{BasicValueEnum, FunctionValue};
-use inkwell::AddressSpace;
+use inkwell::types::{BasicTypeEnum, StructType};
+use inkwell::values::{BasicMetadataValueEnum, BasicValueEnum, CallSiteValue, CallableValue, FunctionValue, GlobalValue, IntValue, PointerValue};
+use inkwell::{AddressSpace, FloatPredicate, IntPredicate};
 use inkwell::debug_info::{DIFile, DICompileUnit, DIBasicType, DISubprogram, DISubroutineType, DIType, DIFlags};
 use std::collections::HashMap;
 
@@ -17,6 +19,22 @@ pub struct LLVMBackend<'ctx> {
     builder: inkwell::builder::Builder<'ctx>,
     variables: HashMap<String, inkwell::values::PointerValue<'ctx>>,
     functions: HashMap<String, FunctionValue<'ctx>>,
+    // Classes: the struct of their instances, their virtual table and their IR layout
+    class_types: HashMap<String, StructType<'ctx>>,
+    vtables: HashMap<String, GlobalValue<'ctx>>,
+    class_layouts: HashMap<String, ClassLayout>,
+    // Interfaces: their IR layout, and the virtual table and interface table of each class implementing them
+    interface_layouts: HashMap<String, InterfaceLayout>,
+    interface_tables: HashMap<String, Vec<(GlobalValue<'ctx>, GlobalValue<'ctx>)>>,
+    // Enums: the header every value starts with, the struct of each variant and their IR layout
+    enum_types: HashMap<String, (StructType<'ctx>, Vec<StructType<'ctx>>)>,
+    enum_layouts: HashMap<String, EnumLayout>,
+    // Closures: the struct of the environment of each closure function, and its IR layout
+    closure_types: HashMap<String, StructType<'ctx>>,
+    closure_layouts: HashMap<String, ClosureLayout>,
+    // Blocks of the function being compiled, and the landing pad calls unwind to inside an invoke
+    blocks: HashMap<String, BasicBlock<'ctx>>,
+    unwind_block: Option<BasicBlock<'ctx>>,
     // Debug information
     di_builder: Option<inkwell::debug_info::DebugInfoBuilder<'ctx>>,
     di_compile_unit: Option<DICompileUnit<'ctx>>,
@@ -66,6 +84,17 @@ impl<'ctx> LLVMBackend<'ctx> {
             builder,
             variables: HashMap::new(),
             functions: HashMap::new(),
+            class_types: HashMap::new(),
+            vtables: HashMap::new(),
+            class_layouts: HashMap::new(),
+            interface_layouts: HashMap::new(),
+            interface_tables: HashMap::new(),
+            enum_types: HashMap::new(),
+            enum_layouts: HashMap::new(),
+            closure_types: HashMap::new(),
+            closure_layouts: HashMap::new(),
+            blocks: HashMap::new(),
+            unwind_block: None,
             di_builder: Some(di_builder),
             di_compile_unit: Some(di_compile_unit),
             di_file: Some(di_file),
@@ -79,11 +108,33 @@ impl<'ctx> LLVMBackend<'ctx> {
             self.compile_global_variable(global_var)?;
         }
 
-        // Compile functions
+        // Declare the instance structs of classes, which parameters and fields may refer to
+        for interface in &ir_module.interfaces {
+            self.interface_layouts.insert(interface.name.clone(), interface.clone());
+        }
+        for class in &ir_module.classes {
+            self.declare_class(class)?;
+        }
+        for enumeration in &ir_module.enums {
+            self.declare_enum(enumeration)?;
+        }
+        for closure in &ir_module.closures {
+            self.declare_closure(closure)?;
+        }
+
+        // Declare every function before compiling any, since closures refer to functions lifted after them
+        for function in &ir_module.functions {
+            self.declare_function(function)?;
+        }
         for function in &ir_module.functions {
             self.compile_function(function)?;
         }
 
+        // Fill the virtual and interface tables, now that the methods they point to exist
+        for class in &ir_module.classes {
+            self.define_vtable(class)?;
+        }
+
         // Future-oriented: Apply optimization passes
         self.apply_optimizations()?;
 
@@ -119,18 +170,135 @@ impl<'ctx> LLVMBackend<'ctx> {
         let global = self.module.add_global(llvm_type, Some(AddressSpace::default()), &global_var.name);
 
         if let Some(initializer) = &global_var.initializer {
-            let llvm_value = self.convert_value(initializer)?;
+            // Outside a function a text cannot be built with the builder, so it gets a global of its own
+            let llvm_value = match initializer {
+                crate::ir::Value::Constant(crate::ir::Constant::String(text)) => {
+                    self.constant_string(text, &format!("{}.text", global_var.name)).into()
+                }
+                _ => self.convert_value(initializer)?,
+            };
             global.set_initializer(&llvm_value);
         }
+        global.set_constant(global_var.constant);
+        // Functions read and write it as they do their own variables
+        self.variables.insert(global_var.name.clone(), global.as_pointer_value());
+
+        Ok(())
+    }
+
+    /// Pointer to a private constant holding `text` and a terminating zero, usable as an initializer
+    fn constant_string(&self, text: &str, name: &str) -> PointerValue<'ctx> {
+        let bytes = self.context.const_string(text.as_bytes(), true);
+        let global = self.module.add_global(bytes.get_type(), Some(AddressSpace::default()), name);
+        global.set_initializer(&bytes);
+        global.set_constant(true);
+        global.set_linkage(Linkage::Private);
+        global.as_pointer_value().const_cast(self.context.i8_type().ptr_type(AddressSpace::default()))
+    }
+
+    /// Declare the struct of the instances of a class and its virtual table
+    ///
+    /// An instance starts with a pointer to the virtual table of its class, followed by its fields
+    /// in layout order; fields inherited come first, so an instance can be used as one of its parent.
+    fn declare_class(&mut self, class: &ClassLayout) -> Result<(), String> {
+        let struct_type = self.context.opaque_struct_type(&class.name);
+        self.class_types.insert(class.name.clone(), struct_type);
+        self.class_layouts.insert(class.name.clone(), class.clone());
+
+        let pointer_type = self.context.i8_type().ptr_type(AddressSpace::default());
+        let mut field_types: Vec<BasicTypeEnum> = vec![pointer_type.into()];
+        for (_, field_type) in &class.fields {
+            field_types.push(self.convert_type(field_type)?);
+        }
+        struct_type.set_body(&field_types, false);
+
+        let vtable_type = pointer_type.array_type(class.methods.len() as u32);
+        let vtable = self.module.add_global(vtable_type, None, &format!("{}.vtable", class.name));
+        self.vtables.insert(class.name.clone(), vtable);
+
+        // The interface tables of a class are found through its virtual table, which identifies it
+        for (interface, functions) in &class.interfaces {
+            let itable_type = pointer_type.array_type(functions.len() as u32);
+            let itable = self.module.add_global(itable_type, None, &format!("{}.{}.itable", class.name, interface));
+            self.interface_tables.entry(interface.clone()).or_default().push((vtable, itable));
+        }
+        Ok(())
+    }
+
+    /// Declare the structs of the values of an enum
+    ///
+    /// A value points to a struct that starts with the index of its variant, followed by the fields of
+    /// that variant; it is read through the header `{ i64 }` until its variant is known.
+    fn declare_enum(&mut self, enumeration: &EnumLayout) -> Result<(), String> {
+        let tag_type = self.context.i64_type();
+        let header = self.context.opaque_struct_type(&enumeration.name);
+        header.set_body(&[tag_type.into()], false);
+
+        let mut variants = Vec::new();
+        for (variant, fields) in &enumeration.variants {
+            let variant_type = self.context.opaque_struct_type(&format!("{}.{}", enumeration.name, variant));
+            let mut field_types: Vec<BasicTypeEnum> = vec![tag_type.into()];
+            for field_type in fields {
+                field_types.push(self.convert_type(field_type)?);
+            }
+            variant_type.set_body(&field_types, false);
+            variants.push(variant_type);
+        }
+        self.enum_types.insert(enumeration.name.clone(), (header, variants));
+        self.enum_layouts.insert(enumeration.name.clone(), enumeration.clone());
+        Ok(())
+    }
+
+    /// Declare the struct of the environment of a closure function
+    ///
+    /// A variable captured by value is copied into the environment; one captured by reference is
+    /// stored as the address of the variable its function shares with the closure.
+    fn declare_closure(&mut self, closure: &ClosureLayout) -> Result<(), String> {
+        let name = format!("{}.$env", closure.function);
+        let struct_type = self.context.opaque_struct_type(&name);
+        let mut field_types: Vec<BasicTypeEnum> = Vec::new();
+        for (_, capture_type, capture) in &closure.captures {
+            let capture_type = self.convert_type(capture_type)?;
+            field_types.push(match capture {
+                Capture::ByValue => capture_type,
+                Capture::ByReference => capture_type.ptr_type(AddressSpace::default()).into(),
+            });
+        }
+        struct_type.set_body(&field_types, false);
+        self.closure_types.insert(name, struct_type);
+        self.closure_layouts.insert(closure.function.clone(), closure.clone());
+        Ok(())
+    }
 
+    /// Point each slot of the virtual table of a class at the function run for its method
+    fn define_vtable(&mut self, class: &ClassLayout) -> Result<(), String> {
+        let pointer_type = self.context.i8_type().ptr_type(AddressSpace::default());
+        let mut slots = Vec::new();
+        for (method, function) in &class.methods {
+            let function = self.functions.get(function)
+                .ok_or_else(|| format!("Method {}.{} has no function {}", class.name, method, function))?;
+            slots.push(function.as_global_value().as_pointer_value().const_cast(pointer_type));
+        }
+        let vtable = self.vtables.get(&class.name)
+            .ok_or_else(|| format!("Class {} was not declared", class.name))?;
+        vtable.set_initializer(&pointer_type.const_array(&slots));
+
+        for (interface, functions) in &class.interfaces {
+            let mut slots = Vec::new();
+            for function in functions {
+                let function = self.functions.get(function)
+                    .ok_or_else(|| format!("Interface {} of {} has no function {}", interface, class.name, function))?;
+                slots.push(function.as_global_value().as_pointer_value().const_cast(pointer_type));
+            }
+            let itable = self.module.get_global(&format!("{}.{}.itable", class.name, interface))
+                .ok_or_else(|| format!("Class {} has no table for interface {}", class.name, interface))?;
+            itable.set_initializer(&pointer_type.const_array(&slots));
+        }
         Ok(())
     }
 
     /// Compile a function
     fn compile_function(&mut self, function: &crate::ir::Function) -> Result<(), String> {
-        // Convert return type
-        let return_type = self.convert_type(&function.return_type)?;
-
         // Convert parameter types
         let param_types: Vec<BasicTypeEnum> = function
             .parameters
@@ -138,16 +306,7 @@ impl<'ctx> LLVMBackend<'ctx> {
             .map(|param| self.convert_type(&param.param_type))
             .collect::<Result<Vec<_>, _>>()?;
 
-        // Create function type
-        let fn_type = if function.return_type == crate::ir::Type::Void {
-            self.context.void_type().fn_type(&param_types, false)
-        } else {
-            return_type.fn_type(&param_types, false)
-        };
-
-        // Create function
-        let llvm_function = self.module.add_function(&function.name, fn_type, None);
-        self.functions.insert(function.name.clone(), llvm_function);
+        let llvm_function = self.declare_function(function)?;
 
         // Set parameter names
         for (i, param) in llvm_function.get_param_iter().enumerate() {
@@ -201,7 +360,8 @@ impl<'ctx> LLVMBackend<'ctx> {
             }
         }
 
-        // Create basic blocks
+        // Create basic blocks first, so that terminators can branch to the blocks after them
+        self.blocks.clear();
         for (i, block) in function.blocks.iter().enumerate() {
             let block_name = if i == 0 {
                 "entry"
@@ -210,9 +370,20 @@ impl<'ctx> LLVMBackend<'ctx> {
             };
 
             let llvm_block = self.context.append_basic_block(llvm_function, block_name);
+            self.blocks.insert(block.name.clone(), llvm_block);
+        }
+
+        // Parameters are read as variables, so each gets one in the entry block
+        if let Some(entry) = function.blocks.first() {
+            self.builder.position_at_end(self.block(&entry.name)?);
+            for (param, parameter) in llvm_function.get_param_iter().zip(&function.parameters) {
+                self.store_result(&parameter.name, param);
+            }
+        }
 
+        for block in &function.blocks {
             // Compile instructions in the block
-            self.builder.position_at_end(llvm_block);
+            self.builder.position_at_end(self.block(&block.name)?);
 
             // Set debug location for the block if available
             if let (Some(ref di_builder), Some(ref di_file)) = (&self.di_builder, &self.di_file) {
@@ -232,6 +403,26 @@ impl<'ctx> LLVMBackend<'ctx> {
         Ok(())
     }
 
+    /// Add a function to the module, unless it was declared already
+    fn declare_function(&mut self, function: &crate::ir::Function) -> Result<FunctionValue<'ctx>, String> {
+        if let Some(llvm_function) = self.functions.get(&function.name) {
+            return Ok(*llvm_function);
+        }
+        let param_types: Vec<inkwell::types::BasicMetadataTypeEnum> = function
+            .parameters
+            .iter()
+            .map(|param| self.convert_type(&param.param_type).map(Into::into))
+            .collect::<Result<Vec<_>, _>>()?;
+        let fn_type = if function.return_type == crate::ir::Type::Void {
+            self.context.void_type().fn_type(&param_types, false)
+        } else {
+            self.convert_type(&function.return_type)?.fn_type(&param_types, false)
+        };
+        let llvm_function = self.module.add_function(&function.name, fn_type, None);
+        self.functions.insert(function.name.clone(), llvm_function);
+        Ok(llvm_function)
+    }
+
     /// Compile an instruction with enhanced debug information
     fn compile_instruction(&mut self, instruction: &crate::ir::Instruction) -> Result<(), String> {
         // Set debug location for the instruction if available
@@ -244,6 +435,7 @@ impl<'ctx> LLVMBackend<'ctx> {
                 crate::ir::Instruction::Call { debug_info, .. } => debug_info,
                 crate::ir::Instruction::Alloca { debug_info, .. } => debug_info,
                 crate::ir::Instruction::Return { debug_info, .. } => debug_info,
+                crate::ir::Instruction::Throw { debug_info, .. } => debug_info,
                 // Elegant instructions
                 crate::ir::Instruction::Chain { debug_info, .. } => debug_info,
                 crate::ir::Instruction::Pipeline { debug_info, .. } => debug_info,
@@ -254,6 +446,27 @@ impl<'ctx> LLVMBackend<'ctx> {
                 crate::ir::Instruction::Range { debug_info, .. } => debug_info,
                 crate::ir::Instruction::ObjectLiteral { debug_info, .. } => debug_info,
                 crate::ir::Instruction::MemberAccess { debug_info, .. } => debug_info,
+                // Instances of classes
+                crate::ir::Instruction::NewObject { debug_info, .. } => debug_info,
+                crate::ir::Instruction::GetField { debug_info, .. } => debug_info,
+                crate::ir::Instruction::SetField { debug_info, .. } => debug_info,
+                crate::ir::Instruction::VirtualCall { debug_info, .. } => debug_info,
+                crate::ir::Instruction::InterfaceCall { debug_info, .. } => debug_info,
+                // Values of enums, and tuples
+                crate::ir::Instruction::NewVariant { debug_info, .. } => debug_info,
+                crate::ir::Instruction::MakeTuple { debug_info, .. } => debug_info,
+                // Closures
+                crate::ir::Instruction::AllocaShared { debug_info, .. } => debug_info,
+                crate::ir::Instruction::MakeClosure { debug_info, .. } => debug_info,
+                crate::ir::Instruction::BindCapture { debug_info, .. } => debug_info,
+                crate::ir::Instruction::ClosureCall { debug_info, .. } => debug_info,
+                // Exceptions
+                crate::ir::Instruction::LandingPad { debug_info, .. } => debug_info,
+                crate::ir::Instruction::ExceptionIs { debug_info, .. } => debug_info,
+                crate::ir::Instruction::Catch { debug_info, .. } => debug_info,
+                // Optionals
+                crate::ir::Instruction::NullCheck { debug_info, .. } => debug_info,
+                crate::ir::Instruction::Unwrap { debug_info, .. } => debug_info,
                 crate::ir::Instruction::ForEachLoop { debug_info, .. } => debug_info,
                 crate::ir::Instruction::PatternMatch { debug_info, .. } => debug_info,
                 crate::ir::Instruction::Await { debug_info, .. } => debug_info,
@@ -284,74 +497,145 @@ impl<'ctx> LLVMBackend<'ctx> {
         }
 
         match instruction {
-            crate::ir::Instruction::BinaryOp { result, op, left, right } => {
+            crate::ir::Instruction::BinaryOp { result, op, left, right, .. } => {
                 self.compile_binary_op(result, op, left, right)
             }
-            crate::ir::Instruction::UnaryOp { result, op, operand } => {
+            crate::ir::Instruction::UnaryOp { result, op, operand, .. } => {
                 self.compile_unary_op(result, op, operand)
             }
-            crate::ir::Instruction::Load { result, variable } => {
+            crate::ir::Instruction::Load { result, variable, .. } => {
                 self.compile_load(result, variable)
             }
-            crate::ir::Instruction::Store { variable, value } => {
+            crate::ir::Instruction::Store { variable, value, .. } => {
                 self.compile_store(variable, value)
             }
-            crate::ir::Instruction::Call { result, function, arguments } => {
-                self.compile_call(result, function, arguments)
+            crate::ir::Instruction::Call { result, function, arguments, .. } => {
+                self.compile_call(result.as_deref(), function, arguments)
+            }
+            crate::ir::Instruction::Alloca { result, alloca_type, .. } => {
+                self.compile_alloca(result, alloca_type)
+            }
+            crate::ir::Instruction::Return { value, .. } => {
+                self.compile_return(value.as_ref())?;
+                // As after a throw, the instructions after a return in a case still need a block
+                let after = self.context.append_basic_block(self.current_function()?, "return.after");
+                self.builder.position_at_end(after);
+                Ok(())
             }
-            crate::ir::Instruction::Alloca { result, alloca_type } => {
-                self.compile_alloca(result, alloloca_type)
+            // Instances of classes
+            crate::ir::Instruction::NewObject { result, class, .. } => {
+                self.compile_new_object(result, class)
             }
-            crate::ir::Instruction::Return { value } => {
-                self.compile_return(value)
+            crate::ir::Instruction::GetField { result, object, class, index, .. } => {
+                self.compile_get_field(result, object, class, *index)
+            }
+            crate::ir::Instruction::SetField { object, class, index, value, .. } => {
+                self.compile_set_field(object, class, *index, value)
+            }
+            crate::ir::Instruction::VirtualCall { result, object, class, slot, arguments, .. } => {
+                self.compile_virtual_call(result.as_deref(), object, class, *slot, arguments)
+            }
+            crate::ir::Instruction::InterfaceCall { result, object, interface, slot, arguments, .. } => {
+                self.compile_interface_call(result.as_deref(), object, interface, *slot, arguments)
+            }
+            // Values of enums, and tuples
+            crate::ir::Instruction::NewVariant { result, enumeration, variant, fields, .. } => {
+                self.compile_new_variant(result, enumeration, *variant, fields)
+            }
+            crate::ir::Instruction::MakeTuple { result, elements, .. } => {
+                self.compile_make_tuple(result, elements)
+            }
+            crate::ir::Instruction::PatternMatch { expression, cases, default, .. } => {
+                self.compile_pattern_match(expression, cases, default.as_deref())
+            }
+            // Closures
+            crate::ir::Instruction::AllocaShared { result, alloca_type, .. } => {
+                self.compile_alloca_shared(result, alloca_type)
+            }
+            crate::ir::Instruction::MakeClosure { result, function, captures, .. } => {
+                self.compile_make_closure(result, function, captures)
+            }
+            crate::ir::Instruction::BindCapture { variable, function, index, .. } => {
+                self.compile_bind_capture(variable, function, *index)
+            }
+            crate::ir::Instruction::ClosureCall { result, closure, function_type, arguments, .. } => {
+                self.compile_closure_call(result.as_deref(), closure, function_type, arguments)
+            }
+            // Exceptions
+            crate::ir::Instruction::Throw { exception, exception_type, .. } => {
+                self.compile_throw(exception, exception_type, self.unwind_block)?;
+                // Instructions after the throw are never run, but still need a block
+                let after = self.context.append_basic_block(self.current_function()?, "throw.after");
+                self.builder.position_at_end(after);
+                Ok(())
+            }
+            crate::ir::Instruction::LandingPad { result, .. } => {
+                self.compile_landing_pad(result)
+            }
+            crate::ir::Instruction::ExceptionIs { result, exception, class, .. } => {
+                self.compile_exception_is(result, exception, class)
+            }
+            crate::ir::Instruction::Catch { result, exception, .. } => {
+                self.compile_catch(result, exception)
+            }
+            // Optionals
+            crate::ir::Instruction::NullCheck { result, value, .. } => {
+                self.compile_null_check(result, value)
+            }
+            crate::ir::Instruction::Unwrap { result, value, .. } => {
+                self.compile_unwrap(result, value)
+            }
+            // Pipelines
+            crate::ir::Instruction::Pipeline { result, initial, element_type, stages, .. } => {
+                self.compile_pipeline(result, initial, element_type, stages)
             }
             // Concurrency instructions
-            crate::ir::Instruction::MakeChannel { result, channel_type } => {
+            crate::ir::Instruction::MakeChannel { result, channel_type, .. } => {
                 self.compile_make_channel(result, channel_type)
             }
-            crate::ir::Instruction::ChannelSend { channel, value } => {
+            crate::ir::Instruction::ChannelSend { channel, value, .. } => {
                 self.compile_channel_send(channel, value)
             }
-            crate::ir::Instruction::ChannelReceive { result, channel } => {
+            crate::ir::Instruction::ChannelReceive { result, channel, .. } => {
                 self.compile_channel_receive(result, channel)
             }
-            crate::ir::Instruction::MakeGoroutine { result, function } => {
+            crate::ir::Instruction::MakeGoroutine { result, function, .. } => {
                 self.compile_make_goroutine(result, function)
             }
-            crate::ir::Instruction::GoRoutine { function, arguments } => {
+            crate::ir::Instruction::GoRoutine { function, arguments, .. } => {
                 self.compile_goroutine(function, arguments)
             }
-            crate::ir::Instruction::MutexLock { mutex } => {
+            crate::ir::Instruction::MutexLock { mutex, .. } => {
                 self.compile_mutex_lock(mutex)
             }
-            crate::ir::Instruction::MutexUnlock { mutex } => {
+            crate::ir::Instruction::MutexUnlock { mutex, .. } => {
                 self.compile_mutex_unlock(mutex)
             }
-            crate::ir::Instruction::ConditionWait { condition, mutex } => {
+            crate::ir::Instruction::ConditionWait { condition, mutex, .. } => {
                 self.compile_condition_wait(condition, mutex)
             }
-            crate::ir::Instruction::ConditionSignal { condition } => {
+            crate::ir::Instruction::ConditionSignal { condition, .. } => {
                 self.compile_condition_signal(condition)
             }
-            crate::ir::Instruction::ConditionBroadcast { condition } => {
+            crate::ir::Instruction::ConditionBroadcast { condition, .. } => {
                 self.compile_condition_broadcast(condition)
             }
-            crate::ir::Instruction::AtomicLoad { result, address, ordering } => {
+            crate::ir::Instruction::AtomicLoad { result, address, ordering, .. } => {
                 self.compile_atomic_load(result, address, ordering)
             }
-            crate::ir::Instruction::AtomicStore { address, value, ordering } => {
+            crate::ir::Instruction::AtomicStore { address, value, ordering, .. } => {
                 self.compile_atomic_store(address, value, ordering)
             }
-            crate::ir::Instruction::AtomicExchange { result, address, value, ordering } => {
+            crate::ir::Instruction::AtomicExchange { result, address, value, ordering, .. } => {
                 self.compile_atomic_exchange(result, address, value, ordering)
             }
-            crate::ir::Instruction::AtomicCompareExchange { result, address, expected, desired, success_ordering, failure_ordering } => {
+            crate::ir::Instruction::AtomicCompareExchange { result, address, expected, desired, success_ordering, failure_ordering, .. } => {
                 self.compile_atomic_compare_exchange(result, address, expected, desired, success_ordering, failure_ordering)
             }
-            crate::ir::Instruction::AtomicFetchAdd { result, address, value, ordering } => {
+            crate::ir::Instruction::AtomicFetchAdd { result, address, value, ordering, .. } => {
                 self.compile_atomic_fetch_add(result, address, value, ordering)
             }
-            crate::ir::Instruction::AtomicFetchSub { result, address, value, ordering } => {
+            crate::ir::Instruction::AtomicFetchSub { result, address, value, ordering, .. } => {
                 self.compile_atomic_fetch_sub(result, address, value, ordering)
             }
             // Other instructions...
@@ -363,6 +647,1042 @@ impl<'ctx> LLVMBackend<'ctx> {
         }
     }
 
+    /// Compile binary operation instruction. Ints and bools use integer instructions, floats float ones, and
+    /// texts are compared through `strcmp`
+    fn compile_binary_op(&mut self, result: &str, op: &crate::ir::BinaryOp, left: &crate::ir::Value, right: &crate::ir::Value) -> Result<(), String> {
+        use crate::ir::BinaryOp;
+        let (left, right) = (self.convert_value(left)?, self.convert_value(right)?);
+        let value: BasicValueEnum = match (left, right) {
+            (BasicValueEnum::IntValue(left), BasicValueEnum::IntValue(right)) => match op {
+                BinaryOp::Add => self.builder.build_int_add(left, right, result),
+                BinaryOp::Sub => self.builder.build_int_sub(left, right, result),
+                BinaryOp::Mul => self.builder.build_int_mul(left, right, result),
+                BinaryOp::Div => self.builder.build_int_signed_div(left, right, result),
+                BinaryOp::Mod => self.builder.build_int_signed_rem(left, right, result),
+                BinaryOp::And | BinaryOp::BitAnd => self.builder.build_and(left, right, result),
+                BinaryOp::Or | BinaryOp::BitOr => self.builder.build_or(left, right, result),
+                BinaryOp::BitXor => self.builder.build_xor(left, right, result),
+                BinaryOp::LeftShift => self.builder.build_left_shift(left, right, result),
+                BinaryOp::RightShift => self.builder.build_right_shift(left, right, true, result),
+                _ => self.builder.build_int_compare(Self::int_predicate(op)?, left, right, result),
+            }.into(),
+            (BasicValueEnum::FloatValue(left), BasicValueEnum::FloatValue(right)) => match op {
+                BinaryOp::Add => self.builder.build_float_add(left, right, result).into(),
+                BinaryOp::Sub => self.builder.build_float_sub(left, right, result).into(),
+                BinaryOp::Mul => self.builder.build_float_mul(left, right, result).into(),
+                BinaryOp::Div => self.builder.build_float_div(left, right, result).into(),
+                BinaryOp::Mod => self.builder.build_float_rem(left, right, result).into(),
+                _ => self.builder.build_float_compare(Self::float_predicate(op)?, left, right, result).into(),
+            },
+            (BasicValueEnum::PointerValue(left), BasicValueEnum::PointerValue(right)) => {
+                let strcmp = self.runtime_function("strcmp", Some(self.context.i32_type().into()), &[self.untyped_pointer(), self.untyped_pointer()]);
+                let order = self.builder.build_call(strcmp, &[left.into(), right.into()], "order")
+                    .try_as_basic_value().left().ok_or("strcmp returns no value")?;
+                self.builder.build_int_compare(Self::int_predicate(op)?, order.into_int_value(), self.context.i32_type().const_zero(), result).into()
+            }
+            (left, right) => return Err(format!("Operation {:?} is not supported on {:?} and {:?}", op, left.get_type(), right.get_type())),
+        };
+        self.store_result(result, value);
+        Ok(())
+    }
+
+    /// Predicate comparing two ints, or the order `strcmp` gives two texts, with `op`
+    fn int_predicate(op: &crate::ir::BinaryOp) -> Result<IntPredicate, String> {
+        use crate::ir::BinaryOp;
+        match op {
+            BinaryOp::Eq => Ok(IntPredicate::EQ),
+            BinaryOp::Ne => Ok(IntPredicate::NE),
+            BinaryOp::Lt => Ok(IntPredicate::SLT),
+            BinaryOp::Gt => Ok(IntPredicate::SGT),
+            BinaryOp::Le => Ok(IntPredicate::SLE),
+            BinaryOp::Ge => Ok(IntPredicate::SGE),
+            _ => Err(format!("Operation {:?} does not compare values", op)),
+        }
+    }
+
+    /// Predicate comparing two floats with `op`
+    fn float_predicate(op: &crate::ir::BinaryOp) -> Result<FloatPredicate, String> {
+        use crate::ir::BinaryOp;
+        match op {
+            BinaryOp::Eq => Ok(FloatPredicate::OEQ),
+            BinaryOp::Ne => Ok(FloatPredicate::ONE),
+            BinaryOp::Lt => Ok(FloatPredicate::OLT),
+            BinaryOp::Gt => Ok(FloatPredicate::OGT),
+            BinaryOp::Le => Ok(FloatPredicate::OLE),
+            BinaryOp::Ge => Ok(FloatPredicate::OGE),
+            _ => Err(format!("Operation {:?} is not supported on floats", op)),
+        }
+    }
+
+    /// Compile unary operation instruction; `&` takes the address of a variable, and `*` reads through one
+    fn compile_unary_op(&mut self, result: &str, op: &crate::ir::UnaryOp, operand: &crate::ir::Value) -> Result<(), String> {
+        use crate::ir::UnaryOp;
+        if let (UnaryOp::AddressOf, crate::ir::Value::Variable(name)) = (op, operand) {
+            let variable = self.variables.get(name).copied().ok_or_else(|| format!("Variable {} not found", name))?;
+            self.store_result(result, variable.into());
+            return Ok(());
+        }
+        let value: BasicValueEnum = match (op, self.convert_value(operand)?) {
+            (UnaryOp::Neg, BasicValueEnum::IntValue(value)) => self.builder.build_int_neg(value, result).into(),
+            (UnaryOp::Neg, BasicValueEnum::FloatValue(value)) => self.builder.build_float_neg(value, result).into(),
+            (UnaryOp::Not | UnaryOp::BitNot, BasicValueEnum::IntValue(value)) => self.builder.build_not(value, result).into(),
+            (UnaryOp::Increment, BasicValueEnum::IntValue(value)) => {
+                self.builder.build_int_add(value, value.get_type().const_int(1, false), result).into()
+            }
+            (UnaryOp::Decrement, BasicValueEnum::IntValue(value)) => {
+                self.builder.build_int_sub(value, value.get_type().const_int(1, false), result).into()
+            }
+            (UnaryOp::Dereference, BasicValueEnum::PointerValue(value)) => self.builder.build_load(value, result),
+            (op, value) => return Err(format!("Operation {:?} is not supported on {:?}", op, value.get_type())),
+        };
+        self.store_result(result, value);
+        Ok(())
+    }
+
+    /// Compile load instruction: read a variable into one named after the result
+    fn compile_load(&mut self, result: &str, variable: &str) -> Result<(), String> {
+        let variable_ptr = self.variables.get(variable).copied().ok_or_else(|| format!("Variable {} not found", variable))?;
+        let value = self.builder.build_load(variable_ptr, result);
+        self.store_result(result, value);
+        Ok(())
+    }
+
+    /// Compile store instruction; a variable stored to before any alloca gets the type of its first value
+    fn compile_store(&mut self, variable: &str, value: &crate::ir::Value) -> Result<(), String> {
+        let value = self.convert_value(value)?;
+        match self.variables.get(variable) {
+            Some(variable_ptr) => {
+                self.builder.build_store(*variable_ptr, value);
+            }
+            None => self.store_result(variable, value),
+        }
+        Ok(())
+    }
+
+    /// Compile call instruction, to a function of the module or one the runtime provides
+    fn compile_call(&mut self, result: Option<&str>, function: &str, arguments: &[crate::ir::Value]) -> Result<(), String> {
+        let llvm_function = self.functions.get(function).copied()
+            .or_else(|| self.module.get_function(function))
+            .ok_or_else(|| format!("Function {} not found", function))?;
+        let arguments = arguments.iter().map(|argument| self.convert_value(argument)).collect::<Result<Vec<_>, _>>()?;
+        let value = self.build_call_site(llvm_function.into(), &arguments, result.unwrap_or(""))?.try_as_basic_value().left();
+        if let (Some(result), Some(value)) = (result, value) {
+            self.store_result(result, value);
+        }
+        Ok(())
+    }
+
+    /// Compile alloca instruction: a variable on the stack of the function
+    fn compile_alloca(&mut self, result: &str, alloca_type: &Type) -> Result<(), String> {
+        let llvm_type = self.convert_type(alloca_type)?;
+        let variable = self.builder.build_alloca(llvm_type, result);
+        self.variables.insert(result.to_string(), variable);
+        Ok(())
+    }
+
+    /// Compile a return, from the terminator of a block or the instructions of a case
+    fn compile_return(&mut self, value: Option<&crate::ir::Value>) -> Result<(), String> {
+        if let Some(value) = value {
+            let llvm_value = self.convert_value(value)?;
+            self.builder.build_return(Some(&llvm_value));
+        } else if self.current_function()?.get_type().get_return_type().is_some() {
+            // The flow analysis rejects functions returning a value whose end a path reaches (K0214), so
+            // only blocks no path reaches fall off the end here
+            self.builder.build_unreachable();
+        } else {
+            self.builder.build_return(None);
+        }
+        Ok(())
+    }
+
+    /// Struct of the instances of class `class`
+    fn class_type(&self, class: &str) -> Result<StructType<'ctx>, String> {
+        self.class_types.get(class).copied().ok_or_else(|| format!("Class {} was not declared", class))
+    }
+
+    /// Pointer to the instance `object` as an instance of class `class`, which it is or extends
+    fn object_pointer(&self, object: &crate::ir::Value, class: &str) -> Result<inkwell::values::PointerValue<'ctx>, String> {
+        let object = self.convert_value(object)?.into_pointer_value();
+        let object_type = self.class_type(class)?.ptr_type(AddressSpace::default());
+        Ok(self.builder.build_pointer_cast(object, object_type, "object"))
+    }
+
+    /// Keep the value of an instruction in a variable named after its result, as the other instructions do
+    fn store_result(&mut self, result: &str, value: BasicValueEnum<'ctx>) {
+        let result_ptr = self.builder.build_alloca(value.get_type(), result);
+        self.builder.build_store(result_ptr, value);
+        self.variables.insert(result.to_string(), result_ptr);
+    }
+
+    /// Compile new object instruction: allocate an instance with zeroed fields and set its virtual table
+    fn compile_new_object(&mut self, result: &str, class: &str) -> Result<(), String> {
+        let struct_type = self.class_type(class)?;
+        let object = self.builder.build_malloc(struct_type, result)
+            .map_err(|error| format!("Cannot allocate an instance of {}: {}", class, error))?;
+        self.builder.build_store(object, struct_type.const_zero());
+
+        let vtable = self.vtables.get(class).ok_or_else(|| format!("Class {} has no virtual table", class))?;
+        let pointer_type = self.context.i8_type().ptr_type(AddressSpace::default());
+        let vtable_ptr = self.builder.build_struct_gep(object, 0, "vtable_ptr")
+            .map_err(|_| format!("Instances of {} have no virtual table pointer", class))?;
+        self.builder.build_store(vtable_ptr, self.builder.build_pointer_cast(vtable.as_pointer_value(), pointer_type, "vtable"));
+
+        self.store_result(result, object.into());
+        Ok(())
+    }
+
+    /// Compile get field instruction; field `index` follows the virtual table pointer
+    fn compile_get_field(&mut self, result: &str, object: &crate::ir::Value, class: &str, index: usize) -> Result<(), String> {
+        let object = self.object_pointer(object, class)?;
+        let field_ptr = self.builder.build_struct_gep(object, index as u32 + 1, "field_ptr")
+            .map_err(|_| format!("Class {} has no field {}", class, index))?;
+        let value = self.builder.build_load(field_ptr, result);
+        self.store_result(result, value);
+        Ok(())
+    }
+
+    /// Compile set field instruction
+    fn compile_set_field(&mut self, object: &crate::ir::Value, class: &str, index: usize, value: &crate::ir::Value) -> Result<(), String> {
+        let object = self.object_pointer(object, class)?;
+        let field_ptr = self.builder.build_struct_gep(object, index as u32 + 1, "field_ptr")
+            .map_err(|_| format!("Class {} has no field {}", class, index))?;
+        let value = self.convert_value(value)?;
+        self.builder.build_store(field_ptr, value);
+        Ok(())
+    }
+
+    /// Compile virtual call instruction: call the function in slot `slot` of the object's virtual table
+    ///
+    /// Every function in a slot has the type of the one `class` itself puts there, with the instance
+    /// passed first.
+    fn compile_virtual_call(&mut self, result: Option<&str>, object: &crate::ir::Value, class: &str, slot: usize, arguments: &[crate::ir::Value]) -> Result<(), String> {
+        let method = self.class_layouts.get(class)
+            .and_then(|layout| layout.methods.get(slot))
+            .and_then(|(_, function)| self.functions.get(function))
+            .copied()
+            .ok_or_else(|| format!("Class {} has no method in slot {}", class, slot))?;
+        let function_type = method.get_type();
+
+        let object = self.object_pointer(object, class)?;
+        let pointer_type = self.context.i8_type().ptr_type(AddressSpace::default());
+        let vtable_ptr = self.builder.build_struct_gep(object, 0, "vtable_ptr")
+            .map_err(|_| format!("Instances of {} have no virtual table pointer", class))?;
+        let vtable = self.builder.build_load(vtable_ptr, "vtable").into_pointer_value();
+        let slots = self.builder.build_pointer_cast(vtable, pointer_type.ptr_type(AddressSpace::default()), "slots");
+        let slot_ptr = unsafe {
+            self.builder.build_gep(slots, &[self.context.i64_type().const_int(slot as u64, false)], "slot_ptr")
+        };
+        let function = self.builder.build_load(slot_ptr, "method").into_pointer_value();
+        let function = self.builder.build_pointer_cast(function, function_type.ptr_type(AddressSpace::default()), "method");
+        let callee = CallableValue::try_from(function).map_err(|_| format!("Slot {} of {} is not a function", slot, class))?;
+
+        let receiver_type = function_type.get_param_types()[0].into_pointer_type();
+        let mut llvm_arguments = vec![self.builder.build_pointer_cast(object, receiver_type, "ini").into()];
+        for argument in arguments {
+            llvm_arguments.push(self.convert_value(argument)?.into());
+        }
+        let call = self.build_call_site(callee, &llvm_arguments, result.unwrap_or(""))?;
+        if let (Some(result), Some(value)) = (result, call.try_as_basic_value().left()) {
+            self.store_result(result, value);
+        }
+        Ok(())
+    }
+
+    /// Compile interface call instruction: call the function in slot `slot` of the table the object's
+    /// class has for interface `interface`
+    ///
+    /// The table is selected by comparing the object's virtual table with those of the classes
+    /// implementing the interface, all of which the module defines.
+    fn compile_interface_call(&mut self, result: Option<&str>, object: &crate::ir::Value, interface: &str, slot: usize, arguments: &[crate::ir::Value]) -> Result<(), String> {
+        let method_type = self.interface_layouts.get(interface)
+            .and_then(|layout| layout.methods.get(slot))
+            .map(|(_, method_type)| method_type.clone())
+            .ok_or_else(|| format!("Interface {} has no method in slot {}", interface, slot))?;
+        let Type::Function { param_types, return_type } = method_type else {
+            return Err(format!("Slot {} of interface {} is not a method", slot, interface));
+        };
+        // Implementations receive their instance as a pointer to their own class, passed here untyped
+        let pointer_type = self.context.i8_type().ptr_type(AddressSpace::default());
+        let mut llvm_param_types: Vec<inkwell::types::BasicMetadataTypeEnum> = vec![pointer_type.into()];
+        for param_type in &param_types {
+            llvm_param_types.push(self.convert_type(param_type)?.into());
+        }
+        let function_type = if *return_type == Type::Void {
+            self.context.void_type().fn_type(&llvm_param_types, false)
+        } else {
+            self.convert_type(&return_type)?.fn_type(&llvm_param_types, false)
+        };
+
+        let object = self.convert_value(object)?.into_pointer_value();
+        let object = self.builder.build_pointer_cast(object, pointer_type, "object");
+        let vtable_ptr = self.builder.build_pointer_cast(object, pointer_type.ptr_type(AddressSpace::default()), "vtable_ptr");
+        let vtable = self.builder.build_load(vtable_ptr, "vtable").into_pointer_value();
+
+        let slots_type = pointer_type.ptr_type(AddressSpace::default());
+        let mut table = slots_type.const_null();
+        for (class_vtable, itable) in self.interface_tables.get(interface).cloned().unwrap_or_default() {
+            let class_vtable = self.builder.build_pointer_cast(class_vtable.as_pointer_value(), pointer_type, "class_vtable");
+            let is_class = self.builder.build_int_compare(inkwell::IntPredicate::EQ, vtable, class_vtable, "is_class");
+            let itable = self.builder.build_pointer_cast(itable.as_pointer_value(), slots_type, "itable");
+            table = self.builder.build_select(is_class, itable, table, "table").into_pointer_value();
+        }
+        let slot_ptr = unsafe {
+            self.builder.build_gep(table, &[self.context.i64_type().const_int(slot as u64, false)], "slot_ptr")
+        };
+        let function = self.builder.build_load(slot_ptr, "method").into_pointer_value();
+        let function = self.builder.build_pointer_cast(function, function_type.ptr_type(AddressSpace::default()), "method");
+        let callee = CallableValue::try_from(function).map_err(|_| format!("Slot {} of {} is not a function", slot, interface))?;
+
+        let mut llvm_arguments = vec![object.into()];
+        for argument in arguments {
+            llvm_arguments.push(self.convert_value(argument)?.into());
+        }
+        let call = self.build_call_site(callee, &llvm_arguments, result.unwrap_or(""))?;
+        if let (Some(result), Some(value)) = (result, call.try_as_basic_value().left()) {
+            self.store_result(result, value);
+        }
+        Ok(())
+    }
+
+    /// Structs of the header and of variant `variant` of enum `enumeration`
+    fn variant_type(&self, enumeration: &str, variant: usize) -> Result<(StructType<'ctx>, StructType<'ctx>), String> {
+        let (header, variants) = self.enum_types.get(enumeration)
+            .ok_or_else(|| format!("Enum {} was not declared", enumeration))?;
+        let variant_type = variants.get(variant)
+            .ok_or_else(|| format!("Enum {} has no variant {}", enumeration, variant))?;
+        Ok((*header, *variant_type))
+    }
+
+    /// Compile new variant instruction: allocate the struct of the variant, and set its index and fields
+    fn compile_new_variant(&mut self, result: &str, enumeration: &str, variant: usize, fields: &[crate::ir::Value]) -> Result<(), String> {
+        let (header, variant_type) = self.variant_type(enumeration, variant)?;
+        let value = self.builder.build_malloc(variant_type, result)
+            .map_err(|error| format!("Cannot allocate a value of {}: {}", enumeration, error))?;
+        let tag_ptr = self.builder.build_struct_gep(value, 0, "tag_ptr")
+            .map_err(|_| format!("Values of {} have no variant index", enumeration))?;
+        self.builder.build_store(tag_ptr, self.context.i64_type().const_int(variant as u64, false));
+        for (index, field) in fields.iter().enumerate() {
+            let field_ptr = self.builder.build_struct_gep(value, index as u32 + 1, "field_ptr")
+                .map_err(|_| format!("Variant {} of {} has no field {}", variant, enumeration, index))?;
+            let field = self.convert_value(field)?;
+            self.builder.build_store(field_ptr, field);
+        }
+
+        let value = self.builder.build_pointer_cast(value, header.ptr_type(AddressSpace::default()), "variant");
+        self.store_result(result, value.into());
+        Ok(())
+    }
+
+    /// Compile make tuple instruction: tuples are structs of their elements, passed by value
+    fn compile_make_tuple(&mut self, result: &str, elements: &[crate::ir::Value]) -> Result<(), String> {
+        let elements = elements.iter().map(|element| self.convert_value(element)).collect::<Result<Vec<_>, _>>()?;
+        let element_types: Vec<BasicTypeEnum> = elements.iter().map(|element| element.get_type()).collect();
+        let mut tuple = self.context.struct_type(&element_types, false).get_undef().as_aggregate_value_enum();
+        for (index, element) in elements.into_iter().enumerate() {
+            tuple = self.builder.build_insert_value(tuple, element, index as u32, "tuple")
+                .ok_or_else(|| format!("Tuple has no element {}", index))?;
+        }
+        self.store_result(result, tuple.as_basic_value_enum());
+        Ok(())
+    }
+
+    /// Compile pattern match instruction: branch through the decision tree of its cases to the block of
+    /// the case that runs, then continue after the match
+    fn compile_pattern_match(&mut self, expression: &crate::ir::Value, cases: &[MatchCase], default: Option<&[crate::ir::Instruction]>) -> Result<(), String> {
+        let function = self.builder.get_insert_block().and_then(|block| block.get_parent())
+            .ok_or("Pattern match outside of a function")?;
+        let value = self.convert_value(expression)?;
+        let decision_block = self.builder.get_insert_block().ok_or("Pattern match outside of a block")?;
+
+        // Each case body is compiled once, in a block of its own; cases that do not return continue after the match
+        let end_block = self.context.append_basic_block(function, "match.end");
+        let mut case_blocks = Vec::new();
+        for (index, case) in cases.iter().enumerate() {
+            let block = self.context.append_basic_block(function, &format!("match.case{}", index));
+            self.builder.position_at_end(block);
+            for instruction in &case.body {
+                self.compile_instruction(instruction)?;
+            }
+            self.branch_unless_terminated(end_block);
+            case_blocks.push(block);
+        }
+        // Without a default, the analyzer proved that some case matches every value
+        let default_block = self.context.append_basic_block(function, "match.default");
+        self.builder.position_at_end(default_block);
+        match default {
+            Some(default) => {
+                for instruction in default {
+                    self.compile_instruction(instruction)?;
+                }
+                self.branch_unless_terminated(end_block);
+            }
+            None => {
+                self.builder.build_unreachable();
+            }
+        }
+
+        self.builder.position_at_end(decision_block);
+        let decision = crate::ir::decision_tree(cases);
+        self.compile_decision(&decision, value, cases, &case_blocks, default_block)?;
+        self.builder.position_at_end(end_block);
+        Ok(())
+    }
+
+    /// Branch to `target` unless the block being compiled already ends, as one ending with a return does
+    fn branch_unless_terminated(&self, target: inkwell::basic_block::BasicBlock<'ctx>) {
+        if self.builder.get_insert_block().is_some_and(|block| block.get_terminator().is_none()) {
+            self.builder.build_unconditional_branch(target);
+        }
+    }
+
+    /// Compile a node of the decision tree of a pattern match on `value`, in the current block
+    fn compile_decision<C: Case>(
+        &mut self,
+        decision: &Decision,
+        value: BasicValueEnum<'ctx>,
+        cases: &[C],
+        case_blocks: &[inkwell::basic_block::BasicBlock<'ctx>],
+        default_block: inkwell::basic_block::BasicBlock<'ctx>,
+    ) -> Result<(), String> {
+        let function = self.builder.get_insert_block().and_then(|block| block.get_parent())
+            .ok_or("Pattern match outside of a function")?;
+        match decision {
+            Decision::Fail => {
+                self.builder.build_unconditional_branch(default_block);
+            }
+            Decision::Run { case, bindings, otherwise } => {
+                for (variable, access) in bindings {
+                    let part = self.match_part(value, access)?;
+                    let variable = self.variables.get(variable).copied()
+                        .ok_or_else(|| format!("Variable {} not found", variable))?;
+                    self.builder.build_store(variable, part);
+                }
+                match (cases[*case].guard(), otherwise) {
+                    (Some((instructions, condition)), Some(otherwise)) => {
+                        for instruction in instructions {
+                            self.compile_instruction(instruction)?;
+                        }
+                        let condition = self.convert_value(condition)?.into_int_value();
+                        let otherwise_block = self.context.append_basic_block(function, "match.guard_failed");
+                        self.builder.build_conditional_branch(condition, case_blocks[*case], otherwise_block);
+                        self.builder.position_at_end(otherwise_block);
+                        self.compile_decision(otherwise, value, cases, case_blocks, default_block)?;
+                    }
+                    _ => {
+                        self.builder.build_unconditional_branch(case_blocks[*case]);
+                    }
+                }
+            }
+            Decision::Switch { access, enumeration, branches, default } => {
+                let (header, _) = self.variant_type(enumeration, 0)?;
+                let part = self.match_part(value, access)?.into_pointer_value();
+                let part = self.builder.build_pointer_cast(part, header.ptr_type(AddressSpace::default()), "variant");
+                let tag_ptr = self.builder.build_struct_gep(part, 0, "tag_ptr")
+                    .map_err(|_| format!("Values of {} have no variant index", enumeration))?;
+                let tag = self.builder.build_load(tag_ptr, "tag").into_int_value();
+
+                let default_branch = self.context.append_basic_block(function, "match.other");
+                let mut targets = Vec::new();
+                for (variant, _) in branches {
+                    let block = self.context.append_basic_block(function, &format!("match.variant{}", variant));
+                    targets.push((self.context.i64_type().const_int(*variant as u64, false), block));
+                }
+                self.builder.build_switch(tag, default_branch, &targets);
+                for ((_, branch), (_, block)) in branches.iter().zip(&targets) {
+                    self.builder.position_at_end(*block);
+                    self.compile_decision(branch, value, cases, case_blocks, default_block)?;
+                }
+                self.builder.position_at_end(default_branch);
+                self.compile_decision(default, value, cases, case_blocks, default_block)?;
+            }
+            Decision::Test { access, test, then, otherwise } => {
+                let part = self.match_part(value, access)?;
+                let condition = self.compile_test(part, test)?;
+                let then_block = self.context.append_basic_block(function, "match.then");
+                let otherwise_block = self.context.append_basic_block(function, "match.else");
+                self.builder.build_conditional_branch(condition, then_block, otherwise_block);
+                self.builder.position_at_end(then_block);
+                self.compile_decision(then, value, cases, case_blocks, default_block)?;
+                self.builder.position_at_end(otherwise_block);
+                self.compile_decision(otherwise, value, cases, case_blocks, default_block)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Part of the matched value `value` the steps of `access` lead to
+    fn match_part(&self, value: BasicValueEnum<'ctx>, access: &[Step]) -> Result<BasicValueEnum<'ctx>, String> {
+        let mut part = value;
+        for step in access {
+            part = match step {
+                Step::Element(index) => self.builder.build_extract_value(part.into_struct_value(), *index as u32, "element")
+                    .ok_or_else(|| format!("Tuple has no element {}", index))?,
+                Step::Field { enumeration, variant, index } => {
+                    let (_, variant_type) = self.variant_type(enumeration, *variant)?;
+                    let pointer = self.builder.build_pointer_cast(part.into_pointer_value(), variant_type.ptr_type(AddressSpace::default()), "variant");
+                    let field_ptr = self.builder.build_struct_gep(pointer, *index as u32 + 1, "field_ptr")
+                        .map_err(|_| format!("Variant {} of {} has no field {}", variant, enumeration, index))?;
+                    self.builder.build_load(field_ptr, "field")
+                }
+            };
+        }
+        Ok(part)
+    }
+
+    /// Condition that `part` passes `test`
+    fn compile_test(&mut self, part: BasicValueEnum<'ctx>, test: &Test) -> Result<inkwell::values::IntValue<'ctx>, String> {
+        let int_type = self.context.i64_type();
+        Ok(match test {
+            Test::Between(low, high) => {
+                let part = part.into_int_value();
+                let above = self.builder.build_int_compare(IntPredicate::SGE, part, int_type.const_int(*low as u64, true), "above");
+                let below = self.builder.build_int_compare(IntPredicate::SLE, part, int_type.const_int(*high as u64, true), "below");
+                self.builder.build_and(above, below, "between")
+            }
+            // An optional value is `nol` when its flag is not set
+            Test::Equal(crate::ir::Constant::Null) => {
+                let flag = self.builder.build_extract_value(part.into_struct_value(), 0, "flag")
+                    .ok_or("Optional value has no flag")?;
+                self.builder.build_not(flag.into_int_value(), "is_null")
+            }
+            Test::Equal(crate::ir::Constant::String(text)) => {
+                let pointer_type = self.context.i8_type().ptr_type(AddressSpace::default());
+                let strcmp = self.module.get_function("strcmp").unwrap_or_else(|| {
+                    let strcmp_type = self.context.i32_type().fn_type(&[pointer_type.into(), pointer_type.into()], false);
+                    self.module.add_function("strcmp", strcmp_type, None)
+                });
+                let text = self.builder.build_global_string_ptr(text, "pattern").as_pointer_value();
+                let order = self.builder.build_call(strcmp, &[part.into(), text.into()], "order")
+                    .try_as_basic_value().left().ok_or("strcmp returns no value")?;
+                self.builder.build_int_compare(IntPredicate::EQ, order.into_int_value(), self.context.i32_type().const_zero(), "equal")
+            }
+            Test::Equal(constant) => {
+                let constant = self.convert_constant(constant)?;
+                match (part, constant) {
+                    (BasicValueEnum::FloatValue(part), BasicValueEnum::FloatValue(constant)) => {
+                        self.builder.build_float_compare(FloatPredicate::OEQ, part, constant, "equal")
+                    }
+                    (part, constant) => self.builder.build_int_compare(IntPredicate::EQ, part.into_int_value(), constant.into_int_value(), "equal"),
+                }
+            }
+        })
+    }
+
+    /// Compile shared alloca instruction: a variable on the heap, which closures capturing it keep the address of
+    fn compile_alloca_shared(&mut self, result: &str, alloca_type: &Type) -> Result<(), String> {
+        let llvm_type = self.convert_type(alloca_type)?;
+        let variable = self.builder.build_malloc(llvm_type, result)
+            .map_err(|_| format!("Cannot allocate shared variable {}", result))?;
+        self.variables.insert(result.to_string(), variable);
+        Ok(())
+    }
+
+    /// Compile make closure instruction: fill a new environment with the captured variables, and pair it
+    /// with the closure's function as `{ function, environment }`
+    fn compile_make_closure(&mut self, result: &str, function: &str, captures: &[String]) -> Result<(), String> {
+        let layout = self.closure_layouts.get(function).cloned()
+            .ok_or_else(|| format!("Closure {} was not declared", function))?;
+        let environment_type = self.closure_types.get(&format!("{}.$env", function)).copied()
+            .ok_or_else(|| format!("Closure {} has no environment", function))?;
+        let llvm_function = self.functions.get(function).copied()
+            .ok_or_else(|| format!("Function {} not found", function))?;
+
+        let environment = self.builder.build_malloc(environment_type, "env")
+            .map_err(|_| format!("Cannot allocate the environment of {}", function))?;
+        for (index, (name, (_, _, capture))) in captures.iter().zip(&layout.captures).enumerate() {
+            let field = self.builder.build_struct_gep(environment, index as u32, name)
+                .map_err(|_| format!("Environment of {} has no capture {}", function, index))?;
+            let value = match capture {
+                Capture::ByValue => self.convert_value(&crate::ir::Value::Variable(name.clone()))?,
+                Capture::ByReference => self.variables.get(name).copied()
+                    .ok_or_else(|| format!("Variable {} not found", name))?
+                    .into(),
+            };
+            self.builder.build_store(field, value);
+        }
+
+        let pointer_type = self.context.i8_type().ptr_type(AddressSpace::default());
+        let closure_type = self.context.struct_type(&[pointer_type.into(), pointer_type.into()], false);
+        let function_ptr = self.builder.build_pointer_cast(llvm_function.as_global_value().as_pointer_value(), pointer_type, "function");
+        let environment = self.builder.build_pointer_cast(environment, pointer_type, "env");
+        let closure = self.builder.build_insert_value(closure_type.get_undef(), function_ptr, 0, "closure")
+            .and_then(|closure| self.builder.build_insert_value(closure, environment, 1, result))
+            .ok_or_else(|| format!("Cannot build closure {}", result))?;
+        self.store_result(result, closure.into_struct_value().into());
+        Ok(())
+    }
+
+    /// Compile bind capture instruction: read capture `index` from the environment the closure's function
+    /// received as its first parameter
+    fn compile_bind_capture(&mut self, variable: &str, function: &str, index: usize) -> Result<(), String> {
+        let capture = self.closure_layouts.get(function)
+            .and_then(|layout| layout.captures.get(index))
+            .map(|(_, _, capture)| *capture)
+            .ok_or_else(|| format!("Closure {} has no capture {}", function, index))?;
+        let environment = self.functions.get(function)
+            .and_then(|llvm_function| llvm_function.get_first_param())
+            .ok_or_else(|| format!("Function {} receives no environment", function))?
+            .into_pointer_value();
+        let field = self.builder.build_struct_gep(environment, index as u32, variable)
+            .map_err(|_| format!("Environment of {} has no capture {}", function, index))?;
+        let value = self.builder.build_load(field, variable);
+        match capture {
+            // The function works on its own copy of a value captured by value
+            Capture::ByValue => self.store_result(variable, value),
+            // and on the variable itself when it is shared
+            Capture::ByReference => {
+                self.variables.insert(variable.to_string(), value.into_pointer_value());
+            }
+        }
+        Ok(())
+    }
+
+    /// Compile closure call instruction: call the function of a closure, passing its environment first
+    fn compile_closure_call(&mut self, result: Option<&str>, closure: &crate::ir::Value, function_type: &Type, arguments: &[crate::ir::Value]) -> Result<(), String> {
+        let closure = self.convert_value(closure)?;
+        let arguments = arguments.iter().map(|argument| self.convert_value(argument)).collect::<Result<Vec<_>, _>>()?;
+        let value = self.call_closure(closure, function_type, &arguments, result.unwrap_or(""))?;
+        if let (Some(result), Some(value)) = (result, value) {
+            self.store_result(result, value);
+        }
+        Ok(())
+    }
+
+    /// Call the function of `closure`, of type `function_type`, with its environment and `arguments`
+    fn call_closure(&mut self, closure: BasicValueEnum<'ctx>, function_type: &Type, arguments: &[BasicValueEnum<'ctx>], name: &str) -> Result<Option<BasicValueEnum<'ctx>>, String> {
+        let Type::Function { param_types, return_type } = function_type else {
+            return Err(format!("A value of type {:?} cannot be called", function_type));
+        };
+        // Functions receive their environment as a pointer to their own struct, passed here untyped
+        let pointer_type = self.context.i8_type().ptr_type(AddressSpace::default());
+        let mut llvm_param_types: Vec<inkwell::types::BasicMetadataTypeEnum> = vec![pointer_type.into()];
+        for param_type in param_types {
+            llvm_param_types.push(self.convert_type(param_type)?.into());
+        }
+        let llvm_function_type = if **return_type == Type::Void {
+            self.context.void_type().fn_type(&llvm_param_types, false)
+        } else {
+            self.convert_type(return_type)?.fn_type(&llvm_param_types, false)
+        };
+
+        let closure = closure.into_struct_value();
+        let function = self.builder.build_extract_value(closure, 0, "function")
+            .ok_or("A closure holds its function first")?
+            .into_pointer_value();
+        let environment = self.builder.build_extract_value(closure, 1, "env")
+            .ok_or("A closure holds its environment second")?;
+        let function = self.builder.build_pointer_cast(function, llvm_function_type.ptr_type(AddressSpace::default()), "function");
+        let callee = CallableValue::try_from(function).map_err(|_| "A closure does not hold a function".to_string())?;
+
+        let mut llvm_arguments = vec![environment.into()];
+        for argument in arguments {
+            llvm_arguments.push((*argument).into());
+        }
+        Ok(self.build_call_site(callee, &llvm_arguments, name)?.try_as_basic_value().left())
+    }
+
+    /// Compile pipeline instruction: run the list through each stage in turn. A loop stage walks the list
+    /// once, applying its filters and maps to each element and collecting those that pass into a new list.
+    /// Lists are handled through the runtime, which holds each element in a slot of the list's element size
+    fn compile_pipeline(&mut self, result: &str, initial: &crate::ir::Value, element_type: &Type, stages: &[Stage]) -> Result<(), String> {
+        let pointer_type = self.context.i8_type().ptr_type(AddressSpace::default());
+        let list_type = self.convert_type(&Type::Array { element_type: Box::new(element_type.clone()) })?;
+        let initial = self.convert_value(initial)?.into_pointer_value();
+        let mut list = self.builder.build_pointer_cast(initial, pointer_type, "list");
+        let mut element_type = element_type.clone();
+
+        for stage in stages {
+            match stage {
+                Stage::Loop(steps) => {
+                    let output_type = steps.iter().fold(&element_type, |ty, step| match step {
+                        LoopStep::Map { element_type, .. } => element_type,
+                        LoopStep::Filter(_) => ty,
+                    }).clone();
+                    let output = self.new_list(&output_type)?;
+                    let start = self.context.i64_type().const_zero();
+                    self.compile_list_loop(list, start, "loop", |backend, index, next_block| {
+                        let mut element = backend.list_element(list, index, &element_type)?;
+                        let mut ty = element_type.clone();
+                        for step in steps {
+                            match step {
+                                LoopStep::Filter(predicate) => {
+                                    let predicate = backend.convert_value(predicate)?;
+                                    let function_type = Type::Function { param_types: vec![ty.clone()], return_type: Box::new(Type::Bool) };
+                                    let passes = backend.call_closure(predicate, &function_type, &[element], "passes")?
+                                        .ok_or("A filter returns no value")?;
+                                    let function = backend.current_function()?;
+                                    let passed_block = backend.context.append_basic_block(function, "loop.passed");
+                                    backend.builder.build_conditional_branch(passes.into_int_value(), passed_block, next_block);
+                                    backend.builder.position_at_end(passed_block);
+                                }
+                                LoopStep::Map { function, element_type } => {
+                                    let function = backend.convert_value(function)?;
+                                    let function_type = Type::Function { param_types: vec![ty], return_type: Box::new(element_type.clone()) };
+                                    element = backend.call_closure(function, &function_type, &[element], "mapped")?
+                                        .ok_or("A map returns no value")?;
+                                    ty = element_type.clone();
+                                }
+                            }
+                        }
+                        backend.list_push(output, element)
+                    })?;
+                    list = output;
+                    element_type = output_type;
+                }
+                Stage::Take(count) => {
+                    let count = self.convert_value(count)?;
+                    let start = self.context.i64_type().const_zero();
+                    list = self.list_slice(list, start, count.into_int_value())?;
+                }
+                Stage::Skip(count) => {
+                    let count = self.convert_value(count)?;
+                    let length = self.list_length(list)?;
+                    list = self.list_slice(list, count.into_int_value(), length)?;
+                }
+                Stage::Sort(comparator) => {
+                    let start = self.context.i64_type().const_zero();
+                    let length = self.list_length(list)?;
+                    list = self.list_slice(list, start, length)?;
+                    let comparator = comparator.as_ref().map(|comparator| self.convert_value(comparator)).transpose()?;
+                    self.compile_insertion_sort(list, &element_type, comparator)?;
+                }
+                Stage::Reduce { function, initial, result_type } => {
+                    let function = self.convert_value(function)?;
+                    let function_type = Type::Function {
+                        param_types: vec![result_type.clone(), element_type.clone()],
+                        return_type: Box::new(result_type.clone()),
+                    };
+                    // Without an initial value, the reduction starts from the first element
+                    let (accumulated, start) = match initial {
+                        Some(initial) => (self.convert_value(initial)?, 0),
+                        None => (self.list_element(list, self.context.i64_type().const_zero(), &element_type)?, 1),
+                    };
+                    let accumulator = self.builder.build_alloca(accumulated.get_type(), "accumulator");
+                    self.builder.build_store(accumulator, accumulated);
+                    let start = self.context.i64_type().const_int(start, false);
+                    self.compile_list_loop(list, start, "reduce", |backend, index, _| {
+                        let element = backend.list_element(list, index, &element_type)?;
+                        let accumulated = backend.builder.build_load(accumulator, "accumulated");
+                        let accumulated = backend.call_closure(function, &function_type, &[accumulated, element], "reduced")?
+                            .ok_or("A reduction returns no value")?;
+                        backend.builder.build_store(accumulator, accumulated);
+                        Ok(())
+                    })?;
+                    // A reduction ends its pipeline
+                    let value = self.builder.build_load(accumulator, result);
+                    self.store_result(result, value);
+                    return Ok(());
+                }
+            }
+        }
+
+        let list = self.builder.build_pointer_cast(list, list_type.into_pointer_type(), result);
+        self.store_result(result, list.into());
+        Ok(())
+    }
+
+    /// Sort `list` in place, comparing its elements with `comparator` when given and by their natural order otherwise
+    fn compile_insertion_sort(&mut self, list: PointerValue<'ctx>, element_type: &Type, comparator: Option<BasicValueEnum<'ctx>>) -> Result<(), String> {
+        let function = self.current_function()?;
+        let int_type = self.context.i64_type();
+        let position = self.builder.build_alloca(int_type, "sort.position");
+        self.compile_list_loop(list, int_type.const_int(1, false), "sort", |backend, index, next_block| {
+            // Move the element at `index` back until the one before it is not greater
+            backend.builder.build_store(position, index);
+            let check_block = backend.context.append_basic_block(function, "sort.check");
+            let compare_block = backend.context.append_basic_block(function, "sort.compare");
+            let swap_block = backend.context.append_basic_block(function, "sort.swap");
+            backend.builder.build_unconditional_branch(check_block);
+
+            backend.builder.position_at_end(check_block);
+            let current = backend.builder.build_load(position, "current").into_int_value();
+            let more = backend.builder.build_int_compare(IntPredicate::SGT, current, int_type.const_zero(), "more");
+            backend.builder.build_conditional_branch(more, compare_block, next_block);
+
+            backend.builder.position_at_end(compare_block);
+            let previous = backend.builder.build_int_sub(current, int_type.const_int(1, false), "previous");
+            let left = backend.list_element(list, previous, element_type)?;
+            let right = backend.list_element(list, current, element_type)?;
+            let greater = match comparator {
+                Some(comparator) => {
+                    let function_type = Type::Function {
+                        param_types: vec![element_type.clone(), element_type.clone()],
+                        return_type: Box::new(Type::Int),
+                    };
+                    let order = backend.call_closure(comparator, &function_type, &[left, right], "order")?
+                        .ok_or("A comparison returns no value")?;
+                    backend.builder.build_int_compare(IntPredicate::SGT, order.into_int_value(), int_type.const_zero(), "greater")
+                }
+                None => backend.compile_greater(left, right, element_type)?,
+            };
+            backend.builder.build_conditional_branch(greater, swap_block, next_block);
+
+            backend.builder.position_at_end(swap_block);
+            let swap = backend.runtime_function("kodeon_list_swap", None, &[backend.untyped_pointer(), int_type.into(), int_type.into()]);
+            backend.builder.build_call(swap, &[list.into(), previous.into(), current.into()], "");
+            backend.builder.build_store(position, previous);
+            backend.builder.build_unconditional_branch(check_block);
+            Ok(())
+        })
+    }
+
+    /// Condition that `left` comes after `right` in the natural order of `element_type`
+    fn compile_greater(&mut self, left: BasicValueEnum<'ctx>, right: BasicValueEnum<'ctx>, element_type: &Type) -> Result<IntValue<'ctx>, String> {
+        Ok(match element_type {
+            Type::Float => self.builder.build_float_compare(FloatPredicate::OGT, left.into_float_value(), right.into_float_value(), "greater"),
+            Type::String => {
+                let strcmp = self.runtime_function("strcmp", Some(self.context.i32_type().into()), &[self.untyped_pointer(), self.untyped_pointer()]);
+                let order = self.builder.build_call(strcmp, &[left.into(), right.into()], "order")
+                    .try_as_basic_value().left().ok_or("strcmp returns no value")?;
+                self.builder.build_int_compare(IntPredicate::SGT, order.into_int_value(), self.context.i32_type().const_zero(), "greater")
+            }
+            Type::Int | Type::Bool => self.builder.build_int_compare(IntPredicate::SGT, left.into_int_value(), right.into_int_value(), "greater"),
+            _ => return Err(format!("Values of type {:?} have no natural order", element_type)),
+        })
+    }
+
+    /// Run `body` for each index of `list` from `start`, in the current function. `body` is given the index and
+    /// the block that moves on to the next one, and falls through to it unless it branches elsewhere
+    fn compile_list_loop(
+        &mut self,
+        list: PointerValue<'ctx>,
+        start: IntValue<'ctx>,
+        name: &str,
+        body: impl FnOnce(&mut Self, IntValue<'ctx>, inkwell::basic_block::BasicBlock<'ctx>) -> Result<(), String>,
+    ) -> Result<(), String> {
+        let function = self.current_function()?;
+        let int_type = self.context.i64_type();
+        let index = self.builder.build_alloca(int_type, &format!("{}.index", name));
+        self.builder.build_store(index, start);
+        let length = self.list_length(list)?;
+
+        let condition_block = self.context.append_basic_block(function, &format!("{}.condition", name));
+        let body_block = self.context.append_basic_block(function, &format!("{}.body", name));
+        let next_block = self.context.append_basic_block(function, &format!("{}.next", name));
+        let end_block = self.context.append_basic_block(function, &format!("{}.end", name));
+        self.builder.build_unconditional_branch(condition_block);
+
+        self.builder.position_at_end(condition_block);
+        let current = self.builder.build_load(index, "index").into_int_value();
+        let more = self.builder.build_int_compare(IntPredicate::SLT, current, length, "more");
+        self.builder.build_conditional_branch(more, body_block, end_block);
+
+        self.builder.position_at_end(body_block);
+        body(self, current, next_block)?;
+        self.branch_unless_terminated(next_block);
+
+        self.builder.position_at_end(next_block);
+        let following = self.builder.build_int_add(current, int_type.const_int(1, false), "following");
+        self.builder.build_store(index, following);
+        self.builder.build_unconditional_branch(condition_block);
+
+        self.builder.position_at_end(end_block);
+        Ok(())
+    }
+
+    /// Function the builder is inserting into
+    fn current_function(&self) -> Result<FunctionValue<'ctx>, String> {
+        self.builder.get_insert_block().and_then(|block| block.get_parent())
+            .ok_or_else(|| "Instruction outside of a function".to_string())
+    }
+
+    /// Untyped pointer, which the runtime takes lists and their elements as
+    fn untyped_pointer(&self) -> inkwell::types::BasicMetadataTypeEnum<'ctx> {
+        self.context.i8_type().ptr_type(AddressSpace::default()).into()
+    }
+
+    /// Runtime function `name`, declared the first time it is used
+    fn runtime_function(&self, name: &str, return_type: Option<BasicTypeEnum<'ctx>>, param_types: &[inkwell::types::BasicMetadataTypeEnum<'ctx>]) -> FunctionValue<'ctx> {
+        self.module.get_function(name).unwrap_or_else(|| {
+            let function_type = match return_type {
+                Some(return_type) => return_type.fn_type(param_types, false),
+                None => self.context.void_type().fn_type(param_types, false),
+            };
+            self.module.add_function(name, function_type, None)
+        })
+    }
+
+    /// A new empty list holding elements of `element_type`
+    fn new_list(&mut self, element_type: &Type) -> Result<PointerValue<'ctx>, String> {
+        let element_size = self.convert_type(element_type)?.size_of().ok_or_else(|| format!("Type {:?} has no size", element_type))?;
+        let new = self.runtime_function("kodeon_list_new", Some(self.context.i8_type().ptr_type(AddressSpace::default()).into()), &[self.context.i64_type().into()]);
+        self.call_runtime(new, &[element_size.into()], "list")
+    }
+
+    /// Number of elements of `list`
+    fn list_length(&mut self, list: PointerValue<'ctx>) -> Result<IntValue<'ctx>, String> {
+        let length = self.runtime_function("kodeon_list_length", Some(self.context.i64_type().into()), &[self.untyped_pointer()]);
+        Ok(self.builder.build_call(length, &[list.into()], "length")
+            .try_as_basic_value().left().ok_or("kodeon_list_length returns no value")?.into_int_value())
+    }
+
+    /// Element `index` of `list`, which holds elements of `element_type`
+    fn list_element(&mut self, list: PointerValue<'ctx>, index: IntValue<'ctx>, element_type: &Type) -> Result<BasicValueEnum<'ctx>, String> {
+        let at = self.runtime_function("kodeon_list_at", Some(self.context.i8_type().ptr_type(AddressSpace::default()).into()), &[self.untyped_pointer(), self.context.i64_type().into()]);
+        let slot = self.call_runtime(at, &[list.into(), index.into()], "slot")?;
+        let slot_type = self.convert_type(element_type)?.ptr_type(AddressSpace::default());
+        let slot = self.builder.build_pointer_cast(slot, slot_type, "slot");
+        Ok(self.builder.build_load(slot, "element"))
+    }
+
+    /// Append `element` to `list`; the runtime copies it from a slot on the stack
+    fn list_push(&mut self, list: PointerValue<'ctx>, element: BasicValueEnum<'ctx>) -> Result<(), String> {
+        let slot = self.builder.build_alloca(element.get_type(), "slot");
+        self.builder.build_store(slot, element);
+        let slot = self.builder.build_pointer_cast(slot, self.context.i8_type().ptr_type(AddressSpace::default()), "slot");
+        let push = self.runtime_function("kodeon_list_push", None, &[self.untyped_pointer(), self.untyped_pointer()]);
+        self.builder.build_call(push, &[list.into(), slot.into()], "");
+        Ok(())
+    }
+
+    /// New list with the elements of `list` from `start` up to `end`, both clamped to its length
+    fn list_slice(&mut self, list: PointerValue<'ctx>, start: IntValue<'ctx>, end: IntValue<'ctx>) -> Result<PointerValue<'ctx>, String> {
+        let int_type = self.context.i64_type().into();
+        let slice = self.runtime_function("kodeon_list_slice", Some(self.context.i8_type().ptr_type(AddressSpace::default()).into()), &[self.untyped_pointer(), int_type, int_type]);
+        self.call_runtime(slice, &[list.into(), start.into(), end.into()], "slice")
+    }
+
+    /// Call runtime function `function`, which returns a pointer
+    fn call_runtime(&mut self, function: FunctionValue<'ctx>, arguments: &[inkwell::values::BasicMetadataValueEnum<'ctx>], name: &str) -> Result<PointerValue<'ctx>, String> {
+        Ok(self.builder.build_call(function, arguments, name)
+            .try_as_basic_value().left()
+            .ok_or_else(|| format!("{} returns no value", function.get_name().to_string_lossy()))?
+            .into_pointer_value())
+    }
+
+    /// Call `callee`; inside an invoke, as an invoke that unwinds to its landing pad and continues in a new block
+    fn build_call_site(&mut self, callee: CallableValue<'ctx>, arguments: &[BasicValueEnum<'ctx>], name: &str) -> Result<CallSiteValue<'ctx>, String> {
+        let Some(unwind) = self.unwind_block else {
+            let arguments: Vec<BasicMetadataValueEnum> = arguments.iter().map(|argument| (*argument).into()).collect();
+            return Ok(self.builder.build_call(callee, &arguments, name));
+        };
+        let normal = self.context.append_basic_block(self.current_function()?, "invoke.cont");
+        let call = self.builder.build_invoke(callee, arguments, normal, unwind, name);
+        self.builder.position_at_end(normal);
+        Ok(call)
+    }
+
+    /// Block `name` of the function being compiled
+    fn block(&self, name: &str) -> Result<BasicBlock<'ctx>, String> {
+        self.blocks.get(name).copied().ok_or_else(|| format!("Block {} does not exist", name))
+    }
+
+    /// Compile a throw: hand the value, and the virtual table of its class for an instance, to the runtime,
+    /// which unwinds to `unwind` if given or out of the function. Texts are thrown without a virtual table
+    fn compile_throw(&mut self, exception: &crate::ir::Value, exception_type: &Type, unwind: Option<BasicBlock<'ctx>>) -> Result<(), String> {
+        let pointer_type = self.context.i8_type().ptr_type(AddressSpace::default());
+        let BasicValueEnum::PointerValue(object) = self.convert_value(exception)? else {
+            return Err(format!("A value of type {:?} cannot be thrown", exception_type));
+        };
+        let vtable = match exception_type {
+            Type::Object { name } if self.class_layouts.contains_key(name) => {
+                let object = self.object_pointer(exception, name)?;
+                let vtable_ptr = self.builder.build_struct_gep(object, 0, "vtable_ptr")
+                    .map_err(|_| format!("Instances of {} have no virtual table pointer", name))?;
+                let vtable = self.builder.build_load(vtable_ptr, "vtable").into_pointer_value();
+                self.builder.build_pointer_cast(vtable, pointer_type, "vtable")
+            }
+            _ => pointer_type.const_null(),
+        };
+        let object = self.builder.build_pointer_cast(object, pointer_type, "exception");
+        let throw = self.runtime_function("kodeon_throw", None, &[self.untyped_pointer(), self.untyped_pointer()]);
+        self.build_unwinding_call(throw, &[object.into(), vtable.into()], unwind)
+    }
+
+    /// Call runtime function `function`, which never returns, as an invoke when `unwind` is given
+    fn build_unwinding_call(&mut self, function: FunctionValue<'ctx>, arguments: &[BasicValueEnum<'ctx>], unwind: Option<BasicBlock<'ctx>>) -> Result<(), String> {
+        let enclosing = std::mem::replace(&mut self.unwind_block, unwind);
+        let call = self.build_call_site(function.into(), arguments, "");
+        self.unwind_block = enclosing;
+        call?;
+        self.builder.build_unreachable();
+        Ok(())
+    }
+
+    /// Compile landing pad instruction: take the error being thrown. Every pad catches all errors, so that
+    /// the runtime stops at the innermost one; the clauses then test its class, and resume unwinding when
+    /// none of them matches
+    fn compile_landing_pad(&mut self, result: &str) -> Result<(), String> {
+        let pointer_type = self.context.i8_type().ptr_type(AddressSpace::default());
+        let i32_type = self.context.i32_type();
+        let pad_type = self.context.struct_type(&[pointer_type.into(), i32_type.into()], false);
+        let personality = self.runtime_function("kodeon_personality", Some(i32_type.into()), &[
+            i32_type.into(),
+            i32_type.into(),
+            self.context.i64_type().into(),
+            self.untyped_pointer(),
+            self.untyped_pointer(),
+        ]);
+        let pad = self.builder.build_landing_pad(pad_type, personality, &[pointer_type.const_null().into()], false, "pad");
+        let exception = self.builder.build_extract_value(pad.into_struct_value(), 0, "exception")
+            .ok_or("A landing pad holds the error first")?;
+        self.store_result(result, exception);
+        Ok(())
+    }
+
+    /// Compile exception test instruction: compare the virtual table the error was thrown with against
+    /// those of `class` and of every class extending it
+    fn compile_exception_is(&mut self, result: &str, exception: &crate::ir::Value, class: &str) -> Result<(), String> {
+        let pointer_type = self.context.i8_type().ptr_type(AddressSpace::default());
+        let exception = self.convert_value(exception)?;
+        let vtable_of = self.runtime_function("kodeon_exception_vtable", Some(pointer_type.into()), &[self.untyped_pointer()]);
+        let vtable = self.call_runtime(vtable_of, &[exception.into()], "thrown_vtable")?;
+
+        let mut matches = self.context.bool_type().const_zero();
+        for descendant in self.descendants(class) {
+            let class_vtable = self.vtables.get(&descendant).ok_or_else(|| format!("Class {} has no virtual table", descendant))?;
+            let class_vtable = self.builder.build_pointer_cast(class_vtable.as_pointer_value(), pointer_type, "class_vtable");
+            let is_class = self.builder.build_int_compare(IntPredicate::EQ, vtable, class_vtable, "is_class");
+            matches = self.builder.build_or(matches, is_class, "matches");
+        }
+        self.store_result(result, matches.into());
+        Ok(())
+    }
+
+    /// Class `class` and the classes extending it, directly or not
+    fn descendants(&self, class: &str) -> Vec<String> {
+        let mut classes = vec![class.to_string()];
+        let mut index = 0;
+        while index < classes.len() {
+            let mut children: Vec<String> = self.class_layouts.values()
+                .filter(|layout| layout.parent.as_deref() == Some(classes[index].as_str()))
+                .map(|layout| layout.name.clone())
+                .collect();
+            children.sort();
+            classes.extend(children);
+            index += 1;
+        }
+        classes
+    }
+
+    /// Compile catch instruction: the runtime ends the unwinding and returns the value that was thrown
+    fn compile_catch(&mut self, result: &str, exception: &crate::ir::Value) -> Result<(), String> {
+        let exception = self.convert_value(exception)?;
+        let catch = self.runtime_function("kodeon_catch", Some(self.context.i8_type().ptr_type(AddressSpace::default()).into()), &[self.untyped_pointer()]);
+        let value = self.call_runtime(catch, &[exception.into()], "caught")?;
+        self.store_result(result, value.into());
+        Ok(())
+    }
+
+    /// Compile a test of whether an optional, a struct of a flag and the value, is `nol`
+    fn compile_null_check(&mut self, result: &str, value: &crate::ir::Value) -> Result<(), String> {
+        let optional = self.convert_value(value)?.into_struct_value();
+        let flag = self.builder.build_extract_value(optional, 0, "flag")
+            .ok_or("Optional value has no flag")?;
+        let is_null = self.builder.build_not(flag.into_int_value(), "is_null");
+        self.store_result(result, is_null.into());
+        Ok(())
+    }
+
+    /// Compile the read of the value an optional holds
+    fn compile_unwrap(&mut self, result: &str, value: &crate::ir::Value) -> Result<(), String> {
+        let optional = self.convert_value(value)?.into_struct_value();
+        let held = self.builder.build_extract_value(optional, 1, "held")
+            .ok_or("Optional value has no value")?;
+        self.store_result(result, held);
+        Ok(())
+    }
+
     /// Compile make channel instruction
     fn compile_make_channel(&mut self, result: &str, channel_type: &crate::ir::Type) -> Result<(), String> {
         // For now, we'll create a simple pointer to represent the channel
@@ -601,20 +1921,50 @@ impl<'ctx> LLVMBackend<'ctx> {
     fn compile_terminator(&mut self, terminator: &crate::ir::Terminator) -> Result<(), String> {
         match terminator {
             crate::ir::Terminator::Return { value } => {
-                if let Some(val) = value {
-                    let llvm_value = self.convert_value(val)?;
-                    self.builder.build_return(Some(&llvm_value));
-                } else {
-                    self.builder.build_return(None);
-                }
-            }
-            crate::ir::Terminator::Branch { target: _target } => {
-                // For now, we'll just return void
-                self.builder.build_return(None);
-            }
-            crate::ir::Terminator::ConditionalBranch { condition: _condition, then_target: _then_target, else_target: _else_target } => {
-                // For now, we'll just return void
-                self.builder.build_return(None);
+                self.compile_return(value.as_ref())?;
+            }
+            crate::ir::Terminator::Branch { target } => {
+                self.builder.build_unconditional_branch(self.block(target)?);
+            }
+            crate::ir::Terminator::ConditionalBranch { condition, then_target, else_target } => {
+                let condition = self.convert_value(condition)?.into_int_value();
+                self.builder.build_conditional_branch(condition, self.block(then_target)?, self.block(else_target)?);
+            }
+            crate::ir::Terminator::Invoke { call, normal, unwind } => {
+                // Calls made while compiling the instruction become invokes of the landing pad
+                self.unwind_block = Some(self.block(unwind)?);
+                let compiled = self.compile_instruction(call);
+                self.unwind_block = None;
+                compiled?;
+                self.builder.build_unconditional_branch(self.block(normal)?);
+            }
+            crate::ir::Terminator::Unwind { exception, exception_type, unwind } => {
+                let unwind = unwind.as_deref().map(|unwind| self.block(unwind)).transpose()?;
+                self.compile_throw(exception, exception_type, unwind)?;
+            }
+            crate::ir::Terminator::Resume { exception, unwind } => {
+                let unwind = unwind.as_deref().map(|unwind| self.block(unwind)).transpose()?;
+                let exception = self.convert_value(exception)?;
+                let resume = self.runtime_function("kodeon_resume", None, &[self.untyped_pointer()]);
+                self.build_unwinding_call(resume, &[exception], unwind)?;
+            }
+            crate::ir::Terminator::Match { expression, arms, default } => {
+                let value = self.convert_value(expression)?;
+                let case_blocks = arms.iter().map(|arm| self.block(&arm.target)).collect::<Result<Vec<_>, _>>()?;
+                let default_block = match default {
+                    Some(default) => self.block(default)?,
+                    // Without a default, the analyzer proved that some case matches every value
+                    None => {
+                        let decision_block = self.builder.get_insert_block().ok_or("Pattern match outside of a block")?;
+                        let default_block = self.context.append_basic_block(self.current_function()?, "match.default");
+                        self.builder.position_at_end(default_block);
+                        self.builder.build_unreachable();
+                        self.builder.position_at_end(decision_block);
+                        default_block
+                    }
+                };
+                let decision = crate::ir::decision_tree(arms.as_slice());
+                self.compile_decision(&decision, value, arms.as_slice(), &case_blocks, default_block)?;
             }
         }
 
@@ -660,27 +2010,28 @@ impl<'ctx> LLVMBackend<'ctx> {
                 let elem_llvm_type = self.convert_type(element_type)?;
                 Ok(elem_llvm_type.ptr_type(AddressSpace::default()).into())
             }
-            Type::Object { .. } => {
-                // Objects are pointers in LLVM
-                Ok(self.context.i8_type().ptr_type(AddressSpace::default()).into())
-            }
-            Type::Function { param_types, return_type } => {
-                // Function types need to be converted recursively
-                let param_llvm_types: Vec<BasicTypeEnum> = param_types
-                    .iter()
-                    .map(|ty| self.convert_type(ty))
-                    .collect::<Result<Vec<_>, _>>()?;
-
-                let return_llvm_type = if **return_type == Type::Void {
-                    None
-                } else {
-                    Some(self.convert_type(return_type)?)
-                };
-
-                match return_llvm_type {
-                    Some(ret_ty) => Ok(ret_ty.fn_type(&param_llvm_types, false).ptr_type(AddressSpace::default()).into()),
-                    None => Ok(self.context.void_type().fn_type(&param_llvm_types, false).ptr_type(AddressSpace::default()).into()),
+            Type::Object { name } => {
+                // Instances of classes point to their struct, values of enums to their header; other objects are opaque pointers
+                if let Some((header, _)) = self.enum_types.get(name) {
+                    return Ok(header.ptr_type(AddressSpace::default()).into());
                 }
+                // The environment a closure's function receives
+                if let Some(environment) = self.closure_types.get(name) {
+                    return Ok(environment.ptr_type(AddressSpace::default()).into());
+                }
+                match self.class_types.get(name) {
+                    Some(struct_type) => Ok(struct_type.ptr_type(AddressSpace::default()).into()),
+                    None => Ok(self.context.i8_type().ptr_type(AddressSpace::default()).into()),
+                }
+            }
+            Type::Tuple { element_types } => {
+                let element_types = element_types.iter().map(|ty| self.convert_type(ty)).collect::<Result<Vec<_>, _>>()?;
+                Ok(self.context.struct_type(&element_types, false).into())
+            }
+            Type::Function { .. } => {
+                // Function values are closures: their function, and the environment passed to it first
+                let pointer_type = self.context.i8_type().ptr_type(AddressSpace::default());
+                Ok(self.context.struct_type(&[pointer_type.into(), pointer_type.into()], false).into())
             }
             // Go-style concurrency types
             Type::Channel { element_type } => {
@@ -702,7 +2053,7 @@ impl<'ctx> LLVMBackend<'ctx> {
             }
             // Rust-style types
             Type::Trait { .. } => {
-                // Traits are represented as pointers to vtables
+                // Interface values are pointers to instances, whose first field points to their virtual table
                 Ok(self.context.i8_type().ptr_type(AddressSpace::default()).into())
             }
             // Kotlin-style types
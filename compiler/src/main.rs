@@ -2,70 +2,106 @@
 
 use std::env;
 use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::Path;
 use std::process;
-use kodeon_compiler::lexer::Lexer;
+use kodeon_compiler::diagnostics::{Diagnostic, DiagnosticRenderer, ErrorFormat, SourceMap};
+use kodeon_compiler::error_messages::{ErrorMessages, Language};
 use kodeon_compiler::parser::Parser;
 use kodeon_compiler::semantic_analyzer::SemanticAnalyzer;
 use kodeon_compiler::ir::{IRGenerator, print_ir};
 use kodeon_compiler::llvm_backend::LLVMBackend;
-use kodeon_compiler::debugger::{Debugger, create_debugger};
+use kodeon_compiler::optimizer::Optimizer;
+use kodeon_compiler::debugger::create_debugger;
+use kodeon_compiler::doc_generator::{DocError, DocFormat, DocGenerator};
 use inkwell::context::Context;
 
 fn main() {
     let args: Vec<String> = env::args().collect();
 
     if args.len() < 2 {
-        eprintln!("Usage: {} <input_file> [--debug]", args[0]);
+        eprintln!("Usage: {} <input_file> [--debug] [--error-format=human|json] [--lang=en|id]", args[0]);
+        eprintln!("       {} doc <package_dir> [--format markdown|html] [--output <dir>]", args[0]);
+        eprintln!("       {} explain <code> [--lang=en|id]", args[0]);
         process::exit(1);
     }
 
+    let language = match args.iter().find_map(|arg| arg.strip_prefix("--lang=")) {
+        Some(tag) => Language::from_tag(tag).unwrap_or_else(|| {
+            eprintln!("Unknown language '{}', expected en or id", tag);
+            process::exit(1);
+        }),
+        None => Language::from_env(),
+    };
+
+    if args[1] == "doc" {
+        generate_docs(&args, language);
+        return;
+    }
+
+    if args[1] == "explain" {
+        explain(&args, language);
+        return;
+    }
+
     let input_file = &args[1];
     let debug_mode = args.contains(&"--debug".to_string());
+    let error_format = match args.iter().find_map(|arg| arg.strip_prefix("--error-format=")) {
+        Some(name) => ErrorFormat::from_name(name).unwrap_or_else(|| {
+            eprintln!("Unknown error format '{}', expected human or json", name);
+            process::exit(1);
+        }),
+        None => ErrorFormat::Human,
+    };
 
     // Read the input file
     let source_code = match fs::read_to_string(input_file) {
         Ok(code) => code,
         Err(e) => {
-            eprintln!("Error reading file {}: {}", input_file, e);
+            let diagnostic = ErrorMessages::unlocated("K0502", &[("path", input_file.clone())], language).with_note(e.to_string());
+            eprintln!("{}", DiagnosticRenderer::new(&SourceMap::new()).with_color(error_format == ErrorFormat::Human && use_color()).render_as(&diagnostic, error_format));
             process::exit(1);
         }
     };
 
-    // Lexical analysis
-    let mut lexer = Lexer::new(&source_code);
-    let tokens = match lexer.tokenize() {
-        Ok(tokens) => tokens,
-        Err(e) => {
-            eprintln!("Lexical analysis error: {}", e);
-            process::exit(1);
+    let mut sources = SourceMap::new();
+    let file = sources.add(input_file.as_str(), source_code.as_str());
+    let source = sources.get(file).unwrap();
+    let renderer = DiagnosticRenderer::new(&sources).with_color(error_format == ErrorFormat::Human && use_color());
+    let report = |diagnostics: Vec<Diagnostic>| -> ! {
+        for diagnostic in &diagnostics {
+            eprintln!("{}", renderer.render_as(diagnostic, error_format));
         }
-    };
-
-    // Parsing
-    let mut parser = Parser::new(&tokens).expect("Failed to create parser");
-    let ast = match parser.parse_program() {
-        Ok(ast) => ast,
-        Err(e) => {
-            eprintln!("Parsing error: {}", e);
-            process::exit(1);
+        if error_format == ErrorFormat::Human && diagnostics.len() > 1 {
+            eprintln!("{}", ErrorMessages::message("K0501", &[("count", diagnostics.len().to_string())], language));
         }
+        process::exit(1);
     };
 
+    // Lexical analysis and parsing
+    let mut parser = Parser::new(&source_code).with_file(file);
+    let output = parser.parse_program();
+    if output.has_errors() {
+        report(output.errors.iter().map(|e| e.to_diagnostic(source, language)).collect());
+    }
+    let ast = output.ast;
+
     // Semantic analysis
     let mut semantic_analyzer = SemanticAnalyzer::new();
-    if let Err(e) = semantic_analyzer.analyze(&ast) {
-        eprintln!("Semantic analysis error: {}", e);
-        process::exit(1);
+    let program = match semantic_analyzer.analyze(&ast) {
+        Ok(program) => program,
+        Err(e) => report(vec![e.to_diagnostic(source, language)]),
+    };
+    for warning in semantic_analyzer.warnings() {
+        eprintln!("{}", renderer.render_as(&warning.to_diagnostic(source, language), error_format));
     }
 
     // IR generation
     let mut ir_generator = IRGenerator::new();
-    let ir_module = match ir_generator.generate_ir(&ast) {
+    ir_generator.set_module_debug_info(input_file.clone(), 1, 1);
+    let mut ir_module = match ir_generator.generate_ir(&program) {
         Ok(module) => module,
-        Err(e) => {
-            eprintln!("IR generation error: {}", e);
-            process::exit(1);
-        }
+        Err(e) => report(vec![e.to_diagnostic(source, language)]),
     };
 
     if debug_mode {
@@ -77,14 +113,16 @@ fn main() {
             process::exit(1);
         }
     } else {
-        // Normal mode - compile to LLVM IR
+        // Normal mode - optimize, then compile to LLVM IR
+        if let Err(e) = Optimizer::new().optimize(&mut ir_module) {
+            report(vec![ErrorMessages::unlocated("K0304", &[], language).with_note(e)]);
+        }
         let context = Context::create();
         let module_name = input_file.clone();
         let mut llvm_backend = LLVMBackend::new(&context, &module_name);
 
         if let Err(e) = llvm_backend.compile_ir(&ir_module) {
-            eprintln!("LLVM compilation error: {}", e);
-            process::exit(1);
+            report(vec![ErrorMessages::unlocated("K0302", &[], language).with_note(e)]);
         }
 
         // Print the generated IR
@@ -94,3 +132,77 @@ fn main() {
         llvm_backend.print_ir();
     }
 }
+
+/// `explain` subcommand: print the long-form description of an error code
+fn explain(args: &[String], language: Language) {
+    let code = match args.get(2) {
+        Some(code) if !code.starts_with("--") => code,
+        _ => {
+            eprintln!("Usage: {} explain <code> [--lang=en|id]", args[0]);
+            process::exit(1);
+        }
+    };
+
+    match ErrorMessages::explain(code, language) {
+        Some(text) => print!("{}", text),
+        None => {
+            eprintln!("Unknown error code '{}'", code);
+            process::exit(1);
+        }
+    }
+}
+
+/// Whether diagnostics printed to the terminal are colored
+fn use_color() -> bool {
+    io::stderr().is_terminal() && env::var_os("NO_COLOR").is_none()
+}
+
+/// `doc` subcommand: write the API reference of a package
+fn generate_docs(args: &[String], language: Language) {
+    let package = match args.get(2) {
+        Some(package) if !package.starts_with("--") => package,
+        _ => {
+            eprintln!("Usage: {} doc <package_dir> [--format markdown|html] [--output <dir>]", args[0]);
+            process::exit(1);
+        }
+    };
+
+    let option = |name: &str| {
+        args.iter()
+            .position(|arg| arg == name)
+            .map(|index| args.get(index + 1).cloned().unwrap_or_default())
+    };
+
+    let format = match option("--format") {
+        Some(name) => DocFormat::from_name(&name).unwrap_or_else(|| {
+            eprintln!("Unknown documentation format '{}', expected markdown or html", name);
+            process::exit(1);
+        }),
+        None => DocFormat::Markdown,
+    };
+    let output = option("--output").unwrap_or_else(|| "docs/api".to_string());
+
+    let mut generator = DocGenerator::new(format);
+    let written = generator.add_package(Path::new(package)).and_then(|()| generator.write(Path::new(&output)));
+    let error = match written {
+        Ok(files) => {
+            println!("Documented {} modules in {}", files.len() - 1, output);
+            return;
+        }
+        Err(error) => error,
+    };
+
+    // A file that does not parse is reported with its source, like the errors of a compiled program
+    let mut sources = SourceMap::new();
+    let diagnostic = match error {
+        DocError::Io(path, error) => {
+            ErrorMessages::unlocated("K0502", &[("path", path.display().to_string())], language).with_note(error.to_string())
+        }
+        DocError::Parse(path, source, error) => {
+            let file = sources.add(path.display().to_string(), source);
+            error.to_diagnostic(sources.get(file).unwrap(), language)
+        }
+    };
+    eprintln!("{}", DiagnosticRenderer::new(&sources).with_color(use_color()).render(&diagnostic));
+    process::exit(1);
+}
@@ -3,7 +3,7 @@
 
 use std::collections::HashMap;
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 
 /// Module resolver for handling imports
 pub struct ModuleResolver {
@@ -17,6 +17,12 @@ pub struct ModuleResolver {
     project_root: Option<PathBuf>,
 }
 
+impl Default for ModuleResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ModuleResolver {
     /// Create a new module resolver
     pub fn new() -> Self {
@@ -142,7 +148,6 @@ impl ModuleResolver {
 mod tests {
     use super::*;
     use std::fs;
-    use std::path::PathBuf;
     use tempfile::TempDir;
 
     #[test]
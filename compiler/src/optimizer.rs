@@ -1,7 +1,8 @@
 //! Optimizer for the KODEON programming language
 //! Implements various optimization passes for the IR
 
-use crate::ir::{IRModule, Instruction, Value, Constant, BinaryOperator, UnaryOperator};
+use crate::ir::{IRModule, Instruction, Value, Constant, BinaryOp, UnaryOp, Stage};
+use std::collections::HashMap;
 
 /// Optimization pass trait
 pub trait OptimizationPass {
@@ -19,28 +20,34 @@ pub struct ConstantFolding;
 impl OptimizationPass for ConstantFolding {
     fn run(&self, module: &mut IRModule) -> Result<(), String> {
         for function in &mut module.functions {
-            for block in &mut function.blocks {
-                let mut i = 0;
-                while i < block.instructions.len() {
-                    if let Some(folded_value) = self.try_fold_instruction(&block.instructions[i]) {
-                        // Replace the instruction with a constant
-                        block.instructions[i] = Instruction::Load {
-                            result: match &block.instructions[i] {
-                                Instruction::BinaryOp { result, .. } => result.clone(),
-                                Instruction::UnaryOp { result, .. } => result.clone(),
-                                _ => "folded".to_string(),
-                            },
-                            variable: match folded_value {
-                                Value::Constant(Constant::Int(val)) => format!("int_const_{}", val),
-                                Value::Constant(Constant::Float(val)) => format!("float_const_{}", val),
-                                Value::Constant(Constant::Bool(val)) => format!("bool_const_{}", val),
-                                Value::Constant(Constant::String(ref val)) => format!("string_const_{}", val),
-                                _ => "const".to_string(),
-                            },
-                            debug_info: None,
-                        };
+            // Folding an operation makes its uses constant, which may let them fold in turn
+            loop {
+                let mut folded = HashMap::new();
+                for block in &mut function.blocks {
+                    block.instructions.retain(|instruction| match (instruction, self.try_fold_instruction(instruction)) {
+                        (Instruction::BinaryOp { result, .. } | Instruction::UnaryOp { result, .. }, Some(Value::Constant(constant))) => {
+                            folded.insert(result.clone(), constant);
+                            false
+                        }
+                        _ => true,
+                    });
+                }
+                if folded.is_empty() {
+                    break;
+                }
+
+                // The result of an operation is only read, so each read is replaced by the constant
+                for block in &mut function.blocks {
+                    let operands = block.instructions.iter_mut()
+                        .flat_map(Instruction::operands_mut)
+                        .chain(block.terminator.operands_mut());
+                    for operand in operands {
+                        if let Value::Variable(name) = operand {
+                            if let Some(constant) = folded.get(name) {
+                                *operand = Value::Constant(constant.clone());
+                            }
+                        }
                     }
-                    i += 1;
                 }
             }
         }
@@ -54,76 +61,82 @@ impl OptimizationPass for ConstantFolding {
 
 impl ConstantFolding {
     /// Try to fold an instruction into a constant value
-    fn try_fold_instruction(&self, instruction: &Instruction) -> Option<Value> {
-        match instruction {
-            Instruction::BinaryOp { op, left, right, .. } => {
-                if let (Value::Constant(left_const), Value::Constant(right_const)) = (left, right) {
-                    match (left_const, right_const, op) {
-                        // Integer operations
-                        (Constant::Int(left_val), Constant::Int(right_val), BinaryOperator::Add) => {
-                            Some(Value::Constant(Constant::Int(left_val + right_val)))
-                        }
-                        (Constant::Int(left_val), Constant::Int(right_val), BinaryOperator::Sub) => {
-                            Some(Value::Constant(Constant::Int(left_val - right_val)))
-                        }
-                        (Constant::Int(left_val), Constant::Int(right_val), BinaryOperator::Mul) => {
-                            Some(Value::Constant(Constant::Int(left_val * right_val)))
-                        }
-                        (Constant::Int(left_val), Constant::Int(right_val), BinaryOperator::Div) => {
-                            if *right_val != 0 {
-                                Some(Value::Constant(Constant::Int(left_val / right_val)))
-                            } else {
-                                None
-                            }
-                        }
-                        // Float operations
-                        (Constant::Float(left_val), Constant::Float(right_val), BinaryOperator::Add) => {
-                            Some(Value::Constant(Constant::Float(left_val + right_val)))
-                        }
-                        (Constant::Float(left_val), Constant::Float(right_val), BinaryOperator::Sub) => {
-                            Some(Value::Constant(Constant::Float(left_val - right_val)))
-                        }
-                        (Constant::Float(left_val), Constant::Float(right_val), BinaryOperator::Mul) => {
-                            Some(Value::Constant(Constant::Float(left_val * right_val)))
-                        }
-                        (Constant::Float(left_val), Constant::Float(right_val), BinaryOperator::Div) => {
-                            if *right_val != 0.0 {
-                                Some(Value::Constant(Constant::Float(left_val / right_val)))
-                            } else {
-                                None
-                            }
-                        }
-                        // Boolean operations
-                        (Constant::Bool(left_val), Constant::Bool(right_val), BinaryOperator::And) => {
-                            Some(Value::Constant(Constant::Bool(*left_val && *right_val)))
-                        }
-                        (Constant::Bool(left_val), Constant::Bool(right_val), BinaryOperator::Or) => {
-                            Some(Value::Constant(Constant::Bool(*left_val || *right_val)))
-                        }
-                        _ => None,
-                    }
-                } else {
-                    None
-                }
-            }
-            Instruction::UnaryOp { op, operand, .. } => {
-                if let Value::Constant(const_val) = operand {
-                    match (const_val, op) {
-                        (Constant::Bool(val), UnaryOperator::Not) => {
-                            Some(Value::Constant(Constant::Bool(!val)))
-                        }
-                        (Constant::Int(val), UnaryOperator::Neg) => {
-                            Some(Value::Constant(Constant::Int(-val)))
-                        }
-                        (Constant::Float(val), UnaryOperator::Neg) => {
-                            Some(Value::Constant(Constant::Float(-val)))
-                        }
-                        _ => None,
-                    }
-                } else {
-                    None
+    ///
+    /// Integer operations that would overflow or divide by zero are not folded, so the
+    /// program behaves the same as when the operation runs.
+    pub fn try_fold_instruction(&self, instruction: &Instruction) -> Option<Value> {
+        let constant = match instruction {
+            Instruction::BinaryOp { op, left: Value::Constant(left), right: Value::Constant(right), .. } => {
+                match (left, right) {
+                    (Constant::Int(left), Constant::Int(right)) => Self::fold_int(op, *left, *right),
+                    (Constant::Float(left), Constant::Float(right)) => Self::fold_float(op, *left, *right),
+                    (Constant::Bool(left), Constant::Bool(right)) => Self::fold_bool(op, *left, *right),
+                    _ => None,
                 }
             }
+            Instruction::UnaryOp { op, operand: Value::Constant(operand), .. } => match (operand, op) {
+                (Constant::Bool(value), UnaryOp::Not) => Some(Constant::Bool(!value)),
+                (Constant::Int(value), UnaryOp::Neg) => value.checked_neg().map(Constant::Int),
+                (Constant::Int(value), UnaryOp::BitNot) => Some(Constant::Int(!value)),
+                (Constant::Float(value), UnaryOp::Neg) => Some(Constant::Float(-value)),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        constant.map(Value::Constant)
+    }
+
+    /// Fold an operation on two integers
+    fn fold_int(op: &BinaryOp, left: i64, right: i64) -> Option<Constant> {
+        let value = match op {
+            BinaryOp::Add => left.checked_add(right)?,
+            BinaryOp::Sub => left.checked_sub(right)?,
+            BinaryOp::Mul => left.checked_mul(right)?,
+            BinaryOp::Div => left.checked_div(right)?,
+            BinaryOp::Mod => left.checked_rem(right)?,
+            BinaryOp::BitAnd => left & right,
+            BinaryOp::BitOr => left | right,
+            BinaryOp::BitXor => left ^ right,
+            BinaryOp::LeftShift => left.checked_shl(u32::try_from(right).ok()?)?,
+            BinaryOp::RightShift => left.checked_shr(u32::try_from(right).ok()?)?,
+            BinaryOp::Eq => return Some(Constant::Bool(left == right)),
+            BinaryOp::Ne => return Some(Constant::Bool(left != right)),
+            BinaryOp::Lt => return Some(Constant::Bool(left < right)),
+            BinaryOp::Gt => return Some(Constant::Bool(left > right)),
+            BinaryOp::Le => return Some(Constant::Bool(left <= right)),
+            BinaryOp::Ge => return Some(Constant::Bool(left >= right)),
+            _ => return None,
+        };
+        Some(Constant::Int(value))
+    }
+
+    /// Fold an operation on two floats
+    fn fold_float(op: &BinaryOp, left: f64, right: f64) -> Option<Constant> {
+        let value = match op {
+            BinaryOp::Add => left + right,
+            BinaryOp::Sub => left - right,
+            BinaryOp::Mul => left * right,
+            BinaryOp::Div if right != 0.0 => left / right,
+            BinaryOp::Mod if right != 0.0 => left % right,
+            BinaryOp::Eq => return Some(Constant::Bool(left == right)),
+            BinaryOp::Ne => return Some(Constant::Bool(left != right)),
+            BinaryOp::Lt => return Some(Constant::Bool(left < right)),
+            BinaryOp::Gt => return Some(Constant::Bool(left > right)),
+            BinaryOp::Le => return Some(Constant::Bool(left <= right)),
+            BinaryOp::Ge => return Some(Constant::Bool(left >= right)),
+            _ => return None,
+        };
+        Some(Constant::Float(value))
+    }
+
+    /// Fold an operation on two booleans
+    fn fold_bool(op: &BinaryOp, left: bool, right: bool) -> Option<Constant> {
+        match op {
+            BinaryOp::And => Some(Constant::Bool(left && right)),
+            BinaryOp::Or => Some(Constant::Bool(left || right)),
+            BinaryOp::Eq => Some(Constant::Bool(left == right)),
+            BinaryOp::Ne => Some(Constant::Bool(left != right)),
             _ => None,
         }
     }
@@ -160,17 +173,62 @@ impl OptimizationPass for DeadCodeElimination {
     }
 }
 
+/// Pipeline fusion optimization pass
+/// Merges adjacent map and filter stages of a pipeline into one loop, so the list is walked once
+/// and no list is built between them
+pub struct PipelineFusion;
+
+impl OptimizationPass for PipelineFusion {
+    fn run(&self, module: &mut IRModule) -> Result<(), String> {
+        for function in &mut module.functions {
+            for block in &mut function.blocks {
+                for instruction in block.instructions_mut() {
+                    if let Instruction::Pipeline { stages, .. } = instruction {
+                        *stages = Self::fuse(std::mem::take(stages));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "Pipeline Fusion"
+    }
+}
+
+impl PipelineFusion {
+    /// `stages` with each run of adjacent loops merged into one loop running their steps in order
+    pub fn fuse(stages: Vec<Stage>) -> Vec<Stage> {
+        let mut fused: Vec<Stage> = Vec::new();
+        for stage in stages {
+            match (fused.last_mut(), stage) {
+                (Some(Stage::Loop(steps)), Stage::Loop(next)) => steps.extend(next),
+                (_, stage) => fused.push(stage),
+            }
+        }
+        fused
+    }
+}
+
 /// Optimizer that runs multiple optimization passes
 pub struct Optimizer {
     passes: Vec<Box<dyn OptimizationPass>>,
 }
 
+impl Default for Optimizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Optimizer {
     /// Create a new optimizer
     pub fn new() -> Self {
         Optimizer {
             passes: vec![
                 Box::new(ConstantFolding),
+                Box::new(PipelineFusion),
                 Box::new(DeadCodeElimination),
             ],
         }
@@ -184,8 +242,7 @@ impl Optimizer {
     /// Run all optimization passes on an IR module
     pub fn optimize(&self, module: &mut IRModule) -> Result<(), String> {
         for pass in &self.passes {
-            println!("Running optimization pass: {}", pass.name());
-            pass.run(module)?;
+            pass.run(module).map_err(|error| format!("{}: {}", pass.name(), error))?;
         }
         Ok(())
     }
@@ -194,27 +251,44 @@ impl Optimizer {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::ir::{Function, BasicBlock, Parameter, Type, Terminator};
+    use crate::ir::{Function, BasicBlock, Parameter, Type, Terminator, LoopStep};
 
     #[test]
     fn test_constant_folding() {
-        let mut module = IRModule {
-            functions: vec![],
-            global_vars: vec![],
-            debug_info: None,
-        };
+        let mut module = IRModule::new("test".to_string());
 
         let optimizer = Optimizer::new();
         assert!(optimizer.optimize(&mut module).is_ok());
     }
 
+    #[test]
+    fn test_constant_folding_replaces_the_uses_of_folded_results() {
+        let operation = |result: &str, op, left, right| Instruction::BinaryOp { result: result.to_string(), op, left, right, debug_info: None };
+        let mut block = BasicBlock::new("entry".to_string());
+        block.add_instruction(operation("jumlah", BinaryOp::Add, Value::Constant(Constant::Int(2)), Value::Constant(Constant::Int(3))));
+        block.add_instruction(operation("kali", BinaryOp::Mul, Value::Variable("jumlah".to_string()), Value::Constant(Constant::Int(4))));
+        block.add_instruction(Instruction::Store { variable: "x".to_string(), value: Value::Variable("kali".to_string()), debug_info: None });
+        block.set_terminator(Terminator::Return { value: Some(Value::Variable("kali".to_string())) });
+        let mut function = Function::new("f".to_string(), Type::Int);
+        function.add_parameter(Parameter::new("n".to_string(), Type::Int));
+        function.add_block(block);
+        let mut module = IRModule::new("test".to_string());
+        module.functions.push(function);
+
+        ConstantFolding.run(&mut module).unwrap();
+        // Both operations are gone, and the store and the return use their value
+        let block = &module.functions[0].blocks[0];
+        assert!(matches!(&block.instructions[..], [Instruction::Store { value: Value::Constant(Constant::Int(20)), .. }]), "{:?}", block.instructions);
+        assert!(matches!(block.terminator, Terminator::Return { value: Some(Value::Constant(Constant::Int(20))) }));
+    }
+
     #[test]
     fn test_constant_folding_int_addition() {
         let folding = ConstantFolding;
 
         let instruction = Instruction::BinaryOp {
             result: "result".to_string(),
-            op: BinaryOperator::Add,
+            op: BinaryOp::Add,
             left: Value::Constant(Constant::Int(2)),
             right: Value::Constant(Constant::Int(3)),
             debug_info: None,
@@ -229,4 +303,71 @@ mod tests {
             panic!("Expected integer constant");
         }
     }
+
+    #[test]
+    fn test_constant_folding_int_overflow_is_not_folded() {
+        let folding = ConstantFolding;
+
+        let instruction = Instruction::BinaryOp {
+            result: "result".to_string(),
+            op: BinaryOp::Mul,
+            left: Value::Constant(Constant::Int(i64::MAX)),
+            right: Value::Constant(Constant::Int(2)),
+            debug_info: None,
+        };
+        assert!(folding.try_fold_instruction(&instruction).is_none());
+
+        let instruction = Instruction::BinaryOp {
+            result: "result".to_string(),
+            op: BinaryOp::Mod,
+            left: Value::Constant(Constant::Int(7)),
+            right: Value::Constant(Constant::Int(0)),
+            debug_info: None,
+        };
+        assert!(folding.try_fold_instruction(&instruction).is_none());
+    }
+
+    #[test]
+    fn test_pipeline_fusion_merges_adjacent_loops() {
+        let filter = |name: &str| LoopStep::Filter(Value::Variable(name.to_string()));
+        let map = |name: &str| LoopStep::Map { function: Value::Variable(name.to_string()), element_type: Type::Int };
+        let stages = vec![
+            Stage::Loop(vec![filter("positif")]),
+            Stage::Loop(vec![map("ganda")]),
+            Stage::Loop(vec![filter("genap")]),
+            Stage::Take(Value::Constant(Constant::Int(3))),
+            Stage::Loop(vec![map("kuadrat")]),
+            Stage::Sort(None),
+        ];
+
+        let fused = PipelineFusion::fuse(stages);
+        assert_eq!(fused.len(), 4);
+        match &fused[0] {
+            Stage::Loop(steps) => {
+                assert!(matches!(&steps[..], [LoopStep::Filter(_), LoopStep::Map { .. }, LoopStep::Filter(_)]));
+            }
+            other => panic!("Expected a loop, got {:?}", other),
+        }
+        // Loops are not merged across a stage that needs the whole list
+        assert!(matches!(fused[1], Stage::Take(_)));
+        assert!(matches!(&fused[2], Stage::Loop(steps) if steps.len() == 1));
+    }
+
+    #[test]
+    fn test_constant_folding_int_comparison() {
+        let folding = ConstantFolding;
+
+        let instruction = Instruction::BinaryOp {
+            result: "result".to_string(),
+            op: BinaryOp::Lt,
+            left: Value::Constant(Constant::Int(2)),
+            right: Value::Constant(Constant::Int(3)),
+            debug_info: None,
+        };
+
+        match folding.try_fold_instruction(&instruction) {
+            Some(Value::Constant(Constant::Bool(value))) => assert!(value),
+            other => panic!("Expected boolean constant, got {:?}", other),
+        }
+    }
 }
@@ -0,0 +1,352 @@
+//! Exhaustiveness and reachability of the cases of a `ketika` statement
+//!
+//! The check follows Maranget's usefulness algorithm ("Warnings for pattern matching", 2007): a
+//! case is unreachable when its pattern matches no value the cases above it leave, and the cases
+//! are exhaustive when a wildcard after them would be unreachable. Asking whether that wildcard
+//! is useful also yields a witness, a value no case matches, which the error shows.
+//!
+//! Whole numbers are matched by ranges; a literal is the range of one value. A range is split at
+//! the bounds of the ranges it overlaps, so each piece is either inside or outside each of them.
+
+use crate::types::Type;
+
+/// Pattern as far as the check is concerned: bindings are wildcards, and literals are constructors
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern {
+    Wildcard,
+    Variant(usize, Vec<Pattern>), // index of the variant in its enum, and patterns of its fields
+    Tuple(Vec<Pattern>),
+    Bool(bool),
+    Range(i64, i64),  // whole numbers from the first to the second, both included
+    Constant(String), // float, text or `nol`, compared by its source form
+}
+
+/// Case of a `ketika` statement
+#[derive(Debug, Clone)]
+pub struct Case {
+    pub pattern: Pattern,
+    pub guarded: bool, // a case with `jika` may not run for the values its pattern matches
+}
+
+/// Outcome of checking the cases of a `ketika` statement
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub unreachable: Vec<usize>, // indices of the cases that can never run
+    pub missing: Option<String>, // a value no case matches, written as a pattern
+}
+
+/// Variants of an enum type with the types of their fields, in declaration order; `None` for other types
+pub type Variants<'a> = dyn Fn(&Type) -> Option<Vec<(String, Vec<Type>)>> + 'a;
+
+/// Check `cases`, tried in order on a value of type `scrutinee`
+pub fn check(cases: &[Case], scrutinee: &Type, variants: &Variants) -> Report {
+    let checker = Checker { variants };
+    let types = [scrutinee.clone()];
+    let mut rows: Vec<Vec<Pattern>> = Vec::new();
+    let mut unreachable = Vec::new();
+    for (index, case) in cases.iter().enumerate() {
+        let row = vec![case.pattern.clone()];
+        if checker.useful(&rows, &row, &types).is_none() {
+            unreachable.push(index);
+        }
+        // A guard may reject the values, so they are left to the cases below
+        if !case.guarded {
+            rows.push(row);
+        }
+    }
+    let missing = checker.useful(&rows, &[Pattern::Wildcard], &types)
+        .map(|witness| checker.describe(&witness[0], scrutinee));
+    Report { unreachable, missing }
+}
+
+/// Head of a pattern that is not a wildcard
+#[derive(Debug, Clone, PartialEq)]
+enum Constructor {
+    Variant(usize),
+    Tuple,
+    Bool(bool),
+    Range(i64, i64),
+    Constant(String),
+}
+
+impl Constructor {
+    /// Pattern of this constructor applied to `fields`
+    fn apply(self, fields: Vec<Pattern>) -> Pattern {
+        match self {
+            Constructor::Variant(index) => Pattern::Variant(index, fields),
+            Constructor::Tuple => Pattern::Tuple(fields),
+            Constructor::Bool(value) => Pattern::Bool(value),
+            Constructor::Range(start, end) => Pattern::Range(start, end),
+            Constructor::Constant(value) => Pattern::Constant(value),
+        }
+    }
+
+    /// Check whether `pattern` matches every value this constructor builds
+    ///
+    /// Ranges are split beforehand, so a range piece is inside or outside each range pattern.
+    fn is_covered_by(&self, pattern: &Pattern) -> bool {
+        match (pattern, self) {
+            (Pattern::Wildcard, _) | (Pattern::Tuple(_), Constructor::Tuple) => true,
+            (Pattern::Variant(index, _), Constructor::Variant(wanted)) => index == wanted,
+            (Pattern::Bool(value), Constructor::Bool(wanted)) => value == wanted,
+            (Pattern::Range(start, end), Constructor::Range(low, high)) => start <= low && high <= end,
+            (Pattern::Constant(value), Constructor::Constant(wanted)) => value == wanted,
+            _ => false,
+        }
+    }
+}
+
+/// Usefulness check over the variants of the enums in the program
+struct Checker<'a> {
+    variants: &'a Variants<'a>,
+}
+
+impl Checker<'_> {
+    /// Values matched by `row` and by none of `rows`, as a pattern for each column, if there are any
+    ///
+    /// `types` holds the type of each column.
+    fn useful(&self, rows: &[Vec<Pattern>], row: &[Pattern], types: &[Type]) -> Option<Vec<Pattern>> {
+        let Some((head, rest)) = row.split_first() else {
+            return if rows.is_empty() { Some(Vec::new()) } else { None };
+        };
+        let ty = &types[0];
+        let column: Vec<&Pattern> = rows.iter().map(|row| &row[0]).chain(std::iter::once(head)).collect();
+
+        let constructors = match head {
+            Pattern::Wildcard => {
+                let used: Vec<&Pattern> = rows.iter().map(|row| &row[0]).filter(|pattern| **pattern != Pattern::Wildcard).collect();
+                let all = self.constructors(ty, &column);
+                let missing = all.as_ref().and_then(|all| {
+                    all.iter().find(|constructor| !used.iter().any(|pattern| constructor.is_covered_by(pattern))).cloned()
+                });
+                match (all, missing) {
+                    (Some(all), None) => all,
+                    // Values built by none of the constructors in the column only match the rows starting with a wildcard
+                    (_, missing) => {
+                        let default: Vec<Vec<Pattern>> = rows.iter()
+                            .filter(|row| row[0] == Pattern::Wildcard)
+                            .map(|row| row[1..].to_vec())
+                            .collect();
+                        let mut witness = self.useful(&default, rest, &types[1..])?;
+                        let head = match missing {
+                            Some(missing) if !used.is_empty() => {
+                                let arity = self.field_types(&missing, ty, &column).len();
+                                missing.apply(vec![Pattern::Wildcard; arity])
+                            }
+                            _ => Pattern::Wildcard,
+                        };
+                        witness.insert(0, head);
+                        return Some(witness);
+                    }
+                }
+            }
+            Pattern::Range(start, end) => split(*start, *end, &column),
+            Pattern::Variant(index, _) => vec![Constructor::Variant(*index)],
+            Pattern::Tuple(_) => vec![Constructor::Tuple],
+            Pattern::Bool(value) => vec![Constructor::Bool(*value)],
+            Pattern::Constant(value) => vec![Constructor::Constant(value.clone())],
+        };
+
+        for constructor in constructors {
+            let fields = self.field_types(&constructor, ty, &column);
+            let specialized: Vec<Vec<Pattern>> = rows.iter()
+                .filter_map(|row| specialize(row, &constructor, fields.len()))
+                .collect();
+            let Some(row) = specialize(row, &constructor, fields.len()) else {
+                continue;
+            };
+            let types: Vec<Type> = fields.iter().chain(&types[1..]).cloned().collect();
+            if let Some(mut witness) = self.useful(&specialized, &row, &types) {
+                let rest = witness.split_off(fields.len());
+                return Some(std::iter::once(constructor.apply(witness)).chain(rest).collect());
+            }
+        }
+        None
+    }
+
+    /// Constructors that together build every value of type `ty`, with ranges split at the bounds
+    /// of the ranges in `column`; `None` when the values are too many to list
+    fn constructors(&self, ty: &Type, column: &[&Pattern]) -> Option<Vec<Constructor>> {
+        if let Some(variants) = (self.variants)(ty) {
+            return Some((0..variants.len()).map(Constructor::Variant).collect());
+        }
+        match ty {
+            Type::Bool => Some(vec![Constructor::Bool(true), Constructor::Bool(false)]),
+            Type::Int => Some(split(i64::MIN, i64::MAX, column)),
+            Type::Tuple(_) => Some(vec![Constructor::Tuple]),
+            // An optional value is one of the values of its type, or `nol`
+            Type::Optional(inner) => {
+                let mut constructors = self.constructors(inner, column)?;
+                constructors.push(Constructor::Constant("nol".to_string()));
+                Some(constructors)
+            }
+            _ if column.iter().any(|pattern| matches!(pattern, Pattern::Tuple(_))) => Some(vec![Constructor::Tuple]),
+            _ => None,
+        }
+    }
+
+    /// Types of the fields of the values `constructor` builds in a column of type `ty`
+    fn field_types(&self, constructor: &Constructor, ty: &Type, column: &[&Pattern]) -> Vec<Type> {
+        let ty = ty.non_optional();
+        match constructor {
+            Constructor::Variant(index) => {
+                if let Some(mut variants) = (self.variants)(ty) {
+                    if *index < variants.len() {
+                        return variants.swap_remove(*index).1;
+                    }
+                }
+                // Without the type of the column, the patterns tell how many fields there are
+                let arity = column.iter().rev().find_map(|pattern| match pattern {
+                    Pattern::Variant(variant, fields) if variant == index => Some(fields.len()),
+                    _ => None,
+                });
+                vec![Type::Any; arity.unwrap_or(0)]
+            }
+            Constructor::Tuple => match ty {
+                Type::Tuple(elements) => elements.clone(),
+                _ => {
+                    let arity = column.iter().rev().find_map(|pattern| match pattern {
+                        Pattern::Tuple(elements) => Some(elements.len()),
+                        _ => None,
+                    });
+                    vec![Type::Any; arity.unwrap_or(0)]
+                }
+            },
+            _ => Vec::new(),
+        }
+    }
+
+    /// `pattern`, matching values of type `ty`, written as in the source
+    fn describe(&self, pattern: &Pattern, ty: &Type) -> String {
+        let ty = ty.non_optional();
+        match pattern {
+            Pattern::Wildcard => "_".to_string(),
+            Pattern::Variant(index, fields) => {
+                let variants = (self.variants)(ty).unwrap_or_default();
+                let Some((name, field_types)) = variants.get(*index) else {
+                    return "_".to_string();
+                };
+                if fields.is_empty() {
+                    return name.clone();
+                }
+                let fields: Vec<String> = fields.iter().enumerate()
+                    .map(|(index, field)| self.describe(field, field_types.get(index).unwrap_or(&Type::Any)))
+                    .collect();
+                format!("{}({})", name, fields.join(", "))
+            }
+            Pattern::Tuple(elements) => {
+                let elements: Vec<String> = elements.iter().enumerate()
+                    .map(|(index, element)| match ty {
+                        Type::Tuple(types) => self.describe(element, types.get(index).unwrap_or(&Type::Any)),
+                        _ => self.describe(element, &Type::Any),
+                    })
+                    .collect();
+                format!("({})", elements.join(", "))
+            }
+            Pattern::Bool(true) => "benar".to_string(),
+            Pattern::Bool(false) => "salah".to_string(),
+            // One value of the range, preferring 0 and values near it
+            Pattern::Range(start, end) if *start <= 0 && 0 <= *end => "0".to_string(),
+            Pattern::Range(start, end) if *start == i64::MIN => end.to_string(),
+            Pattern::Range(start, _) => start.to_string(),
+            Pattern::Constant(value) => value.clone(),
+        }
+    }
+}
+
+/// Pieces of the range from `low` to `high` such that each range in `column` contains each piece
+/// or is disjoint from it
+fn split(low: i64, high: i64, column: &[&Pattern]) -> Vec<Constructor> {
+    // An empty range matches nothing
+    if low > high {
+        return Vec::new();
+    }
+    let mut starts = vec![low];
+    for pattern in column {
+        if let Pattern::Range(start, end) = pattern {
+            if low < *start && *start <= high {
+                starts.push(*start);
+            }
+            if low <= *end && *end < high {
+                starts.push(end + 1);
+            }
+        }
+    }
+    starts.sort_unstable();
+    starts.dedup();
+    starts.iter().enumerate()
+        .map(|(index, start)| Constructor::Range(*start, starts.get(index + 1).map_or(high, |next| next - 1)))
+        .collect()
+}
+
+/// `row` without its first pattern, and with the fields of that pattern in front if it matches
+/// the values `constructor` builds; `None` if it does not
+fn specialize(row: &[Pattern], constructor: &Constructor, arity: usize) -> Option<Vec<Pattern>> {
+    let fields = match (&row[0], constructor) {
+        (Pattern::Wildcard, _) => vec![Pattern::Wildcard; arity],
+        (Pattern::Variant(_, fields) | Pattern::Tuple(fields), _) if constructor.is_covered_by(&row[0]) => fields.clone(),
+        (pattern, _) if constructor.is_covered_by(pattern) => Vec::new(),
+        _ => return None,
+    };
+    // Patterns of a different length only meet on values whose type is not known
+    if fields.len() != arity {
+        return None;
+    }
+    Some(fields.into_iter().chain(row[1..].iter().cloned()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `Bentuk` is `Lingkaran(float)`, `Persegi(float, float)` or `Kosong`
+    fn shapes(ty: &Type) -> Option<Vec<(String, Vec<Type>)>> {
+        match ty {
+            Type::Named { name, .. } if name == "Bentuk" => Some(vec![
+                ("Lingkaran".to_string(), vec![Type::Float]),
+                ("Persegi".to_string(), vec![Type::Float, Type::Float]),
+                ("Kosong".to_string(), Vec::new()),
+            ]),
+            _ => None,
+        }
+    }
+
+    fn cases(patterns: Vec<Pattern>) -> Vec<Case> {
+        patterns.into_iter().map(|pattern| Case { pattern, guarded: false }).collect()
+    }
+
+    #[test]
+    fn test_enum_variants_must_all_be_matched() {
+        let bentuk = Type::named("Bentuk");
+        let circle = Pattern::Variant(0, vec![Pattern::Wildcard]);
+        let square = Pattern::Variant(1, vec![Pattern::Wildcard, Pattern::Wildcard]);
+
+        let report = check(&cases(vec![circle.clone(), Pattern::Variant(2, Vec::new())]), &bentuk, &shapes);
+        assert_eq!(report, Report { unreachable: Vec::new(), missing: Some("Persegi(_, _)".to_string()) });
+
+        let report = check(&cases(vec![circle.clone(), square, Pattern::Variant(2, Vec::new()), Pattern::Wildcard]), &bentuk, &shapes);
+        assert_eq!(report, Report { unreachable: vec![3], missing: None });
+
+        // A guarded case leaves its values to the cases below
+        let guarded = vec![Case { pattern: circle, guarded: true }, Case { pattern: Pattern::Wildcard, guarded: false }];
+        assert_eq!(check(&guarded, &bentuk, &shapes), Report { unreachable: Vec::new(), missing: None });
+    }
+
+    #[test]
+    fn test_ranges_are_split_and_tuples_nested() {
+        let report = check(&cases(vec![Pattern::Range(i64::MIN, -1), Pattern::Range(1, i64::MAX)]), &Type::Int, &shapes);
+        assert_eq!(report.missing.as_deref(), Some("0"));
+        let report = check(&cases(vec![Pattern::Range(0, 9), Pattern::Range(3, 5), Pattern::Wildcard]), &Type::Int, &shapes);
+        assert_eq!(report, Report { unreachable: vec![1], missing: None });
+
+        let pair = Type::Tuple(vec![Type::Bool, Type::Bool]);
+        let report = check(&cases(vec![
+            Pattern::Tuple(vec![Pattern::Bool(true), Pattern::Wildcard]),
+            Pattern::Tuple(vec![Pattern::Wildcard, Pattern::Bool(true)]),
+        ]), &pair, &shapes);
+        assert_eq!(report.missing.as_deref(), Some("(salah, salah)"));
+
+        let text = check(&cases(vec![Pattern::Constant("\"a\"".to_string()), Pattern::Constant("\"a\"".to_string())]), &Type::String, &shapes);
+        assert_eq!(text, Report { unreachable: vec![1], missing: Some("_".to_string()) });
+    }
+}
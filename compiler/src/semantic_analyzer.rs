@@ -1,7 +1,17 @@
 //! Semantic analyzer for the KODEON programming language
 
-use crate::parser::{ASTNode, Position};
-use std::collections::HashMap;
+use crate::diagnostics::{Diagnostic, Severity, SourceFile};
+use crate::flow;
+use crate::error_messages::{ErrorMessages, Language};
+use crate::hir::{self, SymbolId, SymbolInfo, SymbolKind};
+use crate::lexer::{Position, Span};
+use crate::parser::{
+    ASTNode, BinaryOperator, CatchClause, FunctionParameter, InterpolationPart, LiteralPattern, MatchCase, Parser, Pattern, PatternKind,
+    PipelineOperation, PipelineStage, PositionedASTNode, Statement, TypeExpr, TypeParameter, UnaryOperator,
+};
+use crate::patterns;
+use crate::types::{Type, Unifier};
+use std::collections::{HashMap, HashSet};
 
 /// Symbol table entry with position information
 #[derive(Debug, Clone)]
@@ -11,6 +21,7 @@ pub struct Symbol {
     pub is_initialized: bool,
     pub is_used: bool,
     pub position: Position, // Position where symbol was declared
+    pub id: Option<SymbolId>, // symbol of the analyzed program; None for tables built outside the analyzer
 }
 
 /// Types of symbols
@@ -19,14 +30,15 @@ pub enum SymbolType {
     Variable(VariableInfo),
     Function(FunctionSignature),
     Class(ClassInfo),
+    Enum(EnumInfo),
     Parameter(VariableInfo),
 }
 
 /// Information about a variable
 #[derive(Debug, Clone)]
 pub struct VariableInfo {
-    pub var_type: Option<String>,
-    pub inferred_type: Option<String>,
+    pub var_type: Option<TypeExpr>, // declared type, if annotated
+    pub inferred_type: Option<Type>, // type found by the analyzer; `any` where it could not tell
     pub is_constant: bool,
 }
 
@@ -34,7 +46,9 @@ pub struct VariableInfo {
 #[derive(Debug, Clone)]
 pub struct FunctionSignature {
     pub parameters: Vec<Parameter>,
-    pub return_type: Option<String>,
+    pub return_type: Option<TypeExpr>,
+    pub inferred_type: Option<Type>, // `fungsi(...) -> ...` type found by the analyzer
+    pub type_parameters: Vec<(String, Option<Type>)>, // `<T: Bentuk>` of a generic function, with the bound of each
     pub position: Position, // Position where function was declared
 }
 
@@ -42,7 +56,7 @@ pub struct FunctionSignature {
 #[derive(Debug, Clone)]
 pub struct Parameter {
     pub name: String,
-    pub param_type: Option<String>,
+    pub param_type: Option<TypeExpr>,
     pub position: Position, // Position where parameter was declared
 }
 
@@ -51,9 +65,31 @@ pub struct Parameter {
 pub struct ClassInfo {
     pub fields: HashMap<String, VariableInfo>,
     pub methods: HashMap<String, FunctionSignature>,
+    pub parent: Option<String>, // class this one extends
+    pub interfaces: Vec<String>, // interfaces the class implements
+    pub access: HashMap<String, String>, // access modifier of each member declared with one
+    pub assigned_fields: HashSet<String>, // fields its methods create by assigning `ini.<field>` without declaring them
+    pub is_interface: bool, // declared with `antarmuka`: its methods are signatures without a body
+    pub type_parameters: Vec<(String, Option<Type>)>, // `<T: Bentuk>` of a generic class, with the bound of each
     pub position: Position, // Position where class was declared
 }
 
+/// Enum information
+#[derive(Debug, Clone)]
+pub struct EnumInfo {
+    pub variants: Vec<VariantInfo>, // in declaration order
+    pub type_parameters: Vec<(String, Option<Type>)>, // `<T>` of a generic enum, with the bound of each
+    pub position: Position, // Position where enum was declared
+}
+
+/// Variant of an enum
+#[derive(Debug, Clone)]
+pub struct VariantInfo {
+    pub name: String,
+    pub fields: Vec<(Option<String>, Type)>, // name, if given, and type of each field
+    pub position: Position, // Position where variant was declared
+}
+
 /// Scope in the symbol table
 #[derive(Debug)]
 pub struct Scope {
@@ -69,6 +105,12 @@ pub struct SymbolTable {
     pub current_scope: usize,
 }
 
+impl Default for SymbolTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl SymbolTable {
     /// Create a new symbol table
     pub fn new() -> Self {
@@ -126,6 +168,17 @@ impl SymbolTable {
 
         None
     }
+
+    /// Look up a symbol for updating, searching through scopes
+    pub fn lookup_symbol_mut(&mut self, name: &str) -> Option<&mut Symbol> {
+        let mut scope_index = self.current_scope;
+
+        while !self.scopes[scope_index].symbols.contains_key(name) {
+            scope_index = self.scopes[scope_index].parent?;
+        }
+
+        self.scopes[scope_index].symbols.get_mut(name)
+    }
 }
 
 /// Enhanced semantic analysis error with detailed information
@@ -168,6 +221,204 @@ pub enum SemanticError {
         suggestion: String,
         example: String,
     },
+    WrongArgumentCount {
+        name: String,
+        expected: usize,
+        found: usize,
+        position: Position, // Position of the call
+        context: String,
+        suggestion: String,
+        example: String,
+    },
+    InaccessibleMember {
+        member: String,
+        class: String, // class that declares the member
+        access: String, // private or protected
+        position: Position, // Position of the use
+        context: String,
+        suggestion: String,
+        example: String,
+    },
+    MissingInterfaceMethod {
+        class: String,
+        interface: String,
+        method: String,
+        position: Position, // Position of the class definition
+        context: String,
+        suggestion: String,
+        example: String,
+    },
+    NonExhaustiveMatch {
+        missing: String, // a value no case matches, written as a pattern
+        position: Position, // Position of the `ketika` statement
+        context: String,
+        suggestion: String,
+        example: String,
+    },
+    UnreachablePattern {
+        case: String, // the case, e.g. `kasus _` or `sebaliknya`
+        position: Position,
+        context: String,
+        suggestion: String,
+        example: String,
+    },
+    PossiblyNull {
+        value: String, // the value used, e.g. `pengguna.alamat`, or its type
+        position: Position,
+        context: String,
+        suggestion: String,
+        example: String,
+    },
+    ImmutableAssignment {
+        name: String,
+        declared_at: Position, // Position of the declaration or parameter
+        position: Position, // Position of the assignment
+        context: String,
+        suggestion: String,
+        example: String,
+    },
+    NotConstant {
+        name: String, // the `konstan` declared
+        position: Position, // Position of the part that is not computed when compiling
+        context: String,
+        suggestion: String,
+        example: String,
+    },
+    MissingReturn {
+        function: String, // e.g. `'tanda'` or `a lambda`
+        returns: String, // the type it returns
+        position: Position, // Position of the function
+        context: String,
+        suggestion: String,
+        example: String,
+    },
+    OutsideLoop {
+        keyword: String, // `pecah` or `lanjut`
+        position: Position,
+        context: String,
+        suggestion: String,
+        example: String,
+    },
+    UndeclaredMember {
+        member: String,
+        class: String, // class of the value, whose members and inherited ones were searched
+        position: Position, // Position of the use
+        context: String,
+        suggestion: String,
+        example: String,
+    },
+    InheritanceCycle {
+        class: String,
+        chain: String, // the class and its ancestors up to itself, e.g. `A -> B -> A`
+        position: Position, // Position of the class definition
+        context: String,
+        suggestion: String,
+        example: String,
+    },
+    SuperOutsideSubclass {
+        position: Position,
+        context: String,
+        suggestion: String,
+        example: String,
+    },
+    InterfaceExtended {
+        class: String,
+        interface: String,
+        position: Position, // Position of the class definition
+        context: String,
+        suggestion: String,
+        example: String,
+    },
+    NotAnInterface {
+        name: String, // the class named after `implementasi`
+        position: Position, // Position of the class definition
+        context: String,
+        suggestion: String,
+        example: String,
+    },
+    UnknownInterfaceMember {
+        interface: String,
+        member: String,
+        position: Position,
+        context: String,
+        suggestion: String,
+        example: String,
+    },
+    InterfaceInstantiated {
+        interface: String,
+        position: Position,
+        context: String,
+        suggestion: String,
+        example: String,
+    },
+    HiddenInterfaceMethod {
+        class: String,
+        interface: String,
+        method: String,
+        access: String, // `private` or `protected`
+        position: Position, // Position of the method
+        context: String,
+        suggestion: String,
+        example: String,
+    },
+    PropagateOutsideFunction {
+        position: Position,
+        context: String,
+        suggestion: String,
+        example: String,
+    },
+    PropagateNonOptional {
+        found: String, // type of the value `?` is applied to
+        position: Position,
+        context: String,
+        suggestion: String,
+        example: String,
+    },
+    PropagateReturnMismatch {
+        returned: String, // what `?` returns early
+        expected: String, // declared return type of the function
+        position: Position,
+        context: String,
+        suggestion: String,
+        example: String,
+    },
+    InvalidInstantiation {
+        definition: String, // generic function or class used
+        type_arguments: String, // types given to its type parameters, as `T = bool`
+        line: usize, // line of the operation of its body the types do not support
+        position: Position, // where the definition is used
+        context: String,
+        suggestion: String,
+        example: String,
+    },
+    NotThrowable {
+        found: String, // type of the value thrown
+        position: Position, // of the value thrown
+        context: String,
+        suggestion: String,
+        example: String,
+    },
+    GenericCatch {
+        class: String, // generic class named by the clause
+        position: Position, // of the `tangkap` clause
+        context: String,
+        suggestion: String,
+        example: String,
+    },
+    NotCatchable {
+        name: String, // what the clause names instead of a class
+        position: Position, // of the `tangkap` clause
+        context: String,
+        suggestion: String,
+        example: String,
+    },
+    UnknownVariant {
+        name: String, // name the pattern matches fields of
+        position: Position, // of the pattern
+        context: String,
+        suggestion: String,
+        example: String,
+    },
 }
 
 impl std::fmt::Display for SemanticError {
@@ -245,279 +496,4608 @@ impl std::fmt::Display for SemanticError {
                 }
                 Ok(())
             }
-        }
-    }
-}
-
-impl std::error::Error for SemanticError {}
-
-/// Semantic analyzer
-pub struct SemanticAnalyzer {
-    symbol_table: SymbolTable,
-}
-
-impl SemanticAnalyzer {
-    /// Create a new semantic analyzer
-    pub fn new() -> Self {
-        SemanticAnalyzer {
-            symbol_table: SymbolTable::new(),
-        }
-    }
-
-    /// Analyze an AST
-    pub fn analyze(&mut self, ast: &ASTNode) -> Result<(), SemanticError> {
-        self.analyze_node(ast)
-    }
-
-    /// Analyze a node in the AST
-    fn analyze_node(&mut self, node: &ASTNode) -> Result<(), SemanticError> {
-        match node {
-            ASTNode::Program(statements) => {
-                for statement in statements {
-                    self.analyze_statement(statement)?;
+            SemanticError::WrongArgumentCount { name, expected, found, position, context, suggestion, example } => {
+                writeln!(f, "❌ Semantic error at line {}, column {}: '{}' takes {} argument(s) but {} were given",
+                       position.line, position.column, name, expected, found)?;
+                if !context.is_empty() {
+                    writeln!(f, "   💡 Context: {}", context)?;
+                }
+                if !suggestion.is_empty() {
+                    writeln!(f, "   💡 Tip: {}", suggestion)?;
+                }
+                if !example.is_empty() {
+                    writeln!(f, "   📘 Example:\n{}", example)?;
                 }
                 Ok(())
             }
-            ASTNode::BinaryOp { left, operator: _, right } => {
-                self.analyze_node(&left.node)?;
-                self.analyze_node(&right.node)?;
-                Ok(())
-            }
-            ASTNode::UnaryOp { operator: _, operand } => {
-                self.analyze_node(&operand.node)?;
+            SemanticError::InaccessibleMember { member, class, access, position, context, suggestion, example } => {
+                writeln!(f, "❌ Semantic error at line {}, column {}: '{}' of class '{}' is {}",
+                       position.line, position.column, member, class, access)?;
+                if !context.is_empty() {
+                    writeln!(f, "   💡 Context: {}", context)?;
+                }
+                if !suggestion.is_empty() {
+                    writeln!(f, "   💡 Tip: {}", suggestion)?;
+                }
+                if !example.is_empty() {
+                    writeln!(f, "   📘 Example:\n{}", example)?;
+                }
                 Ok(())
             }
-            ASTNode::Identifier(name) => {
-                // Check if identifier is declared
-                if self.symbol_table.lookup_symbol(name).is_none() {
-                    return Err(SemanticError::UndeclaredVariable {
-                        name: name.clone(),
-                        position: Position::start(), // This would be the actual position in a real implementation
-                    });
+            SemanticError::MissingInterfaceMethod { class, interface, method, position, context, suggestion, example } => {
+                writeln!(f, "❌ Semantic error at line {}, column {}: Class '{}' does not implement '{}' of interface '{}'",
+                       position.line, position.column, class, method, interface)?;
+                if !context.is_empty() {
+                    writeln!(f, "   💡 Context: {}", context)?;
+                }
+                if !suggestion.is_empty() {
+                    writeln!(f, "   💡 Tip: {}", suggestion)?;
+                }
+                if !example.is_empty() {
+                    writeln!(f, "   📘 Example:\n{}", example)?;
                 }
                 Ok(())
             }
-            ASTNode::Number(_) => Ok(()),
-            ASTNode::String(_) => Ok(()),
-            ASTNode::Boolean(_) => Ok(()),
-            ASTNode::FunctionCall { name, arguments } => {
-                // Check if function is declared
-                if self.symbol_table.lookup_symbol(name).is_none() {
-                    return Err(SemanticError::UndeclaredVariable {
-                        name: name.clone(),
-                        position: Position::start(), // This would be the actual position in a real implementation
-                    });
+            SemanticError::NonExhaustiveMatch { missing, position, context, suggestion, example } => {
+                writeln!(f, "❌ Semantic error at line {}, column {}: 'ketika' does not handle {}",
+                       position.line, position.column, missing)?;
+                if !context.is_empty() {
+                    writeln!(f, "   💡 Context: {}", context)?;
                 }
-
-                // Analyze arguments
-                for arg in arguments {
-                    self.analyze_node(&arg.node)?;
+                if !suggestion.is_empty() {
+                    writeln!(f, "   💡 Tip: {}", suggestion)?;
+                }
+                if !example.is_empty() {
+                    writeln!(f, "   📘 Example:\n{}", example)?;
                 }
                 Ok(())
             }
-            ASTNode::MemberAccess { object, property: _ } => {
-                self.analyze_node(&object.node)?;
+            SemanticError::UnreachablePattern { case, position, context, suggestion, example } => {
+                writeln!(f, "❌ Semantic error at line {}, column {}: {} can never run",
+                       position.line, position.column, case)?;
+                if !context.is_empty() {
+                    writeln!(f, "   💡 Context: {}", context)?;
+                }
+                if !suggestion.is_empty() {
+                    writeln!(f, "   💡 Tip: {}", suggestion)?;
+                }
+                if !example.is_empty() {
+                    writeln!(f, "   📘 Example:\n{}", example)?;
+                }
                 Ok(())
             }
-            _ => Ok(())
-        }
-    }
-
-    /// Analyze a statement
-    fn analyze_statement(&mut self, statement: &crate::parser::Statement) -> Result<(), SemanticError> {
-        match statement {
-            crate::parser::Statement::Declaration { identifier, value } => {
-                // Check for duplicate declaration
-                if self.symbol_table.lookup_symbol(identifier).is_some() {
-                    return Err(SemanticError::DuplicateDeclaration {
-                        name: identifier.clone(),
-                        first_position: Position::start(), // This would be the actual position in a real implementation
-                        duplicate_position: Position::start(), // This would be the actual position in a real implementation
-                    });
+            SemanticError::PossiblyNull { value, position, context, suggestion, example } => {
+                writeln!(f, "❌ Semantic error at line {}, column {}: '{}' may be nol here",
+                       position.line, position.column, value)?;
+                if !context.is_empty() {
+                    writeln!(f, "   💡 Context: {}", context)?;
+                }
+                if !suggestion.is_empty() {
+                    writeln!(f, "   💡 Tip: {}", suggestion)?;
+                }
+                if !example.is_empty() {
+                    writeln!(f, "   📘 Example:\n{}", example)?;
                 }
-
-                // Add to symbol table
-                let symbol = Symbol {
-                    name: identifier.clone(),
-                    symbol_type: SymbolType::Variable(VariableInfo {
-                        var_type: None,
-                        inferred_type: None,
-                        is_constant: false,
-                    }),
-                    is_initialized: true,
-                    is_used: false,
-                };
-                self.symbol_table.add_symbol(identifier.clone(), symbol);
-
-                // Analyze the value
-                self.analyze_node(value)?;
                 Ok(())
             }
-            crate::parser::Statement::Assignment { identifier, value } => {
-                // Check if variable is declared
-                if self.symbol_table.lookup_symbol(identifier).is_none() {
-                    return Err(SemanticError::UndeclaredVariable {
-                        name: identifier.clone(),
-                        position: Position::start(), // This would be the actual position in a real implementation
-                    });
+            SemanticError::ImmutableAssignment { name, declared_at, position, context, suggestion, example } => {
+                writeln!(f, "❌ Semantic error at line {}, column {}: '{}' cannot be assigned",
+                       position.line, position.column, name)?;
+                writeln!(f, "   Declared at line {}, column {}", declared_at.line, declared_at.column)?;
+                if !context.is_empty() {
+                    writeln!(f, "   💡 Context: {}", context)?;
+                }
+                if !suggestion.is_empty() {
+                    writeln!(f, "   💡 Tip: {}", suggestion)?;
+                }
+                if !example.is_empty() {
+                    writeln!(f, "   📘 Example:\n{}", example)?;
                 }
-
-                // Mark as used
-                // In a real implementation, we would update the symbol table
-
-                // Analyze the value
-                self.analyze_node(value)?;
                 Ok(())
             }
-            crate::parser::Statement::IfStatement { condition, then_block, else_block } => {
-                // Analyze condition
-                self.analyze_node(condition)?;
-
-                // Analyze then block
-                self.symbol_table.enter_scope(Position::start()); // This would be the actual position in a real implementation
-                for stmt in then_block {
-                    self.analyze_statement(stmt)?;
+            SemanticError::NotConstant { name, position, context, suggestion, example } => {
+                writeln!(f, "❌ Semantic error at line {}, column {}: The value of '{}' is not computed when compiling",
+                       position.line, position.column, name)?;
+                if !context.is_empty() {
+                    writeln!(f, "   💡 Context: {}", context)?;
                 }
-                self.symbol_table.exit_scope();
-
-                // Analyze else block if present
-                if let Some(else_block) = else_block {
-                    self.symbol_table.enter_scope(Position::start()); // This would be the actual position in a real implementation
-                    for stmt in else_block {
-                        self.analyze_statement(stmt)?;
-                    }
-                    self.symbol_table.exit_scope();
+                if !suggestion.is_empty() {
+                    writeln!(f, "   💡 Tip: {}", suggestion)?;
+                }
+                if !example.is_empty() {
+                    writeln!(f, "   📘 Example:\n{}", example)?;
                 }
-
                 Ok(())
             }
-            crate::parser::Statement::FunctionDef { name, parameters: _, body } => {
-                // Add function to symbol table
-                let symbol = Symbol {
-                    name: name.clone(),
-                    symbol_type: SymbolType::Function(FunctionSignature {
-                        parameters: vec![], // In a real implementation, we would populate this
-                        return_type: None,
-                    }),
-                    is_initialized: true,
-                    is_used: false,
-                };
-                self.symbol_table.add_symbol(name.clone(), symbol);
-
-                // Analyze function body
-                self.symbol_table.enter_scope(Position::start()); // This would be the actual position in a real implementation
-                for stmt in body {
-                    self.analyze_statement(stmt)?;
+            SemanticError::MissingReturn { function, returns, position, context, suggestion, example } => {
+                writeln!(f, "❌ Semantic error at line {}, column {}: {} may end without returning a value of type '{}'",
+                       position.line, position.column, function, returns)?;
+                if !context.is_empty() {
+                    writeln!(f, "   💡 Context: {}", context)?;
+                }
+                if !suggestion.is_empty() {
+                    writeln!(f, "   💡 Tip: {}", suggestion)?;
+                }
+                if !example.is_empty() {
+                    writeln!(f, "   📘 Example:\n{}", example)?;
                 }
-                self.symbol_table.exit_scope();
-
                 Ok(())
             }
-            crate::parser::Statement::ClassDef { name, body } => {
-                // Add class to symbol table
-                let symbol = Symbol {
-                    name: name.clone(),
-                    symbol_type: SymbolType::Class(ClassInfo {
-                        fields: HashMap::new(),
-                        methods: HashMap::new(),
+            SemanticError::OutsideLoop { keyword, position, context, suggestion, example } => {
+                writeln!(f, "❌ Semantic error at line {}, column {}: '{}' is not inside a loop",
+                       position.line, position.column, keyword)?;
+                if !context.is_empty() {
+                    writeln!(f, "   💡 Context: {}", context)?;
+                }
+                if !suggestion.is_empty() {
+                    writeln!(f, "   💡 Tip: {}", suggestion)?;
+                }
+                if !example.is_empty() {
+                    writeln!(f, "   📘 Example:\n{}", example)?;
+                }
+                Ok(())
+            }
+            SemanticError::UndeclaredMember { member, class, position, context, suggestion, example } => {
+                writeln!(f, "❌ Semantic error at line {}, column {}: Class '{}' has no member '{}'",
+                       position.line, position.column, class, member)?;
+                if !context.is_empty() {
+                    writeln!(f, "   💡 Context: {}", context)?;
+                }
+                if !suggestion.is_empty() {
+                    writeln!(f, "   💡 Tip: {}", suggestion)?;
+                }
+                if !example.is_empty() {
+                    writeln!(f, "   📘 Example:\n{}", example)?;
+                }
+                Ok(())
+            }
+            SemanticError::InheritanceCycle { class, chain: _, position, context, suggestion, example } => {
+                writeln!(f, "❌ Semantic error at line {}, column {}: Class '{}' inherits from itself",
+                       position.line, position.column, class)?;
+                if !context.is_empty() {
+                    writeln!(f, "   💡 Context: {}", context)?;
+                }
+                if !suggestion.is_empty() {
+                    writeln!(f, "   💡 Tip: {}", suggestion)?;
+                }
+                if !example.is_empty() {
+                    writeln!(f, "   📘 Example:\n{}", example)?;
+                }
+                Ok(())
+            }
+            SemanticError::SuperOutsideSubclass { position, context, suggestion, example } => {
+                writeln!(f, "❌ Semantic error at line {}, column {}: 'super' used outside a class that extends another",
+                       position.line, position.column)?;
+                if !context.is_empty() {
+                    writeln!(f, "   💡 Context: {}", context)?;
+                }
+                if !suggestion.is_empty() {
+                    writeln!(f, "   💡 Tip: {}", suggestion)?;
+                }
+                if !example.is_empty() {
+                    writeln!(f, "   📘 Example:\n{}", example)?;
+                }
+                Ok(())
+            }
+            SemanticError::InterfaceExtended { class, interface, position, context, suggestion, example } => {
+                writeln!(f, "❌ Semantic error at line {}, column {}: Class '{}' cannot extend interface '{}'",
+                       position.line, position.column, class, interface)?;
+                if !context.is_empty() {
+                    writeln!(f, "   💡 Context: {}", context)?;
+                }
+                if !suggestion.is_empty() {
+                    writeln!(f, "   💡 Tip: {}", suggestion)?;
+                }
+                if !example.is_empty() {
+                    writeln!(f, "   📘 Example:\n{}", example)?;
+                }
+                Ok(())
+            }
+            SemanticError::NotAnInterface { name, position, context, suggestion, example } => {
+                writeln!(f, "❌ Semantic error at line {}, column {}: '{}' is not an interface",
+                       position.line, position.column, name)?;
+                if !context.is_empty() {
+                    writeln!(f, "   💡 Context: {}", context)?;
+                }
+                if !suggestion.is_empty() {
+                    writeln!(f, "   💡 Tip: {}", suggestion)?;
+                }
+                if !example.is_empty() {
+                    writeln!(f, "   📘 Example:\n{}", example)?;
+                }
+                Ok(())
+            }
+            SemanticError::UnknownInterfaceMember { interface, member, position, context, suggestion, example } => {
+                writeln!(f, "❌ Semantic error at line {}, column {}: Interface '{}' has no method '{}'",
+                       position.line, position.column, interface, member)?;
+                if !context.is_empty() {
+                    writeln!(f, "   💡 Context: {}", context)?;
+                }
+                if !suggestion.is_empty() {
+                    writeln!(f, "   💡 Tip: {}", suggestion)?;
+                }
+                if !example.is_empty() {
+                    writeln!(f, "   📘 Example:\n{}", example)?;
+                }
+                Ok(())
+            }
+            SemanticError::InterfaceInstantiated { interface, position, context, suggestion, example } => {
+                writeln!(f, "❌ Semantic error at line {}, column {}: Interface '{}' cannot be instantiated",
+                       position.line, position.column, interface)?;
+                if !context.is_empty() {
+                    writeln!(f, "   💡 Context: {}", context)?;
+                }
+                if !suggestion.is_empty() {
+                    writeln!(f, "   💡 Tip: {}", suggestion)?;
+                }
+                if !example.is_empty() {
+                    writeln!(f, "   📘 Example:\n{}", example)?;
+                }
+                Ok(())
+            }
+            SemanticError::HiddenInterfaceMethod { class, interface, method, access, position, context, suggestion, example } => {
+                writeln!(f, "❌ Semantic error at line {}, column {}: Method '{}' of class '{}' is {} and cannot implement interface '{}'",
+                       position.line, position.column, method, class, access, interface)?;
+                if !context.is_empty() {
+                    writeln!(f, "   💡 Context: {}", context)?;
+                }
+                if !suggestion.is_empty() {
+                    writeln!(f, "   💡 Tip: {}", suggestion)?;
+                }
+                if !example.is_empty() {
+                    writeln!(f, "   📘 Example:\n{}", example)?;
+                }
+                Ok(())
+            }
+            SemanticError::PropagateOutsideFunction { position, context, suggestion, example } => {
+                writeln!(f, "❌ Semantic error at line {}, column {}: '?' used outside a function",
+                       position.line, position.column)?;
+                if !context.is_empty() {
+                    writeln!(f, "   💡 Context: {}", context)?;
+                }
+                if !suggestion.is_empty() {
+                    writeln!(f, "   💡 Tip: {}", suggestion)?;
+                }
+                if !example.is_empty() {
+                    writeln!(f, "   📘 Example:\n{}", example)?;
+                }
+                Ok(())
+            }
+            SemanticError::PropagateNonOptional { found, position, context, suggestion, example } => {
+                writeln!(f, "❌ Semantic error at line {}, column {}: '?' needs a value of an optional type or of 'Hasil', found '{}'",
+                       position.line, position.column, found)?;
+                if !context.is_empty() {
+                    writeln!(f, "   💡 Context: {}", context)?;
+                }
+                if !suggestion.is_empty() {
+                    writeln!(f, "   💡 Tip: {}", suggestion)?;
+                }
+                if !example.is_empty() {
+                    writeln!(f, "   📘 Example:\n{}", example)?;
+                }
+                Ok(())
+            }
+            SemanticError::PropagateReturnMismatch { returned, expected, position, context, suggestion, example } => {
+                writeln!(f, "❌ Semantic error at line {}, column {}: '?' returns '{}' early from a function declared to return '{}'",
+                       position.line, position.column, returned, expected)?;
+                if !context.is_empty() {
+                    writeln!(f, "   💡 Context: {}", context)?;
+                }
+                if !suggestion.is_empty() {
+                    writeln!(f, "   💡 Tip: {}", suggestion)?;
+                }
+                if !example.is_empty() {
+                    writeln!(f, "   📘 Example:\n{}", example)?;
+                }
+                Ok(())
+            }
+            SemanticError::InvalidInstantiation { definition, type_arguments, line: _, position, context, suggestion, example } => {
+                writeln!(f, "❌ Semantic error at line {}, column {}: '{}' cannot be used with {}",
+                       position.line, position.column, definition, type_arguments)?;
+                if !context.is_empty() {
+                    writeln!(f, "   💡 Context: {}", context)?;
+                }
+                if !suggestion.is_empty() {
+                    writeln!(f, "   💡 Tip: {}", suggestion)?;
+                }
+                if !example.is_empty() {
+                    writeln!(f, "   📘 Example:\n{}", example)?;
+                }
+                Ok(())
+            }
+            SemanticError::NotThrowable { found, position, context, suggestion, example } => {
+                writeln!(f, "❌ Semantic error at line {}, column {}: Cannot throw a value of type '{}'",
+                       position.line, position.column, found)?;
+                if !context.is_empty() {
+                    writeln!(f, "   💡 Context: {}", context)?;
+                }
+                if !suggestion.is_empty() {
+                    writeln!(f, "   💡 Tip: {}", suggestion)?;
+                }
+                if !example.is_empty() {
+                    writeln!(f, "   📘 Example:\n{}", example)?;
+                }
+                Ok(())
+            }
+            SemanticError::GenericCatch { class, position, context, suggestion, example } => {
+                writeln!(f, "❌ Semantic error at line {}, column {}: Cannot catch the errors of generic class '{}'",
+                       position.line, position.column, class)?;
+                if !context.is_empty() {
+                    writeln!(f, "   💡 Context: {}", context)?;
+                }
+                if !suggestion.is_empty() {
+                    writeln!(f, "   💡 Tip: {}", suggestion)?;
+                }
+                if !example.is_empty() {
+                    writeln!(f, "   📘 Example:\n{}", example)?;
+                }
+                Ok(())
+            }
+            SemanticError::NotCatchable { name, position, context, suggestion, example } => {
+                writeln!(f, "❌ Semantic error at line {}, column {}: '{}' is not a class",
+                       position.line, position.column, name)?;
+                if !context.is_empty() {
+                    writeln!(f, "   💡 Context: {}", context)?;
+                }
+                if !suggestion.is_empty() {
+                    writeln!(f, "   💡 Tip: {}", suggestion)?;
+                }
+                if !example.is_empty() {
+                    writeln!(f, "   📘 Example:\n{}", example)?;
+                }
+                Ok(())
+            }
+            SemanticError::UnknownVariant { name, position, context, suggestion, example } => {
+                writeln!(f, "❌ Semantic error at line {}, column {}: '{}' is not an enum variant",
+                       position.line, position.column, name)?;
+                if !context.is_empty() {
+                    writeln!(f, "   💡 Context: {}", context)?;
+                }
+                if !suggestion.is_empty() {
+                    writeln!(f, "   💡 Tip: {}", suggestion)?;
+                }
+                if !example.is_empty() {
+                    writeln!(f, "   📘 Example:\n{}", example)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for SemanticError {}
+
+impl SemanticError {
+    /// Stable code of the error, e.g. `K0201`
+    pub fn code(&self) -> &'static str {
+        match self {
+            SemanticError::UndeclaredVariable { .. } => "K0201",
+            SemanticError::DuplicateDeclaration { .. } => "K0202",
+            SemanticError::TypeMismatch { .. } => "K0203",
+            SemanticError::InvalidOperation { .. } => "K0204",
+            SemanticError::UninitializedVariable { .. } => "K0205",
+            SemanticError::WrongArgumentCount { .. } => "K0206",
+            SemanticError::InaccessibleMember { .. } => "K0207",
+            SemanticError::MissingInterfaceMethod { .. } => "K0208",
+            SemanticError::NonExhaustiveMatch { .. } => "K0209",
+            SemanticError::UnreachablePattern { .. } => "K0210",
+            SemanticError::PossiblyNull { .. } => "K0211",
+            SemanticError::ImmutableAssignment { .. } => "K0212",
+            SemanticError::NotConstant { .. } => "K0213",
+            SemanticError::MissingReturn { .. } => "K0214",
+            SemanticError::OutsideLoop { .. } => "K0215",
+            SemanticError::UndeclaredMember { .. } => "K0216",
+            SemanticError::InheritanceCycle { .. } => "K0217",
+            SemanticError::SuperOutsideSubclass { .. } => "K0218",
+            SemanticError::InterfaceExtended { .. } => "K0219",
+            SemanticError::NotAnInterface { .. } => "K0220",
+            SemanticError::UnknownInterfaceMember { .. } => "K0221",
+            SemanticError::InterfaceInstantiated { .. } => "K0222",
+            SemanticError::HiddenInterfaceMethod { .. } => "K0223",
+            SemanticError::PropagateOutsideFunction { .. } => "K0224",
+            SemanticError::PropagateNonOptional { .. } => "K0225",
+            SemanticError::PropagateReturnMismatch { .. } => "K0226",
+            SemanticError::InvalidInstantiation { .. } => "K0227",
+            SemanticError::NotThrowable { .. } => "K0228",
+            SemanticError::GenericCatch { .. } => "K0229",
+            SemanticError::NotCatchable { .. } => "K0230",
+            SemanticError::UnknownVariant { .. } => "K0231",
+        }
+    }
+
+    /// Where the error was found; for a duplicate declaration, the second one
+    pub fn position(&self) -> &Position {
+        match self {
+            SemanticError::UndeclaredVariable { position, .. }
+            | SemanticError::TypeMismatch { position, .. }
+            | SemanticError::InvalidOperation { position, .. }
+            | SemanticError::UninitializedVariable { position, .. }
+            | SemanticError::WrongArgumentCount { position, .. }
+            | SemanticError::InaccessibleMember { position, .. }
+            | SemanticError::MissingInterfaceMethod { position, .. }
+            | SemanticError::NonExhaustiveMatch { position, .. }
+            | SemanticError::UnreachablePattern { position, .. }
+            | SemanticError::PossiblyNull { position, .. }
+            | SemanticError::ImmutableAssignment { position, .. }
+            | SemanticError::NotConstant { position, .. }
+            | SemanticError::MissingReturn { position, .. }
+            | SemanticError::OutsideLoop { position, .. }
+            | SemanticError::UndeclaredMember { position, .. }
+            | SemanticError::InheritanceCycle { position, .. }
+            | SemanticError::SuperOutsideSubclass { position, .. }
+            | SemanticError::InterfaceExtended { position, .. }
+            | SemanticError::NotAnInterface { position, .. }
+            | SemanticError::UnknownInterfaceMember { position, .. }
+            | SemanticError::InterfaceInstantiated { position, .. }
+            | SemanticError::HiddenInterfaceMethod { position, .. }
+            | SemanticError::PropagateOutsideFunction { position, .. }
+            | SemanticError::PropagateNonOptional { position, .. }
+            | SemanticError::PropagateReturnMismatch { position, .. }
+            | SemanticError::InvalidInstantiation { position, .. }
+            | SemanticError::NotThrowable { position, .. }
+            | SemanticError::GenericCatch { position, .. }
+            | SemanticError::NotCatchable { position, .. }
+            | SemanticError::UnknownVariant { position, .. } => position,
+            SemanticError::DuplicateDeclaration { duplicate_position, .. } => duplicate_position,
+        }
+    }
+
+    /// Details that fill the placeholders of the catalog message
+    pub fn arguments(&self) -> Vec<(&'static str, String)> {
+        match self {
+            SemanticError::UndeclaredVariable { name, .. }
+            | SemanticError::DuplicateDeclaration { name, .. }
+            | SemanticError::UninitializedVariable { name, .. }
+            | SemanticError::ImmutableAssignment { name, .. }
+            | SemanticError::NotConstant { name, .. } => vec![("name", name.clone())],
+            SemanticError::TypeMismatch { expected, found, .. } => {
+                vec![("expected", expected.clone()), ("found", found.clone())]
+            }
+            SemanticError::InvalidOperation { message, .. } => vec![("message", message.clone())],
+            SemanticError::WrongArgumentCount { name, expected, found, .. } => vec![
+                ("name", name.clone()),
+                ("expected", expected.to_string()),
+                ("found", found.to_string()),
+            ],
+            SemanticError::InaccessibleMember { member, class, access, .. } => vec![
+                ("member", member.clone()),
+                ("class", class.clone()),
+                ("access", access.clone()),
+            ],
+            SemanticError::MissingInterfaceMethod { class, interface, method, .. } => vec![
+                ("class", class.clone()),
+                ("interface", interface.clone()),
+                ("method", method.clone()),
+            ],
+            SemanticError::NonExhaustiveMatch { missing, .. } => vec![("missing", missing.clone())],
+            SemanticError::UnreachablePattern { case, .. } => vec![("case", case.clone())],
+            SemanticError::PossiblyNull { value, .. } => vec![("value", value.clone())],
+            SemanticError::MissingReturn { function, returns, .. } => {
+                vec![("function", function.clone()), ("returns", returns.clone())]
+            }
+            SemanticError::OutsideLoop { keyword, .. } => vec![("keyword", keyword.clone())],
+            SemanticError::UndeclaredMember { member, class, .. } => vec![("member", member.clone()), ("class", class.clone())],
+            SemanticError::InheritanceCycle { class, chain, .. } => vec![("class", class.clone()), ("chain", chain.clone())],
+            SemanticError::SuperOutsideSubclass { .. } => Vec::new(),
+            SemanticError::InterfaceExtended { class, interface, .. } => vec![("class", class.clone()), ("interface", interface.clone())],
+            SemanticError::NotAnInterface { name, .. } => vec![("name", name.clone())],
+            SemanticError::UnknownInterfaceMember { interface, member, .. } => vec![("interface", interface.clone()), ("member", member.clone())],
+            SemanticError::InterfaceInstantiated { interface, .. } => vec![("interface", interface.clone())],
+            SemanticError::HiddenInterfaceMethod { class, interface, method, access, .. } => vec![("class", class.clone()), ("interface", interface.clone()), ("method", method.clone()), ("access", access.clone())],
+            SemanticError::PropagateOutsideFunction { .. } => Vec::new(),
+            SemanticError::PropagateNonOptional { found, .. } => vec![("found", found.clone())],
+            SemanticError::PropagateReturnMismatch { returned, expected, .. } => vec![("returned", returned.clone()), ("expected", expected.clone())],
+            SemanticError::InvalidInstantiation { definition, type_arguments, line, .. } => vec![("definition", definition.clone()), ("type_arguments", type_arguments.clone()), ("line", line.to_string())],
+            SemanticError::NotThrowable { found, .. } => vec![("found", found.clone())],
+            SemanticError::GenericCatch { class, .. } => vec![("class", class.clone())],
+            SemanticError::NotCatchable { name, .. } => vec![("name", name.clone())],
+            SemanticError::UnknownVariant { name, .. } => vec![("name", name.clone())],
+        }
+    }
+
+    /// Describe the error as a diagnostic pointing into `file`
+    pub fn to_diagnostic(&self, file: &SourceFile, language: Language) -> Diagnostic {
+        let (context, suggestion, example) = match self {
+            SemanticError::UndeclaredVariable { context, suggestion, example, .. }
+            | SemanticError::DuplicateDeclaration { context, suggestion, example, .. }
+            | SemanticError::TypeMismatch { context, suggestion, example, .. }
+            | SemanticError::InvalidOperation { context, suggestion, example, .. }
+            | SemanticError::UninitializedVariable { context, suggestion, example, .. }
+            | SemanticError::WrongArgumentCount { context, suggestion, example, .. }
+            | SemanticError::InaccessibleMember { context, suggestion, example, .. }
+            | SemanticError::MissingInterfaceMethod { context, suggestion, example, .. }
+            | SemanticError::NonExhaustiveMatch { context, suggestion, example, .. }
+            | SemanticError::UnreachablePattern { context, suggestion, example, .. }
+            | SemanticError::PossiblyNull { context, suggestion, example, .. }
+            | SemanticError::ImmutableAssignment { context, suggestion, example, .. }
+            | SemanticError::NotConstant { context, suggestion, example, .. }
+            | SemanticError::MissingReturn { context, suggestion, example, .. }
+            | SemanticError::OutsideLoop { context, suggestion, example, .. }
+            | SemanticError::UndeclaredMember { context, suggestion, example, .. }
+            | SemanticError::InheritanceCycle { context, suggestion, example, .. }
+            | SemanticError::SuperOutsideSubclass { context, suggestion, example, .. }
+            | SemanticError::InterfaceExtended { context, suggestion, example, .. }
+            | SemanticError::NotAnInterface { context, suggestion, example, .. }
+            | SemanticError::UnknownInterfaceMember { context, suggestion, example, .. }
+            | SemanticError::InterfaceInstantiated { context, suggestion, example, .. }
+            | SemanticError::HiddenInterfaceMethod { context, suggestion, example, .. }
+            | SemanticError::PropagateOutsideFunction { context, suggestion, example, .. }
+            | SemanticError::PropagateNonOptional { context, suggestion, example, .. }
+            | SemanticError::PropagateReturnMismatch { context, suggestion, example, .. }
+            | SemanticError::InvalidInstantiation { context, suggestion, example, .. }
+            | SemanticError::NotThrowable { context, suggestion, example, .. }
+            | SemanticError::GenericCatch { context, suggestion, example, .. }
+            | SemanticError::NotCatchable { context, suggestion, example, .. }
+            | SemanticError::UnknownVariant { context, suggestion, example, .. } => (context, suggestion, example),
+        };
+
+        let diagnostic = ErrorMessages::diagnostic(
+            self.code(),
+            &self.arguments(),
+            file.span_at(self.position()),
+            (context, suggestion, example),
+            language,
+        );
+        match self {
+            SemanticError::DuplicateDeclaration { first_position: declared_at, .. }
+            | SemanticError::ImmutableAssignment { declared_at, .. } => {
+                let secondary = ErrorMessages::get_message(self.code(), language)
+                    .map_or("", |message| message.secondary.as_str());
+                diagnostic.with_secondary(file.span_at(declared_at), secondary)
+            }
+            _ => diagnostic,
+        }
+    }
+}
+
+/// Problem the analyzer reports without rejecting the program
+#[derive(Debug, Clone)]
+pub enum SemanticWarning {
+    UnusedVariable {
+        name: String,
+        position: Position,
+        span: Option<Span>, // the declaration, when the analyzer knows where it ends
+        context: String,
+        suggestion: String,
+        example: String,
+    },
+    UnusedParameter {
+        name: String,
+        position: Position,
+        span: Option<Span>,
+        context: String,
+        suggestion: String,
+        example: String,
+    },
+    UnreachableCode {
+        position: Position, // Position of the first statement no path reaches
+        span: Option<Span>, // from that statement to the end of its block
+        context: String,
+        suggestion: String,
+        example: String,
+    },
+}
+
+impl std::fmt::Display for SemanticWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let (position, headline) = match self {
+            SemanticWarning::UnusedVariable { name, position, .. } => (position, format!("Variable '{}' is never read", name)),
+            SemanticWarning::UnusedParameter { name, position, .. } => (position, format!("Parameter '{}' is never read", name)),
+            SemanticWarning::UnreachableCode { position, .. } => (position, "Unreachable code".to_string()),
+        };
+        writeln!(f, "⚠️ Warning at line {}, column {}: {}", position.line, position.column, headline)?;
+        let (context, suggestion, example) = self.details();
+        if !context.is_empty() {
+            writeln!(f, "   💡 Context: {}", context)?;
+        }
+        if !suggestion.is_empty() {
+            writeln!(f, "   💡 Tip: {}", suggestion)?;
+        }
+        if !example.is_empty() {
+            writeln!(f, "   📘 Example:\n{}", example)?;
+        }
+        Ok(())
+    }
+}
+
+impl SemanticWarning {
+    /// Stable code of the warning, e.g. `K0401`
+    pub fn code(&self) -> &'static str {
+        match self {
+            SemanticWarning::UnusedVariable { .. } => "K0401",
+            SemanticWarning::UnusedParameter { .. } => "K0402",
+            SemanticWarning::UnreachableCode { .. } => "K0403",
+        }
+    }
+
+    /// Where the warning was found
+    pub fn position(&self) -> &Position {
+        match self {
+            SemanticWarning::UnusedVariable { position, .. }
+            | SemanticWarning::UnusedParameter { position, .. }
+            | SemanticWarning::UnreachableCode { position, .. } => position,
+        }
+    }
+
+    /// Details that fill the placeholders of the catalog message
+    pub fn arguments(&self) -> Vec<(&'static str, String)> {
+        match self {
+            SemanticWarning::UnusedVariable { name, .. } | SemanticWarning::UnusedParameter { name, .. } => {
+                vec![("name", name.clone())]
+            }
+            SemanticWarning::UnreachableCode { .. } => Vec::new(),
+        }
+    }
+
+    fn details(&self) -> (&str, &str, &str) {
+        match self {
+            SemanticWarning::UnusedVariable { context, suggestion, example, .. }
+            | SemanticWarning::UnusedParameter { context, suggestion, example, .. }
+            | SemanticWarning::UnreachableCode { context, suggestion, example, .. } => (context, suggestion, example),
+        }
+    }
+
+    /// Describe the warning as a diagnostic pointing into `file`
+    pub fn to_diagnostic(&self, file: &SourceFile, language: Language) -> Diagnostic {
+        let span = match self {
+            SemanticWarning::UnusedVariable { span: Some(span), .. }
+            | SemanticWarning::UnusedParameter { span: Some(span), .. }
+            | SemanticWarning::UnreachableCode { span: Some(span), .. } => *span,
+            _ => file.span_at(self.position()),
+        };
+        ErrorMessages::diagnostic(self.code(), &self.arguments(), span, self.details(), language)
+            .with_severity(Severity::Warning)
+    }
+}
+
+/// Names the runtime provides without a declaration
+const BUILTIN_NAMES: &[&str] = &[
+    "tampilkan", "print", "masukan", "input", "panjang", "len",
+    "int", "float", "string", "str", "bool", "integer", "desimal", "teks", "boolean",
+    "angka", "number", "daftar", "list", "objek", "object", "array",
+    "super", "induk", "tidak_ada",
+];
+
+/// Functions of the core standard library, called without a declaration and resolved against the
+/// runtime library when linking; the runtime checks their arguments
+const RUNTIME_FUNCTIONS: &[&str] = &[
+    "panjang_string", "string_length", "gabung_string", "concat_string",
+    "akar_kuadrat", "square_root", "nilai_mutlak", "absolute_value",
+    "panjang_array", "array_length", "keluar", "exit",
+    "baca_input", "ke_huruf_besar", "acak", "tambah_item", "panjang_daftar",
+    "show", "cetak",
+    "buat_mutex", "create_mutex", "make_mutex", "buat_kondisi", "create_condition", "make_condition",
+    "tukar_atomik", "atomic_exchange", "banding_tukar_atomik", "atomic_compare_exchange",
+    "ambil_tambah_atomik", "atomic_fetch_add", "ambil_kurang_atomik", "atomic_fetch_sub",
+];
+
+/// Type of a builtin name; the runtime checks the arguments of builtins
+fn builtin_type(name: &str) -> Type {
+    let returning = |return_type| Type::Function {
+        parameters: vec![Type::Any],
+        return_type: Box::new(return_type),
+        variadic: true,
+    };
+    match name {
+        "tampilkan" | "print" => returning(Type::Void),
+        "masukan" | "input" | "str" | "string" | "teks" => returning(Type::String),
+        "panjang" | "len" | "int" | "integer" => returning(Type::Int),
+        "float" | "desimal" | "angka" | "number" => returning(Type::Float),
+        "bool" | "boolean" => returning(Type::Bool),
+        "daftar" | "list" | "array" => returning(Type::List(Box::new(Type::Any))),
+        _ => Type::Any,
+    }
+}
+
+/// Names of the type parameters `ty` mentions
+fn parameters_in(ty: &Type) -> Vec<String> {
+    let mut names = Vec::new();
+    ty.transform(&mut |ty| {
+        if let Type::Parameter(name) = ty {
+            names.push(name.clone());
+        }
+        None
+    });
+    names
+}
+
+/// Check whether values of the type are plain data, so that operators on them can be checked
+fn is_concrete(ty: &Type) -> bool {
+    matches!(ty, Type::Int | Type::Float | Type::Bool | Type::String | Type::Void | Type::List(_) | Type::Map(_, _))
+}
+
+/// Suggestion and example for turning a value of type `found` into one of type `expected`
+fn conversion(expected: &Type, found: &Type, value: Option<&ASTNode>) -> (String, String) {
+    let (function, target, example) = match expected.non_optional() {
+        Type::String => ("str", "a string", "   buat pesan = \"Jumlah: \" + str(jumlah)"),
+        Type::Int => ("int", "an int", "   buat umur = int(\"25\") + 1"),
+        Type::Float => ("float", "a float", "   buat harga = float(\"9.5\") * 2"),
+        Type::Bool => ("bool", "a bool", "   buat aktif = bool(1)"),
+        _ => {
+            return (
+                format!("Use a value of type '{}' here, or change the declared type", expected),
+                "   buat jumlah: int = 5\n   jumlah = jumlah + 1".to_string(),
+            );
+        }
+    };
+    let argument = match value {
+        Some(ASTNode::Identifier(name)) => name.as_str(),
+        _ => "...",
+    };
+    (
+        format!("Call {}({}) to convert the {} to {}", function, argument, found, target),
+        example.to_string(),
+    )
+}
+
+/// Example shown for errors in the variants of an enum and the cases that match them
+const MATCH_EXAMPLE: &str = "   tipe Bentuk:\n       Lingkaran(jari: float)\n       Kosong\n   ketika bentuk:\n       kasus Lingkaran(r):\n           tampilkan r\n       kasus Kosong:\n           tampilkan \"kosong\"";
+
+/// Example shown for errors in `lempar` statements and `tangkap` clauses
+const TRY_EXAMPLE: &str = "   kelas GalatBagi:\n       buat pesan = \"pembagian dengan nol\"\n   coba:\n       lempar GalatBagi()\n   tangkap (e: GalatBagi):\n       tampilkan e.pesan\n   akhirnya:\n       tampilkan \"selesai\"";
+
+/// Example shown for errors in uses of values that may be `nol`
+const NULL_EXAMPLE: &str = "   fungsi sapa(pengguna: Pengguna?):\n       jika pengguna == nol:\n           kembalikan\n       tampilkan pengguna.nama\n       tampilkan pengguna?.alamat?.kota ?: \"-\"";
+
+/// Example shown for errors in uses of `?`
+const PROPAGATE_EXAMPLE: &str = "   fungsi baca_angka(teks: string): Hasil<int, string>:\n       jika teks == \"\":\n           kembalikan Gagal(\"teks kosong\")\n       kembalikan Sukses(int(teks))\n   fungsi jumlah(a: string, b: string): Hasil<int, string>:\n       kembalikan Sukses(baca_angka(a)? + baca_angka(b)?)";
+
+/// Example shown for assignments to bindings that cannot change
+const MUTABLE_EXAMPLE: &str = "   biarkan mut jumlah = 0\n   jumlah = jumlah + 1\n   fungsi hitung_mundur(mut n):\n       n = n - 1";
+
+/// Example shown for `konstan` values that are not computed when compiling
+const CONSTANT_EXAMPLE: &str = "   konstan DETIK_PER_HARI = 24 * 60 * 60\n   biarkan sekarang = waktu()";
+
+/// Definitions every program can use, declared with the builtins
+const PRELUDE: &str = "tipe Hasil<T, E>:\n    Sukses(T)\n    Gagal(E)\n";
+
+/// Id the analyzer gave a symbol it declared
+fn symbol_id(symbol: &Symbol) -> SymbolId {
+    symbol.id.expect("the analyzer gives every symbol it declares an id")
+}
+
+/// Type of the instances of class `class` in its own body, where its type parameters stand for themselves
+fn instance_type(class: &str, info: &ClassInfo) -> Type {
+    Type::Named {
+        name: class.to_string(),
+        arguments: info.type_parameters.iter().map(|(name, _)| Type::Parameter(name.clone())).collect(),
+    }
+}
+
+/// Pattern the exhaustiveness check sees for `pattern`
+fn checked_pattern(pattern: &hir::Pattern) -> patterns::Pattern {
+    match &pattern.kind {
+        hir::PatternKind::Wildcard | hir::PatternKind::Binding(_) => patterns::Pattern::Wildcard,
+        hir::PatternKind::Literal(LiteralPattern::Integer(value)) => patterns::Pattern::Range(*value, *value),
+        hir::PatternKind::Literal(LiteralPattern::Boolean(value)) => patterns::Pattern::Bool(*value),
+        hir::PatternKind::Literal(literal) => patterns::Pattern::Constant(literal.to_string()),
+        hir::PatternKind::Range { start, end } => patterns::Pattern::Range(*start, *end),
+        hir::PatternKind::Tuple(elements) => patterns::Pattern::Tuple(elements.iter().map(checked_pattern).collect()),
+        hir::PatternKind::Variant { index, fields, .. } => {
+            patterns::Pattern::Variant(*index, fields.iter().map(checked_pattern).collect())
+        }
+    }
+}
+
+/// Check whether the last statement of a block always leaves it, so the statements after the block are
+/// only reached from its other branches
+fn always_exits(statements: &[Statement]) -> bool {
+    match statements.last().map(|statement| &statement.node) {
+        Some(ASTNode::ReturnStmt(_) | ASTNode::ThrowStmt(_) | ASTNode::BreakStmt | ASTNode::ContinueStmt) => true,
+        Some(ASTNode::IfStatement { then_block, else_block: Some(else_block), .. }) => {
+            always_exits(then_block) && always_exits(else_block)
+        }
+        _ => false,
+    }
+}
+
+/// Blocks of the statements nested in `statement`, such as the branches of a `jika`
+fn nested_blocks(statement: &Statement) -> Vec<&[Statement]> {
+    match &statement.node {
+        ASTNode::IfStatement { then_block, else_block, .. } => {
+            vec![then_block, else_block.as_deref().unwrap_or_default()]
+        }
+        ASTNode::WhileLoop { body, .. }
+        | ASTNode::DoWhileLoop { body, .. }
+        | ASTNode::ForLoop { body, .. }
+        | ASTNode::ForEachLoop { body, .. }
+        | ASTNode::GoStmt { body } => vec![body],
+        ASTNode::TryCatch { try_block, catches, finally_block } => {
+            let mut blocks = vec![try_block.as_slice()];
+            blocks.extend(catches.iter().map(|catch| catch.body.as_slice()));
+            blocks.push(finally_block.as_deref().unwrap_or_default());
+            blocks
+        }
+        ASTNode::WhenStmt { cases, else_case, .. } => {
+            let mut blocks: Vec<&[Statement]> = cases.iter().map(|case| case.body.as_slice()).collect();
+            blocks.push(else_case.as_deref().unwrap_or_default());
+            blocks
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Names assigned by `statements` and the blocks nested in them
+fn assigned_names<'a>(statements: &'a [Statement], names: &mut HashSet<&'a str>) {
+    for statement in statements {
+        if let ASTNode::Assignment { identifier, .. } = &statement.node {
+            names.insert(identifier);
+        }
+        for block in nested_blocks(statement) {
+            assigned_names(block, names);
+        }
+    }
+}
+
+/// Names assigned in the bodies of the functions and methods `statements` define, at any depth
+fn names_assigned_by_functions<'a>(statements: &'a [Statement], names: &mut HashSet<&'a str>) {
+    for statement in statements {
+        match &statement.node {
+            ASTNode::FunctionDef { body, .. } => {
+                assigned_names(body, names);
+                names_assigned_by_functions(body, names);
+            }
+            ASTNode::ClassDef { body, .. } => names_assigned_by_functions(body, names),
+            _ => {}
+        }
+        for block in nested_blocks(statement) {
+            names_assigned_by_functions(block, names);
+        }
+    }
+}
+
+/// Fields of `ini` assigned by `statements` and the blocks nested in them
+fn assigned_fields(statements: &[Statement], fields: &mut HashSet<String>) {
+    for statement in statements {
+        if let ASTNode::MemberAssignment { object, property, .. } = &statement.node {
+            if matches!(object.node, ASTNode::SelfRef) {
+                fields.insert(property.clone());
+            }
+        }
+        for block in nested_blocks(statement) {
+            assigned_fields(block, fields);
+        }
+    }
+}
+
+/// How an error names the value written as `node`: a variable, or a member of one
+fn describe(node: &ASTNode) -> Option<String> {
+    match node {
+        ASTNode::Identifier(name) => Some(name.clone()),
+        ASTNode::SelfRef => Some("ini".to_string()),
+        ASTNode::MemberAccess { object, property } => Some(format!("{}.{}", describe(&object.node)?, property)),
+        ASTNode::FunctionCall { name, .. } => Some(format!("{}(...)", name)),
+        ASTNode::MethodCall { object, method, .. } => Some(format!("{}.{}(...)", describe(&object.node)?, method)),
+        _ => None,
+    }
+}
+
+/// HIR of a quantum or neural network statement, which the later stages do not compile yet
+fn unsupported(construct: &'static str, symbol: Option<SymbolId>, operands: Vec<hir::Expression>, body: Vec<hir::Statement>) -> hir::StatementKind {
+    hir::StatementKind::Unsupported { construct, symbol, operands, body }
+}
+
+/// Return type of a function whose body is being analyzed
+struct ReturnType {
+    expected: Type,
+    annotated: bool, // declared in the source, so each `kembalikan` is checked against it
+    found: Vec<Type>, // types returned by an unannotated function, reconciled at the end of its body
+    first_symbol: usize, // symbols from this index on are the parameters and variables of the function
+}
+
+/// Lambda whose body is being analyzed
+struct Closure {
+    first_symbol: usize, // symbols from this index on are declared in the lambda
+    captures: Vec<SymbolId>, // variables of enclosing functions the lambda uses, in order of first use
+    captures_self: bool, // uses `ini` of the enclosing method
+}
+
+/// Operation of a generic body on values of its type parameters, checked again for each use of the definition
+struct GenericOperation {
+    definition: SymbolId, // generic function or class whose body has the operation
+    left: Type,
+    operator: BinaryOperator,
+    right: Type,
+    operands: [(Position, Span); 2], // where the left and the right operand are
+}
+
+/// Use of a generic definition, with the types inferred for its type parameters
+struct Instantiation {
+    definition: SymbolId,
+    within: Option<SymbolId>, // generic definition whose body has the use, whose type parameters the types may name
+    type_arguments: Vec<(String, Type)>,
+    position: Position,
+}
+
+/// Semantic analyzer
+pub struct SemanticAnalyzer {
+    symbol_table: SymbolTable,
+    symbols: Vec<SymbolInfo>, // every symbol declared so far, indexed by `SymbolId`
+    open_imports: bool, // an `impor` without alias brings in names this analyzer does not resolve
+    unifier: Unifier, // type variables of the program and what they were inferred to be
+    return_types: Vec<ReturnType>, // return type of each enclosing function
+    current_class: Option<(String, usize)>, // class whose body is analyzed, and the scope of that body
+    type_parameters: Vec<(String, Option<Type>)>, // type parameters of the generic definitions being analyzed, with their bounds
+    generic_definitions: Vec<(SymbolId, Vec<String>)>, // generic functions and classes whose body is being analyzed, innermost last
+    generic_operations: Vec<GenericOperation>, // operations of generic bodies on values of their type parameters
+    instantiations: Vec<Instantiation>, // uses of generic definitions in the program
+    closures: Vec<Closure>, // lambdas whose body is being analyzed, innermost last
+    assigned: HashSet<SymbolId>, // variables assigned after their declaration
+    lambda_parameters: Option<Vec<Type>>, // types the next lambda analyzed takes for its parameters without annotation
+    non_null: HashSet<SymbolId>, // optional variables proved not to be `nol` where the analysis is
+    assigned_by_functions: HashSet<String>, // names the functions and methods of the program assign, which calls may change
+    first_program_symbol: usize, // symbols before this index are the builtins and the prelude
+    constants: HashMap<SymbolId, hir::Constant>, // value of each `konstan`
+    prelude: Vec<hir::Statement>, // definitions of the prelude, added to the program analyzed
+    result_enum: Option<SymbolId>, // `Hasil` of the prelude, which `?` returns the errors of
+    warnings: Vec<SemanticWarning>, // found in the last program analyzed
+    loops: usize, // loops around the statement analyzed, inside the innermost function
+}
+
+impl Default for SemanticAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SemanticAnalyzer {
+    /// Create a new semantic analyzer
+    pub fn new() -> Self {
+        let mut symbol_table = SymbolTable::new();
+        let mut symbols = Vec::new();
+        for name in BUILTIN_NAMES {
+            symbol_table.add_symbol(name.to_string(), Symbol {
+                name: name.to_string(),
+                symbol_type: SymbolType::Function(FunctionSignature {
+                    parameters: vec![],
+                    return_type: None,
+                    inferred_type: Some(builtin_type(name)),
+                    type_parameters: Vec::new(),
+                    position: Position::start(),
+                }),
+                is_initialized: true,
+                is_used: false,
+                position: Position::start(),
+                id: Some(SymbolId(symbols.len())),
+            });
+            symbols.push(SymbolInfo {
+                name: name.to_string(),
+                kind: SymbolKind::Builtin,
+                ty: builtin_type(name),
+                position: Position::start(),
+                captured: None,
+            });
+        }
+
+        let mut analyzer = SemanticAnalyzer {
+            symbol_table,
+            symbols,
+            open_imports: false,
+            unifier: Unifier::new(),
+            return_types: Vec::new(),
+            current_class: None,
+            type_parameters: Vec::new(),
+            generic_definitions: Vec::new(),
+            generic_operations: Vec::new(),
+            instantiations: Vec::new(),
+            closures: Vec::new(),
+            assigned: HashSet::new(),
+            lambda_parameters: None,
+            non_null: HashSet::new(),
+            assigned_by_functions: HashSet::new(),
+            first_program_symbol: 0,
+            constants: HashMap::new(),
+            prelude: Vec::new(),
+            result_enum: None,
+            warnings: Vec::new(),
+            loops: 0,
+        };
+        analyzer.declare_prelude();
+        analyzer.first_program_symbol = analyzer.symbols.len();
+        // The program gets a scope of its own, so its definitions may shadow the builtins and the prelude
+        analyzer.symbol_table.enter_scope(Position::start());
+        analyzer
+    }
+
+    /// Declare the definitions of the prelude next to the builtins
+    fn declare_prelude(&mut self) {
+        let ast = Parser::new(PRELUDE).parse_program().into_result().expect("the prelude parses");
+        let ASTNode::Program(statements) = ast else {
+            unreachable!("parse_program returns a program");
+        };
+        self.prelude = self.analyze_statements(&statements).expect("the prelude is well-typed");
+        self.result_enum = self.symbol_table.lookup_symbol("Hasil").map(symbol_id);
+    }
+
+    /// Symbols collected by the last analysis
+    pub fn symbol_table(&self) -> &SymbolTable {
+        &self.symbol_table
+    }
+
+    /// Analyze an AST and lower it to the typed HIR
+    pub fn analyze(&mut self, ast: &ASTNode) -> Result<hir::Program, SemanticError> {
+        match ast {
+            ASTNode::Program(statements) => {
+                let mut names = HashSet::new();
+                names_assigned_by_functions(statements, &mut names);
+                self.assigned_by_functions = names.into_iter().map(str::to_string).collect();
+                let mut body = self.analyze_statements(statements)?;
+                // The prelude goes last, so the statements of the program keep their indexes
+                body.append(&mut self.prelude);
+                self.resolve_types();
+                self.check_instantiations()?;
+                // Closures share a variable with its function if either assigns it after its declaration
+                for symbol in &self.assigned {
+                    let info = &mut self.symbols[symbol.0];
+                    if info.captured.is_some() {
+                        info.captured = Some(hir::Capture::ByReference);
+                    }
+                }
+                let mut program = hir::Program { body, symbols: self.symbols.clone() };
+                let unifier = &self.unifier;
+                program.visit_types_mut(&mut |ty| *ty = unifier.finish(ty));
+                // Reads before assignment and dead code are found on the control-flow graph of each body
+                let report = flow::analyze(&program);
+                if let Some(error) = report.errors.into_iter().next() {
+                    return Err(error);
+                }
+                self.warnings = report.warnings;
+                Ok(program)
+            }
+            _ => Err(SemanticError::InvalidOperation {
+                message: "Expected a program".to_string(),
+                position: Position::start(),
+                context: "Semantic analysis starts at the root of the tree returned by parse_program".to_string(),
+                suggestion: "Pass the whole program instead of a single node".to_string(),
+                example: String::new(),
+            }),
+        }
+    }
+
+    /// Warnings about the last program analyzed, in source order
+    pub fn warnings(&self) -> &[SemanticWarning] {
+        &self.warnings
+    }
+
+    /// Replace the type variables in the symbol table by the types inferred for them
+    fn resolve_types(&mut self) {
+        let unifier = &self.unifier;
+        let finish = |ty: &mut Option<Type>| {
+            if let Some(ty) = ty {
+                *ty = unifier.finish(ty);
+            }
+        };
+        for scope in &mut self.symbol_table.scopes {
+            for symbol in scope.symbols.values_mut() {
+                match &mut symbol.symbol_type {
+                    SymbolType::Variable(info) | SymbolType::Parameter(info) => finish(&mut info.inferred_type),
+                    SymbolType::Function(signature) => finish(&mut signature.inferred_type),
+                    SymbolType::Class(info) => {
+                        for field in info.fields.values_mut() {
+                            finish(&mut field.inferred_type);
+                        }
+                        for method in info.methods.values_mut() {
+                            finish(&mut method.inferred_type);
+                        }
+                    }
+                    SymbolType::Enum(info) => {
+                        for (_, ty) in info.variants.iter_mut().flat_map(|variant| &mut variant.fields) {
+                            *ty = unifier.finish(ty);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Declare the functions and classes of a block before its statements, so they can be used before their definition
+    fn declare_definitions(&mut self, statements: &[Statement]) -> Result<(), SemanticError> {
+        for statement in statements {
+            match &statement.node {
+                ASTNode::FunctionDef { name, type_parameters, parameters, return_type, variadic, .. } => {
+                    // A method shares its signature with its class, so calls on instances see what its body infers
+                    let signature = match self.class_method(name, &statement.position) {
+                        Some(signature) => signature,
+                        None => self.signature(type_parameters, parameters, return_type, *variadic, &statement.position),
+                    };
+                    let symbol = self.declare(name, SymbolType::Function(signature), true, &statement.position)?;
+                    if let Some(class) = self.current_class_symbol() {
+                        let is_static = matches!(statement.node, ASTNode::FunctionDef { is_static: true, .. });
+                        self.symbols[symbol.0].kind = SymbolKind::Method { class, is_static };
+                    }
+                }
+                ASTNode::ClassDef { name, type_parameters, parent_class, interfaces, body, .. } => {
+                    if let Some(parent) = parent_class {
+                        self.unifier.add_superclass(name, parent);
+                    }
+                    for interface in interfaces {
+                        self.unifier.add_interface(name, interface);
+                    }
+                    let type_parameters = self.type_parameter_types(type_parameters);
+                    let mut info = ClassInfo {
+                        fields: HashMap::new(),
+                        methods: HashMap::new(),
+                        parent: parent_class.clone(),
+                        interfaces: interfaces.clone(),
+                        access: HashMap::new(),
+                        assigned_fields: HashSet::new(),
+                        is_interface: false,
+                        type_parameters: type_parameters.clone(),
+                        position: statement.position.clone(),
+                    };
+                    // Fields and methods of a generic class are typed in terms of its type parameters
+                    self.with_type_parameters(&type_parameters, |analyzer| {
+                        for member in body {
+                            let (member_name, access_modifier) = match &member.node {
+                                ASTNode::Declaration { identifier, type_annotation, mutable, access_modifier, .. } => {
+                                    info.fields.insert(identifier.clone(), VariableInfo {
+                                        var_type: type_annotation.clone(),
+                                        inferred_type: Some(analyzer.annotated_or_fresh(type_annotation)),
+                                        is_constant: !mutable,
+                                    });
+                                    (identifier, access_modifier)
+                                }
+                                ASTNode::FunctionDef { name, type_parameters, parameters, return_type, variadic, access_modifier, body, .. } => {
+                                    let signature = analyzer.signature(type_parameters, parameters, return_type, *variadic, &member.position);
+                                    info.methods.insert(name.clone(), signature);
+                                    assigned_fields(body, &mut info.assigned_fields);
+                                    (name, access_modifier)
+                                }
+                                _ => continue,
+                            };
+                            if let Some(access_modifier) = access_modifier {
+                                info.access.insert(member_name.clone(), access_modifier.clone());
+                            }
+                        }
+                    });
+                    self.declare(name, SymbolType::Class(info), true, &statement.position)?;
+                }
+                ASTNode::InterfaceDef { name, methods, .. } => {
+                    let mut info = ClassInfo {
+                        fields: HashMap::new(),
+                        methods: HashMap::new(),
+                        parent: None,
+                        interfaces: Vec::new(),
+                        access: HashMap::new(),
+                        assigned_fields: HashSet::new(),
+                        is_interface: true,
+                        type_parameters: Vec::new(),
+                        position: statement.position.clone(),
+                    };
+                    for method in methods {
+                        if let ASTNode::FunctionDef { name, type_parameters, parameters, return_type, variadic, .. } = &method.node {
+                            let signature = self.signature(type_parameters, parameters, return_type, *variadic, &method.position);
+                            info.methods.insert(name.clone(), signature);
+                        }
+                    }
+                    self.declare(name, SymbolType::Class(info), true, &statement.position)?;
+                }
+                ASTNode::EnumDef { name, type_parameters, variants, .. } => {
+                    let type_parameters = self.type_parameter_types(type_parameters);
+                    let variants: Vec<VariantInfo> = self.with_type_parameters(&type_parameters, |analyzer| {
+                        variants.iter()
+                            .map(|variant| VariantInfo {
+                                name: variant.name.clone(),
+                                fields: variant.fields.iter()
+                                    .map(|field| (field.name.clone(), analyzer.annotation_type(&field.field_type)))
+                                    .collect(),
+                                position: variant.position.clone(),
+                            })
+                            .collect()
+                    });
+                    let info = EnumInfo {
+                        variants: variants.clone(),
+                        type_parameters: type_parameters.clone(),
+                        position: statement.position.clone(),
+                    };
+                    let enumeration = self.declare(name, SymbolType::Enum(info), true, &statement.position)?;
+
+                    // Each variant is a name of its own, called with its fields to build a value of the enum
+                    let enum_type = self.symbols[enumeration.0].ty.clone();
+                    for (index, variant) in variants.into_iter().enumerate() {
+                        let signature = FunctionSignature {
+                            parameters: variant.fields.iter().enumerate()
+                                .map(|(index, (name, _))| Parameter {
+                                    name: name.clone().unwrap_or_else(|| index.to_string()),
+                                    param_type: None,
+                                    position: variant.position.clone(),
+                                })
+                                .collect(),
+                            return_type: None,
+                            inferred_type: Some(Type::function(
+                                variant.fields.into_iter().map(|(_, ty)| ty).collect(),
+                                enum_type.clone(),
+                            )),
+                            type_parameters: type_parameters.clone(),
+                            position: variant.position.clone(),
+                        };
+                        let symbol = self.declare(&variant.name, SymbolType::Function(signature), true, &variant.position)?;
+                        self.symbols[symbol.0].kind = SymbolKind::Variant { enumeration, index };
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // A class may not be its own ancestor, or its fields and constructor would contain themselves
+        for statement in statements {
+            let ASTNode::ClassDef { name, parent_class: Some(parent), .. } = &statement.node else {
+                continue;
+            };
+            let mut chain = vec![name.clone()];
+            let mut current = Some(parent.clone());
+            while let Some(class) = current {
+                if chain.contains(&class) {
+                    if class != *name {
+                        break; // a cycle above this class, reported at the classes in it
+                    }
+                    chain.push(class);
+                    return Err(SemanticError::InheritanceCycle {
+                        class: name.clone(),
+                        chain: chain.join(" -> "),
+                        position: statement.position.clone(),
+                        context: format!("Its parent classes lead back to it: {}", chain.join(" -> ")),
+                        suggestion: format!("Remove '{}' from the parent classes of its ancestors, or extend another class", name),
+                        example: "kelas Hewan:\n    buat nama: teks\n\nkelas Kucing(Hewan):\n    buat warna: teks".to_string(),
+                    });
+                }
+                current = self.class_info(&class).and_then(|info| info.parent.clone());
+                chain.push(class);
+            }
+        }
+
+        // With every class of the block declared, methods can be matched with the methods they override
+        // and the interfaces they implement
+        for statement in statements {
+            let ASTNode::ClassDef { name, parent_class, interfaces, body, .. } = &statement.node else {
+                continue;
+            };
+            if let Some(parent) = parent_class {
+                if self.class_info(parent).is_some_and(|info| info.is_interface) {
+                    return Err(SemanticError::InterfaceExtended {
+                        class: name.clone(),
+                        interface: parent.clone(),
+                        position: statement.position.clone(),
+                        context: "A class extends another class, and implements interfaces".to_string(),
+                        suggestion: format!("Write 'kelas {} implementasi {}'", name, parent),
+                        example: "kelas Persegi implementasi Bentuk:\n    fungsi luas(): float:\n        kembalikan 1.0".to_string(),
+                    });
+                }
+                for member in body {
+                    if let ASTNode::FunctionDef { name: method, is_static: false, .. } = &member.node {
+                        self.check_override(name, parent, method, &member.position)?;
+                    }
+                }
+            }
+            for interface in interfaces {
+                self.check_conformance(name, interface, &statement.position)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that class `class`, defined at `position`, has every method of interface `interface`, taking
+    /// and returning the types the interface declares
+    fn check_conformance(&mut self, class: &str, interface: &str, position: &Position) -> Result<(), SemanticError> {
+        let Some(info) = self.class_info(interface) else {
+            return Ok(()); // reported as an undeclared name when the class is analyzed
+        };
+        if !info.is_interface {
+            return Err(SemanticError::NotAnInterface {
+                name: interface.to_string(),
+                position: position.clone(),
+                context: format!("'implementasi' names interfaces, and '{}' is a class", interface),
+                suggestion: format!("Extend the class with 'kelas {}({})', or declare '{}' with 'antarmuka'", class, interface, interface),
+                example: "antarmuka Bentuk:\n    fungsi luas(): float\nkelas Persegi implementasi Bentuk:\n    fungsi luas(): float:\n        kembalikan 1.0".to_string(),
+            });
+        }
+        // In declaration order, so the first method missing is the one reported
+        let mut methods: Vec<(String, FunctionSignature)> = info.methods.iter()
+            .map(|(method, signature)| (method.clone(), signature.clone()))
+            .collect();
+        methods.sort_by_key(|(_, signature)| (signature.position.line, signature.position.column));
+
+        for (method, signature) in methods {
+            let implementation = self.find_member(class, &method)
+                .and_then(|(owner, info)| Some((owner, info.methods.get(&method)?.clone())));
+            let Some((owner, implementation)) = implementation else {
+                return Err(SemanticError::MissingInterfaceMethod {
+                    class: class.to_string(),
+                    interface: interface.to_string(),
+                    method: method.clone(),
+                    position: position.clone(),
+                    context: format!("'{}' declares 'fungsi {}', and '{}' says it implements '{}'", interface, method, class, interface),
+                    suggestion: format!("Define '{}' in 'kelas {}'", method, class),
+                    example: "antarmuka Bentuk:\n    fungsi luas(): float\nkelas Persegi implementasi Bentuk:\n    var sisi = 1.0\n    fungsi luas(): float:\n        kembalikan sisi * sisi".to_string(),
+                });
+            };
+            // Calls through the interface come from anywhere, so a method hidden in its class cannot answer them
+            let access = self.class_info(&owner).and_then(|info| info.access.get(&method)).cloned();
+            if let Some(access) = access.filter(|access| access == "private" || access == "protected") {
+                return Err(SemanticError::HiddenInterfaceMethod {
+                    class: owner,
+                    interface: interface.to_string(),
+                    method: method.clone(),
+                    context: format!("'{}' declares 'fungsi {}', and code holding a '{}' may call it from anywhere", interface, method, interface),
+                    access,
+                    position: implementation.position,
+                    suggestion: format!("Remove the access modifier of '{}'", method),
+                    example: "antarmuka Bentuk:\n    fungsi luas(): float\nkelas Persegi implementasi Bentuk:\n    fungsi luas(): float:\n        kembalikan 1.0".to_string(),
+                });
+            }
+            let (Some(expected), Some(found)) = (signature.inferred_type, implementation.inferred_type) else {
+                continue;
+            };
+            // Calls through the interface table pass and expect exactly the declared types
+            if !self.unifier.unify_exact(&expected, &found) {
+                return Err(SemanticError::TypeMismatch {
+                    expected: self.unifier.resolve(&expected).to_string(),
+                    found: self.unifier.resolve(&found).to_string(),
+                    position: implementation.position,
+                    context: format!("'{}.{}' implements '{}.{}', so both are called the same way", owner, method, interface, method),
+                    suggestion: format!("Give '{}' the parameters and return type declared in '{}'", method, interface),
+                    example: "antarmuka Bentuk:\n    fungsi luas(): float\nkelas Persegi implementasi Bentuk:\n    fungsi luas(): float:\n        kembalikan 1.0".to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that method `method` of class `class` takes and returns the same types as the method of
+    /// class `parent` or its ancestors it overrides, so that calls dispatched to either agree
+    fn check_override(&mut self, class: &str, parent: &str, method: &str, position: &Position) -> Result<(), SemanticError> {
+        let method_type = |info: &ClassInfo| info.methods.get(method).and_then(|signature| signature.inferred_type.clone());
+        let Some(own) = self.class_info(class).and_then(method_type) else {
+            return Ok(());
+        };
+        let Some((owner, inherited)) = self.find_member(parent, method)
+            .and_then(|(owner, info)| Some((owner, method_type(info)?)))
+        else {
+            return Ok(());
+        };
+        if Self::is_constructor(method) || self.unifier.unify_exact(&own, &inherited) {
+            return Ok(());
+        }
+        Err(SemanticError::TypeMismatch {
+            expected: self.unifier.resolve(&inherited).to_string(),
+            found: self.unifier.resolve(&own).to_string(),
+            position: position.clone(),
+            context: format!("'{}.{}' overrides '{}.{}', so both are called the same way", class, method, owner, method),
+            suggestion: format!("Give '{}' the same parameters and return type as in '{}'", method, owner),
+            example: "kelas Hewan:\n    fungsi suara(): string:\n        kembalikan \"...\"\nkelas Kucing(Hewan):\n    fungsi suara(): string:\n        kembalikan \"meong\"".to_string(),
+        })
+    }
+
+    /// Signature of a function definition found at `position`, typed from its annotations
+    fn signature(
+        &mut self,
+        type_parameters: &[TypeParameter],
+        parameters: &[FunctionParameter],
+        return_type: &Option<TypeExpr>,
+        variadic: bool,
+        position: &Position,
+    ) -> FunctionSignature {
+        let type_parameters = self.type_parameter_types(type_parameters);
+        let inferred_type = self.with_type_parameters(&type_parameters, |analyzer| {
+            let mut parameter_types = Vec::new();
+            for parameter in parameters {
+                parameter_types.push(analyzer.annotated_or_fresh(&parameter.type_annotation));
+            }
+            Type::Function {
+                parameters: parameter_types,
+                return_type: Box::new(analyzer.annotated_or_fresh(return_type)),
+                variadic,
+            }
+        });
+
+        FunctionSignature {
+            parameters: parameters.iter()
+                .map(|parameter| Parameter {
+                    name: parameter.name.clone(),
+                    param_type: parameter.type_annotation.clone(),
+                    position: parameter.position.clone(),
+                })
+                .collect(),
+            return_type: return_type.clone(),
+            inferred_type: Some(inferred_type),
+            type_parameters,
+            position: position.clone(),
+        }
+    }
+
+    /// Type parameters of a generic definition, with the types of their bounds
+    fn type_parameter_types(&mut self, type_parameters: &[TypeParameter]) -> Vec<(String, Option<Type>)> {
+        type_parameters.iter()
+            .map(|parameter| (parameter.name.clone(), parameter.bound.as_ref().map(|bound| self.annotation_type(bound))))
+            .collect()
+    }
+
+    /// Run `analyze` with `type_parameters` in scope, so annotations naming them refer to them
+    fn with_type_parameters<R>(&mut self, type_parameters: &[(String, Option<Type>)], analyze: impl FnOnce(&mut Self) -> R) -> R {
+        let outer = self.type_parameters.len();
+        self.type_parameters.extend(type_parameters.iter().cloned());
+        self.set_bounds();
+        let result = analyze(self);
+        self.type_parameters.truncate(outer);
+        self.set_bounds();
+        result
+    }
+
+    /// Run `analyze` on the body of `definition`, with its type parameters `type_parameters` in scope
+    fn with_generic_definition<R>(&mut self, definition: SymbolId, type_parameters: &[(String, Option<Type>)], analyze: impl FnOnce(&mut Self) -> R) -> R {
+        if type_parameters.is_empty() {
+            return analyze(self);
+        }
+        self.generic_definitions.push((definition, type_parameters.iter().map(|(name, _)| name.clone()).collect()));
+        let result = self.with_type_parameters(type_parameters, analyze);
+        self.generic_definitions.pop();
+        result
+    }
+
+    /// Let the unifier accept a value of a bounded type parameter in scope where its bound is required
+    fn set_bounds(&mut self) {
+        let bounds = self.type_parameters.iter()
+            .filter_map(|(name, bound)| Some((name.clone(), bound.clone()?)))
+            .collect();
+        self.unifier.set_bounds(bounds);
+    }
+
+    /// Type written as `annotation`
+    fn annotation_type(&mut self, annotation: &TypeExpr) -> Type {
+        let unifier = &mut self.unifier;
+        let ty = Type::from_annotation(annotation, &mut || unifier.fresh());
+        let type_parameters: Vec<String> = self.type_parameters.iter().map(|(name, _)| name.clone()).collect();
+        let ty = ty.with_parameters(&type_parameters);
+
+        // A generic class named without type arguments, as in `buat kotak: Kotak = Kotak(1)`, has them inferred
+        let symbol_table = &self.symbol_table;
+        let unifier = &mut self.unifier;
+        ty.transform(&mut |ty| match ty {
+            Type::Named { name, arguments } if arguments.is_empty() => match &symbol_table.lookup_symbol(name)?.symbol_type {
+                SymbolType::Class(ClassInfo { type_parameters, .. }) | SymbolType::Enum(EnumInfo { type_parameters, .. })
+                    if !type_parameters.is_empty() => Some(Type::Named {
+                        name: name.clone(),
+                        arguments: type_parameters.iter().map(|_| unifier.fresh()).collect(),
                     }),
-                    is_initialized: true,
-                    is_used: false,
+                _ => None,
+            },
+            _ => None,
+        })
+    }
+
+    /// Type written as `annotation`, or a type to infer if there is none
+    fn annotated_or_fresh(&mut self, annotation: &Option<TypeExpr>) -> Type {
+        match annotation {
+            Some(annotation) => self.annotation_type(annotation),
+            None => self.unifier.fresh(),
+        }
+    }
+
+    /// Symbol `declare_definitions` declared in the current scope for the definition of `name` at `position`
+    fn definition(&self, name: &str, position: &Position) -> Option<&Symbol> {
+        self.symbol_table.scopes[self.symbol_table.current_scope].symbols.get(name)
+            .filter(|symbol| symbol.position == *position)
+    }
+
+    /// Class that declares `name`, if it is a class
+    fn class_info(&self, name: &str) -> Option<&ClassInfo> {
+        match &self.symbol_table.lookup_symbol(name)?.symbol_type {
+            SymbolType::Class(info) => Some(info),
+            _ => None,
+        }
+    }
+
+    /// Enum that declares `name`, if it is an enum
+    fn enum_info(&self, name: &str) -> Option<&EnumInfo> {
+        match &self.symbol_table.lookup_symbol(name)?.symbol_type {
+            SymbolType::Enum(info) => Some(info),
+            _ => None,
+        }
+    }
+
+    /// Variants of the enum values of type `ty` are built from, with the types of their fields
+    fn enum_variants(&self, ty: &Type) -> Option<Vec<(String, Vec<Type>)>> {
+        let Type::Named { name, arguments } = self.unifier.resolve(ty) else {
+            return None;
+        };
+        let info = self.enum_info(&name)?;
+        let substitution: HashMap<String, Type> = info.type_parameters.iter().map(|(name, _)| name.clone()).zip(arguments).collect();
+        Some(info.variants.iter()
+            .map(|variant| {
+                let fields = variant.fields.iter().map(|(_, ty)| self.unifier.resolve(&ty.substitute(&substitution))).collect();
+                (variant.name.clone(), fields)
+            })
+            .collect())
+    }
+
+    /// Enum that declares variant `name`, and the position of the variant in it
+    fn variant_symbol(&self, name: &str) -> Option<(SymbolId, usize)> {
+        let symbol = symbol_id(self.symbol_table.lookup_symbol(name)?);
+        match self.symbols[symbol.0].kind {
+            SymbolKind::Variant { enumeration, index } => Some((enumeration, index)),
+            _ => None,
+        }
+    }
+
+    /// Type of the function variant `name` is, with new types to infer for the type parameters of its enum
+    fn variant_type(&mut self, name: &str) -> Type {
+        let Some(SymbolType::Function(signature)) = self.symbol_table.lookup_symbol(name).map(|symbol| symbol.symbol_type.clone()) else {
+            return Type::Any;
+        };
+        let (_, substitution) = self.fresh_type_arguments(&signature.type_parameters);
+        match &signature.inferred_type {
+            Some(function_type) => self.unifier.instantiate(function_type).substitute(&substitution),
+            None => Type::Any,
+        }
+    }
+
+    /// Signature the enclosing class recorded for the method defined at `position`, while its body is analyzed
+    fn class_method(&self, name: &str, position: &Position) -> Option<FunctionSignature> {
+        let (class, scope) = self.current_class.as_ref()?;
+        if *scope != self.symbol_table.current_scope {
+            return None;
+        }
+        self.class_info(class)?.methods.get(name)
+            .filter(|signature| signature.position == *position)
+            .cloned()
+    }
+
+    /// Type the enclosing class recorded for field `name`, while its body is analyzed
+    fn class_field(&self, name: &str) -> Option<Type> {
+        let (class, scope) = self.current_class.as_ref()?;
+        if *scope != self.symbol_table.current_scope {
+            return None;
+        }
+        self.class_info(class)?.fields.get(name)?.inferred_type.clone()
+    }
+
+    /// Symbol of the class whose body is analyzed, while its own scope is the current one
+    fn current_class_symbol(&self) -> Option<SymbolId> {
+        let (class, scope) = self.current_class.as_ref()?;
+        if *scope != self.symbol_table.current_scope {
+            return None;
+        }
+        self.symbol_table.lookup_symbol(class).map(symbol_id)
+    }
+
+    /// Class `member` is declared in when looked up on an instance of `class`: the class itself or
+    /// its nearest ancestor declaring it
+    fn find_member(&self, class: &str, member: &str) -> Option<(String, &ClassInfo)> {
+        let mut visited: Vec<&str> = Vec::new();
+        let mut current = class;
+        // A class extending itself, directly or not, is reported by nothing else; the walk stops at the cycle
+        while !visited.contains(&current) {
+            visited.push(current);
+            let info = self.class_info(current)?;
+            if info.fields.contains_key(member) || info.methods.contains_key(member) {
+                return Some((current.to_string(), info));
+            }
+            current = info.parent.as_deref()?;
+        }
+        None
+    }
+
+    /// Whether `class` and every class it extends are declared in the program, so that their members are all known
+    fn knows_ancestors(&self, class: &str) -> bool {
+        let mut visited: Vec<&str> = Vec::new();
+        let mut current = class;
+        while !visited.contains(&current) {
+            visited.push(current);
+            match self.class_info(current) {
+                Some(ClassInfo { parent: Some(parent), .. }) => current = parent,
+                Some(_) => return true,
+                None => return false,
+            }
+        }
+        true
+    }
+
+    /// Whether the methods of `class` or of a class it extends create field `field` by assigning it
+    fn assigns_field(&self, class: &str, field: &str) -> bool {
+        let mut visited: Vec<&str> = Vec::new();
+        let mut current = class;
+        while !visited.contains(&current) {
+            visited.push(current);
+            let Some(info) = self.class_info(current) else {
+                return false;
+            };
+            if info.assigned_fields.contains(field) {
+                return true;
+            }
+            match info.parent.as_deref() {
+                Some(parent) => current = parent,
+                None => return false,
+            }
+        }
+        false
+    }
+
+    /// Whether `class` is `ancestor` or extends it
+    fn is_subclass(&self, class: &str, ancestor: &str) -> bool {
+        let mut visited: Vec<&str> = Vec::new();
+        let mut current = class;
+        while !visited.contains(&current) {
+            if current == ancestor {
+                return true;
+            }
+            visited.push(current);
+            match self.class_info(current).and_then(|info| info.parent.as_deref()) {
+                Some(parent) => current = parent,
+                None => return false,
+            }
+        }
+        false
+    }
+
+    /// Class of the instances of type `object`; a bounded type parameter has the members of its bound
+    fn object_class(&self, object: &Type) -> Option<String> {
+        match self.unifier.resolve(object).non_optional() {
+            Type::Named { name, .. } if self.class_info(name).is_some() => Some(name.clone()),
+            Type::Parameter(name) => {
+                let (_, bound) = self.type_parameters.iter().rev().find(|(parameter, _)| parameter == name)?;
+                self.object_class(bound.as_ref()?)
+            }
+            _ => None,
+        }
+    }
+
+    /// Type of the instances of class `class` in its own body
+    fn class_type(&self, class: &str) -> Type {
+        match self.class_info(class) {
+            Some(info) => instance_type(class, info),
+            None => Type::named(class),
+        }
+    }
+
+    /// Type argument of each type parameter of the class of `object`, e.g. `T` is `int` for a `Kotak<int>`
+    fn type_arguments(&self, object: &Type) -> HashMap<String, Type> {
+        let Type::Named { name, arguments } = self.unifier.resolve(object).non_optional().clone() else {
+            return HashMap::new();
+        };
+        match self.class_info(&name) {
+            Some(info) if info.type_parameters.len() == arguments.len() => {
+                info.type_parameters.iter().map(|(parameter, _)| parameter.clone()).zip(arguments).collect()
+            }
+            _ => HashMap::new(),
+        }
+    }
+
+    /// New types to infer for the type parameters of a use of a generic definition, in order and by name
+    fn fresh_type_arguments(&mut self, type_parameters: &[(String, Option<Type>)]) -> (Vec<Type>, HashMap<String, Type>) {
+        let arguments: Vec<Type> = type_parameters.iter().map(|_| self.unifier.fresh()).collect();
+        let substitution = type_parameters.iter().map(|(name, _)| name.clone()).zip(arguments.iter().cloned()).collect();
+        (arguments, substitution)
+    }
+
+    /// Check that the types inferred for the type parameters of generic definition `name` extend or implement their bounds
+    fn check_bounds(&mut self, name: &str, type_parameters: &[(String, Option<Type>)], arguments: &[Type], position: &Position) -> Result<(), SemanticError> {
+        // The operations of its body are checked with these types once they are inferred
+        if let Some(definition) = self.symbol_table.lookup_symbol(name).and_then(|symbol| symbol.id).filter(|_| !type_parameters.is_empty()) {
+            self.instantiations.push(Instantiation {
+                definition,
+                within: self.generic_definitions.last().map(|(definition, _)| *definition),
+                type_arguments: type_parameters.iter().map(|(name, _)| name.clone()).zip(arguments.iter().cloned()).collect(),
+                position: position.clone(),
+            });
+        }
+        for ((parameter, bound), argument) in type_parameters.iter().zip(arguments) {
+            let Some(bound) = bound else {
+                continue;
+            };
+            if self.unifier.unify(bound, argument) {
+                continue;
+            }
+            return Err(SemanticError::TypeMismatch {
+                expected: self.unifier.resolve(bound).to_string(),
+                found: self.unifier.resolve(argument).to_string(),
+                position: position.clone(),
+                context: format!("'{}' declares '{}: {}', so '{}' only stands for types that extend or implement '{}'", name, parameter, bound, parameter, bound),
+                suggestion: format!("Pass an instance of a class declared with 'implementasi {}'", bound),
+                example: "antarmuka Bernilai:\n    fungsi nilai(): int\nfungsi terbesar<T: Bernilai>(a: T, b: T): T:\n    jika a.nilai() > b.nilai():\n        kembalikan a\n    kembalikan b".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Check the operations of each generic body again, with the types the uses of its definition give its type parameters
+    ///
+    /// A use in another generic body that passes on its type parameters is checked with the types of each use of that
+    /// definition in turn.
+    fn check_instantiations(&mut self) -> Result<(), SemanticError> {
+        let instantiations = std::mem::take(&mut self.instantiations);
+        let operations = std::mem::take(&mut self.generic_operations);
+        let mut pending: Vec<(&Instantiation, HashMap<String, Type>)> = instantiations.iter()
+            .map(|instantiation| (instantiation, HashMap::new()))
+            .collect();
+        let mut checked = HashSet::new();
+        while let Some((instantiation, outer)) = pending.pop() {
+            // Types still unknown, or naming type parameters the use does not give, are left unchecked
+            let type_arguments: Vec<(String, Type)> = instantiation.type_arguments.iter()
+                .map(|(name, ty)| (name.clone(), self.unifier.finish(ty).substitute(&outer)))
+                .filter(|(_, ty)| *ty != Type::Any && parameters_in(ty).is_empty())
+                .collect();
+            let described = type_arguments.iter().map(|(name, ty)| format!("{} = {}", name, ty)).collect::<Vec<_>>().join(", ");
+            if type_arguments.is_empty() || !checked.insert((instantiation.definition, described.clone())) {
+                continue;
+            }
+            let substitution: HashMap<String, Type> = type_arguments.into_iter().collect();
+            for operation in operations.iter().filter(|operation| operation.definition == instantiation.definition) {
+                let [left, right] = operation.operands.clone().map(|(position, span)| PositionedASTNode {
+                    node: ASTNode::Identifier(String::new()),
+                    position,
+                    span,
+                });
+                let (left_type, right_type) = (operation.left.substitute(&substitution), operation.right.substitute(&substitution));
+                if self.binary_type(&left, &left_type, &operation.operator, &right, &right_type).is_ok() {
+                    continue;
+                }
+                let definition = self.symbols[instantiation.definition.0].name.clone();
+                return Err(SemanticError::InvalidInstantiation {
+                    context: format!(
+                        "'{}' computes with '{}' and '{}' at line {}, which these types do not support",
+                        definition, operation.left, operation.right, left.position.line
+                    ),
+                    definition,
+                    type_arguments: described,
+                    line: left.position.line,
+                    position: instantiation.position.clone(),
+                    suggestion: "Pass values of types the operators of the body work on, or bound the type parameter by an interface and call its methods instead".to_string(),
+                    example: "fungsi tambah<T>(a: T, b: T): T:\n    kembalikan a + b\nbuat jumlah = tambah(1, 2)".to_string(),
+                });
+            }
+            pending.extend(instantiations.iter()
+                .filter(|inner| inner.within == Some(instantiation.definition) && inner.definition != instantiation.definition)
+                .map(|inner| (inner, substitution.clone())));
+        }
+        Ok(())
+    }
+
+    /// Check that member `member` of class `class` may be used where the analyzer is: a private member
+    /// only in the body of its class, a protected one also in the bodies of the classes extending it
+    fn check_access(&self, class: &str, member: &str, position: &Position) -> Result<(), SemanticError> {
+        let Some((owner, info)) = self.find_member(class, member) else {
+            // Under a parent this analyzer does not know, such as an imported class, the member may still exist
+            if !self.knows_ancestors(class) || self.assigns_field(class, member) {
+                return Ok(());
+            }
+            return Err(SemanticError::UndeclaredMember {
+                member: member.to_string(),
+                class: class.to_string(),
+                position: position.clone(),
+                context: format!("Neither '{}' nor the classes it extends declare a field or method '{}'", class, member),
+                suggestion: format!("Declare '{}' in 'kelas {}', or check the spelling", member, class),
+                example: "kelas Titik:\n    var x: int = 0\nbuat t = Titik()\ntampilkan(t.x)".to_string(),
+            });
+        };
+        let current = self.current_class.as_ref().map(|(current, _)| current.as_str());
+        let (allowed, suggestion) = match info.access.get(member).map(String::as_str) {
+            Some("private") => (
+                current == Some(owner.as_str()),
+                format!("Use '{}' only inside 'kelas {}', or call a public method of the class that uses it", member, owner),
+            ),
+            Some("protected") => (
+                current.is_some_and(|current| self.is_subclass(current, &owner)),
+                format!("Use '{}' only inside 'kelas {}' or a class that extends it", member, owner),
+            ),
+            _ => return Ok(()),
+        };
+        if allowed {
+            return Ok(());
+        }
+        let access = info.access[member].clone();
+        Err(SemanticError::InaccessibleMember {
+            member: member.to_string(),
+            class: owner.clone(),
+            context: format!("'{}' is declared {} in class '{}'", member, access, owner),
+            access,
+            position: position.clone(),
+            suggestion,
+            example: "kelas Akun:\n    pribadi buat saldo = 0\n    fungsi lihat_saldo():\n        kembalikan ini.saldo\n\ntampilkan(akun.lihat_saldo())".to_string(),
+        })
+    }
+
+    /// Check that `member` of a value of type `class`, if it is an interface, is one of its methods; the
+    /// instance behind the value may be of any class implementing it
+    fn check_interface_member(&self, class: &str, member: &str, is_call: bool, position: &Position) -> Result<(), SemanticError> {
+        let Some(info) = self.class_info(class).filter(|info| info.is_interface) else {
+            return Ok(());
+        };
+        if is_call && info.methods.contains_key(member) {
+            return Ok(());
+        }
+        let mut methods: Vec<&str> = info.methods.keys().map(String::as_str).collect();
+        methods.sort();
+        Err(SemanticError::UnknownInterfaceMember {
+            interface: class.to_string(),
+            member: member.to_string(),
+            position: position.clone(),
+            context: format!("A value of type '{}' only has the methods the interface declares: {}", class, methods.join(", ")),
+            suggestion: format!("Declare '{}' in 'antarmuka {}', or give the value the type of its class", member, class),
+            example: "antarmuka Bentuk:\n    fungsi luas(): float\nbuat bentuk: Bentuk = Persegi()\ntampilkan(bentuk.luas())".to_string(),
+        })
+    }
+
+    /// Type of field `field` of a value of type `object`, if it is an instance of a class that declares
+    /// or inherits it
+    fn field_type(&self, object: &Type, field: &str) -> Option<Type> {
+        let class = self.object_class(object)?;
+        let field_type = self.find_member(&class, field)?.1.fields.get(field)?.inferred_type.clone()?;
+        Some(field_type.substitute(&self.type_arguments(object)))
+    }
+
+    /// Type of method `method` looked up on an instance of `class`, with fresh types for what it leaves open
+    fn method_type(&mut self, class: &str, method: &str) -> Option<Type> {
+        let signature = self.find_member(class, method)?.1.methods.get(method)?.clone();
+        let (_, substitution) = self.fresh_type_arguments(&signature.type_parameters);
+        Some(self.unifier.instantiate(&signature.inferred_type?).substitute(&substitution))
+    }
+
+    /// Check a call constructing an instance of `class`, against the constructor it declares or inherits,
+    /// and return the type of the instance
+    fn check_constructor(&mut self, class: &str, arguments: &[PositionedASTNode], argument_types: Vec<Type>, position: &Position) -> Result<Type, SemanticError> {
+        if self.class_info(class).is_some_and(|info| info.is_interface) {
+            return Err(SemanticError::InterfaceInstantiated {
+                interface: class.to_string(),
+                position: position.clone(),
+                context: format!("'{}' only declares methods; its values are instances of the classes implementing it", class),
+                suggestion: format!("Create an instance of a class declared with 'implementasi {}'", class),
+                example: "buat bentuk: Bentuk = Persegi()".to_string(),
+            });
+        }
+        // The type arguments of a generic class are inferred from the arguments of its constructor
+        let type_parameters = self.class_info(class).map(|info| info.type_parameters.clone()).unwrap_or_default();
+        let (type_arguments, substitution) = self.fresh_type_arguments(&type_parameters);
+        let constructor = ["baru", "new"].into_iter()
+            .find_map(|name| Some((name, self.find_member(class, name)?.0)));
+        if let Some((constructor, owner)) = constructor {
+            self.check_access(class, constructor, position)?;
+            if let Some(constructor_type) = self.method_type(class, constructor) {
+                let constructor_type = constructor_type.substitute(&substitution);
+                self.check_call(&format!("{}.{}", owner, constructor), &constructor_type, arguments, argument_types, position)?;
+            }
+        }
+        self.check_bounds(class, &type_parameters, &type_arguments, position)?;
+        Ok(self.class_type(class).substitute(&substitution))
+    }
+
+    /// Whether a method named `name` constructs the instances of its class
+    fn is_constructor(name: &str) -> bool {
+        name == "baru" || name == "new"
+    }
+
+    /// Type of the elements `untuk ... dalam` visits in a value of type `iterable`
+    fn element_type(&self, iterable: &Type) -> Type {
+        match self.unifier.resolve(iterable).non_optional() {
+            Type::List(element) => (**element).clone(),
+            Type::String => Type::String,
+            Type::Map(key, _) => (**key).clone(),
+            _ => Type::Any,
+        }
+    }
+
+    /// Analyze a block in its own scope
+    fn analyze_block(&mut self, statements: &[Statement], position: &Position) -> Result<Vec<hir::Statement>, SemanticError> {
+        self.symbol_table.enter_scope(position.clone());
+        let result = self.analyze_statements(statements);
+        self.symbol_table.exit_scope();
+        result
+    }
+
+    /// Analyze the statements of a scope that is already entered
+    fn analyze_statements(&mut self, statements: &[Statement]) -> Result<Vec<hir::Statement>, SemanticError> {
+        self.declare_definitions(statements)?;
+        let mut analyzed = Vec::new();
+        for statement in statements {
+            analyzed.push(self.analyze_statement(statement)?);
+        }
+        Ok(analyzed)
+    }
+
+    /// Analyze a statement and lower it to the HIR
+    fn analyze_statement(&mut self, statement: &Statement) -> Result<hir::Statement, SemanticError> {
+        let position = &statement.position;
+        let kind = match &statement.node {
+            ASTNode::Declaration { identifier, type_annotation, value, mutable, constant, .. } => {
+                // The value is checked first: `buat x = x + 1` reads an outer `x`
+                let analyzed = match value {
+                    Some(value) => Some(self.analyze_expression(value)?),
+                    None => None,
+                };
+                // A field takes the type its class recorded, so that accesses through instances share it
+                let declared = match self.class_field(identifier) {
+                    Some(field_type) => Some(field_type),
+                    None => type_annotation.as_ref().map(|annotation| self.annotation_type(annotation)),
+                };
+                let var_type = match (declared, value, &analyzed) {
+                    (Some(declared), Some(value), Some(found)) => {
+                        let context = format!("'{}' is declared as '{}'", identifier, self.unifier.resolve(&declared));
+                        self.expect_type(&declared, &found.ty, value, context)?;
+                        declared
+                    }
+                    (Some(declared), _, _) => declared,
+                    (None, _, Some(found)) => found.ty.clone(),
+                    (None, _, None) => self.unifier.fresh(),
+                };
+                let info = VariableInfo {
+                    var_type: type_annotation.clone(),
+                    inferred_type: Some(var_type),
+                    is_constant: !mutable,
+                };
+                let is_field = self.class_field(identifier).is_some();
+                if *constant && !is_field {
+                    return self.analyze_constant(identifier, info, analyzed, statement);
+                }
+                let symbol = self.declare(identifier, SymbolType::Variable(info), value.is_some(), position)?;
+                if let (true, Some(class)) = (is_field, self.current_class_symbol()) {
+                    self.symbols[symbol.0].kind = SymbolKind::Field { class };
+                }
+                if let Some(analyzed) = &analyzed {
+                    self.track_assignment(symbol, &analyzed.ty);
+                }
+                hir::StatementKind::Let { symbol, value: analyzed }
+            }
+            ASTNode::Assignment { identifier, value } => {
+                self.check_assignable(identifier, position)?;
+                let analyzed = self.analyze_expression(value)?;
+                let target = self.symbol_table.lookup_symbol(identifier).map(|symbol| {
+                    let expected = match &symbol.symbol_type {
+                        SymbolType::Variable(info) | SymbolType::Parameter(info) => info.inferred_type.clone(),
+                        _ => None,
+                    };
+                    (symbol_id(symbol), expected)
+                });
+                match target {
+                    // `saldo = ...` in a method assigns the field of the instance it is called on
+                    Some((symbol, expected)) if matches!(self.symbols[symbol.0].kind, SymbolKind::Field { .. }) => {
+                        if let Some(expected) = expected {
+                            let context = format!("Field '{}' has type '{}'", identifier, self.unifier.resolve(&expected));
+                            self.expect_type(&expected, &analyzed.ty, value, context)?;
+                        }
+                        let object = self.self_expression(symbol, position, statement.span);
+                        hir::StatementKind::MemberAssign { object, field: identifier.clone(), value: analyzed }
+                    }
+                    Some((symbol, expected)) => {
+                        if let Some(expected) = expected {
+                            let context = format!(
+                                "'{}' holds values of type '{}'; a variable keeps the type of its first value",
+                                identifier,
+                                self.unifier.resolve(&expected),
+                            );
+                            self.expect_type(&expected, &analyzed.ty, value, context)?;
+                        }
+                        self.capture(symbol);
+                        self.assigned.insert(symbol);
+                        self.track_assignment(symbol, &analyzed.ty);
+                        hir::StatementKind::Assign { symbol, value: analyzed }
+                    }
+                    None => match self.current_class.as_ref().map(|(class, _)| self.class_type(class)) {
+                        // A field inherited from a parent class is assigned on the instance, as `ini.nama = ...`
+                        Some(object_type) if self.field_type(&object_type, identifier).is_some() => {
+                            let expected = self.field_type(&object_type, identifier).unwrap_or(Type::Any);
+                            let context = format!("Field '{}' has type '{}'", identifier, self.unifier.resolve(&expected));
+                            self.expect_type(&expected, &analyzed.ty, value, context)?;
+                            let object = self.analyze_node(&ASTNode::SelfRef, position, statement.span)?;
+                            hir::StatementKind::MemberAssign { object, field: identifier.clone(), value: analyzed }
+                        }
+                        // Assigning to a new name declares it in the current scope
+                        _ => {
+                            let symbol = self.declare_variable(identifier, analyzed.ty.clone(), position)?;
+                            hir::StatementKind::Let { symbol, value: Some(analyzed) }
+                        }
+                    },
+                }
+            }
+            ASTNode::ExpressionStmt(expression) => hir::StatementKind::Expression(self.analyze_expression(expression)?),
+            ASTNode::ThrowStmt(expression) => {
+                let value = self.analyze_expression(expression)?;
+                let ty = self.unifier.resolve(&value.ty);
+                let throwable = matches!(ty, Type::String | Type::Any | Type::Variable(_)) || self.object_class(&ty).is_some();
+                if !throwable {
+                    return Err(SemanticError::NotThrowable {
+                        found: ty.to_string(),
+                        position: expression.position.clone(),
+                        context: "An error is an instance of a class, which 'tangkap' clauses catch by class, or a text".to_string(),
+                        suggestion: "Throw an instance of a class describing the error".to_string(),
+                        example: TRY_EXAMPLE.to_string(),
+                    });
+                }
+                hir::StatementKind::Throw(value)
+            }
+            ASTNode::ReturnStmt(value) => {
+                let analyzed = match value {
+                    Some(value) => Some(self.analyze_expression(value)?),
+                    None => None,
+                };
+                let found = analyzed.as_ref().map(|analyzed| analyzed.ty.clone());
+                self.check_return(value.as_deref(), found, position)?;
+                hir::StatementKind::Return(analyzed)
+            }
+            ASTNode::IfStatement { condition, then_block, else_block } => {
+                let (when_true, when_false) = self.null_facts(&condition.node);
+                let condition = self.analyze_condition(condition, "The condition of 'jika' must be true or false")?;
+                let before = self.non_null.clone();
+                self.non_null.extend(when_true);
+                let then_hir = self.analyze_block(then_block, position)?;
+                let after_then = std::mem::replace(&mut self.non_null, before.clone());
+                self.non_null.extend(when_false);
+                let else_hir = match else_block {
+                    Some(else_block) => Some(self.analyze_block(else_block, position)?),
+                    None => None,
+                };
+                let after_else = std::mem::take(&mut self.non_null);
+                // After the statement, a variable is known not to be `nol` if every branch that goes on proved it
+                let then_exits = always_exits(then_block);
+                let else_exits = else_block.as_deref().is_some_and(always_exits);
+                self.non_null = match (then_exits, else_exits) {
+                    (true, true) => before,
+                    (true, false) => after_else,
+                    (false, true) => after_then,
+                    (false, false) => after_then.intersection(&after_else).copied().collect(),
+                };
+                hir::StatementKind::If { condition, then_block: then_hir, else_block: else_hir }
+            }
+            ASTNode::FunctionDef { name, type_parameters, parameters, return_type, body, variadic, is_async, is_static, .. } => {
+                let declared = match self.definition(name, position) {
+                    Some(symbol @ Symbol { symbol_type: SymbolType::Function(signature), .. }) => Some((symbol_id(symbol), signature.clone())),
+                    _ => None,
+                };
+                let (symbol, signature) = match declared {
+                    Some(declared) => declared,
+                    None => {
+                        let signature = self.signature(type_parameters, parameters, return_type, *variadic, position);
+                        (self.declare(name, SymbolType::Function(signature.clone()), true, position)?, signature)
+                    }
+                };
+                let function_type = signature.inferred_type.clone().unwrap_or(Type::Any);
+                let result = self.with_generic_definition(symbol, &signature.type_parameters, |analyzer| {
+                    analyzer.symbol_table.enter_scope(position.clone());
+                    let result = analyzer.analyze_function(parameters, function_type, return_type.is_some(), body);
+                    analyzer.symbol_table.exit_scope();
+                    result
+                });
+                let (parameters, body) = result?;
+                hir::StatementKind::Function(hir::Function {
+                    symbol,
+                    type_parameters: signature.type_parameters.iter().map(|(name, _)| name.clone()).collect(),
+                    parameters,
+                    body,
+                    is_async: *is_async,
+                    is_static: *is_static,
+                    variadic: *variadic,
+                })
+            }
+            ASTNode::ClassDef { name, parent_class, interfaces, body, .. } => {
+                let parent = match parent_class {
+                    Some(parent) => self.use_name(parent, position)?,
+                    None => None,
+                };
+                let mut interface_symbols = Vec::new();
+                for interface in interfaces {
+                    interface_symbols.extend(self.use_name(interface, position)?);
+                }
+                let symbol = match self.definition(name, position).map(symbol_id) {
+                    Some(symbol) => symbol,
+                    None => {
+                        self.declare_definitions(std::slice::from_ref(statement))?;
+                        self.definition(name, position).map(symbol_id).expect("the class was just declared")
+                    }
+                };
+                let type_parameters = self.class_info(name).map(|info| info.type_parameters.clone()).unwrap_or_default();
+                let result = self.with_generic_definition(symbol, &type_parameters, |analyzer| {
+                    analyzer.symbol_table.enter_scope(position.clone());
+                    let outer_class = analyzer.current_class.replace((name.clone(), analyzer.symbol_table.current_scope));
+                    let result = analyzer.analyze_statements(body);
+                    analyzer.current_class = outer_class;
+                    analyzer.symbol_table.exit_scope();
+                    result
+                });
+                hir::StatementKind::Class(hir::Class {
+                    symbol,
+                    parent,
+                    interfaces: interface_symbols,
+                    type_parameters: type_parameters.into_iter().map(|(name, _)| name).collect(),
+                    body: result?,
+                })
+            }
+            ASTNode::InterfaceDef { name, methods, .. } => {
+                let symbol = match self.definition(name, position).map(symbol_id) {
+                    Some(symbol) => symbol,
+                    None => {
+                        self.declare_definitions(std::slice::from_ref(statement))?;
+                        self.definition(name, position).map(symbol_id).expect("the interface was just declared")
+                    }
+                };
+                let info = self.class_info(name).filter(|info| info.is_interface);
+                let methods = methods.iter()
+                    .filter_map(|method| match &method.node {
+                        ASTNode::FunctionDef { name, .. } => {
+                            let ty = info?.methods.get(name)?.inferred_type.clone()?;
+                            Some((name.clone(), ty))
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                hir::StatementKind::Interface(hir::Interface { symbol, methods })
+            }
+            ASTNode::EnumDef { name, type_parameters, variants, .. } => {
+                let symbol = match self.definition(name, position).map(symbol_id) {
+                    Some(symbol) => symbol,
+                    None => {
+                        self.declare_definitions(std::slice::from_ref(statement))?;
+                        self.definition(name, position).map(symbol_id).expect("the enum was just declared")
+                    }
+                };
+                let info = self.enum_info(name).cloned().expect("the enum was declared with its definition");
+                let variants = variants.iter().zip(info.variants)
+                    .map(|(variant, info)| hir::Variant {
+                        symbol: self.definition(&variant.name, &variant.position).map(symbol_id).expect("variants are declared with their enum"),
+                        fields: info.fields.into_iter().map(|(_, ty)| ty).collect(),
+                    })
+                    .collect();
+                hir::StatementKind::Enum(hir::Enum {
+                    symbol,
+                    type_parameters: type_parameters.iter().map(|parameter| parameter.name.clone()).collect(),
+                    variants,
+                })
+            }
+            ASTNode::WhileLoop { condition, body } => {
+                // A variable the body assigns may be `nol` again when the condition is checked next
+                self.forget_assigned(std::slice::from_ref(statement));
+                let (when_true, _) = self.null_facts(&condition.node);
+                let condition = self.analyze_condition(condition, "The condition of 'selama' must be true or false")?;
+                let before = self.non_null.clone();
+                self.non_null.extend(when_true);
+                let body = self.analyze_loop_body(body, position)?;
+                self.non_null = before;
+                hir::StatementKind::While { condition, body }
+            }
+            ASTNode::DoWhileLoop { body, condition } => {
+                self.forget_assigned(std::slice::from_ref(statement));
+                let before = self.non_null.clone();
+                let body = self.analyze_loop_body(body, position)?;
+                let condition = self.analyze_condition(condition, "The condition after 'ulangi' must be true or false")?;
+                self.non_null = before;
+                hir::StatementKind::DoWhile { body, condition }
+            }
+            ASTNode::ForLoop { variable, start, end, descending, body } => {
+                let start = self.analyze_expression(start)?;
+                let end = self.analyze_expression(end)?;
+                self.forget_assigned(std::slice::from_ref(statement));
+                let before = self.non_null.clone();
+                let (variable, body) = self.analyze_loop(variable, start.ty.clone(), body, position)?;
+                self.non_null = before;
+                hir::StatementKind::For { variable, start, end, descending: *descending, body }
+            }
+            ASTNode::ForEachLoop { variable, iterable: iterable_node, body } => {
+                let iterable = self.analyze_expression(iterable_node)?;
+                self.expect_non_null(&iterable.ty, iterable_node)?;
+                let variable_type = self.element_type(&iterable.ty);
+                self.forget_assigned(std::slice::from_ref(statement));
+                let before = self.non_null.clone();
+                let (variable, body) = self.analyze_loop(variable, variable_type, body, position)?;
+                self.non_null = before;
+                hir::StatementKind::ForEach { variable, iterable, body }
+            }
+            ASTNode::TryCatch { try_block, catches, finally_block } => {
+                // Any statement of the `coba` block may be the one that throws, so what it proves holds in
+                // none of the other blocks, nor after the statement
+                self.forget_assigned(std::slice::from_ref(statement));
+                let before = self.non_null.clone();
+                let try_block = self.analyze_block(try_block, position)?;
+                let mut analyzed = Vec::new();
+                for catch in catches {
+                    self.non_null = before.clone();
+                    let catch = self.analyze_catch(catch, &analyzed)?;
+                    analyzed.push(catch);
+                }
+                self.non_null = before.clone();
+                let finally_block = match finally_block {
+                    Some(finally_block) => Some(self.analyze_block(finally_block, position)?),
+                    None => None,
+                };
+                self.non_null = before;
+                hir::StatementKind::TryCatch { try_block, catches: analyzed, finally_block }
+            }
+            ASTNode::BreakStmt | ASTNode::ContinueStmt if self.loops == 0 => {
+                let keyword = if statement.node == ASTNode::BreakStmt { "pecah" } else { "lanjut" };
+                return Err(SemanticError::OutsideLoop {
+                    keyword: keyword.to_string(),
+                    position: position.clone(),
+                    context: format!("'{}' only works inside a 'selama', 'untuk' or 'ulangi' loop of the same function", keyword),
+                    suggestion: "Move it into a loop, or leave the function with 'kembalikan'".to_string(),
+                    example: "untuk i dari 1 sampai 10:\n    jika i == 5:\n        pecah\n    tampilkan i".to_string(),
+                });
+            }
+            ASTNode::BreakStmt => hir::StatementKind::Break,
+            ASTNode::ContinueStmt => hir::StatementKind::Continue,
+            ASTNode::ImportStmt { module, alias } => {
+                self.open_imports |= alias.is_none();
+                let name = alias.as_deref()
+                    .unwrap_or_else(|| module.rsplit(['/', '.']).next().unwrap_or(module));
+                let symbol = match self.symbol_table.lookup_symbol(name).map(symbol_id) {
+                    Some(symbol) => symbol,
+                    None => {
+                        let symbol = self.declare_variable(name, Type::Any, position)?;
+                        self.symbols[symbol.0].kind = SymbolKind::Import;
+                        symbol
+                    }
+                };
+                hir::StatementKind::Import { module: module.clone(), symbol }
+            }
+            ASTNode::ExportStmt { names } => hir::StatementKind::Export(names.clone()),
+            ASTNode::MemberAssignment { object: object_node, property, value } => {
+                let object = self.analyze_expression(object_node)?;
+                self.expect_non_null(&object.ty, object_node)?;
+                if let Some(class) = self.object_class(&object.ty) {
+                    self.check_access(&class, property, position)?;
+                }
+                let analyzed = self.analyze_expression(value)?;
+                if let Some(expected) = self.field_type(&object.ty, property) {
+                    let context = format!("Field '{}' has type '{}'", property, self.unifier.resolve(&expected));
+                    self.expect_type(&expected, &analyzed.ty, value, context)?;
+                }
+                hir::StatementKind::MemberAssign { object, field: property.clone(), value: analyzed }
+            }
+            ASTNode::IndexAssignment { object: object_node, index, value } => {
+                let object = self.analyze_expression(object_node)?;
+                self.expect_non_null(&object.ty, object_node)?;
+                let index = self.analyze_expression(index)?;
+                let analyzed = self.analyze_expression(value)?;
+                if let Type::List(element) | Type::Map(_, element) = self.unifier.resolve(&object.ty) {
+                    let context = format!("The collection holds values of type '{}'", element);
+                    self.expect_type(&element, &analyzed.ty, value, context)?;
+                }
+                hir::StatementKind::IndexAssign { object, index, value: analyzed }
+            }
+            ASTNode::WhenStmt { expression, cases, else_case } => {
+                let value = self.analyze_expression(expression)?;
+                self.forget_assigned(std::slice::from_ref(statement));
+                let before = self.non_null.clone();
+                let mut analyzed_cases = Vec::new();
+                for case in cases {
+                    self.non_null = before.clone();
+                    // The names a pattern binds are only in scope in its guard and body
+                    self.symbol_table.enter_scope(case.position.clone());
+                    let result = self.analyze_case(case, &value.ty);
+                    self.symbol_table.exit_scope();
+                    analyzed_cases.push(result?);
+                }
+                self.non_null = before.clone();
+                let else_case = match else_case {
+                    Some(else_case) => Some(self.analyze_block(else_case, position)?),
+                    None => None,
+                };
+                self.non_null = before;
+                self.check_cases(cases, &analyzed_cases, &value.ty, else_case.is_some(), position)?;
+                hir::StatementKind::When { value, cases: analyzed_cases, else_case }
+            }
+            ASTNode::GoStmt { body } => {
+                // The body runs alongside the statements after it, so neither relies on what the other proves
+                self.forget_assigned(std::slice::from_ref(statement));
+                let before = std::mem::take(&mut self.non_null);
+                let body = self.analyze_block(body, position);
+                self.non_null = before;
+                hir::StatementKind::Go(body?)
+            }
+            ASTNode::ChannelSendStmt { channel, value } => hir::StatementKind::ChannelSend {
+                channel: self.analyze_expression(channel)?,
+                value: self.analyze_expression(value)?,
+            },
+            ASTNode::AtomicStoreStmt { address, value } => hir::StatementKind::AtomicStore {
+                address: self.analyze_expression(address)?,
+                value: self.analyze_expression(value)?,
+            },
+            ASTNode::ChannelReceiveStmt { channel, variable } => {
+                let channel = self.analyze_expression(channel)?;
+                let variable = match self.symbol_table.lookup_symbol(variable).map(symbol_id) {
+                    Some(symbol) => symbol,
+                    None => {
+                        let element_type = Self::channel_element(&self.unifier.resolve(&channel.ty));
+                        self.declare_variable(variable, element_type, position)?
+                    }
+                };
+                hir::StatementKind::ChannelReceive { channel, variable }
+            }
+            ASTNode::MutexLockStmt { mutex } => hir::StatementKind::MutexLock(self.analyze_expression(mutex)?),
+            ASTNode::MutexUnlockStmt { mutex } => hir::StatementKind::MutexUnlock(self.analyze_expression(mutex)?),
+            ASTNode::WaitConditionStmt { condition, mutex } => hir::StatementKind::WaitCondition {
+                condition: self.analyze_expression(condition)?,
+                mutex: self.analyze_expression(mutex)?,
+            },
+            ASTNode::SignalConditionStmt { condition } => {
+                hir::StatementKind::SignalCondition(self.analyze_expression(condition)?)
+            }
+            ASTNode::BroadcastConditionStmt { condition } => {
+                hir::StatementKind::BroadcastCondition(self.analyze_expression(condition)?)
+            }
+            ASTNode::QubitDeclaration { identifier, initial_state: value }
+            | ASTNode::TensorDeclaration { identifier, shape: value } => {
+                let operands = match value {
+                    Some(value) => vec![self.analyze_expression(value)?],
+                    None => Vec::new(),
+                };
+                let construct = if let ASTNode::QubitDeclaration { .. } = &statement.node { "qubit" } else { "tensor" };
+                let symbol = self.declare_variable(identifier, Type::Any, position)?;
+                unsupported(construct, Some(symbol), operands, Vec::new())
+            }
+            ASTNode::ModelDeclaration { identifier, network } => {
+                let operands = vec![self.analyze_expression(network)?];
+                let symbol = self.declare_variable(identifier, Type::Any, position)?;
+                unsupported("model", Some(symbol), operands, Vec::new())
+            }
+            ASTNode::CircuitDefinition { name, qubit_count, body } => {
+                let operands = vec![self.analyze_expression(qubit_count)?];
+                let symbol = self.declare_variable(name, Type::Any, position)?;
+                unsupported("circuit", Some(symbol), operands, self.analyze_block(body, position)?)
+            }
+            ASTNode::NetworkDefinition { name, body } => {
+                let symbol = self.declare_variable(name, Type::Any, position)?;
+                unsupported("network", Some(symbol), Vec::new(), self.analyze_block(body, position)?)
+            }
+            ASTNode::GateApplication { qubit_indices, parameters, .. } => {
+                let mut operands = self.analyze_expressions(qubit_indices)?;
+                if let Some(parameters) = parameters {
+                    operands.extend(self.analyze_expressions(parameters)?);
+                }
+                unsupported("gate", None, operands, Vec::new())
+            }
+            ASTNode::MeasureStatement { qubits, .. } => {
+                unsupported("measurement", None, self.analyze_expressions(qubits)?, Vec::new())
+            }
+            ASTNode::LayerDefinition { parameters, .. } => {
+                unsupported("layer", None, self.analyze_expressions(parameters)?, Vec::new())
+            }
+            ASTNode::LossFunctionDefinition { parameters, .. } => {
+                unsupported("loss function", None, self.analyze_expressions(parameters)?, Vec::new())
+            }
+            ASTNode::TrainStatement { model, data, labels, epochs, batch_size } => {
+                let mut operands = vec![
+                    self.analyze_expression(model)?,
+                    self.analyze_expression(data)?,
+                    self.analyze_expression(labels)?,
+                ];
+                for option in [epochs, batch_size].into_iter().flatten() {
+                    operands.push(self.analyze_expression(option)?);
+                }
+                unsupported("training", None, operands, Vec::new())
+            }
+            ASTNode::OptimizeStatement { model, optimizer, loss_function } => {
+                let operands = vec![
+                    self.analyze_expression(model)?,
+                    self.analyze_expression(optimizer)?,
+                    self.analyze_expression(loss_function)?,
+                ];
+                unsupported("optimization", None, operands, Vec::new())
+            }
+            // Calls and other expressions used as statements
+            _ => hir::StatementKind::Expression(self.analyze_node(&statement.node, position, statement.span)?),
+        };
+        Ok(hir::Statement { kind, position: position.clone(), span: statement.span })
+    }
+
+    /// Check a `kembalikan` of `value`, of type `found`, against the return type of the enclosing function
+    fn check_return(&mut self, value: Option<&PositionedASTNode>, found: Option<Type>, position: &Position) -> Result<(), SemanticError> {
+        let Some(return_type) = self.return_types.last_mut() else {
+            return Ok(());
+        };
+        if !return_type.annotated {
+            return_type.found.extend(found);
+            return Ok(());
+        }
+        let expected = return_type.expected.clone();
+        let context = format!("The function is declared to return '{}'", self.unifier.resolve(&expected));
+        match (value, found) {
+            (Some(value), Some(found)) => self.expect_type(&expected, &found, value, context),
+            (None, _) if !matches!(self.unifier.resolve(&expected), Type::Optional(_))
+                && !self.unifier.unify(&expected, &Type::Void) => {
+                Err(self.type_mismatch(&expected, &Type::Void, position, None, context))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Record that `symbol` was given a value of type `found`, which may be `nol` if it is optional
+    fn track_assignment(&mut self, symbol: SymbolId, found: &Type) {
+        if let Type::Optional(_) = self.unifier.resolve(found) {
+            self.non_null.remove(&symbol);
+        } else {
+            self.non_null.insert(symbol);
+        }
+    }
+
+    /// Forget what is proved of the variables `statements` assign, for statements that may run them
+    /// any number of times
+    fn forget_assigned(&mut self, statements: &[Statement]) {
+        let mut names = HashSet::new();
+        assigned_names(statements, &mut names);
+        for name in names {
+            if let Some(symbol) = self.symbol_table.lookup_symbol(name).map(symbol_id) {
+                self.non_null.remove(&symbol);
+            }
+        }
+    }
+
+    /// Symbol of the variable named by `node`, if the analysis tracks whether it is `nol`
+    fn narrowable(&self, node: &ASTNode) -> Option<SymbolId> {
+        let ASTNode::Identifier(name) = node else {
+            return None;
+        };
+        let symbol = self.symbol_table.lookup_symbol(name).map(symbol_id)?;
+        match self.symbols[symbol.0].kind {
+            SymbolKind::Variable { .. } | SymbolKind::Parameter => Some(symbol),
+            _ => None,
+        }
+    }
+
+    /// Analyze a condition, which must be true or false; `context` says where it is
+    fn analyze_condition(&mut self, condition: &PositionedASTNode, context: &str) -> Result<hir::Expression, SemanticError> {
+        let analyzed = self.analyze_expression(condition)?;
+        self.expect_type(&Type::Bool, &analyzed.ty, condition, context.to_string())?;
+        Ok(analyzed)
+    }
+
+    /// Variables that are not `nol` when `condition` holds, and when it does not
+    fn null_facts(&self, condition: &ASTNode) -> (HashSet<SymbolId>, HashSet<SymbolId>) {
+        match condition {
+            ASTNode::BinaryOp { left, operator, right } => match operator {
+                BinaryOperator::Equal | BinaryOperator::SamaDengan | BinaryOperator::NotEqual => {
+                    let variable = match (&left.node, &right.node) {
+                        (ASTNode::Null, other) | (other, ASTNode::Null) => self.narrowable(other),
+                        _ => None,
+                    };
+                    let facts = variable.into_iter().collect();
+                    if *operator == BinaryOperator::NotEqual {
+                        (facts, HashSet::new())
+                    } else {
+                        (HashSet::new(), facts)
+                    }
+                }
+                // An instance of a class is never `nol`
+                BinaryOperator::Is => (self.narrowable(&left.node).into_iter().collect(), HashSet::new()),
+                BinaryOperator::And | BinaryOperator::Or => {
+                    let (left_true, left_false) = self.null_facts(&left.node);
+                    let (right_true, right_false) = self.null_facts(&right.node);
+                    if *operator == BinaryOperator::And {
+                        (&left_true | &right_true, &left_false & &right_false)
+                    } else {
+                        (&left_true & &right_true, &left_false | &right_false)
+                    }
+                }
+                _ => (HashSet::new(), HashSet::new()),
+            },
+            ASTNode::UnaryOp { operator: UnaryOperator::Not | UnaryOperator::Tidak, operand } => {
+                let (when_true, when_false) = self.null_facts(&operand.node);
+                (when_false, when_true)
+            }
+            _ => (HashSet::new(), HashSet::new()),
+        }
+    }
+
+    /// Check that a value of type `ty`, written as `value`, cannot be `nol` where it is used
+    fn expect_non_null(&self, ty: &Type, value: &PositionedASTNode) -> Result<(), SemanticError> {
+        let Type::Optional(inner) = self.unifier.resolve(ty) else {
+            return Ok(());
+        };
+        let described = describe(&value.node).unwrap_or_else(|| format!("value of type '{}?'", inner));
+        Err(SemanticError::PossiblyNull {
+            value: described.clone(),
+            position: value.position.clone(),
+            context: format!("'{}' has the optional type '{}?', and nothing before this use proves it is not 'nol'", described, inner),
+            suggestion: "Check it against 'nol' first, use '?.' to skip the access when it is 'nol', or give a default with '?:'".to_string(),
+            example: NULL_EXAMPLE.to_string(),
+        })
+    }
+
+    /// Analyze a function body with its parameters declared, given the function's type
+    fn analyze_function(&mut self, parameters: &[FunctionParameter], function_type: Type, annotated: bool, body: &[Statement]) -> Result<(Vec<SymbolId>, Vec<hir::Statement>), SemanticError> {
+        let (parameter_types, return_type, variadic) = match function_type {
+            Type::Function { parameters, return_type, variadic } => (parameters, *return_type, variadic),
+            _ => (Vec::new(), Type::Any, false),
+        };
+        let first_symbol = self.symbols.len();
+        let mut parameter_symbols = Vec::new();
+        for (index, parameter) in parameters.iter().enumerate() {
+            let mut parameter_type = parameter_types.get(index).cloned().unwrap_or(Type::Any);
+            // `...angka` collects the remaining arguments in a list
+            if variadic && index + 1 == parameters.len() {
+                parameter_type = Type::List(Box::new(parameter_type));
+            }
+            let info = VariableInfo {
+                var_type: parameter.type_annotation.clone(),
+                inferred_type: Some(parameter_type),
+                is_constant: !parameter.mutable,
+            };
+            parameter_symbols.push(self.declare(&parameter.name, SymbolType::Parameter(info), true, &parameter.position)?);
+        }
+
+        self.return_types.push(ReturnType { expected: return_type.clone(), annotated, found: Vec::new(), first_symbol });
+        // The body may run after the variables of the enclosing function are assigned again, and is
+        // outside the loops around the function
+        let non_null = std::mem::take(&mut self.non_null);
+        let loops = std::mem::take(&mut self.loops);
+        let result = self.analyze_statements(body);
+        self.non_null = non_null;
+        self.loops = loops;
+        let found = self.return_types.pop().map_or_else(Vec::new, |return_type| return_type.found);
+
+        // Without a declared type, a function returns nothing if it never returns a value, and `any`
+        // if it returns values of different types
+        if found.is_empty() {
+            if let Type::Variable(_) = self.unifier.resolve(&return_type) {
+                self.unifier.unify(&return_type, &Type::Void);
+            }
+        } else if !self.unifier.unify_all(&return_type, &found) {
+            self.unifier.unify(&return_type, &Type::Any);
+        }
+        Ok((parameter_symbols, result?))
+    }
+
+    /// Analyze a body that binds one variable, such as a loop or a catch block
+    fn analyze_loop(&mut self, variable: &str, variable_type: Type, body: &[Statement], position: &Position) -> Result<(SymbolId, Vec<hir::Statement>), SemanticError> {
+        self.symbol_table.enter_scope(position.clone());
+        self.loops += 1;
+        let result = self.declare_variable(variable, variable_type, position)
+            .and_then(|symbol| Ok((symbol, self.analyze_statements(body)?)));
+        self.loops -= 1;
+        self.symbol_table.exit_scope();
+        result
+    }
+
+    /// Analyze the body of a `selama` or `ulangi` loop, where `pecah` and `lanjut` may be used
+    fn analyze_loop_body(&mut self, body: &[Statement], position: &Position) -> Result<Vec<hir::Statement>, SemanticError> {
+        self.loops += 1;
+        let result = self.analyze_block(body, position);
+        self.loops -= 1;
+        result
+    }
+
+    /// Analyze a `tangkap` clause of a `coba` statement, after the clauses `earlier`. Its variable holds an
+    /// instance of the class it catches, or any error when it names none
+    fn analyze_catch(&mut self, catch: &CatchClause, earlier: &[hir::Catch]) -> Result<hir::Catch, SemanticError> {
+        let position = &catch.position;
+        let variable_type = match &catch.class {
+            Some(class) => {
+                self.use_name(class, position)?;
+                match self.class_info(class) {
+                    Some(info) if !info.is_interface && info.type_parameters.is_empty() => Type::named(class),
+                    Some(info) if !info.is_interface => {
+                        return Err(SemanticError::GenericCatch {
+                            class: class.clone(),
+                            position: position.clone(),
+                            context: "Which type arguments an error was created with is not known when it is caught".to_string(),
+                            suggestion: "Catch a class without type parameters, or leave out the class to catch every error".to_string(),
+                            example: TRY_EXAMPLE.to_string(),
+                        });
+                    }
+                    _ => {
+                        return Err(SemanticError::NotCatchable {
+                            name: class.clone(),
+                            position: position.clone(),
+                            context: "A 'tangkap' clause catches the errors that are instances of a class".to_string(),
+                            suggestion: "Name a class, or leave out the class to catch every error".to_string(),
+                            example: TRY_EXAMPLE.to_string(),
+                        });
+                    }
+                }
+            }
+            None => Type::Any,
+        };
+
+        // An error reaches a clause only if no clause above it catches it
+        let covered = earlier.iter().any(|clause| match (&clause.class, &catch.class) {
+            (None, _) => true,
+            (Some(caught), Some(class)) => self.is_subclass(class, caught),
+            (Some(_), None) => false,
+        });
+        if covered {
+            return Err(SemanticError::UnreachablePattern {
+                case: match (&catch.variable, &catch.class) {
+                    (Some(variable), Some(class)) => format!("'tangkap ({}: {})'", variable, class),
+                    (Some(variable), None) => format!("'tangkap {}'", variable),
+                    (None, _) => "'tangkap'".to_string(),
+                },
+                position: position.clone(),
+                context: "The clauses above it already catch every error this one catches".to_string(),
+                suggestion: "Remove the clause, or move it above the clauses that catch its errors".to_string(),
+                example: TRY_EXAMPLE.to_string(),
+            });
+        }
+
+        let (variable, body) = match &catch.variable {
+            Some(variable) => {
+                let (variable, body) = self.analyze_loop(variable, variable_type, &catch.body, position)?;
+                (Some(variable), body)
+            }
+            None => (None, self.analyze_block(&catch.body, position)?),
+        };
+        Ok(hir::Catch { variable, class: catch.class.clone(), body, position: position.clone() })
+    }
+
+    /// Analyze a case of a `ketika` statement on a value of type `ty`, in the scope of the names its pattern binds
+    fn analyze_case(&mut self, case: &MatchCase, ty: &Type) -> Result<hir::MatchCase, SemanticError> {
+        let pattern = self.analyze_pattern(&case.pattern, ty)?;
+        let guard = match &case.guard {
+            Some(guard) => Some(self.analyze_condition(guard, "The condition after 'jika' in a 'kasus' must be true or false")?),
+            None => None,
+        };
+        let body = self.analyze_statements(&case.body)?;
+        Ok(hir::MatchCase { pattern, guard, body, position: case.position.clone() })
+    }
+
+    /// Check a pattern against the type `ty` of the values it is matched with, and declare the names it binds
+    fn analyze_pattern(&mut self, pattern: &Pattern, ty: &Type) -> Result<hir::Pattern, SemanticError> {
+        let position = &pattern.position;
+        let kind = match &pattern.kind {
+            PatternKind::Wildcard => hir::PatternKind::Wildcard,
+            // A bare name is a variant without fields if an enum declares it, and binds the value otherwise
+            PatternKind::Name(name) if self.variant_symbol(name).is_some() => self.analyze_variant_pattern(name, &[], ty, position)?,
+            PatternKind::Name(name) => hir::PatternKind::Binding(self.declare_variable(name, ty.clone(), position)?),
+            PatternKind::Literal(literal) => {
+                let expected = match literal {
+                    LiteralPattern::Integer(_) => Type::Int,
+                    LiteralPattern::Float(_) => Type::Float,
+                    LiteralPattern::String(_) => Type::String,
+                    LiteralPattern::Boolean(_) => Type::Bool,
+                    LiteralPattern::Null => Type::Optional(Box::new(self.unifier.fresh())),
+                };
+                self.expect_pattern_type(&expected, ty, pattern)?;
+                hir::PatternKind::Literal(literal.clone())
+            }
+            PatternKind::Range { start, end, inclusive } => {
+                self.expect_pattern_type(&Type::Int, ty, pattern)?;
+                let end = if *inclusive { *end } else { end.saturating_sub(1) };
+                hir::PatternKind::Range { start: *start, end }
+            }
+            PatternKind::Tuple(elements) => {
+                let element_types: Vec<Type> = elements.iter().map(|_| self.unifier.fresh()).collect();
+                self.expect_pattern_type(&Type::Tuple(element_types.clone()), ty, pattern)?;
+                let mut analyzed = Vec::new();
+                for (element, element_type) in elements.iter().zip(&element_types) {
+                    analyzed.push(self.analyze_pattern(element, element_type)?);
+                }
+                hir::PatternKind::Tuple(analyzed)
+            }
+            PatternKind::Variant { name, fields } => self.analyze_variant_pattern(name, fields, ty, position)?,
+        };
+        Ok(hir::Pattern { kind, ty: ty.clone(), position: position.clone() })
+    }
+
+    /// Check a pattern that matches the values variant `name` builds from `fields`
+    fn analyze_variant_pattern(&mut self, name: &str, fields: &[Pattern], ty: &Type, position: &Position) -> Result<hir::PatternKind, SemanticError> {
+        let Some((enumeration, index)) = self.variant_symbol(name) else {
+            return Err(SemanticError::UnknownVariant {
+                name: name.to_string(),
+                position: position.clone(),
+                context: "Only the variants of an enum take fields in a 'kasus' pattern".to_string(),
+                suggestion: format!("Declare '{}' in a 'tipe', or match the value with a name or a literal", name),
+                example: MATCH_EXAMPLE.to_string(),
+            });
+        };
+        if let Some(used) = self.symbol_table.lookup_symbol_mut(name) {
+            used.is_used = true;
+        }
+        let (field_types, enum_type) = match self.variant_type(name) {
+            Type::Function { parameters, return_type, .. } => (parameters, *return_type),
+            _ => (Vec::new(), Type::Any),
+        };
+        if !self.unifier.unify(&enum_type, ty) {
+            return Err(self.type_mismatch(ty, &enum_type, position, None, format!("'{}' builds values of type '{}'", name, self.unifier.resolve(&enum_type))));
+        }
+        if fields.len() != field_types.len() {
+            return Err(SemanticError::WrongArgumentCount {
+                name: name.to_string(),
+                expected: field_types.len(),
+                found: fields.len(),
+                position: position.clone(),
+                context: format!("'{}' is declared with {} field(s)", name, field_types.len()),
+                suggestion: "Give one pattern for each field of the variant, or '_' for the ones you do not need".to_string(),
+                example: MATCH_EXAMPLE.to_string(),
+            });
+        }
+        let mut analyzed = Vec::new();
+        for (field, field_type) in fields.iter().zip(&field_types) {
+            analyzed.push(self.analyze_pattern(field, field_type)?);
+        }
+        Ok(hir::PatternKind::Variant { enumeration, index, fields: analyzed })
+    }
+
+    /// Check that a pattern matching values of type `expected` is matched with values of type `found`
+    fn expect_pattern_type(&mut self, expected: &Type, found: &Type, pattern: &Pattern) -> Result<(), SemanticError> {
+        if self.unifier.unify(expected, found) {
+            return Ok(());
+        }
+        let context = format!("The pattern '{}' matches values of type '{}'", pattern, self.unifier.resolve(expected));
+        Err(self.type_mismatch(expected, found, &pattern.position, None, context))
+    }
+
+    /// Report the cases of a `ketika` statement on a value of type `ty` that can never run, and the
+    /// values none of them matches when it has no `sebaliknya`
+    fn check_cases(&self, cases: &[MatchCase], analyzed: &[hir::MatchCase], ty: &Type, has_else: bool, position: &Position) -> Result<(), SemanticError> {
+        let checked: Vec<patterns::Case> = analyzed.iter()
+            .map(|case| patterns::Case { pattern: checked_pattern(&case.pattern), guarded: case.guard.is_some() })
+            .collect();
+        let report = patterns::check(&checked, &self.unifier.resolve(ty), &|ty: &Type| self.enum_variants(ty));
+        if let Some(&index) = report.unreachable.first() {
+            return Err(SemanticError::UnreachablePattern {
+                case: format!("'kasus {}'", cases[index].pattern),
+                position: cases[index].position.clone(),
+                context: "The cases above it already match every value this one matches".to_string(),
+                suggestion: "Remove the case, or move it above the cases that match the same values".to_string(),
+                example: MATCH_EXAMPLE.to_string(),
+            });
+        }
+        match report.missing {
+            Some(missing) if !has_else => Err(SemanticError::NonExhaustiveMatch {
+                missing: format!("'{}'", missing),
+                position: position.clone(),
+                context: "A 'ketika' statement must have a case for every value it can be given".to_string(),
+                suggestion: format!("Add 'kasus {}:', or a 'sebaliknya:' for the values no case matches", missing),
+                example: MATCH_EXAMPLE.to_string(),
+            }),
+            None if has_else => Err(SemanticError::UnreachablePattern {
+                case: "'sebaliknya'".to_string(),
+                position: position.clone(),
+                context: "The cases above it already match every value".to_string(),
+                suggestion: "Remove the 'sebaliknya' block".to_string(),
+                example: MATCH_EXAMPLE.to_string(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Analyze an expression, infer its type and lower it to the HIR
+    fn analyze_expression(&mut self, expression: &PositionedASTNode) -> Result<hir::Expression, SemanticError> {
+        self.analyze_node(&expression.node, &expression.position, expression.span)
+    }
+
+    /// Analyze an expression node found at `position`, infer its type and lower it to the HIR
+    fn analyze_node(&mut self, node: &ASTNode, position: &Position, span: Span) -> Result<hir::Expression, SemanticError> {
+        use hir::ExpressionKind as Kind;
+
+        let (kind, ty) = match node {
+            ASTNode::Integer(value) => (Kind::Integer(*value), Type::Int),
+            ASTNode::Float(value) => (Kind::Float(*value), Type::Float),
+            ASTNode::String(value) => (Kind::String(value.clone()), Type::String),
+            ASTNode::Boolean(value) => (Kind::Boolean(*value), Type::Bool),
+            ASTNode::Null => (Kind::Null, Type::Optional(Box::new(self.unifier.fresh()))),
+            ASTNode::SelfRef => {
+                self.capture_self();
+                (Kind::SelfRef, match &self.current_class {
+                    Some((class, _)) => self.class_type(class),
+                    None => Type::Any,
+                })
+            }
+            ASTNode::SuperRef => match self.current_class.as_ref().and_then(|(class, _)| self.class_info(class)?.parent.clone()) {
+                Some(parent) => {
+                    self.capture_self();
+                    (Kind::SelfRef, Type::named(&parent))
+                }
+                None => return Err(SemanticError::SuperOutsideSubclass {
+                    position: position.clone(),
+                    context: "'super' is the current object seen as an instance of the parent class".to_string(),
+                    suggestion: "Name the parent class after the class name, or use 'ini' instead".to_string(),
+                    example: "kelas Kucing(Hewan):\n    fungsi suara():\n        kembalikan super.suara()".to_string(),
+                }),
+            },
+            ASTNode::Identifier(name) => self.analyze_name(name, position, span)?,
+            ASTNode::BinaryOp { left, operator, right } => {
+                let left_hir = self.analyze_expression(left)?;
+                // `pengguna != nol dan pengguna.aktif` only reads the right operand where the left one proved
+                // `pengguna` is not `nol`
+                let facts = match operator {
+                    BinaryOperator::And => self.null_facts(&left.node).0,
+                    BinaryOperator::Or => self.null_facts(&left.node).1,
+                    _ => HashSet::new(),
+                };
+                let before = self.non_null.clone();
+                self.non_null.extend(facts);
+                let right_hir = self.analyze_expression(right);
+                self.non_null = before;
+                let right_hir = right_hir?;
+                let ty = self.binary_type(left, &left_hir.ty, operator, right, &right_hir.ty)?;
+                (Kind::Binary { left: Box::new(left_hir), operator: operator.clone(), right: Box::new(right_hir) }, ty)
+            }
+            ASTNode::UnaryOp { operator, operand } => {
+                if let (UnaryOperator::Increment | UnaryOperator::Decrement, ASTNode::Identifier(name)) = (operator, &operand.node) {
+                    self.check_assignable(name, position)?;
+                }
+                let operand_hir = self.analyze_expression(operand)?;
+                let ty = match operator {
+                    UnaryOperator::Not | UnaryOperator::Tidak => {
+                        self.expect_type(&Type::Bool, &operand_hir.ty, operand, "'tidak' negates a condition, which is true or false".to_string())?;
+                        Type::Bool
+                    }
+                    UnaryOperator::Negate | UnaryOperator::Balik
+                    | UnaryOperator::Increment | UnaryOperator::Decrement => operand_hir.ty.clone(),
+                    UnaryOperator::BitNot => {
+                        self.expect_type(&Type::Int, &operand_hir.ty, operand, "'~' flips the bits of an integer".to_string())?;
+                        Type::Int
+                    }
+                    UnaryOperator::Dereference | UnaryOperator::AddressOf => Type::Any,
+                };
+                let operand = operand_hir;
+                // `x++` assigns `x`
+                if let (UnaryOperator::Increment | UnaryOperator::Decrement, Kind::Variable(symbol)) = (operator, &operand.kind) {
+                    self.assigned.insert(*symbol);
+                }
+                (Kind::Unary { operator: operator.clone(), operand: Box::new(operand) }, ty)
+            }
+            ASTNode::Spread(value) => {
+                let value = self.analyze_expression(value)?;
+                let ty = value.ty.clone();
+                (Kind::Spread(Box::new(value)), ty)
+            }
+            ASTNode::AwaitExpr(value) => {
+                let value = self.analyze_expression(value)?;
+                let ty = value.ty.clone();
+                (Kind::Await(Box::new(value)), ty)
+            }
+            ASTNode::OptionalExpr { value } => {
+                let value = self.analyze_expression(value)?;
+                let ty = Type::Optional(Box::new(value.ty.clone()));
+                (Kind::Optional(Box::new(value)), ty)
+            }
+            ASTNode::YieldExpr(value) => (Kind::Yield(Box::new(self.analyze_expression(value)?)), Type::Any),
+            ASTNode::PointerExpr { value }
+            | ASTNode::ReferenceExpr { value }
+            | ASTNode::AtomicLoadExpr { address: value }
+            | ASTNode::CreateAddressExpr { initial_value: value }
+            | ASTNode::SuperpositionExpr { qubit: value }
+            | ASTNode::GradientExpression { expression: value } => {
+                let construct = match node {
+                    ASTNode::PointerExpr { .. } => "pointer",
+                    ASTNode::ReferenceExpr { .. } => "reference",
+                    ASTNode::AtomicLoadExpr { .. } => "atomic load",
+                    ASTNode::CreateAddressExpr { .. } => "address",
+                    ASTNode::SuperpositionExpr { .. } => "superposition",
+                    _ => "gradient",
+                };
+                let operands = vec![self.analyze_expression(value)?];
+                (Kind::Unsupported { construct, operands }, Type::Any)
+            }
+            ASTNode::ChannelReceiveExpr { channel } => {
+                let channel = self.analyze_expression(channel)?;
+                let ty = Self::channel_element(&self.unifier.resolve(&channel.ty));
+                (Kind::ChannelReceive(Box::new(channel)), ty)
+            }
+            ASTNode::MemberAccess { object: object_node, property } => {
+                let object = self.analyze_expression(object_node)?;
+                self.expect_non_null(&object.ty, object_node)?;
+                if let Some(class) = self.object_class(&object.ty) {
+                    self.check_interface_member(&class, property, false, position)?;
+                    self.check_access(&class, property, position)?;
+                }
+                let ty = self.field_type(&object.ty, property).unwrap_or(Type::Any);
+                (Kind::Member { object: Box::new(object), field: property.clone() }, ty)
+            }
+            ASTNode::InterpolatedString(parts) => {
+                let mut pieces = Vec::new();
+                for part in parts {
+                    pieces.push(match part {
+                        InterpolationPart::Text(text) => hir::Expression {
+                            kind: Kind::String(text.clone()),
+                            ty: Type::String,
+                            position: position.clone(),
+                            span,
+                        },
+                        InterpolationPart::Expression(expression) => self.analyze_expression(expression)?,
+                    });
+                }
+                (Kind::Interpolation(pieces), Type::String)
+            }
+            ASTNode::FunctionCall { name, arguments } => self.analyze_call(name, arguments, position, span)?,
+            ASTNode::MethodCall { object, method, arguments } => {
+                self.analyze_method_call(object, method, arguments, position)?
+            }
+            ASTNode::Index { object: object_node, index } => {
+                let object = self.analyze_expression(object_node)?;
+                self.expect_non_null(&object.ty, object_node)?;
+                let index = self.analyze_expression(index)?;
+                let ty = match self.unifier.resolve(&object.ty) {
+                    Type::List(element) | Type::Map(_, element) => *element,
+                    Type::String => Type::String,
+                    _ => Type::Any,
+                };
+                (Kind::Index { object: Box::new(object), index: Box::new(index) }, ty)
+            }
+            ASTNode::Elvis { value: value_node, default: default_node } => {
+                let value = self.analyze_expression(value_node)?;
+                let inner = self.optional_inner(&value.ty);
+                let default = self.analyze_expression(default_node)?;
+                // `a ?: b ?: nol` may still be `nol`
+                let ty = if let Type::Optional(_) = self.unifier.resolve(&default.ty) {
+                    Type::Optional(Box::new(inner))
+                } else {
+                    inner
+                };
+                let context = format!("The default of '?:' is used in place of a value of type '{}'", self.unifier.resolve(&ty));
+                self.expect_type(&ty, &default.ty, default_node, context)?;
+                (Kind::Elvis { value: Box::new(value), default: Box::new(default) }, ty)
+            }
+            ASTNode::OptionalChain { object: object_node, property, arguments } => {
+                self.analyze_optional_chain(object_node, property, arguments.as_deref(), position, span)?
+            }
+            ASTNode::Propagate(value_node) => {
+                let value = self.analyze_expression(value_node)?;
+                self.analyze_propagate(value, position, span)?
+            }
+            ASTNode::ArrayLiteral(elements) => {
+                let elements = self.analyze_expressions(elements)?;
+                let ty = Type::List(Box::new(self.common_type(&elements)));
+                (Kind::List(elements), ty)
+            }
+            ASTNode::TupleLiteral(elements) => {
+                let elements = self.analyze_expressions(elements)?;
+                let ty = Type::Tuple(elements.iter().map(|element| element.ty.clone()).collect());
+                (Kind::Tuple(elements), ty)
+            }
+            ASTNode::Lambda { parameters, return_type, body } => {
+                let mut expected = self.lambda_parameters.take().unwrap_or_default().into_iter();
+                let parameter_types = parameters.iter()
+                    .map(|parameter| match (&parameter.type_annotation, expected.next()) {
+                        (None, Some(expected)) => expected,
+                        (annotation, _) => self.annotated_or_fresh(annotation),
+                    })
+                    .collect();
+                let returned = self.annotated_or_fresh(return_type);
+                let ty = Type::function(parameter_types, returned);
+                self.symbol_table.enter_scope(position.clone());
+                self.closures.push(Closure { first_symbol: self.symbols.len(), captures: Vec::new(), captures_self: false });
+                let result = self.analyze_function(parameters, ty.clone(), return_type.is_some(), body);
+                let closure = self.closures.pop().expect("the lambda's closure was pushed above");
+                self.symbol_table.exit_scope();
+                let (parameters, body) = result?;
+                let lambda = hir::Lambda { parameters, body, captures: closure.captures, captures_self: closure.captures_self };
+                (Kind::Lambda(lambda), ty)
+            }
+            ASTNode::Pipeline { source, stages } => self.analyze_pipeline(source, stages)?,
+            ASTNode::ObjectLiteral(fields) => {
+                // Fields are visited in key order, so the HIR does not depend on the hash map's order
+                let mut names: Vec<&String> = fields.keys().collect();
+                names.sort();
+                let mut values = Vec::new();
+                for name in names {
+                    values.push((name.clone(), self.analyze_expression(&fields[name])?));
+                }
+                let ty = Type::Map(Box::new(Type::String), Box::new(self.common_type(values.iter().map(|(_, value)| value))));
+                (Kind::Map(values), ty)
+            }
+            ASTNode::ListComprehension { expression: element, variable, iterable, condition } => {
+                let iterable = self.analyze_expression(iterable)?;
+                let variable_type = self.element_type(&iterable.ty);
+                self.symbol_table.enter_scope(position.clone());
+                let result = self.analyze_comprehension(variable, variable_type, element, condition, position);
+                self.symbol_table.exit_scope();
+                let (variable, element, condition) = result?;
+                let ty = Type::List(Box::new(element.ty.clone()));
+                (Kind::Comprehension {
+                    element: Box::new(element),
+                    variable,
+                    iterable: Box::new(iterable),
+                    condition: condition.map(Box::new),
+                }, ty)
+            }
+            ASTNode::RangeExpr { start, end, inclusive } => {
+                let start = self.analyze_expression(start)?;
+                let end = self.analyze_expression(end)?;
+                let ty = Type::List(Box::new(start.ty.clone()));
+                (Kind::Range { start: Box::new(start), end: Box::new(end), inclusive: *inclusive }, ty)
+            }
+            ASTNode::EntanglementExpr { qubit1, qubit2 } => {
+                let operands = vec![self.analyze_expression(qubit1)?, self.analyze_expression(qubit2)?];
+                (Kind::Unsupported { construct: "entanglement", operands }, Type::Any)
+            }
+            ASTNode::SimulateExpr { circuit, shots } => {
+                let mut operands = vec![self.analyze_expression(circuit)?];
+                if let Some(shots) = shots {
+                    operands.push(self.analyze_expression(shots)?);
+                }
+                (Kind::Unsupported { construct: "simulation", operands }, Type::Any)
+            }
+            ASTNode::PredictExpression { model, data } => {
+                let operands = vec![self.analyze_expression(model)?, self.analyze_expression(data)?];
+                (Kind::Unsupported { construct: "prediction", operands }, Type::Any)
+            }
+            // The element type of `buat_channel(int)` names a type, not a value
+            ASTNode::MakeChannelExpr { .. } => (Kind::MakeChannel, Type::Any),
+            ASTNode::CreateConditionExpr => (Kind::Unsupported { construct: "condition", operands: Vec::new() }, Type::Any),
+            _ => (Kind::Unsupported { construct: "statement", operands: Vec::new() }, Type::Any),
+        };
+        match &kind {
+            Kind::Call { callee: hir::Callee::External(_), .. } => self.forget_after_call(false),
+            Kind::Call { callee: hir::Callee::Symbol(symbol), .. } if symbol.0 < self.first_program_symbol => self.forget_after_call(false),
+            Kind::Call { .. } | Kind::Construct { .. } | Kind::MethodCall { .. } | Kind::SuperCall { .. } => self.forget_after_call(true),
+            _ => {}
+        }
+        Ok(hir::Expression { kind, ty, position: position.clone(), span })
+    }
+
+    /// Forget what is proved of the variables a call may assign: those a lambda shares and assigns, which
+    /// the call may run, and, when it runs functions of the program, those declared outside the current
+    /// function that some function or method assigns
+    fn forget_after_call(&mut self, runs_program: bool) {
+        let first_local = self.return_types.last().map(|return_type| return_type.first_symbol);
+        let (symbols, assigned, assigned_by_functions) = (&self.symbols, &self.assigned, &self.assigned_by_functions);
+        self.non_null.retain(|symbol| {
+            let info = &symbols[symbol.0];
+            if info.captured.is_some() && assigned.contains(symbol) {
+                return false;
+            }
+            let local = first_local.is_some_and(|first| symbol.0 >= first);
+            local || !runs_program || !assigned_by_functions.contains(&info.name)
+        });
+    }
+
+    /// Analyze a pipeline; each stage takes the list the one before it produces
+    fn analyze_pipeline(&mut self, source_node: &PositionedASTNode, stages: &[PipelineStage]) -> Result<(hir::ExpressionKind, Type), SemanticError> {
+        let mut source = self.analyze_expression(source_node)?;
+        let mut ty = source.ty.clone();
+        let mut analyzed = Vec::new();
+        for stage in stages {
+            let keyword = stage.operation.keyword();
+            // `objek.saring(...)` calls the method of the object's class; stages only work on lists
+            if stage.chained && self.object_class(&ty).is_some() {
+                self.expect_non_null(&ty, source_node)?;
+                let object = match std::mem::take(&mut analyzed) {
+                    stages if stages.is_empty() => source,
+                    stages => hir::Expression {
+                        kind: hir::ExpressionKind::Pipeline { source: Box::new(source), stages },
+                        ty: ty.clone(),
+                        position: source_node.position.clone(),
+                        span: source_node.span,
+                    },
                 };
-                self.symbol_table.add_symbol(name.clone(), symbol);
+                let (kind, method_type) = self.call_method(object, None, keyword, &stage.arguments, &stage.position)?;
+                source = hir::Expression { kind, ty: method_type.clone(), position: stage.position.clone(), span: source_node.span };
+                ty = method_type;
+                continue;
+            }
+            let element = self.unifier.fresh();
+            let list = Type::List(Box::new(element.clone()));
+            if !self.unifier.unify(&list, &ty) {
+                let context = format!("'{}' is a stage of a pipeline, which works on a list", keyword);
+                return Err(self.type_mismatch(&list, &ty, &stage.position, None, context));
+            }
+
+            // Each stage is checked as a call of a function with the stage's signature
+            let (parameters, result) = match (stage.operation, stage.arguments.len()) {
+                (PipelineOperation::Filter, _) => (vec![Type::function(vec![element.clone()], Type::Bool)], list),
+                (PipelineOperation::Map, _) => {
+                    let mapped = self.unifier.fresh();
+                    (vec![Type::function(vec![element.clone()], mapped.clone())], Type::List(Box::new(mapped)))
+                }
+                (PipelineOperation::Take | PipelineOperation::Skip, _) => (vec![Type::Int], list),
+                (PipelineOperation::Sort, 0) => (Vec::new(), list),
+                (PipelineOperation::Sort, _) => (vec![Type::function(vec![element.clone(), element.clone()], Type::Int)], list),
+                (PipelineOperation::Reduce, 1) => (vec![Type::function(vec![element.clone(), element.clone()], element.clone())], element),
+                (PipelineOperation::Reduce, _) => {
+                    let accumulator = self.unifier.fresh();
+                    let function = Type::function(vec![accumulator.clone(), element], accumulator.clone());
+                    (vec![function, accumulator.clone()], accumulator)
+                }
+                (PipelineOperation::Fold, _) => {
+                    let accumulator = self.unifier.fresh();
+                    let function = Type::function(vec![accumulator.clone(), element], accumulator.clone());
+                    (vec![accumulator.clone(), function], accumulator)
+                }
+            };
+            // Values are analyzed before lambdas, so the parameters of a lambda take the types the values settle
+            let mut analyzed_arguments: Vec<Option<hir::Expression>> = stage.arguments.iter().map(|_| None).collect();
+            for lambdas in [false, true] {
+                for (index, (argument, parameter)) in stage.arguments.iter().zip(&parameters).enumerate() {
+                    if matches!(argument.node, ASTNode::Lambda { .. }) == lambdas {
+                        analyzed_arguments[index] = Some(self.analyze_argument(argument, parameter)?);
+                    }
+                }
+            }
+            let arguments: Vec<hir::Expression> = analyzed_arguments.into_iter().flatten().collect();
+            let argument_types = arguments.iter().map(|argument| argument.ty.clone()).collect();
+            ty = self.check_call(keyword, &Type::function(parameters, result), &stage.arguments, argument_types, &stage.position)?;
+
+            let count = arguments.len();
+            let mut arguments = arguments.into_iter();
+            let mut argument = || arguments.next().expect("check_call checked the number of arguments");
+            let kind = match stage.operation {
+                PipelineOperation::Filter => hir::StageKind::Filter(argument()),
+                PipelineOperation::Map => hir::StageKind::Map(argument()),
+                PipelineOperation::Take => hir::StageKind::Take(argument()),
+                PipelineOperation::Skip => hir::StageKind::Skip(argument()),
+                PipelineOperation::Sort => hir::StageKind::Sort((count == 1).then(argument)),
+                PipelineOperation::Reduce => hir::StageKind::Reduce { function: argument(), initial: (count == 2).then(argument) },
+                // `lipat(awal, f)` is `kurangi(f, awal)`
+                PipelineOperation::Fold => {
+                    let initial = argument();
+                    hir::StageKind::Reduce { function: argument(), initial: Some(initial) }
+                }
+            };
+            analyzed.push(hir::Stage { kind, ty: ty.clone() });
+        }
+        if analyzed.is_empty() {
+            return Ok((source.kind, ty));
+        }
+        Ok((hir::ExpressionKind::Pipeline { source: Box::new(source), stages: analyzed }, ty))
+    }
+
+    /// Analyze an argument passed where a value of type `expected` is required; the parameters of a lambda
+    /// without annotation take the types of the parameters of `expected`
+    fn analyze_argument(&mut self, argument: &PositionedASTNode, expected: &Type) -> Result<hir::Expression, SemanticError> {
+        if let (ASTNode::Lambda { parameters, .. }, Type::Function { parameters: expected, .. }) = (&argument.node, self.unifier.resolve(expected)) {
+            if parameters.len() == expected.len() {
+                self.lambda_parameters = Some(expected);
+            }
+        }
+        let analyzed = self.analyze_expression(argument)?;
+        // A mismatch is reported when the call is checked
+        self.unifier.unify(expected, &analyzed.ty);
+        Ok(analyzed)
+    }
+
+    /// Analyze a list of expressions and infer their types
+    fn analyze_expressions(&mut self, expressions: &[PositionedASTNode]) -> Result<Vec<hir::Expression>, SemanticError> {
+        let mut analyzed = Vec::new();
+        for expression in expressions {
+            analyzed.push(self.analyze_expression(expression)?);
+        }
+        Ok(analyzed)
+    }
+
+    /// Analyze the element and condition of a list comprehension in its own, already entered, scope
+    fn analyze_comprehension(
+        &mut self,
+        variable: &str,
+        variable_type: Type,
+        element: &PositionedASTNode,
+        condition: &Option<Box<PositionedASTNode>>,
+        position: &Position,
+    ) -> Result<(SymbolId, hir::Expression, Option<hir::Expression>), SemanticError> {
+        let variable = self.declare_variable(variable, variable_type, position)?;
+        let element = self.analyze_expression(element)?;
+        let condition = match condition {
+            Some(condition) => Some(self.analyze_condition(condition, "The condition after 'jika' in a list comprehension must be true or false")?),
+            None => None,
+        };
+        Ok((variable, element, condition))
+    }
+
+    /// Type shared by the elements of a literal; a literal of mixed values holds `any`
+    fn common_type<'a>(&mut self, elements: impl IntoIterator<Item = &'a hir::Expression>) -> Type {
+        let types: Vec<Type> = elements.into_iter().map(|element| element.ty.clone()).collect();
+        let element = self.unifier.fresh();
+        if self.unifier.unify_all(&element, &types) { element } else { Type::Any }
+    }
+
+    /// Element type of a channel, if the type is an annotated `channel<T>`
+    fn channel_element(channel: &Type) -> Type {
+        match channel.non_optional() {
+            Type::Named { name, arguments } if arguments.len() == 1
+                && matches!(name.to_lowercase().as_str(), "channel" | "saluran") => arguments[0].clone(),
+            _ => Type::Any,
+        }
+    }
+
+    /// `ini` in the body of the class declaring `member`
+    fn self_expression(&mut self, member: SymbolId, position: &Position, span: Span) -> hir::Expression {
+        self.capture_self();
+        let ty = match self.symbols[member.0].kind {
+            SymbolKind::Field { class } | SymbolKind::Method { class, .. } => self.symbols[class.0].ty.clone(),
+            _ => Type::Any,
+        };
+        hir::Expression { kind: hir::ExpressionKind::SelfRef, ty, position: position.clone(), span }
+    }
+
+    /// Bind a name to the symbol it refers to, and infer the type of its value
+    fn analyze_name(&mut self, name: &str, position: &Position, span: Span) -> Result<(hir::ExpressionKind, Type), SemanticError> {
+        // A field inherited from a parent class is read from the instance, as `ini.nama`
+        if self.symbol_table.lookup_symbol(name).is_none() {
+            let class = self.current_class.as_ref().map(|(class, _)| class.clone());
+            if let Some((class, ty)) = class.and_then(|class| Some((class.clone(), self.field_type(&self.class_type(&class), name)?))) {
+                // `pribadi` fields of the parent are as unreachable here as through `ini`
+                self.check_access(&class, name, position)?;
+                let object = self.analyze_node(&ASTNode::SelfRef, position, span)?;
+                return Ok((hir::ExpressionKind::Member { object: Box::new(object), field: name.to_string() }, ty));
+            }
+        }
+        let Some(symbol) = self.use_name(name, position)? else {
+            return Ok((hir::ExpressionKind::External(name.to_string()), Type::Any));
+        };
+        let ty = match self.symbol_table.lookup_symbol(name).map(|symbol| &symbol.symbol_type) {
+            Some(SymbolType::Variable(info) | SymbolType::Parameter(info)) => {
+                info.inferred_type.clone().unwrap_or(Type::Any)
+            }
+            // Each use of a function may pick its own types for what the function leaves open
+            Some(SymbolType::Function(signature)) => {
+                let signature = signature.clone();
+                let (_, substitution) = self.fresh_type_arguments(&signature.type_parameters);
+                match &signature.inferred_type {
+                    Some(function_type) => self.unifier.instantiate(function_type).substitute(&substitution),
+                    None => Type::Any,
+                }
+            }
+            // Classes and the names of open imports
+            _ => Type::Any,
+        };
+        // A variant without fields is a value of its enum
+        if let SymbolKind::Variant { enumeration, index } = self.symbols[symbol.0].kind {
+            return match ty {
+                Type::Function { parameters, return_type, .. } if parameters.is_empty() => {
+                    Ok((hir::ExpressionKind::Variant { enumeration, index, arguments: Vec::new() }, *return_type))
+                }
+                Type::Function { parameters, .. } => Err(SemanticError::WrongArgumentCount {
+                    name: name.to_string(),
+                    expected: parameters.len(),
+                    found: 0,
+                    position: position.clone(),
+                    context: format!("'{}' is declared with {} field(s)", name, parameters.len()),
+                    suggestion: format!("Build it with a value for each field, as '{}(...)'", name),
+                    example: MATCH_EXAMPLE.to_string(),
+                }),
+                _ => Ok((hir::ExpressionKind::Variant { enumeration, index, arguments: Vec::new() }, Type::Any)),
+            };
+        }
+        // A constant is read as its value
+        if let Some(constant) = self.constants.get(&symbol) {
+            return Ok((constant.kind(), ty));
+        }
+        // A field of the enclosing class is read from the instance, as `ini.nama`
+        if let SymbolKind::Field { .. } = self.symbols[symbol.0].kind {
+            let object = self.self_expression(symbol, position, span);
+            return Ok((hir::ExpressionKind::Member { object: Box::new(object), field: name.to_string() }, ty));
+        }
+        // A variable a check proved not to be `nol` is read as the value it holds
+        if self.non_null.contains(&symbol) {
+            if let Type::Optional(inner) = self.unifier.resolve(&ty) {
+                let variable = hir::Expression { kind: hir::ExpressionKind::Variable(symbol), ty, position: position.clone(), span };
+                return Ok((hir::ExpressionKind::Unwrap(Box::new(variable)), *inner));
+            }
+        }
+        Ok((hir::ExpressionKind::Variable(symbol), ty))
+    }
+
+    /// Type of what an optional of type `ty` holds; a value whose type is not known yet is taken to be optional
+    fn optional_inner(&mut self, ty: &Type) -> Type {
+        match self.unifier.resolve(ty) {
+            Type::Optional(inner) => *inner,
+            Type::Variable(_) => {
+                let inner = self.unifier.fresh();
+                self.unifier.unify(ty, &Type::Optional(Box::new(inner.clone())));
+                inner
+            }
+            other => other,
+        }
+    }
+
+    /// Analyze `objek?.anggota` or `objek?.metode(...)`, which is `nol` when the object is, and otherwise
+    /// reads the member of the object it holds
+    fn analyze_optional_chain(
+        &mut self,
+        object_node: &PositionedASTNode,
+        property: &str,
+        arguments: Option<&[PositionedASTNode]>,
+        position: &Position,
+        span: Span,
+    ) -> Result<(hir::ExpressionKind, Type), SemanticError> {
+        let object = self.analyze_expression(object_node)?;
+        let inner = self.optional_inner(&object.ty);
+        // The access reads a variable holding the object, named as the object is when it is a variable
+        let name = match &object_node.node {
+            ASTNode::Identifier(name) => name.clone(),
+            _ => "nilai?".to_string(),
+        };
+        let receiver = PositionedASTNode {
+            node: ASTNode::Identifier(name.clone()),
+            position: object_node.position.clone(),
+            span: object_node.span,
+        };
+        self.symbol_table.enter_scope(position.clone());
+        let result = self.declare_variable(&name, inner, position).and_then(|binding| {
+            let access = match arguments {
+                Some(arguments) => {
+                    let (kind, ty) = self.analyze_method_call(&receiver, property, arguments, position)?;
+                    hir::Expression { kind, ty, position: position.clone(), span }
+                }
+                None => {
+                    let member = ASTNode::MemberAccess { object: Box::new(receiver), property: property.to_string() };
+                    self.analyze_node(&member, position, span)?
+                }
+            };
+            Ok((binding, access))
+        });
+        self.symbol_table.exit_scope();
+        let (binding, access) = result?;
+        let ty = match self.unifier.resolve(&access.ty) {
+            optional @ Type::Optional(_) => optional,
+            Type::Void => Type::Void,
+            _ => Type::Optional(Box::new(access.ty.clone())),
+        };
+        let kind = hir::ExpressionKind::OptionalChain { object: Box::new(object), binding, access: Box::new(access) };
+        Ok((kind, ty))
+    }
+
+    /// Analyze `nilai?`, which gives the value an optional or a `Sukses` holds, and otherwise returns the
+    /// `nol` or the `Gagal` from the enclosing function
+    fn analyze_propagate(&mut self, value: hir::Expression, position: &Position, span: Span) -> Result<(hir::ExpressionKind, Type), SemanticError> {
+        let suggestion = "Check the value with 'jika' or 'ketika' instead, or declare the function to return an optional or a 'Hasil'";
+        let Some(return_type) = self.return_types.last() else {
+            return Err(SemanticError::PropagateOutsideFunction {
+                position: position.clone(),
+                context: "'?' returns early from the enclosing function when the value is 'nol' or a 'Gagal'".to_string(),
+                suggestion: suggestion.to_string(),
+                example: PROPAGATE_EXAMPLE.to_string(),
+            });
+        };
+        let (expected, annotated) = (return_type.expected.clone(), return_type.annotated);
+        let (returned, error, result) = match self.unifier.resolve(&value.ty) {
+            Type::Optional(inner) => (Type::Optional(Box::new(self.unifier.fresh())), None, *inner),
+            Type::Named { name, arguments }
+                if arguments.len() == 2 && self.symbol_table.lookup_symbol(&name).map(symbol_id) == self.result_enum => {
+                let returned = Type::Named { name, arguments: vec![self.unifier.fresh(), arguments[1].clone()] };
+                (returned, Some(arguments[1].clone()), arguments[0].clone())
+            }
+            other => {
+                return Err(SemanticError::PropagateNonOptional {
+                    found: other.to_string(),
+                    position: position.clone(),
+                    context: "Only a 'nol' or a 'Gagal' can be returned early by '?'".to_string(),
+                    suggestion: "Remove the '?', since the value is always there".to_string(),
+                    example: PROPAGATE_EXAMPLE.to_string(),
+                });
+            }
+        };
+        if !annotated {
+            if let Some(return_type) = self.return_types.last_mut() {
+                return_type.found.push(returned);
+            }
+        } else if !self.unifier.unify(&expected, &returned) {
+            return Err(SemanticError::PropagateReturnMismatch {
+                returned: self.unifier.resolve(&returned).to_string(),
+                expected: self.unifier.resolve(&expected).to_string(),
+                position: position.clone(),
+                context: "The value '?' returns when it cannot go on must fit the return type of the function".to_string(),
+                suggestion: suggestion.to_string(),
+                example: PROPAGATE_EXAMPLE.to_string(),
+            });
+        }
+
+        let expression = |kind, ty| hir::Expression { kind, ty, position: position.clone(), span };
+        let (error, early) = match (error, self.result_enum) {
+            (Some(error_type), Some(enumeration)) => {
+                // The error of a `Gagal` is kept in a variable no program can name, and returned in a new `Gagal`
+                self.symbol_table.enter_scope(position.clone());
+                let error = self.declare_variable("galat?", error_type.clone(), position);
+                self.symbol_table.exit_scope();
+                let error = error?;
+                let arguments = vec![expression(hir::ExpressionKind::Variable(error), error_type)];
+                (Some(error), expression(hir::ExpressionKind::Variant { enumeration, index: 1, arguments }, expected))
+            }
+            _ => (None, expression(hir::ExpressionKind::Null, expected)),
+        };
+        let kind = hir::ExpressionKind::Propagate { value: Box::new(value), error, early: Box::new(early) };
+        Ok((kind, result))
+    }
+
+    /// Analyze a call to the function, class or function value named `name`
+    fn analyze_call(&mut self, name: &str, arguments: &[PositionedASTNode], position: &Position, span: Span) -> Result<(hir::ExpressionKind, Type), SemanticError> {
+        let analyzed = self.analyze_expressions(arguments)?;
+        let argument_types = analyzed.iter().map(|argument| argument.ty.clone()).collect();
+        let (symbol, callee) = match self.symbol_table.lookup_symbol_mut(name) {
+            Some(symbol) => {
+                symbol.is_used = true;
+                (symbol_id(symbol), &symbol.symbol_type)
+            }
+            // Functions of the runtime library, and names an open import may bring in, are resolved when linking
+            None if self.open_imports || RUNTIME_FUNCTIONS.contains(&name) => {
+                let callee = hir::Callee::External(name.to_string());
+                return Ok((hir::ExpressionKind::Call { callee, arguments: analyzed, type_arguments: Vec::new() }, Type::Any));
+            }
+            None => {
+                return Err(SemanticError::UndeclaredVariable {
+                    name: name.to_string(),
+                    position: position.clone(),
+                    context: format!("No function, class or variable named '{}' is declared in this scope or any enclosing scope", name),
+                    suggestion: format!("Declare '{}' with 'fungsi' before calling it, or check the spelling", name),
+                    example: "   fungsi tambah(a, b):\n       kembalikan a + b\n   hasil = tambah(1, 2)".to_string(),
+                });
+            }
+        };
+        let (callee_type, type_parameters) = match callee {
+            // Calling a class constructs an instance
+            SymbolType::Class(_) => (None, Vec::new()),
+            SymbolType::Enum(_) => (Some(Type::Any), Vec::new()),
+            SymbolType::Function(signature) => match &signature.inferred_type {
+                Some(function_type) => (Some(self.unifier.instantiate(function_type)), signature.type_parameters.clone()),
+                None => (Some(Type::Any), Vec::new()),
+            },
+            SymbolType::Variable(info) | SymbolType::Parameter(info) => (Some(info.inferred_type.clone().unwrap_or(Type::Any)), Vec::new()),
+        };
+        self.capture(symbol);
+        let Some(callee_type) = callee_type else {
+            let ty = self.check_constructor(name, arguments, argument_types, position)?;
+            return Ok((hir::ExpressionKind::Construct { class: symbol, arguments: analyzed }, ty));
+        };
+        // The type arguments of a generic function are inferred from the arguments of each call
+        let (type_arguments, substitution) = self.fresh_type_arguments(&type_parameters);
+        let ty = self.check_call(name, &callee_type.substitute(&substitution), arguments, argument_types, position)?;
+        self.check_bounds(name, &type_parameters, &type_arguments, position)?;
+        if let SymbolKind::Variant { enumeration, index } = self.symbols[symbol.0].kind {
+            return Ok((hir::ExpressionKind::Variant { enumeration, index, arguments: analyzed }, ty));
+        }
+        // A method called by its bare name in another method of its class is called on the same instance
+        if let SymbolKind::Method { is_static: false, .. } = self.symbols[symbol.0].kind {
+            let object = self.self_expression(symbol, position, span);
+            let kind = hir::ExpressionKind::MethodCall { object: Box::new(object), method: name.to_string(), arguments: analyzed };
+            return Ok((kind, ty));
+        }
+        Ok((hir::ExpressionKind::Call { callee: hir::Callee::Symbol(symbol), arguments: analyzed, type_arguments }, ty))
+    }
 
-                // Analyze class body
-                self.symbol_table.enter_scope(Position::start()); // This would be the actual position in a real implementation
-                for stmt in body {
-                    self.analyze_statement(stmt)?;
-                }
-                self.symbol_table.exit_scope();
+    /// Analyze a call of method `method` on `object`
+    fn analyze_method_call(&mut self, object: &PositionedASTNode, method: &str, arguments: &[PositionedASTNode], position: &Position) -> Result<(hir::ExpressionKind, Type), SemanticError> {
+        if object.node == ASTNode::SuperRef {
+            return self.analyze_super_call(object, method, arguments, position);
+        }
+        // `Kelas.baru(...)` constructs an instance, and `Kelas.metode(...)` calls a static method
+        let class_symbol = match &object.node {
+            ASTNode::Identifier(name) if self.class_info(name).is_some() => {
+                self.symbol_table.lookup_symbol(name).map(|symbol| (name.clone(), symbol_id(symbol)))
+            }
+            _ => None,
+        };
+        let object_hir = self.analyze_expression(object)?;
+        if class_symbol.is_none() {
+            self.expect_non_null(&object_hir.ty, object)?;
+        }
+        self.call_method(object_hir, class_symbol, method, arguments, position)
+    }
 
-                Ok(())
+    /// Analyze a call of method `method` of the analyzed `object`, or of class `class_symbol` when the
+    /// object names a class
+    fn call_method(&mut self, object_hir: hir::Expression, class_symbol: Option<(String, SymbolId)>, method: &str, arguments: &[PositionedASTNode], position: &Position) -> Result<(hir::ExpressionKind, Type), SemanticError> {
+        let analyzed = self.analyze_expressions(arguments)?;
+        let argument_types = analyzed.iter().map(|argument| argument.ty.clone()).collect();
+
+        let class = match class_symbol {
+            Some((class, symbol)) if Self::is_constructor(method) => {
+                let ty = self.check_constructor(&class, arguments, argument_types, position)?;
+                return Ok((hir::ExpressionKind::Construct { class: symbol, arguments: analyzed }, ty));
+            }
+            Some((class, _)) => Some(class),
+            None => self.object_class(&object_hir.ty),
+        };
+        if let Some(class) = &class {
+            self.check_interface_member(class, method, true, position)?;
+            self.check_access(class, method, position)?;
+        }
+        let method_type = class.as_ref()
+            .and_then(|class| self.method_type(class, method))
+            .map(|method_type| method_type.substitute(&self.type_arguments(&object_hir.ty)));
+        let ty = match (class, method_type) {
+            (Some(class), Some(method_type)) => {
+                self.check_call(&format!("{}.{}", class, method), &method_type, arguments, argument_types, position)?
+            }
+            // Methods of runtime types are checked when the program runs
+            _ => Type::Any,
+        };
+        let kind = hir::ExpressionKind::MethodCall {
+            object: Box::new(object_hir),
+            method: method.to_string(),
+            arguments: analyzed,
+        };
+        Ok((kind, ty))
+    }
+
+    /// Analyze `super.metode(...)`, which calls the method the parent class declares or inherits
+    fn analyze_super_call(&mut self, object: &PositionedASTNode, method: &str, arguments: &[PositionedASTNode], position: &Position) -> Result<(hir::ExpressionKind, Type), SemanticError> {
+        let object_hir = self.analyze_expression(object)?;
+        let analyzed = self.analyze_expressions(arguments)?;
+        let argument_types = analyzed.iter().map(|argument| argument.ty.clone()).collect();
+        let parent = self.object_class(&object_hir.ty).unwrap_or_default();
+        let owner = self.find_member(&parent, method).map(|(owner, _)| owner);
+        let owner_symbol = owner.as_ref()
+            .and_then(|owner| self.symbol_table.lookup_symbol(owner))
+            .map(symbol_id);
+        let (Some(owner), Some(class)) = (owner, owner_symbol) else {
+            // A parent without a constructor has nothing to run for `super.baru()`
+            if Self::is_constructor(method) {
+                let no_constructor = Type::Function { parameters: Vec::new(), return_type: Box::new(Type::Void), variadic: false };
+                self.check_call(&format!("{}.{}", parent, method), &no_constructor, arguments, argument_types, position)?;
+                return Ok((hir::ExpressionKind::Null, Type::Void));
             }
-            crate::parser::Statement::WhileLoop { condition, body } => {
-                // Analyze condition
-                self.analyze_node(condition)?;
+            return Err(SemanticError::UndeclaredVariable {
+                name: format!("super.{}", method),
+                position: position.clone(),
+                context: format!("Class '{}' and its ancestors declare no method '{}'", parent, method),
+                suggestion: format!("Declare '{}' in '{}', or check the spelling", method, parent),
+                example: "kelas Kucing(Hewan):\n    fungsi suara():\n        kembalikan super.suara()".to_string(),
+            });
+        };
+        self.check_access(&parent, method, position)?;
+        let ty = match self.method_type(&parent, method) {
+            Some(method_type) => self.check_call(&format!("{}.{}", owner, method), &method_type, arguments, argument_types, position)?,
+            None => Type::Any,
+        };
+        Ok((hir::ExpressionKind::SuperCall { class, method: method.to_string(), arguments: analyzed }, ty))
+    }
 
-                // Analyze body
-                self.symbol_table.enter_scope(Position::start()); // This would be the actual position in a real implementation
-                for stmt in body {
-                    self.analyze_statement(stmt)?;
+    /// Check the arguments of a call against the type of the function called, and infer what it returns
+    fn check_call(&mut self, name: &str, callee: &Type, arguments: &[PositionedASTNode], argument_types: Vec<Type>, position: &Position) -> Result<Type, SemanticError> {
+        match self.unifier.resolve(callee) {
+            Type::Function { parameters, return_type, variadic } => {
+                // A spread argument fills a number of parameters only known at run time
+                if arguments.iter().any(|argument| matches!(argument.node, ASTNode::Spread(_))) {
+                    return Ok(*return_type);
                 }
-                self.symbol_table.exit_scope();
+                if !variadic && arguments.len() != parameters.len() {
+                    return Err(SemanticError::WrongArgumentCount {
+                        name: name.to_string(),
+                        expected: parameters.len(),
+                        found: arguments.len(),
+                        position: position.clone(),
+                        context: format!("'{}' is declared with {} parameter(s)", name, parameters.len()),
+                        suggestion: "Pass one argument for each parameter of the function".to_string(),
+                        example: "   fungsi tambah(a, b):\n       kembalikan a + b\n   tampilkan(tambah(1, 2))".to_string(),
+                    });
+                }
+                for (index, (argument, found)) in arguments.iter().zip(&argument_types).enumerate() {
+                    // Arguments past the last parameter of a variadic function are collected by it
+                    let expected = match parameters.get(index).or(if variadic { parameters.last() } else { None }) {
+                        Some(expected) => expected.clone(),
+                        None => continue,
+                    };
+                    let context = format!(
+                        "Argument {} of '{}' has type '{}'",
+                        index + 1,
+                        name,
+                        self.unifier.resolve(&expected),
+                    );
+                    self.expect_type(&expected, found, argument, context)?;
+                }
+                Ok(*return_type)
+            }
+            // A value of unknown type that is called must be a function
+            callee @ Type::Variable(_) => {
+                let return_type = self.unifier.fresh();
+                self.unifier.unify(&callee, &Type::function(argument_types, return_type.clone()));
+                Ok(return_type)
+            }
+            _ => Ok(Type::Any),
+        }
+    }
 
-                Ok(())
+    /// Check the operands of a binary operation, of types `left_type` and `right_type`, and infer the type of its result
+    fn binary_type(&mut self, left: &PositionedASTNode, left_type: &Type, operator: &BinaryOperator, right: &PositionedASTNode, right_type: &Type) -> Result<Type, SemanticError> {
+        // Computing with a value that may be `nol` is rejected; comparing it, with `nol` among others, is not
+        let computes = !matches!(
+            operator,
+            BinaryOperator::Equal | BinaryOperator::NotEqual | BinaryOperator::SamaDengan | BinaryOperator::And
+                | BinaryOperator::Or | BinaryOperator::In | BinaryOperator::Is | BinaryOperator::Assign
+        );
+        if computes {
+            self.expect_non_null(left_type, left)?;
+            self.expect_non_null(right_type, right)?;
+        }
+        let left_resolved = self.unifier.resolve(left_type);
+        let right_resolved = self.unifier.resolve(right_type);
+        // Values of a type parameter can only be checked with the types each use of the definition gives it
+        let named: Vec<String> = parameters_in(&left_resolved).into_iter().chain(parameters_in(&right_resolved)).collect();
+        let definition = self.generic_definitions.iter().rev()
+            .find(|(_, parameters)| named.iter().any(|name| parameters.contains(name)));
+        if let Some((definition, _)) = definition {
+            self.generic_operations.push(GenericOperation {
+                definition: *definition,
+                left: left_resolved.clone(),
+                operator: operator.clone(),
+                right: right_resolved.clone(),
+                operands: [(left.position.clone(), left.span), (right.position.clone(), right.span)],
+            });
+        }
+
+        match operator {
+            BinaryOperator::Add | BinaryOperator::Tambah => {
+                if left_resolved == Type::String || right_resolved == Type::String {
+                    // Text only joins other text: `"Jumlah: " + str(jumlah)`
+                    for (operand, operand_type) in [(left, left_type), (right, right_type)] {
+                        if !self.unifier.unify(&Type::String, operand_type) {
+                            let context = "'+' joins a string only with another string; other values are not converted automatically".to_string();
+                            return Err(self.type_mismatch(&Type::String, operand_type, &operand.position, Some(&operand.node), context));
+                        }
+                    }
+                    return Ok(Type::String);
+                }
+                if let (Type::List(_), Type::List(_)) = (&left_resolved, &right_resolved) {
+                    let context = "'+' joins lists with elements of the same type".to_string();
+                    self.expect_type(left_type, right_type, right, context)?;
+                    return Ok(left_type.clone());
+                }
+                self.arithmetic(left, &left_resolved, right, &right_resolved)
             }
-            crate::parser::Statement::ForLoop { variable, start, end, body } => {
-                // Check for duplicate declaration
-                if self.symbol_table.lookup_symbol(variable).is_some() {
-                    return Err(SemanticError::DuplicateDeclaration {
-                        name: variable.clone(),
-                        first_position: Position::start(), // This would be the actual position in a real implementation
-                        duplicate_position: Position::start(), // This would be the actual position in a real implementation
-                    });
+            BinaryOperator::Subtract | BinaryOperator::Multiply | BinaryOperator::Divide
+            | BinaryOperator::Modulo | BinaryOperator::Power
+            | BinaryOperator::Kurang | BinaryOperator::Kali | BinaryOperator::Bagi => {
+                // Repeating text or a list: `"-" * 20`
+                if matches!(operator, BinaryOperator::Multiply | BinaryOperator::Kali) {
+                    match (&left_resolved, &right_resolved) {
+                        (repeated @ (Type::String | Type::List(_)), Type::Int)
+                        | (Type::Int, repeated @ (Type::String | Type::List(_))) => return Ok(repeated.clone()),
+                        _ => {}
+                    }
+                }
+                self.arithmetic(left, &left_resolved, right, &right_resolved)
+            }
+            BinaryOperator::Less | BinaryOperator::Greater | BinaryOperator::LessEqual
+            | BinaryOperator::GreaterEqual | BinaryOperator::LebihDari | BinaryOperator::KurangDari => {
+                let comparable = (left_resolved.is_numeric() && right_resolved.is_numeric())
+                    || left_resolved == right_resolved;
+                if is_concrete(&left_resolved) && is_concrete(&right_resolved) && !comparable {
+                    let context = "Only numbers with numbers and strings with strings can be ordered".to_string();
+                    return Err(self.type_mismatch(&left_resolved, &right_resolved, &right.position, Some(&right.node), context));
                 }
+                Ok(Type::Bool)
+            }
+            BinaryOperator::Equal | BinaryOperator::NotEqual | BinaryOperator::SamaDengan
+            | BinaryOperator::In | BinaryOperator::Is => Ok(Type::Bool),
+            BinaryOperator::And | BinaryOperator::Or => {
+                let context = "'dan' and 'atau' combine conditions, which are true or false".to_string();
+                self.expect_type(&Type::Bool, left_type, left, context.clone())?;
+                self.expect_type(&Type::Bool, right_type, right, context)?;
+                Ok(Type::Bool)
+            }
+            BinaryOperator::BitAnd | BinaryOperator::BitOr | BinaryOperator::BitXor
+            | BinaryOperator::LeftShift | BinaryOperator::RightShift => {
+                let context = "Bitwise operators work on the bits of integers".to_string();
+                self.expect_type(&Type::Int, left_type, left, context.clone())?;
+                self.expect_type(&Type::Int, right_type, right, context)?;
+                Ok(Type::Int)
+            }
+            BinaryOperator::Range => Ok(Type::List(Box::new(left_type.clone()))),
+            BinaryOperator::Assign => Ok(right_type.clone()),
+        }
+    }
 
-                // Add loop variable to symbol table
-                let symbol = Symbol {
-                    name: variable.clone(),
-                    symbol_type: SymbolType::Variable(VariableInfo {
-                        var_type: None,
-                        inferred_type: None,
-                        is_constant: false,
-                    }),
-                    is_initialized: true,
-                    is_used: false,
-                };
-                self.symbol_table.add_symbol(variable.clone(), symbol);
+    /// Check the operands of an arithmetic operator and infer the type of its result
+    fn arithmetic(&mut self, left: &PositionedASTNode, left_type: &Type, right: &PositionedASTNode, right_type: &Type) -> Result<Type, SemanticError> {
+        let context = "Arithmetic operators work on numbers only".to_string();
+        match (left_type, right_type) {
+            (Type::Int, Type::Int) => Ok(Type::Int),
+            (left_type, right_type) if left_type.is_numeric() && right_type.is_numeric() => Ok(Type::Float),
+            (left_type, right_type) if is_concrete(left_type) && !left_type.is_numeric() => {
+                let expected = if right_type.is_numeric() { right_type.clone() } else { Type::Int };
+                Err(self.type_mismatch(&expected, left_type, &left.position, Some(&left.node), context))
+            }
+            (left_type, right_type) if is_concrete(right_type) && !right_type.is_numeric() => {
+                let expected = if left_type.is_numeric() { left_type.clone() } else { Type::Int };
+                Err(self.type_mismatch(&expected, right_type, &right.position, Some(&right.node), context))
+            }
+            // One operand is not known yet; it may turn out to be either number
+            _ => Ok(Type::Any),
+        }
+    }
 
-                // Analyze start and end expressions
-                self.analyze_node(start)?;
-                self.analyze_node(end)?;
+    /// Check that a value of type `found` from `value` can be used where `expected` is required
+    fn expect_type(&mut self, expected: &Type, found: &Type, value: &PositionedASTNode, context: String) -> Result<(), SemanticError> {
+        if self.unifier.unify(expected, found) {
+            return Ok(());
+        }
+        Err(self.type_mismatch(expected, found, &value.position, Some(&value.node), context))
+    }
 
-                // Analyze body
-                self.symbol_table.enter_scope(Position::start()); // This would be the actual position in a real implementation
-                for stmt in body {
-                    self.analyze_statement(stmt)?;
-                }
-                self.symbol_table.exit_scope();
+    /// Type mismatch error for a value of type `found` at `position` where `expected` is required
+    fn type_mismatch(&self, expected: &Type, found: &Type, position: &Position, value: Option<&ASTNode>, context: String) -> SemanticError {
+        let expected = self.unifier.resolve(expected);
+        let found = self.unifier.resolve(found);
+        let (suggestion, example) = conversion(&expected, &found, value);
+        SemanticError::TypeMismatch {
+            expected: expected.to_string(),
+            found: found.to_string(),
+            position: position.clone(),
+            context,
+            suggestion,
+            example,
+        }
+    }
 
-                Ok(())
+    /// Check that a name read at `position` is declared, mark it used and return its symbol
+    ///
+    /// A name no declaration matches may come from an `impor` without alias; it has no symbol.
+    fn use_name(&mut self, name: &str, position: &Position) -> Result<Option<SymbolId>, SemanticError> {
+        match self.symbol_table.lookup_symbol_mut(name) {
+            Some(symbol) => {
+                symbol.is_used = true;
+                let symbol = symbol_id(symbol);
+                self.capture(symbol);
+                Ok(Some(symbol))
             }
-            crate::parser::Statement::TryCatch { try_block, catch_block, finally_block } => {
-                // Analyze try block
-                self.symbol_table.enter_scope(Position::start()); // This would be the actual position in a real implementation
-                for stmt in try_block {
-                    self.analyze_statement(stmt)?;
-                }
-                self.symbol_table.exit_scope();
+            None if self.open_imports => Ok(None),
+            None => Err(SemanticError::UndeclaredVariable {
+                name: name.to_string(),
+                position: position.clone(),
+                context: format!("'{}' is not declared in this scope or any enclosing scope", name),
+                suggestion: format!("Declare it before this line with 'buat {} = ...', or check the spelling", name),
+                example: "   buat jumlah = 10\n   hasil = jumlah + 5".to_string(),
+            }),
+        }
+    }
+
+    /// Record that the lambdas being analyzed use `symbol`, if it is a variable declared outside them
+    fn capture(&mut self, symbol: SymbolId) {
+        if !matches!(self.symbols[symbol.0].kind, SymbolKind::Variable { .. } | SymbolKind::Parameter) {
+            return;
+        }
+        // Each lambda the variable is declared outside of captures it, the innermost first
+        for closure in self.closures.iter_mut().rev() {
+            if symbol.0 >= closure.first_symbol {
+                break;
+            }
+            if !closure.captures.contains(&symbol) {
+                closure.captures.push(symbol);
+            }
+            self.symbols[symbol.0].captured.get_or_insert(hir::Capture::ByValue);
+        }
+    }
+
+    /// Record that the lambdas being analyzed use `ini`
+    fn capture_self(&mut self) {
+        for closure in &mut self.closures {
+            closure.captures_self = true;
+        }
+    }
+
+    /// Declare a `konstan` and compute its value
+    fn analyze_constant(&mut self, name: &str, info: VariableInfo, value: Option<hir::Expression>, statement: &Statement) -> Result<hir::Statement, SemanticError> {
+        let not_constant = |position: &Position, context: String| SemanticError::NotConstant {
+            name: name.to_string(),
+            position: position.clone(),
+            context,
+            suggestion: "Compute the value from literals and other constants, or declare a variable with 'biarkan' if it is only known when the program runs".to_string(),
+            example: CONSTANT_EXAMPLE.to_string(),
+        };
+        let Some(value) = value else {
+            return Err(not_constant(&statement.position, format!("'{}' is declared without a value", name)));
+        };
+        let mut constant = value.evaluate()
+            .map_err(|error| not_constant(&error.position, format!("The value of a 'konstan' is computed when compiling, but {}", error.reason)))?;
+        let ty = info.inferred_type.as_ref().map(|ty| self.unifier.resolve(ty));
+        if let (Some(Type::Float), hir::Constant::Integer(integer)) = (&ty, &constant) {
+            constant = hir::Constant::Float(*integer as f64);
+        }
+        let info = VariableInfo { is_constant: true, ..info };
+        let symbol = self.declare(name, SymbolType::Variable(info), true, &statement.position)?;
+        self.symbols[symbol.0].kind = SymbolKind::Constant;
+        self.constants.insert(symbol, constant.clone());
+        Ok(hir::Statement { kind: hir::StatementKind::Const { symbol, value: constant }, position: statement.position.clone(), span: statement.span })
+    }
+
+    /// Check that `name` may be assigned: variables declared with `buat` or `biarkan mut`, a `biarkan` declared
+    /// without a value, and parameters declared with `mut`
+    fn check_assignable(&self, name: &str, position: &Position) -> Result<(), SemanticError> {
+        let Some(symbol) = self.symbol_table.lookup_symbol(name) else {
+            return Ok(());
+        };
+        let (context, fix) = match (&self.symbols[symbol_id(symbol).0].kind, &symbol.symbol_type) {
+            (SymbolKind::Constant, _) => (
+                format!("'{}' is declared with 'konstan', so its value is fixed when compiling", name),
+                format!("Declare a variable with 'biarkan mut {}' if its value changes", name),
+            ),
+            (SymbolKind::Variable { .. }, SymbolType::Variable(info)) if info.is_constant && symbol.is_initialized => (
+                format!("'{}' is declared with 'biarkan', which keeps the value it is given", name),
+                format!("Declare it with 'mut': 'biarkan mut {}'", name),
+            ),
+            (SymbolKind::Parameter, SymbolType::Parameter(info)) if info.is_constant => (
+                format!("'{}' is a parameter, which keeps the argument it is called with", name),
+                format!("Declare the parameter with 'mut': 'fungsi ...(mut {})'", name),
+            ),
+            _ => return Ok(()),
+        };
+        Err(SemanticError::ImmutableAssignment {
+            name: name.to_string(),
+            declared_at: symbol.position.clone(),
+            position: position.clone(),
+            context,
+            suggestion: fix,
+            example: MUTABLE_EXAMPLE.to_string(),
+        })
+    }
+
+    /// Declare a mutable, initialized variable of type `var_type`
+    fn declare_variable(&mut self, name: &str, var_type: Type, position: &Position) -> Result<SymbolId, SemanticError> {
+        let info = VariableInfo {
+            var_type: None,
+            inferred_type: Some(var_type),
+            is_constant: false,
+        };
+        self.declare(name, SymbolType::Variable(info), true, position)
+    }
+
+    /// Add a symbol to the current scope, rejecting a second declaration of the same name in that scope
+    fn declare(&mut self, name: &str, symbol_type: SymbolType, is_initialized: bool, position: &Position) -> Result<SymbolId, SemanticError> {
+        if let Some(existing) = self.symbol_table.scopes[self.symbol_table.current_scope].symbols.get(name) {
+            // Definitions are declared ahead of their block, so meeting one again is not a duplicate
+            if existing.position == *position {
+                return Ok(symbol_id(existing));
+            }
+            return Err(SemanticError::DuplicateDeclaration {
+                name: name.to_string(),
+                first_position: existing.position.clone(),
+                duplicate_position: position.clone(),
+                context: format!("'{}' is already declared in this scope", name),
+                suggestion: "Rename one of the declarations, or assign to the existing one without 'buat'".to_string(),
+                example: "   buat nilai = 1\n   nilai = 2".to_string(),
+            });
+        }
+
+        let (kind, ty) = match &symbol_type {
+            SymbolType::Variable(info) => (SymbolKind::Variable { mutable: !info.is_constant }, info.inferred_type.clone()),
+            SymbolType::Parameter(info) => (SymbolKind::Parameter, info.inferred_type.clone()),
+            SymbolType::Function(signature) => (SymbolKind::Function, signature.inferred_type.clone()),
+            SymbolType::Class(info) if info.is_interface => (SymbolKind::Interface, Some(Type::named(name))),
+            SymbolType::Class(info) => (SymbolKind::Class, Some(instance_type(name, info))),
+            SymbolType::Enum(info) => (SymbolKind::Enum, Some(Type::Named {
+                name: name.to_string(),
+                arguments: info.type_parameters.iter().map(|(name, _)| Type::Parameter(name.clone())).collect(),
+            })),
+        };
+        let id = SymbolId(self.symbols.len());
+        self.symbols.push(SymbolInfo {
+            name: name.to_string(),
+            kind,
+            ty: ty.unwrap_or(Type::Any),
+            position: position.clone(),
+            captured: None,
+        });
+        self.symbol_table.add_symbol(name.to_string(), Symbol {
+            name: name.to_string(),
+            symbol_type,
+            is_initialized,
+            is_used: false,
+            position: position.clone(),
+            id: Some(id),
+        });
+        Ok(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+
+    fn analyze(source: &str) -> Result<(), SemanticError> {
+        let ast = Parser::new(source).parse_program().into_result().unwrap();
+        SemanticAnalyzer::new().analyze(&ast).map(|_| ())
+    }
+
+    #[test]
+    fn test_undeclared_variable_position() {
+        let error = analyze("buat a = 1\nbuat b = a + c\n").unwrap_err();
+
+        match error {
+            SemanticError::UndeclaredVariable { name, position, .. } => {
+                assert_eq!(name, "c");
+                assert_eq!((position.line, position.column), (2, 14));
+            }
+            other => panic!("Expected undeclared variable, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_duplicate_declaration_positions() {
+        let error = analyze("buat x = 1\njika benar:\n    buat x = 2\nbuat x = 3\n").unwrap_err();
+
+        match error {
+            SemanticError::DuplicateDeclaration { first_position, duplicate_position, .. } => {
+                assert_eq!(first_position.line, 1);
+                assert_eq!(duplicate_position.line, 4);
+            }
+            other => panic!("Expected duplicate declaration, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_scopes() {
+        // Functions may be called before their definition and may shadow builtins
+        assert!(analyze("tampilkan(dua())\nfungsi dua():\n    kembalikan 2\nfungsi panjang(x):\n    kembalikan 0\n").is_ok());
+        assert!(analyze("untuk i dari 1 sampai 3:\n    tampilkan i\n").is_ok());
+        assert!(analyze("fungsi f(a):\n    buat b = a\ntampilkan b\n").is_err());
+        assert!(analyze("buat kuadrat = [x * x untuk x dalam [1, 2]]\ntampilkan x\n").is_err());
+    }
+
+    #[test]
+    fn test_signatures_carry_annotations() {
+        let source = "kelas Penghitung:\n    var nilai: int\n    fungsi ambil(): int {\n        kembalikan ini.nilai\n    }\nfungsi tambah(a: int, b) -> float:\n    kembalikan a + b\n";
+        let ast = Parser::new(source).parse_program().into_result().unwrap();
+        let mut analyzer = SemanticAnalyzer::new();
+        analyzer.analyze(&ast).unwrap();
+        let symbols = &analyzer.symbol_table().scopes[1].symbols;
+
+        match &symbols["tambah"].symbol_type {
+            SymbolType::Function(signature) => {
+                assert_eq!(signature.parameters[0].param_type, Some(TypeExpr::named("int")));
+                assert_eq!(signature.parameters[1].param_type, None);
+                assert_eq!(signature.parameters[1].position.column, 23);
+                assert_eq!(signature.return_type, Some(TypeExpr::named("float")));
+            }
+            other => panic!("Expected a function, got {:?}", other),
+        }
+        match &symbols["Penghitung"].symbol_type {
+            SymbolType::Class(info) => {
+                assert_eq!(info.fields["nilai"].var_type, Some(TypeExpr::named("int")));
+                assert_eq!(info.methods["ambil"].return_type, Some(TypeExpr::named("int")));
+            }
+            other => panic!("Expected a class, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_inferred_types() {
+        let source = "buat angka = [1, 2]\nbuat n = panjang(angka)\nfungsi sapa(nama):\n    kembalikan \"Halo, \" + nama\nfungsi tambah(a: int, b) -> float:\n    kembalikan a + b\nbuat pesan = sapa(\"Andi\")\n";
+        let ast = Parser::new(source).parse_program().into_result().unwrap();
+        let mut analyzer = SemanticAnalyzer::new();
+        analyzer.analyze(&ast).unwrap();
+        let symbols = &analyzer.symbol_table().scopes[1].symbols;
+        let type_of = |name: &str| match &symbols[name].symbol_type {
+            SymbolType::Variable(info) => info.inferred_type.clone().unwrap().to_string(),
+            SymbolType::Function(signature) => signature.inferred_type.clone().unwrap().to_string(),
+            other => panic!("Expected a variable or function, got {:?}", other),
+        };
+
+        assert_eq!(type_of("angka"), "list<int>");
+        assert_eq!(type_of("n"), "int");
+        assert_eq!(type_of("sapa"), "fungsi(string) -> string");
+        assert_eq!(type_of("tambah"), "fungsi(int, any) -> float");
+        assert_eq!(type_of("pesan"), "string");
+    }
+
+    #[test]
+    fn test_string_concatenation_suggests_str() {
+        let error = analyze("buat counter = 0\ntampilkan(\"Counter: \" + counter)\n").unwrap_err();
+
+        match error {
+            SemanticError::TypeMismatch { expected, found, position, suggestion, .. } => {
+                assert_eq!((expected.as_str(), found.as_str()), ("string", "int"));
+                assert_eq!((position.line, position.column), (2, 25));
+                assert!(suggestion.contains("str(counter)"));
+            }
+            other => panic!("Expected a type mismatch, got {:?}", other),
+        }
+        assert!(analyze("buat counter = 0\ntampilkan(\"Counter: \" + str(counter))\n").is_ok());
+    }
+
+    #[test]
+    fn test_calls_are_checked_against_signatures() {
+        match analyze("fungsi tambah(a, b):\n    kembalikan a + b\ntambah(1)\n").unwrap_err() {
+            SemanticError::WrongArgumentCount { name, expected, found, .. } => {
+                assert_eq!((name.as_str(), expected, found), ("tambah", 2, 1));
+            }
+            other => panic!("Expected a wrong argument count, got {:?}", other),
+        }
+        match analyze("fungsi kuadrat(x: int) -> int:\n    kembalikan x * x\nkuadrat(\"dua\")\n").unwrap_err() {
+            SemanticError::TypeMismatch { expected, found, position, .. } => {
+                assert_eq!((expected.as_str(), found.as_str()), ("int", "string"));
+                assert_eq!((position.line, position.column), (3, 9));
+            }
+            other => panic!("Expected a type mismatch, got {:?}", other),
+        }
+        // Each call may pick its own types for an untyped parameter
+        assert!(analyze("fungsi sama(x):\n    kembalikan x\nbuat a = sama(1) + 1\nbuat b = sama(\"b\") + \"c\"\n").is_ok());
+        assert!(analyze("fungsi f(...angka: int):\n    kembalikan panjang(angka)\nf(1, 2, 3)\nf(1, \"dua\")\n").is_err());
 
-                // Analyze catch block
-                self.symbol_table.enter_scope(Position::start()); // This would be the actual position in a real implementation
-                for stmt in catch_block {
-                    self.analyze_statement(stmt)?;
+        // Only functions of the runtime library may be called without a declaration
+        match analyze("fungsi tambah(a, b):\n    kembalikan a + b\ntmbah(1, 2)\n").unwrap_err() {
+            SemanticError::UndeclaredVariable { name, position, .. } => {
+                assert_eq!((name.as_str(), position.line, position.column), ("tmbah", 3, 1));
+            }
+            other => panic!("Expected an undeclared name, got {:?}", other),
+        }
+        assert!(analyze("buat akar = akar_kuadrat(16)\n").is_ok());
+    }
+
+    #[test]
+    fn test_returns_and_assignments() {
+        assert!(analyze("fungsi nama() -> teks:\n    kembalikan 5\n").is_err());
+        assert!(analyze("fungsi nama() -> teks:\n    kembalikan\n").is_err());
+        assert!(analyze("buat x = 5\nx = \"lima\"\n").is_err());
+        assert!(analyze("kelas Titik:\n    var x: int = 0\nbuat t = Titik()\nt.x = \"nol\"\n").is_err());
+        assert!(analyze("buat harga: float = 5\nharga = harga * 1.5\n").is_ok());
+        // Without a declared type, returning different types makes the result `any`
+        assert!(analyze("fungsi ubah(x):\n    jika x:\n        kembalikan 1\n    kembalikan \"satu\"\nbuat y = ubah(benar) + 1\n").is_ok());
+    }
+
+    #[test]
+    fn test_conditions_are_bool_and_bitwise_operands_int() {
+        let mismatch = |source: &str| match analyze(source).unwrap_err() {
+            SemanticError::TypeMismatch { expected, found, position, .. } => (expected, found, position.line, position.column),
+            other => panic!("Expected a type mismatch, got {:?}", other),
+        };
+        assert_eq!(mismatch("jika \"x\":\n    tampilkan 1\n"), ("bool".to_string(), "string".to_string(), 1, 6));
+        assert_eq!(mismatch("buat b = benar dan 5\n"), ("bool".to_string(), "int".to_string(), 1, 20));
+        assert_eq!(mismatch("buat b = tidak 5\n"), ("bool".to_string(), "int".to_string(), 1, 16));
+        assert_eq!(mismatch("buat n = 0\nselama n:\n    n = n - 1\n").0, "bool");
+        assert_eq!(mismatch("buat n = 0\nulangi:\n    n = n + 1\nselama n + 1\n").0, "bool");
+        assert_eq!(mismatch("buat d = [x untuk x dalam [1, 2] jika x]\n").0, "bool");
+
+        assert!(analyze("fungsi f(a, b):\n    kembalikan a dan tidak b\njika f(benar, salah) atau 1 > 0:\n    tampilkan 1\n").is_ok());
+
+        // The lexer has no bitwise operators yet, so they are put in the tree by hand
+        let bitwise = |source: &str| {
+            let mut ast = Parser::new(source).parse_program().into_result().unwrap();
+            if let ASTNode::Program(statements) = &mut ast {
+                if let ASTNode::Declaration { value: Some(value), .. } = &mut statements[0].node {
+                    if let ASTNode::BinaryOp { operator, .. } = &mut value.node {
+                        *operator = BinaryOperator::BitAnd;
+                    }
                 }
-                self.symbol_table.exit_scope();
+            }
+            SemanticAnalyzer::new().analyze(&ast).map(|_| ())
+        };
+        match bitwise("buat n = 1.5 - 1\n").unwrap_err() {
+            SemanticError::TypeMismatch { expected, found, .. } => assert_eq!((expected.as_str(), found.as_str()), ("int", "float")),
+            other => panic!("Expected a type mismatch, got {:?}", other),
+        }
+        assert!(bitwise("buat n = 6 - 3\n").is_ok());
+    }
+
+    #[test]
+    fn test_break_and_continue_need_a_loop() {
+        match analyze("buat x = 1\njika x > 0:\n    pecah\n").unwrap_err() {
+            SemanticError::OutsideLoop { keyword, position, .. } => {
+                assert_eq!(keyword, "pecah");
+                assert_eq!((position.line, position.column), (3, 5));
+            }
+            other => panic!("Expected a break outside a loop, got {:?}", other),
+        }
+        // A function inside a loop starts outside it
+        let error = analyze("selama benar:\n    fungsi f():\n        lanjut\n    pecah\n").unwrap_err();
+        assert_eq!(error.code(), "K0215");
+        assert!(error.to_string().contains("'lanjut' is not inside a loop"));
+
+        assert!(analyze("untuk i dari 1 sampai 3:\n    jika i == 2:\n        lanjut\n    ketika i:\n        kasus 3:\n            pecah\n        sebaliknya:\n            tampilkan i\nulangi:\n    pecah\nselama benar\n").is_ok());
+    }
 
-                // Analyze finally block if present
-                if let Some(finally_block) = finally_block {
-                    self.symbol_table.enter_scope(Position::start()); // This would be the actual position in a real implementation
-                    for stmt in finally_block {
-                        self.analyze_statement(stmt)?;
+    #[test]
+    fn test_hir_binds_names_to_symbols() {
+        let source = "buat x = 1\nx = x + 2\nfungsi f(x):\n    kembalikan x\nbuat daftar_kosong = []\ntampilkan(f(\"a\"))\n";
+        let ast = Parser::new(source).parse_program().into_result().unwrap();
+        let program = SemanticAnalyzer::new().analyze(&ast).unwrap();
+
+        let outer = match &program.body[0].kind {
+            hir::StatementKind::Let { symbol, value: Some(value) } => {
+                assert_eq!(value.ty, Type::Int);
+                *symbol
+            }
+            other => panic!("Expected a declaration, got {:?}", other),
+        };
+        assert_eq!(program.symbol(outer).name, "x");
+        assert_eq!(program.symbol(outer).kind, SymbolKind::Variable { mutable: true });
+        match &program.body[1].kind {
+            hir::StatementKind::Assign { symbol, value } => {
+                assert_eq!(*symbol, outer);
+                match &value.kind {
+                    hir::ExpressionKind::Binary { left, .. } => {
+                        assert!(matches!(left.kind, hir::ExpressionKind::Variable(id) if id == outer));
                     }
-                    self.symbol_table.exit_scope();
+                    other => panic!("Expected a binary operation, got {:?}", other),
                 }
+            }
+            other => panic!("Expected an assignment, got {:?}", other),
+        }
+        // The parameter shadows the outer `x` with a symbol of its own
+        match &program.body[2].kind {
+            hir::StatementKind::Function(function) => {
+                assert_eq!(program.symbol(function.symbol).ty.to_string(), "fungsi(any) -> any");
+                assert_ne!(function.parameters[0], outer);
+                assert_eq!(program.symbol(function.parameters[0]).kind, SymbolKind::Parameter);
+            }
+            other => panic!("Expected a function, got {:?}", other),
+        }
 
-                Ok(())
+        // No type variable is left for the later stages
+        let mut program = program;
+        let mut unresolved = 0;
+        program.visit_types_mut(&mut |ty| {
+            if ty.to_string().contains("unknown") {
+                unresolved += 1;
             }
-            crate::parser::Statement::Expression(expr) => {
-                self.analyze_node(expr)?;
-                Ok(())
+        });
+        assert_eq!(unresolved, 0);
+        match &program.body[4].kind {
+            hir::StatementKind::Expression(hir::Expression { kind: hir::ExpressionKind::Call { callee, arguments, .. }, .. }) => {
+                assert!(matches!(callee, hir::Callee::Symbol(id) if program.symbol(*id).kind == SymbolKind::Builtin));
+                assert!(matches!(&arguments[0].kind, hir::ExpressionKind::Call { callee: hir::Callee::Symbol(_), .. }));
+                assert_eq!(arguments[0].ty, Type::String);
             }
-            crate::parser::Statement::ReturnStmt(expr) => {
-                self.analyze_node(expr)?;
-                Ok(())
+            other => panic!("Expected a call, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_access_modifiers_are_checked() {
+        let class = "kelas Akun:\n    pribadi var saldo: int = 0\n    terlindungi var pemilik = \"\"\n    fungsi lihat(): int:\n        kembalikan ini.saldo\n    pribadi fungsi periksa():\n        kembalikan saldo > 0\n";
+        assert!(analyze(&format!("{}buat akun = Akun()\ntampilkan(akun.lihat())\n", class)).is_ok());
+        assert!(analyze(&format!("{}kelas Tabungan(Akun):\n    fungsi nama():\n        kembalikan ini.pemilik\n", class)).is_ok());
+
+        let error = analyze(&format!("{}buat akun = Akun()\ntampilkan(akun.saldo)\n", class)).unwrap_err();
+        assert_eq!(error.code(), "K0207");
+        match error {
+            SemanticError::InaccessibleMember { member, class, access, position, .. } => {
+                assert_eq!((member.as_str(), class.as_str(), access.as_str()), ("saldo", "Akun", "private"));
+                assert_eq!(position.line, 9);
+            }
+            other => panic!("Expected an inaccessible member, got {:?}", other),
+        }
+        assert!(analyze(&format!("{}buat akun = Akun()\nakun.pemilik = \"Budi\"\n", class)).is_err());
+        assert!(analyze(&format!("{}buat akun = Akun()\nakun.periksa()\n", class)).is_err());
+        // A private member is not reachable from a class extending its own
+        assert!(analyze(&format!("{}kelas Tabungan(Akun):\n    fungsi bocor():\n        kembalikan ini.saldo\n", class)).is_err());
+        let error = analyze(&format!("{}kelas Tabungan(Akun):\n    fungsi bocor():\n        kembalikan saldo\n", class)).unwrap_err();
+        assert!(matches!(error, SemanticError::InaccessibleMember { .. }), "{:?}", error);
+    }
+
+    #[test]
+    fn test_inheritance_and_super() {
+        let classes = "kelas Hewan:\n    var nama: string = \"\"\n    fungsi baru(nama: string):\n        ini.nama = nama\n    fungsi suara(): string:\n        kembalikan \"...\"\nkelas Kucing(Hewan):\n    fungsi suara(): string:\n        kembalikan nama + \": meong, bukan \" + super.suara()\n";
+        assert!(analyze(&format!("{}buat kucing = Kucing(\"Tom\")\nbuat teks: string = kucing.suara() + kucing.nama\n", classes)).is_ok());
+
+        // Constructors, inherited ones included, are checked like any call
+        let error = analyze(&format!("{}buat kucing = Kucing(1)\n", classes)).unwrap_err();
+        assert!(matches!(error, SemanticError::TypeMismatch { .. }), "{:?}", error);
+        assert!(analyze(&format!("{}buat kucing = Kucing()\n", classes)).is_err());
+
+        // An override takes and returns what the method it overrides does
+        let error = analyze("kelas Hewan:\n    fungsi suara(): string:\n        kembalikan \"...\"\nkelas Anjing(Hewan):\n    fungsi suara(): int:\n        kembalikan 1\n").unwrap_err();
+        match error {
+            SemanticError::TypeMismatch { context, position, .. } => {
+                assert!(context.contains("'Anjing.suara' overrides 'Hewan.suara'"), "{}", context);
+                assert_eq!(position.line, 5);
+            }
+            other => panic!("Expected a type mismatch, got {:?}", other),
+        }
+
+        let error = analyze("kelas Hewan:\n    fungsi suara():\n        kembalikan super.suara()\n").unwrap_err();
+        assert_eq!(error.code(), "K0218");
+
+        // A class may not be its own ancestor
+        let error = analyze("kelas A(B):\n    var x = 1\nkelas B(A):\n    var y = 2\nbuat a = A()\n").unwrap_err();
+        match error {
+            SemanticError::InheritanceCycle { class, chain, position, .. } => {
+                assert_eq!(class, "A");
+                assert_eq!(chain, "A -> B -> A");
+                assert_eq!(position.line, 1);
+            }
+            other => panic!("Expected an inheritance cycle, got {:?}", other),
+        }
+        assert!(analyze("kelas C(C):\n    var x = 1\n").is_err());
+        assert!(analyze("kelas D(A):\n    var z = 3\nkelas A(B):\n    var x = 1\nkelas B(A):\n    var y = 2\n").is_err());
+    }
+
+    #[test]
+    fn test_interfaces_are_implemented() {
+        let shapes = "antarmuka Bentuk:\n    fungsi luas(): float\n    fungsi nama(): string\n";
+        let square = "kelas Persegi implementasi Bentuk:\n    var sisi = 2.0\n    fungsi luas(): float:\n        kembalikan sisi * sisi\n    fungsi nama(): string:\n        kembalikan \"persegi\"\n";
+        let source = format!("{}{}buat bentuk: Bentuk = Persegi()\nbuat luas: float = bentuk.luas()\n", shapes, square);
+        assert!(analyze(&source).is_ok(), "{:?}", analyze(&source));
+        // A subclass implements the interfaces of its parent
+        assert!(analyze(&format!("{}{}kelas Ubin(Persegi):\n    var warna = 0\nbuat bentuk: Bentuk = Ubin()\n", shapes, square)).is_ok());
+
+        let error = analyze(&format!("{}kelas Titik implementasi Bentuk:\n    fungsi luas(): float:\n        kembalikan 0.0\n", shapes)).unwrap_err();
+        assert_eq!(error.code(), "K0208");
+        match error {
+            SemanticError::MissingInterfaceMethod { class, interface, method, position, .. } => {
+                assert_eq!((class.as_str(), interface.as_str(), method.as_str()), ("Titik", "Bentuk", "nama"));
+                assert_eq!(position.line, 4);
+            }
+            other => panic!("Expected a missing interface method, got {:?}", other),
+        }
+
+        let error = analyze(&format!("{}kelas Titik implementasi Bentuk:\n    fungsi luas(): string:\n        kembalikan \"nol\"\n    fungsi nama(): string:\n        kembalikan \"titik\"\n", shapes)).unwrap_err();
+        match error {
+            SemanticError::TypeMismatch { context, position, .. } => {
+                assert!(context.contains("'Titik.luas' implements 'Bentuk.luas'"), "{}", context);
+                assert_eq!(position.line, 5);
+            }
+            other => panic!("Expected a type mismatch, got {:?}", other),
+        }
+        // An `int` would be converted to a `float` when returned directly, but not through the interface table
+        let error = analyze(&format!("{}kelas Titik implementasi Bentuk:\n    fungsi luas(): int:\n        kembalikan 0\n    fungsi nama(): string:\n        kembalikan \"titik\"\n", shapes)).unwrap_err();
+        assert!(matches!(error, SemanticError::TypeMismatch { ref expected, ref found, .. } if expected.contains("float") && found.contains("int")), "{:?}", error);
+
+        // A method hidden in its class cannot be called through the interface
+        let error = analyze(&format!("{}kelas Titik implementasi Bentuk:\n    pribadi fungsi luas(): float:\n        kembalikan 0.0\n    fungsi nama(): string:\n        kembalikan \"titik\"\n", shapes)).unwrap_err();
+        match error {
+            SemanticError::HiddenInterfaceMethod { class, method, access, position, .. } => {
+                assert_eq!((class.as_str(), method.as_str(), access.as_str()), ("Titik", "luas", "private"));
+                assert_eq!(position.line, 5);
+            }
+            other => panic!("Expected a hidden interface method, got {:?}", other),
+        }
+
+        // Interfaces are implemented, not extended, and classes are extended, not implemented
+        assert_eq!(analyze(&format!("{}{}kelas Ubin(Bentuk):\n    var warna = 0\n", shapes, square)).unwrap_err().code(), "K0219");
+        assert_eq!(analyze(&format!("{}{}kelas Ubin implementasi Persegi:\n    var warna = 0\n", shapes, square)).unwrap_err().code(), "K0220");
+
+        // Interface values only have the methods of the interface, and interfaces have no instances
+        let error = analyze(&format!("{}{}buat bentuk: Bentuk = Persegi()\ntampilkan(bentuk.sisi)\n", shapes, square)).unwrap_err();
+        assert!(matches!(error, SemanticError::UnknownInterfaceMember { ref interface, ref member, .. } if interface == "Bentuk" && member == "sisi"), "{:?}", error);
+        assert_eq!(analyze(&format!("{}buat bentuk = Bentuk()\n", shapes)).unwrap_err().code(), "K0222");
+        assert!(analyze(&format!("{}buat bentuk: Bentuk = 1\n", shapes)).is_err());
+    }
+
+    #[test]
+    fn test_generics_infer_their_type_arguments() {
+        let identity = "fungsi pertama<T>(daftar: T[]): T:\n    kembalikan daftar[0]\n";
+        let source = format!("{}buat angka: int = pertama([1, 2])\nbuat kata: string = pertama([\"a\"])\n", identity);
+        assert!(analyze(&source).is_ok(), "{:?}", analyze(&source));
+        // Each call infers its own type argument, and the body cannot assume what `T` is
+        assert!(analyze(&format!("{}buat angka: int = pertama([\"a\"])\n", identity)).is_err());
+        assert!(analyze("fungsi salah<T>(nilai: T): T:\n    kembalikan 1\n").is_err());
+
+        let boxes = "kelas Kotak<T>:\n    var isi: T\n    fungsi baru(isi: T):\n        ini.isi = isi\n    fungsi ambil(): T:\n        kembalikan isi\n";
+        let source = format!("{}buat kotak = Kotak(5)\nbuat isi: int = kotak.ambil()\nbuat lagi: int = kotak.isi\n", boxes);
+        assert!(analyze(&source).is_ok(), "{:?}", analyze(&source));
+        assert!(analyze(&format!("{}buat kotak: Kotak<teks> = Kotak(5)\n", boxes)).is_err());
+        assert!(analyze(&format!("{}buat kotak = Kotak(\"a\")\nbuat isi: int = kotak.ambil()\n", boxes)).is_err());
+
+        // A bound gives the values of a type parameter the methods of the bound, and limits its arguments
+        let valued = "antarmuka Bernilai:\n    fungsi nilai(): int\nkelas Koin implementasi Bernilai:\n    fungsi nilai(): int:\n        kembalikan 5\n";
+        let largest = "fungsi terbesar<T: Bernilai>(a: T, b: T): T:\n    jika a.nilai() > b.nilai():\n        kembalikan a\n    kembalikan b\n";
+        let source = format!("{}{}buat koin: Koin = terbesar(Koin(), Koin())\n", valued, largest);
+        assert!(analyze(&source).is_ok(), "{:?}", analyze(&source));
+        let error = analyze(&format!("{}{}terbesar(1, 2)\n", valued, largest)).unwrap_err();
+        match error {
+            SemanticError::TypeMismatch { expected, found, context, position, .. } => {
+                assert_eq!((expected.as_str(), found.as_str()), ("Bernilai", "int"));
+                assert!(context.contains("'terbesar' declares 'T: Bernilai'"), "{}", context);
+                assert_eq!(position.line, 10);
             }
+            other => panic!("Expected a type mismatch, got {:?}", other),
         }
+        assert!(analyze(&format!("{}fungsi nama<T: Bernilai>(a: T): string:\n    kembalikan a.nama()\n", valued)).is_err());
+
+        // Operators of a generic body are checked with the types of each use, including uses through other generics
+        let adding = "fungsi tambah<T>(a: T, b: T): T:\n    kembalikan a + b\nfungsi maks<T>(a: T, b: T): T:\n    jika a > b:\n        kembalikan a\n    kembalikan b\n";
+        let source = format!("{}buat n: int = tambah(1, 2)\nbuat s: string = tambah(\"a\", \"b\")\nbuat m: float = maks(1.5, 2.5)\n", adding);
+        assert!(analyze(&source).is_ok(), "{:?}", analyze(&source));
+        let error = analyze(&format!("{}buat b = tambah(benar, salah)\n", adding)).unwrap_err();
+        match &error {
+            SemanticError::InvalidInstantiation { definition, type_arguments, line, position, .. } => {
+                assert_eq!((definition.as_str(), type_arguments.as_str(), *line), ("tambah", "T = bool", 2));
+                assert_eq!(position.line, 7);
+            }
+            other => panic!("Expected an invalid instantiation, got {:?}", other),
+        }
+        assert_eq!(error.code(), "K0227");
+        let doubling = format!("{}fungsi dobel<U>(a: U): U:\n    kembalikan tambah(a, a)\n", adding);
+        assert!(analyze(&format!("{}buat d: int = dobel(2)\n", doubling)).is_ok());
+        assert!(matches!(analyze(&format!("{}buat d = dobel(benar)\n", doubling)), Err(SemanticError::InvalidInstantiation { .. })));
+        let pair = "kelas Pasangan<T>:\n    var a: T\n    var b: T\n    fungsi baru(a: T, b: T):\n        ini.a = a\n        ini.b = b\n    fungsi jumlah(): T:\n        kembalikan a + b\n";
+        assert!(analyze(&format!("{}buat p = Pasangan(1, 2)\n", pair)).is_ok());
+        assert!(matches!(analyze(&format!("{}buat p = Pasangan(benar, salah)\n", pair)), Err(SemanticError::InvalidInstantiation { .. })));
+    }
+
+    #[test]
+    fn test_bare_members_are_members_of_ini() {
+        let source = "kelas Penghitung:\n    var nilai = 0\n    fungsi tambah():\n        nilai = nilai + 1\n    fungsi dua_kali():\n        tambah()\n        tambah()\n";
+        let ast = Parser::new(source).parse_program().into_result().unwrap();
+        let program = SemanticAnalyzer::new().analyze(&ast).unwrap();
+        let hir::StatementKind::Class(class) = &program.body[0].kind else {
+            panic!("Expected a class, got {:?}", program.body[0].kind);
+        };
+
+        let hir::StatementKind::Let { symbol: field, .. } = &class.body[0].kind else {
+            panic!("Expected a field, got {:?}", class.body[0].kind);
+        };
+        assert_eq!(program.symbol(*field).kind, SymbolKind::Field { class: class.symbol });
+        let hir::StatementKind::Function(tambah) = &class.body[1].kind else {
+            panic!("Expected a method, got {:?}", class.body[1].kind);
+        };
+        assert_eq!(program.symbol(tambah.symbol).kind, SymbolKind::Method { class: class.symbol, is_static: false });
+        match &tambah.body[0].kind {
+            hir::StatementKind::MemberAssign { object, field, value } => {
+                assert!(matches!(object.kind, hir::ExpressionKind::SelfRef));
+                assert_eq!(object.ty, Type::named("Penghitung"));
+                assert_eq!(field, "nilai");
+                assert!(matches!(&value.kind, hir::ExpressionKind::Binary { left, .. }
+                    if matches!(&left.kind, hir::ExpressionKind::Member { field, .. } if field == "nilai")));
+            }
+            other => panic!("Expected a field assignment, got {:?}", other),
+        }
+        let hir::StatementKind::Function(dua_kali) = &class.body[2].kind else {
+            panic!("Expected a method, got {:?}", class.body[2].kind);
+        };
+        assert!(matches!(&dua_kali.body[0].kind, hir::StatementKind::Expression(hir::Expression {
+            kind: hir::ExpressionKind::MethodCall { object, method, .. }, ..
+        }) if method == "tambah" && matches!(object.kind, hir::ExpressionKind::SelfRef)));
+    }
+
+    #[test]
+    fn test_members_must_be_declared() {
+        let class = "kelas Titik:\n    var x: int = 0\n    fungsi geser():\n        x = x + 1\n";
+        match analyze(&format!("{}buat t = Titik()\ntampilkan(t.z)\n", class)).unwrap_err() {
+            SemanticError::UndeclaredMember { member, class, position, .. } => {
+                assert_eq!((member.as_str(), class.as_str(), position.line), ("z", "Titik", 6));
+            }
+            other => panic!("Expected an undeclared member, got {:?}", other),
+        }
+        let error = analyze(&format!("{}buat t = Titik()\nt.y = 3\n", class)).unwrap_err();
+        assert_eq!(error.code(), "K0216");
+        assert!(analyze(&format!("{}buat t = Titik()\nt.foo()\n", class)).is_err());
+        assert!(analyze(&format!("{}kelas Titik3(Titik):\n    var z = 0\nbuat t = Titik3()\nt.geser()\ntampilkan(t.x + t.z)\n", class)).is_ok());
+
+        // Methods create the fields they assign through `ini`, for the class and the classes extending it
+        let dynamic = "kelas Hewan:\n    fungsi baru(nama):\n        ini.nama = nama\nkelas Kucing(Hewan):\n    fungsi sapa():\n        kembalikan ini.nama\n";
+        assert!(analyze(&format!("{}tampilkan(Kucing(\"Tom\").nama)\n", dynamic)).is_ok());
+        // Members of a class extending one the analyzer does not know are checked when the program runs
+        assert!(analyze("impor \"hewan\"\nkelas Kucing(Hewan):\n    var umur = 1\ntampilkan(Kucing().nama)\n").is_ok());
+    }
+
+    #[test]
+    fn test_enum_variants_are_typed() {
+        let options = "tipe Pilihan<T>:\n    Ada(T)\n    Tidak\n";
+        let source = format!("{}buat a: Pilihan<int> = Ada(1)\nbuat b: Pilihan<teks> = Tidak\n", options);
+        assert!(analyze(&source).is_ok(), "{:?}", analyze(&source));
+        assert!(analyze(&format!("{}buat a: Pilihan<int> = Ada(\"a\")\n", options)).is_err());
+
+        // A variant with fields is built by calling it, and its fields are checked by the patterns
+        let error = analyze(&format!("{}buat a = Ada\n", options)).unwrap_err();
+        assert_eq!(error.code(), "K0206");
+        let source = format!("{}buat a = Ada(1)\nketika a:\n    kasus Ada(n):\n        buat m: teks = n\n    kasus Tidak:\n        tampilkan 0\n", options);
+        assert!(matches!(analyze(&source), Err(SemanticError::TypeMismatch { .. })));
+        let source = format!("{}buat a = Ada(1)\nketika a:\n    kasus Ada(n, m):\n        tampilkan n\n    kasus _:\n        tampilkan 0\n", options);
+        assert_eq!(analyze(&source).unwrap_err().code(), "K0206");
+        let error = analyze("ketika 1:\n    kasus Ada(n):\n        tampilkan n\n").unwrap_err();
+        assert!(error.to_string().contains("'Ada' is not an enum variant"), "{}", error);
+        assert_eq!(error.code(), "K0231");
+    }
+
+    #[test]
+    fn test_when_is_exhaustive_and_reachable() {
+        let shapes = "tipe Bentuk:\n    Lingkaran(float)\n    Persegi(float, float)\n    Kosong\nbuat b = Kosong\n";
+        let source = format!("{}ketika b:\n    kasus Lingkaran(r) jika r > 1.0:\n        tampilkan r\n    kasus Lingkaran(_):\n        tampilkan 0\n    kasus Persegi(l, _):\n        tampilkan l\n    kasus Kosong:\n        tampilkan 1\n", shapes);
+        assert!(analyze(&source).is_ok(), "{:?}", analyze(&source));
+
+        let error = analyze(&format!("{}ketika b:\n    kasus Lingkaran(r):\n        tampilkan r\n    kasus Kosong:\n        tampilkan 1\n", shapes)).unwrap_err();
+        assert_eq!(error.code(), "K0209");
+        match error {
+            SemanticError::NonExhaustiveMatch { missing, position, .. } => {
+                assert_eq!(missing, "'Persegi(_, _)'");
+                assert_eq!(position.line, 6);
+            }
+            other => panic!("Expected a non-exhaustive match, got {:?}", other),
+        }
+        // A guarded case may not run, so it covers nothing
+        let error = analyze(&format!("{}ketika b:\n    kasus Lingkaran(r) jika r > 1.0:\n        tampilkan r\n    kasus Persegi(_, _):\n        tampilkan 0\n    kasus Kosong:\n        tampilkan 1\n", shapes)).unwrap_err();
+        assert!(matches!(error, SemanticError::NonExhaustiveMatch { ref missing, .. } if missing == "'Lingkaran(_)'"), "{}", error);
+
+        let error = analyze("ketika 5:\n    kasus 0..10:\n        tampilkan 0\n    kasus 3:\n        tampilkan 3\n    kasus _:\n        tampilkan 1\n").unwrap_err();
+        assert_eq!(error.code(), "K0210");
+        match error {
+            SemanticError::UnreachablePattern { case, position, .. } => {
+                assert_eq!(case, "'kasus 3'");
+                assert_eq!(position.line, 4);
+            }
+            other => panic!("Expected an unreachable pattern, got {:?}", other),
+        }
+        let error = analyze("ketika (1, benar):\n    kasus (_, benar):\n        tampilkan 0\n    kasus (n, salah):\n        tampilkan n\n    sebaliknya:\n        tampilkan 1\n").unwrap_err();
+        assert!(matches!(error, SemanticError::UnreachablePattern { ref case, .. } if case == "'sebaliknya'"), "{}", error);
+        let error = analyze("ketika 5:\n    kasus 0...9:\n        tampilkan 0\n").unwrap_err();
+        assert!(error.to_string().contains("'ketika' does not handle '-1'"), "{}", error);
+
+        // Values of other types have too many values to list, so they need a wildcard or `sebaliknya`
+        assert!(analyze("ketika \"a\":\n    kasus \"a\":\n        tampilkan 0\n    sebaliknya:\n        tampilkan 1\n").is_ok());
+        assert_eq!(analyze("ketika \"a\":\n    kasus \"a\":\n        tampilkan 0\n").unwrap_err().code(), "K0209");
+    }
+
+    #[test]
+    fn test_lambdas_capture_their_environment() {
+        let source = "buat faktor = 2\nbuat jumlah = 0\nbuat kali = (x: int) => x * faktor\nbuat tambah = fungsi(n):\n    jumlah = jumlah + n\n    buat dalam = () => n + faktor\ntambah(1)\nbuat y: int = kali(3)\n";
+        let ast = Parser::new(source).parse_program().into_result().unwrap();
+        let program = SemanticAnalyzer::new().analyze(&ast).unwrap();
+        let lambda = |statements: &[hir::Statement], index: usize| match &statements[index].kind {
+            hir::StatementKind::Let { value: Some(hir::Expression { kind: hir::ExpressionKind::Lambda(lambda), ty, .. }), .. } => {
+                (lambda.clone(), ty.to_string())
+            }
+            other => panic!("Expected a lambda, got {:?}", other),
+        };
+        let captures = |lambda: &hir::Lambda| -> Vec<String> {
+            lambda.captures.iter().map(|&symbol| program.symbol(symbol).name.clone()).collect()
+        };
+
+        let (kali, ty) = lambda(&program.body, 2);
+        assert_eq!(ty, "fungsi(int) -> int");
+        assert_eq!(captures(&kali), ["faktor"]);
+        // A lambda captures what the lambdas nested in it use from outside it
+        let (tambah, ty) = lambda(&program.body, 3);
+        assert_eq!(ty, "fungsi(int)");
+        assert_eq!(captures(&tambah), ["jumlah", "faktor"]);
+        let (dalam, _) = lambda(&tambah.body, 1);
+        assert_eq!(captures(&dalam), ["n", "faktor"]);
+        assert!(!kali.captures_self);
+
+        // Variables assigned after their declaration are shared by reference; the others are copied
+        let faktor = kali.captures[0];
+        assert_eq!(program.symbol(faktor).captured, Some(hir::Capture::ByValue));
+        assert_eq!(program.symbol(tambah.captures[0]).captured, Some(hir::Capture::ByReference));
+        assert_eq!(program.symbol(tambah.parameters[0]).captured, Some(hir::Capture::ByValue));
+        let y = program.symbols.iter().find(|symbol| symbol.name == "y").unwrap();
+        assert_eq!(y.captured, None);
+
+        assert!(matches!(analyze("buat kali = (x: int) => x * 2\nkali(\"a\")\n"), Err(SemanticError::TypeMismatch { .. })));
+        assert_eq!(analyze("buat kali = (x) => x * 2\nkali(1, 2)\n").unwrap_err().code(), "K0206");
+        let source = "kelas Akun:\n    buat saldo = 0\n    fungsi penambah():\n        kembalikan (n: int) => saldo + n\n";
+        assert!(analyze(source).is_ok(), "{:?}", analyze(source));
+    }
+
+    #[test]
+    fn test_pipelines_are_typed() {
+        let source = "buat angka = [3, 1, 4]\nbuat teks = angka |> saring(x => x > 1) |> petakan(x => \"n{x}\")\nbuat total = angka |> lipat(0, (s, x) => s + x)\n";
+        let ast = Parser::new(source).parse_program().into_result().unwrap();
+        let program = SemanticAnalyzer::new().analyze(&ast).unwrap();
+        let pipeline = |index: usize| match &program.body[index].kind {
+            hir::StatementKind::Let { value: Some(hir::Expression { kind: hir::ExpressionKind::Pipeline { stages, .. }, ty, .. }), .. } => {
+                (stages.clone(), ty.to_string())
+            }
+            other => panic!("Expected a pipeline, got {:?}", other),
+        };
+
+        let (stages, ty) = pipeline(1);
+        assert_eq!(ty, "list<string>");
+        assert_eq!(stages[0].ty.to_string(), "list<int>");
+        assert!(matches!(&stages[1].kind, hir::StageKind::Map(lambda) if lambda.ty.to_string() == "fungsi(int) -> string"));
+        // A fold is a reduction from the value it is given
+        let (stages, ty) = pipeline(2);
+        assert_eq!(ty, "int");
+        assert!(matches!(&stages[0].kind, hir::StageKind::Reduce { initial: Some(_), .. }));
+
+        assert!(matches!(analyze("buat n = 5 |> ambil(2)\n"), Err(SemanticError::TypeMismatch { .. })));
+        assert!(matches!(analyze("buat angka = [1]\nbuat b = angka |> saring(x => x + 1)\n"), Err(SemanticError::TypeMismatch { .. })));
+
+        // On an instance of a class, a name like a stage calls the method the class declares
+        let sieve = "kelas Saringan:\n    buat batas = 1\n    fungsi saring(nilai: int): bool:\n        kembalikan nilai > batas\n";
+        let source = format!("{}buat s = Saringan()\nbuat lolos: bool = s.saring(2)\nbuat angka = [1, 2].saring(x => s.saring(x))\n", sieve);
+        let ast = Parser::new(&source).parse_program().into_result().unwrap();
+        let program = SemanticAnalyzer::new().analyze(&ast).unwrap();
+        let value = |index: usize| match &program.body[index].kind {
+            hir::StatementKind::Let { value: Some(value), .. } => value.clone(),
+            other => panic!("Expected a variable, got {:?}", other),
+        };
+        assert!(matches!(value(2).kind, hir::ExpressionKind::MethodCall { ref method, .. } if method == "saring"));
+        assert!(matches!(value(3).kind, hir::ExpressionKind::Pipeline { .. }));
+        assert_eq!(analyze(&format!("{}buat s = Saringan()\nbuat b = s.petakan(x => x)\n", sieve)).unwrap_err().code(), "K0216");
+    }
+
+    #[test]
+    fn test_try_catches_by_class() {
+        let classes = "kelas Galat:\n    buat pesan = \"galat\"\nkelas GalatBagi(Galat):\n    buat pembagi = 0\n";
+        let source = format!("{}coba:\n    lempar GalatBagi()\ntangkap (e: GalatBagi):\n    tampilkan e.pembagi\ntangkap e:\n    lempar e\n", classes);
+        let ast = Parser::new(&source).parse_program().into_result().unwrap();
+        let program = SemanticAnalyzer::new().analyze(&ast).unwrap();
+        let catches = program.body.iter().find_map(|statement| match &statement.kind {
+            hir::StatementKind::TryCatch { catches, .. } => Some(catches.clone()),
+            _ => None,
+        }).expect("Expected a try statement");
+        let types: Vec<String> = catches.iter()
+            .map(|catch| program.symbols[catch.variable.unwrap().0].ty.to_string())
+            .collect();
+        assert_eq!(types, ["GalatBagi", "any"]);
+
+        // A clause after one catching its class, or every error, is never reached
+        let source = format!("{}coba:\n    tampilkan 1\ntangkap (e: Galat):\n    tampilkan 2\ntangkap (e: GalatBagi):\n    tampilkan 3\n", classes);
+        let error = analyze(&source).unwrap_err();
+        assert_eq!(error.code(), "K0210");
+        assert!(error.to_string().contains("tangkap (e: GalatBagi)"), "{}", error);
+        assert_eq!(analyze("coba:\n    tampilkan 1\ntangkap e:\n    tampilkan 2\ntangkap e:\n    tampilkan 3\n").unwrap_err().code(), "K0210");
+
+        let error = analyze("coba:\n    tampilkan 1\ntangkap (e: int):\n    tampilkan 2\n").unwrap_err();
+        assert!(error.to_string().contains("'int' is not a class"), "{}", error);
+        assert_eq!(error.code(), "K0230");
+        let error = analyze("kelas Galat<T>:\n    var isi: T\ncoba:\n    tampilkan 1\ntangkap (e: Galat):\n    tampilkan 2\n").unwrap_err();
+        assert_eq!(error.code(), "K0229");
+        let error = analyze("lempar 42\n").unwrap_err();
+        assert!(error.to_string().contains("Cannot throw a value of type 'int'"), "{}", error);
+        assert_eq!(error.code(), "K0228");
+    }
+
+    #[test]
+    fn test_optionals_are_checked_before_use() {
+        let class = "kelas Pengguna:\n    buat nama = \"anonim\"\n";
+        let error = analyze(&format!("{}fungsi sapa(p: Pengguna?): string:\n    kembalikan p.nama\n", class)).unwrap_err();
+        assert_eq!(error.code(), "K0211");
+        assert!(error.to_string().contains("'p' may be nol"), "{}", error);
+
+        // A check against `nol` proves the value is there where it holds, and after a branch that leaves
+        for body in [
+            "    jika p == nol:\n        kembalikan \"-\"\n    kembalikan p.nama\n",
+            "    jika p != nol:\n        kembalikan p.nama\n    kembalikan \"-\"\n",
+            "    jika p != nol dan p.nama != \"\":\n        kembalikan p.nama\n    kembalikan \"-\"\n",
+            "    jika tidak (p == nol atau p.nama == \"\"):\n        kembalikan p.nama\n    kembalikan \"-\"\n",
+            "    kembalikan p?.nama ?: \"-\"\n",
+        ] {
+            analyze(&format!("{}fungsi sapa(p: Pengguna?): string:\n{}", class, body)).unwrap();
+        }
+        let after_if = "    jika p != nol:\n        tampilkan p.nama\n    kembalikan p.nama\n";
+        assert_eq!(analyze(&format!("{}fungsi sapa(p: Pengguna?): string:\n{}", class, after_if)).unwrap_err().code(), "K0211");
+
+        // Assignments change what is known, and a loop may run its assignments before any statement of its body
+        analyze("buat x: int? = nol\nx = 5\ntampilkan x + 1\n").unwrap();
+        assert_eq!(analyze("buat x: int? = 5\nx = nol\ntampilkan x + 1\n").unwrap_err().code(), "K0211");
+        assert_eq!(analyze("buat x: int? = 1\nselama benar:\n    tampilkan x + 1\n    x = nol\n").unwrap_err().code(), "K0211");
+
+        // A call may assign a variable other functions can reach: a global a function assigns, or a variable
+        // a lambda shares and assigns
+        let global = format!("{}buat p: Pengguna? = Pengguna()\nfungsi ubah():\n    p = nol\n", class);
+        analyze(&format!("{}jika p != nol:\n    tampilkan akar_kuadrat(4.0)\n    tampilkan p.nama\n", global)).unwrap();
+        assert_eq!(analyze(&format!("{}jika p != nol:\n    ubah()\n    tampilkan p.nama\n", global)).unwrap_err().code(), "K0211");
+        assert_eq!(analyze(&format!("{}fungsi cetak():\n    jika p != nol:\n        ubah()\n        tampilkan p.nama\n", global)).unwrap_err().code(), "K0211");
+        let shared = "    buat q: Pengguna? = p\n    buat hapus = fungsi():\n        q = nol\n    jika q != nol:\n        hapus()\n        kembalikan q.nama\n    kembalikan \"-\"\n";
+        assert_eq!(analyze(&format!("{}fungsi sapa(p: Pengguna?): string:\n{}", class, shared)).unwrap_err().code(), "K0211");
+        analyze(&format!("{}fungsi sapa(p: Pengguna?): string:\n{}", class, shared.replace("        hapus()\n", ""))).unwrap();
+
+        let source = format!("{}buat p: Pengguna? = nol\nbuat nama_pengguna = p?.nama\nbuat tampil = nama_pengguna ?: \"-\"\n", class);
+        let ast = Parser::new(&source).parse_program().into_result().unwrap();
+        let program = SemanticAnalyzer::new().analyze(&ast).unwrap();
+        let type_of = |name: &str| program.symbols.iter().find(|symbol| symbol.name == name).unwrap().ty.to_string();
+        assert_eq!(type_of("nama_pengguna"), "string?");
+        assert_eq!(type_of("tampil"), "string");
+    }
+
+    #[test]
+    fn test_question_mark_propagates_nol_and_errors() {
+        analyze("fungsi naik(x: int?): int?:\n    kembalikan x? + 1\n").unwrap();
+        let error = analyze("fungsi naik(x: int?): int:\n    kembalikan x? + 1\n").unwrap_err();
+        assert_eq!(error.code(), "K0226");
+        assert!(error.to_string().contains("declared to return 'int'"), "{}", error);
+        let error = analyze("buat x: int? = 1\nbuat y = x?\n").unwrap_err();
+        assert_eq!(error.code(), "K0224");
+        let error = analyze("fungsi naik(x: int): int?:\n    kembalikan x?\n").unwrap_err();
+        assert!(matches!(error, SemanticError::PropagateNonOptional { ref found, .. } if found == "int"), "{:?}", error);
+
+        let baca = "fungsi baca(teks: string): Hasil<int, string>:\n    jika teks == \"\":\n        kembalikan Gagal(\"kosong\")\n    kembalikan Sukses(1)\n";
+        let source = format!("{}fungsi jumlah(a: string, b: string): Hasil<int, string>:\n    kembalikan Sukses(baca(a)? + baca(b)?)\n", baca);
+        let ast = Parser::new(&source).parse_program().into_result().unwrap();
+        let program = SemanticAnalyzer::new().analyze(&ast).unwrap();
+        let jumlah = program.symbols.iter().find(|symbol| symbol.name == "jumlah").unwrap();
+        assert_eq!(jumlah.ty.to_string(), "fungsi(string, string) -> Hasil<int, string>");
+        // The error type of the function must take the errors `?` returns
+        let error = analyze(&format!("{}fungsi satu(): Hasil<int, int>:\n    kembalikan Sukses(baca(\"1\")?)\n", baca)).unwrap_err();
+        assert!(error.to_string().contains("'?' returns 'Hasil<"), "{}", error);
+    }
+
+    #[test]
+    fn test_immutable_bindings_cannot_be_assigned() {
+        analyze("buat a = 1\na = 2\nbiarkan mut b = 1\nb = 2\nbiarkan c\nc = 3\nfungsi f(mut x):\n    x = x + 1\n").unwrap();
+        let error = analyze("biarkan jumlah = 0\njumlah = 1\n").unwrap_err();
+        assert_eq!(error.code(), "K0212");
+        assert!(error.to_string().contains("biarkan mut jumlah"), "{}", error);
+        assert!(error.to_string().contains("Declared at line 1"), "{}", error);
+        let error = analyze("fungsi f(n):\n    n = n - 1\n").unwrap_err();
+        assert!(error.to_string().contains("'fungsi ...(mut n)'"), "{}", error);
+        assert_eq!(analyze("konstan BATAS = 10\nBATAS = 11\n").unwrap_err().code(), "K0212");
+        assert_eq!(analyze("biarkan i = 0\ni++\n").unwrap_err().code(), "K0212");
+    }
+
+    #[test]
+    fn test_konstan_values_are_computed_when_compiling() {
+        let source = "konstan DETIK = 24 * 60 * 60\nkonstan SETENGAH: float = 1 / 2 + DETIK\nkonstan GARIS = \"-\" * 3 + \">\"\nkonstan BESAR = DETIK > 1000 dan tidak salah\n";
+        let ast = Parser::new(source).parse_program().into_result().unwrap();
+        let program = SemanticAnalyzer::new().analyze(&ast).unwrap();
+        let values: Vec<&hir::Constant> = program.body.iter()
+            .filter_map(|statement| match &statement.kind {
+                hir::StatementKind::Const { value, .. } => Some(value),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(values, [
+            &hir::Constant::Integer(86400),
+            &hir::Constant::Float(86400.0),
+            &hir::Constant::String("--->".to_string()),
+            &hir::Constant::Boolean(true),
+        ]);
+
+        let error = analyze("fungsi sekarang(): int:\n    kembalikan 0\nkonstan MULAI = sekarang()\n").unwrap_err();
+        assert_eq!(error.code(), "K0213");
+        assert!(error.to_string().contains("calls only run when the program runs"), "{}", error);
+        let error = analyze("buat x = 1\nkonstan Y = x + 1\n").unwrap_err();
+        assert!(error.to_string().contains("a variable only has a value"), "{}", error);
+        assert!(analyze("konstan NOL = 1 / 0\n").unwrap_err().to_string().contains("divides by zero"));
+        assert!(analyze("konstan BESAR = 9223372036854775807 + 1\n").unwrap_err().to_string().contains("overflows"));
+        assert_eq!(analyze("konstan KOSONG\n").unwrap_err().code(), "K0213");
     }
 }
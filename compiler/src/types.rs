@@ -0,0 +1,472 @@
+//! Types of KODEON values, inferred and checked by the semantic analyzer
+//!
+//! Inference is Hindley-Milner style: a value whose type is not known yet gets a type
+//! variable, and using the value binds the variable by unification. Unlike classic
+//! Hindley-Milner, a few conversions are implicit: an `int` is accepted where a `float` is
+//! expected, and a value where an optional is expected.
+
+use crate::parser::TypeExpr;
+use std::collections::HashMap;
+
+/// Type of a value
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    Int,
+    Float,
+    Bool,
+    String,
+    Void, // result of a function that returns nothing
+    List(Box<Type>),
+    Map(Box<Type>, Box<Type>), // key, value
+    Optional(Box<Type>),
+    Tuple(Vec<Type>), // (int, string)
+    Function {
+        parameters: Vec<Type>,
+        return_type: Box<Type>,
+        variadic: bool, // the last parameter takes any number of arguments
+    },
+    Named {
+        name: String,
+        arguments: Vec<Type>,
+    }, // classes and other named types, e.g. `Penghitung` or `channel<int>`
+    Parameter(String), // type parameter of the generic function or class being defined, e.g. `T`
+    Variable(usize), // not inferred yet
+    Any,             // only checked at run time, e.g. values of untyped imports
+}
+
+impl Type {
+    /// Named type without type arguments, e.g. a class
+    pub fn named(name: &str) -> Self {
+        Type::Named { name: name.to_string(), arguments: Vec::new() }
+    }
+
+    /// Type of a function that takes `parameters` and returns `return_type`
+    pub fn function(parameters: Vec<Type>, return_type: Type) -> Self {
+        Type::Function { parameters, return_type: Box::new(return_type), variadic: false }
+    }
+
+    /// Type written as `annotation`; `fresh` supplies the element type of a bare `Daftar` or `Peta`
+    pub fn from_annotation(annotation: &TypeExpr, fresh: &mut impl FnMut() -> Type) -> Type {
+        match annotation {
+            TypeExpr::Named { name, arguments } => {
+                let mut arguments: Vec<Type> = arguments.iter()
+                    .map(|argument| Type::from_annotation(argument, fresh))
+                    .collect();
+                match (name.to_lowercase().as_str(), arguments.len()) {
+                    ("int" | "integer" | "bulat", 0) => Type::Int,
+                    ("float" | "double" | "desimal" | "pecahan" | "angka" | "number", 0) => Type::Float,
+                    ("string" | "str" | "teks", 0) => Type::String,
+                    ("bool" | "boolean", 0) => Type::Bool,
+                    ("void" | "kosong", 0) => Type::Void,
+                    ("any" | "apapun" | "objek" | "object", 0) => Type::Any,
+                    ("list" | "daftar" | "array" | "larik", 0) => Type::List(Box::new(fresh())),
+                    ("list" | "daftar" | "array" | "larik", 1) => Type::List(Box::new(arguments.remove(0))),
+                    ("map" | "peta" | "kamus" | "dict", 0) => Type::Map(Box::new(fresh()), Box::new(fresh())),
+                    ("map" | "peta" | "kamus" | "dict", 2) => {
+                        let value = arguments.remove(1);
+                        Type::Map(Box::new(arguments.remove(0)), Box::new(value))
+                    }
+                    ("opsional" | "optional", 1) => Type::Optional(Box::new(arguments.remove(0))),
+                    // The `Hasil` enum of the prelude, under either name
+                    ("hasil" | "result", 2) => Type::Named { name: "Hasil".to_string(), arguments },
+                    _ => Type::Named { name: name.clone(), arguments },
+                }
+            }
+            TypeExpr::Array(element) => Type::List(Box::new(Type::from_annotation(element, fresh))),
+            TypeExpr::Optional(inner) => Type::Optional(Box::new(Type::from_annotation(inner, fresh))),
+            TypeExpr::Tuple(elements) => Type::Tuple(elements.iter().map(|element| Type::from_annotation(element, fresh)).collect()),
+            TypeExpr::Function { parameters, return_type } => Type::function(
+                parameters.iter().map(|parameter| Type::from_annotation(parameter, fresh)).collect(),
+                match return_type {
+                    Some(return_type) => Type::from_annotation(return_type, fresh),
+                    None => Type::Void,
+                },
+            ),
+        }
+    }
+
+    /// `self` with the named types in `parameters` turned into type parameters
+    pub fn with_parameters(&self, parameters: &[String]) -> Type {
+        self.transform(&mut |ty| match ty {
+            Type::Named { name, arguments } if arguments.is_empty() && parameters.contains(name) => {
+                Some(Type::Parameter(name.clone()))
+            }
+            _ => None,
+        })
+    }
+
+    /// `self` with each type parameter replaced by its argument in `arguments`
+    pub fn substitute(&self, arguments: &HashMap<String, Type>) -> Type {
+        self.transform(&mut |ty| match ty {
+            Type::Parameter(name) => arguments.get(name).cloned(),
+            _ => None,
+        })
+    }
+
+    /// Rebuild the type bottom-up, letting `replace` substitute any part of it
+    pub fn transform(&self, replace: &mut impl FnMut(&Type) -> Option<Type>) -> Type {
+        if let Some(replacement) = replace(self) {
+            return replacement;
+        }
+        match self {
+            Type::List(element) => Type::List(Box::new(element.transform(replace))),
+            Type::Map(key, value) => Type::Map(Box::new(key.transform(replace)), Box::new(value.transform(replace))),
+            Type::Optional(inner) => Type::Optional(Box::new(inner.transform(replace))),
+            Type::Tuple(elements) => Type::Tuple(elements.iter().map(|element| element.transform(replace)).collect()),
+            Type::Function { parameters, return_type, variadic } => Type::Function {
+                parameters: parameters.iter().map(|parameter| parameter.transform(replace)).collect(),
+                return_type: Box::new(return_type.transform(replace)),
+                variadic: *variadic,
+            },
+            Type::Named { name, arguments } => Type::Named {
+                name: name.clone(),
+                arguments: arguments.iter().map(|argument| argument.transform(replace)).collect(),
+            },
+            other => other.clone(),
+        }
+    }
+
+    /// Check whether the type is `int` or `float`
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, Type::Int | Type::Float)
+    }
+
+    /// Type without its optional marker
+    pub fn non_optional(&self) -> &Type {
+        match self {
+            Type::Optional(inner) => inner.non_optional(),
+            other => other,
+        }
+    }
+}
+
+impl std::fmt::Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let list = |types: &[Type]| types.iter().map(|t| t.to_string()).collect::<Vec<_>>().join(", ");
+        match self {
+            Type::Int => write!(f, "int"),
+            Type::Float => write!(f, "float"),
+            Type::Bool => write!(f, "bool"),
+            Type::String => write!(f, "string"),
+            Type::Void => write!(f, "void"),
+            Type::List(element) => write!(f, "list<{}>", element),
+            Type::Map(key, value) => write!(f, "map<{}, {}>", key, value),
+            // A suffix after a function type would apply to its return type
+            Type::Optional(inner) if matches!(**inner, Type::Function { .. }) => write!(f, "({})?", inner),
+            Type::Optional(inner) => write!(f, "{}?", inner),
+            Type::Tuple(elements) => write!(f, "({})", list(elements)),
+            Type::Function { parameters, return_type, variadic } => {
+                write!(f, "fungsi({}{})", if *variadic { "..." } else { "" }, list(parameters))?;
+                if **return_type != Type::Void {
+                    write!(f, " -> {}", return_type)?;
+                }
+                Ok(())
+            }
+            Type::Named { name, arguments } if arguments.is_empty() => write!(f, "{}", name),
+            Type::Named { name, arguments } => write!(f, "{}<{}>", name, list(arguments)),
+            Type::Parameter(name) => write!(f, "{}", name),
+            Type::Variable(_) => write!(f, "unknown"),
+            Type::Any => write!(f, "any"),
+        }
+    }
+}
+
+/// Type variables and the types they stand for, solved by unification
+#[derive(Debug, Default)]
+pub struct Unifier {
+    bindings: Vec<Option<Type>>,         // type bound to each variable, by number
+    superclasses: HashMap<String, String>, // parent of each class that extends another
+    interfaces: HashMap<String, Vec<String>>, // interfaces each class implements itself
+    bounds: HashMap<String, Type>, // bound of each type parameter in scope that declares one
+}
+
+impl Unifier {
+    /// Create a unifier without variables
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// New type variable
+    pub fn fresh(&mut self) -> Type {
+        self.bindings.push(None);
+        Type::Variable(self.bindings.len() - 1)
+    }
+
+    /// Record that `class` extends `parent`, so its instances are accepted as `parent`
+    pub fn add_superclass(&mut self, class: &str, parent: &str) {
+        self.superclasses.insert(class.to_string(), parent.to_string());
+    }
+
+    /// Record that `class` implements `interface`, so its instances and those of its subclasses are accepted as `interface`
+    pub fn add_interface(&mut self, class: &str, interface: &str) {
+        self.interfaces.entry(class.to_string()).or_default().push(interface.to_string());
+    }
+
+    /// Set the bounds of the type parameters in scope, so that their values are accepted where the bound is
+    pub fn set_bounds(&mut self, bounds: HashMap<String, Type>) {
+        self.bounds = bounds;
+    }
+
+    /// Follow the bindings of a variable at the top of `ty`
+    fn shallow(&self, ty: &Type) -> Type {
+        let mut ty = ty.clone();
+        while let Type::Variable(variable) = ty {
+            match &self.bindings[variable] {
+                Some(bound) => ty = bound.clone(),
+                None => break,
+            }
+        }
+        ty
+    }
+
+    /// `ty` with every bound variable replaced by its type
+    pub fn resolve(&self, ty: &Type) -> Type {
+        self.map(&self.shallow(ty), &mut |ty| Some(ty.clone()))
+    }
+
+    /// `ty` with its variables resolved, and those still unknown replaced by `any`
+    pub fn finish(&self, ty: &Type) -> Type {
+        self.map(&self.shallow(ty), &mut |ty| match ty {
+            Type::Variable(_) => Some(Type::Any),
+            _ => None,
+        })
+    }
+
+    /// Copy of `ty` in which each unknown variable is replaced by a new one
+    ///
+    /// Each use of a function instantiates its type, so one call does not constrain the
+    /// parameters the function left open for the next call.
+    pub fn instantiate(&mut self, ty: &Type) -> Type {
+        let resolved = self.resolve(ty);
+        let mut renamed: HashMap<usize, Type> = HashMap::new();
+        let mut variables = Vec::new();
+        self.map(&resolved, &mut |ty| {
+            if let Type::Variable(variable) = ty {
+                variables.push(*variable);
+            }
+            None
+        });
+        for variable in variables {
+            if let std::collections::hash_map::Entry::Vacant(entry) = renamed.entry(variable) {
+                entry.insert(self.fresh());
+            }
+        }
+        self.map(&resolved, &mut |ty| match ty {
+            Type::Variable(variable) => renamed.get(variable).cloned(),
+            _ => None,
+        })
+    }
+
+    /// Rebuild `ty` bottom-up, letting `replace` substitute any part of it
+    fn map(&self, ty: &Type, replace: &mut impl FnMut(&Type) -> Option<Type>) -> Type {
+        let ty = self.shallow(ty);
+        if let Some(replacement) = replace(&ty) {
+            if replacement != ty {
+                return replacement;
+            }
+        }
+        let mut inner = |ty: &Type| Box::new(self.map(ty, replace));
+        match &ty {
+            Type::List(element) => Type::List(inner(element)),
+            Type::Map(key, value) => {
+                let key = inner(key);
+                Type::Map(key, inner(value))
+            }
+            Type::Optional(value) => Type::Optional(inner(value)),
+            Type::Tuple(elements) => Type::Tuple(elements.iter().map(|element| *inner(element)).collect()),
+            Type::Function { parameters, return_type, variadic } => Type::Function {
+                parameters: parameters.iter().map(|parameter| *inner(parameter)).collect(),
+                return_type: inner(return_type),
+                variadic: *variadic,
+            },
+            Type::Named { name, arguments } => Type::Named {
+                name: name.clone(),
+                arguments: arguments.iter().map(|argument| *inner(argument)).collect(),
+            },
+            other => other.clone(),
+        }
+    }
+
+    /// Check whether `variable` appears in `ty`
+    fn occurs(&self, variable: usize, ty: &Type) -> bool {
+        let mut found = false;
+        self.map(ty, &mut |ty| {
+            found |= *ty == Type::Variable(variable);
+            None
+        });
+        found
+    }
+
+    /// Check whether a value of type `found` can be used where `expected` is required,
+    /// binding type variables so that it can
+    pub fn unify(&mut self, expected: &Type, found: &Type) -> bool {
+        let expected = self.shallow(expected);
+        let found = self.shallow(found);
+        match (&expected, &found) {
+            (Type::Variable(a), Type::Variable(b)) if a == b => true,
+            // A variable unified with `any` becomes `any` too, rather than staying open
+            (Type::Variable(variable), other) | (other, Type::Variable(variable)) => {
+                // A cyclic type such as `x = [x]` is left to the run time
+                if !self.occurs(*variable, other) {
+                    self.bindings[*variable] = Some(other.clone());
+                }
+                true
+            }
+            (Type::Any, _) | (_, Type::Any) => true,
+            (Type::Float, Type::Int) => true,
+            (Type::List(expected), Type::List(found)) => self.unify(expected, found),
+            (Type::Map(expected_key, expected_value), Type::Map(found_key, found_value)) => {
+                self.unify(expected_key, found_key) && self.unify(expected_value, found_value)
+            }
+            (Type::Optional(expected), Type::Optional(found)) => self.unify(expected, found),
+            (Type::Tuple(expected), Type::Tuple(found)) => {
+                expected.len() == found.len()
+                    && expected.iter().zip(found).all(|(expected, found)| self.unify(expected, found))
+            }
+            // A value can be used where an optional one is expected, but not the other way around
+            (Type::Optional(expected), found) => self.unify(expected, found),
+            (
+                Type::Function { parameters: expected_parameters, return_type: expected_return, .. },
+                Type::Function { parameters: found_parameters, return_type: found_return, .. },
+            ) => {
+                expected_parameters.len() == found_parameters.len()
+                    // A function that accepts the expected arguments can stand in for the expected one
+                    && expected_parameters.iter().zip(found_parameters)
+                        .all(|(expected, found)| self.unify(found, expected))
+                    && self.unify(expected_return, found_return)
+            }
+            (
+                Type::Named { name: expected_name, arguments: expected_arguments },
+                Type::Named { name: found_name, arguments: found_arguments },
+            ) => {
+                if expected_name == found_name {
+                    expected_arguments.len() == found_arguments.len()
+                        && expected_arguments.iter().zip(found_arguments)
+                            .all(|(expected, found)| self.unify(expected, found))
+                } else {
+                    self.is_subtype(found_name, expected_name)
+                }
+            }
+            // Whatever type a bounded parameter stands for implements its bound
+            (expected, Type::Parameter(name)) if *expected != found && self.bounds.contains_key(name) => {
+                let bound = self.bounds[name].clone();
+                self.unify(expected, &bound)
+            }
+            (expected, found) => expected == found,
+        }
+    }
+
+    /// Check whether `a` and `b` are the same type, binding type variables so that they are; unlike
+    /// `unify`, no value is converted, as an `int` is to a `float`
+    pub fn unify_exact(&mut self, a: &Type, b: &Type) -> bool {
+        self.unify(a, b) && self.unify(b, a)
+    }
+
+    /// Unify `expected` with every type in `found`, or leave the bindings as they were if one does not fit
+    pub fn unify_all(&mut self, expected: &Type, found: &[Type]) -> bool {
+        let bindings = self.bindings.clone();
+        if found.iter().all(|found| self.unify(expected, found)) {
+            return true;
+        }
+        self.bindings = bindings;
+        false
+    }
+
+    /// Check whether `class` extends or implements `ancestor`, directly or through its parents
+    fn is_subtype(&self, class: &str, ancestor: &str) -> bool {
+        let implements = |class: &str| self.interfaces.get(class).is_some_and(|interfaces| interfaces.iter().any(|name| name == ancestor));
+        if implements(class) {
+            return true;
+        }
+        let mut current = class;
+        let mut steps = 0;
+        while let Some(parent) = self.superclasses.get(current) {
+            if parent == ancestor || implements(parent) {
+                return true;
+            }
+            current = parent;
+            // Guards against a cycle of classes extending each other
+            steps += 1;
+            if steps > self.superclasses.len() {
+                break;
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unify_binds_variables() {
+        let mut unifier = Unifier::new();
+        let element = unifier.fresh();
+        let list = Type::List(Box::new(element.clone()));
+
+        assert!(unifier.unify(&list, &Type::List(Box::new(Type::Int))));
+        assert_eq!(unifier.resolve(&element), Type::Int);
+        assert!(!unifier.unify(&list, &Type::List(Box::new(Type::String))));
+        assert!(unifier.unify(&Type::Float, &Type::Int));
+        assert!(!unifier.unify(&Type::Int, &Type::Float));
+        assert!(unifier.unify(&Type::Optional(Box::new(Type::String)), &Type::String));
+        assert!(!unifier.unify(&Type::String, &Type::Optional(Box::new(Type::String))));
+        assert!(!unifier.unify_exact(&Type::Float, &Type::Int));
+        assert!(!unifier.unify_exact(&Type::Optional(Box::new(Type::String)), &Type::String));
+
+        let mixed = unifier.fresh();
+        assert!(!unifier.unify_all(&mixed, &[Type::Int, Type::String]));
+        assert_eq!(unifier.resolve(&mixed), mixed);
+    }
+
+    #[test]
+    fn test_instantiate_renames_open_variables() {
+        let mut unifier = Unifier::new();
+        let parameter = unifier.fresh();
+        let identity = Type::function(vec![parameter.clone()], parameter);
+
+        let first = unifier.instantiate(&identity);
+        let second = unifier.instantiate(&identity);
+        if let (Type::Function { parameters: first, .. }, Type::Function { parameters: second, .. }) = (&first, &second) {
+            assert!(unifier.unify(&first[0], &Type::Int));
+            assert!(unifier.unify(&second[0], &Type::String));
+        }
+        assert_eq!(unifier.resolve(&first).to_string(), "fungsi(int) -> int");
+        assert_eq!(unifier.finish(&identity).to_string(), "fungsi(any) -> any");
+    }
+
+    #[test]
+    fn test_subclasses_and_annotations() {
+        let mut unifier = Unifier::new();
+        unifier.add_superclass("Mobil", "Kendaraan");
+        assert!(unifier.unify(&Type::named("Kendaraan"), &Type::named("Mobil")));
+        assert!(!unifier.unify(&Type::named("Mobil"), &Type::named("Kendaraan")));
+        unifier.add_interface("Kendaraan", "Bergerak");
+        assert!(unifier.unify(&Type::named("Bergerak"), &Type::named("Mobil")));
+        assert!(!unifier.unify(&Type::named("Mobil"), &Type::named("Bergerak")));
+
+        let generic = Type::List(Box::new(Type::named("T"))).with_parameters(&["T".to_string()]);
+        assert_eq!(generic, Type::List(Box::new(Type::Parameter("T".to_string()))));
+        let arguments = HashMap::from([("T".to_string(), Type::Int)]);
+        assert_eq!(generic.substitute(&arguments), Type::List(Box::new(Type::Int)));
+        let parameter = Type::Parameter("T".to_string());
+        assert!(!unifier.unify(&Type::named("Bergerak"), &parameter));
+        unifier.set_bounds(HashMap::from([("T".to_string(), Type::named("Mobil"))]));
+        assert!(unifier.unify(&Type::named("Bergerak"), &parameter));
+        assert!(unifier.unify(&parameter, &parameter));
+
+        let annotation = TypeExpr::Named {
+            name: "Peta".to_string(),
+            arguments: vec![TypeExpr::named("teks"), TypeExpr::Array(Box::new(TypeExpr::named("int")))],
+        };
+        let ty = Type::from_annotation(&annotation, &mut || unifier.fresh());
+        assert_eq!(ty.to_string(), "map<string, list<int>>");
+        let annotation = TypeExpr::Named {
+            name: "Result".to_string(),
+            arguments: vec![TypeExpr::Named { name: "Opsional".to_string(), arguments: vec![TypeExpr::named("int")] }, TypeExpr::named("teks")],
+        };
+        let ty = Type::from_annotation(&annotation, &mut || unifier.fresh());
+        assert_eq!(ty.to_string(), "Hasil<int?, string>");
+    }
+}
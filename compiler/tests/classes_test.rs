@@ -0,0 +1,134 @@
+//! Tests for the lowering of classes and interfaces to IR
+
+use kodeon_compiler::ir::{IRModule, Instruction, Type, Value};
+
+mod common;
+use common::{function, generate};
+
+fn instructions<'a>(module: &'a IRModule, name: &str) -> &'a [Instruction] {
+    &function(module, name).blocks[0].instructions
+}
+
+const ANIMALS: &str = r#"
+kelas Hewan:
+    var nama: string = "?"
+    fungsi baru(nama: string):
+        ini.nama = nama
+    fungsi suara(): string:
+        kembalikan "..."
+    fungsi perkenalan(): string:
+        kembalikan nama + ": " + suara()
+
+kelas Kucing(Hewan):
+    var nyawa = 9
+    fungsi suara(): string:
+        kembalikan "meong"
+    fungsi asli(): string:
+        kembalikan super.suara()
+
+buat hewan: Hewan = Kucing("Tom")
+tampilkan(hewan.perkenalan())
+"#;
+
+#[test]
+fn test_class_layouts_put_inherited_members_first() {
+    let module = generate(ANIMALS);
+
+    let names: Vec<&str> = module.classes.iter().map(|class| class.name.as_str()).collect();
+    assert_eq!(names, ["Hewan", "Kucing"]);
+    let kucing = &module.classes[1];
+    assert_eq!(kucing.parent.as_deref(), Some("Hewan"));
+    assert_eq!(kucing.fields, [("nama".to_string(), Type::String), ("nyawa".to_string(), Type::Int)]);
+
+    // An override takes the slot of the method it overrides; constructors get no slot
+    let methods: Vec<(&str, &str)> = kucing.methods.iter().map(|(m, f)| (m.as_str(), f.as_str())).collect();
+    assert_eq!(methods, [("suara", "Kucing.suara"), ("perkenalan", "Hewan.perkenalan"), ("asli", "Kucing.asli")]);
+}
+
+#[test]
+fn test_construction_initializes_fields_then_runs_the_constructor() {
+    let module = generate(ANIMALS);
+
+    let calls: Vec<&Instruction> = instructions(&module, "main").iter()
+        .filter(|instruction| matches!(instruction, Instruction::NewObject { .. } | Instruction::Call { .. } | Instruction::VirtualCall { .. }))
+        .collect();
+    assert!(matches!(calls[0], Instruction::NewObject { class, .. } if class == "Kucing"));
+    assert!(matches!(calls[1], Instruction::Call { function, .. } if function == "Kucing.$init"));
+    assert!(matches!(calls[2], Instruction::Call { function, arguments, .. } if function == "Hewan.baru" && arguments.len() == 2));
+    // `hewan` is a `Hewan`, so the call goes through the slot of `perkenalan`
+    assert!(matches!(calls[3], Instruction::VirtualCall { class, slot: 1, .. } if class == "Hewan"));
+
+    // The initializer of a class runs the one of its parent first
+    let init = instructions(&module, "Kucing.$init");
+    assert!(matches!(&init[0], Instruction::Call { function, .. } if function == "Hewan.$init"));
+    assert!(matches!(&init[1], Instruction::SetField { class, index: 1, value: Value::Constant(_), .. } if class == "Kucing"));
+}
+
+#[test]
+fn test_methods_receive_their_instance() {
+    let module = generate(ANIMALS);
+
+    let perkenalan = module.functions.iter().find(|f| f.name == "Hewan.perkenalan").expect("Missing method");
+    assert_eq!(perkenalan.parameters[0].name, "ini");
+    assert_eq!(perkenalan.parameters[0].param_type, Type::Object { name: "Hewan".to_string() });
+
+    // Bare fields and methods are those of `ini`, and calls to them dispatch
+    let body = instructions(&module, "Hewan.perkenalan");
+    assert!(body.iter().any(|instruction| matches!(instruction,
+        Instruction::GetField { object: Value::Variable(object), index: 0, .. } if object == "ini")));
+    assert!(body.iter().any(|instruction| matches!(instruction,
+        Instruction::VirtualCall { slot: 0, .. })));
+
+    // `super.suara()` runs the parent's method without dispatch
+    assert!(instructions(&module, "Kucing.asli").iter().any(|instruction| matches!(instruction,
+        Instruction::Call { function, .. } if function == "Hewan.suara")));
+}
+
+const SHAPES: &str = r#"
+antarmuka Bentuk:
+    fungsi luas(): float
+    fungsi nama(): string
+
+kelas Persegi implementasi Bentuk:
+    var sisi = 2.0
+    fungsi nama(): string:
+        kembalikan "persegi"
+    fungsi luas(): float:
+        kembalikan sisi * sisi
+
+kelas Ubin(Persegi):
+    fungsi nama(): string:
+        kembalikan "ubin"
+
+fungsi cetak(bentuk: Bentuk):
+    tampilkan(bentuk.nama())
+
+buat bentuk: Bentuk = Ubin()
+cetak(bentuk)
+"#;
+
+#[test]
+fn test_classes_fill_the_tables_of_their_interfaces() {
+    let module = generate(SHAPES);
+
+    assert_eq!(module.interfaces.len(), 1);
+    let bentuk = &module.interfaces[0];
+    assert_eq!(bentuk.name, "Bentuk");
+    assert_eq!(bentuk.method_slot("nama"), Some(1));
+
+    // Tables follow the order of the interface, not of the class, and subclasses inherit them
+    let persegi = module.classes.iter().find(|class| class.name == "Persegi").expect("Missing class");
+    assert_eq!(persegi.interface_table("Bentuk"), Some(&["Persegi.luas".to_string(), "Persegi.nama".to_string()][..]));
+    let ubin = module.classes.iter().find(|class| class.name == "Ubin").expect("Missing class");
+    assert_eq!(ubin.interface_table("Bentuk"), Some(&["Persegi.luas".to_string(), "Ubin.nama".to_string()][..]));
+}
+
+#[test]
+fn test_interface_values_dispatch_through_interface_tables() {
+    let module = generate(SHAPES);
+
+    let cetak = module.functions.iter().find(|f| f.name == "cetak").expect("Missing function");
+    assert_eq!(cetak.parameters[0].param_type, Type::Trait { name: "Bentuk".to_string() });
+    assert!(instructions(&module, "cetak").iter().any(|instruction| matches!(instruction,
+        Instruction::InterfaceCall { interface, slot: 1, object: Value::Variable(object), .. } if interface == "Bentuk" && object == "bentuk")));
+}
@@ -0,0 +1,97 @@
+//! Tests for lambdas, and the environments closures carry their captured variables in
+
+use kodeon_compiler::hir::Capture;
+use kodeon_compiler::ir::{ClosureLayout, Instruction, Type, Value};
+
+mod common;
+use common::{function, generate, instructions};
+
+const COUNTERS: &str = r#"
+buat faktor = 2
+buat jumlah = 0
+buat kali = (x: int) => x * faktor
+buat tambah = fungsi(n):
+    jumlah = jumlah + n
+tambah(1)
+buat y: int = kali(3)
+
+fungsi pencacah(mut mulai: int):
+    buat naik = fungsi():
+        mulai = mulai + 1
+    naik()
+    kembalikan mulai
+
+kelas Akun:
+    buat saldo = 0
+    fungsi penambah():
+        kembalikan (n: int) => ini.saldo + n
+"#;
+
+#[test]
+fn test_closures_are_laid_out_with_their_captures() {
+    let module = generate(COUNTERS);
+
+    let names: Vec<&str> = module.closures.iter().map(|layout| layout.function.as_str()).collect();
+    assert_eq!(names, ["main.lambda0", "main.lambda1", "pencacah.lambda2", "Akun.penambah.lambda3"]);
+    assert_eq!(module.closures[0], ClosureLayout {
+        function: "main.lambda0".to_string(),
+        captures: vec![("faktor".to_string(), Type::Int, Capture::ByValue)],
+    });
+    // Variables assigned after their declaration are shared, so every closure sees the same one
+    assert_eq!(module.closures[1].captures, [("jumlah".to_string(), Type::Int, Capture::ByReference)]);
+    assert_eq!(module.closures[2].captures[0].2, Capture::ByReference);
+    // A lambda in a method captures the instance it was made by
+    assert_eq!(module.closures[3].capture_index("ini"), Some(0));
+    assert_eq!(module.closures[3].captures[0].1, Type::Object { name: "Akun".to_string() });
+
+    // The function of a closure receives its environment first
+    let lambda = function(&module, "main.lambda0");
+    let parameters: Vec<&str> = lambda.parameters.iter().map(|parameter| parameter.name.as_str()).collect();
+    assert_eq!(parameters, ["$env", "x"]);
+    assert_eq!(lambda.parameters[0].param_type, Type::Object { name: "main.lambda0.$env".to_string() });
+    assert!(matches!(
+        instructions(lambda).next(),
+        Some(Instruction::BindCapture { variable, function, index: 0, .. }) if variable == "faktor" && function == "main.lambda0"
+    ));
+}
+
+#[test]
+fn test_closures_share_variables_and_are_called_through_their_environment() {
+    let module = generate(COUNTERS);
+    let main = function(&module, "main");
+
+    let shared: Vec<&str> = instructions(main)
+        .filter_map(|instruction| match instruction {
+            Instruction::AllocaShared { result, .. } => Some(result.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(shared, ["jumlah"]);
+
+    let closures: Vec<(&str, &[String])> = instructions(main)
+        .filter_map(|instruction| match instruction {
+            Instruction::MakeClosure { function, captures, .. } => Some((function.as_str(), captures.as_slice())),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(closures, [("main.lambda0", &["faktor".to_string()][..]), ("main.lambda1", &["jumlah".to_string()][..])]);
+
+    // Calls through a variable go through the closure it holds
+    let calls: Vec<(&Value, &Type)> = instructions(main)
+        .filter_map(|instruction| match instruction {
+            Instruction::ClosureCall { closure, function_type, .. } => Some((closure, function_type)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(calls.len(), 2);
+    assert!(matches!(calls[1].0, Value::Variable(name) if name == "kali"));
+    assert_eq!(calls[1].1, &Type::Function { param_types: vec![Type::Int], return_type: Box::new(Type::Int) });
+
+    // A parameter a closure shares is received under another name and moved to the heap
+    let pencacah = function(&module, "pencacah");
+    assert_eq!(pencacah.parameters[0].name, "mulai.arg");
+    assert!(matches!(
+        instructions(pencacah).next(),
+        Some(Instruction::AllocaShared { result, alloca_type: Type::Int, .. }) if result == "mulai"
+    ));
+}
@@ -0,0 +1,31 @@
+//! Helpers shared by the tests that compile KODEON source to IR
+
+// Each test crate uses only some of the helpers
+#![allow(dead_code)]
+
+use kodeon_compiler::ir::{BasicBlock, Function, IRGenerator, IRModule, Instruction};
+use kodeon_compiler::parser::Parser;
+use kodeon_compiler::semantic_analyzer::SemanticAnalyzer;
+
+/// IR of `source`, which must parse and analyze without errors
+pub fn generate(source: &str) -> IRModule {
+    let mut parser = Parser::new(source);
+    let ast = parser.parse_program().into_result().expect("Failed to parse program");
+    let program = SemanticAnalyzer::new().analyze(&ast).expect("Failed to analyze program");
+    IRGenerator::new().generate_ir(&program).expect("Failed to generate IR")
+}
+
+/// Function `name` of `module`
+pub fn function<'a>(module: &'a IRModule, name: &str) -> &'a Function {
+    module.functions.iter().find(|f| f.name == name).expect("Missing function")
+}
+
+/// Block `name` of `function`
+pub fn block<'a>(function: &'a Function, name: &str) -> &'a BasicBlock {
+    function.blocks.iter().find(|block| block.name == name).expect("Missing block")
+}
+
+/// Instructions of every block of `function`, in order
+pub fn instructions(function: &Function) -> impl Iterator<Item = &Instruction> {
+    function.blocks.iter().flat_map(|block| &block.instructions)
+}
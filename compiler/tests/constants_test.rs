@@ -0,0 +1,45 @@
+//! Tests for `konstan`: its value is computed when compiling, used in place of its reads, and kept in a
+//! constant global
+
+use kodeon_compiler::ir::{BinaryOp, Constant, GlobalVariable, IRModule, Instruction, Type, Value};
+
+mod common;
+use common::{function, generate};
+
+fn global<'a>(module: &'a IRModule, name: &str) -> &'a GlobalVariable {
+    module.global_vars.iter().find(|global| global.name == name).expect("Missing global")
+}
+
+const SOURCE: &str = r#"
+konstan MENIT = 60
+konstan JAM = MENIT * 60
+konstan PI: float = 3
+konstan SALAM = "Halo, " + "dunia"
+
+fungsi detik(jam: int): int:
+    kembalikan jam * JAM
+"#;
+
+#[test]
+fn test_constants_are_emitted_as_constant_globals() {
+    let module = generate(SOURCE);
+    let jam = global(&module, "JAM");
+    assert!(jam.constant);
+    assert_eq!(jam.var_type, Type::Int);
+    assert!(matches!(jam.initializer, Some(Value::Constant(Constant::Int(3600)))));
+    // An integer given to a `float` constant is converted when compiling
+    assert!(matches!(global(&module, "PI").initializer, Some(Value::Constant(Constant::Float(value))) if value == 3.0));
+    assert!(matches!(&global(&module, "SALAM").initializer, Some(Value::Constant(Constant::String(text))) if text == "Halo, dunia"));
+}
+
+#[test]
+fn test_reads_of_constants_use_their_value() {
+    let module = generate(SOURCE);
+    let detik = function(&module, "detik");
+    let instructions: Vec<&Instruction> = detik.blocks.iter().flat_map(|block| &block.instructions).collect();
+    assert!(instructions.iter().any(|instruction| matches!(
+        instruction,
+        Instruction::BinaryOp { op: BinaryOp::Mul, right: Value::Constant(Constant::Int(3600)), .. }
+    )));
+    assert!(!instructions.iter().any(|instruction| matches!(instruction, Instruction::Load { variable, .. } if variable == "JAM")));
+}
@@ -0,0 +1,174 @@
+//! Tests for the blocks `jika` statements and loops are generated as, and for the checks that each block
+//! ends once, at its terminator
+
+use kodeon_compiler::ir::{BasicBlock, BinaryOp, Constant, Function, IRBuilder, Instruction, Terminator, Type, Value};
+
+mod common;
+use common::{block, function, generate};
+
+/// Name of the only block of `function` whose name ends with `suffix`
+fn named<'a>(function: &'a Function, suffix: &str) -> &'a str {
+    let names: Vec<&str> = function.blocks.iter().map(|block| block.name.as_str()).filter(|name| name.ends_with(suffix)).collect();
+    let [name] = names.as_slice() else {
+        panic!("Expected one block named *{}, got {:?}", suffix, names);
+    };
+    name
+}
+
+fn branches_to(block: &BasicBlock, target: &str) -> bool {
+    block.terminator.successors().contains(&target)
+}
+
+#[test]
+fn test_if_branches_to_then_and_else_blocks() {
+    let module = generate(r#"
+fungsi tanda(x: int): int:
+    buat hasil = 0
+    jika x < 0:
+        hasil = -1
+    sebaliknya:
+        hasil = 1
+    kembalikan hasil
+"#);
+    let tanda = function(&module, "tanda");
+    let (then, otherwise, end) = (named(tanda, ".then"), named(tanda, ".else"), named(tanda, ".end"));
+    let Terminator::ConditionalBranch { condition: Value::Variable(condition), then_target, else_target } = &tanda.blocks[0].terminator else {
+        panic!("Expected a conditional branch, got {:?}", tanda.blocks[0].terminator);
+    };
+    assert_eq!((then_target.as_str(), else_target.as_str()), (then, otherwise));
+    assert!(tanda.blocks[0].instructions.iter().any(|instruction| matches!(instruction, Instruction::BinaryOp { result, op: BinaryOp::Lt, .. } if result == condition)));
+    // Both branches store to the variable and meet at the end, which returns
+    for name in [then, otherwise] {
+        assert!(matches!(block(tanda, name).instructions.last(), Some(Instruction::Store { variable, .. }) if variable == "hasil"));
+        assert!(matches!(&block(tanda, name).terminator, Terminator::Branch { target } if target == end));
+    }
+    assert!(matches!(block(tanda, end).terminator, Terminator::Return { value: Some(_) }));
+
+    // Without an else block, a false condition goes straight to the end
+    let module = generate("fungsi cetak(x: int):\n    jika x > 0:\n        tampilkan x\n");
+    let cetak = function(&module, "cetak");
+    assert!(matches!(&cetak.blocks[0].terminator, Terminator::ConditionalBranch { else_target, .. } if else_target == named(cetak, ".end")));
+}
+
+#[test]
+fn test_loops_test_their_condition_in_a_header() {
+    let module = generate(r#"
+fungsi jumlah(n: int): int:
+    buat total = 0
+    untuk i dari 1 sampai n:
+        total = total + i
+    buat k = n
+    selama k > 0:
+        k = k - 1
+    ulangi:
+        k = k + 1
+    selama k < n
+    kembalikan total
+"#);
+    let jumlah = function(&module, "jumlah");
+    let conditions: Vec<&BasicBlock> = jumlah.blocks.iter().filter(|block| block.name.ends_with(".condition")).collect();
+    assert_eq!(conditions.len(), 3);
+    // Each condition runs the body again or leaves the loop, and the body goes back to the condition
+    for condition in &conditions {
+        let prefix = condition.name.trim_end_matches("condition");
+        assert!(matches!(&condition.terminator, Terminator::ConditionalBranch { then_target, else_target, .. }
+            if *then_target == format!("{}body", prefix) && *else_target == format!("{}end", prefix)));
+    }
+
+    // `untuk` counts up to the end, included, stepping after the body
+    let counting = conditions[0].name.trim_end_matches("condition");
+    let step = block(jumlah, &format!("{}step", counting));
+    assert!(matches!(step.instructions.first(), Some(Instruction::BinaryOp { op: BinaryOp::Add, left: Value::Variable(variable), .. }) if variable == "i"));
+    assert!(branches_to(step, &conditions[0].name));
+    assert!(branches_to(block(jumlah, &format!("{}body", counting)), &step.name));
+    assert!(matches!(conditions[0].instructions.first(), Some(Instruction::BinaryOp { op: BinaryOp::Le, .. })));
+
+    // `selama` tests first; `ulangi` runs its body first
+    let (waiting, repeating) = (conditions[1].name.trim_end_matches("condition"), conditions[2].name.trim_end_matches("condition"));
+    assert!(branches_to(block(jumlah, &format!("{}end", counting)), &format!("{}condition", waiting)));
+    assert!(branches_to(block(jumlah, &format!("{}end", waiting)), &format!("{}body", repeating)));
+}
+
+#[test]
+fn test_break_and_continue_branch_to_the_innermost_loop() {
+    let module = generate(r#"
+fungsi cari(batas: int): int:
+    buat n = 0
+    selama benar:
+        untuk i dari 0 sampai batas:
+            jika i == 3:
+                lanjut
+            jika i == 5:
+                pecah
+            n = n + i
+        pecah
+    kembalikan n
+"#);
+    let cari = function(&module, "cari");
+    let inner = named(cari, ".step").trim_end_matches("step");
+    let outer = cari.blocks.iter()
+        .map(|block| block.name.as_str())
+        .find(|name| name.ends_with(".condition") && !name.starts_with(inner))
+        .expect("Missing outer loop")
+        .trim_end_matches("condition");
+    let jumps: Vec<&str> = cari.blocks.iter()
+        .filter(|block| block.name.ends_with(".then"))
+        .map(|block| match &block.terminator {
+            Terminator::Branch { target } => target.as_str(),
+            other => panic!("Expected a branch, got {:?}", other),
+        })
+        .collect();
+    assert_eq!(jumps, [format!("{}step", inner), format!("{}end", inner)]);
+    // The `pecah` after the inner loop leaves the outer one
+    assert!(branches_to(block(cari, &format!("{}end", inner)), &format!("{}end", outer)));
+    module.verify().expect("Generated blocks should verify");
+}
+
+#[test]
+fn test_every_block_ends_once() {
+    let mut builder = IRBuilder::new();
+    builder.create_function("f".to_string(), vec![], Type::Void);
+    builder.create_block("entry".to_string()).unwrap();
+    builder.set_terminator(Terminator::Branch { target: "end".to_string() }).unwrap();
+    assert!(builder.set_terminator(Terminator::Return { value: None }).is_err());
+
+    // A branch to a block the function does not have, or a return among the instructions, is rejected
+    let mut module = builder.take_module();
+    assert!(module.verify().unwrap_err().contains("end"));
+    module.functions[0].add_block(BasicBlock::new("end".to_string()));
+    module.verify().expect("Blocks should verify");
+    module.functions[0].blocks[1].add_instruction(Instruction::Return { value: None, debug_info: None });
+    assert!(module.verify().is_err());
+}
+
+#[test]
+fn test_returns_match_the_return_type() {
+    let module = generate(r#"
+fungsi mutlak(x: int): int:
+    jika x < 0:
+        kembalikan -x
+    sebaliknya:
+        kembalikan x
+
+fungsi sapa(nama: string):
+    selama benar:
+        tampilkan nama
+        pecah
+"#);
+    // Nothing reaches the end of `mutlak`, or the statements after `pecah`, so no block returns nothing
+    // from a function returning `int`, and none is left after the `pecah`
+    let mutlak = function(&module, "mutlak");
+    assert!(mutlak.blocks.iter().all(|block| !matches!(block.terminator, Terminator::Return { value: None })));
+    assert!(!mutlak.blocks.iter().any(|block| block.name.starts_with("after")));
+    assert!(!function(&module, "sapa").blocks.iter().any(|block| block.name.starts_with("after")));
+
+    let mut builder = IRBuilder::new();
+    builder.create_function("f".to_string(), vec![], Type::Int);
+    builder.create_block("entry".to_string()).unwrap();
+    let mut module = builder.take_module();
+    assert!(module.verify().unwrap_err().contains("does not have type"));
+    module.functions[0].blocks[0].set_terminator(Terminator::Return { value: Some(Value::Constant(Constant::String("satu".to_string()))) });
+    assert!(module.verify().is_err());
+    module.functions[0].blocks[0].set_terminator(Terminator::Return { value: Some(Value::Constant(Constant::Int(1))) });
+    module.verify().expect("Returning an int should verify");
+}
@@ -3,7 +3,7 @@
 #[cfg(test)]
 mod tests {
     use kodeon_compiler::debugger::Debugger;
-    use kodeon_compiler::ir::{IRModule, Function, BasicBlock, Instruction, Type, DebugInfo};
+    use kodeon_compiler::ir::DebugInfo;
 
     #[test]
     fn test_debugger_creation() {
@@ -27,7 +27,7 @@ mod tests {
         // Remove breakpoint
         debugger.remove_breakpoint("test.kodeon", 10);
         let breakpoints = debugger.list_breakpoints();
-        assert!(breakpoints.get("test.kodeon").map_or(true, |bps| bps.is_empty()));
+        assert!(breakpoints.get("test.kodeon").is_none_or(|bps| bps.is_empty()));
     }
 
     #[test]
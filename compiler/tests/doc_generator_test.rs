@@ -0,0 +1,39 @@
+//! Generate the API reference of the standard library
+
+use kodeon_compiler::doc_generator::{DocFormat, DocGenerator};
+use std::path::{Path, PathBuf};
+
+fn stdlib_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap().join("stdlib")
+}
+
+#[test]
+fn test_stdlib_markdown_reference() {
+    let output = tempfile::tempdir().unwrap();
+    let mut generator = DocGenerator::new(DocFormat::Markdown);
+    generator.add_package(&stdlib_root()).unwrap();
+    let written = generator.write(output.path()).unwrap();
+
+    assert!(written.contains(&output.path().join("index.md")));
+    let index = std::fs::read_to_string(output.path().join("index.md")).unwrap();
+    assert!(index.contains("[`core.math`](core.math.md)"));
+
+    let math = std::fs::read_to_string(output.path().join("core.math.md")).unwrap();
+    assert!(math.contains("<a id=\"Matematika.sinus\"></a>"));
+    assert!(math.contains("```kodeon\nfungsi sinus(x)\n```"));
+    assert!(math.contains("Sinus dari `x` dalam radian."));
+    assert!(math.contains("Shorthand for [`Math.sine`](#Math.sine)."));
+}
+
+#[test]
+fn test_stdlib_html_reference() {
+    let output = tempfile::tempdir().unwrap();
+    let mut generator = DocGenerator::new(DocFormat::Html);
+    generator.add_package(&stdlib_root()).unwrap();
+    generator.write(output.path()).unwrap();
+
+    let math = std::fs::read_to_string(output.path().join("core.math.html")).unwrap();
+    assert!(math.starts_with("<!DOCTYPE html>"));
+    assert!(math.contains("<h5 id=\"Math.square_root\"><code>Math.square_root</code></h5>"));
+    assert!(math.contains("<p>Square root of <code>x</code>; throws if <code>x</code> is negative.</p>"));
+}
@@ -1,6 +1,8 @@
 //! Test for enhanced error handling in the KODEON compiler
 
-use kodeon_compiler::{Lexer, Parser, SemanticAnalyzer};
+use kodeon_compiler::ir::{IRError, IRGenerator};
+use kodeon_compiler::parser::ParseError;
+use kodeon_compiler::{DiagnosticRenderer, Language, Lexer, Parser, SemanticAnalyzer, SourceMap};
 
 #[test]
 fn test_enhanced_parse_error() {
@@ -11,10 +13,10 @@ fn test_enhanced_parse_error() {
         y = 10
     "#;
 
-    let mut parser = Parser::new(source).expect("Failed to create parser");
+    let mut parser = Parser::new(source);
 
     // This should produce an enhanced error message
-    let result = parser.parse_program();
+    let result = parser.parse_program().into_result();
 
     assert!(result.is_err());
 
@@ -31,6 +33,35 @@ fn test_enhanced_parse_error() {
     println!("Enhanced error message:\n{}", error_string);
 }
 
+#[test]
+fn test_multiple_parse_errors_in_one_run() {
+    // Test case with a syntax error in three different statements
+    let source = r#"
+fungsi utama() {
+    buat x = 1 +
+    buat = 5
+    tampilkan x
+}
+buat y = 10 20
+tampilkan y
+"#;
+
+    let mut parser = Parser::new(source);
+    let output = parser.parse_program();
+
+    assert_eq!(output.errors.len(), 3);
+    let messages: Vec<String> = output.errors.iter().map(|error| error.to_string()).collect();
+    assert!(messages[0].contains("line 3"));
+    assert!(messages[1].contains("line 4"));
+    assert!(messages[2].contains("line 7"));
+
+    // The statements around the errors are still in the AST
+    match output.ast {
+        kodeon_compiler::ASTNode::Program(statements) => assert_eq!(statements.len(), 3),
+        _ => panic!("Expected a program"),
+    }
+}
+
 #[test]
 fn test_enhanced_semantic_error() {
     // Test case with semantic error (undeclared variable)
@@ -40,8 +71,8 @@ fn test_enhanced_semantic_error() {
     }
     "#;
 
-    let mut parser = Parser::new(source).expect("Failed to create parser");
-    let ast = parser.parse_program().expect("Failed to parse");
+    let mut parser = Parser::new(source);
+    let ast = parser.parse_program().into_result().expect("Failed to parse");
 
     let mut semantic_analyzer = SemanticAnalyzer::new();
     let result = semantic_analyzer.analyze(&ast);
@@ -74,8 +105,8 @@ fn test_type_mismatch_error() {
     }
     "#;
 
-    let mut parser = Parser::new(source).expect("Failed to create parser");
-    let ast = parser.parse_program().expect("Failed to parse");
+    let mut parser = Parser::new(source);
+    let ast = parser.parse_program().into_result().expect("Failed to parse");
 
     let mut semantic_analyzer = SemanticAnalyzer::new();
     let result = semantic_analyzer.analyze(&ast);
@@ -94,3 +125,70 @@ fn test_type_mismatch_error() {
     assert!(error_string.contains("📘"));
     println!("Enhanced error message:\n{}", error_string);
 }
+
+#[test]
+fn test_diagnostic_snippets() {
+    let source = "buat total = 0\nbuat total = 1\n";
+    let mut sources = SourceMap::new();
+    let file = sources.add("contoh.kodeon", source);
+
+    let mut parser = Parser::new(source).with_file(file);
+    let ast = parser.parse_program().into_result().expect("Failed to parse");
+    let error = SemanticAnalyzer::new().analyze(&ast).unwrap_err();
+    let diagnostic = error.to_diagnostic(sources.get(file).unwrap(), Language::English);
+    let renderer = DiagnosticRenderer::new(&sources);
+
+    let rendered = renderer.render(&diagnostic);
+    assert!(rendered.starts_with("error[K0202]: Duplicate declaration of 'total'\n --> contoh.kodeon:2:1\n"));
+    assert!(rendered.contains("1 | buat total = 0\n  | ---- first declared here\n"));
+    assert!(rendered.contains("2 | buat total = 1\n  | ^^^^ declared again here\n"));
+
+    let json: serde_json::Value = serde_json::from_str(&renderer.render_json(&diagnostic)).unwrap();
+    assert_eq!(json["labels"].as_array().unwrap().len(), 2);
+    assert_eq!(json["labels"][0]["line_start"], 2);
+    assert_eq!(json["code"], "K0202");
+}
+
+#[test]
+fn test_localized_diagnostics() {
+    let source = "buat salam = \"halo\ntampilkan salam\n";
+    let mut sources = SourceMap::new();
+    let file = sources.add("salam.kodeon", source);
+
+    let error = ParseError::from(Lexer::new(source).with_file(file).tokenize().unwrap_err());
+    assert_eq!(error.code(), "K0002");
+    assert_eq!(error.position().line, 1);
+
+    let renderer = DiagnosticRenderer::new(&sources);
+    let rendered = renderer.render(&error.to_diagnostic(sources.get(file).unwrap(), Language::Indonesian));
+    assert!(rendered.starts_with("error[K0002]: Teks tidak ditutup\n --> salam.kodeon:1:14\n"), "{}", rendered);
+    assert!(rendered.contains("^ teks dimulai di sini"), "{}", rendered);
+    assert!(rendered.contains("= help: Tambahkan tanda kutip penutup"), "{}", rendered);
+    assert!(rendered.contains("contoh:\n"), "{}", rendered);
+
+    let rendered = renderer.render(&error.to_diagnostic(sources.get(file).unwrap(), Language::English));
+    assert!(rendered.starts_with("error[K0002]: Unterminated string\n"), "{}", rendered);
+}
+
+#[test]
+fn test_code_generation_diagnostics() {
+    let source = "buat x = 1\nbuat alamat = buat_alamat(x)\n";
+    let mut sources = SourceMap::new();
+    let file = sources.add("alamat.kodeon", source);
+
+    let mut parser = Parser::new(source).with_file(file);
+    let ast = parser.parse_program().into_result().expect("Failed to parse");
+    let program = SemanticAnalyzer::new().analyze(&ast).expect("Failed to analyze");
+    let error = IRGenerator::new().generate_ir(&program).unwrap_err();
+    assert_eq!(error.code(), "K0303");
+
+    let renderer = DiagnosticRenderer::new(&sources);
+    let rendered = renderer.render(&error.to_diagnostic(sources.get(file).unwrap(), Language::Indonesian));
+    assert!(rendered.starts_with("error[K0303]: Ekspresi address belum dapat dikompilasi\n --> alamat.kodeon:2:"), "{}", rendered);
+    assert!(rendered.contains("belum didukung oleh pembuatan kode"), "{}", rendered);
+
+    // Failures inside the compiler keep what went wrong in a note under the translated message
+    let diagnostic = IRError::Internal("no current block".to_string()).to_diagnostic(sources.get(file).unwrap(), Language::English);
+    assert_eq!(diagnostic.message, "The compiler failed while generating code for the program");
+    assert_eq!(diagnostic.notes.last().map(String::as_str), Some("no current block"));
+}
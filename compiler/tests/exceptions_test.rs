@@ -0,0 +1,129 @@
+//! Tests for `coba` statements: calls that may throw become invokes of a landing pad, and `akhirnya`
+//! blocks run on every way out of the statement
+
+use kodeon_compiler::ir::{BasicBlock, Instruction, Terminator, Value};
+
+mod common;
+use common::{block, function, generate};
+
+/// Whether `block` prints `text`, as the `akhirnya` blocks below do
+fn prints(block: &BasicBlock, text: &str) -> bool {
+    block.instructions.iter().any(|instruction| matches!(
+        instruction,
+        Instruction::Call { function, arguments, .. } if function == "tampilkan" && matches!(arguments.as_slice(), [Value::Constant(constant)] if format!("{:?}", constant).contains(text))
+    ))
+}
+
+const DIVISION: &str = r#"
+kelas Galat:
+    buat pesan = "galat"
+kelas GalatBagi(Galat):
+    buat pembagi = 0
+
+fungsi bagi(a: int, b: int): int:
+    jika b == 0:
+        lempar GalatBagi()
+    kembalikan a / b
+
+fungsi hitung(x: int): int:
+    coba:
+        buat y = bagi(10, x)
+        kembalikan y
+    tangkap (e: GalatBagi):
+        tampilkan e.pesan
+    tangkap e:
+        lempar e
+    akhirnya:
+        tampilkan "selesai"
+    kembalikan 0
+
+fungsi ulangi(n: int):
+    selama benar:
+        coba:
+            jika bagi(10, n) > 1:
+                pecah
+        akhirnya:
+            tampilkan "keluar"
+"#;
+
+#[test]
+fn test_calls_in_try_unwind_to_its_landing_pad() {
+    let module = generate(DIVISION);
+
+    // A throw outside of any `coba` statement unwinds out of the function
+    let bagi = function(&module, "bagi");
+    assert!(bagi.blocks.iter().any(|block| matches!(block.terminator, Terminator::Unwind { unwind: None, .. })));
+
+    let hitung = function(&module, "hitung");
+    let Terminator::Invoke { call, unwind, .. } = &hitung.blocks[0].terminator else {
+        panic!("Expected an invoke, got {:?}", hitung.blocks[0].terminator);
+    };
+    assert!(matches!(call.as_ref(), Instruction::Call { function, .. } if function == "bagi"));
+    assert_eq!(unwind, "try0.landing");
+    assert!(matches!(block(hitung, "try0.landing").instructions.first(), Some(Instruction::LandingPad { .. })));
+
+    // Clauses are tested in order, by class
+    let test = &block(hitung, "try0.landing").instructions[1];
+    assert!(matches!(test, Instruction::ExceptionIs { class, .. } if class == "GalatBagi"));
+    assert!(matches!(block(hitung, "try0.catch1").instructions.first(), Some(Instruction::Catch { .. })));
+    // Errors the clauses throw unwind to a pad that runs the finally block, then keep unwinding
+    assert!(matches!(&block(hitung, "try0.catch1").terminator, Terminator::Unwind { unwind: Some(pad), .. } if pad == "try0.cleanup"));
+    let cleanup = block(hitung, "try0.cleanup");
+    assert!(prints(cleanup, "selesai"));
+    assert!(matches!(cleanup.terminator, Terminator::Resume { unwind: None, .. }));
+}
+
+#[test]
+fn test_finally_runs_before_leaving_the_try() {
+    let module = generate(DIVISION);
+
+    // `kembalikan` in the try block runs the finally block first
+    let hitung = function(&module, "hitung");
+    let returning = hitung.blocks.iter()
+        .find(|block| matches!(block.terminator, Terminator::Return { value: Some(Value::Variable(_)) })
+            && block.instructions.iter().any(|instruction| matches!(instruction, Instruction::Store { value: Value::Variable(name), .. } if name == "y")))
+        .expect("Missing return");
+    assert!(prints(returning, "selesai"));
+    // As does the end of each clause; the try block returns and the last clause throws, so only the
+    // first clause reaches the end of the statement
+    let ending: Vec<&BasicBlock> = hitung.blocks.iter()
+        .filter(|block| matches!(&block.terminator, Terminator::Branch { target } if target == "try0.end"))
+        .collect();
+    assert_eq!(ending.len(), 1);
+    assert!(ending.iter().all(|block| prints(block, "selesai")));
+
+    // So does `pecah`, before leaving the loop, besides the end of the try block and the landing pad
+    let ulangi = function(&module, "ulangi");
+    let copies = ulangi.blocks.iter().filter(|block| prints(block, "keluar")).map(|block| {
+        block.instructions.iter().filter(|instruction| matches!(instruction, Instruction::Call { function, .. } if function == "tampilkan")).count()
+    });
+    assert_eq!(copies.sum::<usize>(), 3);
+}
+
+#[test]
+fn test_return_value_is_kept_from_the_finally_block() {
+    let module = generate(r#"
+fungsi nilai(): int:
+    buat x = 1
+    coba:
+        kembalikan x
+    akhirnya:
+        x = 2
+"#);
+    let nilai = function(&module, "nilai");
+    let returning = nilai.blocks.iter()
+        .find(|block| matches!(block.terminator, Terminator::Return { value: Some(_) }))
+        .expect("Missing return");
+    let Terminator::Return { value: Some(Value::Variable(copy)) } = &returning.terminator else {
+        panic!("Expected a variable returned, got {:?}", returning.terminator);
+    };
+    // `x` is copied before the finally block changes it, and the copy is returned
+    assert_ne!(copy, "x");
+    let stores: Vec<(&str, &Value)> = returning.instructions.iter()
+        .filter_map(|instruction| match instruction {
+            Instruction::Store { variable, value, .. } => Some((variable.as_str(), value)),
+            _ => None,
+        })
+        .collect();
+    assert!(matches!(stores.as_slice(), [.., (first, Value::Variable(read)), ("x", _)] if first == copy && read == "x"), "{:?}", stores);
+}
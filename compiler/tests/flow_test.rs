@@ -0,0 +1,112 @@
+//! Tests for the flow analysis: variables read before they are assigned, and the warnings for unused
+//! variables, unused parameters and unreachable code
+
+use kodeon_compiler::semantic_analyzer::SemanticError;
+use kodeon_compiler::{DiagnosticRenderer, Language, Parser, SemanticAnalyzer, SemanticWarning, SourceMap};
+
+fn analyze(source: &str) -> (Result<(), SemanticError>, Vec<SemanticWarning>) {
+    let mut parser = Parser::new(source);
+    let ast = parser.parse_program().into_result().expect("Failed to parse program");
+    let mut analyzer = SemanticAnalyzer::new();
+    let result = analyzer.analyze(&ast).map(|_| ());
+    (result, analyzer.warnings().to_vec())
+}
+
+#[test]
+fn test_reads_on_a_path_without_assignment_are_rejected() {
+    let source = r#"
+fungsi muat(ada_data: bool): int:
+    var total: int
+    jika ada_data:
+        total = 1
+    kembalikan total
+"#;
+    let error = analyze(source).0.unwrap_err();
+    assert_eq!(error.code(), "K0205");
+    assert!(matches!(error, SemanticError::UninitializedVariable { ref name, ref position, .. } if name == "total" && position.line == 6), "{}", error);
+
+    // Every clause of `coba` assigns, and the loop body only runs after the assignment
+    let source = r#"
+fungsi muat(): int:
+    var total: int
+    coba:
+        total = 1
+    tangkap e:
+        total = 0
+    untuk i dari 0 sampai 3 lakukan:
+        total = total + i
+    kembalikan total
+"#;
+    let (result, warnings) = analyze(source);
+    assert!(result.is_ok(), "{:?}", result);
+    assert!(warnings.is_empty(), "{:?}", warnings);
+}
+
+#[test]
+fn test_warnings_render_with_their_spans() {
+    let source = "fungsi sapa(nama, waktu):\n    buat salam = \"Halo\"\n    kembalikan nama\n    tampilkan nama\n\ntampilkan sapa(\"Andi\", \"pagi\")\n";
+    let mut sources = SourceMap::new();
+    let file = sources.add("sapa.kodeon", source);
+
+    let mut parser = Parser::new(source).with_file(file);
+    let ast = parser.parse_program().into_result().expect("Failed to parse program");
+    let mut analyzer = SemanticAnalyzer::new();
+    analyzer.analyze(&ast).expect("Failed to analyze program");
+
+    let codes: Vec<&str> = analyzer.warnings().iter().map(|warning| warning.code()).collect();
+    assert_eq!(codes, ["K0402", "K0401", "K0403"]);
+
+    let renderer = DiagnosticRenderer::new(&sources);
+    let render = |index: usize| renderer.render(&analyzer.warnings()[index].to_diagnostic(sources.get(file).unwrap(), Language::English));
+
+    let rendered = render(0);
+    assert!(rendered.starts_with("warning[K0402]: Parameter 'waktu' is never read\n --> sapa.kodeon:1:19\n"), "{}", rendered);
+    assert!(rendered.contains("^^^^^"), "{}", rendered);
+
+    let rendered = render(1);
+    assert!(rendered.starts_with("warning[K0401]: Variable 'salam' is never read\n --> sapa.kodeon:2:5\n"), "{}", rendered);
+    assert!(rendered.contains("^^^^^^^^^^^^^^^^^^^ declared here"), "{}", rendered);
+
+    let rendered = render(2);
+    assert!(rendered.starts_with("warning[K0403]: Unreachable code\n --> sapa.kodeon:4:5\n"), "{}", rendered);
+    assert!(rendered.contains("^^^^^^^^^^^^^^ no path reaches this code"), "{}", rendered);
+}
+
+#[test]
+fn test_functions_returning_a_value_return_on_every_path() {
+    let source = r#"
+fungsi tanda(x: int): int:
+    jika x < 0:
+        kembalikan -1
+    sebaliknya jika x > 0:
+        kembalikan 1
+"#;
+    let error = analyze(source).0.unwrap_err();
+    assert_eq!(error.code(), "K0214");
+    assert!(matches!(error, SemanticError::MissingReturn { ref function, ref returns, ref position, .. }
+        if function == "'tanda'" && returns == "int" && position.line == 2), "{}", error);
+    // Without a declared type, a function returning values is checked too, and so is a lambda
+    assert_eq!(analyze("fungsi cari(a):\n    jika a:\n        kembalikan 1\n").0.unwrap_err().code(), "K0214");
+    let error = analyze("buat f = fungsi(a):\n    jika a:\n        kembalikan 1\ntampilkan f(benar)\n").0.unwrap_err();
+    assert!(matches!(error, SemanticError::MissingReturn { ref function, .. } if function == "a lambda"), "{}", error);
+
+    // Every path returns or throws, or loops until it returns
+    let source = r#"
+fungsi tanda(x: int): int:
+    jika x < 0:
+        kembalikan -1
+    lempar "nol"
+
+fungsi tunggu(): int:
+    buat n = 0
+    selama benar:
+        n = n + 1
+        jika n > 3:
+            kembalikan n
+
+fungsi cetak(x: int):
+    jika x > 0:
+        tampilkan x
+"#;
+    assert!(analyze(source).0.is_ok(), "{:?}", analyze(source).0);
+}
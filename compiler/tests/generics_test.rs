@@ -0,0 +1,96 @@
+//! Tests for the specialization of generic functions and classes in IR
+
+use kodeon_compiler::ir::{Instruction, Type};
+use kodeon_compiler::types;
+
+mod common;
+use common::{function, generate};
+
+const FIRST: &str = r#"
+fungsi pertama<T>(daftar: T[]): T:
+    kembalikan daftar[0]
+
+buat angka = pertama([1, 2])
+buat kata = pertama(["a", "b"])
+buat lagi = pertama([3])
+"#;
+
+#[test]
+fn test_generic_functions_are_generated_per_type_arguments() {
+    let module = generate(FIRST);
+
+    // One function for each set of type arguments, and none for the generic definition
+    let names: Vec<&str> = module.functions.iter().map(|f| f.name.as_str()).filter(|name| name.starts_with("pertama")).collect();
+    assert_eq!(names, ["pertama<int>", "pertama<string>"]);
+    let int = function(&module, "pertama<int>");
+    assert_eq!(int.return_type, Type::Int);
+    assert_eq!(int.parameters[0].param_type, Type::from(&types::Type::List(Box::new(types::Type::Int))));
+    assert_eq!(function(&module, "pertama<string>").return_type, Type::String);
+
+    let calls: Vec<&str> = function(&module, "main").blocks[0].instructions.iter()
+        .filter_map(|instruction| match instruction {
+            Instruction::Call { function, .. } if function.starts_with("pertama") => Some(function.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(calls, ["pertama<int>", "pertama<string>", "pertama<int>"]);
+}
+
+const BOXES: &str = r#"
+kelas Kotak<T>:
+    var isi: T
+    fungsi baru(isi: T):
+        ini.isi = isi
+    fungsi ambil(): T:
+        kembalikan isi
+
+buat angka = Kotak(5)
+buat kata: Kotak<teks> = Kotak("a")
+tampilkan(angka.ambil() + 1)
+"#;
+
+#[test]
+fn test_generic_classes_get_a_layout_per_type_arguments() {
+    let module = generate(BOXES);
+
+    let names: Vec<&str> = module.classes.iter().map(|class| class.name.as_str()).collect();
+    assert_eq!(names, ["Kotak<int>", "Kotak<string>"]);
+    assert_eq!(module.classes[0].fields, [("isi".to_string(), Type::Int)]);
+    assert_eq!(module.classes[1].fields, [("isi".to_string(), Type::String)]);
+    assert_eq!(function(&module, "Kotak<string>.ambil").return_type, Type::String);
+    let baru = function(&module, "Kotak<int>.baru");
+    assert_eq!(baru.parameters[0].param_type, Type::Object { name: "Kotak<int>".to_string() });
+    assert_eq!(baru.parameters[1].param_type, Type::Int);
+
+    // Instances are built and dispatched through the layout of their specialization
+    let main = &function(&module, "main").blocks[0].instructions;
+    assert!(main.iter().any(|instruction| matches!(instruction, Instruction::NewObject { class, .. } if class == "Kotak<int>")));
+    assert!(main.iter().any(|instruction| matches!(instruction, Instruction::Call { function, .. } if function == "Kotak<string>.baru")));
+    assert!(main.iter().any(|instruction| matches!(instruction,
+        Instruction::VirtualCall { class, slot: 0, .. } if class == "Kotak<int>")));
+}
+
+#[test]
+fn test_bounded_type_parameters_dispatch_on_their_arguments() {
+    let module = generate(r#"
+antarmuka Bernilai:
+    fungsi nilai(): int
+
+kelas Koin implementasi Bernilai:
+    fungsi nilai(): int:
+        kembalikan 5
+
+fungsi terbesar<T: Bernilai>(a: T, b: T): T:
+    jika a.nilai() > b.nilai():
+        kembalikan a
+    kembalikan b
+
+buat koin = terbesar(Koin(), Koin())
+"#);
+
+    // In the specialization for `Koin`, `a` is a `Koin`, so `nilai` is called through its virtual table
+    let terbesar = function(&module, "terbesar<Koin>");
+    assert_eq!(terbesar.return_type, Type::Object { name: "Koin".to_string() });
+    assert!(terbesar.blocks[0].instructions.iter().any(|instruction| matches!(instruction,
+        Instruction::VirtualCall { class, slot: 0, .. } if class == "Koin")));
+}
@@ -92,8 +92,8 @@ fn test_hello_world_lexing() {
 fn test_simple_program_parsing() {
     let source = "buat x = 42\nbuat y = x + 1";
 
-    let mut parser = Parser::new(source).unwrap();
-    let ast = parser.parse_program().unwrap();
+    let mut parser = Parser::new(source);
+    let ast = parser.parse_program().into_result().unwrap();
 
     // Should be a Program node containing two declarations
     match ast {
@@ -108,8 +108,8 @@ fn test_simple_program_parsing() {
 fn test_if_statement_parsing() {
     let source = "jika x > 0 maka:\n    tampilkan \"positif\"";
 
-    let mut parser = Parser::new(source).unwrap();
-    let ast = parser.parse_program().unwrap();
+    let mut parser = Parser::new(source);
+    let ast = parser.parse_program().into_result().unwrap();
 
     // Should be a Program node containing an if statement
     match ast {
@@ -124,8 +124,8 @@ fn test_if_statement_parsing() {
 fn test_while_loop_parsing() {
     let source = "selama x < 10 maka:\n    x = x + 1";
 
-    let mut parser = Parser::new(source).unwrap();
-    let ast = parser.parse_program().unwrap();
+    let mut parser = Parser::new(source);
+    let ast = parser.parse_program().into_result().unwrap();
 
     // Should be a Program node containing a while loop
     match ast {
@@ -140,8 +140,8 @@ fn test_while_loop_parsing() {
 fn test_for_loop_parsing() {
     let source = "untuk i dari 0 sampai 10 lakukan:\n    tampilkan i";
 
-    let mut parser = Parser::new(source).unwrap();
-    let ast = parser.parse_program().unwrap();
+    let mut parser = Parser::new(source);
+    let ast = parser.parse_program().into_result().unwrap();
 
     // Should be a Program node containing a for loop
     match ast {
@@ -156,8 +156,8 @@ fn test_for_loop_parsing() {
 fn test_function_definition_parsing() {
     let source = "fungsi tambah(a, b):\n    kembalikan a + b";
 
-    let mut parser = Parser::new(source).unwrap();
-    let ast = parser.parse_program().unwrap();
+    let mut parser = Parser::new(source);
+    let ast = parser.parse_program().into_result().unwrap();
 
     // Should be a Program node containing a function definition
     match ast {
@@ -172,8 +172,8 @@ fn test_function_definition_parsing() {
 fn test_class_definition_parsing() {
     let source = "kelas Mobil:\n    buat merek\n    buat model";
 
-    let mut parser = Parser::new(source).unwrap();
-    let ast = parser.parse_program().unwrap();
+    let mut parser = Parser::new(source);
+    let ast = parser.parse_program().into_result().unwrap();
 
     // Should be a Program node containing a class definition
     match ast {
@@ -198,7 +198,7 @@ fn test_comments_lexing() {
         Token::Buat,
         Token::Identifier("x".to_string()),
         Token::Assign,
-        Token::Number(42.0),
+        Token::Integer(42),
         Token::BlockComment(" Block comment ".to_string()),
         Token::Eof,
     ];
@@ -213,8 +213,8 @@ fn test_comments_lexing() {
 fn test_unary_operators_parsing() {
     let source = "buat x = -5\nbuat y = tidak benar\n++x\n--y";
 
-    let mut parser = Parser::new(source).unwrap();
-    let ast = parser.parse_program().unwrap();
+    let mut parser = Parser::new(source);
+    let ast = parser.parse_program().into_result().unwrap();
 
     // Should be a Program node containing four statements
     match ast {
@@ -29,16 +29,16 @@ fn test_simple_llvm_generation() {
     }
 
     // Parse
-    let mut parser = Parser::new(source).unwrap();
-    let ast = parser.parse_program().unwrap();
+    let mut parser = Parser::new(source);
+    let ast = parser.parse_program().into_result().unwrap();
 
     // Semantic analysis
     let mut semantic_analyzer = SemanticAnalyzer::new();
-    semantic_analyzer.analyze(&ast).unwrap();
+    let program = semantic_analyzer.analyze(&ast).unwrap();
 
     // Generate IR
     let mut ir_generator = IRGenerator::new();
-    let ir_module = ir_generator.generate_ir(&ast).unwrap();
+    let ir_module = ir_generator.generate_ir(&program).unwrap();
 
     // Generate LLVM IR
     let context = Context::create();
@@ -76,16 +76,16 @@ fn test_function_llvm_generation() {
     }
 
     // Parse
-    let mut parser = Parser::new(source).unwrap();
-    let ast = parser.parse_program().unwrap();
+    let mut parser = Parser::new(source);
+    let ast = parser.parse_program().into_result().unwrap();
 
     // Semantic analysis
     let mut semantic_analyzer = SemanticAnalyzer::new();
-    semantic_analyzer.analyze(&ast).unwrap();
+    let program = semantic_analyzer.analyze(&ast).unwrap();
 
     // Generate IR
     let mut ir_generator = IRGenerator::new();
-    let ir_module = ir_generator.generate_ir(&ast).unwrap();
+    let ir_module = ir_generator.generate_ir(&program).unwrap();
 
     // Generate LLVM IR
     let context = Context::create();
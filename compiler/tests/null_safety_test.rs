@@ -0,0 +1,84 @@
+//! Tests for optionals: checks against `nol` read the flag of the optional, and `?:`, `?.` and `?` match
+//! on it, running the code that needs the value only when there is one
+
+use kodeon_compiler::ir::{Constant, Function, Instruction, MatchCase, Pattern, Value};
+
+mod common;
+use common::{function, generate, instructions};
+
+/// Cases of the first match in `function`
+fn match_cases(function: &Function) -> &[MatchCase] {
+    instructions(function)
+        .find_map(|instruction| match instruction {
+            Instruction::PatternMatch { cases, .. } => Some(cases.as_slice()),
+            _ => None,
+        })
+        .expect("Expected a match")
+}
+
+const USERS: &str = r#"
+kelas Pengguna:
+    buat nama = "anonim"
+
+fungsi sapa(p: Pengguna?): string:
+    jika p == nol:
+        kembalikan "-"
+    kembalikan p.nama
+
+fungsi nama_atau(p: Pengguna?): string:
+    kembalikan p?.nama ?: "-"
+
+fungsi naik(x: int?): int?:
+    kembalikan x? + 1
+"#;
+
+#[test]
+fn test_checks_against_nol_read_the_flag() {
+    let module = generate(USERS);
+    let sapa = function(&module, "sapa");
+    assert!(instructions(sapa).any(|instruction| matches!(instruction, Instruction::NullCheck { value: Value::Variable(name), .. } if name == "p")));
+    // After the check, `p` is read as the instance it holds
+    assert!(instructions(sapa).any(|instruction| matches!(instruction, Instruction::Unwrap { value: Value::Variable(name), .. } if name == "p")));
+}
+
+#[test]
+fn test_elvis_and_safe_navigation_match_on_nol() {
+    let module = generate(USERS);
+    let nama_atau = function(&module, "nama_atau");
+    let cases = match_cases(nama_atau);
+    assert!(matches!(cases[0].pattern, Pattern::Constant(Constant::Null)));
+    assert!(matches!(cases[1].pattern, Pattern::Wildcard));
+    // The field is only read from an instance that is there
+    assert!(!cases[0].body.iter().any(|instruction| matches!(instruction, Instruction::GetField { .. })));
+    assert!(cases[1].body.iter().any(|instruction| matches!(instruction, Instruction::GetField { .. })));
+}
+
+#[test]
+fn test_question_mark_returns_early() {
+    let module = generate(USERS);
+    let cases = match_cases(function(&module, "naik"));
+    assert!(matches!(cases[0].pattern, Pattern::Constant(Constant::Null)));
+    assert!(matches!(cases[0].body.last(), Some(Instruction::Return { value: Some(Value::Constant(Constant::Null)), .. })));
+    assert!(matches!(cases[1].body.first(), Some(Instruction::Unwrap { .. })));
+
+    let source = r#"
+fungsi baca(teks: string): Hasil<int, string>:
+    jika teks == "":
+        kembalikan Gagal("kosong")
+    kembalikan Sukses(1)
+
+fungsi jumlah(a: string, b: string): Hasil<int, string>:
+    kembalikan Sukses(baca(a)? + baca(b)?)
+"#;
+    let module = generate(source);
+    let cases = match_cases(function(&module, "jumlah"));
+    // A `Gagal` returns a new `Gagal` holding its error, and a `Sukses` gives its value
+    let Pattern::Variant { enumeration, index: 1, fields } = &cases[0].pattern else {
+        panic!("Expected a 'Gagal' case, got {:?}", cases[0].pattern);
+    };
+    assert_eq!(enumeration, "Hasil<int, string>");
+    assert!(matches!(fields.as_slice(), [Pattern::Binding(_)]));
+    assert!(cases[0].body.iter().any(|instruction| matches!(instruction, Instruction::NewVariant { variant: 1, .. })));
+    assert!(matches!(cases[0].body.last(), Some(Instruction::Return { .. })));
+    assert!(matches!(cases[1].pattern, Pattern::Variant { index: 0, .. }));
+}
@@ -1,27 +1,24 @@
 //! Test for optimization passes
 
 use kodeon_compiler::optimizer::{Optimizer, ConstantFolding, DeadCodeElimination};
-use kodeon_compiler::ir::{IRModule, Function, BasicBlock, Instruction, Value, Constant, BinaryOperator};
+use kodeon_compiler::ir::{Instruction, Value, Constant, BinaryOp, UnaryOp};
 
 #[test]
 fn test_optimizer_creation() {
-    let optimizer = Optimizer::new();
     // The optimizer should be created successfully
-    assert!(true);
+    let _optimizer = Optimizer::new();
 }
 
 #[test]
 fn test_constant_folding_creation() {
-    let folding = ConstantFolding;
     // The constant folding pass should be created successfully
-    assert!(true);
+    let _folding = ConstantFolding;
 }
 
 #[test]
 fn test_dead_code_elimination_creation() {
-    let dce = DeadCodeElimination;
     // The dead code elimination pass should be created successfully
-    assert!(true);
+    let _dce = DeadCodeElimination;
 }
 
 #[test]
@@ -30,7 +27,7 @@ fn test_constant_folding_int_addition() {
 
     let instruction = Instruction::BinaryOp {
         result: "result".to_string(),
-        op: BinaryOperator::Add,
+        op: BinaryOp::Add,
         left: Value::Constant(Constant::Int(2)),
         right: Value::Constant(Constant::Int(3)),
         debug_info: None,
@@ -52,7 +49,7 @@ fn test_constant_folding_float_multiplication() {
 
     let instruction = Instruction::BinaryOp {
         result: "result".to_string(),
-        op: BinaryOperator::Mul,
+        op: BinaryOp::Mul,
         left: Value::Constant(Constant::Float(2.5)),
         right: Value::Constant(Constant::Float(4.0)),
         debug_info: None,
@@ -74,7 +71,7 @@ fn test_constant_folding_boolean_and() {
 
     let instruction = Instruction::BinaryOp {
         result: "result".to_string(),
-        op: BinaryOperator::And,
+        op: BinaryOp::And,
         left: Value::Constant(Constant::Bool(true)),
         right: Value::Constant(Constant::Bool(false)),
         debug_info: None,
@@ -84,7 +81,7 @@ fn test_constant_folding_boolean_and() {
     assert!(folded.is_some());
 
     if let Some(Value::Constant(Constant::Bool(val))) = folded {
-        assert_eq!(val, false);
+        assert!(!val);
     } else {
         panic!("Expected boolean constant");
     }
@@ -96,7 +93,7 @@ fn test_constant_folding_unary_not() {
 
     let instruction = Instruction::UnaryOp {
         result: "result".to_string(),
-        op: kodeon_compiler::ir::UnaryOperator::Not,
+        op: UnaryOp::Not,
         operand: Value::Constant(Constant::Bool(true)),
         debug_info: None,
     };
@@ -105,7 +102,7 @@ fn test_constant_folding_unary_not() {
     assert!(folded.is_some());
 
     if let Some(Value::Constant(Constant::Bool(val))) = folded {
-        assert_eq!(val, false);
+        assert!(!val);
     } else {
         panic!("Expected boolean constant");
     }
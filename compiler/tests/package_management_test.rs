@@ -3,7 +3,6 @@
 use kodeon_compiler::{Lexer, Parser, SemanticAnalyzer, IRGenerator};
 use kodeon_compiler::module_resolver::ModuleResolver;
 use std::fs;
-use std::path::PathBuf;
 use tempfile::TempDir;
 
 #[test]
@@ -56,19 +55,20 @@ fungsi utama():
     }
 
     // Parse
-    let mut parser = Parser::new(&contents).unwrap();
-    let ast = parser.parse_program().unwrap();
+    let mut parser = Parser::new(&contents);
+    let ast = parser.parse_program().into_result().unwrap();
 
     // Semantic analysis
     let mut semantic_analyzer = SemanticAnalyzer::new();
-    assert!(semantic_analyzer.analyze(&ast).is_ok());
+    let program = semantic_analyzer.analyze(&ast);
+    assert!(program.is_ok());
 
     // Create module resolver with project root
     let module_resolver = ModuleResolver::with_project_root(project_root);
 
     // Generate IR
     let mut ir_generator = IRGenerator::with_module_resolver(module_resolver);
-    let result = ir_generator.generate_ir(&ast);
+    let result = ir_generator.generate_ir(&program.unwrap());
 
     // The IR generation should succeed
     assert!(result.is_ok());
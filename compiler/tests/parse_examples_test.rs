@@ -0,0 +1,75 @@
+//! Parse every KODEON source file shipped in the repository
+
+use kodeon_compiler::parser::Parser;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Example files that are not expected to parse, relative to the repository root
+const EXCLUDED: &[&str] = &[
+    // Intentionally contain errors to demonstrate error messages
+    "examples/error_handling/error_demo.kodeon",
+    "examples/error_handling/error_demo_en.kodeon",
+    // C-style typed declarations (`angka x = 10;`)
+    "examples/debug_demo.kodeon",
+    "examples/debugging/variable_inspection.kodeon",
+    // Showcases of syntax borrowed from other languages
+    "examples/multi_language_features.kodeon",
+    "examples/multi_language_features_extended.kodeon",
+    "examples/complete_multi_language_demo.kodeon",
+    "examples/extended_features/extended_features_demo.kodeon",
+];
+
+fn repository_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap().to_path_buf()
+}
+
+fn kodeon_files(directory: &str) -> Vec<PathBuf> {
+    let root = repository_root();
+    WalkDir::new(root.join(directory))
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.into_path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "kodeon"))
+        .filter(|path| {
+            let relative = path.strip_prefix(&root).unwrap().to_string_lossy().replace('\\', "/");
+            !EXCLUDED.contains(&relative.as_str())
+        })
+        .collect()
+}
+
+fn assert_all_parse(directory: &str) {
+    let files = kodeon_files(directory);
+    assert!(!files.is_empty(), "No .kodeon files found in {}", directory);
+
+    let failures: Vec<String> = files
+        .iter()
+        .filter_map(|path| {
+            let source = std::fs::read_to_string(path).unwrap();
+            Parser::new(&source)
+                .parse_program()
+                .into_result()
+                .err()
+                .map(|error| format!("{}\n{}", path.display(), error))
+        })
+        .collect();
+
+    assert!(failures.is_empty(), "Failed to parse:\n{}", failures.join("\n"));
+}
+
+#[test]
+fn test_stdlib_parses() {
+    assert_all_parse("stdlib");
+}
+
+#[test]
+fn test_examples_parse() {
+    assert_all_parse("examples");
+}
+
+#[test]
+fn test_excluded_files_exist() {
+    let root = repository_root();
+    for file in EXCLUDED {
+        assert!(root.join(file).exists(), "Excluded file {} no longer exists", file);
+    }
+}
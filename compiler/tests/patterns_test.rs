@@ -0,0 +1,177 @@
+//! Tests for enums, and the decision trees `ketika` statements are lowered to
+
+use kodeon_compiler::ir::{
+    decision_tree, Constant, Decision, EnumLayout, Function, Instruction, MatchArm, Step, Terminator, Test, Type,
+};
+
+mod common;
+use common::{function, generate};
+
+fn match_cases(function: &Function) -> &[MatchArm] {
+    function.blocks.iter()
+        .find_map(|block| match &block.terminator {
+            Terminator::Match { arms, .. } => Some(arms.as_slice()),
+            _ => None,
+        })
+        .expect("Missing pattern match")
+}
+
+const SHAPES: &str = r#"
+tipe Bentuk:
+    Lingkaran(jari: float)
+    Persegi(float, float)
+    Kosong
+
+tipe Pilihan<T>:
+    Ada(T)
+    Tidak
+
+fungsi luas(bentuk: Bentuk): float:
+    ketika bentuk:
+        kasus Lingkaran(r) jika r > 0.0:
+            kembalikan 3.14 * r * r
+        kasus Persegi(l, t):
+            kembalikan l * t
+        kasus _:
+            kembalikan 0.0
+
+buat bentuk = Persegi(2.0, 3.0)
+buat angka: Pilihan<int> = Ada(1)
+buat kosong: Pilihan<teks> = Tidak
+"#;
+
+#[test]
+fn test_enums_are_laid_out_and_built() {
+    let module = generate(SHAPES);
+
+    // A generic enum is laid out once for each set of type arguments it is used with
+    let names: Vec<&str> = module.enums.iter().map(|layout| layout.name.as_str()).collect();
+    assert_eq!(names, ["Bentuk", "Pilihan<int>", "Pilihan<string>"]);
+    assert_eq!(module.enums[0], EnumLayout {
+        name: "Bentuk".to_string(),
+        variants: vec![
+            ("Lingkaran".to_string(), vec![Type::Float]),
+            ("Persegi".to_string(), vec![Type::Float, Type::Float]),
+            ("Kosong".to_string(), vec![]),
+        ],
+    });
+    assert_eq!(module.enums[1].variants[0].1, [Type::Int]);
+    assert_eq!(module.enums[1].variant_index("Tidak"), Some(1));
+
+    let variants: Vec<(&str, usize, usize)> = function(&module, "main").blocks[0].instructions.iter()
+        .filter_map(|instruction| match instruction {
+            Instruction::NewVariant { enumeration, variant, fields, .. } => Some((enumeration.as_str(), *variant, fields.len())),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(variants, [("Bentuk", 1, 2), ("Pilihan<int>", 0, 1), ("Pilihan<string>", 1, 0)]);
+}
+
+#[test]
+fn test_enum_matches_switch_on_the_variant() {
+    let module = generate(SHAPES);
+    let luas = function(&module, "luas");
+    let cases = match_cases(luas);
+
+    // Each case runs in a block of its own, which its return ends
+    assert_eq!(cases.len(), 3);
+    assert!(cases[0].guard.is_some());
+    let body = luas.blocks.iter().find(|block| block.name == cases[1].target).expect("Missing case block");
+    assert!(matches!(body.terminator, Terminator::Return { value: Some(_) }));
+
+    let Decision::Switch { access, enumeration, branches, default } = decision_tree(cases) else {
+        panic!("expected a switch on the variant");
+    };
+    assert!(access.is_empty());
+    assert_eq!(enumeration, "Bentuk");
+    let variants: Vec<usize> = branches.iter().map(|(variant, _)| *variant).collect();
+    assert_eq!(variants, [0, 1]);
+
+    // A circle runs the first case if its guard holds, and the wildcard case otherwise
+    let Decision::Run { case: 0, bindings, otherwise: Some(otherwise) } = &branches[0].1 else {
+        panic!("expected the guarded case, found {:?}", branches[0].1);
+    };
+    let field = Step::Field { enumeration: "Bentuk".to_string(), variant: 0, index: 0 };
+    assert_eq!(bindings[0].1, [field]);
+    assert!(matches!(**otherwise, Decision::Run { case: 2, otherwise: None, .. }));
+    assert!(matches!(&branches[1].1, Decision::Run { case: 1, bindings, .. } if bindings.len() == 2));
+    assert!(matches!(*default, Decision::Run { case: 2, .. }));
+}
+
+#[test]
+fn test_tuple_and_literal_matches_test_each_part_once() {
+    let module = generate(r#"
+buat titik = (0, 5)
+ketika titik:
+    kasus (0, 0):
+        tampilkan "asal"
+    kasus (0, y):
+        tampilkan y
+    kasus (1...9, _):
+        tampilkan "dekat"
+    sebaliknya:
+        tampilkan "jauh"
+"#);
+    let main = function(&module, "main");
+    assert!(main.blocks[0].instructions.iter().any(|instruction| matches!(instruction, Instruction::MakeTuple { elements, .. } if elements.len() == 2)));
+
+    let Decision::Test { access, test: Test::Equal(Constant::Int(0)), then, otherwise } = decision_tree(match_cases(main)) else {
+        panic!("expected a test of the first element");
+    };
+    assert_eq!(access, [Step::Element(0)]);
+    // With the first element 0, only the second is left to test
+    let Decision::Test { access, test: Test::Equal(Constant::Int(0)), then: origin, otherwise: axis } = *then else {
+        panic!("expected a test of the second element");
+    };
+    assert_eq!(access, [Step::Element(1)]);
+    assert!(matches!(*origin, Decision::Run { case: 0, .. }));
+    assert!(matches!(*axis, Decision::Run { case: 1, ref bindings, .. } if bindings[0].1 == [Step::Element(1)]));
+    // Otherwise the range is tested, and failing it leaves the default
+    let Decision::Test { test: Test::Between(1, 9), then, otherwise, .. } = *otherwise else {
+        panic!("expected a range test");
+    };
+    assert!(matches!(*then, Decision::Run { case: 2, .. }));
+    assert!(matches!(*otherwise, Decision::Fail));
+}
+
+#[test]
+fn test_cases_may_branch_and_loop() {
+    let module = generate(r#"
+tipe Perintah:
+    Hitung(int)
+    Diam
+
+fungsi jalankan(perintah: Perintah): int:
+    buat total = 0
+    ketika perintah:
+        kasus Hitung(n):
+            buat i = 0
+            selama i < n:
+                jika i == 5:
+                    pecah
+                total = total + i
+                i = i + 1
+        kasus Diam:
+            kembalikan 0
+    kembalikan total
+"#);
+    module.verify().expect("Generated blocks should verify");
+    let jalankan = function(&module, "jalankan");
+    let cases = match_cases(jalankan);
+    let block = |name: &str| jalankan.blocks.iter().find(|block| block.name == name).expect("Missing block");
+
+    // The loop in the first case starts in its block, and leaves the match at its end
+    let Terminator::Branch { target: condition } = &block(&cases[0].target).terminator else {
+        panic!("Expected a branch to the loop, got {:?}", block(&cases[0].target).terminator);
+    };
+    assert!(condition.ends_with(".condition"));
+    let end = condition.replace(".condition", ".end");
+    let Terminator::Branch { target: after } = &block(&end).terminator else {
+        panic!("Expected a branch out of the match, got {:?}", block(&end).terminator);
+    };
+    assert!(after.starts_with("match") && after.ends_with(".end"));
+    // `pecah` inside the `jika` leaves the loop
+    let then = jalankan.blocks.iter().find(|block| block.name.ends_with(".then")).expect("Missing then block");
+    assert!(matches!(&then.terminator, Terminator::Branch { target } if *target == end));
+    assert!(matches!(block(&cases[1].target).terminator, Terminator::Return { value: Some(_) }));
+}
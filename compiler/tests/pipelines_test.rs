@@ -0,0 +1,73 @@
+//! Tests for `|>` pipelines and method chains over lists, and the fusion of their loops
+
+use kodeon_compiler::ir::{IRModule, Instruction, LoopStep, Stage, Type};
+use kodeon_compiler::optimizer::{OptimizationPass, PipelineFusion};
+
+mod common;
+use common::{function, generate};
+
+fn pipelines(module: &IRModule) -> Vec<(&Type, &[Stage])> {
+    function(module, "main")
+        .blocks
+        .iter()
+        .flat_map(|block| block.instructions.iter())
+        .filter_map(|instruction| match instruction {
+            Instruction::Pipeline { element_type, stages, .. } => Some((element_type, stages.as_slice())),
+            _ => None,
+        })
+        .collect()
+}
+
+const NUMBERS: &str = r#"
+buat angka = [3, 1, 4, 1, 5]
+buat faktor = 2
+buat jumlah = angka
+    |> saring(x => x > 1)
+    |> petakan(x => x * faktor)
+    |> urutkan()
+    |> ambil(2)
+    |> kurangi((s, x) => s + x, 0)
+buat teks = angka.saring(x => x < 4).petakan(x => "n{x}").lompati(1)
+buat total: int = angka |> lipat(0, (s, x) => s + x)
+"#;
+
+#[test]
+fn test_pipelines_are_lowered_to_stages() {
+    let module = generate(NUMBERS);
+    let pipelines = pipelines(&module);
+    assert_eq!(pipelines.len(), 3);
+
+    let (element_type, stages) = pipelines[0];
+    assert_eq!(element_type, &Type::Int);
+    assert_eq!(stages.len(), 5);
+    assert!(matches!(&stages[0], Stage::Loop(steps) if matches!(steps[..], [LoopStep::Filter(_)])));
+    assert!(matches!(&stages[1], Stage::Loop(steps) if matches!(steps[..], [LoopStep::Map { element_type: Type::Int, .. }])));
+    assert!(matches!(stages[2], Stage::Sort(None)));
+    assert!(matches!(stages[3], Stage::Take(_)));
+    assert!(matches!(&stages[4], Stage::Reduce { initial: Some(_), result_type: Type::Int, .. }));
+
+    // A method chain builds the same stages as a pipeline
+    let (_, stages) = pipelines[1];
+    assert!(matches!(&stages[1], Stage::Loop(steps) if matches!(steps[..], [LoopStep::Map { element_type: Type::String, .. }])));
+    assert!(matches!(stages[2], Stage::Skip(_)));
+
+    // A fold is a reduction from the value it is given
+    let (_, stages) = pipelines[2];
+    assert!(matches!(stages, [Stage::Reduce { initial: Some(_), .. }]));
+}
+
+#[test]
+fn test_adjacent_map_and_filter_stages_are_fused_into_one_loop() {
+    let mut module = generate(NUMBERS);
+    PipelineFusion.run(&mut module).expect("Fusion failed");
+    let pipelines = pipelines(&module);
+
+    let (_, stages) = pipelines[0];
+    assert_eq!(stages.len(), 4);
+    assert!(matches!(&stages[0], Stage::Loop(steps) if matches!(steps[..], [LoopStep::Filter(_), LoopStep::Map { .. }])));
+    assert!(matches!(stages[1], Stage::Sort(None)));
+
+    let (_, stages) = pipelines[1];
+    assert_eq!(stages.len(), 2);
+    assert!(matches!(stages[1], Stage::Skip(_)));
+}
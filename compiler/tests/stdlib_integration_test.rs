@@ -1,13 +1,13 @@
 //! Test for standard library integration
 
-use kodeon_compiler::lexer::Lexer;
 use kodeon_compiler::parser::Parser;
 use kodeon_compiler::ir::IRGenerator;
 use kodeon_compiler::module_resolver::ModuleResolver;
+use kodeon_compiler::semantic_analyzer::SemanticAnalyzer;
 
 #[test]
 fn test_module_resolver_stdlib() {
-    let mut resolver = ModuleResolver::new();
+    let resolver = ModuleResolver::new();
 
     // Test that core modules can be resolved
     assert!(resolver.resolve_module("core").is_ok());
@@ -35,8 +35,8 @@ fn test_import_parsing() {
         }
     "#;
 
-    let mut parser = Parser::new(source).expect("Failed to create parser");
-    let ast = parser.parse_program().expect("Failed to parse program");
+    let mut parser = Parser::new(source);
+    let ast = parser.parse_program().into_result().expect("Failed to parse program");
 
     // The parsing should succeed without errors
     assert!(matches!(ast, kodeon_compiler::parser::ASTNode::Program(_)));
@@ -53,11 +53,12 @@ fn test_import_ir_generation() {
         }
     "#;
 
-    let mut parser = Parser::new(source).expect("Failed to create parser");
-    let ast = parser.parse_program().expect("Failed to parse program");
+    let mut parser = Parser::new(source);
+    let ast = parser.parse_program().into_result().expect("Failed to parse program");
+    let program = SemanticAnalyzer::new().analyze(&ast).expect("Failed to analyze program");
 
     let mut ir_generator = IRGenerator::new();
-    let result = ir_generator.generate_ir(&ast);
+    let result = ir_generator.generate_ir(&program);
 
     // IR generation should succeed
     assert!(result.is_ok());
@@ -0,0 +1,49 @@
+//! Tests for joining strings: `+` on two strings and interpolation both call the runtime's `string_concat`
+
+use kodeon_compiler::ir::{BinaryOp, Instruction, Value};
+
+mod common;
+use common::{function, generate};
+
+const SOURCE: &str = r#"
+fungsi sapa(nama: string): string:
+    kembalikan "Halo, " + nama
+
+fungsi jumlah(a: int, b: int): int:
+    kembalikan a + b
+
+fungsi laporan(nama: string, umur: int): string:
+    kembalikan "{nama} berumur {umur}"
+"#;
+
+fn calls<'a>(instructions: &[&'a Instruction], name: &str) -> Vec<&'a [Value]> {
+    instructions.iter().filter_map(|instruction| match instruction {
+        Instruction::Call { function, arguments, .. } if function == name => Some(arguments.as_slice()),
+        _ => None,
+    }).collect()
+}
+
+#[test]
+fn test_adding_strings_calls_string_concat() {
+    let module = generate(SOURCE);
+    let sapa = function(&module, "sapa");
+    let instructions: Vec<&Instruction> = sapa.blocks.iter().flat_map(|block| &block.instructions).collect();
+    assert_eq!(calls(&instructions, "string_concat").len(), 1);
+    assert!(!instructions.iter().any(|instruction| matches!(instruction, Instruction::BinaryOp { op: BinaryOp::Add, .. })));
+
+    // Numbers are still added
+    let jumlah = function(&module, "jumlah");
+    let instructions: Vec<&Instruction> = jumlah.blocks.iter().flat_map(|block| &block.instructions).collect();
+    assert!(calls(&instructions, "string_concat").is_empty());
+    assert!(instructions.iter().any(|instruction| matches!(instruction, Instruction::BinaryOp { op: BinaryOp::Add, .. })));
+}
+
+#[test]
+fn test_interpolation_converts_and_joins_its_parts() {
+    let module = generate(SOURCE);
+    let laporan = function(&module, "laporan");
+    let instructions: Vec<&Instruction> = laporan.blocks.iter().flat_map(|block| &block.instructions).collect();
+    // Only `umur` is not a string already
+    assert_eq!(calls(&instructions, "to_string").len(), 1);
+    assert_eq!(calls(&instructions, "string_concat").len(), 2);
+}